@@ -30,6 +30,7 @@ use near_primitives::sharding::{
 use near_primitives::syncing::{
     get_num_state_parts, ReceiptProofResponse, RootProof, ShardStateSyncResponseHeader,
     ShardStateSyncResponseHeaderV1, ShardStateSyncResponseHeaderV2, StateHeaderKey, StatePartKey,
+    StatePartStatus,
 };
 use near_primitives::transaction::ExecutionOutcomeWithIdAndProof;
 use near_primitives::types::chunk_extra::ChunkExtra;
@@ -44,7 +45,11 @@ use near_primitives::views::{
     FinalExecutionOutcomeWithReceiptView, FinalExecutionStatus, LightClientBlockView,
     SignedTransactionView,
 };
-use near_store::{ColState, ColStateHeaders, ColStateParts, ShardTries, StoreUpdate};
+use near_store::flat_state::MAX_FORK_DELTAS;
+use near_store::{
+    ColFlatState, ColFlatStateDeltas, ColState, ColStateDlInfos, ColStateHeaders, ColStateParts,
+    ShardTries, StoreUpdate,
+};
 
 use near_primitives::state_record::StateRecord;
 
@@ -67,7 +72,7 @@ use actix::Message;
 #[cfg(feature = "delay_detector")]
 use delay_detector::DelayDetector;
 use near_primitives::shard_layout::{account_id_to_shard_uid, ShardLayout, ShardUId};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 /// Maximum number of orphans chain can store.
 pub const MAX_ORPHAN_SIZE: usize = 1024;
@@ -100,6 +105,9 @@ pub const NUM_EPOCHS_TO_KEEP_STORE_DATA: u64 = 5;
 /// Maximum number of height to go through at each step when cleaning forks during garbage collection.
 const GC_FORK_CLEAN_STEP: u64 = 1000;
 
+/// Number of state parts computed at once when a peer requests a part which isn't cached.
+const STATE_PARTS_PREFETCH: usize = 8;
+
 /// apply_chunks may be called in two code paths, through process_block or through catchup_blocks
 /// When it is called through process_block, it is possible that the shard state for the next epoch
 /// has not been caught up yet, thus the two modes IsCaughtUp and NotCaughtUp.
@@ -815,6 +823,57 @@ impl Chain {
         Ok(())
     }
 
+    /// Removes the flat state deltas of all blocks more than `MAX_FORK_DELTAS` heights below the
+    /// final head, which the flat state never reverts anymore. Other deltas are removed together
+    /// with the trie changes of their blocks, which archival nodes don't garbage collect.
+    pub fn clear_flat_state_deltas(
+        &mut self,
+        tries: ShardTries,
+        gc_blocks_limit: NumBlocks,
+    ) -> Result<(), Error> {
+        let final_head = self.store.final_head()?;
+        let stop_height = final_head.height.saturating_sub(MAX_FORK_DELTAS as BlockHeight);
+        // Blocks below the tail are garbage collected together with their deltas.
+        let tail = std::cmp::max(self.store.flat_state_deltas_tail()?, self.store.tail()?);
+        let mut gc_blocks_remaining = gc_blocks_limit;
+        for height in tail + 1..stop_height {
+            if gc_blocks_remaining == 0 {
+                return Ok(());
+            }
+            let blocks_current_height = match self.store.get_all_block_hashes_by_height(height) {
+                Ok(blocks_current_height) => blocks_current_height
+                    .iter()
+                    .map(|(epoch_id, block_hashes)| {
+                        (epoch_id.clone(), block_hashes.iter().cloned().collect::<Vec<_>>())
+                    })
+                    .collect::<Vec<_>>(),
+                Err(_) => vec![],
+            };
+            let mut store_update = self.store.store().store_update();
+            for (epoch_id, block_hashes) in blocks_current_height {
+                let num_shards = self.runtime_adapter.num_shards(&epoch_id)?;
+                for block_hash in block_hashes.iter() {
+                    for shard_id in 0..num_shards {
+                        let shard_uid =
+                            self.runtime_adapter.shard_id_to_uid(shard_id, &epoch_id)?;
+                        // Shards which are not tracked have no chunk extra and no flat state.
+                        let state_root = match self.store.get_chunk_extra(block_hash, &shard_uid) {
+                            Ok(chunk_extra) => *chunk_extra.state_root(),
+                            Err(_) => continue,
+                        };
+                        tries.remove_flat_state_delta(shard_uid, &state_root, &mut store_update);
+                    }
+                    gc_blocks_remaining = gc_blocks_remaining.saturating_sub(1);
+                }
+            }
+            let mut chain_store_update = self.store.store_update();
+            chain_store_update.merge(store_update);
+            chain_store_update.update_flat_state_deltas_tail(height);
+            chain_store_update.commit()?;
+        }
+        Ok(())
+    }
+
     /// Do basic validation of a block upon receiving it. Check that block is
     /// well-formed (various roots match).
     pub fn validate_block(&mut self, block: &MaybeValidated<Block>) -> Result<(), Error> {
@@ -1108,13 +1167,19 @@ impl Chain {
         chain_store_update.clear_chunk_data_and_headers(chunk_height)?;
         chain_store_update.commit()?;
 
-        // clear all trie data
+        // clear all trie data, unless state sync to `sync_hash` was interrupted and resumes now,
+        // in which case the trie holds nothing but the state parts it has already applied.
 
+        let resume_state_sync = self.store.has_state_parts_progress(&sync_hash);
         let tries = self.runtime_adapter.get_tries();
         let mut chain_store_update = self.mut_store().store_update();
-        let mut store_update = StoreUpdate::new_with_tries(tries);
-        store_update.delete_all(ColState);
-        chain_store_update.merge(store_update);
+        if !resume_state_sync {
+            let mut store_update = StoreUpdate::new_with_tries(tries);
+            store_update.delete_all(ColState);
+            store_update.delete_all(ColFlatState);
+            store_update.delete_all(ColFlatStateDeltas);
+            chain_store_update.merge(store_update);
+        }
 
         // The reason to reset tail here is not to allow Tail be greater than Head
         chain_store_update.reset_tail();
@@ -1856,20 +1921,32 @@ impl Chain {
         if part_id >= num_parts {
             return Err(ErrorKind::InvalidStateRequest("part_id out of bound".to_string()).into());
         }
-        let state_part = self
+        // Peers request parts in order, so the following parts which are not cached yet are
+        // computed along with the requested one, in parallel.
+        let mut part_ids = vec![part_id];
+        for next_part_id in (part_id + 1..num_parts).take(STATE_PARTS_PREFETCH - 1) {
+            let next_key = StatePartKey(sync_hash, shard_id, next_part_id).try_to_vec()?;
+            if !self.store.owned_store().exists(ColStateParts, &next_key)? {
+                part_ids.push(next_part_id);
+            }
+        }
+        let state_parts = self
             .runtime_adapter
-            .obtain_state_part(shard_id, &sync_prev_hash, &state_root, part_id, num_parts)
-            .log_storage_error("obtain_state_part fail")?;
+            .obtain_state_parts(shard_id, &sync_prev_hash, &state_root, &part_ids, num_parts)
+            .log_storage_error("obtain_state_parts fail")?;
 
         // Before saving State Part data, we need to make sure we can calculate and save State Header
         self.get_state_response_header(shard_id, sync_hash)?;
 
         // Saving the part data
         let mut store_update = self.store.owned_store().store_update();
-        store_update.set(ColStateParts, &key, &state_part);
+        for (part_id, state_part) in part_ids.iter().zip(&state_parts) {
+            let key = StatePartKey(sync_hash, shard_id, *part_id).try_to_vec()?;
+            store_update.set(ColStateParts, &key, state_part);
+        }
         store_update.commit()?;
 
-        Ok(state_part)
+        Ok(state_parts.into_iter().next().unwrap())
     }
 
     pub fn set_state_header(
@@ -2062,23 +2139,48 @@ impl Chain {
         num_parts: u64,
         data: &Vec<u8>,
     ) -> Result<(), Error> {
-        let shard_state_header = self.get_state_header(shard_id, sync_hash)?;
-        let chunk = shard_state_header.take_chunk();
-        let state_root = *chunk.take_header().take_inner().prev_state_root();
-        if !self.runtime_adapter.validate_state_part(&state_root, part_id, num_parts, data) {
+        let valid =
+            self.set_state_parts(shard_id, sync_hash, num_parts, &[(part_id, data.clone())])?;
+        if !valid[0] {
             byzantine_assert!(false);
             return Err(ErrorKind::Other(
                 "set_state_part failed: validate_state_part failed".into(),
             )
             .into());
         }
+        Ok(())
+    }
 
-        // Saving the part data.
+    /// Validates the given parts in parallel and saves the valid ones.
+    /// Returns whether each part is valid, in the order of `parts`.
+    pub fn set_state_parts(
+        &mut self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        num_parts: u64,
+        parts: &[(u64, Vec<u8>)],
+    ) -> Result<Vec<bool>, Error> {
+        let shard_state_header = self.get_state_header(shard_id, sync_hash)?;
+        let chunk = shard_state_header.take_chunk();
+        let state_root = *chunk.take_header().take_inner().prev_state_root();
+        let runtime_adapter = &self.runtime_adapter;
+        let valid: Vec<bool> = parts
+            .par_iter()
+            .map(|(part_id, data)| {
+                runtime_adapter.validate_state_part(&state_root, *part_id, num_parts, data)
+            })
+            .collect();
+
+        // Saving the part data along with the progress, so that the part isn't requested again
+        // if state sync gets interrupted.
         let mut store_update = self.store.owned_store().store_update();
-        let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec()?;
-        store_update.set(ColStateParts, &key, data);
+        for ((part_id, data), _) in parts.iter().zip(&valid).filter(|(_, valid)| **valid) {
+            let key = StatePartKey(sync_hash, shard_id, *part_id).try_to_vec()?;
+            store_update.set(ColStateParts, &key, data);
+            store_update.set_ser(ColStateDlInfos, &key, &StatePartStatus::Downloaded)?;
+        }
         store_update.commit()?;
-        Ok(())
+        Ok(valid)
     }
 
     pub fn schedule_apply_state_parts(
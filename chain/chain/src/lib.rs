@@ -13,6 +13,7 @@ mod lightclient;
 mod metrics;
 pub mod migrations;
 pub mod missing_chunks;
+pub mod state_sync_dump;
 mod store;
 pub mod store_validator;
 pub mod test_utils;
@@ -0,0 +1,122 @@
+//! Dumps of state sync data in a directory, which nodes can sync state from instead of requesting
+//! state parts from peers.
+//!
+//! A dump has the following layout:
+//! * `<sync_hash>/shard_<shard_id>/header` — borsh-serialized [`ShardStateSyncResponseHeader`];
+//! * `<sync_hash>/shard_<shard_id>/part_<part_id>` — state part as it is sent over the network.
+//!
+//! `sync_hash` is the hash of the first block of the epoch the state is synced to, so a dump holds
+//! a directory per epoch. The files are written once and never modified, so the dump can be
+//! copied to and from an object store as is.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::info;
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::syncing::{get_num_state_parts, ShardStateSyncResponseHeader, StatePartKey};
+use near_primitives::types::ShardId;
+use near_store::ColStateParts;
+
+use crate::{Chain, ChainStoreAccess, Error};
+
+/// Name of the file which holds the state header of a shard.
+pub const STATE_HEADER_FILE: &str = "header";
+
+/// Number of state parts which are computed at once while dumping the state of a shard.
+const DUMP_PARTS_BATCH_SIZE: usize = 64;
+
+/// Returns the directory of the dump in `root` which holds the state of the shard.
+pub fn shard_dump_dir(root: &Path, sync_hash: &CryptoHash, shard_id: ShardId) -> PathBuf {
+    root.join(sync_hash.to_string()).join(format!("shard_{}", shard_id))
+}
+
+fn part_file_name(part_id: u64) -> String {
+    format!("part_{}", part_id)
+}
+
+/// Writes `data` to `path` so that readers never see a partially written file.
+fn write_file(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, data)?;
+    fs::rename(&tmp_path, path)
+}
+
+fn read_file(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Reads the state header of the shard from the dump in `root`, `None` if the dump doesn't have it.
+/// The header is not validated.
+pub fn read_state_header(
+    root: &Path,
+    sync_hash: &CryptoHash,
+    shard_id: ShardId,
+) -> io::Result<Option<ShardStateSyncResponseHeader>> {
+    read_file(&shard_dump_dir(root, sync_hash, shard_id).join(STATE_HEADER_FILE))?
+        .map(|data| ShardStateSyncResponseHeader::try_from_slice(&data))
+        .transpose()
+}
+
+/// Reads a state part of the shard from the dump in `root`, `None` if the dump doesn't have it.
+/// The part is not validated.
+pub fn read_state_part(
+    root: &Path,
+    sync_hash: &CryptoHash,
+    shard_id: ShardId,
+    part_id: u64,
+) -> io::Result<Option<Vec<u8>>> {
+    read_file(&shard_dump_dir(root, sync_hash, shard_id).join(part_file_name(part_id)))
+}
+
+impl Chain {
+    /// Writes the state header and all state parts of the shard for state sync to `sync_hash`
+    /// into the dump in `root` and returns the number of parts.
+    ///
+    /// Parts which are in `ColStateParts` already are copied from there, the rest are computed
+    /// in parallel. The header is written last, so a dump which has it is complete.
+    pub fn dump_state_sync_data(
+        &mut self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        root: &Path,
+    ) -> Result<u64, Error> {
+        let header = self.get_state_response_header(shard_id, sync_hash)?;
+        let state_root = header.chunk_prev_state_root();
+        let num_parts = get_num_state_parts(header.state_root_node().memory_usage);
+        let sync_prev_hash = *self.get_block_header(&sync_hash)?.prev_hash();
+
+        let dir = shard_dump_dir(root, &sync_hash, shard_id);
+        fs::create_dir_all(&dir)?;
+        let store = self.store().owned_store();
+        let mut missing_part_ids = vec![];
+        for part_id in 0..num_parts {
+            let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec()?;
+            match store.get(ColStateParts, &key)? {
+                Some(part) => write_file(&dir.join(part_file_name(part_id)), &part)?,
+                None => missing_part_ids.push(part_id),
+            }
+        }
+        for part_ids in missing_part_ids.chunks(DUMP_PARTS_BATCH_SIZE) {
+            let parts = self.runtime_adapter.obtain_state_parts(
+                shard_id,
+                &sync_prev_hash,
+                &state_root,
+                part_ids,
+                num_parts,
+            )?;
+            for (part_id, part) in part_ids.iter().zip(parts) {
+                write_file(&dir.join(part_file_name(*part_id)), &part)?;
+            }
+            info!(target: "chain", "Dumped state parts up to {} of {} for shard {}", part_ids.last().unwrap(), num_parts, shard_id);
+        }
+        write_file(&dir.join(STATE_HEADER_FILE), &header.try_to_vec()?)?;
+        Ok(num_parts)
+    }
+}
@@ -20,7 +20,7 @@ use near_primitives::sharding::{
 };
 use near_primitives::syncing::{
     get_num_state_parts, ReceiptProofResponse, ShardStateSyncResponseHeader, StateHeaderKey,
-    StatePartKey,
+    StatePartKey, StatePartStatus,
 };
 use near_primitives::transaction::{
     ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, SignedTransaction,
@@ -28,9 +28,9 @@ use near_primitives::transaction::{
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{
-    AccountId, BlockExtra, BlockHeight, EpochId, GCCount, NumBlocks, ShardId, StateChanges,
-    StateChangesExt, StateChangesForSplitStates, StateChangesKinds, StateChangesKindsExt,
-    StateChangesRequest,
+    AccountId, BlockExtra, BlockHeight, EpochId, GCCount, NumBlocks, ShardId, StateChangeInBlock,
+    StateChanges, StateChangesExt, StateChangesForSplitStates, StateChangesKinds,
+    StateChangesKindsExt, StateChangesRequest,
 };
 use near_primitives::utils::{get_block_shard_id, index_to_bytes, to_timestamp};
 use near_primitives::views::LightClientBlockView;
@@ -41,10 +41,11 @@ use near_store::{
     ColChunkPerHeightShard, ColChunks, ColEpochLightClientBlocks, ColGCCount,
     ColHeaderHashesByHeight, ColIncomingReceipts, ColInvalidChunks, ColNextBlockHashes,
     ColOutcomeIds, ColOutgoingReceipts, ColPartialChunks, ColProcessedBlockHeights,
-    ColReceiptIdToShardId, ColReceipts, ColState, ColStateChanges, ColStateDlInfos,
-    ColStateHeaders, ColStateParts, ColTransactionResult, ColTransactions, ColTrieChanges, DBCol,
-    KeyForStateChanges, ShardTries, Store, StoreUpdate, TrieChanges, WrappedTrieChanges,
-    CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY,
+    ColReceiptIdToShardId, ColReceipts, ColState, ColStateChanges, ColStateChangesHistory,
+    ColStateDlInfos, ColStateHeaders, ColStateParts, ColTransactionResult, ColTransactions,
+    ColTrieChanges, DBCol, KeyForStateChanges, KeyForStateChangesHistory, ShardTries, Store,
+    StoreUpdate, TrieChanges, WrappedTrieChanges, CHUNK_TAIL_KEY, FINAL_HEAD_KEY,
+    FLAT_STATE_DELTAS_TAIL_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY,
     LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, SHOULD_COL_GC, TAIL_KEY,
 };
 
@@ -89,6 +90,8 @@ pub trait ChainStoreAccess {
     fn chunk_tail(&self) -> Result<BlockHeight, Error>;
     /// Tail height of the fork cleaning process.
     fn fork_tail(&self) -> Result<BlockHeight, Error>;
+    /// Height up to which the flat state deltas of all blocks are removed.
+    fn flat_state_deltas_tail(&self) -> Result<BlockHeight, Error>;
     /// Head of the header chain (not the same thing as head_header).
     fn header_head(&self) -> Result<Tip, Error>;
     /// Header of the block at the head of the block chain (not the same thing as header_head).
@@ -346,6 +349,8 @@ pub struct ChainStore {
     block_ordinal_to_hash: SizedCache<Vec<u8>, CryptoHash>,
     /// Processed block heights.
     processed_block_heights: SizedCache<Vec<u8>, ()>,
+    /// The underlying store while reads go to a snapshot, see `pin_snapshot`.
+    unpinned_store: Option<Arc<Store>>,
 }
 
 pub fn option_to_not_found<T>(res: io::Result<Option<T>>, field_name: &str) -> Result<T, Error> {
@@ -387,6 +392,7 @@ impl ChainStore {
             block_merkle_tree: SizedCache::with_size(CACHE_SIZE),
             block_ordinal_to_hash: SizedCache::with_size(CACHE_SIZE),
             processed_block_heights: SizedCache::with_size(CACHE_SIZE),
+            unpinned_store: None,
         }
     }
 
@@ -394,6 +400,26 @@ impl ChainStore {
         self.store.clone()
     }
 
+    /// Makes the following reads go to a snapshot of the current state of the database until
+    /// `unpin_snapshot` is called, so that reads spanning several columns are consistent with
+    /// each other. The caches are kept, they are only filled with data read from the snapshot.
+    /// Returns false if a snapshot is already pinned.
+    pub fn pin_snapshot(&mut self) -> bool {
+        if self.unpinned_store.is_some() {
+            return false;
+        }
+        let snapshot = Arc::new(self.store.snapshot());
+        self.unpinned_store = Some(std::mem::replace(&mut self.store, snapshot));
+        true
+    }
+
+    /// Releases the snapshot pinned by `pin_snapshot`.
+    pub fn unpin_snapshot(&mut self) {
+        if let Some(store) = self.unpinned_store.take() {
+            self.store = store;
+        }
+    }
+
     pub fn store_update(&mut self) -> ChainStoreUpdate<'_> {
         ChainStoreUpdate::new(self)
     }
@@ -401,6 +427,8 @@ impl ChainStore {
     pub fn iterate_state_sync_infos(&self) -> Vec<(CryptoHash, StateSyncInfo)> {
         self.store
             .iter(ColStateDlInfos)
+            // Skip `StatePartStatus`es, which are keyed by `StatePartKey`.
+            .filter(|(k, _)| k.len() == std::mem::size_of::<CryptoHash>())
             .map(|(k, v)| {
                 (
                     CryptoHash::try_from(k.as_ref()).unwrap(),
@@ -611,6 +639,24 @@ impl ChainStore {
         }
     }
 
+    /// Returns how far state sync got with the part, `None` if it wasn't downloaded yet.
+    pub fn get_state_part_status(
+        &self,
+        sync_hash: CryptoHash,
+        shard_id: ShardId,
+        part_id: u64,
+    ) -> Result<Option<StatePartStatus>, Error> {
+        let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec()?;
+        Ok(self.store.get_ser(ColStateDlInfos, &key)?)
+    }
+
+    /// Returns true if state sync to `sync_hash` has already downloaded some state parts.
+    pub fn has_state_parts_progress(&self, sync_hash: &CryptoHash) -> bool {
+        self.store
+            .iter_prefix(ColStateDlInfos, sync_hash.as_ref())
+            .any(|(key, _)| key.len() > std::mem::size_of::<CryptoHash>())
+    }
+
     /// Returns latest known height and time it was seen.
     pub fn get_latest_known(&mut self) -> Result<LatestKnown, Error> {
         if self.latest_known.is_none() {
@@ -669,6 +715,46 @@ impl ChainStore {
         Ok(StateChanges::from_changes(&mut block_changes)?)
     }
 
+    /// Returns up to `limit` changes of `trie_key` made by the blocks of the canonical chain with
+    /// heights in `from_height..=to_height`, in the order of heights, without replaying the
+    /// blocks. For every block only the last change is returned, which holds the value after the
+    /// block. If there are more changes in the range, the height of the next one is returned as
+    /// well, to be passed as `from_height` of the next call.
+    ///
+    /// The blocks are found in `ColStateChangesHistory`, so only changes which are still in
+    /// `ColStateChanges` are returned. Archival nodes keep all of them.
+    pub fn get_state_changes_history(
+        &self,
+        trie_key: &TrieKey,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+        limit: usize,
+    ) -> Result<(Vec<StateChangeInBlock>, Option<BlockHeight>), Error> {
+        let raw_key = trie_key.to_vec();
+        let start = KeyForStateChangesHistory::first_from_height(&raw_key, from_height);
+        let mut history = vec![];
+        for (block_height, block_hash) in start.find_iter(&self.store) {
+            if block_height > to_height {
+                break;
+            }
+            // Skip the blocks of forks.
+            let canonical_hash: Option<CryptoHash> =
+                self.store.get_ser(ColBlockHeight, &index_to_bytes(block_height))?;
+            if canonical_hash != Some(block_hash) {
+                continue;
+            }
+            let storage_key = KeyForStateChanges::new(&block_hash, &raw_key);
+            let mut changes = StateChanges::from_changes(storage_key.find_exact_iter(&self.store))?;
+            if let Some(change) = changes.pop() {
+                if history.len() == limit {
+                    return Ok((history, Some(block_height)));
+                }
+                history.push(StateChangeInBlock { block_height, block_hash, change });
+            }
+        }
+        Ok((history, None))
+    }
+
     /// Retrieve the key-value changes from the store and decode them appropriately.
     ///
     /// We store different types of data, so we need to take care of all the types. That is, the
@@ -804,6 +890,13 @@ impl ChainStoreAccess for ChainStore {
             .map_err(|e| e.into())
     }
 
+    fn flat_state_deltas_tail(&self) -> Result<BlockHeight, Error> {
+        self.store
+            .get_ser(ColBlockMisc, FLAT_STATE_DELTAS_TAIL_KEY)
+            .map(|option| option.unwrap_or_else(|| self.genesis_height))
+            .map_err(|e| e.into())
+    }
+
     /// Header of the block at the head of the block chain (not the same thing as header_head).
     fn head_header(&mut self) -> Result<&BlockHeader, Error> {
         self.get_block_header(&self.head()?.last_block_hash)
@@ -1141,12 +1234,16 @@ struct ChainStoreCacheUpdate {
 pub struct ChainStoreUpdateImpl<T> {
     chain_store: T,
     store_updates: Vec<StoreUpdate>,
+    /// Data which GC moves to the cold database of a split store. Committed before
+    /// `store_updates`, so that a crash in between leaves the data in both databases.
+    cold_store_update: Option<StoreUpdate>,
     /// Blocks added during this update. Takes ownership (unclear how to not do it because of failure exists).
     chain_store_cache_update: ChainStoreCacheUpdate,
     head: Option<Tip>,
     tail: Option<BlockHeight>,
     chunk_tail: Option<BlockHeight>,
     fork_tail: Option<BlockHeight>,
+    flat_state_deltas_tail: Option<BlockHeight>,
     header_head: Option<Tip>,
     final_head: Option<Tip>,
     largest_target_height: Option<BlockHeight>,
@@ -1173,11 +1270,13 @@ impl<'a> ChainStoreUpdate<'a> {
         ChainStoreUpdate {
             chain_store,
             store_updates: vec![],
+            cold_store_update: None,
             chain_store_cache_update: ChainStoreCacheUpdate::default(),
             head: None,
             tail: None,
             chunk_tail: None,
             fork_tail: None,
+            flat_state_deltas_tail: None,
             header_head: None,
             final_head: None,
             largest_target_height: None,
@@ -1277,6 +1376,14 @@ impl<'a> ChainStoreAccess for ChainStoreUpdate<'a> {
         }
     }
 
+    fn flat_state_deltas_tail(&self) -> Result<BlockHeight, Error> {
+        if let Some(flat_state_deltas_tail) = &self.flat_state_deltas_tail {
+            Ok(flat_state_deltas_tail.clone())
+        } else {
+            self.chain_store.flat_state_deltas_tail()
+        }
+    }
+
     /// Head of the header chain (not the same thing as head_header).
     fn header_head(&self) -> Result<Tip, Error> {
         if let Some(header_head) = &self.header_head {
@@ -1975,6 +2082,10 @@ impl<'a> ChainStoreUpdate<'a> {
         self.fork_tail = Some(height);
     }
 
+    pub fn update_flat_state_deltas_tail(&mut self, height: BlockHeight) {
+        self.flat_state_deltas_tail = Some(height);
+    }
+
     pub fn update_chunk_tail(&mut self, height: BlockHeight) {
         self.chunk_tail = Some(height);
     }
@@ -1991,17 +2102,17 @@ impl<'a> ChainStoreUpdate<'a> {
                 let chunk = self.get_chunk(&chunk_hash)?.clone();
                 debug_assert_eq!(chunk.cloned_header().height_created(), height);
                 for transaction in chunk.transactions() {
-                    self.gc_col(ColTransactions, &transaction.get_hash().into());
+                    self.gc_col(ColTransactions, &transaction.get_hash().into())?;
                 }
                 for receipt in chunk.receipts() {
-                    self.gc_col(ColReceipts, &receipt.get_hash().into());
+                    self.gc_col(ColReceipts, &receipt.get_hash().into())?;
                 }
 
                 // 2. Delete chunk_hash-indexed data
                 let chunk_header_hash = chunk_hash.clone().into();
-                self.gc_col(ColChunks, &chunk_header_hash);
-                self.gc_col(ColPartialChunks, &chunk_header_hash);
-                self.gc_col(ColInvalidChunks, &chunk_header_hash);
+                self.gc_col(ColChunks, &chunk_header_hash)?;
+                self.gc_col(ColPartialChunks, &chunk_header_hash)?;
+                self.gc_col(ColInvalidChunks, &chunk_header_hash)?;
             }
 
             let header_hashes = self.chain_store.get_all_header_hashes_by_height(height)?;
@@ -2012,8 +2123,8 @@ impl<'a> ChainStoreUpdate<'a> {
             }
 
             // 4. Delete chunks_tail-related data
-            self.gc_col(ColChunkHashesByHeight, &index_to_bytes(height));
-            self.gc_col(ColHeaderHashesByHeight, &index_to_bytes(height));
+            self.gc_col(ColChunkHashesByHeight, &index_to_bytes(height))?;
+            self.gc_col(ColHeaderHashesByHeight, &index_to_bytes(height))?;
         }
         self.update_chunk_tail(min_chunk_height);
         Ok(())
@@ -2062,41 +2173,40 @@ impl<'a> ChainStoreUpdate<'a> {
                 GCMode::Fork(tries) => {
                     // If the block is on a fork, we delete the state that's the result of applying this block
                     for shard_uid in shard_uids_to_gc {
-                        self.store()
-                            .get_ser(ColTrieChanges, &get_block_shard_uid(&block_hash, &shard_uid))?
-                            .map(|trie_changes: TrieChanges| {
-                                tries
-                                    .revert_insertions(&trie_changes, shard_uid, &mut store_update)
-                                    .map(|_| {
-                                        self.gc_col(
-                                            ColTrieChanges,
-                                            &get_block_shard_uid(&block_hash, &shard_uid),
-                                        );
-                                        self.inc_gc_col_state();
-                                    })
-                                    .map_err(|err| ErrorKind::Other(err.to_string()))
-                            })
-                            .unwrap_or(Ok(()))?;
+                        let key = get_block_shard_uid(&block_hash, &shard_uid);
+                        if let Some(trie_changes) =
+                            self.store().get_ser::<TrieChanges>(ColTrieChanges, &key)?
+                        {
+                            tries
+                                .revert_insertions(&trie_changes, shard_uid, &mut store_update)
+                                .map_err(|err| ErrorKind::Other(err.to_string()))?;
+                            self.gc_col(ColTrieChanges, &key)?;
+                            self.inc_gc_col_state();
+                        }
                     }
                 }
                 GCMode::Canonical(tries) => {
                     // If the block is on canonical chain, we delete the state that's before applying this block
                     for shard_uid in shard_uids_to_gc {
-                        self.store()
-                            .get_ser(ColTrieChanges, &get_block_shard_uid(&block_hash, &shard_uid))?
-                            .map(|trie_changes: TrieChanges| {
-                                tries
-                                    .apply_deletions(&trie_changes, shard_uid, &mut store_update)
-                                    .map(|_| {
-                                        self.gc_col(
-                                            ColTrieChanges,
-                                            &get_block_shard_uid(&block_hash, &shard_uid),
-                                        );
-                                        self.inc_gc_col_state();
-                                    })
-                                    .map_err(|err| ErrorKind::Other(err.to_string()))
-                            })
-                            .unwrap_or(Ok(()))?;
+                        let key = get_block_shard_uid(&block_hash, &shard_uid);
+                        if let Some(trie_changes) =
+                            self.store().get_ser::<TrieChanges>(ColTrieChanges, &key)?
+                        {
+                            tries
+                                .apply_deletions(&trie_changes, shard_uid, &mut store_update)
+                                .map_err(|err| ErrorKind::Other(err.to_string()))?;
+                            // Archival nodes with split storage keep the state before this block
+                            // in the cold database.
+                            if let Some(cold_store_update) = self.cold_store_update() {
+                                ShardTries::copy_deletions_to_cold(
+                                    &trie_changes,
+                                    shard_uid,
+                                    cold_store_update,
+                                );
+                            }
+                            self.gc_col(ColTrieChanges, &key)?;
+                            self.inc_gc_col_state();
+                        }
                     }
                     // Set `block_hash` on previous one
                     block_hash = *self.get_block_header(&block_hash)?.prev_hash();
@@ -2104,7 +2214,7 @@ impl<'a> ChainStoreUpdate<'a> {
                 GCMode::StateSync { .. } => {
                     // Not apply the data from ColTrieChanges
                     for shard_uid in shard_uids_to_gc {
-                        self.gc_col(ColTrieChanges, &get_block_shard_uid(&block_hash, &shard_uid));
+                        self.gc_col(ColTrieChanges, &get_block_shard_uid(&block_hash, &shard_uid))?;
                     }
                 }
             }
@@ -2119,9 +2229,9 @@ impl<'a> ChainStoreUpdate<'a> {
         // 2. Delete shard_id-indexed data (Receipts, State Headers and Parts, etc.)
         for shard_id in 0..block.header().chunk_mask().len() as ShardId {
             let block_shard_id = get_block_shard_id(&block_hash, shard_id);
-            self.gc_outgoing_receipts(&block_hash, shard_id);
-            self.gc_col(ColIncomingReceipts, &block_shard_id);
-            self.gc_col(ColChunkPerHeightShard, &block_shard_id);
+            self.gc_outgoing_receipts(&block_hash, shard_id)?;
+            self.gc_col(ColIncomingReceipts, &block_shard_id)?;
+            self.gc_col(ColChunkPerHeightShard, &block_shard_id)?;
 
             // For incoming State Parts it's done in chain.clear_downloaded_parts()
             // The following code is mostly for outgoing State Parts.
@@ -2134,22 +2244,22 @@ impl<'a> ChainStoreUpdate<'a> {
                     get_num_state_parts(shard_state_header.state_root_node().memory_usage);
                 self.gc_col_state_parts(block_hash, shard_id, state_num_parts)?;
                 let key = StateHeaderKey(shard_id, block_hash).try_to_vec()?;
-                self.gc_col(ColStateHeaders, &key);
+                self.gc_col(ColStateHeaders, &key)?;
             }
         }
         // gc ColChunkExtra based on shard_uid since it's indexed by shard_uid in the storage
         for shard_uid in self.get_shard_uids_to_gc(runtime_adapter, &block_hash) {
             let block_shard_uid = get_block_shard_uid(&block_hash, &shard_uid);
-            self.gc_col(ColChunkExtra, &block_shard_uid);
+            self.gc_col(ColChunkExtra, &block_shard_uid)?;
         }
 
         // 3. Delete block_hash-indexed data
         let block_hash_vec: Vec<u8> = block_hash.as_ref().into();
-        self.gc_col(ColBlock, &block_hash_vec);
-        self.gc_col(ColBlockExtra, &block_hash_vec);
-        self.gc_col(ColNextBlockHashes, &block_hash_vec);
-        self.gc_col(ColChallengedBlocks, &block_hash_vec);
-        self.gc_col(ColBlocksToCatchup, &block_hash_vec);
+        self.gc_col(ColBlock, &block_hash_vec)?;
+        self.gc_col(ColBlockExtra, &block_hash_vec)?;
+        self.gc_col(ColNextBlockHashes, &block_hash_vec)?;
+        self.gc_col(ColChallengedBlocks, &block_hash_vec)?;
+        self.gc_col(ColBlocksToCatchup, &block_hash_vec)?;
         let storage_key = KeyForStateChanges::get_prefix(&block_hash);
         let stored_state_changes: Vec<Vec<u8>> = self
             .chain_store
@@ -2158,15 +2268,18 @@ impl<'a> ChainStoreUpdate<'a> {
             .map(|key| key.0.into())
             .collect();
         for key in stored_state_changes {
-            self.gc_col(ColStateChanges, &key);
+            let raw_key = &key[std::mem::size_of::<CryptoHash>()..];
+            let history_key = KeyForStateChangesHistory::new(raw_key, height, &block_hash);
+            self.gc_col(ColStateChangesHistory, &history_key.into())?;
+            self.gc_col(ColStateChanges, &key)?;
         }
-        self.gc_col(ColBlockRefCount, &block_hash_vec);
+        self.gc_col(ColBlockRefCount, &block_hash_vec)?;
         self.gc_outcomes(&block)?;
         match gc_mode {
             GCMode::StateSync { clear_block_info: false } => {}
-            _ => self.gc_col(ColBlockInfo, &block_hash_vec),
+            _ => self.gc_col(ColBlockInfo, &block_hash_vec)?,
         }
-        self.gc_col(ColStateDlInfos, &block_hash_vec);
+        self.gc_col(ColStateDlInfos, &block_hash_vec)?;
 
         // 4. Update or delete block_hash_per_height
         self.gc_col_block_per_height(&block_hash, height, &block.header().epoch_id())?;
@@ -2211,8 +2324,8 @@ impl<'a> ChainStoreUpdate<'a> {
         epoch_id: &EpochId,
     ) -> Result<(), Error> {
         let mut store_update = self.store().store_update();
-        let epoch_to_hashes_ref = self.chain_store.get_all_block_hashes_by_height(height)?;
-        let mut epoch_to_hashes = epoch_to_hashes_ref.clone();
+        let all_epoch_to_hashes = self.chain_store.get_all_block_hashes_by_height(height)?.clone();
+        let mut epoch_to_hashes = all_epoch_to_hashes.clone();
         let hashes =
             epoch_to_hashes.get_mut(epoch_id).ok_or("current epoch id should exist".to_string())?;
         hashes.remove(&block_hash);
@@ -2220,6 +2333,20 @@ impl<'a> ChainStoreUpdate<'a> {
             epoch_to_hashes.remove(epoch_id);
         }
         let key = index_to_bytes(height);
+        if let Some(cold_store) = self.store().cold_store() {
+            // Blocks of the same height may be GCed in different batches, so the cold copy keeps
+            // the union of everything seen at this height.
+            let mut cold_epoch_to_hashes: HashMap<EpochId, HashSet<CryptoHash>> =
+                cold_store.get_ser(ColBlockPerHeight, &key)?.unwrap_or_default();
+            for (epoch_id, hashes) in all_epoch_to_hashes.into_iter() {
+                cold_epoch_to_hashes.entry(epoch_id).or_default().extend(hashes);
+            }
+            self.cold_store_update().unwrap().set_ser(
+                ColBlockPerHeight,
+                &key,
+                &cold_epoch_to_hashes,
+            )?;
+        }
         if epoch_to_hashes.is_empty() {
             store_update.delete(ColBlockPerHeight, &key);
             self.chain_store.block_hash_per_height.cache_remove(&key);
@@ -2231,7 +2358,7 @@ impl<'a> ChainStoreUpdate<'a> {
         }
         self.inc_gc(ColBlockPerHeight);
         if self.is_height_processed(height)? {
-            self.gc_col(ColProcessedBlockHeights, &key);
+            self.gc_col(ColProcessedBlockHeights, &key)?;
         }
         self.merge(store_update);
         Ok(())
@@ -2245,12 +2372,17 @@ impl<'a> ChainStoreUpdate<'a> {
     ) -> Result<(), Error> {
         for part_id in 0..num_parts {
             let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec()?;
-            self.gc_col(ColStateParts, &key);
+            self.gc_col(ColStateParts, &key)?;
+            self.gc_col(ColStateDlInfos, &key)?;
         }
         Ok(())
     }
 
-    pub fn gc_outgoing_receipts(&mut self, block_hash: &CryptoHash, shard_id: ShardId) {
+    pub fn gc_outgoing_receipts(
+        &mut self,
+        block_hash: &CryptoHash,
+        shard_id: ShardId,
+    ) -> Result<(), Error> {
         let mut store_update = self.store().store_update();
         match self.get_outgoing_receipts(block_hash, shard_id).map(|receipts| {
             receipts.iter().map(|receipt| receipt.receipt_id.clone()).collect::<Vec<_>>()
@@ -2258,6 +2390,7 @@ impl<'a> ChainStoreUpdate<'a> {
             Ok(receipt_ids) => {
                 for receipt_id in receipt_ids {
                     let key: Vec<u8> = receipt_id.into();
+                    self.copy_to_cold(ColReceiptIdToShardId, &key)?;
                     store_update.update_refcount(ColReceiptIdToShardId, &key, &[], -1);
                     self.chain_store.receipt_id_to_shard_id.cache_remove(&key);
                     self.inc_gc(ColReceiptIdToShardId);
@@ -2277,10 +2410,12 @@ impl<'a> ChainStoreUpdate<'a> {
         }
 
         let key = get_block_shard_id(block_hash, shard_id);
+        self.copy_to_cold(ColOutgoingReceipts, &key)?;
         store_update.delete(ColOutgoingReceipts, &key);
         self.chain_store.outgoing_receipts.cache_remove(&key);
         self.inc_gc(ColOutgoingReceipts);
         self.merge(store_update);
+        Ok(())
     }
 
     pub fn gc_outcomes(&mut self, block: &Block) -> Result<(), Error> {
@@ -2293,26 +2428,78 @@ impl<'a> ChainStoreUpdate<'a> {
             let outcome_ids =
                 self.chain_store.get_outcomes_by_block_hash_and_shard_id(block_hash, shard_id)?;
             for outcome_id in outcome_ids {
-                let mut outcomes_with_id = self.chain_store.get_outcomes_by_id(&outcome_id)?;
-                outcomes_with_id.retain(|outcome| &outcome.block_hash != block_hash);
-                if outcomes_with_id.is_empty() {
-                    self.gc_col(ColTransactionResult, &outcome_id.as_ref().into());
-                } else {
-                    store_update.set_ser(
-                        ColTransactionResult,
-                        outcome_id.as_ref(),
-                        &outcomes_with_id,
-                    )?;
-                }
+                self.gc_outcome(&outcome_id, block_hash, &mut store_update)?;
             }
-            self.gc_col(ColOutcomeIds, &get_block_shard_id(block_hash, shard_id));
+            self.gc_col(ColOutcomeIds, &get_block_shard_id(block_hash, shard_id))?;
         }
         self.merge(store_update);
         Ok(())
     }
 
-    fn gc_col(&mut self, col: DBCol, key: &Vec<u8>) {
+    /// Removes the outcome of the block from the outcomes with the id.
+    fn gc_outcome(
+        &mut self,
+        outcome_id: &CryptoHash,
+        block_hash: &CryptoHash,
+        store_update: &mut StoreUpdate,
+    ) -> Result<(), Error> {
+        let all_outcomes_with_id = self.chain_store.get_outcomes_by_id(outcome_id)?;
+        if let Some(cold_store) = self.store().cold_store() {
+            // Outcomes with the same id in other blocks may be GCed in other batches, so the cold
+            // copy keeps all of them.
+            let mut cold_outcomes_with_id: Vec<ExecutionOutcomeWithIdAndProof> =
+                cold_store.get_ser(ColTransactionResult, outcome_id.as_ref())?.unwrap_or_default();
+            for outcome in all_outcomes_with_id.iter() {
+                if !cold_outcomes_with_id.contains(outcome) {
+                    cold_outcomes_with_id.push(outcome.clone());
+                }
+            }
+            self.cold_store_update().unwrap().set_ser(
+                ColTransactionResult,
+                outcome_id.as_ref(),
+                &cold_outcomes_with_id,
+            )?;
+        }
+        let mut outcomes_with_id = all_outcomes_with_id;
+        outcomes_with_id.retain(|outcome| &outcome.block_hash != block_hash);
+        if outcomes_with_id.is_empty() {
+            store_update.delete(ColTransactionResult, outcome_id.as_ref());
+            self.inc_gc(ColTransactionResult);
+        } else {
+            store_update.set_ser(ColTransactionResult, outcome_id.as_ref(), &outcomes_with_id)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the update of the cold database if the store is split into hot and cold
+    /// databases, see `near_store::SplitDB`.
+    fn cold_store_update(&mut self) -> Option<&mut StoreUpdate> {
+        if self.cold_store_update.is_none() {
+            self.cold_store_update = self.store().cold_store().map(|store| store.store_update());
+        }
+        self.cold_store_update.as_mut()
+    }
+
+    /// Copies the value of `key` to the cold database before GC deletes or modifies it, if the
+    /// store is split and the column is cold.
+    fn copy_to_cold(&mut self, col: DBCol, key: &[u8]) -> Result<(), Error> {
+        if !col.is_cold() || self.cold_store_update().is_none() {
+            return Ok(());
+        }
+        if let Some(value) = self.store().get(col, key)? {
+            let cold_store_update = self.cold_store_update.as_mut().unwrap();
+            if col.is_rc() {
+                cold_store_update.update_refcount(col, key, &value, 1);
+            } else {
+                cold_store_update.set(col, key, &value);
+            }
+        }
+        Ok(())
+    }
+
+    fn gc_col(&mut self, col: DBCol, key: &Vec<u8>) -> Result<(), Error> {
         assert!(SHOULD_COL_GC[col as usize]);
+        self.copy_to_cold(col, key)?;
         let mut store_update = self.store().store_update();
         match col {
             DBCol::ColOutgoingReceipts => {
@@ -2356,6 +2543,9 @@ impl<'a> ChainStoreUpdate<'a> {
             DBCol::ColStateChanges => {
                 store_update.delete(col, key);
             }
+            DBCol::ColStateChangesHistory => {
+                store_update.delete(col, key);
+            }
             DBCol::ColBlockRefCount => {
                 store_update.delete(col, key);
                 self.chain_store.block_refcounts.cache_remove(key);
@@ -2440,12 +2630,15 @@ impl<'a> ChainStoreUpdate<'a> {
             | DBCol::_ColLastBlockWithNewChunk
             | DBCol::_ColTransactionRefCount
             | DBCol::ColStateChangesForSplitStates
-            | DBCol::ColCachedContractCode => {
+            | DBCol::ColCachedContractCode
+            | DBCol::ColFlatState
+            | DBCol::ColFlatStateDeltas => {
                 unreachable!();
             }
         }
         self.inc_gc(col);
         self.merge(store_update);
+        Ok(())
     }
 
     /// Merge another StoreUpdate into this one
@@ -2470,6 +2663,11 @@ impl<'a> ChainStoreUpdate<'a> {
         Self::write_col_misc(&mut store_update, TAIL_KEY, &mut self.tail)?;
         Self::write_col_misc(&mut store_update, CHUNK_TAIL_KEY, &mut self.chunk_tail)?;
         Self::write_col_misc(&mut store_update, FORK_TAIL_KEY, &mut self.fork_tail)?;
+        Self::write_col_misc(
+            &mut store_update,
+            FLAT_STATE_DELTAS_TAIL_KEY,
+            &mut self.flat_state_deltas_tail,
+        )?;
         Self::write_col_misc(&mut store_update, HEADER_HEAD_KEY, &mut self.header_head)?;
         Self::write_col_misc(&mut store_update, FINAL_HEAD_KEY, &mut self.final_head)?;
         Self::write_col_misc(
@@ -2651,7 +2849,12 @@ impl<'a> ChainStoreUpdate<'a> {
         {
             store_update.set_ser(ColBlockOrdinal, &index_to_bytes(*block_ordinal), block_hash)?;
         }
-        for mut wrapped_trie_changes in self.trie_changes.drain(..) {
+        for mut wrapped_trie_changes in std::mem::take(&mut self.trie_changes) {
+            if !wrapped_trie_changes.state_changes().is_empty() {
+                let block_height =
+                    self.get_block_header(wrapped_trie_changes.block_hash())?.height();
+                wrapped_trie_changes.state_changes_history_into(block_height, &mut store_update);
+            }
             wrapped_trie_changes
                 .wrapped_into(&mut store_update)
                 .map_err(|err| ErrorKind::Other(err.to_string()))?;
@@ -2767,6 +2970,9 @@ impl<'a> ChainStoreUpdate<'a> {
 
     pub fn commit(mut self) -> Result<(), Error> {
         let store_update = self.finalize()?;
+        if let Some(cold_store_update) = self.cold_store_update.take() {
+            cold_store_update.commit()?;
+        }
         store_update.commit()?;
         let ChainStoreCacheUpdate {
             blocks,
@@ -2892,11 +3098,13 @@ impl Into<SavedStoreUpdate> for ChainStoreUpdate<'_> {
         SavedStoreUpdate {
             chain_store: (),
             store_updates: self.store_updates,
+            cold_store_update: self.cold_store_update,
             chain_store_cache_update: self.chain_store_cache_update,
             head: self.head,
             tail: self.tail,
             chunk_tail: self.chunk_tail,
             fork_tail: self.fork_tail,
+            flat_state_deltas_tail: self.flat_state_deltas_tail,
             header_head: self.header_head,
             final_head: self.final_head,
             largest_target_height: self.largest_target_height,
@@ -2922,11 +3130,13 @@ impl SavedStoreUpdate {
         ChainStoreUpdate {
             chain_store,
             store_updates: self.store_updates,
+            cold_store_update: self.cold_store_update,
             chain_store_cache_update: self.chain_store_cache_update,
             head: self.head,
             tail: self.tail,
             chunk_tail: self.chunk_tail,
             fork_tail: self.fork_tail,
+            flat_state_deltas_tail: self.flat_state_deltas_tail,
             header_head: self.header_head,
             final_head: self.final_head,
             largest_target_height: self.largest_target_height,
@@ -2952,20 +3162,31 @@ mod tests {
     use strum::IntoEnumIterator;
 
     use near_crypto::KeyType;
+    use near_primitives::account::Account;
     use near_primitives::block::{Block, Tip};
     #[cfg(feature = "expensive_tests")]
     use near_primitives::epoch_manager::block_info::BlockInfo;
     use near_primitives::errors::InvalidTxError;
-    use near_primitives::hash::hash;
-    use near_primitives::types::{BlockHeight, EpochId, GCCount, NumBlocks};
+    use near_primitives::hash::{hash, CryptoHash};
+    use near_primitives::shard_layout::ShardUId;
+    use near_primitives::sharding::StateSyncInfo;
+    use near_primitives::syncing::{StatePartKey, StatePartStatus};
+    use near_primitives::transaction::{ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof};
+    use near_primitives::trie_key::TrieKey;
+    use near_primitives::types::{
+        Balance, BlockHeight, EpochId, GCCount, NumBlocks, RawStateChange,
+        RawStateChangesWithTrieKey, StateChangeCause, StateChangeValue,
+    };
     use near_primitives::utils::index_to_bytes;
     use near_primitives::validator_signer::InMemoryValidatorSigner;
     use near_store::test_utils::create_test_store;
-    use near_store::DBCol;
+    use near_store::{
+        create_split_store, create_store, DBCol, ShardTries, Store, TrieChanges, WrappedTrieChanges,
+    };
     #[cfg(feature = "expensive_tests")]
     use {crate::store_validator::StoreValidator, near_chain_configs::GenesisConfig};
 
-    use crate::store::{ChainStoreAccess, GCMode};
+    use crate::store::{ChainStore, ChainStoreAccess, GCMode};
     use crate::test_utils::KeyValueRuntime;
     use crate::{Chain, ChainGenesis, DoomslugThresholdMode};
 
@@ -2974,7 +3195,10 @@ mod tests {
     }
 
     fn get_chain_with_epoch_length(epoch_length: NumBlocks) -> Chain {
-        let store = create_test_store();
+        get_chain_with_store(create_test_store(), epoch_length)
+    }
+
+    fn get_chain_with_store(store: Arc<Store>, epoch_length: NumBlocks) -> Chain {
         let chain_genesis = ChainGenesis::test();
         let validators = vec![vec!["test1"]];
         let runtime_adapter = Arc::new(KeyValueRuntime::new_with_validators(
@@ -3197,11 +3421,166 @@ mod tests {
         assert_ne!(epoch_id_to_hash, epoch_id_to_hash1);
     }
 
-    /// Test that garbage collection works properly. The blocks behind gc head should be garbage
-    /// collected while the blocks that are ahead of it should not.
+    /// Test that reads go to the pinned snapshot until it's released, while the caches keep
+    /// working.
     #[test]
-    fn test_clear_old_data() {
-        let mut chain = get_chain_with_epoch_length(1);
+    fn test_pin_snapshot() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_pin_snapshot").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let mut chain_store = ChainStore::new(store.clone(), 0);
+        let tip = Tip {
+            height: 1,
+            last_block_hash: hash(&[1]),
+            prev_block_hash: hash(&[0]),
+            epoch_id: EpochId::default(),
+            next_epoch_id: EpochId::default(),
+        };
+
+        assert!(chain_store.pin_snapshot());
+        assert!(!chain_store.pin_snapshot());
+        let mut store_update = store.store_update();
+        store_update.set_ser(DBCol::ColBlockMisc, near_store::HEAD_KEY, &tip).unwrap();
+        store_update.commit().unwrap();
+        assert!(chain_store.head().is_err());
+
+        chain_store.unpin_snapshot();
+        assert_eq!(chain_store.head().unwrap(), tip);
+    }
+
+    /// Test that the progress of state parts is stored next to `StateSyncInfo`s without being
+    /// mistaken for them and is cleared together with the parts.
+    #[test]
+    fn test_state_parts_progress() {
+        let mut chain = get_chain();
+        let sync_hash = hash(&[1]);
+        let mut store_update = chain.mut_store().store_update();
+        store_update
+            .add_state_dl_info(StateSyncInfo { epoch_tail_hash: sync_hash, shards: vec![] });
+        store_update.commit().unwrap();
+        assert!(!chain.store().has_state_parts_progress(&sync_hash));
+
+        let mut store_update = chain.store().owned_store().store_update();
+        let key = StatePartKey(sync_hash, 0, 1).try_to_vec().unwrap();
+        store_update.set(DBCol::ColStateParts, &key, &[1]);
+        store_update.set_ser(DBCol::ColStateDlInfos, &key, &StatePartStatus::Applied).unwrap();
+        store_update.commit().unwrap();
+        assert!(chain.store().has_state_parts_progress(&sync_hash));
+        assert_eq!(chain.store().get_state_part_status(sync_hash, 0, 0).unwrap(), None);
+        assert_eq!(
+            chain.store().get_state_part_status(sync_hash, 0, 1).unwrap(),
+            Some(StatePartStatus::Applied)
+        );
+        assert_eq!(chain.store().iterate_state_sync_infos().len(), 1);
+
+        let mut store_update = chain.mut_store().store_update();
+        store_update.gc_col_state_parts(sync_hash, 0, 2).unwrap();
+        store_update.commit().unwrap();
+        assert!(!chain.store().has_state_parts_progress(&sync_hash));
+        assert_eq!(chain.store().iterate_state_sync_infos().len(), 1);
+    }
+
+    /// Test that the history of a key has the changes of the blocks of the canonical chain in the
+    /// requested range of heights.
+    #[test]
+    fn test_state_changes_history() {
+        let mut chain = get_chain();
+        let genesis = chain.get_block_by_height(0).unwrap().clone();
+        let signer =
+            InMemoryValidatorSigner::from_seed("test1".parse().unwrap(), KeyType::ED25519, "test1");
+        let tries = ShardTries::new(chain.store().owned_store(), 0, 1);
+        let trie_key = TrieKey::Account { account_id: "test1".parse().unwrap() };
+        // Keys of accounts whose ids start with the id of the first one.
+        let longer_keys = [
+            TrieKey::Account { account_id: "test1.near".parse().unwrap() },
+            TrieKey::Account { account_id: "test10".parse().unwrap() },
+        ];
+        let block1 = Block::empty_with_height(&genesis, 1, &signer);
+        let block2 = Block::empty_with_height(&block1, 2, &signer);
+        let block3 = Block::empty_with_height(&block2, 3, &signer);
+        let fork_block2 = Block::empty_with_height(&genesis, 2, &signer);
+        // Blocks with the balance of the account they set, if they change it.
+        let blocks = [
+            (&block1, true, Some(1)),
+            (&block2, true, None),
+            (&block3, true, Some(3)),
+            (&fork_block2, false, Some(20)),
+        ];
+        for (block, is_canonical, balance) in blocks.iter() {
+            let mut store_update = chain.mut_store().store_update();
+            store_update.save_block_header(block.header().clone()).unwrap();
+            if *is_canonical {
+                store_update
+                    .update_height_if_not_challenged(block.header().height(), *block.hash())
+                    .unwrap();
+            }
+            let change = |trie_key: &TrieKey, balance| RawStateChangesWithTrieKey {
+                trie_key: trie_key.clone(),
+                changes: vec![RawStateChange {
+                    cause: StateChangeCause::InitialState,
+                    data: Some(
+                        Account::new(balance, 0, CryptoHash::default(), 0).try_to_vec().unwrap(),
+                    ),
+                }],
+            };
+            // The longer keys are changed by every block, with balances of 100 + height.
+            let state_changes = balance
+                .map(|balance| change(&trie_key, balance))
+                .into_iter()
+                .chain(
+                    longer_keys
+                        .iter()
+                        .map(|key| change(key, 100 + block.header().height() as Balance)),
+                )
+                .collect();
+            store_update.save_trie_changes(WrappedTrieChanges::new(
+                tries.clone(),
+                ShardUId::single_shard(),
+                TrieChanges::empty(CryptoHash::default()),
+                state_changes,
+                *block.hash(),
+            ));
+            store_update.commit().unwrap();
+        }
+
+        let key_history = |trie_key, from_height, to_height| -> Vec<(BlockHeight, Balance)> {
+            let (changes, next_height) = chain
+                .store()
+                .get_state_changes_history(trie_key, from_height, to_height, 10)
+                .unwrap();
+            assert_eq!(next_height, None);
+            changes
+                .into_iter()
+                .map(|change| match change.change.value {
+                    StateChangeValue::AccountUpdate { account, .. } => {
+                        (change.block_height, account.amount())
+                    }
+                    value => panic!("unexpected change {:?}", value),
+                })
+                .collect()
+        };
+        let history = |from_height, to_height| key_history(&trie_key, from_height, to_height);
+        assert_eq!(history(0, 10), vec![(1, 1), (3, 3)]);
+        assert_eq!(history(2, 3), vec![(3, 3)]);
+        assert_eq!(history(0, 2), vec![(1, 1)]);
+        assert_eq!(history(4, 10), vec![]);
+        for key in longer_keys.iter() {
+            assert_eq!(key_history(key, 0, 10), vec![(1, 101), (2, 102), (3, 103)]);
+        }
+
+        // Changes past the limit are continued from the height of the next one.
+        let (changes, next_height) =
+            chain.store().get_state_changes_history(&trie_key, 0, 10, 1).unwrap();
+        assert_eq!(changes.iter().map(|change| change.block_height).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(next_height, Some(3));
+        let (changes, next_height) =
+            chain.store().get_state_changes_history(&trie_key, 3, 10, 1).unwrap();
+        assert_eq!(changes.iter().map(|change| change.block_height).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(next_height, None);
+    }
+
+    /// Saves a chain of empty blocks of heights from 1 to `last_height` on top of genesis and
+    /// returns all the blocks, genesis included.
+    fn save_empty_blocks(chain: &mut Chain, last_height: BlockHeight) -> Vec<Block> {
         let runtime_adapter = chain.runtime_adapter.clone();
         let genesis = chain.get_block_by_height(0).unwrap().clone();
         let signer = Arc::new(InMemoryValidatorSigner::from_seed(
@@ -3211,7 +3590,7 @@ mod tests {
         ));
         let mut prev_block = genesis;
         let mut blocks = vec![prev_block.clone()];
-        for i in 1..15 {
+        for i in 1..=last_height {
             // This is a hack to make the KeyValueRuntime to have epoch information stored
             runtime_adapter
                 .get_next_epoch_id_from_prev_block(prev_block.hash())
@@ -3232,6 +3611,81 @@ mod tests {
 
             prev_block = block.clone();
         }
+        blocks
+    }
+
+    /// Test that garbage collection of a split store moves the data behind gc head to the cold
+    /// database, where it can still be read, and deletes the columns which are not cold.
+    #[test]
+    fn test_clear_old_data_split_store() {
+        let hot_store = create_test_store();
+        let cold_store = create_test_store();
+        let mut chain = get_chain_with_store(create_split_store(&hot_store, &cold_store), 1);
+        let blocks = save_empty_blocks(&mut chain, 14);
+
+        chain.epoch_length = 1;
+        let trie = chain.runtime_adapter.get_tries();
+        assert!(chain.clear_data(trie, 100).is_ok());
+
+        // Old blocks are moved to the cold database and can still be read.
+        for i in 0..15 {
+            let block_hash = blocks[i].hash().as_ref().to_vec();
+            assert_eq!(chain.get_block(&blocks[i].hash()).unwrap(), &blocks[i]);
+            assert_eq!(hot_store.exists(DBCol::ColBlock, &block_hash).unwrap(), i >= 8);
+            assert_eq!(cold_store.exists(DBCol::ColBlock, &block_hash).unwrap(), i < 8);
+            // Height indexes are moved as well, so old blocks can still be found by height.
+            let height_key = index_to_bytes(i as BlockHeight);
+            assert!(chain.mut_store().get_all_block_hashes_by_height(i as BlockHeight).is_ok());
+            assert_eq!(hot_store.exists(DBCol::ColBlockPerHeight, &height_key).unwrap(), i >= 8);
+            assert_eq!(cold_store.exists(DBCol::ColBlockPerHeight, &height_key).unwrap(), i < 8);
+            // Columns which are not cold are GCed as usual.
+            assert_eq!(hot_store.exists(DBCol::ColBlockRefCount, &block_hash).unwrap(), i >= 8);
+            assert!(!cold_store.exists(DBCol::ColBlockRefCount, &block_hash).unwrap());
+        }
+        assert_eq!(chain.store().tail().unwrap(), 8);
+    }
+
+    /// Test that the cold copy of outcomes with the same id in two blocks keeps both of them when
+    /// the blocks are GCed one after another.
+    #[test]
+    fn test_gc_outcomes_split_store() {
+        let hot_store = create_test_store();
+        let cold_store = create_test_store();
+        let mut chain = get_chain_with_store(create_split_store(&hot_store, &cold_store), 1);
+        let outcome_id = hash(b"receipt");
+        let block_hashes = [hash(b"block1"), hash(b"block2")];
+        for block_hash in block_hashes.iter() {
+            let mut store_update = chain.mut_store().store_update();
+            store_update.save_outcomes_with_proofs(
+                block_hash,
+                0,
+                vec![ExecutionOutcomeWithId { id: outcome_id, ..Default::default() }],
+                vec![vec![]],
+            );
+            store_update.commit().unwrap();
+        }
+        assert_eq!(chain.store().get_outcomes_by_id(&outcome_id).unwrap().len(), 2);
+
+        for block_hash in block_hashes.iter() {
+            let mut store_update = chain.mut_store().store_update();
+            let mut outcomes_update = store_update.store().store_update();
+            store_update.gc_outcome(&outcome_id, block_hash, &mut outcomes_update).unwrap();
+            store_update.merge(outcomes_update);
+            store_update.commit().unwrap();
+        }
+        assert!(!hot_store.exists(DBCol::ColTransactionResult, outcome_id.as_ref()).unwrap());
+        let cold_outcomes: Vec<ExecutionOutcomeWithIdAndProof> =
+            cold_store.get_ser(DBCol::ColTransactionResult, outcome_id.as_ref()).unwrap().unwrap();
+        assert_eq!(
+            cold_outcomes.iter().map(|outcome| outcome.block_hash).collect::<Vec<_>>(),
+            block_hashes.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_clear_old_data() {
+        let mut chain = get_chain_with_epoch_length(1);
+        let blocks = save_empty_blocks(&mut chain, 14);
 
         chain.epoch_length = 1;
         let trie = chain.runtime_adapter.get_tries();
@@ -2,7 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use strum::IntoEnumIterator;
 use tracing::warn;
 
@@ -14,22 +14,50 @@ use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::epoch_manager::AGGREGATOR_KEY;
 use near_primitives::hash::CryptoHash;
 use near_primitives::sharding::{ChunkHash, ShardChunk, StateSyncInfo};
-use near_primitives::syncing::{ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey};
+use near_primitives::syncing::{
+    ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey, StatePartStatus,
+};
 use near_primitives::transaction::ExecutionOutcomeWithIdAndProof;
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{AccountId, BlockHeight, EpochId, GCCount};
 use near_primitives::utils::get_block_shard_id_rev;
 use near_store::{
     decode_value_with_rc, DBCol, Store, TrieChanges, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC,
+    STORE_VALIDATOR_CURSOR_KEY,
 };
+pub use repair::RepairPlan;
 use validate::StoreValidatorError;
 
 use crate::RuntimeAdapter;
 use near_primitives::shard_layout::get_block_shard_uid_rev;
 use near_primitives::time::Clock;
 
+mod repair;
 mod validate;
 
+/// Columns which have checks, in the order they are validated.
+const VALIDATED_COLS: [DBCol; 19] = [
+    DBCol::ColBlock,
+    DBCol::ColBlockHeader,
+    DBCol::ColBlockHeight,
+    DBCol::ColChunkExtra,
+    DBCol::ColTransactionResult,
+    DBCol::ColEpochInfo,
+    DBCol::ColBlockInfo,
+    DBCol::ColChunks,
+    DBCol::ColStateDlInfos,
+    DBCol::ColStateHeaders,
+    DBCol::ColStateParts,
+    DBCol::ColTransactions,
+    DBCol::ColBlockRefCount,
+    DBCol::ColTrieChanges,
+    DBCol::ColChunkHashesByHeight,
+    DBCol::ColGCCount,
+    DBCol::ColOutcomeIds,
+    DBCol::ColReceipts,
+    DBCol::ColHeaderHashesByHeight,
+];
+
 fn to_string<T: std::fmt::Debug>(v: &T) -> String {
     format!("{:?}", v)
 }
@@ -70,6 +98,21 @@ pub struct ErrorMessage {
     pub col: String,
     pub key: String,
     pub err: StoreValidatorError,
+    pub repair: RepairPlan,
+}
+
+/// Position of the incremental validation, stored in `ColBlockMisc` so that it survives restarts.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoreValidatorCursor {
+    pub col: DBCol,
+    /// Last validated key of `col`, `None` if validation of `col` has not started yet.
+    pub last_key: Option<Vec<u8>>,
+}
+
+impl StoreValidatorCursor {
+    fn start() -> Self {
+        StoreValidatorCursor { col: VALIDATED_COLS[0], last_key: None }
+    }
 }
 
 pub struct StoreValidator {
@@ -80,6 +123,18 @@ pub struct StoreValidator {
     inner: StoreValidatorCache,
     timeout: Option<u64>,
     start_time: Instant,
+    /// Position of the incremental validation, `None` until the first step loads it.
+    cursor: Option<StoreValidatorCursor>,
+    /// Whether the current pass started from the beginning. Checks which depend on data
+    /// collected from other columns are skipped in passes resumed after a restart.
+    full_pass: bool,
+    /// Whether the current pass reads a snapshot of the database. Refcounts collected from a
+    /// database which changes during the pass can't be trusted enough to be repaired.
+    fixed_snapshot: bool,
+    /// Whether the refcounts are checked against the references counted in other columns.
+    /// Incremental passes read a database which keeps changing between their steps, so they
+    /// would report refcounts changed in the middle of the pass as errors.
+    check_refcounts: bool,
 
     pub errors: Vec<ErrorMessage>,
     tests: u64,
@@ -100,6 +155,10 @@ impl StoreValidator {
             inner: StoreValidatorCache::new(),
             timeout: None,
             start_time: Clock::instant(),
+            cursor: None,
+            full_pass: true,
+            fixed_snapshot: false,
+            check_refcounts: true,
             errors: vec![],
             tests: 0,
         }
@@ -134,203 +193,21 @@ impl StoreValidator {
         self.tests
     }
     fn process_error<K: std::fmt::Debug>(&mut self, err: StoreValidatorError, key: K, col: DBCol) {
-        self.errors.push(ErrorMessage { key: to_string(&key), col: to_string(&col), err })
+        let repair = RepairPlan::manual(&err);
+        self.process_error_with_repair(err, key, col, repair)
+    }
+    fn process_error_with_repair<K: std::fmt::Debug>(
+        &mut self,
+        err: StoreValidatorError,
+        key: K,
+        col: DBCol,
+        repair: RepairPlan,
+    ) {
+        self.errors.push(ErrorMessage { key: to_string(&key), col: to_string(&col), err, repair })
     }
     fn validate_col(&mut self, col: DBCol) -> Result<(), StoreValidatorError> {
         for (key, value) in self.store.clone().iter_without_rc_logic(col) {
-            let key_ref = key.as_ref();
-            let value_ref = value.as_ref();
-            match col {
-                DBCol::ColBlockHeader => {
-                    let block_hash = CryptoHash::try_from(key_ref)?;
-                    let header = BlockHeader::try_from_slice(value_ref)?;
-                    // Block Header Hash is valid
-                    self.check(&validate::block_header_hash_validity, &block_hash, &header, col);
-                    // Block Header Height is valid
-                    self.check(&validate::block_header_height_validity, &block_hash, &header, col);
-                    // Block Header can be indexed by Height
-                    self.check(&validate::header_hash_indexed_by_height, &block_hash, &header, col);
-                }
-                DBCol::ColBlock => {
-                    let block_hash = CryptoHash::try_from(key_ref)?;
-                    let block = Block::try_from_slice(value_ref)?;
-                    // Block Hash is valid
-                    self.check(&validate::block_hash_validity, &block_hash, &block, col);
-                    // Block Height is valid
-                    self.check(&validate::block_height_validity, &block_hash, &block, col);
-                    // Block can be indexed by its Height
-                    self.check(&validate::block_indexed_by_height, &block_hash, &block, col);
-                    // Block Header for current Block exists
-                    self.check(&validate::block_header_exists, &block_hash, &block, col);
-                    // Chunks for current Block exist
-                    self.check(&validate::block_chunks_exist, &block_hash, &block, col);
-                    // Chunks for current Block have Height Created not higher than Block Height
-                    self.check(&validate::block_chunks_height_validity, &block_hash, &block, col);
-                    // BlockInfo for current Block exists
-                    self.check(&validate::block_info_exists, &block_hash, &block, col);
-                    // EpochInfo for current Epoch id of Block exists
-                    self.check(&validate::block_epoch_exists, &block_hash, &block, col);
-                    // Increase Block Refcount
-                    self.check(&validate::block_increase_refcount, &block_hash, &block, col);
-                }
-                DBCol::ColBlockHeight => {
-                    let height = BlockHeight::try_from_slice(key_ref)?;
-                    let hash = CryptoHash::try_from(value_ref)?;
-                    // Block on the Canonical Chain is stored properly
-                    self.check(&validate::canonical_header_validity, &height, &hash, col);
-                    // If prev Block exists, it's also on the Canonical Chain and
-                    // there are no Blocks in range (prev_height, height) on the Canonical Chain
-                    self.check(&validate::canonical_prev_block_validity, &height, &hash, col);
-                }
-                DBCol::ColChunks => {
-                    let chunk_hash = ChunkHash::try_from_slice(key_ref)?;
-                    let shard_chunk = ShardChunk::try_from_slice(value_ref)?;
-                    // Chunk Hash is valid
-                    self.check(&validate::chunk_hash_validity, &chunk_hash, &shard_chunk, col);
-                    // Chunk Height Created is not lower than Chunk Tail
-                    self.check(&validate::chunk_tail_validity, &chunk_hash, &shard_chunk, col);
-                    // ShardChunk can be indexed by Height
-                    self.check(
-                        &validate::chunk_indexed_by_height_created,
-                        &chunk_hash,
-                        &shard_chunk,
-                        col,
-                    );
-                    // Check that all Txs in Chunk exist
-                    self.check(&validate::chunk_tx_exists, &chunk_hash, &shard_chunk, col);
-                }
-                DBCol::ColChunkExtra => {
-                    let (block_hash, shard_uid) = get_block_shard_uid_rev(key_ref)?;
-                    let chunk_extra = ChunkExtra::try_from_slice(value_ref)?;
-                    self.check(
-                        &validate::chunk_extra_block_exists,
-                        &(block_hash, shard_uid),
-                        &chunk_extra,
-                        col,
-                    );
-                }
-                DBCol::ColTrieChanges => {
-                    let (block_hash, shard_uid) = get_block_shard_uid_rev(key_ref)?;
-                    let trie_changes = TrieChanges::try_from_slice(value_ref)?;
-                    // ShardChunk should exist for current TrieChanges
-                    self.check(
-                        &validate::trie_changes_chunk_extra_exists,
-                        &(block_hash, shard_uid),
-                        &trie_changes,
-                        col,
-                    );
-                }
-                DBCol::ColChunkHashesByHeight => {
-                    let height = BlockHeight::try_from_slice(key_ref)?;
-                    let chunk_hashes = HashSet::<ChunkHash>::try_from_slice(value_ref)?;
-                    // ShardChunk which can be indexed by Height exists
-                    self.check(&validate::chunk_of_height_exists, &height, &chunk_hashes, col);
-                }
-                DBCol::ColHeaderHashesByHeight => {
-                    let height = BlockHeight::try_from_slice(key_ref)?;
-                    let header_hashes = HashSet::<CryptoHash>::try_from_slice(value_ref)?;
-                    // Headers which can be indexed by Height exists
-                    self.check(
-                        &validate::header_hash_of_height_exists,
-                        &height,
-                        &header_hashes,
-                        col,
-                    );
-                }
-                DBCol::ColOutcomeIds => {
-                    let (block_hash, _) = get_block_shard_id_rev(key_ref)?;
-                    let outcome_ids = Vec::<CryptoHash>::try_from_slice(value_ref)?;
-                    // TransactionResult which can be indexed by Outcome id exists
-                    self.check(
-                        &validate::outcome_by_outcome_id_exists,
-                        &block_hash,
-                        &outcome_ids,
-                        col,
-                    );
-                    // Block which can be indexed by Outcome block_hash exists
-                    self.check(&validate::outcome_id_block_exists, &block_hash, &outcome_ids, col);
-                }
-                DBCol::ColTransactionResult => {
-                    let outcome_id = CryptoHash::try_from_slice(key_ref)?;
-                    let outcomes =
-                        <Vec<ExecutionOutcomeWithIdAndProof>>::try_from_slice(value_ref)?;
-                    // Outcome is reachable in ColOutcomesByBlockHash
-                    self.check(
-                        &validate::outcome_indexed_by_block_hash,
-                        &outcome_id,
-                        &outcomes,
-                        col,
-                    );
-                }
-                DBCol::ColStateDlInfos => {
-                    let block_hash = CryptoHash::try_from(key_ref)?;
-                    let state_sync_info = StateSyncInfo::try_from_slice(value_ref)?;
-                    // StateSyncInfo is valid
-                    self.check(
-                        &validate::state_sync_info_valid,
-                        &block_hash,
-                        &state_sync_info,
-                        col,
-                    );
-                    // Block which can be indexed by StateSyncInfo exists
-                    self.check(
-                        &validate::state_sync_info_block_exists,
-                        &block_hash,
-                        &state_sync_info,
-                        col,
-                    );
-                }
-                DBCol::ColBlockInfo => {
-                    let block_hash = CryptoHash::try_from(key_ref)?;
-                    let block_info = BlockInfo::try_from_slice(value_ref)?;
-                    // Block which can be indexed by BlockInfo exists
-                    self.check(
-                        &validate::block_info_block_header_exists,
-                        &block_hash,
-                        &block_info,
-                        col,
-                    );
-                }
-                DBCol::ColEpochInfo => {
-                    if key_ref != AGGREGATOR_KEY {
-                        let epoch_id = EpochId::try_from_slice(key_ref)?;
-                        let epoch_info = EpochInfo::try_from_slice(value_ref)?;
-                        // Epoch should exist
-                        self.check(&validate::epoch_validity, &epoch_id, &epoch_info, col);
-                    }
-                }
-                DBCol::ColGCCount => {
-                    let col = DBCol::try_from_slice(key_ref)?;
-                    let count = GCCount::try_from_slice(value_ref)?;
-                    self.check(&validate::gc_col_count, &col, &count, col);
-                }
-                DBCol::ColTransactions => {
-                    let (_value, rc) = decode_value_with_rc(value_ref);
-                    let tx_hash = CryptoHash::try_from(key_ref)?;
-                    self.check(&validate::tx_refcount, &tx_hash, &(rc as u64), col);
-                }
-                DBCol::ColReceipts => {
-                    let (_value, rc) = decode_value_with_rc(value_ref);
-                    let receipt_id = CryptoHash::try_from(key_ref)?;
-                    self.check(&validate::receipt_refcount, &receipt_id, &(rc as u64), col);
-                }
-                DBCol::ColBlockRefCount => {
-                    let block_hash = CryptoHash::try_from(key_ref)?;
-                    let refcount = u64::try_from_slice(value_ref)?;
-                    self.check(&validate::block_refcount, &block_hash, &refcount, col);
-                }
-                DBCol::ColStateHeaders => {
-                    let key = StateHeaderKey::try_from_slice(key_ref)?;
-                    let header = ShardStateSyncResponseHeader::try_from_slice(value_ref)?;
-                    self.check(&validate::state_header_block_exists, &key, &header, col);
-                }
-                DBCol::ColStateParts => {
-                    let key = StatePartKey::try_from_slice(key_ref)?;
-                    let part = value_ref.to_vec();
-                    self.check(&validate::state_part_header_exists, &key, &part, col);
-                }
-                _ => {}
-            }
+            self.validate_record(col, key.as_ref(), value.as_ref())?;
             if let Some(timeout) = self.timeout {
                 if self.start_time.elapsed() > Duration::from_millis(timeout) {
                     return Ok(());
@@ -339,14 +216,251 @@ impl StoreValidator {
         }
         Ok(())
     }
-    pub fn validate(&mut self) {
-        self.start_time = Clock::instant();
-
-        // Init checks
+    /// Validates up to `max_keys` records of the column the cursor points to, continuing after
+    /// the last validated key. Returns the number of validated records.
+    fn validate_col_from(&mut self, cursor: &mut StoreValidatorCursor, max_keys: u64) -> u64 {
+        let store = self.store.clone();
+        let from = cursor.last_key.clone().unwrap_or_default();
+        let mut validated = 0;
+        for (key, value) in store.iter_without_rc_logic_from(cursor.col, &from) {
+            if cursor.last_key.as_deref() == Some(key.as_ref()) {
+                continue;
+            }
+            if validated == max_keys {
+                break;
+            }
+            if let Err(e) = self.validate_record(cursor.col, key.as_ref(), value.as_ref()) {
+                self.process_error(e, &key, cursor.col)
+            }
+            cursor.last_key = Some(key.to_vec());
+            validated += 1;
+        }
+        validated
+    }
+    fn validate_record(
+        &mut self,
+        col: DBCol,
+        key_ref: &[u8],
+        value_ref: &[u8],
+    ) -> Result<(), StoreValidatorError> {
+        match col {
+            DBCol::ColBlockHeader => {
+                let block_hash = CryptoHash::try_from(key_ref)?;
+                let header = BlockHeader::try_from_slice(value_ref)?;
+                // Block Header Hash is valid
+                self.check(&validate::block_header_hash_validity, &block_hash, &header, col);
+                // Block Header Height is valid
+                self.check(&validate::block_header_height_validity, &block_hash, &header, col);
+                // Block Header can be indexed by Height
+                self.check(&validate::header_hash_indexed_by_height, &block_hash, &header, col);
+            }
+            DBCol::ColBlock => {
+                let block_hash = CryptoHash::try_from(key_ref)?;
+                let block = Block::try_from_slice(value_ref)?;
+                // Block Hash is valid
+                self.check(&validate::block_hash_validity, &block_hash, &block, col);
+                // Block Height is valid
+                self.check(&validate::block_height_validity, &block_hash, &block, col);
+                // Block can be indexed by its Height
+                self.check(&validate::block_indexed_by_height, &block_hash, &block, col);
+                // Block Header for current Block exists
+                self.check(&validate::block_header_exists, &block_hash, &block, col);
+                // Chunks for current Block exist
+                self.check(&validate::block_chunks_exist, &block_hash, &block, col);
+                // Chunks for current Block have Height Created not higher than Block Height
+                self.check(&validate::block_chunks_height_validity, &block_hash, &block, col);
+                // BlockInfo for current Block exists
+                self.check(&validate::block_info_exists, &block_hash, &block, col);
+                // EpochInfo for current Epoch id of Block exists
+                self.check(&validate::block_epoch_exists, &block_hash, &block, col);
+                // Increase Block Refcount
+                self.check(&validate::block_increase_refcount, &block_hash, &block, col);
+            }
+            DBCol::ColBlockHeight => {
+                let height = BlockHeight::try_from_slice(key_ref)?;
+                let hash = CryptoHash::try_from(value_ref)?;
+                // Block on the Canonical Chain is stored properly
+                self.check(&validate::canonical_header_validity, &height, &hash, col);
+                // If prev Block exists, it's also on the Canonical Chain and
+                // there are no Blocks in range (prev_height, height) on the Canonical Chain
+                self.check(&validate::canonical_prev_block_validity, &height, &hash, col);
+            }
+            DBCol::ColChunks => {
+                let chunk_hash = ChunkHash::try_from_slice(key_ref)?;
+                let shard_chunk = ShardChunk::try_from_slice(value_ref)?;
+                // Chunk Hash is valid
+                self.check(&validate::chunk_hash_validity, &chunk_hash, &shard_chunk, col);
+                // Chunk Height Created is not lower than Chunk Tail
+                self.check(&validate::chunk_tail_validity, &chunk_hash, &shard_chunk, col);
+                // ShardChunk can be indexed by Height
+                self.check(
+                    &validate::chunk_indexed_by_height_created,
+                    &chunk_hash,
+                    &shard_chunk,
+                    col,
+                );
+                // Check that all Txs in Chunk exist
+                self.check(&validate::chunk_tx_exists, &chunk_hash, &shard_chunk, col);
+            }
+            DBCol::ColChunkExtra => {
+                let (block_hash, shard_uid) = get_block_shard_uid_rev(key_ref)?;
+                let chunk_extra = ChunkExtra::try_from_slice(value_ref)?;
+                // Block for current ChunkExtra exists, otherwise ChunkExtra is orphaned
+                self.check_or_repair(
+                    &validate::chunk_extra_block_exists,
+                    &(block_hash, shard_uid),
+                    &chunk_extra,
+                    col,
+                    &|| RepairPlan::DeleteRecord { col, key: key_ref.to_vec() },
+                );
+            }
+            DBCol::ColTrieChanges => {
+                let (block_hash, shard_uid) = get_block_shard_uid_rev(key_ref)?;
+                let trie_changes = TrieChanges::try_from_slice(value_ref)?;
+                // ShardChunk should exist for current TrieChanges
+                self.check(
+                    &validate::trie_changes_chunk_extra_exists,
+                    &(block_hash, shard_uid),
+                    &trie_changes,
+                    col,
+                );
+            }
+            DBCol::ColChunkHashesByHeight => {
+                let height = BlockHeight::try_from_slice(key_ref)?;
+                let chunk_hashes = HashSet::<ChunkHash>::try_from_slice(value_ref)?;
+                // ShardChunk which can be indexed by Height exists
+                self.check(&validate::chunk_of_height_exists, &height, &chunk_hashes, col);
+            }
+            DBCol::ColHeaderHashesByHeight => {
+                let height = BlockHeight::try_from_slice(key_ref)?;
+                let header_hashes = HashSet::<CryptoHash>::try_from_slice(value_ref)?;
+                // Headers which can be indexed by Height exists
+                self.check(&validate::header_hash_of_height_exists, &height, &header_hashes, col);
+            }
+            DBCol::ColOutcomeIds => {
+                let (block_hash, _) = get_block_shard_id_rev(key_ref)?;
+                let outcome_ids = Vec::<CryptoHash>::try_from_slice(value_ref)?;
+                // TransactionResult which can be indexed by Outcome id exists
+                self.check(&validate::outcome_by_outcome_id_exists, &block_hash, &outcome_ids, col);
+                // Block which can be indexed by Outcome block_hash exists
+                self.check(&validate::outcome_id_block_exists, &block_hash, &outcome_ids, col);
+            }
+            DBCol::ColTransactionResult => {
+                let outcome_id = CryptoHash::try_from_slice(key_ref)?;
+                let outcomes = <Vec<ExecutionOutcomeWithIdAndProof>>::try_from_slice(value_ref)?;
+                // Outcome is reachable in ColOutcomesByBlockHash
+                self.check(&validate::outcome_indexed_by_block_hash, &outcome_id, &outcomes, col);
+            }
+            DBCol::ColStateDlInfos if key_ref.len() != std::mem::size_of::<CryptoHash>() => {
+                let key = StatePartKey::try_from_slice(key_ref)?;
+                let status = StatePartStatus::try_from_slice(value_ref)?;
+                // State Part which progress is stored exists
+                self.check(&validate::state_part_status_part_exists, &key, &status, col);
+            }
+            DBCol::ColStateDlInfos => {
+                let block_hash = CryptoHash::try_from(key_ref)?;
+                let state_sync_info = StateSyncInfo::try_from_slice(value_ref)?;
+                // StateSyncInfo is valid
+                self.check(&validate::state_sync_info_valid, &block_hash, &state_sync_info, col);
+                // Block which can be indexed by StateSyncInfo exists
+                self.check(
+                    &validate::state_sync_info_block_exists,
+                    &block_hash,
+                    &state_sync_info,
+                    col,
+                );
+            }
+            DBCol::ColBlockInfo => {
+                let block_hash = CryptoHash::try_from(key_ref)?;
+                let block_info = BlockInfo::try_from_slice(value_ref)?;
+                // Block which can be indexed by BlockInfo exists
+                self.check(
+                    &validate::block_info_block_header_exists,
+                    &block_hash,
+                    &block_info,
+                    col,
+                );
+            }
+            DBCol::ColEpochInfo => {
+                if key_ref != AGGREGATOR_KEY {
+                    let epoch_id = EpochId::try_from_slice(key_ref)?;
+                    let epoch_info = EpochInfo::try_from_slice(value_ref)?;
+                    // Epoch should exist
+                    self.check(&validate::epoch_validity, &epoch_id, &epoch_info, col);
+                }
+            }
+            DBCol::ColGCCount => {
+                let col = DBCol::try_from_slice(key_ref)?;
+                let count = GCCount::try_from_slice(value_ref)?;
+                self.check(&validate::gc_col_count, &col, &count, col);
+            }
+            // Refcounts are only known after a full pass over ColChunks and ColBlock
+            DBCol::ColTransactions if self.full_pass && self.check_refcounts => {
+                let (_value, rc) = decode_value_with_rc(value_ref);
+                let tx_hash = CryptoHash::try_from(key_ref)?;
+                let expected = self.inner.tx_refcount.get(&tx_hash).copied().unwrap_or_default();
+                let repair = self.refcount_repair(col, key_ref, rc, expected);
+                self.check_or_repair(&validate::tx_refcount, &tx_hash, &(rc as u64), col, &|| {
+                    repair.clone()
+                });
+            }
+            DBCol::ColReceipts if self.full_pass && self.check_refcounts => {
+                let (_value, rc) = decode_value_with_rc(value_ref);
+                let receipt_id = CryptoHash::try_from(key_ref)?;
+                let expected =
+                    self.inner.receipt_refcount.get(&receipt_id).copied().unwrap_or_default();
+                let repair = self.refcount_repair(col, key_ref, rc, expected);
+                self.check_or_repair(
+                    &validate::receipt_refcount,
+                    &receipt_id,
+                    &(rc as u64),
+                    col,
+                    &|| repair.clone(),
+                );
+            }
+            DBCol::ColBlockRefCount if self.full_pass && self.check_refcounts => {
+                let block_hash = CryptoHash::try_from(key_ref)?;
+                let refcount = u64::try_from_slice(value_ref)?;
+                self.check(&validate::block_refcount, &block_hash, &refcount, col);
+            }
+            DBCol::ColStateHeaders => {
+                let key = StateHeaderKey::try_from_slice(key_ref)?;
+                let header = ShardStateSyncResponseHeader::try_from_slice(value_ref)?;
+                self.check(&validate::state_header_block_exists, &key, &header, col);
+            }
+            DBCol::ColStateParts => {
+                let key = StatePartKey::try_from_slice(key_ref)?;
+                let part = value_ref.to_vec();
+                self.check(&validate::state_part_header_exists, &key, &part, col);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    /// Suggests setting the refcount of a record to the number of found references, if those
+    /// were counted over a snapshot. Otherwise blocks processed during the pass may have changed
+    /// both the refcount and the references, and the refcount has to be checked again.
+    fn refcount_repair(&self, col: DBCol, key: &[u8], found: i64, expected: u64) -> RepairPlan {
+        if self.fixed_snapshot {
+            RepairPlan::SetRefcount { col, key: key.to_vec(), found, expected }
+        } else {
+            RepairPlan::Manual {
+                hint: "refcount was counted while the database changed, validate a snapshot to repair it".to_string(),
+            }
+        }
+    }
+    fn init_checks(&mut self) {
         // Check Head-Tail validity and fill cache with their values
         if let Err(e) = validate::head_tail_validity(self) {
             self.process_error(e, "HEAD / HEADER_HEAD / TAIL / CHUNK_TAIL", DBCol::ColBlockMisc)
         }
+    }
+    pub fn validate(&mut self) {
+        self.start_time = Clock::instant();
+        self.full_pass = true;
+
+        // Init checks
+        self.init_checks();
 
         // Main loop
         for col in DBCol::iter() {
@@ -367,7 +481,85 @@ impl StoreValidator {
                 return;
             }
         }
+        self.final_checks();
+    }
 
+    /// Like `validate`, but reads a snapshot of the database taken at the start, so that the
+    /// collected refcounts are consistent and can be repaired. Safe to run on a live database.
+    pub fn validate_snapshot(&mut self) {
+        let store = self.store.clone();
+        self.store = Arc::new(store.snapshot());
+        self.inner = StoreValidatorCache::new();
+        self.fixed_snapshot = true;
+        self.check_refcounts = true;
+        self.validate();
+        self.fixed_snapshot = false;
+        self.store = store;
+    }
+
+    /// Validates up to `max_keys` records, continuing from the position where the previous step
+    /// stopped. The position is persisted, so validation resumes from it after a restart.
+    /// Returns `true` when a pass over all columns is completed, the next step starts a new one.
+    /// Refcounts are not checked, use `validate_snapshot` for them.
+    pub fn validate_step(&mut self, max_keys: u64) -> Result<bool, StoreValidatorError> {
+        self.check_refcounts = false;
+        let mut cursor = match self.cursor.take() {
+            Some(cursor) => cursor,
+            None => {
+                let cursor = self
+                    .store
+                    .get_ser::<StoreValidatorCursor>(
+                        DBCol::ColBlockMisc,
+                        STORE_VALIDATOR_CURSOR_KEY,
+                    )?
+                    .unwrap_or_else(StoreValidatorCursor::start);
+                self.inner = StoreValidatorCache::new();
+                self.full_pass = cursor == StoreValidatorCursor::start();
+                self.init_checks();
+                cursor
+            }
+        };
+        let mut max_keys = max_keys;
+        let finished = loop {
+            let validated = self.validate_col_from(&mut cursor, max_keys);
+            if validated == max_keys {
+                break false;
+            }
+            max_keys -= validated;
+            match VALIDATED_COLS.iter().find(|col| **col as usize > cursor.col as usize) {
+                Some(col) => cursor = StoreValidatorCursor { col: *col, last_key: None },
+                None => break true,
+            }
+        };
+
+        let mut store_update = self.store.store_update();
+        if finished {
+            if self.full_pass {
+                self.final_checks();
+            }
+            store_update.delete(DBCol::ColBlockMisc, STORE_VALIDATOR_CURSOR_KEY);
+        } else {
+            store_update.set_ser(DBCol::ColBlockMisc, STORE_VALIDATOR_CURSOR_KEY, &cursor)?;
+            self.cursor = Some(cursor);
+        }
+        store_update.commit()?;
+        Ok(finished)
+    }
+
+    /// Applies the safe repairs of the errors found so far in a single store update.
+    /// Returns the number of applied repairs.
+    pub fn apply_safe_repairs(&self) -> Result<usize, StoreValidatorError> {
+        let mut store_update = self.store.store_update();
+        let mut applied = 0;
+        for error in self.errors.iter().filter(|error| error.repair.is_safe()) {
+            error.repair.apply(&self.store, &mut store_update)?;
+            applied += 1;
+        }
+        store_update.commit()?;
+        Ok(applied)
+    }
+
+    fn final_checks(&mut self) {
         // Final checks
         // There is no more than one Block which Height is lower than Tail and not equal to Genesis
         if let Err(e) = validate::block_height_cmp_tail_final(self) {
@@ -377,6 +569,9 @@ impl StoreValidator {
         if let Err(_) = validate::gc_col_count_final(self) {
             // TODO #2861
         }
+        if !self.check_refcounts {
+            return;
+        }
         // Check that all refs are counted
         if let Err(e) = validate::tx_refcount_final(self) {
             self.process_error(e, "TX_REFCOUNT", DBCol::ColTransactions)
@@ -402,10 +597,26 @@ impl StoreValidator {
             self.process_error(e, key, col);
         }
     }
+
+    /// Like `check`, but suggests `repair` if the check fails.
+    fn check_or_repair<K: std::fmt::Debug, V>(
+        &mut self,
+        f: &dyn Fn(&mut StoreValidator, &K, &V) -> Result<(), StoreValidatorError>,
+        key: &K,
+        value: &V,
+        col: DBCol,
+        repair: &dyn Fn() -> RepairPlan,
+    ) {
+        self.tests += 1;
+        if let Err(e) = f(self, key, value) {
+            self.process_error_with_repair(e, key, col, repair());
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use near_primitives::shard_layout::{get_block_shard_uid, ShardUId};
     use near_store::test_utils::create_test_store;
 
     use crate::test_utils::KeyValueRuntime;
@@ -480,6 +691,99 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validate_step() {
+        let (chain, mut sv) = init();
+        let store = chain.store().owned_store();
+        let mut full_sv =
+            StoreValidator::new(None, sv.config.clone(), sv.runtime_adapter.clone(), store.clone());
+        full_sv.check_refcounts = false;
+        full_sv.validate();
+
+        // Steps over a fresh pass do the same checks as a single validation without the refcount
+        // checks.
+        while !sv.validate_step(1).unwrap() {}
+        assert_eq!(sv.tests_done(), full_sv.tests_done());
+        assert_eq!(sv.num_failed(), full_sv.num_failed());
+        assert!(store.get(DBCol::ColBlockMisc, STORE_VALIDATOR_CURSOR_KEY).unwrap().is_none());
+
+        assert!(!sv.validate_step(1).unwrap());
+        let cursor = store
+            .get_ser::<StoreValidatorCursor>(DBCol::ColBlockMisc, STORE_VALIDATOR_CURSOR_KEY)
+            .unwrap()
+            .unwrap();
+        assert_eq!(cursor.col, DBCol::ColBlock);
+        assert!(cursor.last_key.is_some());
+
+        // After a restart, validation continues from the stored cursor.
+        let mut sv =
+            StoreValidator::new(None, sv.config.clone(), sv.runtime_adapter.clone(), store.clone());
+        while !sv.validate_step(1).unwrap() {}
+        assert!(!sv.full_pass);
+        assert!(sv.tests_done() < full_sv.tests_done());
+        assert!(sv.validate_step(u64::MAX).unwrap());
+        assert!(sv.full_pass);
+    }
+
+    #[test]
+    fn test_repair_orphaned_chunk_extra() {
+        let (chain, mut sv) = init();
+        let store = chain.store().owned_store();
+        let key = get_block_shard_uid(&CryptoHash::default(), &ShardUId::single_shard());
+        let mut store_update = store.store_update();
+        store_update
+            .set_ser(
+                DBCol::ColChunkExtra,
+                &key,
+                &ChunkExtra::new_with_only_state_root(&CryptoHash::default()),
+            )
+            .unwrap();
+        store_update.commit().unwrap();
+
+        assert!(sv.validate_col(DBCol::ColChunkExtra).is_ok());
+        assert_eq!(sv.num_failed(), 1);
+        assert_eq!(
+            sv.errors[0].repair,
+            RepairPlan::DeleteRecord { col: DBCol::ColChunkExtra, key: key.clone() }
+        );
+        assert_eq!(sv.apply_safe_repairs().unwrap(), 1);
+        assert!(store.get(DBCol::ColChunkExtra, &key).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_refcount_repair_needs_snapshot() {
+        let (chain, mut sv) = init();
+        let store = chain.store().owned_store();
+        let key = [1u8; 32];
+        let mut store_update = store.store_update();
+        store_update.update_refcount(DBCol::ColTransactions, &key, &[1], 2);
+        store_update.commit().unwrap();
+        let expected_repair = RepairPlan::SetRefcount {
+            col: DBCol::ColTransactions,
+            key: key.to_vec(),
+            found: 2,
+            expected: 0,
+        };
+
+        sv.validate();
+        assert!(sv.errors.iter().all(|error| error.repair != expected_repair));
+        assert!(sv
+            .errors
+            .iter()
+            .any(|error| error.col == "ColTransactions" && !error.repair.is_safe()));
+
+        sv.errors.clear();
+        sv.validate_snapshot();
+        assert!(sv.errors.iter().any(|error| error.repair == expected_repair));
+        assert!(!sv.fixed_snapshot);
+
+        // Incremental passes don't check refcounts.
+        let mut sv =
+            StoreValidator::new(None, sv.config.clone(), sv.runtime_adapter.clone(), store.clone());
+        assert!(sv.validate_step(u64::MAX).unwrap());
+        assert!(sv.errors.iter().all(|error| error.col != "ColTransactions"));
+    }
+
     #[test]
     fn test_validation_failed() {
         let (_chain, mut sv) = init();
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use near_primitives::serialize::base64_format;
+use near_store::{DBCol, Store, StoreUpdate};
+
+use crate::store_validator::validate::StoreValidatorError;
+
+/// Machine-readable description of how to fix an error found by the store validator.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RepairPlan {
+    /// Delete a record nothing refers to, e.g. `ColChunkExtra` of a Block which is not stored.
+    DeleteRecord {
+        col: DBCol,
+        #[serde(with = "base64_format")]
+        key: Vec<u8>,
+    },
+    /// Set the refcount of a record to the number of references found by the validator.
+    /// The record is deleted if nothing refers to it.
+    SetRefcount {
+        col: DBCol,
+        #[serde(with = "base64_format")]
+        key: Vec<u8>,
+        found: i64,
+        expected: u64,
+    },
+    /// The error can't be fixed automatically.
+    Manual { hint: String },
+}
+
+impl RepairPlan {
+    /// Suggests what to do about an error which has no automatic repair, based on its class.
+    pub(crate) fn manual(err: &StoreValidatorError) -> Self {
+        let hint = match err {
+            StoreValidatorError::IOError(_) | StoreValidatorError::DBCorruption(_) => {
+                "record can't be decoded, restore the database from a backup or a snapshot"
+            }
+            StoreValidatorError::InvalidData { .. } => {
+                "stored data is invalid, restore the database from a backup or a snapshot"
+            }
+            StoreValidatorError::DBNotFound { .. } => {
+                "referenced data is missing, restore the database from a snapshot or resync the node"
+            }
+            StoreValidatorError::Discrepancy { .. }
+            | StoreValidatorError::ValidationFailed { .. } => {
+                "inspect the records with state-viewer before changing anything"
+            }
+        };
+        RepairPlan::Manual { hint: hint.to_string() }
+    }
+
+    /// Whether the repair can be applied automatically without losing data which is still in use.
+    pub fn is_safe(&self) -> bool {
+        !matches!(self, RepairPlan::Manual { .. })
+    }
+
+    /// Adds the changes of the repair to `store_update`. Manual repairs are skipped.
+    pub fn apply(&self, store: &Store, store_update: &mut StoreUpdate) -> std::io::Result<()> {
+        match self {
+            RepairPlan::DeleteRecord { col, key } => store_update.delete(*col, key),
+            RepairPlan::SetRefcount { col, key, found, expected } => {
+                // Records with non-positive refcount have no value, those can't be rebuilt.
+                if let Some(value) = store.get(*col, key)? {
+                    store_update.update_refcount(*col, key, &value, *expected as i64 - found);
+                }
+            }
+            RepairPlan::Manual { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_store::test_utils::create_test_store;
+    use near_store::DBCol;
+
+    use super::RepairPlan;
+
+    #[test]
+    fn test_set_refcount() {
+        let store = create_test_store();
+        let mut store_update = store.store_update();
+        store_update.update_refcount(DBCol::ColTransactions, &[1], &[1], 3);
+        store_update.update_refcount(DBCol::ColTransactions, &[2], &[2], 1);
+        store_update.commit().unwrap();
+
+        let mut store_update = store.store_update();
+        let plans = [
+            RepairPlan::SetRefcount {
+                col: DBCol::ColTransactions,
+                key: vec![1],
+                found: 3,
+                expected: 1,
+            },
+            RepairPlan::SetRefcount {
+                col: DBCol::ColTransactions,
+                key: vec![2],
+                found: 1,
+                expected: 0,
+            },
+        ];
+        for plan in plans.iter() {
+            assert!(plan.is_safe());
+            plan.apply(&store, &mut store_update).unwrap();
+        }
+        store_update.commit().unwrap();
+
+        let records: Vec<_> = store.iter_without_rc_logic(DBCol::ColTransactions).collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(near_store::decode_value_with_rc(&records[0].1), (Some(&[1u8][..]), 1));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let plan = RepairPlan::DeleteRecord { col: DBCol::ColChunkExtra, key: vec![1, 2, 3] };
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(json, r#"{"action":"delete_record","col":"ColChunkExtra","key":"AQID"}"#);
+        assert_eq!(serde_json::from_str::<RepairPlan>(&json).unwrap(), plan);
+    }
+}
@@ -10,6 +10,7 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::sharding::{ChunkHash, ShardChunk, StateSyncInfo};
 use near_primitives::syncing::{
     get_num_state_parts, ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey,
+    StatePartStatus,
 };
 use near_primitives::transaction::{ExecutionOutcomeWithIdAndProof, SignedTransaction};
 use near_primitives::types::chunk_extra::ChunkExtra;
@@ -18,8 +19,8 @@ use near_primitives::utils::{get_block_shard_id, index_to_bytes};
 use near_store::{
     ColBlock, ColBlockHeader, ColBlockHeight, ColBlockInfo, ColBlockMisc, ColBlockPerHeight,
     ColChunkExtra, ColChunkHashesByHeight, ColChunks, ColHeaderHashesByHeight, ColOutcomeIds,
-    ColStateHeaders, ColTransactionResult, DBCol, TrieChanges, TrieIterator, CHUNK_TAIL_KEY,
-    FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY, NUM_COLS, SHOULD_COL_GC, TAIL_KEY,
+    ColStateHeaders, ColStateParts, ColTransactionResult, DBCol, TrieChanges, TrieIterator,
+    CHUNK_TAIL_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY, NUM_COLS, SHOULD_COL_GC, TAIL_KEY,
 };
 
 use crate::StoreValidator;
@@ -887,6 +888,16 @@ pub(crate) fn state_part_header_exists(
     Ok(())
 }
 
+pub(crate) fn state_part_status_part_exists(
+    sv: &mut StoreValidator,
+    key: &StatePartKey,
+    _status: &StatePartStatus,
+) -> Result<(), StoreValidatorError> {
+    let key = unwrap_or_err!(key.try_to_vec(), "Can't serialize StatePartKey");
+    unwrap_or_err_db!(sv.store.get(ColStateParts, &key), "Can't get State Part from DB");
+    Ok(())
+}
+
 // Final checks
 
 pub(crate) fn block_height_cmp_tail_final(
@@ -962,16 +962,16 @@ impl RuntimeAdapter for KeyValueRuntime {
         _num_parts: u64,
         data: &[u8],
         _epoch_id: &EpochId,
-    ) -> Result<(), Error> {
+    ) -> Result<StoreUpdate, Error> {
         if part_id != 0 {
-            return Ok(());
+            return Ok(self.store.store_update());
         }
         let state = KVState::try_from_slice(data).unwrap();
         self.state.write().unwrap().insert(state_root.clone(), state.clone());
         let data = state.try_to_vec()?;
         let state_size = data.len() as u64;
         self.state_size.write().unwrap().insert(state_root.clone(), state_size);
-        Ok(())
+        Ok(self.store.store_update())
     }
 
     fn get_state_root_node(
@@ -1,17 +1,21 @@
 use std::sync::Arc;
 
 use crate::chain::Chain;
+use crate::store::ChainStoreAccess;
 use crate::test_utils::KeyValueRuntime;
 use crate::types::{ChainGenesis, Tip};
 use crate::DoomslugThresholdMode;
 use near_crypto::KeyType;
 use near_primitives::block::Block;
+use near_primitives::hash::hash;
 use near_primitives::merkle::PartialMerkleTree;
 use near_primitives::shard_layout::ShardUId;
-use near_primitives::types::{NumBlocks, NumShards, StateRoot};
+use near_primitives::types::chunk_extra::ChunkExtra;
+use near_primitives::types::{BlockHeight, NumBlocks, NumShards, StateRoot};
 use near_primitives::validator_signer::InMemoryValidatorSigner;
+use near_store::flat_state::MAX_FORK_DELTAS;
 use near_store::test_utils::{create_test_store, gen_changes};
-use near_store::{ShardTries, StoreUpdate, Trie, WrappedTrieChanges};
+use near_store::{ColFlatStateDeltas, ShardTries, StoreUpdate, Trie, WrappedTrieChanges};
 use rand::Rng;
 
 fn get_chain(num_shards: NumShards) -> Chain {
@@ -547,3 +551,59 @@ fn test_gc_star_small() {
 fn test_gc_star_large() {
     test_gc_star_common(20)
 }
+
+#[test]
+fn test_clear_flat_state_deltas() {
+    let mut chain = get_chain(1);
+    let tries = chain.runtime_adapter.get_tries();
+    let shard_uid = ShardUId { version: 0, shard_id: 0 };
+    let signer = Arc::new(InMemoryValidatorSigner::from_seed(
+        "test1".parse().unwrap(),
+        KeyType::ED25519,
+        "test1",
+    ));
+    let delta_key =
+        |state_root: &StateRoot| [&shard_uid.to_bytes()[..], state_root.as_ref()].concat();
+
+    let mut prev_block = chain.get_block_by_height(0).unwrap().clone();
+    let mut state_roots = vec![];
+    for height in 1..=(MAX_FORK_DELTAS as BlockHeight + 10) {
+        let block = Block::empty(&prev_block, &*signer);
+        let state_root = hash(&height.to_le_bytes());
+        let mut store_update = chain.mut_store().store_update();
+        if height == 1 {
+            store_update.save_block_merkle_tree(*prev_block.hash(), PartialMerkleTree::default());
+        }
+        store_update.save_block(block.clone());
+        store_update.inc_block_refcount(block.header().prev_hash()).unwrap();
+        store_update.save_block_header(block.header().clone()).unwrap();
+        let tip = Tip::from_header(block.header());
+        store_update.save_head(&tip).unwrap();
+        store_update.save_final_head(&tip).unwrap();
+        store_update.save_chunk_extra(
+            block.hash(),
+            &shard_uid,
+            ChunkExtra::new_with_only_state_root(&state_root),
+        );
+        let mut deltas_update = tries.get_store().store_update();
+        deltas_update.set(ColFlatStateDeltas, &delta_key(&state_root), &[]);
+        store_update.merge(deltas_update);
+        store_update.commit().unwrap();
+        state_roots.push((height, state_root));
+        prev_block = block;
+    }
+
+    // Deltas of blocks which are not more than `MAX_FORK_DELTAS` heights below the final head are
+    // kept, the flat state may still be moved across them.
+    chain.clear_flat_state_deltas(tries.clone(), 100).unwrap();
+    let final_height = chain.store().final_head().unwrap().height;
+    let store = tries.get_store();
+    for (height, state_root) in state_roots {
+        let delta = store.get(ColFlatStateDeltas, &delta_key(&state_root)).unwrap();
+        assert_eq!(delta.is_some(), height + MAX_FORK_DELTAS as BlockHeight >= final_height);
+    }
+    assert_eq!(
+        chain.store().flat_state_deltas_tail().unwrap(),
+        final_height - MAX_FORK_DELTAS as BlockHeight - 1
+    );
+}
@@ -685,6 +685,24 @@ pub trait RuntimeAdapter: Send + Sync {
         num_parts: u64,
     ) -> Result<Vec<u8>, Error>;
 
+    /// Get several parts of the state from given state root, in the order of `part_ids`.
+    /// Implementations may compute the parts in parallel.
+    fn obtain_state_parts(
+        &self,
+        shard_id: ShardId,
+        block_hash: &CryptoHash,
+        state_root: &StateRoot,
+        part_ids: &[u64],
+        num_parts: u64,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        part_ids
+            .iter()
+            .map(|&part_id| {
+                self.obtain_state_part(shard_id, block_hash, state_root, part_id, num_parts)
+            })
+            .collect()
+    }
+
     /// Validate state part that expected to be given state root with provided data.
     /// Returns false if the resulting part doesn't match the expected one.
     fn validate_state_part(
@@ -711,6 +729,8 @@ pub trait RuntimeAdapter: Send + Sync {
     ) -> Result<HashMap<ShardUId, StateRoot>, Error>;
 
     /// Should be executed after accepting all the parts to set up a new state.
+    /// Returns the store update writing the part, which the caller has to commit.
+    /// Different parts of the same state can be applied in parallel.
     fn apply_state_part(
         &self,
         shard_id: ShardId,
@@ -719,7 +739,7 @@ pub trait RuntimeAdapter: Send + Sync {
         num_parts: u64,
         part: &[u8],
         epoch_id: &EpochId,
-    ) -> Result<(), Error>;
+    ) -> Result<StoreUpdate, Error>;
 
     /// Returns StateRootNode of a state.
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
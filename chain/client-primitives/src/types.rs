@@ -21,8 +21,8 @@ use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
     BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
     FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
-    QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView, StateChangesRequestView,
-    StateChangesView,
+    QueryRequest, QueryResponse, ReceiptView, StateChangeInBlockView, StateChangesKindsView,
+    StateChangesRequestView, StateChangesView, StateKeyView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 
@@ -84,6 +84,8 @@ pub struct DownloadStatus {
     pub done: bool,
     pub state_requests_count: u64,
     pub last_target: Option<AccountOrPeerIdOrHash>,
+    /// State part received from a peer which is not validated yet.
+    pub received_part: Option<Vec<u8>>,
 }
 
 impl Clone for DownloadStatus {
@@ -96,6 +98,7 @@ impl Clone for DownloadStatus {
             done: self.done,
             state_requests_count: self.state_requests_count,
             last_target: self.last_target.clone(),
+            received_part: self.received_part.clone(),
         }
     }
 }
@@ -606,6 +609,31 @@ impl Message for GetStateChangesWithCauseInBlock {
     type Result = Result<StateChangesView, GetStateChangesError>;
 }
 
+/// Maximum number of changes returned by a single `GetStateChangesHistory` request.
+pub const MAX_STATE_CHANGES_HISTORY_LIMIT: usize = 1000;
+/// Maximum number of heights a single `GetStateChangesHistory` request looks at. Longer ranges
+/// are continued from `GetStateChangesHistoryResponse::next_from_height`.
+pub const MAX_STATE_CHANGES_HISTORY_RANGE: BlockHeight = 1_000_000;
+
+pub struct GetStateChangesHistory {
+    pub key: StateKeyView,
+    pub from_height: BlockHeight,
+    /// Defaults to the height of the chain head.
+    pub to_height: Option<BlockHeight>,
+    /// Capped at `MAX_STATE_CHANGES_HISTORY_LIMIT`.
+    pub limit: usize,
+}
+
+pub struct GetStateChangesHistoryResponse {
+    pub changes: Vec<StateChangeInBlockView>,
+    /// Height to continue from if there may be more changes up to the requested `to_height`.
+    pub next_from_height: Option<BlockHeight>,
+}
+
+impl Message for GetStateChangesHistory {
+    type Result = Result<GetStateChangesHistoryResponse, GetStateChangesError>;
+}
+
 pub struct GetExecutionOutcome {
     pub id: TransactionOrReceiptId,
 }
@@ -782,3 +810,28 @@ impl From<near_chain_primitives::Error> for GetProtocolConfigError {
         }
     }
 }
+
+/// Creates an online checkpoint of the node database in the given subdirectory of the
+/// checkpoints directory.
+pub struct CreateCheckpoint {
+    pub path: std::path::PathBuf,
+}
+
+#[derive(Debug)]
+pub struct CreateCheckpointResponse {
+    /// Directory the checkpoint was written to.
+    pub path: std::path::PathBuf,
+    pub db_version: near_primitives::version::DbVersion,
+    pub head_height: BlockHeight,
+    pub head_hash: CryptoHash,
+}
+
+impl Message for CreateCheckpoint {
+    type Result = Result<CreateCheckpointResponse, CreateCheckpointError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CreateCheckpointError {
+    #[error("Checkpoint cannot be created: {error_message}")]
+    CheckpointError { error_message: String },
+}
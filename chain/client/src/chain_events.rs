@@ -0,0 +1,371 @@
+//! Events of the chain published while the client processes blocks.
+//!
+//! Subscribers, such as the WebSocket endpoint of the JSON-RPC server, receive final blocks
+//! with their state changes and final outcomes of the transactions they watch as soon as the
+//! client learns about them, instead of polling the view client. The events are looked up by
+//! [`ChainEventsActor`] in a thread of its own, the client only tells it about new final blocks.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use actix::{Actor, Addr, Arbiter, ArbiterHandle, Context, Handler, Message};
+use log::{debug, error};
+use tokio::sync::broadcast;
+
+use near_chain::{Chain, ChainGenesis, DoomslugThresholdMode, Error, RuntimeAdapter};
+use near_performance_metrics_macros::perf;
+use near_primitives::block::BlockHeader;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
+use near_primitives::views::{
+    BlockView, FinalExecutionOutcomeView, FinalExecutionStatus, StateChangesView,
+};
+
+/// Number of events kept for subscribers which fall behind. Subscribers which fall further
+/// behind miss events and are told how many.
+const CHAIN_EVENTS_CAPACITY: usize = 1024;
+
+/// Maximum number of transactions watched by all subscribers together. Each of them is looked
+/// up after every final block.
+pub const MAX_WATCHED_TRANSACTIONS: usize = 10_000;
+
+/// Transactions without a final outcome after this time stop being watched. Their hashes may
+/// be wrong, or the transactions may never make it into a block.
+pub const WATCHED_TRANSACTION_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug)]
+pub enum ChainEvent {
+    /// A block became final. Blocks are published in the order of heights.
+    FinalBlock { block: BlockView, state_changes: StateChangesView },
+    /// All outcomes of a watched transaction are in final blocks. The transaction stops being
+    /// watched.
+    FinalTransaction(FinalExecutionOutcomeView),
+    /// A watched transaction got no final outcome within `WATCHED_TRANSACTION_TTL` and stopped
+    /// being watched.
+    TransactionExpired(CryptoHash),
+}
+
+/// Identifies a single `ChainEvents::watch_transaction` call.
+pub type WatchId = u64;
+
+struct WatchedTransaction {
+    /// Watches which haven't been undone yet.
+    watches: Vec<WatchId>,
+    since: Instant,
+}
+
+/// Channel of chain events shared by the publisher and the subscribers.
+#[derive(Clone)]
+pub struct ChainEvents {
+    sender: broadcast::Sender<Arc<ChainEvent>>,
+    /// Transactions whose final outcomes are published.
+    watched_transactions: Arc<Mutex<HashMap<CryptoHash, WatchedTransaction>>>,
+    next_watch_id: Arc<AtomicU64>,
+}
+
+impl ChainEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHAIN_EVENTS_CAPACITY);
+        Self { sender, watched_transactions: Default::default(), next_watch_id: Default::default() }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ChainEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Starts publishing the final outcome of the transaction. Returns `None` if
+    /// `MAX_WATCHED_TRANSACTIONS` other transactions are watched already.
+    pub fn watch_transaction(&self, tx_hash: CryptoHash) -> Option<WatchId> {
+        let mut watched_transactions = self.watched_transactions.lock().unwrap();
+        if !watched_transactions.contains_key(&tx_hash)
+            && watched_transactions.len() >= MAX_WATCHED_TRANSACTIONS
+        {
+            return None;
+        }
+        let watch_id = self.next_watch_id.fetch_add(1, Ordering::Relaxed);
+        watched_transactions
+            .entry(tx_hash)
+            .or_insert_with(|| WatchedTransaction { watches: vec![], since: Instant::now() })
+            .watches
+            .push(watch_id);
+        Some(watch_id)
+    }
+
+    /// Undoes a `watch_transaction` call. Does nothing if the transaction stopped being watched
+    /// since, because it became final or expired.
+    pub fn unwatch_transaction(&self, tx_hash: &CryptoHash, watch_id: WatchId) {
+        let mut watched_transactions = self.watched_transactions.lock().unwrap();
+        if let Some(watched) = watched_transactions.get_mut(tx_hash) {
+            watched.watches.retain(|id| *id != watch_id);
+            if watched.watches.is_empty() {
+                watched_transactions.remove(tx_hash);
+            }
+        }
+    }
+
+    fn watched_transactions(&self) -> Vec<CryptoHash> {
+        self.watched_transactions.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn remove_watched_transaction(&self, tx_hash: &CryptoHash) {
+        self.watched_transactions.lock().unwrap().remove(tx_hash);
+    }
+
+    /// Stops watching the transactions watched for longer than `ttl`, returning their hashes.
+    fn expire_watched_transactions(&self, now: Instant, ttl: Duration) -> Vec<CryptoHash> {
+        let mut watched_transactions = self.watched_transactions.lock().unwrap();
+        let expired: Vec<_> = watched_transactions
+            .iter()
+            .filter(|(_, watched)| now.saturating_duration_since(watched.since) >= ttl)
+            .map(|(tx_hash, _)| *tx_hash)
+            .collect();
+        for tx_hash in expired.iter() {
+            watched_transactions.remove(tx_hash);
+        }
+        expired
+    }
+
+    fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    fn send(&self, event: ChainEvent) {
+        // Fails only if there are no subscribers, nobody misses the event then.
+        let _ = self.sender.send(Arc::new(event));
+    }
+}
+
+impl Default for ChainEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Publishes the events of the chain.
+pub(crate) struct ChainEventsPublisher {
+    events: ChainEvents,
+    /// Height of the last final block the events were published for.
+    last_final_height: BlockHeight,
+}
+
+impl ChainEventsPublisher {
+    pub fn new(events: ChainEvents, chain: &Chain) -> Result<Self, Error> {
+        Ok(Self { events, last_final_height: chain.final_head()?.height })
+    }
+
+    /// Publishes the blocks between the last published final block and `last_final_hash`, and
+    /// the outcomes of watched transactions which became final.
+    pub fn on_new_final_block(
+        &mut self,
+        chain: &mut Chain,
+        runtime_adapter: &dyn RuntimeAdapter,
+        last_final_hash: &CryptoHash,
+    ) -> Result<(), Error> {
+        if last_final_hash == &CryptoHash::default() {
+            return Ok(());
+        }
+        let final_header = chain.get_block_header(last_final_hash)?.clone();
+        let final_height = final_header.height();
+        if final_height <= self.last_final_height {
+            return Ok(());
+        }
+        let last_final_height = std::mem::replace(&mut self.last_final_height, final_height);
+        if !self.events.has_subscribers() {
+            return Ok(());
+        }
+
+        // Final blocks are on the canonical chain, so they are found by walking back from the
+        // new final block.
+        let mut headers: Vec<BlockHeader> = vec![];
+        let mut header = final_header;
+        while header.height() > last_final_height {
+            let prev_header = chain.get_block_header(header.prev_hash())?.clone();
+            headers.push(header);
+            header = prev_header;
+        }
+        for header in headers.into_iter().rev() {
+            let block = chain.get_block(header.hash())?.clone();
+            let block_author =
+                runtime_adapter.get_block_producer(header.epoch_id(), header.height())?;
+            let state_changes = chain
+                .store()
+                .get_state_changes_with_cause_in_block(header.hash())?
+                .into_iter()
+                .map(Into::into)
+                .collect();
+            self.events.send(ChainEvent::FinalBlock {
+                block: BlockView::from_author_block(block_author, block),
+                state_changes,
+            });
+        }
+
+        for tx_hash in self.events.watched_transactions() {
+            // The transaction may be unknown yet, or belong to a shard we don't track.
+            let outcome = match chain.get_final_transaction_result(&tx_hash) {
+                Ok(outcome) => outcome,
+                Err(_) => continue,
+            };
+            if is_final_outcome(chain, &outcome, final_height)? {
+                debug!(target: "client", "Publishing final outcome of transaction {}", tx_hash);
+                self.events.remove_watched_transaction(&tx_hash);
+                self.events.send(ChainEvent::FinalTransaction(outcome));
+            }
+        }
+        for tx_hash in
+            self.events.expire_watched_transactions(Instant::now(), WATCHED_TRANSACTION_TTL)
+        {
+            debug!(target: "client", "Transaction {} has no final outcome, not watching it anymore", tx_hash);
+            self.events.send(ChainEvent::TransactionExpired(tx_hash));
+        }
+        Ok(())
+    }
+}
+
+/// Whether the transaction is finished and all its outcomes are in final blocks.
+fn is_final_outcome(
+    chain: &mut Chain,
+    outcome: &FinalExecutionOutcomeView,
+    final_height: BlockHeight,
+) -> Result<bool, Error> {
+    if let FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started = outcome.status {
+        return Ok(false);
+    }
+    for outcome_with_id in
+        std::iter::once(&outcome.transaction_outcome).chain(outcome.receipts_outcome.iter())
+    {
+        if chain.get_block_header(&outcome_with_id.block_hash)?.height() > final_height {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Sent by the client when the last final block changes.
+pub struct NewFinalBlock(pub CryptoHash);
+
+impl Message for NewFinalBlock {
+    type Result = ();
+}
+
+/// Looks up the events of the chain in a thread of its own, with a chain of its own like the
+/// view client, so that publishing them doesn't hold up block processing.
+pub struct ChainEventsActor {
+    chain: Chain,
+    runtime_adapter: Arc<dyn RuntimeAdapter>,
+    publisher: ChainEventsPublisher,
+}
+
+impl Actor for ChainEventsActor {
+    type Context = Context<Self>;
+}
+
+impl Handler<NewFinalBlock> for ChainEventsActor {
+    type Result = ();
+
+    #[perf]
+    fn handle(&mut self, msg: NewFinalBlock, _ctx: &mut Self::Context) {
+        if let Err(err) =
+            self.publisher.on_new_final_block(&mut self.chain, &*self.runtime_adapter, &msg.0)
+        {
+            error!(target: "client", "Failed to publish chain events: {}", err);
+        }
+    }
+}
+
+/// Starts the chain events actor in a new arbiter (thread).
+pub fn start_chain_events_actor(
+    chain_genesis: ChainGenesis,
+    runtime_adapter: Arc<dyn RuntimeAdapter>,
+    events: ChainEvents,
+) -> (Addr<ChainEventsActor>, ArbiterHandle) {
+    let arbiter = Arbiter::new().handle();
+    let addr = ChainEventsActor::start_in_arbiter(&arbiter, move |_ctx| {
+        let chain = Chain::new_for_view_client(
+            runtime_adapter.clone(),
+            &chain_genesis,
+            DoomslugThresholdMode::TwoThirds,
+        )
+        .unwrap();
+        let publisher = ChainEventsPublisher::new(events, &chain).unwrap();
+        ChainEventsActor { chain, runtime_adapter, publisher }
+    });
+    (addr, arbiter)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use near_chain::ChainGenesis;
+    use near_primitives::hash::hash;
+
+    use super::{ChainEvent, ChainEvents, ChainEventsPublisher, MAX_WATCHED_TRANSACTIONS};
+    use crate::test_utils::TestEnv;
+
+    #[test]
+    fn test_watch_transaction() {
+        let events = ChainEvents::new();
+        let tx_hash = hash(&[1]);
+        let first = events.watch_transaction(tx_hash).unwrap();
+        let second = events.watch_transaction(tx_hash).unwrap();
+        events.unwatch_transaction(&tx_hash, first);
+        assert_eq!(events.watched_transactions(), vec![tx_hash]);
+        // Undoing a watch twice is a no-op.
+        events.unwatch_transaction(&tx_hash, first);
+        assert_eq!(events.watched_transactions(), vec![tx_hash]);
+        events.unwatch_transaction(&tx_hash, second);
+        assert!(events.watched_transactions().is_empty());
+
+        // A watch undone after the transaction expired doesn't affect later watches.
+        let expired = events.watch_transaction(tx_hash).unwrap();
+        let now = Instant::now();
+        assert_eq!(events.expire_watched_transactions(now, Duration::from_secs(60)), vec![]);
+        assert_eq!(events.expire_watched_transactions(now, Duration::from_secs(0)), vec![tx_hash]);
+        let watch = events.watch_transaction(tx_hash).unwrap();
+        events.unwatch_transaction(&tx_hash, expired);
+        assert_eq!(events.watched_transactions(), vec![tx_hash]);
+        events.unwatch_transaction(&tx_hash, watch);
+        assert!(events.watched_transactions().is_empty());
+    }
+
+    #[test]
+    fn test_watched_transactions_limit() {
+        let events = ChainEvents::new();
+        for i in 0..MAX_WATCHED_TRANSACTIONS {
+            events.watch_transaction(hash(&i.to_le_bytes())).unwrap();
+        }
+        assert_eq!(events.watch_transaction(hash(b"one too many")), None);
+        // Transactions watched already can be watched again.
+        assert!(events.watch_transaction(hash(&0usize.to_le_bytes())).is_some());
+    }
+
+    #[test]
+    fn test_final_blocks() {
+        let mut env = TestEnv::builder(ChainGenesis::test()).build();
+        let events = ChainEvents::new();
+        let mut receiver = events.subscribe();
+        let mut publisher = ChainEventsPublisher::new(events, &env.clients[0].chain).unwrap();
+        for height in 1..10 {
+            env.produce_block(0, height);
+            let client = &mut env.clients[0];
+            let last_final_hash = client.chain.head_header().unwrap().last_final_block().clone();
+            let runtime_adapter = client.runtime_adapter.clone();
+            publisher
+                .on_new_final_block(&mut client.chain, &*runtime_adapter, &last_final_hash)
+                .unwrap();
+        }
+
+        let mut heights = vec![];
+        while let Ok(event) = receiver.try_recv() {
+            match Arc::as_ref(&event) {
+                ChainEvent::FinalBlock { block, .. } => heights.push(block.header.height),
+                _ => panic!("no transactions are watched"),
+            }
+        }
+        let final_height = env.clients[0].chain.final_head().unwrap().height;
+        assert!(final_height > 1);
+        assert_eq!(heights, (1..=final_height).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,57 @@
+//! Actor creating online checkpoints of the node database for the admin RPC.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use actix::{Actor, Addr, Handler, SyncArbiter, SyncContext};
+use log::info;
+
+use near_client_primitives::types::{
+    CreateCheckpoint, CreateCheckpointError, CreateCheckpointResponse,
+};
+use near_performance_metrics_macros::perf;
+use near_store::checkpoint::{create_checkpoint, resolve_checkpoint_path};
+use near_store::Store;
+
+/// Writes checkpoints in a thread of its own, so that copying a large database doesn't hold
+/// up the view client threads serving other requests.
+pub struct CheckpointActor {
+    store: Arc<Store>,
+    /// Checkpoints are only written to subdirectories of this directory.
+    checkpoints_dir: PathBuf,
+}
+
+impl Actor for CheckpointActor {
+    type Context = SyncContext<Self>;
+}
+
+impl Handler<CreateCheckpoint> for CheckpointActor {
+    type Result = Result<CreateCheckpointResponse, CreateCheckpointError>;
+
+    #[perf]
+    fn handle(&mut self, msg: CreateCheckpoint, _ctx: &mut Self::Context) -> Self::Result {
+        let map_err = |err: near_store::checkpoint::CheckpointError| {
+            CreateCheckpointError::CheckpointError { error_message: err.to_string() }
+        };
+        let path = resolve_checkpoint_path(&self.checkpoints_dir, &msg.path).map_err(map_err)?;
+        info!(target: "client", "Creating database checkpoint in {}", path.display());
+        let metadata = create_checkpoint(&self.store, &path).map_err(map_err)?;
+        Ok(CreateCheckpointResponse {
+            path,
+            db_version: metadata.db_version,
+            head_height: metadata.head_height,
+            head_hash: metadata.head_hash,
+        })
+    }
+}
+
+/// Starts the checkpoint actor in a new arbiter (thread).
+pub fn start_checkpoint_actor(
+    store: Arc<Store>,
+    checkpoints_dir: PathBuf,
+) -> Addr<CheckpointActor> {
+    SyncArbiter::start(1, move || CheckpointActor {
+        store: store.clone(),
+        checkpoints_dir: checkpoints_dir.clone(),
+    })
+}
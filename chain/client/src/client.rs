@@ -6,6 +6,7 @@ use std::iter;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use actix::Addr;
 use cached::{Cached, SizedCache};
 use log::{debug, error, info, warn};
 use near_primitives::time::Clock;
@@ -41,6 +42,7 @@ use near_primitives::unwrap_or_return;
 use near_primitives::utils::{to_timestamp, MaybeValidated};
 use near_primitives::validator_signer::ValidatorSigner;
 
+use crate::chain_events::{ChainEventsActor, NewFinalBlock};
 use crate::chunks_delay_tracker::ChunksDelayTracker;
 use crate::sync::{BlockSync, EpochSync, HeaderSync, StateSync, StateSyncResult};
 use crate::{metrics, SyncStatus};
@@ -104,6 +106,8 @@ pub struct Client {
     last_time_head_progress_made: Instant,
     /// Keeps track of when the latest blocks and chunks were received.
     chunks_delay_tracker: ChunksDelayTracker,
+    /// Publishes final blocks and transactions to subscribers, if any.
+    chain_events: Option<Addr<ChainEventsActor>>,
 }
 
 impl Client {
@@ -154,7 +158,11 @@ impl Client {
         );
         let block_sync =
             BlockSync::new(network_adapter.clone(), config.block_fetch_horizon, config.archive);
-        let state_sync = StateSync::new(network_adapter.clone(), config.state_sync_timeout);
+        let state_sync = StateSync::new(
+            network_adapter.clone(),
+            config.state_sync_timeout,
+            config.state_sync_dump_dir.clone(),
+        );
         let num_block_producer_seats = config.num_block_producer_seats as usize;
         let data_parts = runtime_adapter.num_data_parts();
         let parity_parts = runtime_adapter.num_total_parts() - data_parts;
@@ -192,9 +200,15 @@ impl Client {
             rebroadcasted_blocks: SizedCache::with_size(NUM_REBROADCAST_BLOCKS),
             last_time_head_progress_made: Clock::instant(),
             chunks_delay_tracker: Default::default(),
+            chain_events: None,
         })
     }
 
+    /// Starts telling the actor publishing events of the chain about new final blocks.
+    pub fn set_chain_events(&mut self, chain_events: Addr<ChainEventsActor>) {
+        self.chain_events = Some(chain_events);
+    }
+
     // Checks if it's been at least `stall_timeout` since the last time the head was updated, or
     // this method was called. If yes, rebroadcasts the current head.
     pub fn check_head_progress_stalled(&mut self, stall_timeout: Duration) -> Result<(), Error> {
@@ -1031,7 +1045,12 @@ impl Client {
                 self.chain.get_block_header(last_final_block).map_or(0, |header| header.height())
             };
             self.chain.blocks_with_missing_chunks.prune_blocks_below_height(last_finalized_height);
-            if !self.config.archive {
+            if let Some(chain_events) = self.chain_events.as_ref() {
+                chain_events.do_send(NewFinalBlock(*last_final_block));
+            }
+            // Archival nodes with split storage run GC too, it moves old data to the cold
+            // database instead of deleting it.
+            if !self.config.archive || self.chain.store().store().cold_store().is_some() {
                 let timer = metrics::GC_TIME.start_timer();
                 if let Err(err) = self
                     .chain
@@ -1042,6 +1061,13 @@ impl Client {
                 };
                 timer.observe_duration();
             }
+            // Flat state deltas are pruned on archival nodes too, which keep the trie changes.
+            if let Err(err) = self.chain.clear_flat_state_deltas(
+                self.runtime_adapter.get_tries(),
+                self.config.gc_blocks_limit,
+            ) {
+                error!(target: "client", "Can't clear old flat state deltas, {:?}", err);
+            }
 
             if self.runtime_adapter.is_next_block_epoch_start(block.hash()).unwrap_or(false) {
                 let next_epoch_protocol_version = unwrap_or_return!(self
@@ -1691,11 +1717,12 @@ impl Client {
                 }
             };
             let state_sync_timeout = self.config.state_sync_timeout;
+            let state_sync_dump_dir = self.config.state_sync_dump_dir.clone();
             let epoch_id = self.chain.get_block(&sync_hash)?.header().epoch_id().clone();
             let (state_sync, new_shard_sync, blocks_catch_up_state) =
                 self.catchup_state_syncs.entry(sync_hash).or_insert_with(|| {
                     (
-                        StateSync::new(network_adapter1, state_sync_timeout),
+                        StateSync::new(network_adapter1, state_sync_timeout, state_sync_dump_dir),
                         new_shard_sync,
                         BlocksCatchUpState::new(sync_hash.clone(), epoch_id),
                     )
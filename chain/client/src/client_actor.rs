@@ -53,6 +53,7 @@ use near_primitives::views::ValidatorInfo;
 use near_store::ColBlock;
 use near_telemetry::TelemetryActor;
 
+use crate::chain_events::ChainEventsActor;
 use crate::client::Client;
 use crate::info::{InfoHelper, ValidatorInfoHelper};
 use crate::sync::{highest_height_peer, StateSync, StateSyncResult};
@@ -70,8 +71,9 @@ use near_client_primitives::types::{
 };
 use near_network_primitives::types::ReasonForBan;
 use near_primitives::block_header::ApprovalType;
-use near_primitives::syncing::StatePartKey;
-use near_store::db::DBCol::ColStateParts;
+use near_primitives::syncing::{StatePartKey, StatePartStatus};
+use near_store::db::DBCol::{ColStateDlInfos, ColStateParts};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 /// Multiplier on `max_block_time` to wait until deciding that chain stalled.
 const STATUS_WAIT_TIME_MULTIPLIER: u64 = 10;
@@ -522,22 +524,12 @@ impl Handler<NetworkClientMessages> for ClientActor {
                                     error!(target: "sync", "State sync received incorrect part_id # {:?} for hash {:?}, potential malicious peer", part_id, hash);
                                     return NetworkClientResponses::NoResponse;
                                 }
-                                if !shard_sync_download.downloads[part_id as usize].done {
-                                    match self
-                                        .client
-                                        .chain
-                                        .set_state_part(shard_id, hash, part_id, num_parts, &data)
-                                    {
-                                        Ok(()) => {
-                                            shard_sync_download.downloads[part_id as usize].done =
-                                                true;
-                                        }
-                                        Err(err) => {
-                                            error!(target: "sync", "State sync set_state_part error, shard = {}, part = {}, hash = {}: {:?}", shard_id, part_id, hash, err);
-                                            shard_sync_download.downloads[part_id as usize].error =
-                                                true;
-                                        }
-                                    }
+                                // The part is validated by the next run of state sync,
+                                // together with the other parts received by then.
+                                let part_download =
+                                    &mut shard_sync_download.downloads[part_id as usize];
+                                if !part_download.done {
+                                    part_download.received_part = Some(data);
                                 }
                             }
                         }
@@ -1531,20 +1523,33 @@ impl SyncJobsActor {
         msg: &ApplyStatePartsRequest,
     ) -> Result<(), near_chain_primitives::error::Error> {
         let store = self.runtime.get_store();
-
-        for part_id in 0..msg.num_parts {
-            let key = StatePartKey(msg.sync_hash, msg.shard_id, part_id).try_to_vec()?;
-            let part = store.get(ColStateParts, &key)?.unwrap();
-
-            self.runtime.apply_state_part(
-                msg.shard_id,
-                &msg.state_root,
-                part_id,
-                msg.num_parts,
-                &part,
-                &msg.epoch_id,
-            )?;
-        }
+        let runtime = &self.runtime;
+
+        // Parts are independent, so they are applied in parallel. Each one is committed together
+        // with its status, so parts applied before state sync got interrupted are skipped.
+        (0..msg.num_parts).into_par_iter().try_for_each(
+            |part_id| -> Result<(), near_chain_primitives::error::Error> {
+                let key = StatePartKey(msg.sync_hash, msg.shard_id, part_id).try_to_vec()?;
+                if store.get_ser(ColStateDlInfos, &key)? == Some(StatePartStatus::Applied) {
+                    return Ok(());
+                }
+                let part = store.get(ColStateParts, &key)?.unwrap();
+
+                let mut store_update = runtime.apply_state_part(
+                    msg.shard_id,
+                    &msg.state_root,
+                    part_id,
+                    msg.num_parts,
+                    &part,
+                    &msg.epoch_id,
+                )?;
+                store_update.set_ser(ColStateDlInfos, &key, &StatePartStatus::Applied)?;
+                Ok(store_update.commit()?)
+            },
+        )?;
+        // The state was written to the trie only, so the flat state has to be rebuilt from it.
+        let shard_uid = self.runtime.shard_id_to_uid(msg.shard_id, &msg.epoch_id)?;
+        self.runtime.get_tries().rebuild_flat_state(shard_uid, &msg.state_root)?;
 
         Ok(())
     }
@@ -1662,11 +1667,12 @@ pub fn start_client(
     network_adapter: Arc<dyn PeerManagerAdapter>,
     validator_signer: Option<Arc<dyn ValidatorSigner>>,
     telemetry_actor: Addr<TelemetryActor>,
+    chain_events: Option<Addr<ChainEventsActor>>,
     #[cfg(feature = "test_features")] adv: Arc<RwLock<AdversarialControls>>,
 ) -> (Addr<ClientActor>, ArbiterHandle) {
     let client_arbiter_handle = Arbiter::current();
     let client_addr = ClientActor::start_in_arbiter(&client_arbiter_handle, move |ctx| {
-        ClientActor::new(
+        let mut client_actor = ClientActor::new(
             client_config,
             chain_genesis,
             runtime_adapter,
@@ -1680,7 +1686,11 @@ pub fn start_client(
             #[cfg(feature = "test_features")]
             adv,
         )
-        .unwrap()
+        .unwrap();
+        if let Some(chain_events) = chain_events {
+            client_actor.client.set_chain_events(chain_events);
+        }
+        client_actor
     });
     (client_addr, client_arbiter_handle)
 }
@@ -1,22 +1,33 @@
 pub use near_client_primitives::types::{
-    Error, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk,
-    GetExecutionOutcome, GetExecutionOutcomeResponse, GetExecutionOutcomesForBlock, GetGasPrice,
-    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetStateChangesWithCauseInBlock, GetValidatorInfo, GetValidatorOrdered,
-    Query, QueryError, Status, StatusResponse, SyncStatus, TxStatus, TxStatusError,
+    CreateCheckpoint, CreateCheckpointError, CreateCheckpointResponse, Error, GetBlock,
+    GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk, GetExecutionOutcome,
+    GetExecutionOutcomeResponse, GetExecutionOutcomesForBlock, GetGasPrice, GetNetworkInfo,
+    GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
+    GetStateChangesHistory, GetStateChangesHistoryResponse, GetStateChangesInBlock,
+    GetStateChangesWithCauseInBlock, GetValidatorInfo, GetValidatorOrdered, Query, QueryError,
+    Status, StatusResponse, SyncStatus, TxStatus, TxStatusError, MAX_STATE_CHANGES_HISTORY_LIMIT,
 };
 
+pub use crate::chain_events::{
+    start_chain_events_actor, ChainEvent, ChainEvents, ChainEventsActor, WatchId,
+    MAX_WATCHED_TRANSACTIONS,
+};
+pub use crate::checkpoint_actor::{start_checkpoint_actor, CheckpointActor};
 pub use crate::client::Client;
 pub use crate::client_actor::{start_client, ClientActor};
+pub use crate::store_validator_actor::{start_store_validator_actor, StoreValidatorActor};
 #[cfg(feature = "test_features")]
 pub use crate::view_client::AdversarialControls;
 pub use crate::view_client::{start_view_client, ViewClientActor};
 
+mod chain_events;
+mod checkpoint_actor;
 mod chunks_delay_tracker;
 mod client;
 mod client_actor;
 mod info;
 mod metrics;
+mod store_validator_actor;
 pub mod sync;
 pub mod test_utils;
 #[cfg(test)]
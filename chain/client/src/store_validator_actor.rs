@@ -0,0 +1,65 @@
+//! Actor validating the node database in the background at a throttled rate.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::{Actor, Addr, Arbiter, ArbiterHandle, AsyncContext, Context};
+use log::{error, info, warn};
+
+use near_chain::{RuntimeAdapter, StoreValidator};
+use near_chain_configs::GenesisConfig;
+use near_primitives::types::AccountId;
+use near_store::Store;
+
+/// Runs the store validator in a thread of its own, so that validation doesn't hold up block
+/// processing. Every second it checks the next `keys_per_second` records, resuming from the
+/// position stored in the database after a restart.
+pub struct StoreValidatorActor {
+    store_validator: StoreValidator,
+    keys_per_second: u64,
+}
+
+impl Actor for StoreValidatorActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.validate_step(ctx);
+    }
+}
+
+impl StoreValidatorActor {
+    /// Errors are only logged, repairs are applied by the `store-validator` tool.
+    fn validate_step(&mut self, ctx: &mut Context<Self>) {
+        match self.store_validator.validate_step(self.keys_per_second) {
+            Ok(finished) => {
+                for error in self.store_validator.errors.drain(..) {
+                    warn!(target: "client", "Store validation: {} {} {}, repair: {:?}", error.col, error.key, error.err, error.repair);
+                }
+                if finished {
+                    info!(target: "client", "Store validation pass completed");
+                }
+            }
+            Err(err) => error!(target: "client", "Store validation step failed: {}", err),
+        }
+        near_performance_metrics::actix::run_later(ctx, Duration::from_secs(1), move |act, ctx| {
+            act.validate_step(ctx);
+        });
+    }
+}
+
+/// Starts the store validator actor in a new arbiter (thread).
+pub fn start_store_validator_actor(
+    me: Option<AccountId>,
+    genesis_config: GenesisConfig,
+    runtime_adapter: Arc<dyn RuntimeAdapter>,
+    store: Arc<Store>,
+    keys_per_second: u64,
+) -> (Addr<StoreValidatorActor>, ArbiterHandle) {
+    assert!(keys_per_second > 0, "store validation rate must be positive");
+    let arbiter = Arbiter::new().handle();
+    let addr = StoreValidatorActor::start_in_arbiter(&arbiter, move |_ctx| StoreValidatorActor {
+        store_validator: StoreValidator::new(me, genesis_config, runtime_adapter, store),
+        keys_per_second,
+    });
+    (addr, arbiter)
+}
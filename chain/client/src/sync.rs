@@ -2,6 +2,7 @@ use near_chain::{near_chain_primitives, ChainStoreAccess, Error};
 use std::cmp::min;
 use std::collections::{HashMap, HashSet};
 use std::ops::Add;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration as TimeDuration;
@@ -28,6 +29,7 @@ use near_primitives::utils::to_timestamp;
 
 use cached::{Cached, SizedCache};
 use near_chain::chain::{ApplyStatePartsRequest, StateSplitRequest};
+use near_chain::state_sync_dump::{read_state_header, read_state_part};
 use near_client_primitives::types::{
     DownloadStatus, ShardSyncDownload, ShardSyncStatus, SyncStatus,
 };
@@ -623,10 +625,21 @@ pub struct StateSync {
 
     /// Maps shard_id to result of splitting state for resharding
     split_state_roots: HashMap<ShardId, Result<HashMap<ShardUId, StateRoot>, Error>>,
+
+    /// Directory with a dump of state sync data, which is read before requesting peers.
+    dump_dir: Option<PathBuf>,
+
+    /// Headers (part `None`) and parts of the dump which failed validation, so that they are
+    /// requested from peers instead of being read again.
+    invalid_dump_data: HashSet<(CryptoHash, ShardId, Option<u64>)>,
 }
 
 impl StateSync {
-    pub fn new(network_adapter: Arc<dyn PeerManagerAdapter>, timeout: TimeDuration) -> Self {
+    pub fn new(
+        network_adapter: Arc<dyn PeerManagerAdapter>,
+        timeout: TimeDuration,
+        dump_dir: Option<PathBuf>,
+    ) -> Self {
         StateSync {
             network_adapter,
             state_sync_time: Default::default(),
@@ -636,6 +649,8 @@ impl StateSync {
             timeout: Duration::from_std(timeout).unwrap(),
             state_parts_apply_results: HashMap::new(),
             split_state_roots: HashMap::new(),
+            dump_dir,
+            invalid_dump_data: HashSet::new(),
         }
     }
 
@@ -692,6 +707,7 @@ impl StateSync {
                     done: false,
                     state_requests_count: 0,
                     last_target: None,
+                    received_part: None,
                 };
                 1
             ],
@@ -723,19 +739,27 @@ impl StateSync {
                         let shard_state_header = chain.get_state_header(shard_id, sync_hash)?;
                         let state_num_parts =
                             get_num_state_parts(shard_state_header.state_root_node().memory_usage);
+                        let mut downloads = Vec::with_capacity(state_num_parts as usize);
+                        for part_id in 0..state_num_parts {
+                            // Parts downloaded before state sync got interrupted are not
+                            // requested again.
+                            let done = chain
+                                .store()
+                                .get_state_part_status(sync_hash, shard_id, part_id)?
+                                .is_some();
+                            downloads.push(DownloadStatus {
+                                start_time: now,
+                                prev_update_time: now,
+                                run_me: Arc::new(AtomicBool::new(!done)),
+                                error: false,
+                                done,
+                                state_requests_count: 0,
+                                last_target: None,
+                                received_part: None,
+                            });
+                        }
                         *shard_sync_download = ShardSyncDownload {
-                            downloads: vec![
-                                DownloadStatus {
-                                    start_time: now,
-                                    prev_update_time: now,
-                                    run_me: Arc::new(AtomicBool::new(true)),
-                                    error: false,
-                                    done: false,
-                                    state_requests_count: 0,
-                                    last_target: None,
-                                };
-                                state_num_parts as usize
-                            ],
+                            downloads,
                             status: ShardSyncStatus::StateDownloadParts,
                         };
                         need_shard = true;
@@ -754,6 +778,42 @@ impl StateSync {
                     }
                 }
                 ShardSyncStatus::StateDownloadParts => {
+                    // Parts received since the last run are validated together, in parallel.
+                    let received_parts: Vec<_> = shard_sync_download
+                        .downloads
+                        .iter_mut()
+                        .enumerate()
+                        .filter_map(|(part_id, part_download)| {
+                            part_download.received_part.take().map(|part| (part_id as u64, part))
+                        })
+                        .collect();
+                    if !received_parts.is_empty() {
+                        // The parts are taken out of the status, so it has to be saved.
+                        update_sync_status = true;
+                        let num_parts = shard_sync_download.downloads.len() as u64;
+                        match chain.set_state_parts(shard_id, sync_hash, num_parts, &received_parts)
+                        {
+                            Ok(valid) => {
+                                for ((part_id, _), valid) in received_parts.iter().zip(valid) {
+                                    let part_download =
+                                        &mut shard_sync_download.downloads[*part_id as usize];
+                                    if valid {
+                                        part_download.done = true;
+                                    } else {
+                                        error!(target: "sync", "State sync received invalid part, shard = {}, part = {}, hash = {}", shard_id, part_id, sync_hash);
+                                        part_download.error = true;
+                                    }
+                                }
+                            }
+                            Err(err) => {
+                                // The parts are requested again.
+                                error!(target: "sync", "State sync failed to save parts, shard = {}, hash = {}: {}", shard_id, sync_hash, err);
+                                for (part_id, _) in received_parts.iter() {
+                                    shard_sync_download.downloads[*part_id as usize].error = true;
+                                }
+                            }
+                        }
+                    }
                     let mut parts_done = true;
                     for part_download in shard_sync_download.downloads.iter_mut() {
                         if !part_download.done {
@@ -763,6 +823,7 @@ impl StateSync {
                             let part_timeout = now - prev > self.timeout;
                             if part_timeout || error {
                                 download_timeout |= part_timeout;
+                                update_sync_status = true;
                                 part_download.run_me.store(true, Ordering::SeqCst);
                                 part_download.error = false;
                                 part_download.prev_update_time = now;
@@ -919,6 +980,10 @@ impl StateSync {
             // Execute syncing for shard `shard_id`
             if need_shard {
                 update_sync_status = true;
+                need_shard =
+                    self.read_shard_from_dump(shard_id, chain, sync_hash, shard_sync_download);
+            }
+            if need_shard {
                 *shard_sync_download = self.request_shard(
                     me,
                     shard_id,
@@ -934,6 +999,102 @@ impl StateSync {
         Ok((update_sync_status, all_done))
     }
 
+    /// Takes the state header or the parts which are still needed from the dump directory, if
+    /// there is one. They are validated as if they came from a peer.
+    /// Returns whether something still has to be requested from peers.
+    fn read_shard_from_dump(
+        &mut self,
+        shard_id: ShardId,
+        chain: &mut Chain,
+        sync_hash: CryptoHash,
+        shard_sync_download: &mut ShardSyncDownload,
+    ) -> bool {
+        let dump_dir = match &self.dump_dir {
+            Some(dump_dir) => dump_dir,
+            None => return true,
+        };
+        match shard_sync_download.status {
+            ShardSyncStatus::StateDownloadHeader => {
+                if self.invalid_dump_data.contains(&(sync_hash, shard_id, None)) {
+                    return true;
+                }
+                let header = match read_state_header(dump_dir, &sync_hash, shard_id) {
+                    Ok(Some(header)) => header,
+                    Ok(None) => return true,
+                    Err(err) => {
+                        warn!(target: "sync", "State sync can't read header from dump, shard = {}, hash = {}: {:?}", shard_id, sync_hash, err);
+                        return true;
+                    }
+                };
+                match chain.set_state_header(shard_id, sync_hash, header) {
+                    Ok(()) => {
+                        shard_sync_download.downloads[0].done = true;
+                        shard_sync_download.downloads[0].run_me.store(false, Ordering::SeqCst);
+                        false
+                    }
+                    Err(err) => {
+                        error!(target: "sync", "State sync set_state_header error for header from dump, shard = {}, hash = {}: {:?}", shard_id, sync_hash, err);
+                        self.invalid_dump_data.insert((sync_hash, shard_id, None));
+                        true
+                    }
+                }
+            }
+            ShardSyncStatus::StateDownloadParts => {
+                let num_parts = shard_sync_download.downloads.len() as u64;
+                let mut parts = vec![];
+                let mut has_more_parts = false;
+                let mut need_request = false;
+                for (part_id, download) in shard_sync_download.downloads.iter().enumerate() {
+                    let part_id = part_id as u64;
+                    if !download.run_me.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    if self.invalid_dump_data.contains(&(sync_hash, shard_id, Some(part_id))) {
+                        need_request = true;
+                        continue;
+                    }
+                    // Validating parts takes time, so only a few are taken on each step and
+                    // nothing is requested until the dump runs out of parts.
+                    if parts.len() as u64 == MAX_STATE_PART_REQUEST {
+                        has_more_parts = true;
+                        break;
+                    }
+                    match read_state_part(dump_dir, &sync_hash, shard_id, part_id) {
+                        Ok(Some(part)) => parts.push((part_id, part)),
+                        Ok(None) => need_request = true,
+                        Err(err) => {
+                            warn!(target: "sync", "State sync can't read part from dump, shard = {}, part = {}, hash = {}: {:?}", shard_id, part_id, sync_hash, err);
+                            need_request = true;
+                        }
+                    }
+                }
+                if parts.is_empty() {
+                    return need_request;
+                }
+                let valid = match chain.set_state_parts(shard_id, sync_hash, num_parts, &parts) {
+                    Ok(valid) => valid,
+                    Err(err) => {
+                        error!(target: "sync", "State sync set_state_parts error for parts from dump, shard = {}, hash = {}: {:?}", shard_id, sync_hash, err);
+                        return true;
+                    }
+                };
+                for ((part_id, _), valid) in parts.iter().zip(valid) {
+                    let download = &mut shard_sync_download.downloads[*part_id as usize];
+                    if valid {
+                        download.done = true;
+                        download.run_me.store(false, Ordering::SeqCst);
+                    } else {
+                        error!(target: "sync", "State sync received invalid part from dump, shard = {}, part = {}, hash = {}", shard_id, part_id, sync_hash);
+                        self.invalid_dump_data.insert((sync_hash, shard_id, Some(*part_id)));
+                        need_request = true;
+                    }
+                }
+                !has_more_parts && need_request
+            }
+            _ => true,
+        }
+    }
+
     pub fn set_apply_result(&mut self, shard_id: ShardId, apply_result: Result<(), Error>) {
         self.state_parts_apply_results.insert(shard_id, apply_result);
     }
@@ -1293,6 +1454,7 @@ mod test {
     use near_network::routing::PartialEdgeInfo;
     use near_network::PeerInfo;
     use near_primitives::merkle::PartialMerkleTree;
+    use near_primitives::syncing::StatePartStatus;
     use near_primitives::types::EpochId;
     use near_primitives::validator_signer::InMemoryValidatorSigner;
     use near_primitives::version::PROTOCOL_VERSION;
@@ -1624,6 +1786,86 @@ mod test {
         assert!(requested_block_hashes.is_empty());
     }
 
+    fn new_shard_sync_download(num_downloads: usize, status: ShardSyncStatus) -> ShardSyncDownload {
+        let now = Clock::utc();
+        let download = DownloadStatus {
+            start_time: now,
+            prev_update_time: now,
+            run_me: Arc::new(AtomicBool::new(true)),
+            error: false,
+            done: false,
+            state_requests_count: 0,
+            last_target: None,
+            received_part: None,
+        };
+        ShardSyncDownload { downloads: vec![download; num_downloads], status }
+    }
+
+    #[test]
+    fn test_read_shard_from_dump() {
+        let mut env = TestEnv::builder(ChainGenesis::test()).clients_count(2).build();
+        let mut blocks = vec![];
+        for i in 1..=12 {
+            let block = env.clients[0].produce_block(i).unwrap().unwrap();
+            blocks.push(block.clone());
+            env.process_block(0, block.clone(), Provenance::PRODUCED);
+            env.process_block(1, block, Provenance::NONE);
+        }
+        let sync_hash = blocks
+            .iter()
+            .skip(1)
+            .map(|block| *block.hash())
+            .find(|hash| env.clients[0].chain.check_sync_hash_validity(hash).unwrap())
+            .unwrap();
+        let dump_dir = tempfile::Builder::new().prefix("state_sync_dump").tempdir().unwrap();
+        let num_parts =
+            env.clients[0].chain.dump_state_sync_data(0, sync_hash, dump_dir.path()).unwrap();
+        let network_adapter = Arc::new(MockPeerManagerAdapter::default());
+        let mut state_sync = StateSync::new(
+            network_adapter.clone(),
+            TimeDuration::from_secs(1),
+            Some(dump_dir.path().to_path_buf()),
+        );
+        let chain = &mut env.clients[1].chain;
+
+        // A header which doesn't match the block is rejected once and not read again.
+        let other_hash = *blocks[0].hash();
+        let other_dir = dump_dir.path().join(other_hash.to_string());
+        std::fs::rename(dump_dir.path().join(sync_hash.to_string()), &other_dir).unwrap();
+        let mut download = new_shard_sync_download(1, ShardSyncStatus::StateDownloadHeader);
+        assert!(state_sync.read_shard_from_dump(0, chain, other_hash, &mut download));
+        assert!(!download.downloads[0].done);
+        assert!(state_sync.invalid_dump_data.contains(&(other_hash, 0, None)));
+        std::fs::rename(&other_dir, dump_dir.path().join(sync_hash.to_string())).unwrap();
+
+        let mut download = new_shard_sync_download(1, ShardSyncStatus::StateDownloadHeader);
+        assert!(!state_sync.read_shard_from_dump(0, chain, sync_hash, &mut download));
+        assert!(download.downloads[0].done);
+
+        let mut download =
+            new_shard_sync_download(num_parts as usize, ShardSyncStatus::StateDownloadParts);
+        assert!(!state_sync.read_shard_from_dump(0, chain, sync_hash, &mut download));
+        for part_id in 0..num_parts {
+            assert!(download.downloads[part_id as usize].done);
+            assert_eq!(
+                chain.store().get_state_part_status(sync_hash, 0, part_id).unwrap(),
+                Some(StatePartStatus::Downloaded)
+            );
+        }
+
+        // Parts missing from the dump are requested from peers.
+        let empty_dir = tempfile::Builder::new().prefix("state_sync_dump").tempdir().unwrap();
+        let mut state_sync = StateSync::new(
+            network_adapter,
+            TimeDuration::from_secs(1),
+            Some(empty_dir.path().to_path_buf()),
+        );
+        let mut download =
+            new_shard_sync_download(num_parts as usize, ShardSyncStatus::StateDownloadParts);
+        assert!(state_sync.read_shard_from_dump(0, chain, sync_hash, &mut download));
+        assert!(download.downloads.iter().all(|download| !download.done));
+    }
+
     #[test]
     fn test_block_sync_archival() {
         let network_adapter = Arc::new(MockPeerManagerAdapter::default());
@@ -23,8 +23,9 @@ use near_client_primitives::types::{
     GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome, GetExecutionOutcomeError,
     GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetNextLightClientBlockError,
     GetProtocolConfig, GetProtocolConfigError, GetReceipt, GetReceiptError, GetStateChangesError,
-    GetStateChangesWithCauseInBlock, GetValidatorInfoError, Query, QueryError, TxStatus,
-    TxStatusError,
+    GetStateChangesHistoryResponse, GetStateChangesWithCauseInBlock, GetValidatorInfoError, Query,
+    QueryError, TxStatus, TxStatusError, MAX_STATE_CHANGES_HISTORY_LIMIT,
+    MAX_STATE_CHANGES_HISTORY_RANGE,
 };
 use near_network::types::{NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest};
 #[cfg(feature = "test_features")]
@@ -57,7 +58,7 @@ use near_primitives::views::{
 
 use crate::{
     sync, GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
+    GetStateChangesHistory, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
 };
 
 /// Max number of queries that we keep.
@@ -195,6 +196,19 @@ impl ViewClientActor {
         }
     }
 
+    /// Runs `f` with the chain reads going to a single snapshot of the database, so that a
+    /// request reading several columns can't observe an update committed by the client actor
+    /// halfway. Trie nodes are read by the runtime directly, they are addressed by hash and
+    /// the state root comes from the snapshot.
+    fn with_snapshot<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let pinned = self.chain.mut_store().pin_snapshot();
+        let result = f(self);
+        if pinned {
+            self.chain.mut_store().unpin_snapshot();
+        }
+        result
+    }
+
     fn handle_query(&mut self, msg: Query) -> Result<QueryResponse, QueryError> {
         let header = match msg.block_reference {
             BlockReference::BlockId(BlockId::Height(block_height)) => {
@@ -533,7 +547,7 @@ impl Handler<Query> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: Query, _: &mut Self::Context) -> Self::Result {
-        self.handle_query(msg)
+        self.with_snapshot(|this| this.handle_query(msg))
     }
 }
 
@@ -543,33 +557,35 @@ impl Handler<GetBlock> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetBlock, _: &mut Self::Context) -> Self::Result {
-        let block = match msg.0 {
-            BlockReference::Finality(finality) => {
-                let block_hash = self.get_block_hash_by_finality(&finality)?;
-                self.chain.get_block(&block_hash).map(Clone::clone)
-            }
-            BlockReference::BlockId(BlockId::Height(height)) => {
-                self.chain.get_block_by_height(height).map(Clone::clone)
-            }
-            BlockReference::BlockId(BlockId::Hash(hash)) => {
-                self.chain.get_block(&hash).map(Clone::clone)
-            }
-            BlockReference::SyncCheckpoint(sync_checkpoint) => {
-                if let Some(block_hash) =
-                    self.get_block_hash_by_sync_checkpoint(&sync_checkpoint)?
-                {
-                    self.chain.get_block(&block_hash).map(Clone::clone)
-                } else {
-                    return Err(GetBlockError::NotSyncedYet);
+        self.with_snapshot(|this| {
+            let block = match msg.0 {
+                BlockReference::Finality(finality) => {
+                    let block_hash = this.get_block_hash_by_finality(&finality)?;
+                    this.chain.get_block(&block_hash).map(Clone::clone)
                 }
-            }
-        }?;
+                BlockReference::BlockId(BlockId::Height(height)) => {
+                    this.chain.get_block_by_height(height).map(Clone::clone)
+                }
+                BlockReference::BlockId(BlockId::Hash(hash)) => {
+                    this.chain.get_block(&hash).map(Clone::clone)
+                }
+                BlockReference::SyncCheckpoint(sync_checkpoint) => {
+                    if let Some(block_hash) =
+                        this.get_block_hash_by_sync_checkpoint(&sync_checkpoint)?
+                    {
+                        this.chain.get_block(&block_hash).map(Clone::clone)
+                    } else {
+                        return Err(GetBlockError::NotSyncedYet);
+                    }
+                }
+            }?;
 
-        let block_author = self
-            .runtime_adapter
-            .get_block_producer(&block.header().epoch_id(), block.header().height())?;
+            let block_author = this
+                .runtime_adapter
+                .get_block_producer(&block.header().epoch_id(), block.header().height())?;
 
-        Ok(BlockView::from_author_block(block_author, block))
+            Ok(BlockView::from_author_block(block_author, block))
+        })
     }
 }
 
@@ -578,12 +594,14 @@ impl Handler<GetBlockWithMerkleTree> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetBlockWithMerkleTree, ctx: &mut Self::Context) -> Self::Result {
-        let block_view = self.handle(GetBlock(msg.0), ctx)?;
-        self.chain
-            .mut_store()
-            .get_block_merkle_tree(&block_view.header.hash)
-            .map(|merkle_tree| (block_view, merkle_tree.clone()))
-            .map_err(|e| e.into())
+        self.with_snapshot(|this| {
+            let block_view = this.handle(GetBlock(msg.0), ctx)?;
+            this.chain
+                .mut_store()
+                .get_block_merkle_tree(&block_view.header.hash)
+                .map(|merkle_tree| (block_view, merkle_tree.clone()))
+                .map_err(|e| e.into())
+        })
     }
 }
 
@@ -592,48 +610,50 @@ impl Handler<GetChunk> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetChunk, _: &mut Self::Context) -> Self::Result {
-        let get_chunk_from_block = |block: Block,
-                                    shard_id: ShardId,
-                                    chain: &mut Chain|
-         -> Result<ShardChunk, near_chain::Error> {
-            let chunk_header = block
-                .chunks()
-                .get(shard_id as usize)
-                .ok_or_else(|| near_chain::Error::from(ErrorKind::InvalidShardId(shard_id)))?
-                .clone();
-            let chunk_hash = chunk_header.chunk_hash();
-            chain.get_chunk(&chunk_hash).and_then(|chunk| {
-                ShardChunk::with_header(chunk.clone(), chunk_header).ok_or(near_chain::Error::from(
-                    ErrorKind::Other(format!(
-                        "Mismatched versions for chunk with hash {}",
-                        chunk_hash.0
-                    )),
-                ))
-            })
-        };
-
-        let chunk = match msg {
-            GetChunk::ChunkHash(chunk_hash) => self.chain.get_chunk(&chunk_hash)?.clone(),
-            GetChunk::BlockHash(block_hash, shard_id) => {
-                let block = self.chain.get_block(&block_hash)?.clone();
-                get_chunk_from_block(block, shard_id, &mut self.chain)?
-            }
-            GetChunk::Height(height, shard_id) => {
-                let block = self.chain.get_block_by_height(height)?.clone();
-                get_chunk_from_block(block, shard_id, &mut self.chain)?
-            }
-        };
-
-        let chunk_inner = chunk.cloned_header().take_inner();
-        let epoch_id =
-            self.runtime_adapter.get_epoch_id_from_prev_block(chunk_inner.prev_block_hash())?;
-        let author = self.runtime_adapter.get_chunk_producer(
-            &epoch_id,
-            chunk_inner.height_created(),
-            chunk_inner.shard_id(),
-        )?;
+        self.with_snapshot(|this| {
+            let get_chunk_from_block = |block: Block,
+                                        shard_id: ShardId,
+                                        chain: &mut Chain|
+             -> Result<ShardChunk, near_chain::Error> {
+                let chunk_header = block
+                    .chunks()
+                    .get(shard_id as usize)
+                    .ok_or_else(|| near_chain::Error::from(ErrorKind::InvalidShardId(shard_id)))?
+                    .clone();
+                let chunk_hash = chunk_header.chunk_hash();
+                chain.get_chunk(&chunk_hash).and_then(|chunk| {
+                    ShardChunk::with_header(chunk.clone(), chunk_header).ok_or(
+                        near_chain::Error::from(ErrorKind::Other(format!(
+                            "Mismatched versions for chunk with hash {}",
+                            chunk_hash.0
+                        ))),
+                    )
+                })
+            };
+
+            let chunk = match msg {
+                GetChunk::ChunkHash(chunk_hash) => this.chain.get_chunk(&chunk_hash)?.clone(),
+                GetChunk::BlockHash(block_hash, shard_id) => {
+                    let block = this.chain.get_block(&block_hash)?.clone();
+                    get_chunk_from_block(block, shard_id, &mut this.chain)?
+                }
+                GetChunk::Height(height, shard_id) => {
+                    let block = this.chain.get_block_by_height(height)?.clone();
+                    get_chunk_from_block(block, shard_id, &mut this.chain)?
+                }
+            };
+
+            let chunk_inner = chunk.cloned_header().take_inner();
+            let epoch_id =
+                this.runtime_adapter.get_epoch_id_from_prev_block(chunk_inner.prev_block_hash())?;
+            let author = this.runtime_adapter.get_chunk_producer(
+                &epoch_id,
+                chunk_inner.height_created(),
+                chunk_inner.shard_id(),
+            )?;
 
-        Ok(ChunkView::from_author_chunk(author, chunk))
+            Ok(ChunkView::from_author_chunk(author, chunk))
+        })
     }
 }
 
@@ -642,7 +662,9 @@ impl Handler<TxStatus> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: TxStatus, _: &mut Self::Context) -> Self::Result {
-        self.get_tx_status(msg.tx_hash, msg.signer_account_id, msg.fetch_receipt)
+        self.with_snapshot(|this| {
+            this.get_tx_status(msg.tx_hash, msg.signer_account_id, msg.fetch_receipt)
+        })
     }
 }
 
@@ -651,42 +673,44 @@ impl Handler<GetValidatorInfo> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetValidatorInfo, _: &mut Self::Context) -> Self::Result {
-        let epoch_identifier = match msg.epoch_reference {
-            EpochReference::EpochId(id) => {
-                // By `EpochId` we can get only cached epochs.
-                // Request for not finished epoch by `EpochId` will return an error because epoch has not been cached yet
-                // If the requested one is current ongoing we need to handle it like `Latest`
-                let tip = self.chain.header_head()?;
-                if tip.epoch_id == id {
-                    ValidatorInfoIdentifier::BlockHash(tip.last_block_hash)
-                } else {
-                    ValidatorInfoIdentifier::EpochId(id)
+        self.with_snapshot(|this| {
+            let epoch_identifier = match msg.epoch_reference {
+                EpochReference::EpochId(id) => {
+                    // By `EpochId` we can get only cached epochs.
+                    // Request for not finished epoch by `EpochId` will return an error because epoch has not been cached yet
+                    // If the requested one is current ongoing we need to handle it like `Latest`
+                    let tip = this.chain.header_head()?;
+                    if tip.epoch_id == id {
+                        ValidatorInfoIdentifier::BlockHash(tip.last_block_hash)
+                    } else {
+                        ValidatorInfoIdentifier::EpochId(id)
+                    }
                 }
-            }
-            EpochReference::BlockId(block_id) => {
-                let block_header = match block_id {
-                    BlockId::Hash(h) => self.chain.get_block_header(&h)?.clone(),
-                    BlockId::Height(h) => self.chain.get_header_by_height(h)?.clone(),
-                };
-                let next_block_hash =
-                    *self.chain.mut_store().get_next_block_hash(block_header.hash())?;
-                let next_block_header = self.chain.get_block_header(&next_block_hash)?.clone();
-                if block_header.epoch_id() != next_block_header.epoch_id()
-                    && block_header.next_epoch_id() == next_block_header.epoch_id()
-                {
-                    ValidatorInfoIdentifier::EpochId(block_header.epoch_id().clone())
-                } else {
-                    return Err(GetValidatorInfoError::ValidatorInfoUnavailable);
+                EpochReference::BlockId(block_id) => {
+                    let block_header = match block_id {
+                        BlockId::Hash(h) => this.chain.get_block_header(&h)?.clone(),
+                        BlockId::Height(h) => this.chain.get_header_by_height(h)?.clone(),
+                    };
+                    let next_block_hash =
+                        *this.chain.mut_store().get_next_block_hash(block_header.hash())?;
+                    let next_block_header = this.chain.get_block_header(&next_block_hash)?.clone();
+                    if block_header.epoch_id() != next_block_header.epoch_id()
+                        && block_header.next_epoch_id() == next_block_header.epoch_id()
+                    {
+                        ValidatorInfoIdentifier::EpochId(block_header.epoch_id().clone())
+                    } else {
+                        return Err(GetValidatorInfoError::ValidatorInfoUnavailable);
+                    }
                 }
-            }
-            EpochReference::Latest => {
-                // use header head because this is latest from the perspective of epoch manager
-                ValidatorInfoIdentifier::BlockHash(self.chain.header_head()?.last_block_hash)
-            }
-        };
-        self.runtime_adapter
-            .get_validator_info(epoch_identifier)
-            .map_err(GetValidatorInfoError::from)
+                EpochReference::Latest => {
+                    // use header head because this is latest from the perspective of epoch manager
+                    ValidatorInfoIdentifier::BlockHash(this.chain.header_head()?.last_block_hash)
+                }
+            };
+            this.runtime_adapter
+                .get_validator_info(epoch_identifier)
+                .map_err(GetValidatorInfoError::from)
+        })
     }
 }
 
@@ -759,6 +783,38 @@ impl Handler<GetStateChangesWithCauseInBlock> for ViewClientActor {
     }
 }
 
+/// Returns the changes of a key made by the blocks of the canonical chain in a range of heights.
+impl Handler<GetStateChangesHistory> for ViewClientActor {
+    type Result = Result<GetStateChangesHistoryResponse, GetStateChangesError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetStateChangesHistory, _: &mut Self::Context) -> Self::Result {
+        self.with_snapshot(|this| {
+            let requested_to_height = match msg.to_height {
+                Some(to_height) => to_height,
+                None => this.chain.head()?.height,
+            };
+            let to_height = std::cmp::min(
+                requested_to_height,
+                msg.from_height.saturating_add(MAX_STATE_CHANGES_HISTORY_RANGE - 1),
+            );
+            let limit = msg.limit.clamp(1, MAX_STATE_CHANGES_HISTORY_LIMIT);
+            let (changes, next_from_height) = this.chain.store().get_state_changes_history(
+                &msg.key.into(),
+                msg.from_height,
+                to_height,
+                limit,
+            )?;
+            let next_from_height = next_from_height
+                .or_else(|| (to_height < requested_to_height).then(|| to_height + 1));
+            Ok(GetStateChangesHistoryResponse {
+                changes: changes.into_iter().map(Into::into).collect(),
+                next_from_height,
+            })
+        })
+    }
+}
+
 /// Returns the next light client block, given the hash of the last block known to the light client.
 /// There are three cases:
 ///  1. The last block known to the light client is in the same epoch as the tip:
@@ -772,37 +828,39 @@ impl Handler<GetNextLightClientBlock> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetNextLightClientBlock, _: &mut Self::Context) -> Self::Result {
-        let last_block_header = self.chain.get_block_header(&msg.last_block_hash)?;
-        let last_epoch_id = last_block_header.epoch_id().clone();
-        let last_next_epoch_id = last_block_header.next_epoch_id().clone();
-        let last_height = last_block_header.height();
-        let head = self.chain.head()?;
-
-        if last_epoch_id == head.epoch_id || last_next_epoch_id == head.epoch_id {
-            let head_header = self.chain.get_block_header(&head.last_block_hash)?;
-            let ret = Chain::create_light_client_block(
-                &head_header.clone(),
-                &*self.runtime_adapter,
-                self.chain.mut_store(),
-            )?;
+        self.with_snapshot(|this| {
+            let last_block_header = this.chain.get_block_header(&msg.last_block_hash)?;
+            let last_epoch_id = last_block_header.epoch_id().clone();
+            let last_next_epoch_id = last_block_header.next_epoch_id().clone();
+            let last_height = last_block_header.height();
+            let head = this.chain.head()?;
+
+            if last_epoch_id == head.epoch_id || last_next_epoch_id == head.epoch_id {
+                let head_header = this.chain.get_block_header(&head.last_block_hash)?;
+                let ret = Chain::create_light_client_block(
+                    &head_header.clone(),
+                    &*this.runtime_adapter,
+                    this.chain.mut_store(),
+                )?;
 
-            if ret.inner_lite.height <= last_height {
-                Ok(None)
+                if ret.inner_lite.height <= last_height {
+                    Ok(None)
+                } else {
+                    Ok(Some(ret))
+                }
             } else {
-                Ok(Some(ret))
-            }
-        } else {
-            match self.chain.mut_store().get_epoch_light_client_block(&last_next_epoch_id.0) {
-                Ok(light_block) => Ok(Some(light_block.clone())),
-                Err(e) => {
-                    if let ErrorKind::DBNotFoundErr(_) = e.kind() {
-                        Ok(None)
-                    } else {
-                        Err(e.into())
+                match this.chain.mut_store().get_epoch_light_client_block(&last_next_epoch_id.0) {
+                    Ok(light_block) => Ok(Some(light_block.clone())),
+                    Err(e) => {
+                        if let ErrorKind::DBNotFoundErr(_) = e.kind() {
+                            Ok(None)
+                        } else {
+                            Err(e.into())
+                        }
                     }
                 }
             }
-        }
+        })
     }
 }
 
@@ -811,78 +869,82 @@ impl Handler<GetExecutionOutcome> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetExecutionOutcome, _: &mut Self::Context) -> Self::Result {
-        let (id, account_id) = match msg.id {
-            TransactionOrReceiptId::Transaction { transaction_hash, sender_id } => {
-                (transaction_hash, sender_id)
-            }
-            TransactionOrReceiptId::Receipt { receipt_id, receiver_id } => {
-                (receipt_id, receiver_id)
-            }
-        };
-        match self.chain.get_execution_outcome(&id) {
-            Ok(outcome) => {
-                let mut outcome_proof = outcome.clone();
-                let epoch_id = self.chain.get_block(&outcome_proof.block_hash)?.header().epoch_id();
-                let target_shard_id =
-                    self.runtime_adapter.account_id_to_shard_id(&account_id, epoch_id)?;
-                let res = self.chain.get_next_block_hash_with_new_chunk(
-                    &outcome_proof.block_hash,
-                    target_shard_id,
-                )?;
-                match res {
-                    Some((h, target_shard_id)) => {
-                        outcome_proof.block_hash = h;
-                        // Here we assume the number of shards is small so this reconstruction
-                        // should be fast
-                        let outcome_roots = self
-                            .chain
-                            .get_block(&h)?
-                            .chunks()
-                            .iter()
-                            .map(|header| header.outcome_root())
-                            .collect::<Vec<_>>();
-                        if target_shard_id >= (outcome_roots.len() as u64) {
-                            return Err(GetExecutionOutcomeError::InconsistentState {
-                                number_or_shards: outcome_roots.len(),
-                                execution_outcome_shard_id: target_shard_id,
-                            });
-                        }
-                        Ok(GetExecutionOutcomeResponse {
-                            outcome_proof: outcome_proof.into(),
-                            outcome_root_proof: merklize(&outcome_roots).1
-                                [target_shard_id as usize]
-                                .clone(),
-                        })
-                    }
-                    None => Err(GetExecutionOutcomeError::NotConfirmed {
-                        transaction_or_receipt_id: id,
-                    }),
+        self.with_snapshot(|this| {
+            let (id, account_id) = match msg.id {
+                TransactionOrReceiptId::Transaction { transaction_hash, sender_id } => {
+                    (transaction_hash, sender_id)
                 }
-            }
-            Err(e) => match e.kind() {
-                ErrorKind::DBNotFoundErr(_) => {
-                    let head = self.chain.head().map_err(|e| TxStatusError::ChainError(e))?;
+                TransactionOrReceiptId::Receipt { receipt_id, receiver_id } => {
+                    (receipt_id, receiver_id)
+                }
+            };
+            match this.chain.get_execution_outcome(&id) {
+                Ok(outcome) => {
+                    let mut outcome_proof = outcome.clone();
+                    let epoch_id =
+                        this.chain.get_block(&outcome_proof.block_hash)?.header().epoch_id();
                     let target_shard_id =
-                        self.runtime_adapter.account_id_to_shard_id(&account_id, &head.epoch_id)?;
-                    if self.runtime_adapter.cares_about_shard(
-                        self.validator_account_id.as_ref(),
-                        &head.last_block_hash,
+                        this.runtime_adapter.account_id_to_shard_id(&account_id, epoch_id)?;
+                    let res = this.chain.get_next_block_hash_with_new_chunk(
+                        &outcome_proof.block_hash,
                         target_shard_id,
-                        true,
-                    ) {
-                        Err(GetExecutionOutcomeError::UnknownTransactionOrReceipt {
-                            transaction_or_receipt_id: id,
-                        })
-                    } else {
-                        Err(GetExecutionOutcomeError::UnavailableShard {
+                    )?;
+                    match res {
+                        Some((h, target_shard_id)) => {
+                            outcome_proof.block_hash = h;
+                            // Here we assume the number of shards is small so this reconstruction
+                            // should be fast
+                            let outcome_roots = this
+                                .chain
+                                .get_block(&h)?
+                                .chunks()
+                                .iter()
+                                .map(|header| header.outcome_root())
+                                .collect::<Vec<_>>();
+                            if target_shard_id >= (outcome_roots.len() as u64) {
+                                return Err(GetExecutionOutcomeError::InconsistentState {
+                                    number_or_shards: outcome_roots.len(),
+                                    execution_outcome_shard_id: target_shard_id,
+                                });
+                            }
+                            Ok(GetExecutionOutcomeResponse {
+                                outcome_proof: outcome_proof.into(),
+                                outcome_root_proof: merklize(&outcome_roots).1
+                                    [target_shard_id as usize]
+                                    .clone(),
+                            })
+                        }
+                        None => Err(GetExecutionOutcomeError::NotConfirmed {
                             transaction_or_receipt_id: id,
-                            shard_id: target_shard_id,
-                        })
+                        }),
                     }
                 }
-                _ => Err(e.into()),
-            },
-        }
+                Err(e) => match e.kind() {
+                    ErrorKind::DBNotFoundErr(_) => {
+                        let head = this.chain.head().map_err(|e| TxStatusError::ChainError(e))?;
+                        let target_shard_id = this
+                            .runtime_adapter
+                            .account_id_to_shard_id(&account_id, &head.epoch_id)?;
+                        if this.runtime_adapter.cares_about_shard(
+                            this.validator_account_id.as_ref(),
+                            &head.last_block_hash,
+                            target_shard_id,
+                            true,
+                        ) {
+                            Err(GetExecutionOutcomeError::UnknownTransactionOrReceipt {
+                                transaction_or_receipt_id: id,
+                            })
+                        } else {
+                            Err(GetExecutionOutcomeError::UnavailableShard {
+                                transaction_or_receipt_id: id,
+                                shard_id: target_shard_id,
+                            })
+                        }
+                    }
+                    _ => Err(e.into()),
+                },
+            }
+        })
     }
 }
 
@@ -921,11 +983,13 @@ impl Handler<GetBlockProof> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetBlockProof, _: &mut Self::Context) -> Self::Result {
-        self.chain.check_block_final_and_canonical(&msg.block_hash)?;
-        self.chain.check_block_final_and_canonical(&msg.head_block_hash)?;
-        let block_header_lite = self.chain.get_block_header(&msg.block_hash)?.clone().into();
-        let block_proof = self.chain.get_block_proof(&msg.block_hash, &msg.head_block_hash)?;
-        Ok(GetBlockProofResponse { block_header_lite, proof: block_proof })
+        self.with_snapshot(|this| {
+            this.chain.check_block_final_and_canonical(&msg.block_hash)?;
+            this.chain.check_block_final_and_canonical(&msg.head_block_hash)?;
+            let block_header_lite = this.chain.get_block_header(&msg.block_hash)?.clone().into();
+            let block_proof = this.chain.get_block_proof(&msg.block_hash, &msg.head_block_hash)?;
+            Ok(GetBlockProofResponse { block_header_lite, proof: block_proof })
+        })
     }
 }
 
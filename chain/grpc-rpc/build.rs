@@ -0,0 +1,5 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto");
+    tonic_build::configure().compile(&["proto/near/rpc/v1/rpc.proto"], &["proto"])?;
+    Ok(())
+}
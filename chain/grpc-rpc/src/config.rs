@@ -0,0 +1,16 @@
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GrpcRpcConfig {
+    pub addr: String,
+}
+
+impl Default for GrpcRpcConfig {
+    fn default() -> Self {
+        Self { addr: "0.0.0.0:3050".to_owned() }
+    }
+}
+
+impl GrpcRpcConfig {
+    pub fn new(addr: &str) -> Self {
+        Self { addr: addr.to_owned() }
+    }
+}
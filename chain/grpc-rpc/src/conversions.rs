@@ -0,0 +1,1491 @@
+//! Conversions of `near_primitives::views` to protobuf messages.
+//!
+//! Every field of a view has a counterpart in the message unless it is deprecated in JSON RPC,
+//! so adding a field to a view means adding it to `rpc.proto` and here. The tests convert the
+//! messages back to views, and fail to compile until then.
+use std::fmt::Debug;
+
+use serde::Serialize;
+
+use near_primitives::challenge::SlashedValidator;
+use near_primitives::merkle::{Direction, MerklePathItem};
+use near_primitives::serialize::from_base64;
+use near_primitives::types::Balance;
+use near_primitives::views::validator_stake_view::ValidatorStakeView;
+use near_primitives::views::{
+    self, EpochValidatorInfo, GasPriceView, QueryResponse, QueryResponseKind, StatusResponse,
+};
+
+use crate::proto;
+
+fn balance(balance: Balance) -> String {
+    balance.to_string()
+}
+
+/// Views keep binary data in base64, which is decoded to bytes.
+fn base64_bytes(value: &str) -> Vec<u8> {
+    // Views are created from binary data, so the value is always valid base64.
+    from_base64(value).unwrap_or_default()
+}
+
+/// Values without a message of their own, such as errors, are sent as JSON.
+fn json<T: Serialize + Debug>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("{:?}", value))
+}
+
+impl From<StatusResponse> for proto::StatusResponse {
+    fn from(status: StatusResponse) -> Self {
+        let sync_info = status.sync_info;
+        Self {
+            version: status.version.version,
+            build: status.version.build,
+            chain_id: status.chain_id,
+            protocol_version: status.protocol_version,
+            latest_protocol_version: status.latest_protocol_version,
+            rpc_addr: status.rpc_addr,
+            validators: status
+                .validators
+                .into_iter()
+                .map(|validator| proto::ValidatorInfo {
+                    account_id: validator.account_id.into(),
+                    is_slashed: validator.is_slashed,
+                })
+                .collect(),
+            sync_info: Some(proto::SyncInfo {
+                latest_block_hash: sync_info.latest_block_hash.into(),
+                latest_block_height: sync_info.latest_block_height,
+                latest_state_root: sync_info.latest_state_root.into(),
+                latest_block_time: sync_info.latest_block_time.timestamp_nanos() as u64,
+                syncing: sync_info.syncing,
+                earliest_block_hash: sync_info.earliest_block_hash.map(Into::into),
+                earliest_block_height: sync_info.earliest_block_height,
+                earliest_block_time: sync_info
+                    .earliest_block_time
+                    .map(|time| time.timestamp_nanos() as u64),
+            }),
+            validator_account_id: status.validator_account_id.map(Into::into),
+        }
+    }
+}
+
+impl From<views::BlockView> for proto::Block {
+    fn from(block: views::BlockView) -> Self {
+        Self {
+            author: block.author.into(),
+            header: Some(block.header.into()),
+            chunks: block.chunks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<views::BlockHeaderView> for proto::BlockHeader {
+    fn from(header: views::BlockHeaderView) -> Self {
+        Self {
+            height: header.height,
+            prev_height: header.prev_height,
+            epoch_id: header.epoch_id.into(),
+            next_epoch_id: header.next_epoch_id.into(),
+            hash: header.hash.into(),
+            prev_hash: header.prev_hash.into(),
+            prev_state_root: header.prev_state_root.into(),
+            chunk_receipts_root: header.chunk_receipts_root.into(),
+            chunk_headers_root: header.chunk_headers_root.into(),
+            chunk_tx_root: header.chunk_tx_root.into(),
+            outcome_root: header.outcome_root.into(),
+            chunks_included: header.chunks_included,
+            challenges_root: header.challenges_root.into(),
+            timestamp_nanosec: header.timestamp_nanosec,
+            random_value: header.random_value.into(),
+            validator_proposals: header.validator_proposals.into_iter().map(Into::into).collect(),
+            chunk_mask: header.chunk_mask,
+            gas_price: balance(header.gas_price),
+            block_ordinal: header.block_ordinal,
+            total_supply: balance(header.total_supply),
+            challenges_result: header.challenges_result.into_iter().map(Into::into).collect(),
+            last_final_block: header.last_final_block.into(),
+            last_ds_final_block: header.last_ds_final_block.into(),
+            next_bp_hash: header.next_bp_hash.into(),
+            block_merkle_root: header.block_merkle_root.into(),
+            epoch_sync_data_hash: header.epoch_sync_data_hash.map(Into::into),
+            approvals: header
+                .approvals
+                .into_iter()
+                .map(|signature| proto::Approval {
+                    signature: signature.map(|signature| signature.to_string()),
+                })
+                .collect(),
+            signature: header.signature.to_string(),
+            latest_protocol_version: header.latest_protocol_version,
+        }
+    }
+}
+
+impl From<ValidatorStakeView> for proto::ValidatorStake {
+    fn from(stake: ValidatorStakeView) -> Self {
+        match stake {
+            ValidatorStakeView::V1(stake) => Self {
+                account_id: stake.account_id.into(),
+                public_key: stake.public_key.to_string(),
+                stake: balance(stake.stake),
+                is_chunk_only: false,
+            },
+            #[cfg(feature = "protocol_feature_chunk_only_producers")]
+            ValidatorStakeView::V2(stake) => Self {
+                account_id: stake.account_id.into(),
+                public_key: stake.public_key.to_string(),
+                stake: balance(stake.stake),
+                is_chunk_only: stake.is_chunk_only,
+            },
+        }
+    }
+}
+
+impl From<SlashedValidator> for proto::SlashedValidator {
+    fn from(validator: SlashedValidator) -> Self {
+        Self { account_id: validator.account_id.into(), is_double_sign: validator.is_double_sign }
+    }
+}
+
+impl From<views::ChunkHeaderView> for proto::ChunkHeader {
+    fn from(header: views::ChunkHeaderView) -> Self {
+        Self {
+            chunk_hash: header.chunk_hash.into(),
+            prev_block_hash: header.prev_block_hash.into(),
+            outcome_root: header.outcome_root.into(),
+            prev_state_root: header.prev_state_root.into(),
+            encoded_merkle_root: header.encoded_merkle_root.into(),
+            encoded_length: header.encoded_length,
+            height_created: header.height_created,
+            height_included: header.height_included,
+            shard_id: header.shard_id,
+            gas_used: header.gas_used,
+            gas_limit: header.gas_limit,
+            balance_burnt: balance(header.balance_burnt),
+            outgoing_receipts_root: header.outgoing_receipts_root.into(),
+            tx_root: header.tx_root.into(),
+            validator_proposals: header.validator_proposals.into_iter().map(Into::into).collect(),
+            signature: header.signature.to_string(),
+        }
+    }
+}
+
+impl From<views::ChunkView> for proto::Chunk {
+    fn from(chunk: views::ChunkView) -> Self {
+        Self {
+            author: chunk.author.into(),
+            header: Some(chunk.header.into()),
+            transactions: chunk.transactions.into_iter().map(Into::into).collect(),
+            receipts: chunk.receipts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<views::SignedTransactionView> for proto::SignedTransaction {
+    fn from(transaction: views::SignedTransactionView) -> Self {
+        Self {
+            signer_id: transaction.signer_id.into(),
+            public_key: transaction.public_key.to_string(),
+            nonce: transaction.nonce,
+            receiver_id: transaction.receiver_id.into(),
+            actions: transaction.actions.into_iter().map(Into::into).collect(),
+            signature: transaction.signature.to_string(),
+            hash: transaction.hash.into(),
+        }
+    }
+}
+
+impl From<views::ActionView> for proto::Action {
+    fn from(action: views::ActionView) -> Self {
+        use proto::action;
+
+        let action = match action {
+            views::ActionView::CreateAccount => {
+                action::Action::CreateAccount(action::CreateAccount {})
+            }
+            views::ActionView::DeployContract { code } => {
+                action::Action::DeployContract(action::DeployContract {
+                    code_hash: base64_bytes(&code),
+                })
+            }
+            views::ActionView::FunctionCall { method_name, args, gas, deposit } => {
+                action::Action::FunctionCall(action::FunctionCall {
+                    method_name,
+                    args: base64_bytes(&args),
+                    gas,
+                    deposit: balance(deposit),
+                })
+            }
+            views::ActionView::Transfer { deposit } => {
+                action::Action::Transfer(action::Transfer { deposit: balance(deposit) })
+            }
+            views::ActionView::Stake { stake, public_key } => {
+                action::Action::Stake(action::Stake {
+                    stake: balance(stake),
+                    public_key: public_key.to_string(),
+                    is_chunk_only: false,
+                })
+            }
+            views::ActionView::AddKey { public_key, access_key } => {
+                action::Action::AddKey(action::AddKey {
+                    public_key: public_key.to_string(),
+                    access_key: Some(access_key.into()),
+                })
+            }
+            views::ActionView::DeleteKey { public_key } => {
+                action::Action::DeleteKey(action::DeleteKey { public_key: public_key.to_string() })
+            }
+            views::ActionView::DeleteAccount { beneficiary_id } => {
+                action::Action::DeleteAccount(action::DeleteAccount {
+                    beneficiary_id: beneficiary_id.into(),
+                })
+            }
+            #[cfg(feature = "protocol_feature_chunk_only_producers")]
+            views::ActionView::StakeChunkOnly { stake, public_key } => {
+                action::Action::Stake(action::Stake {
+                    stake: balance(stake),
+                    public_key: public_key.to_string(),
+                    is_chunk_only: true,
+                })
+            }
+        };
+        Self { action: Some(action) }
+    }
+}
+
+impl From<views::AccessKeyView> for proto::AccessKey {
+    fn from(access_key: views::AccessKeyView) -> Self {
+        use proto::access_key;
+
+        let permission = match access_key.permission {
+            views::AccessKeyPermissionView::FunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+            } => access_key::Permission::FunctionCall(access_key::FunctionCallPermission {
+                allowance: allowance.map(balance),
+                receiver_id,
+                method_names,
+            }),
+            views::AccessKeyPermissionView::FullAccess => {
+                access_key::Permission::FullAccess(access_key::FullAccessPermission {})
+            }
+        };
+        Self { nonce: access_key.nonce, permission: Some(permission) }
+    }
+}
+
+impl From<views::ReceiptView> for proto::Receipt {
+    fn from(receipt: views::ReceiptView) -> Self {
+        use proto::receipt;
+
+        let receipt_enum = match receipt.receipt {
+            views::ReceiptEnumView::Action {
+                signer_id,
+                signer_public_key,
+                gas_price,
+                output_data_receivers,
+                input_data_ids,
+                actions,
+            } => receipt::Receipt::Action(receipt::Action {
+                signer_id: signer_id.into(),
+                signer_public_key: signer_public_key.to_string(),
+                gas_price: balance(gas_price),
+                output_data_receivers: output_data_receivers
+                    .into_iter()
+                    .map(|receiver| proto::DataReceiver {
+                        data_id: receiver.data_id.into(),
+                        receiver_id: receiver.receiver_id.into(),
+                    })
+                    .collect(),
+                input_data_ids: input_data_ids.into_iter().map(Into::into).collect(),
+                actions: actions.into_iter().map(Into::into).collect(),
+            }),
+            views::ReceiptEnumView::Data { data_id, data } => {
+                receipt::Receipt::Data(receipt::Data { data_id: data_id.into(), data })
+            }
+        };
+        Self {
+            predecessor_id: receipt.predecessor_id.into(),
+            receiver_id: receipt.receiver_id.into(),
+            receipt_id: receipt.receipt_id.into(),
+            receipt: Some(receipt_enum),
+        }
+    }
+}
+
+impl From<views::FinalExecutionOutcomeView> for proto::FinalExecutionOutcome {
+    fn from(outcome: views::FinalExecutionOutcomeView) -> Self {
+        use proto::final_execution_status::{NotStarted, Started, Status};
+
+        let status = match &outcome.status {
+            views::FinalExecutionStatus::NotStarted => Status::NotStarted(NotStarted {}),
+            views::FinalExecutionStatus::Started => Status::Started(Started {}),
+            views::FinalExecutionStatus::Failure(error) => Status::Failure(json(error)),
+            views::FinalExecutionStatus::SuccessValue(value) => {
+                Status::SuccessValue(base64_bytes(value))
+            }
+        };
+        Self {
+            status: Some(proto::FinalExecutionStatus { status: Some(status) }),
+            transaction: Some(outcome.transaction.into()),
+            transaction_outcome: Some(outcome.transaction_outcome.into()),
+            receipts_outcome: outcome.receipts_outcome.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<views::ExecutionOutcomeWithIdView> for proto::ExecutionOutcomeWithId {
+    fn from(outcome: views::ExecutionOutcomeWithIdView) -> Self {
+        Self {
+            proof: outcome.proof.into_iter().map(Into::into).collect(),
+            block_hash: outcome.block_hash.into(),
+            id: outcome.id.into(),
+            outcome: Some(outcome.outcome.into()),
+        }
+    }
+}
+
+impl From<MerklePathItem> for proto::MerklePathItem {
+    fn from(item: MerklePathItem) -> Self {
+        let direction = match item.direction {
+            Direction::Left => proto::merkle_path_item::Direction::Left,
+            Direction::Right => proto::merkle_path_item::Direction::Right,
+        };
+        Self { hash: item.hash.into(), direction: direction as i32 }
+    }
+}
+
+impl From<views::ExecutionOutcomeView> for proto::ExecutionOutcome {
+    fn from(outcome: views::ExecutionOutcomeView) -> Self {
+        use proto::execution_status::{Status, Unknown};
+
+        let status = match &outcome.status {
+            views::ExecutionStatusView::Unknown => Status::Unknown(Unknown {}),
+            views::ExecutionStatusView::Failure(error) => Status::Failure(json(error)),
+            views::ExecutionStatusView::SuccessValue(value) => {
+                Status::SuccessValue(base64_bytes(value))
+            }
+            views::ExecutionStatusView::SuccessReceiptId(receipt_id) => {
+                Status::SuccessReceiptId((*receipt_id).into())
+            }
+        };
+        Self {
+            logs: outcome.logs,
+            receipt_ids: outcome.receipt_ids.into_iter().map(Into::into).collect(),
+            gas_burnt: outcome.gas_burnt,
+            tokens_burnt: balance(outcome.tokens_burnt),
+            executor_id: outcome.executor_id.into(),
+            status: Some(proto::ExecutionStatus { status: Some(status) }),
+            metadata: json(&outcome.metadata),
+        }
+    }
+}
+
+impl From<views::AccountView> for proto::Account {
+    fn from(account: views::AccountView) -> Self {
+        Self {
+            amount: balance(account.amount),
+            locked: balance(account.locked),
+            code_hash: account.code_hash.into(),
+            storage_usage: account.storage_usage,
+        }
+    }
+}
+
+impl From<views::StateItem> for proto::StateItem {
+    fn from(item: views::StateItem) -> Self {
+        Self {
+            key: base64_bytes(&item.key),
+            value: base64_bytes(&item.value),
+            proof: item.proof.iter().map(|node| base64_bytes(node)).collect(),
+        }
+    }
+}
+
+impl From<views::AccessKeyInfoView> for proto::AccessKeyInfo {
+    fn from(info: views::AccessKeyInfoView) -> Self {
+        Self { public_key: info.public_key.to_string(), access_key: Some(info.access_key.into()) }
+    }
+}
+
+/// Responses to queries fail to convert if the query returned another kind of response.
+impl TryFrom<QueryResponse> for proto::ViewAccountResponse {
+    type Error = QueryResponseKind;
+
+    fn try_from(response: QueryResponse) -> Result<Self, Self::Error> {
+        match response.kind {
+            QueryResponseKind::ViewAccount(account) => Ok(Self {
+                account: Some(account.into()),
+                block_height: response.block_height,
+                block_hash: response.block_hash.into(),
+            }),
+            kind => Err(kind),
+        }
+    }
+}
+
+impl TryFrom<QueryResponse> for proto::ViewCodeResponse {
+    type Error = QueryResponseKind;
+
+    fn try_from(response: QueryResponse) -> Result<Self, Self::Error> {
+        match response.kind {
+            QueryResponseKind::ViewCode(code) => Ok(Self {
+                code: code.code,
+                hash: code.hash.into(),
+                block_height: response.block_height,
+                block_hash: response.block_hash.into(),
+            }),
+            kind => Err(kind),
+        }
+    }
+}
+
+impl TryFrom<QueryResponse> for proto::ViewStateResponse {
+    type Error = QueryResponseKind;
+
+    fn try_from(response: QueryResponse) -> Result<Self, Self::Error> {
+        match response.kind {
+            QueryResponseKind::ViewState(state) => Ok(Self {
+                values: state.values.into_iter().map(Into::into).collect(),
+                proof: state.proof.iter().map(|node| base64_bytes(node)).collect(),
+                block_height: response.block_height,
+                block_hash: response.block_hash.into(),
+            }),
+            kind => Err(kind),
+        }
+    }
+}
+
+impl TryFrom<QueryResponse> for proto::ViewAccessKeyResponse {
+    type Error = QueryResponseKind;
+
+    fn try_from(response: QueryResponse) -> Result<Self, Self::Error> {
+        match response.kind {
+            QueryResponseKind::AccessKey(access_key) => Ok(Self {
+                access_key: Some(access_key.into()),
+                block_height: response.block_height,
+                block_hash: response.block_hash.into(),
+            }),
+            kind => Err(kind),
+        }
+    }
+}
+
+impl TryFrom<QueryResponse> for proto::ViewAccessKeyListResponse {
+    type Error = QueryResponseKind;
+
+    fn try_from(response: QueryResponse) -> Result<Self, Self::Error> {
+        match response.kind {
+            QueryResponseKind::AccessKeyList(list) => Ok(Self {
+                keys: list.keys.into_iter().map(Into::into).collect(),
+                block_height: response.block_height,
+                block_hash: response.block_hash.into(),
+            }),
+            kind => Err(kind),
+        }
+    }
+}
+
+impl TryFrom<QueryResponse> for proto::CallFunctionResponse {
+    type Error = QueryResponseKind;
+
+    fn try_from(response: QueryResponse) -> Result<Self, Self::Error> {
+        match response.kind {
+            QueryResponseKind::CallResult(result) => Ok(Self {
+                result: result.result,
+                logs: result.logs,
+                block_height: response.block_height,
+                block_hash: response.block_hash.into(),
+            }),
+            kind => Err(kind),
+        }
+    }
+}
+
+impl From<GasPriceView> for proto::GasPriceResponse {
+    fn from(gas_price: GasPriceView) -> Self {
+        Self { gas_price: balance(gas_price.gas_price) }
+    }
+}
+
+impl From<EpochValidatorInfo> for proto::EpochValidatorInfo {
+    fn from(info: EpochValidatorInfo) -> Self {
+        use proto::epoch_validator_info::{CurrentValidator, Kickout, NextValidator};
+
+        Self {
+            current_validators: info
+                .current_validators
+                .into_iter()
+                .map(|validator| CurrentValidator {
+                    account_id: validator.account_id.into(),
+                    public_key: validator.public_key.to_string(),
+                    is_slashed: validator.is_slashed,
+                    stake: balance(validator.stake),
+                    shards: validator.shards,
+                    num_produced_blocks: validator.num_produced_blocks,
+                    num_expected_blocks: validator.num_expected_blocks,
+                })
+                .collect(),
+            next_validators: info
+                .next_validators
+                .into_iter()
+                .map(|validator| NextValidator {
+                    account_id: validator.account_id.into(),
+                    public_key: validator.public_key.to_string(),
+                    stake: balance(validator.stake),
+                    shards: validator.shards,
+                })
+                .collect(),
+            current_fishermen: info.current_fishermen.into_iter().map(Into::into).collect(),
+            next_fishermen: info.next_fishermen.into_iter().map(Into::into).collect(),
+            current_proposals: info.current_proposals.into_iter().map(Into::into).collect(),
+            prev_epoch_kickout: info
+                .prev_epoch_kickout
+                .into_iter()
+                .map(|kickout| Kickout {
+                    account_id: kickout.account_id.into(),
+                    reason: json(&kickout.reason),
+                })
+                .collect(),
+            epoch_start_height: info.epoch_start_height,
+            epoch_height: info.epoch_height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use serde::Serialize;
+
+    use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
+    use near_primitives::block::{genesis_chunks, Block};
+    use near_primitives::challenge::SlashedValidator;
+    use near_primitives::errors::{InvalidTxError, TxExecutionError};
+    use near_primitives::hash::{hash, CryptoHash};
+    use near_primitives::merkle::{Direction, MerklePathItem};
+    use near_primitives::serialize::to_base64;
+    use near_primitives::transaction::SignedTransaction;
+    use near_primitives::types::{AccountId, Balance, ValidatorKickoutReason};
+    use near_primitives::utils::from_timestamp;
+    use near_primitives::version::{Version, PROTOCOL_VERSION};
+    use near_primitives::views::validator_stake_view::ValidatorStakeView;
+    use near_primitives::views::{self, ExecutionStatusView, QueryResponse, QueryResponseKind};
+
+    use crate::proto;
+
+    // Conversions of messages back to views, which only the round trip tests need. They build
+    // the views field by field, so that a field added to a view doesn't compile until it is
+    // added to `rpc.proto` and to the conversions to messages.
+
+    fn account_id(account_id: String) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    fn public_key(public_key: String) -> PublicKey {
+        public_key.parse().unwrap()
+    }
+
+    fn signature(signature: String) -> Signature {
+        signature.parse().unwrap()
+    }
+
+    fn crypto_hash(bytes: Vec<u8>) -> CryptoHash {
+        CryptoHash::try_from(bytes).unwrap()
+    }
+
+    fn balance(balance: String) -> Balance {
+        balance.parse().unwrap()
+    }
+
+    fn from_json<T: serde::de::DeserializeOwned>(json: String) -> T {
+        serde_json::from_str(&json).unwrap()
+    }
+
+    impl From<proto::StatusResponse> for views::StatusResponse {
+        fn from(status: proto::StatusResponse) -> Self {
+            let sync_info = status.sync_info.unwrap();
+            Self {
+                version: Version { version: status.version, build: status.build },
+                chain_id: status.chain_id,
+                protocol_version: status.protocol_version,
+                latest_protocol_version: status.latest_protocol_version,
+                rpc_addr: status.rpc_addr,
+                validators: status
+                    .validators
+                    .into_iter()
+                    .map(|validator| views::ValidatorInfo {
+                        account_id: account_id(validator.account_id),
+                        is_slashed: validator.is_slashed,
+                    })
+                    .collect(),
+                sync_info: views::StatusSyncInfo {
+                    latest_block_hash: crypto_hash(sync_info.latest_block_hash),
+                    latest_block_height: sync_info.latest_block_height,
+                    latest_state_root: crypto_hash(sync_info.latest_state_root),
+                    latest_block_time: from_timestamp(sync_info.latest_block_time),
+                    syncing: sync_info.syncing,
+                    earliest_block_hash: sync_info.earliest_block_hash.map(crypto_hash),
+                    earliest_block_height: sync_info.earliest_block_height,
+                    earliest_block_time: sync_info.earliest_block_time.map(from_timestamp),
+                },
+                validator_account_id: status.validator_account_id.map(account_id),
+            }
+        }
+    }
+
+    impl From<proto::Block> for views::BlockView {
+        fn from(block: proto::Block) -> Self {
+            Self {
+                author: account_id(block.author),
+                header: block.header.unwrap().into(),
+                chunks: block.chunks.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<proto::BlockHeader> for views::BlockHeaderView {
+        fn from(header: proto::BlockHeader) -> Self {
+            Self {
+                height: header.height,
+                prev_height: header.prev_height,
+                epoch_id: crypto_hash(header.epoch_id),
+                next_epoch_id: crypto_hash(header.next_epoch_id),
+                hash: crypto_hash(header.hash),
+                prev_hash: crypto_hash(header.prev_hash),
+                prev_state_root: crypto_hash(header.prev_state_root),
+                chunk_receipts_root: crypto_hash(header.chunk_receipts_root),
+                chunk_headers_root: crypto_hash(header.chunk_headers_root),
+                chunk_tx_root: crypto_hash(header.chunk_tx_root),
+                outcome_root: crypto_hash(header.outcome_root),
+                chunks_included: header.chunks_included,
+                challenges_root: crypto_hash(header.challenges_root),
+                // The legacy field has the same value.
+                timestamp: header.timestamp_nanosec,
+                timestamp_nanosec: header.timestamp_nanosec,
+                random_value: crypto_hash(header.random_value),
+                validator_proposals: header
+                    .validator_proposals
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                chunk_mask: header.chunk_mask,
+                gas_price: balance(header.gas_price),
+                block_ordinal: header.block_ordinal,
+                rent_paid: 0,
+                validator_reward: 0,
+                total_supply: balance(header.total_supply),
+                challenges_result: header
+                    .challenges_result
+                    .into_iter()
+                    .map(|validator| {
+                        SlashedValidator::new(
+                            account_id(validator.account_id),
+                            validator.is_double_sign,
+                        )
+                    })
+                    .collect(),
+                last_final_block: crypto_hash(header.last_final_block),
+                last_ds_final_block: crypto_hash(header.last_ds_final_block),
+                next_bp_hash: crypto_hash(header.next_bp_hash),
+                block_merkle_root: crypto_hash(header.block_merkle_root),
+                epoch_sync_data_hash: header.epoch_sync_data_hash.map(crypto_hash),
+                approvals: header
+                    .approvals
+                    .into_iter()
+                    .map(|approval| approval.signature.map(signature))
+                    .collect(),
+                signature: signature(header.signature),
+                latest_protocol_version: header.latest_protocol_version,
+            }
+        }
+    }
+
+    impl From<proto::ValidatorStake> for ValidatorStakeView {
+        fn from(stake: proto::ValidatorStake) -> Self {
+            #[cfg(feature = "protocol_feature_chunk_only_producers")]
+            if stake.is_chunk_only {
+                return Self::V2(views::validator_stake_view::ValidatorStakeViewV2 {
+                    account_id: account_id(stake.account_id),
+                    public_key: public_key(stake.public_key),
+                    stake: balance(stake.stake),
+                    is_chunk_only: true,
+                });
+            }
+            Self::V1(views::ValidatorStakeViewV1 {
+                account_id: account_id(stake.account_id),
+                public_key: public_key(stake.public_key),
+                stake: balance(stake.stake),
+            })
+        }
+    }
+
+    impl From<proto::ChunkHeader> for views::ChunkHeaderView {
+        fn from(header: proto::ChunkHeader) -> Self {
+            Self {
+                chunk_hash: crypto_hash(header.chunk_hash),
+                prev_block_hash: crypto_hash(header.prev_block_hash),
+                outcome_root: crypto_hash(header.outcome_root),
+                prev_state_root: crypto_hash(header.prev_state_root),
+                encoded_merkle_root: crypto_hash(header.encoded_merkle_root),
+                encoded_length: header.encoded_length,
+                height_created: header.height_created,
+                height_included: header.height_included,
+                shard_id: header.shard_id,
+                gas_used: header.gas_used,
+                gas_limit: header.gas_limit,
+                rent_paid: 0,
+                validator_reward: 0,
+                balance_burnt: balance(header.balance_burnt),
+                outgoing_receipts_root: crypto_hash(header.outgoing_receipts_root),
+                tx_root: crypto_hash(header.tx_root),
+                validator_proposals: header
+                    .validator_proposals
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                signature: signature(header.signature),
+            }
+        }
+    }
+
+    impl From<proto::Chunk> for views::ChunkView {
+        fn from(chunk: proto::Chunk) -> Self {
+            Self {
+                author: account_id(chunk.author),
+                header: chunk.header.unwrap().into(),
+                transactions: chunk.transactions.into_iter().map(Into::into).collect(),
+                receipts: chunk.receipts.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<proto::SignedTransaction> for views::SignedTransactionView {
+        fn from(transaction: proto::SignedTransaction) -> Self {
+            Self {
+                signer_id: account_id(transaction.signer_id),
+                public_key: public_key(transaction.public_key),
+                nonce: transaction.nonce,
+                receiver_id: account_id(transaction.receiver_id),
+                actions: transaction.actions.into_iter().map(Into::into).collect(),
+                signature: signature(transaction.signature),
+                hash: crypto_hash(transaction.hash),
+            }
+        }
+    }
+
+    impl From<proto::Action> for views::ActionView {
+        fn from(action: proto::Action) -> Self {
+            use proto::action::Action;
+
+            match action.action.unwrap() {
+                Action::CreateAccount(_) => Self::CreateAccount,
+                Action::DeployContract(action) => {
+                    Self::DeployContract { code: to_base64(&action.code_hash) }
+                }
+                Action::FunctionCall(action) => Self::FunctionCall {
+                    method_name: action.method_name,
+                    args: to_base64(&action.args),
+                    gas: action.gas,
+                    deposit: balance(action.deposit),
+                },
+                Action::Transfer(action) => Self::Transfer { deposit: balance(action.deposit) },
+                #[cfg(feature = "protocol_feature_chunk_only_producers")]
+                Action::Stake(action) if action.is_chunk_only => Self::StakeChunkOnly {
+                    stake: balance(action.stake),
+                    public_key: public_key(action.public_key),
+                },
+                Action::Stake(action) => Self::Stake {
+                    stake: balance(action.stake),
+                    public_key: public_key(action.public_key),
+                },
+                Action::AddKey(action) => Self::AddKey {
+                    public_key: public_key(action.public_key),
+                    access_key: action.access_key.unwrap().into(),
+                },
+                Action::DeleteKey(action) => {
+                    Self::DeleteKey { public_key: public_key(action.public_key) }
+                }
+                Action::DeleteAccount(action) => {
+                    Self::DeleteAccount { beneficiary_id: account_id(action.beneficiary_id) }
+                }
+            }
+        }
+    }
+
+    impl From<proto::AccessKey> for views::AccessKeyView {
+        fn from(access_key: proto::AccessKey) -> Self {
+            use proto::access_key::Permission;
+
+            let permission = match access_key.permission.unwrap() {
+                Permission::FunctionCall(permission) => {
+                    views::AccessKeyPermissionView::FunctionCall {
+                        allowance: permission.allowance.map(balance),
+                        receiver_id: permission.receiver_id,
+                        method_names: permission.method_names,
+                    }
+                }
+                Permission::FullAccess(_) => views::AccessKeyPermissionView::FullAccess,
+            };
+            Self { nonce: access_key.nonce, permission }
+        }
+    }
+
+    impl From<proto::Receipt> for views::ReceiptView {
+        fn from(receipt: proto::Receipt) -> Self {
+            use proto::receipt::Receipt;
+
+            let receipt_enum = match receipt.receipt.unwrap() {
+                Receipt::Action(action) => views::ReceiptEnumView::Action {
+                    signer_id: account_id(action.signer_id),
+                    signer_public_key: public_key(action.signer_public_key),
+                    gas_price: balance(action.gas_price),
+                    output_data_receivers: action
+                        .output_data_receivers
+                        .into_iter()
+                        .map(|receiver| views::DataReceiverView {
+                            data_id: crypto_hash(receiver.data_id),
+                            receiver_id: account_id(receiver.receiver_id),
+                        })
+                        .collect(),
+                    input_data_ids: action.input_data_ids.into_iter().map(crypto_hash).collect(),
+                    actions: action.actions.into_iter().map(Into::into).collect(),
+                },
+                Receipt::Data(data) => views::ReceiptEnumView::Data {
+                    data_id: crypto_hash(data.data_id),
+                    data: data.data,
+                },
+            };
+            Self {
+                predecessor_id: account_id(receipt.predecessor_id),
+                receiver_id: account_id(receipt.receiver_id),
+                receipt_id: crypto_hash(receipt.receipt_id),
+                receipt: receipt_enum,
+            }
+        }
+    }
+
+    impl From<proto::FinalExecutionOutcome> for views::FinalExecutionOutcomeView {
+        fn from(outcome: proto::FinalExecutionOutcome) -> Self {
+            use proto::final_execution_status::Status;
+
+            let status = match outcome.status.unwrap().status.unwrap() {
+                Status::NotStarted(_) => views::FinalExecutionStatus::NotStarted,
+                Status::Started(_) => views::FinalExecutionStatus::Started,
+                Status::Failure(error) => views::FinalExecutionStatus::Failure(from_json(error)),
+                Status::SuccessValue(value) => {
+                    views::FinalExecutionStatus::SuccessValue(to_base64(&value))
+                }
+            };
+            Self {
+                status,
+                transaction: outcome.transaction.unwrap().into(),
+                transaction_outcome: outcome.transaction_outcome.unwrap().into(),
+                receipts_outcome: outcome.receipts_outcome.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<proto::ExecutionOutcomeWithId> for views::ExecutionOutcomeWithIdView {
+        fn from(outcome: proto::ExecutionOutcomeWithId) -> Self {
+            Self {
+                proof: outcome
+                    .proof
+                    .into_iter()
+                    .map(|item| MerklePathItem {
+                        hash: crypto_hash(item.hash),
+                        direction: match proto::merkle_path_item::Direction::from_i32(
+                            item.direction,
+                        )
+                        .unwrap()
+                        {
+                            proto::merkle_path_item::Direction::Left => Direction::Left,
+                            proto::merkle_path_item::Direction::Right => Direction::Right,
+                        },
+                    })
+                    .collect(),
+                block_hash: crypto_hash(outcome.block_hash),
+                id: crypto_hash(outcome.id),
+                outcome: outcome.outcome.unwrap().into(),
+            }
+        }
+    }
+
+    impl From<proto::ExecutionOutcome> for views::ExecutionOutcomeView {
+        fn from(outcome: proto::ExecutionOutcome) -> Self {
+            use proto::execution_status::Status;
+
+            let status = match outcome.status.unwrap().status.unwrap() {
+                Status::Unknown(_) => ExecutionStatusView::Unknown,
+                Status::Failure(error) => ExecutionStatusView::Failure(from_json(error)),
+                Status::SuccessValue(value) => ExecutionStatusView::SuccessValue(to_base64(&value)),
+                Status::SuccessReceiptId(receipt_id) => {
+                    ExecutionStatusView::SuccessReceiptId(crypto_hash(receipt_id))
+                }
+            };
+            Self {
+                logs: outcome.logs,
+                receipt_ids: outcome.receipt_ids.into_iter().map(crypto_hash).collect(),
+                gas_burnt: outcome.gas_burnt,
+                tokens_burnt: balance(outcome.tokens_burnt),
+                executor_id: account_id(outcome.executor_id),
+                status,
+                metadata: from_json(outcome.metadata),
+            }
+        }
+    }
+
+    impl From<proto::Account> for views::AccountView {
+        fn from(account: proto::Account) -> Self {
+            Self {
+                amount: balance(account.amount),
+                locked: balance(account.locked),
+                code_hash: crypto_hash(account.code_hash),
+                storage_usage: account.storage_usage,
+                storage_paid_at: 0,
+            }
+        }
+    }
+
+    impl From<proto::StateItem> for views::StateItem {
+        fn from(item: proto::StateItem) -> Self {
+            Self {
+                key: to_base64(&item.key),
+                value: to_base64(&item.value),
+                proof: item.proof.iter().map(to_base64).collect(),
+            }
+        }
+    }
+
+    impl From<proto::AccessKeyInfo> for views::AccessKeyInfoView {
+        fn from(info: proto::AccessKeyInfo) -> Self {
+            Self {
+                public_key: public_key(info.public_key),
+                access_key: info.access_key.unwrap().into(),
+            }
+        }
+    }
+
+    impl From<proto::ViewAccountResponse> for QueryResponse {
+        fn from(response: proto::ViewAccountResponse) -> Self {
+            Self {
+                kind: QueryResponseKind::ViewAccount(response.account.unwrap().into()),
+                block_height: response.block_height,
+                block_hash: crypto_hash(response.block_hash),
+            }
+        }
+    }
+
+    impl From<proto::ViewCodeResponse> for QueryResponse {
+        fn from(response: proto::ViewCodeResponse) -> Self {
+            let code =
+                views::ContractCodeView { code: response.code, hash: crypto_hash(response.hash) };
+            Self {
+                kind: QueryResponseKind::ViewCode(code),
+                block_height: response.block_height,
+                block_hash: crypto_hash(response.block_hash),
+            }
+        }
+    }
+
+    impl From<proto::ViewStateResponse> for QueryResponse {
+        fn from(response: proto::ViewStateResponse) -> Self {
+            let state = views::ViewStateResult {
+                values: response.values.into_iter().map(Into::into).collect(),
+                proof: response.proof.iter().map(to_base64).collect(),
+            };
+            Self {
+                kind: QueryResponseKind::ViewState(state),
+                block_height: response.block_height,
+                block_hash: crypto_hash(response.block_hash),
+            }
+        }
+    }
+
+    impl From<proto::ViewAccessKeyResponse> for QueryResponse {
+        fn from(response: proto::ViewAccessKeyResponse) -> Self {
+            Self {
+                kind: QueryResponseKind::AccessKey(response.access_key.unwrap().into()),
+                block_height: response.block_height,
+                block_hash: crypto_hash(response.block_hash),
+            }
+        }
+    }
+
+    impl From<proto::ViewAccessKeyListResponse> for QueryResponse {
+        fn from(response: proto::ViewAccessKeyListResponse) -> Self {
+            let list =
+                views::AccessKeyList { keys: response.keys.into_iter().map(Into::into).collect() };
+            Self {
+                kind: QueryResponseKind::AccessKeyList(list),
+                block_height: response.block_height,
+                block_hash: crypto_hash(response.block_hash),
+            }
+        }
+    }
+
+    impl From<proto::CallFunctionResponse> for QueryResponse {
+        fn from(response: proto::CallFunctionResponse) -> Self {
+            let result = views::CallResult { result: response.result, logs: response.logs };
+            Self {
+                kind: QueryResponseKind::CallResult(result),
+                block_height: response.block_height,
+                block_hash: crypto_hash(response.block_hash),
+            }
+        }
+    }
+
+    impl From<proto::GasPriceResponse> for views::GasPriceView {
+        fn from(response: proto::GasPriceResponse) -> Self {
+            Self { gas_price: balance(response.gas_price) }
+        }
+    }
+
+    impl From<proto::EpochValidatorInfo> for views::EpochValidatorInfo {
+        fn from(info: proto::EpochValidatorInfo) -> Self {
+            Self {
+                current_validators: info
+                    .current_validators
+                    .into_iter()
+                    .map(|validator| views::CurrentEpochValidatorInfo {
+                        account_id: account_id(validator.account_id),
+                        public_key: public_key(validator.public_key),
+                        is_slashed: validator.is_slashed,
+                        stake: balance(validator.stake),
+                        shards: validator.shards,
+                        num_produced_blocks: validator.num_produced_blocks,
+                        num_expected_blocks: validator.num_expected_blocks,
+                    })
+                    .collect(),
+                next_validators: info
+                    .next_validators
+                    .into_iter()
+                    .map(|validator| views::NextEpochValidatorInfo {
+                        account_id: account_id(validator.account_id),
+                        public_key: public_key(validator.public_key),
+                        stake: balance(validator.stake),
+                        shards: validator.shards,
+                    })
+                    .collect(),
+                current_fishermen: info.current_fishermen.into_iter().map(Into::into).collect(),
+                next_fishermen: info.next_fishermen.into_iter().map(Into::into).collect(),
+                current_proposals: info.current_proposals.into_iter().map(Into::into).collect(),
+                prev_epoch_kickout: info
+                    .prev_epoch_kickout
+                    .into_iter()
+                    .map(|kickout| views::ValidatorKickoutView {
+                        account_id: account_id(kickout.account_id),
+                        reason: from_json(kickout.reason),
+                    })
+                    .collect(),
+                epoch_start_height: info.epoch_start_height,
+                epoch_height: info.epoch_height,
+            }
+        }
+    }
+
+    /// Converts the view to a message, encodes and decodes it, and converts it back.
+    fn round_trip<V, M>(view: V) -> V
+    where
+        M: From<V> + Into<V> + Message + Default,
+    {
+        let message = M::from(view);
+        M::decode(message.encode_to_vec().as_slice()).unwrap().into()
+    }
+
+    /// Views without `PartialEq` are compared as JSON.
+    fn assert_round_trip<V, M>(view: V)
+    where
+        V: Serialize,
+        M: From<V> + Into<V> + Message + Default,
+    {
+        let expected = serde_json::to_value(&view).unwrap();
+        assert_eq!(serde_json::to_value(&round_trip::<V, M>(view)).unwrap(), expected);
+    }
+
+    fn assert_query_round_trip<M>(kind: QueryResponseKind)
+    where
+        M: TryFrom<QueryResponse> + Message + Default,
+        QueryResponse: From<M>,
+        <M as TryFrom<QueryResponse>>::Error: std::fmt::Debug,
+    {
+        let response = QueryResponse { kind, block_height: 7, block_hash: hash(b"block") };
+        let message = M::try_from(response.clone()).unwrap();
+        let decoded = M::decode(message.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(QueryResponse::from(decoded), response);
+    }
+
+    fn signer(account_id: &str) -> InMemorySigner {
+        InMemorySigner::from_seed(account_id.parse().unwrap(), KeyType::ED25519, account_id)
+    }
+
+    fn validator_stake(account_id: &str, stake: Balance) -> ValidatorStakeView {
+        ValidatorStakeView::V1(views::ValidatorStakeViewV1 {
+            account_id: account_id.parse().unwrap(),
+            public_key: signer(account_id).public_key(),
+            stake,
+        })
+    }
+
+    fn block() -> Block {
+        let chunks = genesis_chunks(vec![hash(b"state")], 2, 1_000_000, 10, PROTOCOL_VERSION);
+        Block::genesis(
+            PROTOCOL_VERSION,
+            chunks.into_iter().map(|chunk| chunk.take_header()).collect(),
+            from_timestamp(1_600_000_000_123_456_789),
+            10,
+            100,
+            1_000_000_000,
+            hash(b"next block producers"),
+        )
+    }
+
+    /// Actions of all kinds.
+    fn actions() -> Vec<views::ActionView> {
+        let public_key = signer("alice.near").public_key();
+        let function_call = views::AccessKeyView {
+            nonce: 3,
+            permission: views::AccessKeyPermissionView::FunctionCall {
+                allowance: Some(10u128.pow(30)),
+                receiver_id: "contract.near".to_string(),
+                method_names: vec!["method".to_string()],
+            },
+        };
+        vec![
+            views::ActionView::CreateAccount,
+            views::ActionView::DeployContract { code: to_base64(&hash(b"code")) },
+            views::ActionView::FunctionCall {
+                method_name: "method".to_string(),
+                args: to_base64(b"{}"),
+                gas: 100,
+                deposit: 1,
+            },
+            views::ActionView::Transfer { deposit: 10u128.pow(30) },
+            views::ActionView::Stake { stake: 5, public_key: public_key.clone() },
+            views::ActionView::AddKey { public_key: public_key.clone(), access_key: function_call },
+            views::ActionView::AddKey {
+                public_key: public_key.clone(),
+                access_key: views::AccessKeyView {
+                    nonce: 0,
+                    permission: views::AccessKeyPermissionView::FullAccess,
+                },
+            },
+            views::ActionView::DeleteKey { public_key },
+            views::ActionView::DeleteAccount { beneficiary_id: "bob.near".parse().unwrap() },
+        ]
+    }
+
+    fn receipts() -> Vec<views::ReceiptView> {
+        vec![
+            views::ReceiptView {
+                predecessor_id: "alice.near".parse().unwrap(),
+                receiver_id: "bob.near".parse().unwrap(),
+                receipt_id: hash(b"action receipt"),
+                receipt: views::ReceiptEnumView::Action {
+                    signer_id: "alice.near".parse().unwrap(),
+                    signer_public_key: signer("alice.near").public_key(),
+                    gas_price: 100_000_000,
+                    output_data_receivers: vec![views::DataReceiverView {
+                        data_id: hash(b"data"),
+                        receiver_id: "carol.near".parse().unwrap(),
+                    }],
+                    input_data_ids: vec![hash(b"input")],
+                    actions: actions(),
+                },
+            },
+            views::ReceiptView {
+                predecessor_id: "bob.near".parse().unwrap(),
+                receiver_id: "carol.near".parse().unwrap(),
+                receipt_id: hash(b"data receipt"),
+                receipt: views::ReceiptEnumView::Data {
+                    data_id: hash(b"data"),
+                    data: Some(b"value".to_vec()),
+                },
+            },
+            views::ReceiptView {
+                predecessor_id: "bob.near".parse().unwrap(),
+                receiver_id: "carol.near".parse().unwrap(),
+                receipt_id: hash(b"empty data receipt"),
+                receipt: views::ReceiptEnumView::Data { data_id: hash(b"no data"), data: None },
+            },
+        ]
+    }
+
+    fn transaction() -> views::SignedTransactionView {
+        let signer = signer("alice.near");
+        let mut transaction: views::SignedTransactionView = SignedTransaction::send_money(
+            1,
+            signer.account_id.clone(),
+            "bob.near".parse().unwrap(),
+            &signer,
+            10,
+            CryptoHash::default(),
+        )
+        .into();
+        transaction.actions = actions();
+        transaction
+    }
+
+    fn outcome(status: ExecutionStatusView) -> views::ExecutionOutcomeWithIdView {
+        views::ExecutionOutcomeWithIdView {
+            proof: vec![
+                MerklePathItem { hash: hash(b"left"), direction: Direction::Left },
+                MerklePathItem { hash: hash(b"right"), direction: Direction::Right },
+            ],
+            block_hash: hash(b"block"),
+            id: hash(b"outcome"),
+            outcome: views::ExecutionOutcomeView {
+                logs: vec!["log".to_string()],
+                receipt_ids: vec![hash(b"receipt")],
+                gas_burnt: 100,
+                tokens_burnt: 10u128.pow(25),
+                executor_id: "alice.near".parse().unwrap(),
+                status,
+                metadata: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let status = views::StatusResponse {
+            version: Version { version: "1.2.3".to_string(), build: "abc".to_string() },
+            chain_id: "testnet".to_string(),
+            protocol_version: 49,
+            latest_protocol_version: 50,
+            rpc_addr: Some("0.0.0.0:3030".to_string()),
+            validators: vec![views::ValidatorInfo {
+                account_id: "alice.near".parse().unwrap(),
+                is_slashed: true,
+            }],
+            sync_info: views::StatusSyncInfo {
+                latest_block_hash: hash(b"latest"),
+                latest_block_height: 100,
+                latest_state_root: hash(b"state"),
+                latest_block_time: from_timestamp(1_600_000_000_123_456_789),
+                syncing: false,
+                earliest_block_hash: Some(hash(b"earliest")),
+                earliest_block_height: Some(1),
+                earliest_block_time: Some(from_timestamp(1_500_000_000_000_000_001)),
+            },
+            validator_account_id: Some("alice.near".parse().unwrap()),
+        };
+        assert_round_trip::<_, proto::StatusResponse>(status);
+    }
+
+    #[test]
+    fn test_block_round_trip() {
+        let mut block = views::BlockView::from_author_block("alice.near".parse().unwrap(), block());
+        let header = &mut block.header;
+        header.prev_height = Some(9);
+        header.validator_proposals = vec![validator_stake("bob.near", 10u128.pow(30))];
+        header.challenges_result = vec![SlashedValidator::new("carol.near".parse().unwrap(), true)];
+        header.epoch_sync_data_hash = Some(hash(b"epoch sync data"));
+        header.approvals = vec![None, Some(signer("bob.near").sign(b"approval"))];
+        header.signature = signer("alice.near").sign(b"block");
+        block.chunks[1].validator_proposals = vec![validator_stake("dan.near", 1)];
+        block.chunks[1].signature = signer("alice.near").sign(b"chunk");
+        assert_round_trip::<_, proto::Block>(block);
+    }
+
+    #[test]
+    fn test_chunk_round_trip() {
+        let chunk = views::ChunkView {
+            author: "alice.near".parse().unwrap(),
+            header: views::BlockView::from_author_block("alice.near".parse().unwrap(), block())
+                .chunks
+                .remove(0),
+            transactions: vec![transaction()],
+            receipts: receipts(),
+        };
+        assert_round_trip::<_, proto::Chunk>(chunk);
+    }
+
+    #[test]
+    fn test_receipt_round_trip() {
+        for receipt in receipts() {
+            assert_eq!(round_trip::<_, proto::Receipt>(receipt.clone()), receipt);
+        }
+    }
+
+    #[test]
+    fn test_final_execution_outcome_round_trip() {
+        let failure = TxExecutionError::InvalidTxError(InvalidTxError::InvalidSignature);
+        let statuses = vec![
+            views::FinalExecutionStatus::NotStarted,
+            views::FinalExecutionStatus::Started,
+            views::FinalExecutionStatus::Failure(failure.clone()),
+            views::FinalExecutionStatus::SuccessValue(to_base64(b"value")),
+        ];
+        for status in statuses {
+            let outcome = views::FinalExecutionOutcomeView {
+                status,
+                transaction: transaction(),
+                transaction_outcome: outcome(ExecutionStatusView::SuccessReceiptId(hash(b"r"))),
+                receipts_outcome: vec![
+                    outcome(ExecutionStatusView::Unknown),
+                    outcome(ExecutionStatusView::Failure(failure.clone())),
+                    outcome(ExecutionStatusView::SuccessValue(to_base64(b"value"))),
+                ],
+            };
+            assert_eq!(round_trip::<_, proto::FinalExecutionOutcome>(outcome.clone()), outcome);
+        }
+    }
+
+    #[test]
+    fn test_query_round_trip() {
+        let access_key = views::AccessKeyView {
+            nonce: 1,
+            permission: views::AccessKeyPermissionView::FunctionCall {
+                allowance: None,
+                receiver_id: "contract.near".to_string(),
+                method_names: vec![],
+            },
+        };
+        assert_query_round_trip::<proto::ViewAccountResponse>(QueryResponseKind::ViewAccount(
+            views::AccountView {
+                amount: 10u128.pow(30),
+                locked: 10u128.pow(29),
+                code_hash: hash(b"code"),
+                storage_usage: 100,
+                storage_paid_at: 0,
+            },
+        ));
+        assert_query_round_trip::<proto::ViewCodeResponse>(QueryResponseKind::ViewCode(
+            views::ContractCodeView { code: b"code".to_vec(), hash: hash(b"code") },
+        ));
+        assert_query_round_trip::<proto::ViewStateResponse>(QueryResponseKind::ViewState(
+            views::ViewStateResult {
+                values: vec![views::StateItem {
+                    key: to_base64(b"key"),
+                    value: to_base64(b"value"),
+                    proof: vec![to_base64(b"node")],
+                }],
+                proof: vec![to_base64(b"root")],
+            },
+        ));
+        assert_query_round_trip::<proto::ViewAccessKeyResponse>(QueryResponseKind::AccessKey(
+            access_key.clone(),
+        ));
+        assert_query_round_trip::<proto::ViewAccessKeyListResponse>(
+            QueryResponseKind::AccessKeyList(views::AccessKeyList {
+                keys: vec![views::AccessKeyInfoView {
+                    public_key: signer("alice.near").public_key(),
+                    access_key,
+                }],
+            }),
+        );
+        assert_query_round_trip::<proto::CallFunctionResponse>(QueryResponseKind::CallResult(
+            views::CallResult { result: b"result".to_vec(), logs: vec!["log".to_string()] },
+        ));
+        // Other kinds of responses don't convert.
+        let response = QueryResponse {
+            kind: QueryResponseKind::CallResult(Default::default()),
+            block_height: 1,
+            block_hash: CryptoHash::default(),
+        };
+        assert!(proto::ViewAccountResponse::try_from(response).is_err());
+    }
+
+    #[test]
+    fn test_gas_price_round_trip() {
+        assert_round_trip::<_, proto::GasPriceResponse>(views::GasPriceView {
+            gas_price: 100_000_000,
+        });
+    }
+
+    #[test]
+    fn test_validators_round_trip() {
+        let public_key = signer("alice.near").public_key();
+        let info = views::EpochValidatorInfo {
+            current_validators: vec![views::CurrentEpochValidatorInfo {
+                account_id: "alice.near".parse().unwrap(),
+                public_key: public_key.clone(),
+                is_slashed: false,
+                stake: 10u128.pow(30),
+                shards: vec![0, 1],
+                num_produced_blocks: 9,
+                num_expected_blocks: 10,
+            }],
+            next_validators: vec![views::NextEpochValidatorInfo {
+                account_id: "bob.near".parse().unwrap(),
+                public_key,
+                stake: 10u128.pow(30),
+                shards: vec![1],
+            }],
+            current_fishermen: vec![validator_stake("carol.near", 1)],
+            next_fishermen: vec![validator_stake("dan.near", 2)],
+            current_proposals: vec![validator_stake("erin.near", 3)],
+            prev_epoch_kickout: vec![
+                views::ValidatorKickoutView {
+                    account_id: "frank.near".parse().unwrap(),
+                    reason: ValidatorKickoutReason::NotEnoughStake {
+                        stake: 1,
+                        threshold: 10u128.pow(30),
+                    },
+                },
+                views::ValidatorKickoutView {
+                    account_id: "grace.near".parse().unwrap(),
+                    reason: ValidatorKickoutReason::NotEnoughBlocks { produced: 1, expected: 10 },
+                },
+            ],
+            epoch_start_height: 100,
+            epoch_height: 2,
+        };
+        assert_eq!(round_trip::<_, proto::EpochValidatorInfo>(info.clone()), info);
+    }
+
+    #[test]
+    fn test_signed_transaction() {
+        let signer =
+            InMemorySigner::from_seed("alice.near".parse().unwrap(), KeyType::ED25519, "a");
+        let transaction = SignedTransaction::call(
+            1,
+            "alice.near".parse().unwrap(),
+            "bob.near".parse().unwrap(),
+            &signer,
+            10,
+            "method".to_string(),
+            vec![1, 2, 3],
+            100,
+            CryptoHash::default(),
+        );
+        let hash = transaction.get_hash();
+        let view: proto::SignedTransaction =
+            near_primitives::views::SignedTransactionView::from(transaction).into();
+        assert_eq!(view.signer_id, "alice.near");
+        assert_eq!(view.receiver_id, "bob.near");
+        assert_eq!(view.hash, Vec::<u8>::from(hash));
+        assert_eq!(view.public_key, signer.public_key.to_string());
+        assert_eq!(
+            view.actions,
+            vec![proto::Action {
+                action: Some(proto::action::Action::FunctionCall(proto::action::FunctionCall {
+                    method_name: "method".to_string(),
+                    args: vec![1, 2, 3],
+                    gas: 100,
+                    deposit: "10".to_string(),
+                })),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_execution_status() {
+        use proto::execution_status::Status;
+
+        let outcome = near_primitives::views::ExecutionOutcomeView {
+            logs: vec![],
+            receipt_ids: vec![CryptoHash::default()],
+            gas_burnt: 1,
+            tokens_burnt: 2,
+            executor_id: "alice.near".parse().unwrap(),
+            status: ExecutionStatusView::SuccessValue(near_primitives::serialize::to_base64(b"ok")),
+            metadata: Default::default(),
+        };
+        let outcome: proto::ExecutionOutcome = outcome.into();
+        assert_eq!(outcome.tokens_burnt, "2");
+        assert_eq!(outcome.receipt_ids, vec![vec![0; 32]]);
+        assert_eq!(outcome.status.unwrap().status, Some(Status::SuccessValue(b"ok".to_vec())));
+    }
+}
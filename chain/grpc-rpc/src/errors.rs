@@ -0,0 +1,139 @@
+use near_client_primitives::types::{
+    GetBlockError, GetChunkError, GetGasPriceError, GetReceiptError, GetValidatorInfoError,
+    QueryError, StatusError, TxStatusError,
+};
+
+#[derive(Debug)]
+pub(crate) enum ErrorKind {
+    InvalidInput(String),
+    NotFound(String),
+    Unavailable(String),
+    InternalError(String),
+}
+
+pub(crate) type Result<T> = std::result::Result<T, ErrorKind>;
+
+impl From<ErrorKind> for tonic::Status {
+    fn from(error: ErrorKind) -> Self {
+        match error {
+            ErrorKind::InvalidInput(message) => Self::invalid_argument(message),
+            ErrorKind::NotFound(message) => Self::not_found(message),
+            ErrorKind::Unavailable(message) => Self::unavailable(message),
+            ErrorKind::InternalError(message) => Self::internal(message),
+        }
+    }
+}
+
+impl From<actix::MailboxError> for ErrorKind {
+    fn from(err: actix::MailboxError) -> Self {
+        Self::Unavailable(format!(
+            "Server seems to be under a heavy load thus reaching a limit of Actix queue: {}",
+            err
+        ))
+    }
+}
+
+impl From<StatusError> for ErrorKind {
+    fn from(err: StatusError) -> Self {
+        match err {
+            StatusError::NodeIsSyncing | StatusError::NoNewBlocks { .. } => {
+                Self::Unavailable(err.to_string())
+            }
+            StatusError::EpochOutOfBounds { .. }
+            | StatusError::InternalError { .. }
+            | StatusError::Unreachable { .. } => Self::InternalError(err.to_string()),
+        }
+    }
+}
+
+impl From<GetBlockError> for ErrorKind {
+    fn from(err: GetBlockError) -> Self {
+        match err {
+            GetBlockError::UnknownBlock { .. } => Self::NotFound(err.to_string()),
+            GetBlockError::NotSyncedYet => Self::Unavailable(err.to_string()),
+            GetBlockError::IOError { .. } | GetBlockError::Unreachable { .. } => {
+                Self::InternalError(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<GetChunkError> for ErrorKind {
+    fn from(err: GetChunkError) -> Self {
+        match err {
+            GetChunkError::UnknownBlock { .. } | GetChunkError::UnknownChunk { .. } => {
+                Self::NotFound(err.to_string())
+            }
+            GetChunkError::InvalidShardId { .. } => Self::InvalidInput(err.to_string()),
+            GetChunkError::IOError { .. } | GetChunkError::Unreachable { .. } => {
+                Self::InternalError(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<TxStatusError> for ErrorKind {
+    fn from(err: TxStatusError) -> Self {
+        match err {
+            TxStatusError::MissingTransaction(_) | TxStatusError::InvalidTx(_) => {
+                Self::NotFound(err.into())
+            }
+            TxStatusError::ChainError(_)
+            | TxStatusError::InternalError(_)
+            | TxStatusError::TimeoutError => Self::InternalError(err.into()),
+        }
+    }
+}
+
+impl From<QueryError> for ErrorKind {
+    fn from(err: QueryError) -> Self {
+        match err {
+            QueryError::UnknownBlock { .. }
+            | QueryError::UnknownAccount { .. }
+            | QueryError::NoContractCode { .. }
+            | QueryError::UnknownAccessKey { .. } => Self::NotFound(err.to_string()),
+            QueryError::InvalidAccount { .. } => Self::InvalidInput(err.to_string()),
+            QueryError::NoSyncedBlocks | QueryError::UnavailableShard { .. } => {
+                Self::Unavailable(err.to_string())
+            }
+            QueryError::TooLargeContractState { .. }
+            | QueryError::ContractExecutionError { .. }
+            | QueryError::InternalError { .. }
+            | QueryError::Unreachable { .. } => Self::InternalError(err.to_string()),
+        }
+    }
+}
+
+impl From<GetGasPriceError> for ErrorKind {
+    fn from(err: GetGasPriceError) -> Self {
+        match err {
+            GetGasPriceError::UnknownBlock { .. } => Self::NotFound(err.to_string()),
+            GetGasPriceError::InternalError { .. } | GetGasPriceError::Unreachable { .. } => {
+                Self::InternalError(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<GetValidatorInfoError> for ErrorKind {
+    fn from(err: GetValidatorInfoError) -> Self {
+        match err {
+            GetValidatorInfoError::UnknownEpoch => Self::NotFound(err.to_string()),
+            GetValidatorInfoError::ValidatorInfoUnavailable => Self::Unavailable(err.to_string()),
+            GetValidatorInfoError::IOError(_) | GetValidatorInfoError::Unreachable(_) => {
+                Self::InternalError(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<GetReceiptError> for ErrorKind {
+    fn from(err: GetReceiptError) -> Self {
+        match err {
+            GetReceiptError::UnknownReceipt(_) => Self::NotFound(err.to_string()),
+            GetReceiptError::IOError(_) | GetReceiptError::Unreachable(_) => {
+                Self::InternalError(err.to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,531 @@
+#![doc = include_str!("../README.md")]
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use actix::Addr;
+use futures::Stream;
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use near_client::{
+    ChainEvent, ChainEvents, ClientActor, GetBlock, GetChunk, GetGasPrice, GetReceipt,
+    GetValidatorInfo, Query, TxStatus, ViewClientActor,
+};
+use near_crypto::PublicKey;
+use near_jsonrpc::{AccessControl, AccessDenied, API_KEY_HEADER, FORWARDED_FOR_HEADER};
+use near_network::types::NetworkClientMessages;
+use near_primitives::borsh::BorshDeserialize;
+use near_primitives::hash::CryptoHash;
+use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::{AccountId, BlockId, BlockReference, EpochReference, Finality};
+use near_primitives::views::{
+    FinalExecutionOutcomeViewEnum, QueryRequest, QueryResponse, QueryResponseKind,
+};
+
+pub use config::GrpcRpcConfig;
+use errors::ErrorKind;
+use proto::near_rpc_server::{NearRpc, NearRpcServer};
+
+mod config;
+mod conversions;
+mod errors;
+
+/// Messages and services generated from `proto/near/rpc/v1/rpc.proto`, including the client.
+pub mod proto {
+    tonic::include_proto!("near.rpc.v1");
+}
+
+fn parse_hash(bytes: Vec<u8>) -> errors::Result<CryptoHash> {
+    CryptoHash::try_from(bytes)
+        .map_err(|err| ErrorKind::InvalidInput(format!("Invalid hash: {}", err)))
+}
+
+fn parse_account_id(account_id: String) -> errors::Result<AccountId> {
+    account_id
+        .parse()
+        .map_err(|err| ErrorKind::InvalidInput(format!("Invalid account ID: {}", err)))
+}
+
+fn parse_public_key(public_key: String) -> errors::Result<PublicKey> {
+    public_key
+        .parse()
+        .map_err(|err| ErrorKind::InvalidInput(format!("Invalid public key: {}", err)))
+}
+
+fn parse_block_reference(
+    reference: Option<proto::BlockReference>,
+) -> errors::Result<BlockReference> {
+    use proto::block_reference::Reference;
+
+    Ok(match reference.and_then(|reference| reference.reference) {
+        Some(Reference::BlockHash(hash)) => {
+            BlockReference::BlockId(BlockId::Hash(parse_hash(hash)?))
+        }
+        Some(Reference::BlockHeight(height)) => BlockReference::BlockId(BlockId::Height(height)),
+        Some(Reference::Finality(finality)) => {
+            let finality = match proto::Finality::from_i32(finality) {
+                Some(proto::Finality::Final) => Finality::Final,
+                Some(proto::Finality::NearFinal) => Finality::DoomSlug,
+                Some(proto::Finality::Optimistic) => Finality::None,
+                None => {
+                    return Err(ErrorKind::InvalidInput(format!("Unknown finality {}", finality)))
+                }
+            };
+            BlockReference::Finality(finality)
+        }
+        None => BlockReference::Finality(Finality::Final),
+    })
+}
+
+type FinalBlocksStream = Pin<Box<dyn Stream<Item = Result<proto::Block, Status>> + Send + 'static>>;
+
+/// Blocks of the `FinalBlock` events. The stream ends with an error once the receiver falls
+/// behind, so that clients never miss blocks silently.
+fn final_blocks(events: broadcast::Receiver<Arc<ChainEvent>>) -> FinalBlocksStream {
+    let blocks = futures::stream::unfold(Some(events), |events| async move {
+        let mut events = events?;
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let ChainEvent::FinalBlock { block, .. } = event.as_ref() {
+                        return Some((Ok(block.clone().into()), Some(events)));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    let status = Status::resource_exhausted(format!(
+                        "The stream fell behind the chain and missed {} events",
+                        skipped
+                    ));
+                    return Some((Err(status), None));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Box::pin(blocks)
+}
+
+struct NearRpcService {
+    client_addr: Addr<ClientActor>,
+    view_client_addr: Addr<ViewClientActor>,
+    chain_events: ChainEvents,
+    access_control: Arc<AccessControl>,
+}
+
+impl NearRpcService {
+    /// Applies `rpc.access_config` to the request. `method` is the name of the JSON RPC
+    /// counterpart of the called method.
+    fn check_access<T>(&self, method: &str, request: &Request<T>) -> Result<(), Status> {
+        let header =
+            |name: &str| request.metadata().get(name).and_then(|value| value.to_str().ok());
+        let client = self.access_control.client_from(
+            request.remote_addr().map(|addr| addr.ip()),
+            header(FORWARDED_FOR_HEADER),
+            header(API_KEY_HEADER),
+        );
+        self.access_control.authorize(method, &client).map_err(|denied| match denied {
+            AccessDenied::NotAllowed => {
+                Status::permission_denied(format!("Method {} is not allowed", method))
+            }
+            AccessDenied::RateLimited => {
+                Status::resource_exhausted(format!("Rate limit of method {} is exceeded", method))
+            }
+        })
+    }
+
+    /// Sends the query, converting the response to `R`.
+    async fn query<R>(
+        &self,
+        block: Option<proto::BlockReference>,
+        request: QueryRequest,
+    ) -> errors::Result<R>
+    where
+        R: TryFrom<QueryResponse, Error = QueryResponseKind>,
+    {
+        let block_reference = parse_block_reference(block)?;
+        let response = self.view_client_addr.send(Query::new(block_reference, request)).await??;
+        response.try_into().map_err(|kind| {
+            ErrorKind::InternalError(format!("Unexpected response to the query: {:?}", kind))
+        })
+    }
+
+    async fn node_status(&self) -> errors::Result<proto::StatusResponse> {
+        let status =
+            self.client_addr.send(near_client::Status { is_health_check: false }).await??;
+        Ok(status.into())
+    }
+
+    async fn block(&self, request: proto::BlockRequest) -> errors::Result<proto::Block> {
+        let block_reference = parse_block_reference(request.block)?;
+        Ok(self.view_client_addr.send(GetBlock(block_reference)).await??.into())
+    }
+
+    async fn chunk(&self, request: proto::ChunkRequest) -> errors::Result<proto::Chunk> {
+        use proto::chunk_request::Reference;
+
+        let get_chunk = match request.reference {
+            Some(Reference::ChunkHash(hash)) => GetChunk::ChunkHash(ChunkHash(parse_hash(hash)?)),
+            Some(Reference::BlockHash(hash)) => {
+                GetChunk::BlockHash(parse_hash(hash)?, request.shard_id)
+            }
+            Some(Reference::BlockHeight(height)) => GetChunk::Height(height, request.shard_id),
+            None => {
+                return Err(ErrorKind::InvalidInput(
+                    "Either the chunk hash or the block is required".to_string(),
+                ))
+            }
+        };
+        Ok(self.view_client_addr.send(get_chunk).await??.into())
+    }
+
+    async fn tx_status(
+        &self,
+        request: proto::TxRequest,
+    ) -> errors::Result<proto::FinalExecutionOutcome> {
+        let tx_hash = parse_hash(request.tx_hash)?;
+        let signer_account_id = parse_account_id(request.sender_account_id)?;
+        let outcome = self
+            .view_client_addr
+            .send(TxStatus { tx_hash, signer_account_id, fetch_receipt: false })
+            .await??;
+        match outcome {
+            Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome)) => {
+                Ok(outcome.into())
+            }
+            Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome)) => {
+                Ok(outcome.final_outcome.into())
+            }
+            // The transaction is known, but its outcome isn't there yet.
+            None => {
+                Err(ErrorKind::NotFound(format!("Transaction {} is not executed yet", tx_hash)))
+            }
+        }
+    }
+
+    async fn send_tx_async(
+        &self,
+        request: proto::BroadcastTxRequest,
+    ) -> errors::Result<proto::BroadcastTxResponse> {
+        let transaction = SignedTransaction::try_from_slice(&request.signed_transaction)
+            .map_err(|err| ErrorKind::InvalidInput(format!("Invalid transaction: {}", err)))?;
+        let tx_hash = transaction.get_hash();
+        self.client_addr.do_send(NetworkClientMessages::Transaction {
+            transaction,
+            is_forwarded: false,
+            check_only: false,
+        });
+        Ok(proto::BroadcastTxResponse { tx_hash: tx_hash.into() })
+    }
+
+    async fn query_account(
+        &self,
+        request: proto::ViewAccountRequest,
+    ) -> errors::Result<proto::ViewAccountResponse> {
+        let account_id = parse_account_id(request.account_id)?;
+        self.query(request.block, QueryRequest::ViewAccount { account_id }).await
+    }
+
+    async fn query_code(
+        &self,
+        request: proto::ViewCodeRequest,
+    ) -> errors::Result<proto::ViewCodeResponse> {
+        let account_id = parse_account_id(request.account_id)?;
+        self.query(request.block, QueryRequest::ViewCode { account_id }).await
+    }
+
+    async fn query_state(
+        &self,
+        request: proto::ViewStateRequest,
+    ) -> errors::Result<proto::ViewStateResponse> {
+        let account_id = parse_account_id(request.account_id)?;
+        let prefix = request.prefix.into();
+        self.query(request.block, QueryRequest::ViewState { account_id, prefix }).await
+    }
+
+    async fn query_access_key(
+        &self,
+        request: proto::ViewAccessKeyRequest,
+    ) -> errors::Result<proto::ViewAccessKeyResponse> {
+        let account_id = parse_account_id(request.account_id)?;
+        let public_key = parse_public_key(request.public_key)?;
+        self.query(request.block, QueryRequest::ViewAccessKey { account_id, public_key }).await
+    }
+
+    async fn query_access_key_list(
+        &self,
+        request: proto::ViewAccessKeyListRequest,
+    ) -> errors::Result<proto::ViewAccessKeyListResponse> {
+        let account_id = parse_account_id(request.account_id)?;
+        self.query(request.block, QueryRequest::ViewAccessKeyList { account_id }).await
+    }
+
+    async fn query_call_function(
+        &self,
+        request: proto::CallFunctionRequest,
+    ) -> errors::Result<proto::CallFunctionResponse> {
+        let account_id = parse_account_id(request.account_id)?;
+        let query_request = QueryRequest::CallFunction {
+            account_id,
+            method_name: request.method_name,
+            args: request.args.into(),
+        };
+        self.query(request.block, query_request).await
+    }
+
+    async fn block_gas_price(
+        &self,
+        request: proto::GasPriceRequest,
+    ) -> errors::Result<proto::GasPriceResponse> {
+        use proto::gas_price_request::Block;
+
+        let block_id = match request.block {
+            Some(Block::BlockHash(hash)) => Some(BlockId::Hash(parse_hash(hash)?)),
+            Some(Block::BlockHeight(height)) => Some(BlockId::Height(height)),
+            None => None,
+        };
+        Ok(self.view_client_addr.send(GetGasPrice { block_id }).await??.into())
+    }
+
+    async fn epoch_validators(
+        &self,
+        request: proto::ValidatorsRequest,
+    ) -> errors::Result<proto::EpochValidatorInfo> {
+        use proto::validators_request::Block;
+
+        let block_id = match request.block {
+            Some(Block::BlockHash(hash)) => Some(BlockId::Hash(parse_hash(hash)?)),
+            Some(Block::BlockHeight(height)) => Some(BlockId::Height(height)),
+            None => None,
+        };
+        let epoch_reference = match block_id {
+            Some(block_id) => EpochReference::BlockId(block_id),
+            None => EpochReference::Latest,
+        };
+        Ok(self.view_client_addr.send(GetValidatorInfo { epoch_reference }).await??.into())
+    }
+
+    async fn receipt(&self, request: proto::ReceiptRequest) -> errors::Result<proto::Receipt> {
+        let receipt_id = parse_hash(request.receipt_id)?;
+        match self.view_client_addr.send(GetReceipt { receipt_id }).await?? {
+            Some(receipt) => Ok(receipt.into()),
+            None => Err(ErrorKind::NotFound(format!("Receipt {} is not known", receipt_id))),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl NearRpc for NearRpcService {
+    type StreamFinalBlocksStream = FinalBlocksStream;
+
+    async fn status(
+        &self,
+        request: Request<proto::StatusRequest>,
+    ) -> Result<Response<proto::StatusResponse>, Status> {
+        self.check_access("status", &request)?;
+        Ok(Response::new(self.node_status().await?))
+    }
+
+    async fn get_block(
+        &self,
+        request: Request<proto::BlockRequest>,
+    ) -> Result<Response<proto::Block>, Status> {
+        self.check_access("block", &request)?;
+        Ok(Response::new(self.block(request.into_inner()).await?))
+    }
+
+    async fn get_chunk(
+        &self,
+        request: Request<proto::ChunkRequest>,
+    ) -> Result<Response<proto::Chunk>, Status> {
+        self.check_access("chunk", &request)?;
+        Ok(Response::new(self.chunk(request.into_inner()).await?))
+    }
+
+    async fn tx(
+        &self,
+        request: Request<proto::TxRequest>,
+    ) -> Result<Response<proto::FinalExecutionOutcome>, Status> {
+        self.check_access("tx", &request)?;
+        Ok(Response::new(self.tx_status(request.into_inner()).await?))
+    }
+
+    async fn broadcast_tx_async(
+        &self,
+        request: Request<proto::BroadcastTxRequest>,
+    ) -> Result<Response<proto::BroadcastTxResponse>, Status> {
+        self.check_access("broadcast_tx_async", &request)?;
+        Ok(Response::new(self.send_tx_async(request.into_inner()).await?))
+    }
+
+    async fn view_account(
+        &self,
+        request: Request<proto::ViewAccountRequest>,
+    ) -> Result<Response<proto::ViewAccountResponse>, Status> {
+        self.check_access("query", &request)?;
+        Ok(Response::new(self.query_account(request.into_inner()).await?))
+    }
+
+    async fn view_code(
+        &self,
+        request: Request<proto::ViewCodeRequest>,
+    ) -> Result<Response<proto::ViewCodeResponse>, Status> {
+        self.check_access("query", &request)?;
+        Ok(Response::new(self.query_code(request.into_inner()).await?))
+    }
+
+    async fn view_state(
+        &self,
+        request: Request<proto::ViewStateRequest>,
+    ) -> Result<Response<proto::ViewStateResponse>, Status> {
+        self.check_access("query", &request)?;
+        Ok(Response::new(self.query_state(request.into_inner()).await?))
+    }
+
+    async fn view_access_key(
+        &self,
+        request: Request<proto::ViewAccessKeyRequest>,
+    ) -> Result<Response<proto::ViewAccessKeyResponse>, Status> {
+        self.check_access("query", &request)?;
+        Ok(Response::new(self.query_access_key(request.into_inner()).await?))
+    }
+
+    async fn view_access_key_list(
+        &self,
+        request: Request<proto::ViewAccessKeyListRequest>,
+    ) -> Result<Response<proto::ViewAccessKeyListResponse>, Status> {
+        self.check_access("query", &request)?;
+        Ok(Response::new(self.query_access_key_list(request.into_inner()).await?))
+    }
+
+    async fn call_function(
+        &self,
+        request: Request<proto::CallFunctionRequest>,
+    ) -> Result<Response<proto::CallFunctionResponse>, Status> {
+        self.check_access("query", &request)?;
+        Ok(Response::new(self.query_call_function(request.into_inner()).await?))
+    }
+
+    async fn gas_price(
+        &self,
+        request: Request<proto::GasPriceRequest>,
+    ) -> Result<Response<proto::GasPriceResponse>, Status> {
+        self.check_access("gas_price", &request)?;
+        Ok(Response::new(self.block_gas_price(request.into_inner()).await?))
+    }
+
+    async fn validators(
+        &self,
+        request: Request<proto::ValidatorsRequest>,
+    ) -> Result<Response<proto::EpochValidatorInfo>, Status> {
+        self.check_access("validators", &request)?;
+        Ok(Response::new(self.epoch_validators(request.into_inner()).await?))
+    }
+
+    async fn get_receipt(
+        &self,
+        request: Request<proto::ReceiptRequest>,
+    ) -> Result<Response<proto::Receipt>, Status> {
+        self.check_access("EXPERIMENTAL_receipt", &request)?;
+        Ok(Response::new(self.receipt(request.into_inner()).await?))
+    }
+
+    async fn stream_final_blocks(
+        &self,
+        request: Request<proto::StreamFinalBlocksRequest>,
+    ) -> Result<Response<Self::StreamFinalBlocksStream>, Status> {
+        self.check_access("subscribe", &request)?;
+        Ok(Response::new(final_blocks(self.chain_events.subscribe())))
+    }
+}
+
+/// Starts the gRPC server in the current actix system. `access_control` is shared with the
+/// JSON RPC server.
+pub fn start_grpc_rpc(
+    config: GrpcRpcConfig,
+    client_addr: Addr<ClientActor>,
+    view_client_addr: Addr<ViewClientActor>,
+    chain_events: ChainEvents,
+    access_control: Arc<AccessControl>,
+) {
+    let addr: SocketAddr = config
+        .addr
+        .parse()
+        .unwrap_or_else(|err| panic!("Invalid gRPC RPC address {}: {}", config.addr, err));
+    let service = NearRpcService { client_addr, view_client_addr, chain_events, access_control };
+    info!(target: "grpc", "Starting gRPC server at {}", addr);
+    actix::spawn(async move {
+        let server = tonic::transport::Server::builder()
+            .add_service(NearRpcServer::new(service))
+            .serve(addr);
+        if let Err(err) = server.await {
+            error!(target: "grpc", "gRPC server failed: {}", err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    use near_primitives::block::{genesis_chunks, Block};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::utils::from_timestamp;
+    use near_primitives::version::PROTOCOL_VERSION;
+    use near_primitives::views::BlockView;
+
+    use super::*;
+
+    fn final_block(height: u64) -> Arc<ChainEvent> {
+        let chunks = genesis_chunks(vec![CryptoHash::default()], 1, 1_000, 0, PROTOCOL_VERSION);
+        let block = Block::genesis(
+            PROTOCOL_VERSION,
+            chunks.into_iter().map(|chunk| chunk.take_header()).collect(),
+            from_timestamp(0),
+            0,
+            100,
+            1_000,
+            CryptoHash::default(),
+        );
+        let mut block = BlockView::from_author_block("alice.near".parse().unwrap(), block);
+        block.header.height = height;
+        Arc::new(ChainEvent::FinalBlock { block, state_changes: vec![] })
+    }
+
+    fn heights(blocks: Vec<Result<proto::Block, Status>>) -> Vec<Result<u64, tonic::Code>> {
+        blocks
+            .into_iter()
+            .map(|block| match block {
+                Ok(block) => Ok(block.header.unwrap().height),
+                Err(status) => Err(status.code()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_final_blocks() {
+        let (sender, receiver) = broadcast::channel(8);
+        sender.send(final_block(1)).unwrap();
+        sender.send(Arc::new(ChainEvent::TransactionExpired(CryptoHash::default()))).unwrap();
+        sender.send(final_block(2)).unwrap();
+        drop(sender);
+        let blocks = block_on(final_blocks(receiver).collect::<Vec<_>>());
+        assert_eq!(heights(blocks), vec![Ok(1), Ok(2)]);
+    }
+
+    #[test]
+    fn test_final_blocks_lagged() {
+        let (sender, receiver) = broadcast::channel(2);
+        for height in 1..=3 {
+            sender.send(final_block(height)).unwrap();
+        }
+        // The stream ends at the first missed event even though the sender is still open.
+        let blocks = block_on(final_blocks(receiver).collect::<Vec<_>>());
+        assert_eq!(heights(blocks), vec![Err(tonic::Code::ResourceExhausted)]);
+        drop(sender);
+    }
+}
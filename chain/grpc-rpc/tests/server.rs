@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::System;
+use tonic::transport::Channel;
+
+use near_actix_test_utils::run_actix;
+use near_client::test_utils::setup_no_network;
+use near_client::ChainEvents;
+use near_grpc_rpc::proto::near_rpc_client::NearRpcClient;
+use near_grpc_rpc::proto::{self, block_reference::Reference};
+use near_grpc_rpc::{start_grpc_rpc, GrpcRpcConfig};
+use near_jsonrpc::{AccessControl, RpcAccessConfig};
+use near_logger_utils::init_test_logger;
+use near_network::test_utils::open_port;
+
+/// Starts a node without network and the gRPC server for it, and returns the server URL.
+fn start_all(access_config: RpcAccessConfig) -> String {
+    let (client_addr, view_client_addr) = setup_no_network(
+        vec!["test1".parse().unwrap(), "test2".parse().unwrap()],
+        "other".parse().unwrap(),
+        true,
+        false,
+    );
+    let addr = format!("127.0.0.1:{}", open_port());
+    start_grpc_rpc(
+        GrpcRpcConfig { addr: addr.clone() },
+        client_addr,
+        view_client_addr,
+        ChainEvents::new(),
+        Arc::new(AccessControl::new(access_config)),
+    );
+    format!("http://{}", addr)
+}
+
+/// The server starts listening in the background, so the first attempts may be refused.
+async fn connect(url: String) -> NearRpcClient<Channel> {
+    for _ in 0..50 {
+        if let Ok(client) = NearRpcClient::connect(url.clone()).await {
+            return client;
+        }
+        actix::clock::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Failed to connect to the gRPC server at {}", url);
+}
+
+fn genesis() -> Option<proto::BlockReference> {
+    Some(proto::BlockReference { reference: Some(Reference::BlockHeight(0)) })
+}
+
+#[test]
+fn test_status_block_and_account() {
+    init_test_logger();
+
+    run_actix(async {
+        let url = start_all(RpcAccessConfig::default());
+        actix::spawn(async move {
+            let mut client = connect(url).await;
+
+            let status = client.status(proto::StatusRequest {}).await.unwrap().into_inner();
+            assert_eq!(status.chain_id, "unittest");
+            let sync_info = status.sync_info.unwrap();
+            assert_eq!(sync_info.latest_block_height, 0);
+
+            let block = client
+                .get_block(proto::BlockRequest { block: genesis() })
+                .await
+                .unwrap()
+                .into_inner();
+            let header = block.header.unwrap();
+            assert_eq!(header.height, 0);
+            assert_eq!(header.hash, sync_info.latest_block_hash);
+
+            let response = client
+                .view_account(proto::ViewAccountRequest {
+                    account_id: "test".to_string(),
+                    block: genesis(),
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(response.block_height, 0);
+            assert_eq!(response.block_hash, header.hash);
+            assert_eq!(response.account.unwrap().amount, "0");
+
+            let status = client
+                .view_account(proto::ViewAccountRequest {
+                    account_id: "invalid account".to_string(),
+                    block: genesis(),
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+            System::current().stop();
+        });
+    });
+}
+
+/// The access config of JSON RPC applies to gRPC methods by the name of their JSON RPC method.
+#[test]
+fn test_access_control() {
+    init_test_logger();
+
+    run_actix(async {
+        let url = start_all(RpcAccessConfig {
+            denied_methods: vec!["query".to_string()],
+            ..Default::default()
+        });
+        actix::spawn(async move {
+            let mut client = connect(url).await;
+
+            client.status(proto::StatusRequest {}).await.unwrap();
+            for status in [
+                client
+                    .view_account(proto::ViewAccountRequest {
+                        account_id: "test".to_string(),
+                        block: genesis(),
+                    })
+                    .await
+                    .unwrap_err(),
+                client
+                    .view_code(proto::ViewCodeRequest {
+                        account_id: "test".to_string(),
+                        block: genesis(),
+                    })
+                    .await
+                    .unwrap_err(),
+            ] {
+                assert_eq!(status.code(), tonic::Code::PermissionDenied);
+            }
+
+            System::current().stop();
+        });
+    });
+}
@@ -7,7 +7,7 @@ use near_primitives::types::Gas;
 pub use nearcore::{get_default_home, init_configs, NearConfig};
 
 pub use self::streamer::{
-    IndexerChunkView, IndexerExecutionOutcomeWithOptionalReceipt,
+    BlockAcknowledger, IndexerChunkView, IndexerExecutionOutcomeWithOptionalReceipt,
     IndexerExecutionOutcomeWithReceipt, IndexerShard, IndexerTransactionWithOutcome,
     StreamerMessage,
 };
@@ -52,6 +52,7 @@ pub enum SyncModeEnum {
     /// Real-time syncing, always taking the latest finalized block to stream
     LatestSynced,
     /// Starts syncing from the block NEAR Indexer was interrupted last time
+    /// (or the block after the last acknowledged one when streaming with acknowledgements)
     FromInterruption,
     /// Specific block height to start syncing from
     BlockHeight(u64),
@@ -116,11 +117,31 @@ impl Indexer {
             self.view_client.clone(),
             self.client.clone(),
             self.indexer_config.clone(),
+            streamer::open_db(&self.indexer_config.home_dir),
+            None,
             sender,
         ));
         receiver
     }
 
+    /// Boots up `near_indexer::streamer` the same way as `streamer`, but the consumer confirms the processed blocks through the returned `BlockAcknowledger`. The last acknowledged block height is persisted, so with `SyncModeEnum::FromInterruption` the blocks which were streamed but not acknowledged before a restart are sent again.
+    pub fn streamer_with_acks(
+        &self,
+    ) -> (mpsc::Receiver<streamer::StreamerMessage>, BlockAcknowledger) {
+        let (sender, receiver) = mpsc::channel(16);
+        let db = streamer::open_db(&self.indexer_config.home_dir);
+        let acknowledger = BlockAcknowledger::new(db.clone());
+        actix::spawn(streamer::start(
+            self.view_client.clone(),
+            self.client.clone(),
+            self.indexer_config.clone(),
+            db,
+            Some(acknowledger.clone()),
+            sender,
+        ));
+        (receiver, acknowledger)
+    }
+
     /// Expose neard config
     pub fn near_config(&self) -> &nearcore::NearConfig {
         &self.near_config
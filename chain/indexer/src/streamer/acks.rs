@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex};
+
+use rocksdb::DB;
+
+use near_primitives::types::BlockHeight;
+
+pub(crate) const LAST_SYNCED_BLOCK_HEIGHT_KEY: &[u8] = b"last_synced_block_height";
+pub(crate) const LAST_ACKED_BLOCK_HEIGHT_KEY: &[u8] = b"last_acked_block_height";
+
+/// Opens the indexer's own database stored next to the nearcore one
+pub(crate) fn open_db(home_dir: &std::path::Path) -> Arc<DB> {
+    let mut indexer_db_path = nearcore::get_store_path(home_dir);
+    indexer_db_path.push("indexer");
+
+    // TODO: implement proper error handling
+    Arc::new(DB::open_default(indexer_db_path).unwrap())
+}
+
+pub(crate) fn get_block_height(db: &DB, key: &[u8]) -> Option<BlockHeight> {
+    // TODO: implement proper error handling
+    db.get(key).unwrap().map(|value| String::from_utf8(value).unwrap().parse().unwrap())
+}
+
+/// Stores the block height with a synced write, so that it survives a machine
+/// crash and not just a process crash
+fn put_block_height_synced(
+    db: &DB,
+    key: &[u8],
+    block_height: BlockHeight,
+) -> Result<(), rocksdb::Error> {
+    let mut write_options = rocksdb::WriteOptions::default();
+    write_options.set_sync(true);
+    db.put_opt(key, block_height.to_string(), &write_options)
+}
+
+/// Returns the height to resume streaming from with `SyncModeEnum::FromInterruption`
+pub(crate) fn get_interrupted_block_height(
+    db: &DB,
+    acknowledger: Option<&BlockAcknowledger>,
+) -> Option<BlockHeight> {
+    match acknowledger.and_then(|acknowledger| acknowledger.last_acked_block_height()) {
+        // Blocks streamed after the last acknowledged one are sent again
+        Some(last_acked_block_height) => Some(last_acked_block_height + 1),
+        None => get_block_height(db, LAST_SYNCED_BLOCK_HEIGHT_KEY),
+    }
+}
+
+/// Handle for the consumer to confirm the blocks it has processed, returned by
+/// `Indexer::streamer_with_acks`.
+///
+/// The last acknowledged block height is durably persisted in the indexer
+/// database before `ack` returns, so after a restart with
+/// `SyncModeEnum::FromInterruption` the streamer resumes right after it. A block
+/// which was streamed but not acknowledged is sent again, so the consumer sees
+/// every block at least once. The acknowledgement is not atomic with whatever
+/// the consumer stores, so the handling of a block has to be idempotent.
+#[derive(Clone)]
+pub struct BlockAcknowledger {
+    db: Arc<DB>,
+    last_acked_block_height: Arc<Mutex<Option<BlockHeight>>>,
+}
+
+impl BlockAcknowledger {
+    pub(crate) fn new(db: Arc<DB>) -> Self {
+        let last_acked_block_height = get_block_height(&db, LAST_ACKED_BLOCK_HEIGHT_KEY);
+        Self { db, last_acked_block_height: Arc::new(Mutex::new(last_acked_block_height)) }
+    }
+
+    /// Confirms that all the streamed blocks up to and including the given
+    /// height are processed. Acknowledging a height lower than the already
+    /// acknowledged one is a no-op.
+    pub fn ack(&self, block_height: BlockHeight) -> Result<(), rocksdb::Error> {
+        let mut last_acked_block_height = self.last_acked_block_height.lock().unwrap();
+        if matches!(*last_acked_block_height, Some(height) if height >= block_height) {
+            return Ok(());
+        }
+        put_block_height_synced(&self.db, LAST_ACKED_BLOCK_HEIGHT_KEY, block_height)?;
+        *last_acked_block_height = Some(block_height);
+        Ok(())
+    }
+
+    /// Marks the blocks before the first streamed one as acknowledged unless some block was
+    /// acknowledged already, so that the streamed blocks are sent again if the consumer stops
+    /// before acknowledging any of them.
+    pub(crate) fn start_from(&self, block_height: BlockHeight) -> Result<(), rocksdb::Error> {
+        let mut last_acked_block_height = self.last_acked_block_height.lock().unwrap();
+        if last_acked_block_height.is_some() || block_height == 0 {
+            return Ok(());
+        }
+        put_block_height_synced(&self.db, LAST_ACKED_BLOCK_HEIGHT_KEY, block_height - 1)?;
+        *last_acked_block_height = Some(block_height - 1);
+        Ok(())
+    }
+
+    /// Returns the height of the last acknowledged block, if any
+    pub fn last_acked_block_height(&self) -> Option<BlockHeight> {
+        *self.last_acked_block_height.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_temp_db(tmp_dir: &tempfile::TempDir) -> Arc<DB> {
+        Arc::new(DB::open_default(tmp_dir.path()).unwrap())
+    }
+
+    #[test]
+    fn test_ack_is_monotonic() {
+        let tmp_dir = tempfile::Builder::new().prefix("indexer_acks").tempdir().unwrap();
+        let db = open_temp_db(&tmp_dir);
+        let acknowledger = BlockAcknowledger::new(db.clone());
+        assert_eq!(acknowledger.last_acked_block_height(), None);
+
+        acknowledger.ack(5).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(5));
+        acknowledger.ack(3).unwrap();
+        acknowledger.ack(5).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(5));
+        assert_eq!(get_block_height(&db, LAST_ACKED_BLOCK_HEIGHT_KEY), Some(5));
+
+        // Clones share the last acknowledged height.
+        acknowledger.clone().ack(7).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(7));
+
+        // The acknowledged height is persisted across restarts.
+        drop(acknowledger);
+        drop(db);
+        let acknowledger = BlockAcknowledger::new(open_temp_db(&tmp_dir));
+        assert_eq!(acknowledger.last_acked_block_height(), Some(7));
+        acknowledger.ack(6).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(7));
+    }
+
+    #[test]
+    fn test_start_from() {
+        let tmp_dir = tempfile::Builder::new().prefix("indexer_acks").tempdir().unwrap();
+        let db = open_temp_db(&tmp_dir);
+        let acknowledger = BlockAcknowledger::new(db.clone());
+
+        // Nothing precedes the genesis block.
+        acknowledger.start_from(0).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), None);
+
+        acknowledger.start_from(10).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(9));
+        assert_eq!(get_block_height(&db, LAST_ACKED_BLOCK_HEIGHT_KEY), Some(9));
+
+        // Streaming from another height keeps the blocks acknowledged so far.
+        acknowledger.start_from(20).unwrap();
+        acknowledger.start_from(5).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(9));
+        acknowledger.ack(12).unwrap();
+        acknowledger.start_from(20).unwrap();
+        assert_eq!(acknowledger.last_acked_block_height(), Some(12));
+    }
+
+    #[test]
+    fn test_get_interrupted_block_height() {
+        let tmp_dir = tempfile::Builder::new().prefix("indexer_acks").tempdir().unwrap();
+        let db = open_temp_db(&tmp_dir);
+        let acknowledger = BlockAcknowledger::new(db.clone());
+
+        // Nothing was streamed yet.
+        assert_eq!(get_interrupted_block_height(&db, None), None);
+        assert_eq!(get_interrupted_block_height(&db, Some(&acknowledger)), None);
+
+        // Without acknowledgements, streaming resumes from the last synced block.
+        db.put(LAST_SYNCED_BLOCK_HEIGHT_KEY, 42.to_string()).unwrap();
+        assert_eq!(get_interrupted_block_height(&db, None), Some(42));
+        assert_eq!(get_interrupted_block_height(&db, Some(&acknowledger)), Some(42));
+
+        // The blocks streamed after the last acknowledged one are sent again.
+        acknowledger.ack(40).unwrap();
+        assert_eq!(get_interrupted_block_height(&db, Some(&acknowledger)), Some(41));
+        assert_eq!(get_interrupted_block_height(&db, None), Some(42));
+
+        drop(acknowledger);
+        let acknowledger = BlockAcknowledger::new(db.clone());
+        assert_eq!(get_interrupted_block_height(&db, Some(&acknowledger)), Some(41));
+    }
+}
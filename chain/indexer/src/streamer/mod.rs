@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix::Addr;
@@ -12,6 +13,9 @@ pub use near_primitives::views;
 
 use crate::{AwaitForNodeSyncedEnum, IndexerConfig};
 
+pub(crate) use self::acks::open_db;
+pub use self::acks::BlockAcknowledger;
+use self::acks::{get_interrupted_block_height, LAST_SYNCED_BLOCK_HEIGHT_KEY};
 use self::errors::FailedToFetchData;
 use self::fetchers::{
     fetch_block_by_hash, fetch_block_by_height, fetch_chunks, fetch_latest_block, fetch_outcomes,
@@ -26,6 +30,7 @@ use self::utils::convert_transactions_sir_into_local_receipts;
 use crate::streamer::fetchers::fetch_protocol_config;
 use crate::INDEXER;
 
+mod acks;
 mod errors;
 mod fetchers;
 mod types;
@@ -276,18 +281,17 @@ async fn find_local_receipt_by_id_in_block(
 /// compares to already fetched block height and in case it differs fetches new block of given height.
 ///
 /// We have to pass `client: Addr<near_client::ClientActor>` and `view_client: Addr<near_client::ViewClientActor>`.
+/// If the consumer acknowledges the processed blocks through the `acknowledger`,
+/// `SyncModeEnum::FromInterruption` resumes from the block after the last acknowledged one.
 pub(crate) async fn start(
     view_client: Addr<near_client::ViewClientActor>,
     client: Addr<near_client::ClientActor>,
     indexer_config: IndexerConfig,
+    db: Arc<DB>,
+    acknowledger: Option<BlockAcknowledger>,
     blocks_sink: mpsc::Sender<StreamerMessage>,
 ) {
     info!(target: INDEXER, "Starting Streamer...");
-    let mut indexer_db_path = nearcore::get_store_path(&indexer_config.home_dir);
-    indexer_db_path.push("indexer");
-
-    // TODO: implement proper error handling
-    let db = DB::open_default(indexer_db_path).unwrap();
     let mut last_synced_block_height: Option<near_primitives::types::BlockHeight> = None;
 
     'main: loop {
@@ -311,22 +315,19 @@ pub(crate) async fn start(
         };
 
         let latest_block_height = block.header.height;
-        let start_syncing_block_height = if let Some(last_synced_block_height) =
-            last_synced_block_height
-        {
-            last_synced_block_height + 1
-        } else {
-            match indexer_config.sync_mode {
-                crate::SyncModeEnum::FromInterruption => {
-                    match db.get(b"last_synced_block_height").unwrap() {
-                        Some(value) => String::from_utf8(value).unwrap().parse::<u64>().unwrap(),
-                        None => latest_block_height,
+        let start_syncing_block_height =
+            if let Some(last_synced_block_height) = last_synced_block_height {
+                last_synced_block_height + 1
+            } else {
+                match indexer_config.sync_mode {
+                    crate::SyncModeEnum::FromInterruption => {
+                        get_interrupted_block_height(&db, acknowledger.as_ref())
+                            .unwrap_or(latest_block_height)
                     }
+                    crate::SyncModeEnum::LatestSynced => latest_block_height,
+                    crate::SyncModeEnum::BlockHeight(height) => height,
                 }
-                crate::SyncModeEnum::LatestSynced => latest_block_height,
-                crate::SyncModeEnum::BlockHeight(height) => height,
-            }
-        };
+            };
 
         debug!(
             target: INDEXER,
@@ -334,6 +335,10 @@ pub(crate) async fn start(
             start_syncing_block_height,
             latest_block_height
         );
+        if let Some(acknowledger) = acknowledger.as_ref() {
+            // TODO: implement proper error handling
+            acknowledger.start_from(start_syncing_block_height).unwrap();
+        }
         for block_height in start_syncing_block_height..=latest_block_height {
             if let Ok(block) = fetch_block_by_height(&view_client, block_height).await {
                 let response = build_streamer_message(&view_client, block).await;
@@ -358,7 +363,7 @@ pub(crate) async fn start(
                     }
                 }
             }
-            db.put(b"last_synced_block_height", &block_height.to_string()).unwrap();
+            db.put(LAST_SYNCED_BLOCK_HEIGHT_KEY, &block_height.to_string()).unwrap();
             last_synced_block_height = Some(block_height);
         }
     }
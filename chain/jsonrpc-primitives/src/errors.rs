@@ -1,5 +1,6 @@
 use std::fmt;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{to_value, Value};
 
@@ -25,7 +26,7 @@ pub struct RpcError {
     pub data: Option<Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(tag = "name", content = "cause", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcErrorKind {
     RequestValidationError(RpcRequestValidationErrorKind),
@@ -33,11 +34,14 @@ pub enum RpcErrorKind {
     InternalError(Value),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcRequestValidationErrorKind {
     MethodNotFound { method_name: String },
     ParseError { error_message: String },
+    InvalidRequest { error_message: String },
+    MethodNotAllowed { method_name: String },
+    RateLimitExceeded { method_name: String },
 }
 
 /// A general Server Error
@@ -132,6 +136,18 @@ impl RpcError {
         }
     }
 
+    /// Create an invalid request error, e.g. for an entry of a batch which is not a request.
+    pub fn invalid_request(e: String) -> Self {
+        RpcError {
+            code: -32_600,
+            message: "Invalid Request".to_owned(),
+            data: Some(Value::String(e.clone())),
+            error_struct: Some(RpcErrorKind::RequestValidationError(
+                RpcRequestValidationErrorKind::InvalidRequest { error_message: e },
+            )),
+        }
+    }
+
     /// Create a method not found error.
     pub fn method_not_found(method: String) -> Self {
         RpcError {
@@ -143,6 +159,30 @@ impl RpcError {
             )),
         }
     }
+
+    /// Create an error for a method the server is configured not to serve.
+    pub fn method_not_allowed(method: String) -> Self {
+        RpcError {
+            code: -32_000,
+            message: "Method not allowed".to_owned(),
+            data: Some(Value::String(method.clone())),
+            error_struct: Some(RpcErrorKind::RequestValidationError(
+                RpcRequestValidationErrorKind::MethodNotAllowed { method_name: method },
+            )),
+        }
+    }
+
+    /// Create an error for a request over the rate limits of the client.
+    pub fn rate_limit_exceeded(method: String) -> Self {
+        RpcError {
+            code: -32_000,
+            message: "Rate limit exceeded".to_owned(),
+            data: Some(Value::String(method.clone())),
+            error_struct: Some(RpcErrorKind::RequestValidationError(
+                RpcRequestValidationErrorKind::RateLimitExceeded { method_name: method },
+            )),
+        }
+    }
 }
 
 impl fmt::Display for RpcError {
@@ -151,6 +191,46 @@ impl fmt::Display for RpcError {
     }
 }
 
+/// Schema of errors with an optional structured error. A derived schema would require the fields
+/// of `error_struct` and forbid them because of `deny_unknown_fields` at the same time.
+impl JsonSchema for RpcError {
+    fn schema_name() -> String {
+        "RpcError".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject};
+
+        let mut properties = schemars::Map::new();
+        properties.insert("code".to_string(), gen.subschema_for::<i64>());
+        properties.insert("message".to_string(), gen.subschema_for::<String>());
+        properties.insert("data".to_string(), gen.subschema_for::<Value>());
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(ObjectValidation {
+                properties,
+                required: ["code".to_string(), "message".to_string()].into_iter().collect(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        // Either a structured error or none of its fields.
+        let without_error_struct = SchemaObject {
+            object: Some(Box::new(ObjectValidation {
+                properties: ["name", "cause"]
+                    .into_iter()
+                    .map(|field| (field.to_string(), Schema::Bool(false)))
+                    .collect(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        schema.subschemas().any_of =
+            Some(vec![gen.subschema_for::<RpcErrorKind>(), without_error_struct.into()]);
+        schema.into()
+    }
+}
+
 impl From<actix::MailboxError> for RpcError {
     fn from(error: actix::MailboxError) -> Self {
         Self::new(-32_000, "Server error".to_string(), Some(Value::String(error.to_string())))
@@ -1,5 +1,6 @@
 pub mod errors;
 pub mod message;
 pub(crate) mod metrics;
+pub mod openrpc;
 pub mod types;
 pub(crate) mod utils;
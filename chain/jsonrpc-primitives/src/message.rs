@@ -147,6 +147,18 @@ pub struct Notification {
     pub params: Option<Value>,
 }
 
+/// A notification is processed as a request with a null ID, whose answer is dropped.
+impl From<Notification> for Request {
+    fn from(notification: Notification) -> Self {
+        Request {
+            jsonrpc: Version,
+            method: notification.method,
+            params: notification.params,
+            id: Value::Null,
+        }
+    }
+}
+
 /// One message of the JSON RPC protocol.
 ///
 /// One message, directly mapped from the structures of the protocol. See the
@@ -0,0 +1,545 @@
+//! [OpenRPC](https://spec.open-rpc.org) document of the JSON RPC methods, served by `rpc.discover`.
+//!
+//! Schemas of params, results and errors are derived from the request, response and error types
+//! of this crate. The JSON RPC server generates the document in its build script, and a test of
+//! the server checks that the methods listed here are the ones it dispatches.
+//!
+//! Errors of all methods are [`RpcError`](crate::errors::RpcError) objects. The `x-error-cause`
+//! extension of a method is the schema of the `cause` of its `HANDLER_ERROR` errors.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{Schema, SchemaObject, SubschemaValidation};
+use schemars::JsonSchema;
+use serde_json::{json, Value};
+
+use near_chain_configs::GenesisConfig;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockId};
+
+use crate::errors::RpcError;
+use crate::types::blocks::{RpcBlockError, RpcBlockRequest, RpcBlockResponse};
+use crate::types::changes::{
+    RpcStateChangesError, RpcStateChangesHistoryRequest, RpcStateChangesHistoryResponse,
+    RpcStateChangesInBlockByTypeRequest, RpcStateChangesInBlockByTypeResponse,
+    RpcStateChangesInBlockRequest, RpcStateChangesInBlockResponse,
+};
+use crate::types::checkpoint::{
+    RpcCreateCheckpointError, RpcCreateCheckpointRequest, RpcCreateCheckpointResponse,
+};
+use crate::types::chunks::{RpcChunkError, RpcChunkRequest, RpcChunkResponse};
+use crate::types::config::{
+    RpcProtocolConfigError, RpcProtocolConfigRequest, RpcProtocolConfigResponse,
+};
+use crate::types::gas_price::{RpcGasPriceError, RpcGasPriceResponse};
+use crate::types::light_client::{
+    RpcLightClientExecutionProofRequest, RpcLightClientExecutionProofResponse,
+    RpcLightClientNextBlockError, RpcLightClientNextBlockRequest, RpcLightClientNextBlockResponse,
+    RpcLightClientProofError,
+};
+use crate::types::network_info::{RpcNetworkInfoError, RpcNetworkInfoResponse};
+use crate::types::query::{RpcQueryError, RpcQueryRequest, RpcQueryResponse};
+use crate::types::receipts::{RpcReceiptError, RpcReceiptRequest, RpcReceiptResponse};
+use crate::types::sandbox::{
+    RpcSandboxPatchStateError, RpcSandboxPatchStateRequest, RpcSandboxPatchStateResponse,
+};
+use crate::types::status::{RpcHealthResponse, RpcStatusError, RpcStatusResponse};
+use crate::types::subscriptions::{
+    RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError, RpcSubscriptionNotification,
+    RpcUnsubscribeRequest, RpcUnsubscribeResponse,
+};
+use crate::types::transactions::{
+    RpcBroadcastTxSyncResponse, RpcTransactionError, RpcTransactionResponse,
+};
+use crate::types::validator::{
+    RpcValidatorError, RpcValidatorRequest, RpcValidatorResponse, RpcValidatorsOrderedRequest,
+    RpcValidatorsOrderedResponse,
+};
+
+const OPENRPC_VERSION: &str = "1.2.6";
+
+const SCHEMAS_PATH: &str = "#/components/schemas/";
+
+/// Schema of an object with all the fields of the given schemas, for structs flattening several
+/// enums. Derived schemas keep the variants of only one of them.
+pub(crate) fn all_of(schemas: Vec<Schema>) -> Schema {
+    Schema::Object(SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            all_of: Some(schemas),
+            ..Default::default()
+        })),
+        ..Default::default()
+    })
+}
+
+enum Params {
+    /// `params` is an object, the fields of the given schema are the params.
+    ByName(Schema),
+    /// `params` is an array of the given content descriptors.
+    ByPosition(Vec<Value>),
+}
+
+struct Method {
+    name: &'static str,
+    summary: &'static str,
+    params: Params,
+    result: Schema,
+    /// Schema of the `cause` of `HANDLER_ERROR` errors of the method.
+    error_cause: Option<Schema>,
+}
+
+impl Method {
+    fn by_name<Request: JsonSchema, Response: JsonSchema>(
+        gen: &mut SchemaGenerator,
+        name: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        // The request itself is inlined, the params are its fields.
+        let params = Params::ByName(Request::json_schema(gen));
+        Self { name, summary, params, result: gen.subschema_for::<Response>(), error_cause: None }
+    }
+
+    fn by_position<Response: JsonSchema>(
+        gen: &mut SchemaGenerator,
+        name: &'static str,
+        summary: &'static str,
+        params: Vec<Value>,
+    ) -> Self {
+        let params = Params::ByPosition(params);
+        Self { name, summary, params, result: gen.subschema_for::<Response>(), error_cause: None }
+    }
+
+    fn with_error<Error: JsonSchema>(mut self, gen: &mut SchemaGenerator) -> Self {
+        self.error_cause = Some(gen.subschema_for::<Error>());
+        self
+    }
+
+    fn to_value(&self, gen: &SchemaGenerator) -> Value {
+        let (param_structure, params) = match &self.params {
+            Params::ByName(schema) => {
+                let mut params = Vec::new();
+                collect_params(gen, schema, true, &mut params);
+                let params = params
+                    .into_iter()
+                    .map(|(name, schemas, required)| {
+                        json!({
+                            "name": name,
+                            "schema": merge_schemas(schemas),
+                            "required": required,
+                        })
+                    })
+                    .collect();
+                ("by-name", params)
+            }
+            Params::ByPosition(params) => ("by-position", params.clone()),
+        };
+        let mut errors = vec![json!({ "$ref": "#/components/errors/ServerError" })];
+        if !params.is_empty() {
+            errors.insert(0, json!({ "$ref": "#/components/errors/ParseError" }));
+        }
+        let mut method = json!({
+            "name": self.name,
+            "summary": self.summary,
+            "paramStructure": param_structure,
+            "params": params,
+            "result": { "name": "result", "schema": self.result },
+            "errors": errors,
+        });
+        if let Some(error_cause) = &self.error_cause {
+            method["x-error-cause"] = json!(error_cause);
+        }
+        method
+    }
+}
+
+/// Content descriptor of a positional param.
+fn param<T: JsonSchema>(gen: &mut SchemaGenerator, name: &str, required: bool) -> Value {
+    json!({ "name": name, "schema": gen.subschema_for::<T>(), "required": required })
+}
+
+/// Follows references to the definitions of the generator.
+fn resolve<'a>(gen: &'a SchemaGenerator, schema: &'a Schema) -> Option<&'a SchemaObject> {
+    match schema {
+        Schema::Object(SchemaObject { reference: Some(reference), .. }) => {
+            let name = reference.strip_prefix(SCHEMAS_PATH)?;
+            resolve(gen, gen.definitions().get(name)?)
+        }
+        Schema::Object(object) => Some(object),
+        Schema::Bool(_) => None,
+    }
+}
+
+/// Collects the fields of an object schema, including the fields of its alternatives, which are
+/// never required. Schemas of a field which differs between alternatives are all kept.
+fn collect_params(
+    gen: &SchemaGenerator,
+    schema: &Schema,
+    required: bool,
+    params: &mut Vec<(String, Vec<Schema>, bool)>,
+) {
+    let object = match resolve(gen, schema) {
+        Some(object) => object,
+        None => return,
+    };
+    if let Some(validation) = &object.object {
+        for (name, property) in &validation.properties {
+            let property_required = required && validation.required.contains(name);
+            match params.iter_mut().find(|(param_name, _, _)| param_name == name) {
+                Some((_, schemas, param_required)) => {
+                    if !schemas.contains(property) {
+                        schemas.push(property.clone());
+                    }
+                    *param_required &= property_required;
+                }
+                None => params.push((name.clone(), vec![property.clone()], property_required)),
+            }
+        }
+    }
+    if let Some(subschemas) = &object.subschemas {
+        for schema in subschemas.all_of.iter().flatten() {
+            collect_params(gen, schema, required, params);
+        }
+        let alternatives = subschemas.one_of.iter().chain(subschemas.any_of.iter()).flatten();
+        for schema in alternatives {
+            collect_params(gen, schema, false, params);
+        }
+    }
+}
+
+/// Merges the schemas of a field of different alternatives, e.g. values of an enum tag are
+/// merged into one enum.
+fn merge_schemas(mut schemas: Vec<Schema>) -> Schema {
+    if schemas.len() == 1 {
+        return schemas.pop().unwrap();
+    }
+    let enum_values: Option<Vec<Value>> = schemas
+        .iter()
+        .map(|schema| match schema {
+            Schema::Object(SchemaObject { enum_values: Some(values), .. }) => Some(values.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|values| values.concat());
+    match enum_values {
+        Some(enum_values) => {
+            let mut schema = match schemas.swap_remove(0) {
+                Schema::Object(schema) => schema,
+                Schema::Bool(_) => unreachable!("enums are objects"),
+            };
+            schema.enum_values = Some(enum_values);
+            Schema::Object(schema)
+        }
+        None => Schema::Object(SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(schemas),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }),
+    }
+}
+
+fn methods(gen: &mut SchemaGenerator, sandbox: bool) -> Vec<Method> {
+    let signed_transaction_params = vec![json!({
+        "name": "signed_transaction",
+        "summary": "Borsh serialized `SignedTransaction` encoded in base64",
+        "schema": { "type": "string" },
+        "required": true,
+    })];
+    let tx_status_params = vec![
+        param::<CryptoHash>(gen, "tx_hash", true),
+        param::<AccountId>(gen, "sender_account_id", true),
+    ];
+    let gas_price_params = vec![param::<Option<BlockId>>(gen, "block_id", true)];
+
+    let mut methods = vec![
+        Method::by_name::<RpcCreateCheckpointRequest, RpcCreateCheckpointResponse>(
+            gen,
+            "adm_create_checkpoint",
+            "Writes a checkpoint of the database to a directory. Only served to localhost.",
+        )
+        .with_error::<RpcCreateCheckpointError>(gen),
+        Method::by_name::<RpcBlockRequest, RpcBlockResponse>(
+            gen,
+            "block",
+            "Returns a block by its height or hash, or the latest block of a finality.",
+        )
+        .with_error::<RpcBlockError>(gen),
+        Method::by_position::<CryptoHash>(
+            gen,
+            "broadcast_tx_async",
+            "Sends a transaction and returns its hash right away.",
+            signed_transaction_params.clone(),
+        ),
+        Method::by_position::<RpcTransactionResponse>(
+            gen,
+            "broadcast_tx_commit",
+            "Sends a transaction and waits until it is executed.",
+            signed_transaction_params.clone(),
+        )
+        .with_error::<RpcTransactionError>(gen),
+        Method::by_name::<RpcChunkRequest, RpcChunkResponse>(
+            gen,
+            "chunk",
+            "Returns a chunk by its hash, or by a block and a shard.",
+        )
+        .with_error::<RpcChunkError>(gen),
+        Method::by_position::<RpcGasPriceResponse>(
+            gen,
+            "gas_price",
+            "Returns the gas price of a block, or of the latest block if `block_id` is null.",
+            gas_price_params,
+        )
+        .with_error::<RpcGasPriceError>(gen),
+        Method::by_position::<RpcHealthResponse>(
+            gen,
+            "health",
+            "Returns null if the node is healthy.",
+            vec![],
+        )
+        .with_error::<RpcStatusError>(gen),
+        Method::by_name::<
+            RpcLightClientExecutionProofRequest,
+            RpcLightClientExecutionProofResponse,
+        >(
+            gen,
+            "light_client_proof",
+            "Returns a proof of the outcome of a transaction or a receipt.",
+        )
+        .with_error::<RpcLightClientProofError>(gen),
+        Method::by_name::<RpcLightClientNextBlockRequest, RpcLightClientNextBlockResponse>(
+            gen,
+            "next_light_client_block",
+            "Returns the next light client block after the given one.",
+        )
+        .with_error::<RpcLightClientNextBlockError>(gen),
+        Method::by_position::<RpcNetworkInfoResponse>(
+            gen,
+            "network_info",
+            "Returns the peers of the node and the known producers.",
+            vec![],
+        )
+        .with_error::<RpcNetworkInfoError>(gen),
+        Method::by_name::<RpcQueryRequest, RpcQueryResponse>(
+            gen,
+            "query",
+            "Views accounts, access keys, contract code and state, or calls view functions.",
+        )
+        .with_error::<RpcQueryError>(gen),
+        Method::by_position::<Value>(gen, "rpc.discover", "Returns this document.", vec![]),
+        Method::by_position::<RpcStatusResponse>(
+            gen,
+            "status",
+            "Returns the status of the node.",
+            vec![],
+        )
+        .with_error::<RpcStatusError>(gen),
+        Method::by_position::<RpcTransactionResponse>(
+            gen,
+            "tx",
+            "Returns the status of a transaction.",
+            tx_status_params.clone(),
+        )
+        .with_error::<RpcTransactionError>(gen),
+        Method::by_name::<RpcValidatorRequest, RpcValidatorResponse>(
+            gen,
+            "validators",
+            "Returns the validators of an epoch, or of the latest epoch with `[null]` params.",
+        )
+        .with_error::<RpcValidatorError>(gen),
+        Method::by_position::<RpcBroadcastTxSyncResponse>(
+            gen,
+            "EXPERIMENTAL_broadcast_tx_sync",
+            "Sends a transaction and returns its hash once it is validated.",
+            signed_transaction_params.clone(),
+        )
+        .with_error::<RpcTransactionError>(gen),
+        Method::by_name::<
+            RpcStateChangesInBlockByTypeRequest,
+            RpcStateChangesInBlockByTypeResponse,
+        >(
+            gen,
+            "EXPERIMENTAL_changes",
+            "Returns the changes of a block to the state of the given accounts.",
+        )
+        .with_error::<RpcStateChangesError>(gen),
+        Method::by_name::<RpcStateChangesHistoryRequest, RpcStateChangesHistoryResponse>(
+            gen,
+            "EXPERIMENTAL_changes_history",
+            "Returns the changes of a state key made by the blocks in a range of heights.",
+        )
+        .with_error::<RpcStateChangesError>(gen),
+        Method::by_name::<RpcStateChangesInBlockRequest, RpcStateChangesInBlockResponse>(
+            gen,
+            "EXPERIMENTAL_changes_in_block",
+            "Returns the kinds of the state changes of a block.",
+        )
+        .with_error::<RpcStateChangesError>(gen),
+        Method::by_position::<RpcBroadcastTxSyncResponse>(
+            gen,
+            "EXPERIMENTAL_check_tx",
+            "Validates a transaction without sending it.",
+            signed_transaction_params,
+        )
+        .with_error::<RpcTransactionError>(gen),
+        Method::by_position::<GenesisConfig>(
+            gen,
+            "EXPERIMENTAL_genesis_config",
+            "Returns the genesis config of the chain.",
+            vec![],
+        ),
+        Method::by_name::<
+            RpcLightClientExecutionProofRequest,
+            RpcLightClientExecutionProofResponse,
+        >(gen, "EXPERIMENTAL_light_client_proof", "Same as `light_client_proof`.")
+        .with_error::<RpcLightClientProofError>(gen),
+        Method::by_name::<RpcProtocolConfigRequest, RpcProtocolConfigResponse>(
+            gen,
+            "EXPERIMENTAL_protocol_config",
+            "Returns the protocol config at a block.",
+        )
+        .with_error::<RpcProtocolConfigError>(gen),
+        Method::by_name::<RpcReceiptRequest, RpcReceiptResponse>(
+            gen,
+            "EXPERIMENTAL_receipt",
+            "Returns a receipt by its id.",
+        )
+        .with_error::<RpcReceiptError>(gen),
+        Method::by_position::<RpcTransactionResponse>(
+            gen,
+            "EXPERIMENTAL_tx_status",
+            "Returns the status of a transaction with the receipts of its execution.",
+            tx_status_params,
+        )
+        .with_error::<RpcTransactionError>(gen),
+        Method::by_name::<RpcValidatorsOrderedRequest, RpcValidatorsOrderedResponse>(
+            gen,
+            "EXPERIMENTAL_validators_ordered",
+            "Returns the validators of the epoch of a block, ordered as block producers.",
+        )
+        .with_error::<RpcValidatorError>(gen),
+        Method::by_name::<RpcSubscribeRequest, RpcSubscribeResponse>(
+            gen,
+            "subscribe",
+            "Subscribes to `subscription` notifications. Only served over WebSocket.",
+        )
+        .with_error::<RpcSubscriptionError>(gen),
+        Method::by_name::<RpcUnsubscribeRequest, RpcUnsubscribeResponse>(
+            gen,
+            "unsubscribe",
+            "Cancels a subscription. Only served over WebSocket.",
+        )
+        .with_error::<RpcSubscriptionError>(gen),
+    ];
+    if sandbox {
+        methods.push(
+            Method::by_name::<RpcSandboxPatchStateRequest, RpcSandboxPatchStateResponse>(
+                gen,
+                "sandbox_patch_state",
+                "Overwrites records of the state of the sandbox node.",
+            )
+            .with_error::<RpcSandboxPatchStateError>(gen),
+        );
+    }
+    // Params of the `subscription` notifications, they aren't methods.
+    gen.subschema_for::<RpcSubscriptionNotification>();
+    methods
+}
+
+/// Builds the OpenRPC document of the JSON RPC methods, `sandbox` adds the methods which are only
+/// served by sandbox nodes.
+pub fn openrpc_document(sandbox: bool) -> Value {
+    let settings = SchemaSettings::draft07().with(|settings| {
+        settings.definitions_path = SCHEMAS_PATH.to_string();
+    });
+    let mut gen = settings.into_generator();
+    let methods = methods(&mut gen, sandbox);
+    gen.subschema_for::<RpcError>();
+    let methods: Vec<Value> = methods.iter().map(|method| method.to_value(&gen)).collect();
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "NEAR JSON RPC",
+            "version": near_primitives::version::PROTOCOL_VERSION.to_string(),
+            "description": "Errors are `RpcError` objects. The `x-error-cause` of a method is \
+                the schema of the `cause` of its `HANDLER_ERROR` errors.",
+        },
+        "methods": methods,
+        "components": {
+            "schemas": gen.definitions(),
+            "errors": {
+                "ParseError": { "code": -32_700, "message": "Parse error" },
+                "ServerError": { "code": -32_000, "message": "Server error" },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method<'a>(document: &'a Value, name: &str) -> &'a Value {
+        document["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|method| method["name"] == name)
+            .unwrap_or_else(|| panic!("Method {} is missing", name))
+    }
+
+    fn param_names(method: &Value) -> Vec<&str> {
+        method["params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|param| param["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_references_are_defined() {
+        let document = openrpc_document(false);
+        let schemas = document["components"]["schemas"].as_object().unwrap();
+        let text = document.to_string();
+        for reference in text.split("\"$ref\":\"").skip(1) {
+            let reference = &reference[..reference.find('"').unwrap()];
+            if let Some(name) = reference.strip_prefix(SCHEMAS_PATH) {
+                assert!(schemas.contains_key(name), "{} is not defined", reference);
+            }
+        }
+        assert!(schemas.contains_key("RpcError"));
+    }
+
+    #[test]
+    fn test_by_name_params() {
+        let document = openrpc_document(false);
+        let query = method(&document, "query");
+        assert_eq!(query["paramStructure"], "by-name");
+        let names = param_names(query);
+        // Fields of the flattened block reference and of the query request.
+        for name in ["finality", "block_id", "request_type", "account_id", "method_name"] {
+            assert!(names.contains(&name), "query doesn't have the {} param", name);
+        }
+        let request_type =
+            query["params"].as_array().unwrap().iter().find(|p| p["name"] == "request_type");
+        let request_types = request_type.unwrap()["schema"]["enum"].as_array().unwrap();
+        assert!(request_types.contains(&json!("view_account")));
+        assert!(request_types.contains(&json!("call_function")));
+        assert_eq!(query["x-error-cause"]["$ref"], "#/components/schemas/RpcQueryError");
+
+        let history = method(&document, "EXPERIMENTAL_changes_history");
+        let from_block_height =
+            history["params"].as_array().unwrap().iter().find(|p| p["name"] == "from_block_height");
+        assert_eq!(from_block_height.unwrap()["required"], true);
+    }
+
+    #[test]
+    fn test_by_position_params() {
+        let document = openrpc_document(false);
+        let tx = method(&document, "tx");
+        assert_eq!(tx["paramStructure"], "by-position");
+        assert_eq!(param_names(tx), ["tx_hash", "sender_account_id"]);
+        assert_eq!(param_names(method(&document, "broadcast_tx_commit")), ["signed_transaction"]);
+        assert!(param_names(method(&document, "status")).is_empty());
+    }
+}
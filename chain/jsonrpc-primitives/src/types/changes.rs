@@ -1,13 +1,14 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RpcStateChangesInBlockRequest {
     #[serde(flatten)]
     pub block_reference: near_primitives::types::BlockReference,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RpcStateChangesInBlockResponse {
     pub block_hash: near_primitives::hash::CryptoHash,
     pub changes: near_primitives::views::StateChangesView,
@@ -21,13 +22,50 @@ pub struct RpcStateChangesInBlockByTypeRequest {
     pub state_changes_request: near_primitives::views::StateChangesRequestView,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl JsonSchema for RpcStateChangesInBlockByTypeRequest {
+    fn schema_name() -> String {
+        "RpcStateChangesInBlockByTypeRequest".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        crate::openrpc::all_of(vec![
+            gen.subschema_for::<near_primitives::types::BlockReference>(),
+            gen.subschema_for::<near_primitives::views::StateChangesRequestView>(),
+        ])
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RpcStateChangesInBlockByTypeResponse {
     pub block_hash: near_primitives::hash::CryptoHash,
     pub changes: near_primitives::views::StateChangesKindsView,
 }
 
-#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RpcStateChangesHistoryRequest {
+    #[serde(flatten)]
+    pub key: near_primitives::views::StateKeyView,
+    pub from_block_height: near_primitives::types::BlockHeight,
+    /// Defaults to the height of the chain head.
+    pub to_block_height: Option<near_primitives::types::BlockHeight>,
+    /// Maximum number of returned changes, 100 by default and at most 1000.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RpcStateChangesHistoryResponse {
+    pub changes: Vec<near_primitives::views::StateChangeInBlockView>,
+    /// Set if not all changes up to `to_block_height` are returned, either because of the limit
+    /// or because a single request looks at no more than 1000000 heights. Pass it as
+    /// `from_block_height` to get the next page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_from_block_height: Option<near_primitives::types::BlockHeight>,
+}
+
+/// Number of changes returned by `EXPERIMENTAL_changes_history` if the limit is not set.
+pub const DEFAULT_STATE_CHANGES_HISTORY_LIMIT: usize = 100;
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcStateChangesError {
     #[error("Block not found: {error_message}")]
@@ -53,6 +91,12 @@ impl RpcStateChangesInBlockByTypeRequest {
     }
 }
 
+impl RpcStateChangesHistoryRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<Self>(value)?)
+    }
+}
+
 impl From<near_client_primitives::types::GetBlockError> for RpcStateChangesError {
     fn from(error: near_client_primitives::types::GetBlockError) -> Self {
         match error {
@@ -0,0 +1,78 @@
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
+use near_primitives::version::DbVersion;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct RpcCreateCheckpointRequest {
+    /// Directory to write the checkpoint to. It must not exist and has to be inside of the
+    /// checkpoints directory of the node; a relative path is resolved against it.
+    pub path: std::path::PathBuf,
+}
+
+impl RpcCreateCheckpointRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcCreateCheckpointRequest>(value)?)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct RpcCreateCheckpointResponse {
+    pub path: std::path::PathBuf,
+    pub db_version: DbVersion,
+    pub head_height: BlockHeight,
+    pub head_hash: CryptoHash,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcCreateCheckpointError {
+    #[error("Checkpoint cannot be created: {error_message}")]
+    CheckpointError { error_message: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<near_client_primitives::types::CreateCheckpointResponse> for RpcCreateCheckpointResponse {
+    fn from(response: near_client_primitives::types::CreateCheckpointResponse) -> Self {
+        Self {
+            path: response.path,
+            db_version: response.db_version,
+            head_height: response.head_height,
+            head_hash: response.head_hash,
+        }
+    }
+}
+
+impl From<near_client_primitives::types::CreateCheckpointError> for RpcCreateCheckpointError {
+    fn from(error: near_client_primitives::types::CreateCheckpointError) -> Self {
+        match error {
+            near_client_primitives::types::CreateCheckpointError::CheckpointError {
+                error_message,
+            } => Self::CheckpointError { error_message },
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcCreateCheckpointError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcCreateCheckpointError> for crate::errors::RpcError {
+    fn from(error: RpcCreateCheckpointError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcCreateCheckpointError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
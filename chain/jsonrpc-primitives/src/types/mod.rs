@@ -1,5 +1,6 @@
 pub mod blocks;
 pub mod changes;
+pub mod checkpoint;
 pub mod chunks;
 pub mod config;
 pub mod gas_price;
@@ -9,5 +10,6 @@ pub mod query;
 pub mod receipts;
 pub mod sandbox;
 pub mod status;
+pub mod subscriptions;
 pub mod transactions;
 pub mod validator;
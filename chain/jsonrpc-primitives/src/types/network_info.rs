@@ -1,7 +1,8 @@
 use near_network_primitives::types::{KnownProducer, PeerInfo};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct RpcNetworkInfoResponse {
     pub active_peers: Vec<PeerInfo>,
     pub num_active_peers: usize,
@@ -12,7 +13,7 @@ pub struct RpcNetworkInfoResponse {
     pub known_producers: Vec<KnownProducer>,
 }
 
-#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcNetworkInfoError {
     #[error("Internal error: {error_message}")]
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -12,7 +13,20 @@ pub struct RpcQueryRequest {
     pub request: near_primitives::views::QueryRequest,
 }
 
-#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+impl JsonSchema for RpcQueryRequest {
+    fn schema_name() -> String {
+        "RpcQueryRequest".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        crate::openrpc::all_of(vec![
+            gen.subschema_for::<near_primitives::types::BlockReference>(),
+            gen.subschema_for::<near_primitives::views::QueryRequest>(),
+        ])
+    }
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcQueryError {
     #[error("There are no fully synchronized blocks on the node yet")]
@@ -63,7 +77,7 @@ pub enum RpcQueryError {
     InternalError { error_message: String },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct RpcQueryResponse {
     #[serde(flatten)]
     pub kind: QueryResponseKind,
@@ -71,7 +85,7 @@ pub struct RpcQueryResponse {
     pub block_hash: near_primitives::hash::CryptoHash,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(untagged)]
 pub enum QueryResponseKind {
     ViewAccount(near_primitives::views::AccountView),
@@ -0,0 +1,102 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Identifier of a subscription, unique within a WebSocket connection.
+pub type SubscriptionId = u64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RpcSubscribeRequest {
+    /// Every block which becomes final.
+    FinalBlocks,
+    /// The final outcome of a transaction, sent once all its outcomes are in final blocks.
+    TransactionOutcome { tx_hash: near_primitives::hash::CryptoHash },
+    /// Changes of final blocks to the state of the given accounts.
+    StateChanges { account_ids: Vec<near_primitives::types::AccountId> },
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RpcSubscribeResponse {
+    pub subscription: SubscriptionId,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RpcUnsubscribeRequest {
+    pub subscription: SubscriptionId,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RpcUnsubscribeResponse {
+    pub subscription: SubscriptionId,
+}
+
+/// Params of the `subscription` notifications sent over the WebSocket connection.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RpcSubscriptionNotification {
+    pub subscription: SubscriptionId,
+    pub result: RpcSubscriptionEvent,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RpcSubscriptionEvent {
+    FinalBlock {
+        block: near_primitives::views::BlockView,
+    },
+    /// Sent once, the subscription ends after it.
+    TransactionOutcome {
+        outcome: near_primitives::views::FinalExecutionOutcomeView,
+    },
+    /// Sent instead of the outcome if the transaction has no final outcome ten minutes after
+    /// the subscription. The subscription ends after it.
+    TransactionExpired {
+        tx_hash: near_primitives::hash::CryptoHash,
+    },
+    /// Sent for final blocks which changed the state of at least one of the accounts.
+    StateChanges {
+        block_hash: near_primitives::hash::CryptoHash,
+        block_height: near_primitives::types::BlockHeight,
+        changes: near_primitives::views::StateChangesView,
+    },
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSubscriptionError {
+    #[error("Subscription {subscription} doesn't exist")]
+    UnknownSubscription { subscription: SubscriptionId },
+    #[error("At most {limit} subscriptions are allowed per connection")]
+    TooManySubscriptions { limit: usize },
+    #[error("At most {limit} transactions can be watched by all subscribers together")]
+    TooManyWatchedTransactions { limit: usize },
+    #[error("Method {method} is only available over WebSocket")]
+    WebSocketRequired { method: String },
+}
+
+impl RpcSubscribeRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<Self>(value)?)
+    }
+}
+
+impl RpcUnsubscribeRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<Self>(value)?)
+    }
+}
+
+impl From<RpcSubscriptionError> for crate::errors::RpcError {
+    fn from(error: RpcSubscriptionError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSubscriptionError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
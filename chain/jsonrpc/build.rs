@@ -0,0 +1,10 @@
+//! Generates the OpenRPC document served by `rpc.discover` from the types of
+//! `near-jsonrpc-primitives`.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    let sandbox = std::env::var_os("CARGO_FEATURE_SANDBOX").is_some();
+    let document = near_jsonrpc_primitives::openrpc::openrpc_document(sandbox);
+    let path = std::path::Path::new(&std::env::var("OUT_DIR")?).join("openrpc.json");
+    std::fs::write(path, serde_json::to_string_pretty(&document)?)?;
+    Ok(())
+}
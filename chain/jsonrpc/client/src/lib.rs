@@ -8,6 +8,7 @@ use serde::Serialize;
 use near_jsonrpc_primitives::errors::RpcError;
 use near_jsonrpc_primitives::message::{from_slice, Message};
 use near_jsonrpc_primitives::types::changes::{
+    RpcStateChangesHistoryRequest, RpcStateChangesHistoryResponse,
     RpcStateChangesInBlockByTypeRequest, RpcStateChangesInBlockByTypeResponse,
 };
 use near_jsonrpc_primitives::types::validator::RpcValidatorsOrderedRequest;
@@ -227,6 +228,13 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "block", request)
     }
 
+    pub fn adm_create_checkpoint(
+        &self,
+        request: near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointResponse> {
+        call_method(&self.client, &self.server_addr, "adm_create_checkpoint", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_changes(
         &self,
@@ -235,6 +243,14 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_changes", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_changes_history(
+        &self,
+        request: RpcStateChangesHistoryRequest,
+    ) -> RpcRequest<RpcStateChangesHistoryResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_changes_history", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_validators_ordered(
         &self,
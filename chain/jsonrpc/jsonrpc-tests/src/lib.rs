@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use actix::Addr;
 use futures::{future, future::LocalBoxFuture, FutureExt, TryFutureExt};
 use once_cell::sync::Lazy;
@@ -5,8 +7,8 @@ use serde_json::json;
 
 use near_chain_configs::GenesisConfig;
 use near_client::test_utils::setup_no_network_with_validity_period_and_no_epoch_sync;
-use near_client::ViewClientActor;
-use near_jsonrpc::{start_http, RpcConfig};
+use near_client::{ChainEvents, ViewClientActor};
+use near_jsonrpc::{start_http, AccessControl, RpcConfig};
 use near_jsonrpc_primitives::message::{from_slice, Message};
 use near_network::test_utils::open_port;
 #[cfg(feature = "test_features")]
@@ -48,11 +50,16 @@ pub fn start_all_with_validity_period_and_no_epoch_sync(
     #[cfg(feature = "test_features")]
     let (peer_manager_addr, routing_table_addr) = make_peer_manager_routing_table_addr_pair();
 
+    let config = RpcConfig::new(&addr);
+    let access_control = Arc::new(AccessControl::new(config.access_config.clone()));
     start_http(
-        RpcConfig::new(&addr),
+        config,
         TEST_GENESIS_CONFIG.clone(),
         client_addr.clone(),
         view_client_addr.clone(),
+        ChainEvents::new(),
+        None,
+        access_control,
         #[cfg(feature = "test_features")]
         peer_manager_addr,
         #[cfg(feature = "test_features")]
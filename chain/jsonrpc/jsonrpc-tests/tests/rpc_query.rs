@@ -721,3 +721,73 @@ fn test_get_chunk_with_object_in_params() {
         assert_eq!(chunk.header.chunk_hash, same_chunk.header.chunk_hash);
     });
 }
+
+/// Batched requests are answered in order, errors of some of them don't fail the others.
+#[test]
+fn test_batch() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let batch = json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "block", "params": { "block_id": 0 } },
+            { "jsonrpc": "2.0", "id": 2, "method": "unknown_method", "params": [] },
+            1,
+            { "jsonrpc": "2.0", "id": 3, "method": "block", "params": { "finality": "final" } },
+        ]);
+        let mut response = client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "application/json"))
+            .send_json(&batch)
+            .await
+            .unwrap();
+        let responses: serde_json::Value = response.json().await.unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], json!(1));
+        assert_eq!(responses[0]["result"]["header"]["height"], json!(0));
+        assert_eq!(responses[1]["id"], json!(2));
+        assert_eq!(responses[1]["error"]["code"], json!(-32601));
+        assert_eq!(responses[2]["id"], serde_json::Value::Null);
+        assert_eq!(responses[2]["error"]["code"], json!(-32600));
+        assert_eq!(responses[3]["id"], json!(3));
+        assert_eq!(responses[3]["result"]["header"]["height"], json!(0));
+    });
+}
+
+/// Notifications of a batch are processed but not answered, a batch of notifications returns
+/// nothing.
+#[test]
+fn test_batch_notifications() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "block", "params": { "block_id": 0 } },
+            { "jsonrpc": "2.0", "id": 1, "method": "block", "params": { "block_id": 0 } },
+            { "jsonrpc": "2.0", "method": "unknown_method", "params": [] },
+        ]);
+        let mut response = client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "application/json"))
+            .send_json(&batch)
+            .await
+            .unwrap();
+        let responses: serde_json::Value = response.json().await.unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], json!(1));
+        assert_eq!(responses[0]["result"]["header"]["height"], json!(0));
+
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "block", "params": { "block_id": 0 } },
+            { "jsonrpc": "2.0", "method": "unknown_method", "params": [] },
+        ]);
+        let mut response = client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "application/json"))
+            .send_json(&batch)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+        assert!(response.body().await.unwrap().is_empty());
+    });
+}
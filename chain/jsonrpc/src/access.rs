@@ -0,0 +1,400 @@
+//! Access control of the JSON RPC server: method allow and deny lists and token bucket rate
+//! limits per client and method. The gRPC server shares it, so that clients have the same
+//! limits on both.
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::HttpRequest;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use near_jsonrpc_primitives::errors::RpcError;
+
+use crate::metrics;
+
+/// Header with the API key which selects the rate limits of the client.
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// Header in which reverse proxies pass the address of the client, see
+/// `RpcAccessConfig::trusted_proxies`.
+pub const FORWARDED_FOR_HEADER: &str = "X-Forwarded-For";
+
+/// Names of the served methods, as listed by `rpc.discover`.
+static METHOD_NAMES: Lazy<HashSet<String>> = Lazy::new(|| {
+    crate::OPENRPC_DOCUMENT["methods"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|method| method["name"].as_str().map(str::to_owned))
+        .collect()
+});
+
+/// How often buckets which filled up again are forgotten.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Number of requests a client may make per second on average.
+    pub per_second: u32,
+    /// Number of requests a client may make at once after being idle.
+    pub burst: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RateLimits {
+    /// Limit of all requests of a client. Requests are not limited if not set.
+    #[serde(default)]
+    pub requests: Option<RateLimit>,
+    /// Limits of requests of a client to single methods, by method name. They apply on top of
+    /// the limit of all requests.
+    #[serde(default)]
+    pub methods: HashMap<String, RateLimit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RpcAccessConfig {
+    /// If not empty, only the listed methods are served. A pattern ending with `*` matches all
+    /// methods starting with the rest of it, e.g. `EXPERIMENTAL_*`.
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
+    /// Methods which are not served even if they are allowed, in the same format.
+    #[serde(default)]
+    pub denied_methods: Vec<String>,
+    /// Limits of clients without a known API key. Such clients are told apart by IP address, so
+    /// behind a reverse proxy all of them share the limits unless the proxy is trusted.
+    #[serde(default)]
+    pub rate_limits: RateLimits,
+    /// Limits of clients sending an API key in the `X-Api-Key` header, by API key. All clients
+    /// sending the same key share the limits.
+    #[serde(default)]
+    pub api_keys: HashMap<String, RateLimits>,
+    /// Addresses of reverse proxies whose `X-Forwarded-For` header is trusted. The address of a
+    /// client connecting through them is the last address in the header which is not one of
+    /// theirs. The header of other clients is ignored, as they may put anything in it.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// Who sent a request.
+#[derive(Clone, Debug, Default)]
+pub struct RpcClient {
+    pub ip: Option<IpAddr>,
+    pub api_key: Option<String>,
+}
+
+impl RpcClient {
+    /// Client connecting from `peer_ip`, with the values of the `X-Forwarded-For` and
+    /// `X-Api-Key` headers.
+    pub fn new(
+        peer_ip: Option<IpAddr>,
+        forwarded_for: Option<&str>,
+        api_key: Option<&str>,
+        trusted_proxies: &[IpAddr],
+    ) -> Self {
+        Self {
+            ip: peer_ip.map(|ip| forwarded_ip(ip, forwarded_for, trusted_proxies)),
+            api_key: api_key.map(str::to_owned),
+        }
+    }
+
+    pub fn from_request(request: &HttpRequest, trusted_proxies: &[IpAddr]) -> Self {
+        let header = |name: &str| request.headers().get(name).and_then(|value| value.to_str().ok());
+        Self::new(
+            request.peer_addr().map(|addr| addr.ip()),
+            header(FORWARDED_FOR_HEADER),
+            header(API_KEY_HEADER),
+            trusted_proxies,
+        )
+    }
+
+    pub fn is_localhost(&self) -> bool {
+        self.ip.map_or(false, |ip| ip.is_loopback())
+    }
+}
+
+/// Follows the `X-Forwarded-For` header from the end for as long as the addresses are of
+/// trusted proxies.
+fn forwarded_ip(
+    peer_ip: IpAddr,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> IpAddr {
+    let mut ip = peer_ip;
+    for address in forwarded_for.unwrap_or_default().rsplit(',') {
+        if !trusted_proxies.contains(&ip) {
+            break;
+        }
+        match address.trim().parse() {
+            Ok(forwarded_ip) => ip = forwarded_ip,
+            Err(_) => break,
+        }
+    }
+    ip
+}
+
+/// Label of `method` in the metrics. Method names come from clients, so names of methods which
+/// are not served are replaced with `other`, to keep the number of label values bounded.
+fn method_label(method: &str) -> &str {
+    if METHOD_NAMES.contains(method) {
+        method
+    } else {
+        "other"
+    }
+}
+
+fn matches_method(pattern: &str, method: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => pattern == method,
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Adds the tokens accumulated since the last update, returns whether there is a token for
+    /// a request.
+    fn refill(&mut self, limit: RateLimit, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second as f64).min(limit.burst as f64);
+        self.updated = now;
+        self.tokens >= 1.0
+    }
+
+    fn is_full(&self, limit: RateLimit, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * limit.per_second as f64 >= limit.burst as f64
+    }
+}
+
+/// Key of the bucket of a client: the API key or the IP address, and the method for method
+/// limits.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum ClientKey {
+    ApiKey(String),
+    Ip(Option<IpAddr>),
+}
+
+type BucketKey = (ClientKey, Option<String>);
+
+struct Buckets {
+    buckets: HashMap<BucketKey, (TokenBucket, RateLimit)>,
+    pruned: Instant,
+}
+
+/// Why a request is rejected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessDenied {
+    /// The method is not served.
+    NotAllowed,
+    /// The client ran out of tokens of one of its rate limits.
+    RateLimited,
+}
+
+pub struct AccessControl {
+    config: RpcAccessConfig,
+    buckets: Mutex<Buckets>,
+}
+
+impl AccessControl {
+    pub fn new(config: RpcAccessConfig) -> Self {
+        let buckets = Buckets { buckets: HashMap::new(), pruned: Instant::now() };
+        Self { config, buckets: Mutex::new(buckets) }
+    }
+
+    fn is_allowed(&self, method: &str) -> bool {
+        let allowed = self.config.allowed_methods.is_empty()
+            || self.config.allowed_methods.iter().any(|pattern| matches_method(pattern, method));
+        allowed && !self.config.denied_methods.iter().any(|pattern| matches_method(pattern, method))
+    }
+
+    /// Who sent `request`.
+    pub fn client(&self, request: &HttpRequest) -> RpcClient {
+        RpcClient::from_request(request, &self.config.trusted_proxies)
+    }
+
+    /// Client connecting from `peer_ip`, see `RpcClient::new`.
+    pub fn client_from(
+        &self,
+        peer_ip: Option<IpAddr>,
+        forwarded_for: Option<&str>,
+        api_key: Option<&str>,
+    ) -> RpcClient {
+        RpcClient::new(peer_ip, forwarded_for, api_key, &self.config.trusted_proxies)
+    }
+
+    /// Checks whether the method is served, and takes tokens of the rate limits of the client.
+    pub fn check(&self, method: &str, client: &RpcClient) -> Result<(), RpcError> {
+        self.authorize(method, client).map_err(|denied| match denied {
+            AccessDenied::NotAllowed => RpcError::method_not_allowed(method.to_string()),
+            AccessDenied::RateLimited => RpcError::rate_limit_exceeded(method.to_string()),
+        })
+    }
+
+    /// Same as `check`, for servers other than JSON RPC. `method` is the name of the JSON RPC
+    /// counterpart of the method, which the config refers to.
+    pub fn authorize(&self, method: &str, client: &RpcClient) -> Result<(), AccessDenied> {
+        self.check_at(method, client, Instant::now())
+    }
+
+    fn check_at(&self, method: &str, client: &RpcClient, now: Instant) -> Result<(), AccessDenied> {
+        if !self.is_allowed(method) {
+            metrics::RPC_REJECTED_REQUEST_COUNT
+                .with_label_values(&[method_label(method), "not_allowed"])
+                .inc();
+            return Err(AccessDenied::NotAllowed);
+        }
+        let (client_key, rate_limits) =
+            match client.api_key.as_ref().and_then(|key| self.config.api_keys.get_key_value(key)) {
+                Some((key, rate_limits)) => (ClientKey::ApiKey(key.clone()), rate_limits),
+                None => (ClientKey::Ip(client.ip), &self.config.rate_limits),
+            };
+        let limits =
+            rate_limits.requests.map(|limit| (None, limit)).into_iter().chain(
+                rate_limits.methods.get(method).map(|limit| (Some(method.to_string()), *limit)),
+            );
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.saturating_duration_since(buckets.pruned) >= PRUNE_INTERVAL {
+            // Full buckets are the same as the new ones.
+            buckets.buckets.retain(|_, (bucket, limit)| !bucket.is_full(*limit, now));
+            buckets.pruned = now;
+        }
+        // Tokens are only taken if all the limits allow the request, so that requests rejected
+        // by a method limit don't count towards the limit of all requests.
+        let mut keys = vec![];
+        for (bucket_method, limit) in limits {
+            let key = (client_key.clone(), bucket_method);
+            let (bucket, _) = buckets.buckets.entry(key.clone()).or_insert_with(|| {
+                (TokenBucket { tokens: limit.burst as f64, updated: now }, limit)
+            });
+            if !bucket.refill(limit, now) {
+                metrics::RPC_REJECTED_REQUEST_COUNT
+                    .with_label_values(&[method_label(method), "rate_limited"])
+                    .inc();
+                return Err(AccessDenied::RateLimited);
+            }
+            keys.push(key);
+        }
+        for key in keys {
+            let (bucket, _) = buckets.buckets.get_mut(&key).expect("bucket was just refilled");
+            bucket.tokens -= 1.0;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use actix_web::test::TestRequest;
+
+    use super::{
+        method_label, AccessControl, RateLimit, RateLimits, RpcAccessConfig, RpcClient,
+        FORWARDED_FOR_HEADER,
+    };
+
+    fn client(ip: &str, api_key: Option<&str>) -> RpcClient {
+        RpcClient { ip: Some(ip.parse().unwrap()), api_key: api_key.map(str::to_owned) }
+    }
+
+    #[test]
+    fn test_allowed_methods() {
+        let access_control = AccessControl::new(RpcAccessConfig {
+            denied_methods: vec!["adv_*".to_string(), "EXPERIMENTAL_changes".to_string()],
+            ..Default::default()
+        });
+        let client = client("1.2.3.4", None);
+        assert!(access_control.check("query", &client).is_ok());
+        assert!(access_control.check("EXPERIMENTAL_changes_in_block", &client).is_ok());
+        assert!(access_control.check("EXPERIMENTAL_changes", &client).is_err());
+        assert!(access_control.check("adv_produce_blocks", &client).is_err());
+
+        let access_control = AccessControl::new(RpcAccessConfig {
+            allowed_methods: vec!["query".to_string(), "EXPERIMENTAL_*".to_string()],
+            denied_methods: vec!["EXPERIMENTAL_changes".to_string()],
+            ..Default::default()
+        });
+        assert!(access_control.check("query", &client).is_ok());
+        assert!(access_control.check("EXPERIMENTAL_changes_in_block", &client).is_ok());
+        assert!(access_control.check("EXPERIMENTAL_changes", &client).is_err());
+        assert!(access_control.check("block", &client).is_err());
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let rate_limits = RateLimits {
+            requests: Some(RateLimit { per_second: 10, burst: 3 }),
+            methods: [("query".to_string(), RateLimit { per_second: 1, burst: 1 })]
+                .into_iter()
+                .collect(),
+        };
+        let access_control = AccessControl::new(RpcAccessConfig {
+            rate_limits,
+            api_keys: [("key".to_string(), RateLimits::default())].into_iter().collect(),
+            ..Default::default()
+        });
+        let now = Instant::now();
+        let alice = client("1.2.3.4", None);
+        assert!(access_control.check_at("query", &alice, now).is_ok());
+        // Rejected requests don't take tokens of the limit of all requests.
+        assert!(access_control.check_at("query", &alice, now).is_err());
+        assert!(access_control.check_at("query", &alice, now).is_err());
+        assert!(access_control.check_at("block", &alice, now).is_ok());
+        assert!(access_control.check_at("block", &alice, now).is_ok());
+        assert!(access_control.check_at("block", &alice, now).is_err());
+        // Other clients have their own buckets, clients with an API key have no limits.
+        assert!(access_control.check_at("query", &client("1.2.3.5", None), now).is_ok());
+        for _ in 0..10 {
+            assert!(access_control.check_at("query", &client("1.2.3.4", Some("key")), now).is_ok());
+        }
+        // Unknown API keys get the default limits.
+        assert!(access_control.check_at("query", &client("1.2.3.4", Some("other")), now).is_err());
+
+        let later = now + Duration::from_millis(200);
+        assert!(access_control.check_at("block", &alice, later).is_ok());
+        assert!(access_control.check_at("block", &alice, later).is_ok());
+        assert!(access_control.check_at("block", &alice, later).is_err());
+        let later = now + Duration::from_secs(1);
+        assert!(access_control.check_at("query", &alice, later).is_ok());
+    }
+
+    #[test]
+    fn test_forwarded_for() {
+        let proxy = "10.0.0.1".parse().unwrap();
+        let request = |peer_addr: &str, forwarded_for: Option<&str>| {
+            let request = TestRequest::default().peer_addr(peer_addr.parse().unwrap());
+            match forwarded_for {
+                Some(value) => request.insert_header((FORWARDED_FOR_HEADER, value)),
+                None => request,
+            }
+            .to_http_request()
+        };
+        let client_ip = |peer_addr, forwarded_for| {
+            RpcClient::from_request(&request(peer_addr, forwarded_for), &[proxy]).ip.unwrap()
+        };
+        assert_eq!(client_ip("10.0.0.1:3030", Some("1.2.3.4")), "1.2.3.4".parse().unwrap());
+        // Addresses added by clients before the trusted proxy are ignored.
+        assert_eq!(
+            client_ip("10.0.0.1:3030", Some("5.6.7.8, 1.2.3.4, 10.0.0.1")),
+            "1.2.3.4".parse().unwrap()
+        );
+        assert_eq!(client_ip("10.0.0.1:3030", None), proxy);
+        assert_eq!(client_ip("10.0.0.1:3030", Some("unknown")), proxy);
+        // The header of clients which are not trusted proxies is ignored.
+        assert_eq!(client_ip("1.2.3.4:3030", Some("5.6.7.8")), "1.2.3.4".parse().unwrap());
+    }
+
+    #[test]
+    fn test_method_label() {
+        assert_eq!(method_label("query"), "query");
+        assert_eq!(method_label("EXPERIMENTAL_changes_history"), "EXPERIMENTAL_changes_history");
+        assert_eq!(method_label("made_up_method"), "other");
+    }
+}
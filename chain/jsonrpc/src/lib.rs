@@ -1,12 +1,16 @@
 #![doc = include_str!("../README.md")]
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix::Addr;
 use actix_cors::Cors;
-use actix_web::{http, middleware, web, App, Error as HttpError, HttpResponse, HttpServer};
+use actix_web::{
+    http, middleware, web, App, Error as HttpError, HttpRequest, HttpResponse, HttpServer,
+};
 use futures::Future;
 use futures::FutureExt;
+use once_cell::sync::Lazy;
 use prometheus;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -15,15 +19,16 @@ use tracing::info;
 
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    ClientActor, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome, GetGasPrice,
-    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, Query, Status, TxStatus,
-    TxStatusError, ViewClientActor,
+    ChainEvents, CheckpointActor, ClientActor, CreateCheckpoint, GetBlock, GetBlockProof, GetChunk,
+    GetExecutionOutcome, GetGasPrice, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig,
+    GetReceipt, GetStateChanges, GetStateChangesHistory, GetStateChangesInBlock, GetValidatorInfo,
+    GetValidatorOrdered, Query, Status, TxStatus, TxStatusError, ViewClientActor,
 };
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_primitives::errors::RpcError;
-use near_jsonrpc_primitives::message::{Message, Request};
+use near_jsonrpc_primitives::message::{Message, Notification, Request};
 use near_jsonrpc_primitives::types::config::RpcProtocolConfigResponse;
+use near_jsonrpc_primitives::types::subscriptions::RpcSubscriptionError;
 use near_metrics::{Encoder, TextEncoder};
 use near_network::types::{NetworkClientMessages, NetworkClientResponses};
 use near_primitives::hash::CryptoHash;
@@ -32,7 +37,20 @@ use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::AccountId;
 use near_primitives::views::FinalExecutionOutcomeViewEnum;
 
+pub use crate::access::{
+    AccessControl, AccessDenied, RateLimit, RateLimits, RpcAccessConfig, RpcClient, API_KEY_HEADER,
+    FORWARDED_FOR_HEADER,
+};
+
+mod access;
 mod metrics;
+mod subscriptions;
+
+/// Served by `rpc.discover`, generated by the build script.
+static OPENRPC_DOCUMENT: Lazy<Value> = Lazy::new(|| {
+    serde_json::from_str(include_str!(concat!(env!("OUT_DIR"), "/openrpc.json")))
+        .expect("The OpenRPC document is generated as JSON")
+});
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct RpcPollingConfig {
@@ -49,15 +67,22 @@ impl Default for RpcPollingConfig {
     }
 }
 
+fn default_batch_max_size() -> usize {
+    100
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RpcLimitsConfig {
     /// Maximum byte size of the json payload.
     pub json_payload_max_size: usize,
+    /// Maximum number of requests in a batch.
+    #[serde(default = "default_batch_max_size")]
+    pub batch_max_size: usize,
 }
 
 impl Default for RpcLimitsConfig {
     fn default() -> Self {
-        Self { json_payload_max_size: 10 * 1024 * 1024 }
+        Self { json_payload_max_size: 10 * 1024 * 1024, batch_max_size: default_batch_max_size() }
     }
 }
 
@@ -70,6 +95,14 @@ pub struct RpcConfig {
     pub polling_config: RpcPollingConfig,
     #[serde(default)]
     pub limits_config: RpcLimitsConfig,
+    /// Methods served to clients and rate limits of clients.
+    #[serde(default)]
+    pub access_config: RpcAccessConfig,
+    /// Enables the `adm_create_checkpoint` admin method, which is disabled by default.
+    /// Checkpoints are only written to subdirectories of this directory. A relative path is
+    /// resolved against the home directory.
+    #[serde(default)]
+    pub checkpoints_dir: Option<std::path::PathBuf>,
 }
 
 impl Default for RpcConfig {
@@ -80,6 +113,8 @@ impl Default for RpcConfig {
             cors_allowed_origins: vec!["*".to_owned()],
             polling_config: Default::default(),
             limits_config: Default::default(),
+            access_config: Default::default(),
+            checkpoints_dir: None,
         }
     }
 }
@@ -198,7 +233,12 @@ struct JsonRpcHandler {
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
     polling_config: RpcPollingConfig,
+    batch_max_size: usize,
+    access_control: Arc<AccessControl>,
     genesis_config: GenesisConfig,
+    chain_events: ChainEvents,
+    /// Set only if checkpoints are enabled with `RpcConfig::checkpoints_dir`.
+    checkpoint_addr: Option<Addr<CheckpointActor>>,
     #[cfg(feature = "test_features")]
     peer_manager_addr: Addr<near_network::PeerManagerActor>,
     #[cfg(feature = "test_features")]
@@ -206,20 +246,78 @@ struct JsonRpcHandler {
 }
 
 impl JsonRpcHandler {
-    pub async fn process(&self, message: Message) -> Result<Message, HttpError> {
+    pub async fn process(
+        &self,
+        message: Message,
+        client: &RpcClient,
+    ) -> Result<Option<Message>, HttpError> {
         let id = message.id();
         match message {
             Message::Request(request) => {
-                Ok(Message::response(id, self.process_request(request).await))
+                Ok(Some(Message::response(id, self.process_request(request, client).await)))
+            }
+            Message::Notification(notification) => {
+                self.process_notification(notification, client).await;
+                Ok(None)
             }
-            _ => Ok(Message::error(RpcError::parse_error(
+            Message::Batch(messages) => Ok(self.process_batch(messages, client).await),
+            _ => Ok(Some(Message::error(RpcError::parse_error(
                 "JSON RPC Request format was expected".to_owned(),
-            ))),
+            )))),
+        }
+    }
+
+    /// Processes the requests of a batch concurrently. Responses are returned in the order of
+    /// the requests, entries which are not requests are answered with errors. Notifications are
+    /// not answered, so a batch of notifications only returns `None`.
+    async fn process_batch(&self, messages: Vec<Message>, client: &RpcClient) -> Option<Message> {
+        if messages.is_empty() {
+            return Some(Message::error(RpcError::invalid_request("Batch is empty".to_owned())));
+        }
+        if messages.len() > self.batch_max_size {
+            return Some(Message::error(RpcError::invalid_request(format!(
+                "Batch of {} requests exceeds the limit of {} requests",
+                messages.len(),
+                self.batch_max_size
+            ))));
+        }
+        metrics::RPC_BATCH_COUNT.inc();
+        let responses = messages.into_iter().map(|message| async move {
+            match message {
+                Message::Request(request) => {
+                    let id = request.id.clone();
+                    Some(Message::response(id, self.process_request(request, client).await))
+                }
+                Message::Notification(notification) => {
+                    self.process_notification(notification, client).await;
+                    None
+                }
+                _ => Some(Message::error(RpcError::invalid_request(
+                    "JSON RPC Request format was expected".to_owned(),
+                ))),
+            }
+        });
+        let responses: Vec<_> =
+            futures::future::join_all(responses).await.into_iter().flatten().collect();
+        if responses.is_empty() {
+            None
+        } else {
+            Some(Message::Batch(responses))
         }
     }
 
-    async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
+    /// Processes a request which expects no answer. Its result, errors included, is dropped.
+    async fn process_notification(&self, notification: Notification, client: &RpcClient) {
+        let _ = self.process_request(notification.into(), client).await;
+    }
+
+    async fn process_request(
+        &self,
+        request: Request,
+        client: &RpcClient,
+    ) -> Result<Value, RpcError> {
         metrics::HTTP_RPC_REQUEST_COUNT.with_label_values(&[request.method.as_ref()]).inc();
+        self.access_control.check(&request.method, client)?;
         let _rpc_processing_time = metrics::RPC_PROCESSING_TIME
             .with_label_values(&[request.method.as_ref()])
             .start_timer();
@@ -342,6 +440,17 @@ impl JsonRpcHandler {
         }
 
         let response: Result<Value, RpcError> = match request.method.as_ref() {
+            // Admin methods are only served if enabled in the config and only to clients
+            // connecting from the node's host.
+            "adm_create_checkpoint" if self.checkpoint_addr.is_some() && client.is_localhost() => {
+                let rpc_create_checkpoint_request =
+                    near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointRequest::parse(
+                        request.params,
+                    )?;
+                let checkpoint = self.create_checkpoint(rpc_create_checkpoint_request).await?;
+                serde_json::to_value(checkpoint)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             // Handlers ordered alphabetically
             "block" => {
                 let rpc_block_request =
@@ -415,6 +524,7 @@ impl JsonRpcHandler {
                 let query_response = self.query(rpc_query_request).await;
                 process_query_response(query_response)
             }
+            "rpc.discover" => Ok(OPENRPC_DOCUMENT.clone()),
             "status" => {
                 let status_response = self.status().await?;
                 serde_json::to_value(status_response)
@@ -456,6 +566,15 @@ impl JsonRpcHandler {
                 serde_json::to_value(state_changes)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "EXPERIMENTAL_changes_history" => {
+                let rpc_state_changes_history_request =
+                    near_jsonrpc_primitives::types::changes::RpcStateChangesHistoryRequest::parse(
+                        request.params,
+                    )?;
+                let state_changes = self.changes_history(rpc_state_changes_history_request).await?;
+                serde_json::to_value(state_changes)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             "EXPERIMENTAL_changes_in_block" => {
                 let rpc_state_changes_request =
                     near_jsonrpc_primitives::types::changes::RpcStateChangesInBlockRequest::parse(
@@ -532,6 +651,10 @@ impl JsonRpcHandler {
                 serde_json::to_value(sandbox_patch_state_response)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "subscribe" | "unsubscribe" => {
+                Err(RpcSubscriptionError::WebSocketRequired { method: request.method.clone() }
+                    .into())
+            }
             _ => Err(RpcError::method_not_found(request.method.clone())),
         };
 
@@ -976,6 +1099,30 @@ impl JsonRpcHandler {
         })
     }
 
+    async fn changes_history(
+        &self,
+        request: near_jsonrpc_primitives::types::changes::RpcStateChangesHistoryRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::changes::RpcStateChangesHistoryResponse,
+        near_jsonrpc_primitives::types::changes::RpcStateChangesError,
+    > {
+        let history = self
+            .view_client_addr
+            .send(GetStateChangesHistory {
+                key: request.key,
+                from_height: request.from_block_height,
+                to_height: request.to_block_height,
+                limit: request.limit.unwrap_or(
+                    near_jsonrpc_primitives::types::changes::DEFAULT_STATE_CHANGES_HISTORY_LIMIT,
+                ),
+            })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::changes::RpcStateChangesHistoryResponse {
+            changes: history.changes,
+            next_from_block_height: history.next_from_height,
+        })
+    }
+
     async fn next_light_client_block(
         &self,
         request: near_jsonrpc_primitives::types::light_client::RpcLightClientNextBlockRequest,
@@ -1030,6 +1177,19 @@ impl JsonRpcHandler {
         Ok(self.client_addr.send(GetNetworkInfo {}).await??.into())
     }
 
+    async fn create_checkpoint(
+        &self,
+        request_data: near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointResponse,
+        near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointError,
+    > {
+        let checkpoint_addr = self.checkpoint_addr.as_ref().expect("checked by process_request");
+        let checkpoint =
+            checkpoint_addr.send(CreateCheckpoint { path: request_data.path }).await??;
+        Ok(checkpoint.into())
+    }
+
     async fn gas_price(
         &self,
         request_data: near_jsonrpc_primitives::types::gas_price::RpcGasPriceRequest,
@@ -1236,12 +1396,17 @@ impl JsonRpcHandler {
 }
 
 fn rpc_handler(
+    request: HttpRequest,
     message: web::Json<Message>,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let client = handler.access_control.client(&request);
     let response = async move {
-        let message = handler.process(message.0).await?;
-        Ok(HttpResponse::Ok().json(&message))
+        match handler.process(message.0, &client).await? {
+            Some(message) => Ok(HttpResponse::Ok().json(&message)),
+            // Nothing is returned for notifications.
+            None => Ok(HttpResponse::NoContent().finish()),
+        }
     };
     response.boxed()
 }
@@ -1321,16 +1486,32 @@ fn get_cors(cors_allowed_origins: &[String]) -> Cors {
 /// as a tuple containing a name of the server (e.g. `"JSON RPC"`) which can be
 /// used in diagnostic messages and a [`actix_web::dev::Server`] object which
 /// can be used to control the server (most notably stop it).
+///
+/// `access_control` applies `config.access_config`, it is passed separately so that the rate
+/// limits can be shared with the gRPC server.
 pub fn start_http(
     config: RpcConfig,
     genesis_config: GenesisConfig,
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
+    chain_events: ChainEvents,
+    checkpoint_addr: Option<Addr<CheckpointActor>>,
+    access_control: Arc<AccessControl>,
     #[cfg(feature = "test_features")] peer_manager_addr: Addr<near_network::PeerManagerActor>,
     #[cfg(feature = "test_features")] routing_table_addr: Addr<near_network::RoutingTableActor>,
 ) -> Vec<(&'static str, actix_web::dev::Server)> {
-    let RpcConfig { addr, prometheus_addr, cors_allowed_origins, polling_config, limits_config } =
-        config;
+    let RpcConfig {
+        addr,
+        prometheus_addr,
+        cors_allowed_origins,
+        polling_config,
+        limits_config,
+        // `access_control` is created from it by the caller, so that it can be shared with the
+        // other servers.
+        access_config: _,
+        checkpoints_dir: _,
+    } = config;
+    let batch_max_size = limits_config.batch_max_size;
     let prometheus_addr = prometheus_addr.filter(|it| it != &addr);
     let cors_allowed_origins_clone = cors_allowed_origins.clone();
     info!(target:"network", "Starting http server at {}", addr);
@@ -1342,7 +1523,11 @@ pub fn start_http(
                 client_addr: client_addr.clone(),
                 view_client_addr: view_client_addr.clone(),
                 polling_config,
+                batch_max_size,
+                access_control: access_control.clone(),
                 genesis_config: genesis_config.clone(),
+                chain_events: chain_events.clone(),
+                checkpoint_addr: checkpoint_addr.clone(),
                 #[cfg(feature = "test_features")]
                 peer_manager_addr: peer_manager_addr.clone(),
                 #[cfg(feature = "test_features")]
@@ -1351,6 +1536,7 @@ pub fn start_http(
             .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
+            .service(web::resource("/ws").route(web::get().to(subscriptions::ws_handler)))
             .service(
                 web::resource("/status")
                     .route(web::get().to(status_handler))
@@ -1394,3 +1580,61 @@ pub fn start_http(
 
     servers
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::OPENRPC_DOCUMENT;
+
+    /// Names of the methods matched by `JsonRpcHandler::process_request`, read from its source.
+    /// Adversarial methods of `test_features` builds are not documented and not included.
+    fn dispatched_methods() -> BTreeSet<String> {
+        let source = include_str!("lib.rs");
+        let start = source
+            .find("let response: Result<Value, RpcError> = match request.method.as_ref() {")
+            .unwrap();
+        let end = start + source[start..].find("_ => Err(RpcError::method_not_found").unwrap();
+        let mut methods = BTreeSet::new();
+        let mut enabled = true;
+        for line in source[start..end].lines().map(str::trim) {
+            if let Some(feature) = line.strip_prefix("#[cfg(feature = \"") {
+                enabled = match feature.trim_end_matches("\")]") {
+                    "sandbox" => cfg!(feature = "sandbox"),
+                    feature => panic!("Methods of feature {} are not checked", feature),
+                };
+            } else if line.starts_with('"') && line.contains("=>") {
+                let patterns = line.split("=>").next().unwrap().split(" if ").next().unwrap();
+                if enabled {
+                    methods.extend(
+                        patterns.split('|').map(|name| name.trim().trim_matches('"').to_string()),
+                    );
+                }
+                enabled = true;
+            }
+        }
+        methods
+    }
+
+    #[test]
+    fn test_openrpc_document_lists_dispatched_methods() {
+        let documented: BTreeSet<String> = OPENRPC_DOCUMENT["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|method| method["name"].as_str().unwrap().to_string())
+            .collect();
+        let dispatched = dispatched_methods();
+        assert!(dispatched.contains("block") && dispatched.contains("rpc.discover"));
+        assert_eq!(
+            dispatched.difference(&documented).collect::<Vec<_>>(),
+            Vec::<&String>::new(),
+            "Dispatched methods are missing from the OpenRPC document"
+        );
+        assert_eq!(
+            documented.difference(&dispatched).collect::<Vec<_>>(),
+            Vec::<&String>::new(),
+            "Documented methods are not dispatched"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use near_metrics::{HistogramVec, IntCounter, IntCounterVec};
+use near_metrics::{HistogramVec, IntCounter, IntCounterVec, IntGauge};
 use once_cell::sync::Lazy;
 
 pub static RPC_PROCESSING_TIME: Lazy<HistogramVec> = Lazy::new(|| {
@@ -47,3 +47,25 @@ pub static RPC_ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static RPC_WEBSOCKET_CONNECTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    near_metrics::try_create_int_gauge(
+        "near_rpc_websocket_connections",
+        "Number of open WebSocket connections with subscriptions",
+    )
+    .unwrap()
+});
+pub static RPC_BATCH_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    near_metrics::try_create_int_counter(
+        "near_rpc_batch_total",
+        "Total count of JSON RPC batches received, requests of batches are counted by method",
+    )
+    .unwrap()
+});
+pub static RPC_REJECTED_REQUEST_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_rpc_rejected_request_count",
+        "Total count of RPC requests rejected by the method lists or rate limits",
+        &["method", "reason"],
+    )
+    .unwrap()
+});
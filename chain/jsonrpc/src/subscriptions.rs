@@ -0,0 +1,464 @@
+//! WebSocket endpoint of the JSON-RPC server.
+//!
+//! Clients send JSON-RPC requests with the `subscribe` and `unsubscribe` methods over the
+//! connection and receive `subscription` notifications with the events they subscribed to.
+//! Events come from the client as it processes blocks, see [`ChainEvents`].
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws;
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{web, Error as HttpError, HttpRequest, HttpResponse};
+use bytes::BytesMut;
+use futures::channel::mpsc;
+use futures::StreamExt;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+use near_client::{ChainEvent, ChainEvents, WatchId, MAX_WATCHED_TRANSACTIONS};
+use near_jsonrpc_primitives::errors::RpcError;
+use near_jsonrpc_primitives::message::{from_slice, Message, Request};
+use near_jsonrpc_primitives::types::subscriptions::{
+    RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError, RpcSubscriptionEvent,
+    RpcSubscriptionNotification, RpcUnsubscribeRequest, RpcUnsubscribeResponse, SubscriptionId,
+};
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::AccountId;
+
+use crate::access::{AccessControl, RpcClient};
+use crate::{metrics, JsonRpcHandler};
+
+/// Maximum number of active subscriptions of a single connection.
+const MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 100;
+
+/// Maximum number of messages queued for sending to a single connection. A client which reads
+/// its messages slower than they come is disconnected once the queue is full.
+const MAX_QUEUED_MESSAGES: usize = 1000;
+
+enum Subscription {
+    FinalBlocks,
+    TransactionOutcome(CryptoHash, WatchId),
+    StateChanges(HashSet<AccountId>),
+}
+
+/// Subscriptions of a single WebSocket connection.
+struct Subscriptions {
+    chain_events: ChainEvents,
+    access_control: Arc<AccessControl>,
+    client: RpcClient,
+    next_id: SubscriptionId,
+    subscriptions: BTreeMap<SubscriptionId, Subscription>,
+}
+
+impl Subscriptions {
+    fn new(
+        chain_events: ChainEvents,
+        access_control: Arc<AccessControl>,
+        client: RpcClient,
+    ) -> Self {
+        Self { chain_events, access_control, client, next_id: 0, subscriptions: BTreeMap::new() }
+    }
+
+    fn subscribe(
+        &mut self,
+        request: RpcSubscribeRequest,
+    ) -> Result<SubscriptionId, RpcSubscriptionError> {
+        if self.subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_CONNECTION {
+            return Err(RpcSubscriptionError::TooManySubscriptions {
+                limit: MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            });
+        }
+        let subscription = match request {
+            RpcSubscribeRequest::FinalBlocks => Subscription::FinalBlocks,
+            RpcSubscribeRequest::TransactionOutcome { tx_hash } => {
+                let watch_id = self.chain_events.watch_transaction(tx_hash).ok_or(
+                    RpcSubscriptionError::TooManyWatchedTransactions {
+                        limit: MAX_WATCHED_TRANSACTIONS,
+                    },
+                )?;
+                Subscription::TransactionOutcome(tx_hash, watch_id)
+            }
+            RpcSubscribeRequest::StateChanges { account_ids } => {
+                Subscription::StateChanges(account_ids.into_iter().collect())
+            }
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.subscriptions.insert(id, subscription);
+        Ok(id)
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> Result<(), RpcSubscriptionError> {
+        match self.subscriptions.remove(&id) {
+            Some(Subscription::TransactionOutcome(tx_hash, watch_id)) => {
+                self.chain_events.unwatch_transaction(&tx_hash, watch_id);
+                Ok(())
+            }
+            Some(_) => Ok(()),
+            None => Err(RpcSubscriptionError::UnknownSubscription { subscription: id }),
+        }
+    }
+
+    fn process_request(&mut self, request: &Request) -> Result<Value, RpcError> {
+        metrics::HTTP_RPC_REQUEST_COUNT.with_label_values(&[request.method.as_ref()]).inc();
+        self.access_control.check(&request.method, &self.client)?;
+        match request.method.as_ref() {
+            "subscribe" => {
+                let subscribe_request = RpcSubscribeRequest::parse(request.params.clone())?;
+                let subscription = self.subscribe(subscribe_request)?;
+                serde_json::to_value(RpcSubscribeResponse { subscription })
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "unsubscribe" => {
+                let RpcUnsubscribeRequest { subscription } =
+                    RpcUnsubscribeRequest::parse(request.params.clone())?;
+                self.unsubscribe(subscription)?;
+                serde_json::to_value(RpcUnsubscribeResponse { subscription })
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            _ => Err(RpcError::method_not_found(request.method.clone())),
+        }
+    }
+
+    /// Answers a message received from the client.
+    fn process_message(&mut self, message: &[u8]) -> Message {
+        match from_slice(message) {
+            Ok(Message::Request(request)) => {
+                Message::response(request.id.clone(), self.process_request(&request))
+            }
+            Ok(_) => {
+                Message::error(RpcError::parse_error("JSON RPC Request format was expected".into()))
+            }
+            Err(broken) => broken.reply(),
+        }
+    }
+
+    /// Notifications about the event, in the order of subscriptions. Subscriptions to
+    /// transactions end with the notification about their outcome or expiry.
+    fn notifications(&mut self, event: &ChainEvent) -> Vec<RpcSubscriptionNotification> {
+        let mut notifications = vec![];
+        match event {
+            ChainEvent::FinalBlock { block, state_changes } => {
+                for (id, subscription) in self.subscriptions.iter() {
+                    let result = match subscription {
+                        Subscription::FinalBlocks => {
+                            RpcSubscriptionEvent::FinalBlock { block: block.clone() }
+                        }
+                        Subscription::StateChanges(account_ids) => {
+                            let changes: Vec<_> = state_changes
+                                .iter()
+                                .filter(|change| {
+                                    account_ids.contains(change.value.affected_account_id())
+                                })
+                                .cloned()
+                                .collect();
+                            if changes.is_empty() {
+                                continue;
+                            }
+                            RpcSubscriptionEvent::StateChanges {
+                                block_hash: block.header.hash,
+                                block_height: block.header.height,
+                                changes,
+                            }
+                        }
+                        Subscription::TransactionOutcome(..) => continue,
+                    };
+                    notifications.push(RpcSubscriptionNotification { subscription: *id, result });
+                }
+            }
+            ChainEvent::FinalTransaction(outcome) => {
+                for id in self.finish_transaction_subscriptions(&outcome.transaction.hash) {
+                    notifications.push(RpcSubscriptionNotification {
+                        subscription: id,
+                        result: RpcSubscriptionEvent::TransactionOutcome {
+                            outcome: outcome.clone(),
+                        },
+                    });
+                }
+            }
+            ChainEvent::TransactionExpired(tx_hash) => {
+                for id in self.finish_transaction_subscriptions(tx_hash) {
+                    notifications.push(RpcSubscriptionNotification {
+                        subscription: id,
+                        result: RpcSubscriptionEvent::TransactionExpired { tx_hash: *tx_hash },
+                    });
+                }
+            }
+        }
+        notifications
+    }
+
+    /// Ends the subscriptions to the transaction, returning their ids.
+    fn finish_transaction_subscriptions(&mut self, tx_hash: &CryptoHash) -> Vec<SubscriptionId> {
+        let finished: Vec<_> = self
+            .subscriptions
+            .iter()
+            .filter_map(|(id, subscription)| match subscription {
+                Subscription::TransactionOutcome(watched, _) if watched == tx_hash => Some(*id),
+                _ => None,
+            })
+            .collect();
+        for id in finished.iter() {
+            self.unsubscribe(*id).expect("subscription exists");
+        }
+        finished
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for subscription in self.subscriptions.values() {
+            if let Subscription::TransactionOutcome(tx_hash, watch_id) = subscription {
+                self.chain_events.unwatch_transaction(tx_hash, *watch_id);
+            }
+        }
+    }
+}
+
+fn text_message(message: Message) -> ws::Message {
+    let message: String = message.into();
+    ws::Message::Text(message.into())
+}
+
+fn notification_message(notification: RpcSubscriptionNotification) -> ws::Message {
+    let params = serde_json::to_value(notification).expect("notification is serializable");
+    text_message(Message::notification("subscription".to_string(), Some(params)))
+}
+
+fn close_message(code: ws::CloseCode, description: &str) -> ws::Message {
+    ws::Message::Close(Some(ws::CloseReason { code, description: Some(description.to_string()) }))
+}
+
+/// Queues `messages` for sending to the client. Returns `false` if the connection has to end,
+/// either because the client is gone or because it doesn't read its messages and the queue is
+/// full. In the latter case the connection is closed with a message sent through `reserved`: a
+/// sender has a slot of its own in the channel, so the message fits into the full queue.
+fn queue_messages(
+    outgoing: &mut mpsc::Sender<ws::Message>,
+    reserved: &mut mpsc::Sender<ws::Message>,
+    messages: Vec<ws::Message>,
+) -> bool {
+    for message in messages {
+        if let Err(err) = outgoing.try_send(message) {
+            if err.is_full() {
+                debug!(target: "jsonrpc", "WebSocket subscriber doesn't read its messages");
+                let _ = reserved
+                    .try_send(close_message(ws::CloseCode::Again, "too many queued messages"));
+            }
+            return false;
+        }
+    }
+    true
+}
+
+/// Reads messages of the client and events of the chain until either of them ends, writing
+/// answers and notifications to `outgoing`.
+async fn run_connection(
+    mut payload: web::Payload,
+    mut events: broadcast::Receiver<Arc<ChainEvent>>,
+    mut subscriptions: Subscriptions,
+    mut outgoing: mpsc::Sender<ws::Message>,
+) {
+    let mut reserved = outgoing.clone();
+    let mut codec = ws::Codec::new();
+    let mut buffer = BytesMut::new();
+    loop {
+        let (messages, closed): (Vec<ws::Message>, bool) = tokio::select! {
+            chunk = payload.next() => {
+                match chunk {
+                    Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                    _ => break,
+                }
+                let mut messages = vec![];
+                let mut closed = false;
+                while !closed {
+                    let message = match codec.decode(&mut buffer) {
+                        Ok(Some(ws::Frame::Text(text))) | Ok(Some(ws::Frame::Binary(text))) => {
+                            text_message(subscriptions.process_message(&text))
+                        }
+                        Ok(Some(ws::Frame::Ping(bytes))) => ws::Message::Pong(bytes),
+                        Ok(Some(ws::Frame::Pong(_))) => continue,
+                        Ok(Some(ws::Frame::Continuation(_))) => {
+                            closed = true;
+                            close_message(
+                                ws::CloseCode::Unsupported,
+                                "fragmented messages are not supported",
+                            )
+                        }
+                        Ok(Some(ws::Frame::Close(reason))) => {
+                            closed = true;
+                            ws::Message::Close(reason)
+                        }
+                        Ok(None) => break,
+                        Err(err) => {
+                            closed = true;
+                            close_message(ws::CloseCode::Protocol, &err.to_string())
+                        }
+                    };
+                    messages.push(message);
+                }
+                (messages, closed)
+            }
+            event = events.recv() => match event {
+                Ok(event) => (
+                    subscriptions
+                        .notifications(&event)
+                        .into_iter()
+                        .map(notification_message)
+                        .collect(),
+                    false,
+                ),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!(target: "jsonrpc", "WebSocket subscriber missed {} events", skipped);
+                    (vec![close_message(ws::CloseCode::Again, "subscriber fell behind")], true)
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if !queue_messages(&mut outgoing, &mut reserved, messages) {
+            return;
+        }
+        if closed {
+            break;
+        }
+    }
+}
+
+/// Checks the WebSocket upgrade request and returns the `Sec-WebSocket-Accept` header value of
+/// the response, see `actix_http::ws::handshake`.
+fn handshake(request: &HttpRequest) -> Result<HeaderValue, ws::HandshakeError> {
+    ws::verify_handshake(request.head())?;
+    let key = request
+        .headers()
+        .get(header::SEC_WEBSOCKET_KEY)
+        .ok_or(ws::HandshakeError::BadWebsocketKey)?;
+    let accept = ws::hash_key(key.as_ref());
+    Ok(HeaderValue::from_bytes(accept.as_ref()).expect("accept key is a valid header value"))
+}
+
+pub(crate) async fn ws_handler(
+    request: HttpRequest,
+    payload: web::Payload,
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    let accept = match handshake(&request) {
+        Ok(accept) => accept,
+        Err(err) => return Ok(HttpResponse::BadRequest().body(err.to_string())),
+    };
+    let (sender, receiver) = mpsc::channel(MAX_QUEUED_MESSAGES);
+    let events = handler.chain_events.subscribe();
+    let subscriptions = Subscriptions::new(
+        handler.chain_events.clone(),
+        handler.access_control.clone(),
+        handler.access_control.client(&request),
+    );
+    actix::spawn(async move {
+        metrics::RPC_WEBSOCKET_CONNECTIONS.inc();
+        run_connection(payload, events, subscriptions, sender).await;
+        metrics::RPC_WEBSOCKET_CONNECTIONS.dec();
+    });
+    let mut codec = ws::Codec::new();
+    let mut response = HttpResponse::build(StatusCode::SWITCHING_PROTOCOLS);
+    response.upgrade("websocket").insert_header((header::SEC_WEBSOCKET_ACCEPT, accept));
+    Ok(response.streaming(receiver.map(move |message| {
+        let mut buffer = BytesMut::new();
+        codec.encode(message, &mut buffer).map_err(actix_web::error::ErrorInternalServerError)?;
+        Ok::<_, HttpError>(buffer.freeze())
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_http::ws;
+    use futures::channel::mpsc;
+    use near_client::ChainEvents;
+    use near_jsonrpc_primitives::types::subscriptions::{
+        RpcSubscribeRequest, RpcSubscriptionError,
+    };
+    use serde_json::json;
+
+    use super::{queue_messages, Subscriptions, MAX_SUBSCRIPTIONS_PER_CONNECTION};
+    use crate::access::{AccessControl, RpcClient};
+
+    #[test]
+    fn test_subscribe_request() {
+        let request: RpcSubscribeRequest = serde_json::from_value(json!({
+            "event": "state_changes",
+            "account_ids": ["alice.near"],
+        }))
+        .unwrap();
+        assert_eq!(
+            request,
+            RpcSubscribeRequest::StateChanges { account_ids: vec!["alice.near".parse().unwrap()] }
+        );
+        let request: RpcSubscribeRequest =
+            serde_json::from_value(json!({ "event": "final_blocks" })).unwrap();
+        assert_eq!(request, RpcSubscribeRequest::FinalBlocks);
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let mut subscriptions = Subscriptions::new(
+            ChainEvents::new(),
+            Arc::new(AccessControl::new(Default::default())),
+            RpcClient::default(),
+        );
+        let message = subscriptions.process_message(
+            br#"{"jsonrpc":"2.0","id":1,"method":"subscribe","params":{"event":"final_blocks"}}"#,
+        );
+        assert_eq!(
+            serde_json::to_value(message).unwrap(),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "subscription": 0 } })
+        );
+        let message = subscriptions.process_message(
+            br#"{"jsonrpc":"2.0","id":2,"method":"unsubscribe","params":{"subscription":1}}"#,
+        );
+        assert_eq!(
+            serde_json::to_value(message).unwrap()["error"]["cause"]["name"],
+            json!("UNKNOWN_SUBSCRIPTION")
+        );
+
+        for _ in 1..MAX_SUBSCRIPTIONS_PER_CONNECTION {
+            subscriptions.subscribe(RpcSubscribeRequest::FinalBlocks).unwrap();
+        }
+        assert!(matches!(
+            subscriptions.subscribe(RpcSubscribeRequest::FinalBlocks),
+            Err(RpcSubscriptionError::TooManySubscriptions { .. })
+        ));
+        subscriptions.unsubscribe(0).unwrap();
+        assert!(subscriptions.subscribe(RpcSubscribeRequest::FinalBlocks).is_ok());
+    }
+
+    #[test]
+    fn test_queue_messages() {
+        let message = |text: &str| ws::Message::Text(text.to_string().into());
+        // The channel holds one message and one more of every sender.
+        let (mut outgoing, mut receiver) = mpsc::channel(1);
+        let mut reserved = outgoing.clone();
+        assert!(queue_messages(&mut outgoing, &mut reserved, vec![message("1")]));
+        assert!(!queue_messages(
+            &mut outgoing,
+            &mut reserved,
+            vec![message("2"), message("3"), message("4")]
+        ));
+        let mut received = vec![];
+        while let Ok(Some(message)) = receiver.try_next() {
+            received.push(message);
+        }
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[..2], [message("1"), message("2")]);
+        assert!(matches!(
+            &received[2],
+            ws::Message::Close(Some(ws::CloseReason { code: ws::CloseCode::Again, .. }))
+        ));
+
+        // A client which is gone ends the connection without a close message.
+        drop(receiver);
+        assert!(!queue_messages(&mut outgoing, &mut reserved, vec![message("5")]));
+    }
+}
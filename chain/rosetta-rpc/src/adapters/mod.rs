@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use actix::Addr;
+use futures::StreamExt;
 
 use near_chain_configs::Genesis;
 use near_client::ViewClientActor;
@@ -141,15 +142,33 @@ pub(crate) async fn convert_block_to_transactions(
         })
         .await??;
 
+    let receipt_hashes = accounts_changes
+        .iter()
+        .filter_map(|account_change| match account_change.cause {
+            near_primitives::views::StateChangeCauseView::ReceiptProcessing { receipt_hash }
+            | near_primitives::views::StateChangeCauseView::ActionReceiptGasReward {
+                receipt_hash,
+            } => Some(receipt_hash),
+            _ => None,
+        })
+        .collect::<std::collections::HashSet<_>>();
+
     let runtime_config = crate::utils::query_protocol_config(block.header.hash, &view_client_addr)
         .await?
         .runtime_config;
-    let transactions = convert_block_changes_to_transactions(
+    let mut transactions = convert_block_changes_to_transactions(
         &runtime_config,
         &block.header.hash,
         accounts_changes,
         accounts_previous_state,
     )?;
+    convert_staking_pool_calls_to_operations(
+        &view_client_addr,
+        &block.header.hash,
+        receipt_hashes,
+        &mut transactions,
+    )
+    .await?;
     Ok(transactions.into_iter().map(|(_transaction_hash, transaction)| transaction).collect())
 }
 
@@ -389,6 +408,187 @@ fn convert_block_changes_to_transactions(
     Ok(transactions)
 }
 
+/// A call of one of the standard staking pool methods which moves the balance
+/// delegated to the pool.
+#[derive(Debug, Clone, PartialEq)]
+struct StakingPoolCall {
+    type_: crate::models::OperationType,
+    /// The amount is not specified by `unstake_all` and `withdraw_all` calls.
+    requested_amount: Option<near_primitives::types::Balance>,
+}
+
+fn decode_staking_pool_call(
+    method_name: &str,
+    args: &[u8],
+    deposit: near_primitives::types::Balance,
+) -> Option<StakingPoolCall> {
+    #[derive(serde::Deserialize)]
+    struct AmountArgs {
+        #[serde(with = "near_primitives::serialize::u128_dec_format")]
+        amount: near_primitives::types::Balance,
+    }
+    let parse_amount = || serde_json::from_slice::<AmountArgs>(args).ok().map(|args| args.amount);
+
+    let (type_, requested_amount) = match method_name {
+        "deposit_and_stake" => (crate::models::OperationType::DepositAndStake, Some(deposit)),
+        "unstake" => (crate::models::OperationType::Unstake, Some(parse_amount()?)),
+        "unstake_all" => (crate::models::OperationType::Unstake, None),
+        "withdraw" => (crate::models::OperationType::Withdraw, Some(parse_amount()?)),
+        "withdraw_all" => (crate::models::OperationType::Withdraw, None),
+        _ => return None,
+    };
+    Some(StakingPoolCall { type_, requested_amount })
+}
+
+/// Adds DEPOSIT_AND_STAKE, UNSTAKE and WITHDRAW operations for the staking pool
+/// calls made by the successful receipts of the block.
+///
+/// The balances delegated to a staking pool change with every epoch reward, so
+/// these operations carry the requested amount in the metadata instead of the
+/// operation amount.
+async fn convert_staking_pool_calls_to_operations(
+    view_client_addr: &Addr<ViewClientActor>,
+    block_hash: &near_primitives::hash::CryptoHash,
+    receipt_hashes: std::collections::HashSet<near_primitives::hash::CryptoHash>,
+    transactions: &mut std::collections::HashMap<String, crate::models::Transaction>,
+) -> crate::errors::Result<()> {
+    if receipt_hashes.is_empty() {
+        return Ok(());
+    }
+
+    let failed_receipt_hashes = view_client_addr
+        .send(near_client::GetExecutionOutcomesForBlock { block_hash: *block_hash })
+        .await?
+        .map_err(crate::errors::ErrorKind::InternalError)?
+        .into_values()
+        .flatten()
+        .filter(|outcome| {
+            matches!(
+                outcome.outcome.status,
+                near_primitives::views::ExecutionStatusView::Failure(_)
+            )
+        })
+        .map(|outcome| outcome.id)
+        .collect::<std::collections::HashSet<_>>();
+
+    // A block may execute many receipts, so they are fetched concurrently.
+    let receipts = futures::stream::iter(receipt_hashes.difference(&failed_receipt_hashes))
+        .map(|receipt_hash| async move {
+            view_client_addr
+                .send(near_client::GetReceipt { receipt_id: *receipt_hash })
+                .await?
+                .map_err(|err| crate::errors::ErrorKind::InternalError(err.to_string()))
+        })
+        .buffer_unordered(10)
+        .collect::<Vec<crate::errors::Result<Option<near_primitives::views::ReceiptView>>>>()
+        .await;
+
+    let mut staking_pool_calls = vec![];
+    for receipt in receipts {
+        let receipt = match receipt? {
+            Some(receipt) => receipt,
+            None => continue,
+        };
+        let calls = if let near_primitives::views::ReceiptEnumView::Action { actions, .. } =
+            &receipt.receipt
+        {
+            actions
+                .iter()
+                .filter_map(|action| {
+                    if let near_primitives::views::ActionView::FunctionCall {
+                        method_name,
+                        args,
+                        deposit,
+                        ..
+                    } = action
+                    {
+                        let args = near_primitives::serialize::from_base64(args).ok()?;
+                        decode_staking_pool_call(method_name, &args, *deposit)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        } else {
+            continue;
+        };
+        if !calls.is_empty() {
+            staking_pool_calls.push((receipt, calls));
+        }
+    }
+
+    let block_id: near_primitives::types::BlockReference =
+        near_primitives::types::BlockId::Hash(*block_hash).into();
+    let called_contracts = staking_pool_calls
+        .iter()
+        .map(|(receipt, _calls)| &receipt.receiver_id)
+        .collect::<std::collections::HashSet<_>>();
+    let staking_pools = futures::stream::iter(called_contracts)
+        .map(|account_id| {
+            let block_id = block_id.clone();
+            async move {
+                crate::utils::is_staking_pool(block_id, account_id.clone(), view_client_addr)
+                    .await
+                    .map(|is_staking_pool| (account_id, is_staking_pool))
+            }
+        })
+        .buffer_unordered(10)
+        .collect::<Vec<crate::errors::Result<(&near_primitives::types::AccountId, bool)>>>()
+        .await
+        .into_iter()
+        .filter_map(|staking_pool| match staking_pool {
+            Ok((account_id, is_staking_pool)) => is_staking_pool.then(|| Ok(account_id.clone())),
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<crate::errors::Result<
+            std::collections::HashSet<near_primitives::types::AccountId>,
+        >>()?;
+
+    for (receipt, calls) in staking_pool_calls {
+        if !staking_pools.contains(&receipt.receiver_id) {
+            continue;
+        }
+
+        let transaction_identifier =
+            crate::models::TransactionIdentifier::receipt(&receipt.receipt_id);
+        let operations = &mut transactions
+            .entry(transaction_identifier.hash.clone())
+            .or_insert_with(move || crate::models::Transaction {
+                transaction_identifier,
+                operations: vec![],
+                metadata: crate::models::TransactionMetadata {
+                    type_: crate::models::TransactionType::Transaction,
+                },
+            })
+            .operations;
+        for call in calls {
+            let sub_account = if call.type_ == crate::models::OperationType::Withdraw {
+                crate::models::SubAccount::PoolWithdrawable
+            } else {
+                crate::models::SubAccount::PoolStaked
+            };
+            operations.push(crate::models::Operation {
+                operation_identifier: crate::models::OperationIdentifier::new(&operations),
+                related_operations: None,
+                account: crate::models::AccountIdentifier {
+                    address: receipt.predecessor_id.clone().into(),
+                    sub_account: Some(sub_account.in_pool(receipt.receiver_id.clone().into())),
+                },
+                amount: None,
+                type_: call.type_,
+                status: Some(crate::models::OperationStatusKind::Success),
+                metadata: Some(crate::models::OperationMetadata {
+                    requested_amount: call
+                        .requested_amount
+                        .map(crate::models::Amount::from_yoctonear),
+                    ..Default::default()
+                }),
+            });
+        }
+    }
+    Ok(())
+}
+
 pub(crate) async fn collect_transactions(
     genesis: Arc<Genesis>,
     view_client_addr: Addr<ViewClientActor>,
@@ -401,6 +601,143 @@ pub(crate) async fn collect_transactions(
     }
 }
 
+/// Conditions of `/search/transactions` applied to the transactions of a
+/// block.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TransactionsFilter {
+    pub transaction_identifier: Option<crate::models::TransactionIdentifier>,
+    pub account_identifier: Option<crate::models::AccountIdentifier>,
+    pub address: Option<crate::types::AccountId>,
+    pub operation_type: Option<crate::models::OperationType>,
+    pub operation_status: Option<crate::models::OperationStatusKind>,
+    pub success: Option<bool>,
+}
+
+impl TransactionsFilter {
+    /// A transaction matches if it has the requested identifier and at least
+    /// one of its operations matches all the operation conditions.
+    pub(crate) fn matches(&self, transaction: &crate::models::Transaction) -> bool {
+        if let Some(transaction_identifier) = &self.transaction_identifier {
+            if transaction_identifier != &transaction.transaction_identifier {
+                return false;
+            }
+        }
+        transaction.operations.iter().any(|operation| self.matches_operation(operation))
+    }
+
+    fn matches_operation(&self, operation: &crate::models::Operation) -> bool {
+        if let Some(account_identifier) = &self.account_identifier {
+            if account_identifier != &operation.account {
+                return false;
+            }
+        }
+        if let Some(address) = &self.address {
+            if address != &operation.account.address {
+                return false;
+            }
+        }
+        if let Some(operation_type) = self.operation_type {
+            if operation_type != operation.type_ {
+                return false;
+            }
+        }
+        if let Some(operation_status) = self.operation_status {
+            if Some(operation_status) != operation.status {
+                return false;
+            }
+        }
+        if let Some(success) = self.success {
+            let is_successful = operation.status.map_or(false, |status| status.is_successful());
+            if success != is_successful {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A page of `/search/transactions` results.
+///
+/// The matching transactions are pushed in the order of the search, most
+/// recent blocks first. The first `offset` of them are skipped and at most
+/// `limit` of the rest are kept.
+#[derive(Debug)]
+pub(crate) struct TransactionsPage {
+    offset: u64,
+    limit: u64,
+    /// The number of matching transactions pushed so far.
+    total_count: u64,
+    transactions: Vec<crate::models::BlockTransaction>,
+}
+
+impl TransactionsPage {
+    pub(crate) fn new(offset: u64, limit: u64) -> Self {
+        Self { offset, limit, total_count: 0, transactions: vec![] }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        block_identifier: &crate::models::BlockIdentifier,
+        transaction: crate::models::Transaction,
+    ) {
+        if self.total_count >= self.offset && !self.is_full() {
+            self.transactions.push(crate::models::BlockTransaction {
+                block_identifier: block_identifier.clone(),
+                transaction,
+            });
+        }
+        self.total_count += 1;
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.transactions.len() as u64 == self.limit
+    }
+
+    /// `exhausted` tells whether all the matching transactions were pushed,
+    /// otherwise there may be more of them after this page.
+    pub(crate) fn into_response(
+        self,
+        exhausted: bool,
+    ) -> crate::models::SearchTransactionsResponse {
+        let returned_count = self.offset + self.transactions.len() as u64;
+        let next_offset = if !exhausted || self.total_count > returned_count {
+            Some(returned_count.try_into().unwrap())
+        } else {
+            None
+        };
+        crate::models::SearchTransactionsResponse {
+            transactions: self.transactions,
+            total_count: self.total_count.try_into().unwrap(),
+            next_offset,
+        }
+    }
+}
+
+/// Converts the blocks at consecutive heights to `/events/blocks` events.
+/// There are no events for the heights skipped by the chain, which have no
+/// block.
+pub(crate) fn convert_blocks_to_events(
+    blocks: Vec<
+        Result<near_primitives::views::BlockView, near_client_primitives::types::GetBlockError>,
+    >,
+) -> crate::errors::Result<Vec<crate::models::BlockEvent>> {
+    let mut events = vec![];
+    for block in blocks {
+        let block = match block {
+            Ok(block) => block,
+            Err(near_client_primitives::types::GetBlockError::UnknownBlock { .. }) => continue,
+            Err(err) => return Err(crate::errors::ErrorKind::InternalError(err.to_string())),
+        };
+        let block_identifier: crate::models::BlockIdentifier = (&block.header).into();
+        events.push(crate::models::BlockEvent {
+            sequence: block_identifier.index,
+            block_identifier,
+            type_: crate::models::BlockEventType::BlockAdded,
+        });
+    }
+    Ok(events)
+}
+
 /// This is used as a common denominator for matching Rosetta Operations to
 /// and from NEAR Actions (see From and TryFrom implementations).
 ///
@@ -875,7 +1212,10 @@ impl TryFrom<Vec<crate::models::Operation>> for NearActions {
                 | crate::models::OperationType::InitiateDeleteKey
                 | crate::models::OperationType::InitiateDeployContract
                 | crate::models::OperationType::InitiateFunctionCall
-                | crate::models::OperationType::DeleteAccount => {
+                | crate::models::OperationType::DeleteAccount
+                | crate::models::OperationType::DepositAndStake
+                | crate::models::OperationType::Unstake
+                | crate::models::OperationType::Withdraw => {
                     return Err(crate::errors::ErrorKind::InvalidInput(format!(
                         "Unexpected operation `{:?}`",
                         tail_operation.type_
@@ -1590,4 +1930,265 @@ mod tests {
             Err(crate::errors::ErrorKind::InvalidInput(_))
         ));
     }
+
+    #[test]
+    fn test_transactions_filter() {
+        let transaction = crate::models::Transaction {
+            transaction_identifier: crate::models::TransactionIdentifier::transaction(
+                &near_primitives::hash::CryptoHash::default(),
+            ),
+            operations: vec![
+                crate::models::Operation {
+                    operation_identifier: crate::models::OperationIdentifier {
+                        index: 0,
+                        network_index: None,
+                    },
+                    related_operations: None,
+                    type_: crate::models::OperationType::Transfer,
+                    status: Some(crate::models::OperationStatusKind::Success),
+                    account: "sender.near".parse().unwrap(),
+                    amount: Some(-crate::models::Amount::from_yoctonear(1)),
+                    metadata: None,
+                },
+                crate::models::Operation {
+                    operation_identifier: crate::models::OperationIdentifier {
+                        index: 1,
+                        network_index: None,
+                    },
+                    related_operations: None,
+                    type_: crate::models::OperationType::Transfer,
+                    status: Some(crate::models::OperationStatusKind::Success),
+                    account: crate::models::AccountIdentifier {
+                        address: "receiver.near".parse().unwrap(),
+                        sub_account: Some(crate::models::SubAccount::Locked.into()),
+                    },
+                    amount: Some(crate::models::Amount::from_yoctonear(1)),
+                    metadata: None,
+                },
+            ],
+            metadata: crate::models::TransactionMetadata {
+                type_: crate::models::TransactionType::Transaction,
+            },
+        };
+
+        assert!(TransactionsFilter::default().matches(&transaction));
+        assert!(TransactionsFilter {
+            account_identifier: Some("sender.near".parse().unwrap()),
+            operation_type: Some(crate::models::OperationType::Transfer),
+            success: Some(true),
+            ..Default::default()
+        }
+        .matches(&transaction));
+        assert!(TransactionsFilter {
+            address: Some("receiver.near".parse().unwrap()),
+            transaction_identifier: Some(transaction.transaction_identifier.clone()),
+            ..Default::default()
+        }
+        .matches(&transaction));
+
+        // The account identifier must match the sub-account as well.
+        assert!(!TransactionsFilter {
+            account_identifier: Some("receiver.near".parse().unwrap()),
+            ..Default::default()
+        }
+        .matches(&transaction));
+        // All the conditions have to match the same operation.
+        assert!(!TransactionsFilter {
+            account_identifier: Some("sender.near".parse().unwrap()),
+            address: Some("receiver.near".parse().unwrap()),
+            ..Default::default()
+        }
+        .matches(&transaction));
+        assert!(!TransactionsFilter {
+            account_identifier: Some("sender.near".parse().unwrap()),
+            operation_type: Some(crate::models::OperationType::Stake),
+            ..Default::default()
+        }
+        .matches(&transaction));
+        assert!(!TransactionsFilter {
+            address: Some("sender.near".parse().unwrap()),
+            success: Some(false),
+            ..Default::default()
+        }
+        .matches(&transaction));
+        assert!(!TransactionsFilter {
+            address: Some("sender.near".parse().unwrap()),
+            transaction_identifier: Some(crate::models::TransactionIdentifier::receipt(
+                &near_primitives::hash::CryptoHash::default(),
+            )),
+            ..Default::default()
+        }
+        .matches(&transaction));
+    }
+
+    fn transaction(index: u8) -> crate::models::Transaction {
+        crate::models::Transaction {
+            transaction_identifier: crate::models::TransactionIdentifier::transaction(
+                &near_primitives::hash::CryptoHash::try_from(vec![index; 32]).unwrap(),
+            ),
+            operations: vec![],
+            metadata: crate::models::TransactionMetadata {
+                type_: crate::models::TransactionType::Transaction,
+            },
+        }
+    }
+
+    /// Indices of the transactions of the response.
+    fn page_indices(response: &crate::models::SearchTransactionsResponse) -> Vec<String> {
+        response
+            .transactions
+            .iter()
+            .map(|transaction| transaction.transaction.transaction_identifier.hash.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_transactions_page() {
+        let block_identifier = crate::models::BlockIdentifier { index: 10, hash: "hash".into() };
+        let expected = |indices: std::ops::Range<u8>| -> Vec<String> {
+            indices.map(|index| transaction(index).transaction_identifier.hash).collect()
+        };
+
+        // The first page.
+        let mut page = TransactionsPage::new(0, 2);
+        assert!(!page.is_full());
+        for index in 0..2 {
+            page.push(&block_identifier, transaction(index));
+        }
+        assert!(page.is_full());
+        let response = page.into_response(false);
+        assert_eq!(page_indices(&response), expected(0..2));
+        assert_eq!(response.transactions[0].block_identifier, block_identifier);
+        assert_eq!(response.total_count, 2);
+        assert_eq!(response.next_offset, Some(2));
+
+        // A page from an offset. The rest of the transactions of the block are
+        // counted but not returned.
+        let mut page = TransactionsPage::new(2, 2);
+        for index in 0..5 {
+            page.push(&block_identifier, transaction(index));
+        }
+        let response = page.into_response(true);
+        assert_eq!(page_indices(&response), expected(2..4));
+        assert_eq!(response.total_count, 5);
+        assert_eq!(response.next_offset, Some(4));
+
+        // The last page.
+        let mut page = TransactionsPage::new(4, 2);
+        for index in 0..5 {
+            page.push(&block_identifier, transaction(index));
+        }
+        let response = page.into_response(true);
+        assert_eq!(page_indices(&response), expected(4..5));
+        assert_eq!(response.total_count, 5);
+        assert_eq!(response.next_offset, None);
+
+        // A full page may be followed by more results even if no more were
+        // pushed.
+        let mut page = TransactionsPage::new(4, 1);
+        for index in 0..5 {
+            page.push(&block_identifier, transaction(index));
+        }
+        let response = page.into_response(false);
+        assert_eq!(page_indices(&response), expected(4..5));
+        assert_eq!(response.next_offset, Some(5));
+
+        // An offset past the results.
+        let mut page = TransactionsPage::new(10, 2);
+        page.push(&block_identifier, transaction(0));
+        let response = page.into_response(true);
+        assert!(response.transactions.is_empty());
+        assert_eq!(response.total_count, 1);
+        assert_eq!(response.next_offset, None);
+    }
+
+    fn block_at(height: near_primitives::types::BlockHeight) -> near_primitives::views::BlockView {
+        let chunks = near_primitives::block::genesis_chunks(
+            vec![near_primitives::hash::CryptoHash::default()],
+            1,
+            1_000,
+            0,
+            near_primitives::version::PROTOCOL_VERSION,
+        );
+        let block = near_primitives::block::Block::genesis(
+            near_primitives::version::PROTOCOL_VERSION,
+            chunks.into_iter().map(|chunk| chunk.take_header()).collect(),
+            near_primitives::utils::from_timestamp(0),
+            height,
+            100,
+            1_000,
+            near_primitives::hash::CryptoHash::default(),
+        );
+        near_primitives::views::BlockView::from_author_block("test.near".parse().unwrap(), block)
+    }
+
+    #[test]
+    fn test_convert_blocks_to_events() {
+        let unknown_block = || {
+            Err(near_client_primitives::types::GetBlockError::UnknownBlock {
+                error_message: "skipped".to_string(),
+            })
+        };
+        let blocks = vec![Ok(block_at(5)), unknown_block(), unknown_block(), Ok(block_at(8))];
+        let events = convert_blocks_to_events(blocks).unwrap();
+        assert_eq!(events.iter().map(|event| event.sequence).collect::<Vec<_>>(), vec![5, 8]);
+        for event in events {
+            assert_eq!(event.block_identifier.index, event.sequence);
+            assert_eq!(event.type_, crate::models::BlockEventType::BlockAdded);
+        }
+
+        // Skipped heights at the edges of the page.
+        let blocks = vec![unknown_block(), Ok(block_at(6)), unknown_block()];
+        assert_eq!(convert_blocks_to_events(blocks).unwrap().len(), 1);
+
+        let blocks =
+            vec![Ok(block_at(5)), Err(near_client_primitives::types::GetBlockError::NotSyncedYet)];
+        assert!(matches!(
+            convert_blocks_to_events(blocks),
+            Err(crate::errors::ErrorKind::InternalError(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_staking_pool_call() {
+        assert_eq!(
+            decode_staking_pool_call("deposit_and_stake", b"{}", 1000),
+            Some(StakingPoolCall {
+                type_: crate::models::OperationType::DepositAndStake,
+                requested_amount: Some(1000),
+            })
+        );
+        assert_eq!(
+            decode_staking_pool_call("unstake", br#"{"amount": "500"}"#, 0),
+            Some(StakingPoolCall {
+                type_: crate::models::OperationType::Unstake,
+                requested_amount: Some(500),
+            })
+        );
+        assert_eq!(
+            decode_staking_pool_call("unstake_all", b"", 0),
+            Some(StakingPoolCall {
+                type_: crate::models::OperationType::Unstake,
+                requested_amount: None,
+            })
+        );
+        assert_eq!(
+            decode_staking_pool_call("withdraw", br#"{"amount": "300"}"#, 0),
+            Some(StakingPoolCall {
+                type_: crate::models::OperationType::Withdraw,
+                requested_amount: Some(300),
+            })
+        );
+        assert_eq!(
+            decode_staking_pool_call("withdraw_all", b"{}", 0),
+            Some(StakingPoolCall {
+                type_: crate::models::OperationType::Withdraw,
+                requested_amount: None,
+            })
+        );
+        // The amount is passed as a string to avoid precision loss in JSON.
+        assert_eq!(decode_staking_pool_call("unstake", br#"{"amount": 500}"#, 0), None);
+        assert_eq!(decode_staking_pool_call("withdraw", b"{}", 0), None);
+        assert_eq!(decode_staking_pool_call("ft_transfer", b"{}", 1), None);
+    }
 }
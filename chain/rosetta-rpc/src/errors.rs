@@ -71,3 +71,93 @@ impl From<near_client_primitives::types::GetStateChangesError> for ErrorKind {
         }
     }
 }
+
+impl From<near_client_primitives::types::QueryError> for ErrorKind {
+    fn from(err: near_client_primitives::types::QueryError) -> Self {
+        match err {
+            near_client_primitives::types::QueryError::UnknownBlock { .. }
+            | near_client_primitives::types::QueryError::UnknownAccount { .. }
+            | near_client_primitives::types::QueryError::NoContractCode { .. }
+            | near_client_primitives::types::QueryError::UnknownAccessKey { .. } => {
+                Self::NotFound(err.to_string())
+            }
+            near_client_primitives::types::QueryError::InvalidAccount { .. }
+            | near_client_primitives::types::QueryError::TooLargeContractState { .. }
+            | near_client_primitives::types::QueryError::ContractExecutionError { .. } => {
+                Self::InvalidInput(err.to_string())
+            }
+            near_client_primitives::types::QueryError::NoSyncedBlocks
+            | near_client_primitives::types::QueryError::UnavailableShard { .. }
+            | near_client_primitives::types::QueryError::InternalError { .. }
+            | near_client_primitives::types::QueryError::Unreachable { .. } => {
+                Self::InternalError(err.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_client_primitives::types::QueryError;
+
+    use super::ErrorKind;
+
+    #[test]
+    fn test_query_error_kinds() {
+        let account_id: near_primitives::types::AccountId = "alice.near".parse().unwrap();
+        let block_height = 5;
+        let block_hash = near_primitives::hash::hash(b"block");
+
+        for err in [
+            QueryError::UnknownBlock {
+                block_reference: near_primitives::types::BlockId::Height(block_height).into(),
+            },
+            QueryError::UnknownAccount {
+                requested_account_id: account_id.clone(),
+                block_height,
+                block_hash,
+            },
+            QueryError::NoContractCode {
+                contract_account_id: account_id.clone(),
+                block_height,
+                block_hash,
+            },
+            QueryError::UnknownAccessKey {
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                block_height,
+                block_hash,
+            },
+        ] {
+            assert!(matches!(ErrorKind::from(err), ErrorKind::NotFound(_)));
+        }
+
+        for err in [
+            QueryError::InvalidAccount {
+                requested_account_id: account_id.clone(),
+                block_height,
+                block_hash,
+            },
+            QueryError::TooLargeContractState {
+                contract_account_id: account_id,
+                block_height,
+                block_hash,
+            },
+            QueryError::ContractExecutionError {
+                vm_error: "MethodNotFound".to_string(),
+                block_height,
+                block_hash,
+            },
+        ] {
+            assert!(matches!(ErrorKind::from(err), ErrorKind::InvalidInput(_)));
+        }
+
+        for err in [
+            QueryError::NoSyncedBlocks,
+            QueryError::UnavailableShard { requested_shard_id: 0 },
+            QueryError::InternalError { error_message: "error".to_string() },
+            QueryError::Unreachable { error_message: "error".to_string() },
+        ] {
+            assert!(matches!(ErrorKind::from(err), ErrorKind::InternalError(_)));
+        }
+    }
+}
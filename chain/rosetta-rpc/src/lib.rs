@@ -31,6 +31,16 @@ pub const BASE_PATH: &str = "";
 pub const API_VERSION: &str = "1.4.4";
 pub const BLOCKCHAIN: &str = "nearprotocol";
 
+/// The largest number of events returned by a single `/events/blocks` call.
+const MAX_BLOCK_EVENTS_LIMIT: u64 = 100;
+/// The largest number of transactions returned by a single
+/// `/search/transactions` call.
+const MAX_SEARCH_TRANSACTIONS_LIMIT: u64 = 100;
+/// The number of heights of the account history `/search/transactions` looks
+/// at first. Each window of heights without changes of the account doubles the
+/// next one, so that the history of idle accounts is walked quickly.
+const SEARCH_TRANSACTIONS_HISTORY_WINDOW: u64 = 10_000;
+
 /// Verifies that network identifier provided by the user is what we expect.
 ///
 /// `blockchain` and `network` must match and `sub_network_identifier` must not
@@ -64,6 +74,46 @@ async fn check_network_identifier(
     Ok(status)
 }
 
+/// Parses an optional block identifier of a request, defaulting to the final
+/// block.
+fn parse_block_reference(
+    block_identifier: Option<models::PartialBlockIdentifier>,
+) -> Result<near_primitives::types::BlockReference, errors::ErrorKind> {
+    block_identifier.map(TryInto::try_into).unwrap_or(Ok(
+        near_primitives::types::BlockReference::Finality(near_primitives::types::Finality::Final),
+    ))
+}
+
+/// Parses the `limit` of a paginated request, defaulting to the largest one.
+fn parse_limit(limit: Option<i64>, max_limit: u64) -> Result<u64, errors::ErrorKind> {
+    match limit {
+        None => Ok(max_limit),
+        Some(limit) if limit > 0 => Ok(std::cmp::min(limit as u64, max_limit)),
+        Some(limit) => Err(errors::ErrorKind::InvalidInput(format!(
+            "Invalid limit {}, must be positive",
+            limit
+        ))),
+    }
+}
+
+/// Parses a non-negative offset or block index of a request.
+fn parse_index(name: &str, index: i64) -> Result<u64, errors::ErrorKind> {
+    index.try_into().map_err(|_| {
+        errors::ErrorKind::InvalidInput(format!("Invalid {} {}, must not be negative", name, index))
+    })
+}
+
+async fn get_final_block(
+    view_client_addr: &web::Data<Addr<ViewClientActor>>,
+) -> Result<near_primitives::views::BlockView, errors::ErrorKind> {
+    view_client_addr
+        .send(near_client::GetBlock(near_primitives::types::BlockReference::Finality(
+            near_primitives::types::Finality::Final,
+        )))
+        .await?
+        .map_err(|err| errors::ErrorKind::InternalError(err.to_string()))
+}
+
 /// Get List of Available Networks
 ///
 /// This endpoint returns a list of NetworkIdentifiers that the Rosetta server
@@ -180,6 +230,7 @@ async fn network_options(
             operation_types: models::OperationType::iter().collect(),
             errors: errors::ErrorKind::iter().map(models::Error::from_error_kind).collect(),
             historical_balance_lookup: true,
+            call_methods: models::CallMethod::iter().collect(),
         },
     }))
 }
@@ -310,6 +361,32 @@ async fn block_transaction_details(
     Ok(Json(models::BlockTransactionResponse { transaction }))
 }
 
+/// Queries the balances of the account delegated to the staking pool given in
+/// the metadata of a POOL_* sub-account.
+async fn query_pool_balances(
+    sub_account: models::SubAccountIdentifier,
+    block_hash: near_primitives::hash::CryptoHash,
+    account_id: &near_primitives::types::AccountId,
+    view_client_addr: &Addr<ViewClientActor>,
+) -> Result<crate::utils::StakingPoolBalances, errors::ErrorKind> {
+    let pool_id = sub_account
+        .metadata
+        .ok_or_else(|| {
+            errors::ErrorKind::InvalidInput(format!(
+                "{:?} sub-account requires `pool_id` metadata",
+                sub_account.address
+            ))
+        })?
+        .pool_id;
+    crate::utils::query_staking_pool_balances(
+        near_primitives::types::BlockId::Hash(block_hash).into(),
+        pool_id.into(),
+        account_id,
+        view_client_addr,
+    )
+    .await
+}
+
 #[api_v2_operation]
 /// Get an Account Balance
 ///
@@ -340,11 +417,7 @@ async fn account_balance(
 
     check_network_identifier(&client_addr, network_identifier).await?;
 
-    let block_id: near_primitives::types::BlockReference = block_identifier
-        .map(TryInto::try_into)
-        .unwrap_or(Ok(near_primitives::types::BlockReference::Finality(
-            near_primitives::types::Finality::Final,
-        )))?;
+    let block_id = parse_block_reference(block_identifier)?;
 
     // TODO: update error handling once we return structured errors from the
     // view_client handlers
@@ -357,9 +430,9 @@ async fn account_balance(
             .await?
             .runtime_config;
 
-    let account_id = account_identifier.address.into();
+    let account_id: near_primitives::types::AccountId = account_identifier.address.into();
     let (block_hash, block_height, account_info) =
-        match crate::utils::query_account(block_id, account_id, &view_client_addr).await {
+        match crate::utils::query_account(block_id, account_id.clone(), &view_client_addr).await {
             Ok(account_info_response) => account_info_response,
             Err(crate::errors::ErrorKind::NotFound(_)) => (
                 block.header.hash,
@@ -378,6 +451,48 @@ async fn account_balance(
             crate::models::SubAccount::LiquidBalanceForStorage => {
                 account_balances.liquid_for_storage
             }
+            crate::models::SubAccount::PoolStaked => {
+                query_pool_balances(sub_account, block_hash, &account_id, &view_client_addr)
+                    .await?
+                    .staked
+            }
+            crate::models::SubAccount::PoolUnstaked => {
+                query_pool_balances(sub_account, block_hash, &account_id, &view_client_addr)
+                    .await?
+                    .unstaked
+            }
+            crate::models::SubAccount::PoolWithdrawable => {
+                query_pool_balances(sub_account, block_hash, &account_id, &view_client_addr)
+                    .await?
+                    .withdrawable
+            }
+            crate::models::SubAccount::LockupLocked => {
+                crate::utils::query_lockup_balance(
+                    near_primitives::types::BlockId::Hash(block_hash).into(),
+                    account_id,
+                    "get_locked_amount",
+                    &view_client_addr,
+                )
+                .await?
+            }
+            crate::models::SubAccount::LockupOwnersBalance => {
+                crate::utils::query_lockup_balance(
+                    near_primitives::types::BlockId::Hash(block_hash).into(),
+                    account_id,
+                    "get_owners_balance",
+                    &view_client_addr,
+                )
+                .await?
+            }
+            crate::models::SubAccount::LockupLiquidOwnersBalance => {
+                crate::utils::query_lockup_balance(
+                    near_primitives::types::BlockId::Hash(block_hash).into(),
+                    account_id,
+                    "get_liquid_owners_balance",
+                    &view_client_addr,
+                )
+                .await?
+            }
         }
     } else {
         account_balances.liquid
@@ -426,6 +541,386 @@ async fn mempool_transaction(
     Err(errors::ErrorKind::InternalError("Not implemented yet".to_string()).into())
 }
 
+/// Heights of the blocks of an `/events/blocks` page: `limit` heights from
+/// `offset`, or the last `limit` final heights without an offset. Heights below
+/// `earliest_height` are garbage collected and skipped.
+fn block_events_heights(
+    offset: Option<u64>,
+    limit: u64,
+    earliest_height: near_primitives::types::BlockHeight,
+    final_height: near_primitives::types::BlockHeight,
+) -> std::ops::RangeInclusive<near_primitives::types::BlockHeight> {
+    let first_height = match offset {
+        Some(offset) => std::cmp::max(offset, earliest_height),
+        None => std::cmp::max(final_height.saturating_sub(limit - 1), earliest_height),
+    };
+    let last_height = std::cmp::min(first_height.saturating_add(limit - 1), final_height);
+    first_height..=last_height
+}
+
+#[api_v2_operation]
+/// [INDEXER] Get a range of BlockEvents
+///
+/// `/events/blocks` allows the caller to query a sequence of BlockEvents
+/// indicating which blocks were added and removed from storage to reach the
+/// current state. Following BlockEvents allows lightweight clients to update
+/// their state without needing to implement their own syncing logic (like
+/// finding the common parent in a reorg).
+///
+/// NOTE: Our implementation streams final blocks only. Final blocks are never
+/// reverted, so the stream consists of `block_added` events, and the sequence
+/// of an event is the height of its block (heights skipped by the chain are
+/// skipped by the sequence as well).
+async fn events_blocks(
+    genesis: web::Data<Arc<Genesis>>,
+    client_addr: web::Data<Addr<ClientActor>>,
+    view_client_addr: web::Data<Addr<ViewClientActor>>,
+    body: Json<models::EventsBlocksRequest>,
+) -> Result<Json<models::EventsBlocksResponse>, models::Error> {
+    let Json(models::EventsBlocksRequest { network_identifier, offset, limit }) = body;
+
+    check_network_identifier(&client_addr, network_identifier).await?;
+
+    let limit = parse_limit(limit, MAX_BLOCK_EVENTS_LIMIT)?;
+    let final_height = get_final_block(&view_client_addr).await?.header.height;
+    // Blocks below the earliest available one are garbage collected.
+    let earliest_height = match view_client_addr
+        .send(near_client::GetBlock(near_primitives::types::BlockReference::SyncCheckpoint(
+            near_primitives::types::SyncCheckpoint::EarliestAvailable,
+        )))
+        .await?
+    {
+        Ok(block) => block.header.height,
+        Err(_) => genesis.config.genesis_height,
+    };
+
+    let offset = offset.map(|offset| parse_index("offset", offset)).transpose()?;
+    let heights = block_events_heights(offset, limit, earliest_height, final_height);
+
+    let blocks = futures::future::join_all(heights.map(|height| {
+        view_client_addr
+            .send(near_client::GetBlock(near_primitives::types::BlockId::Height(height).into()))
+    }))
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    let events = crate::adapters::convert_blocks_to_events(blocks)?;
+
+    Ok(Json(models::EventsBlocksResponse {
+        max_sequence: final_height.try_into().unwrap(),
+        events,
+    }))
+}
+
+#[api_v2_operation]
+/// [INDEXER] Search for Transactions
+///
+/// `/search/transactions` allows the caller to search for transactions that
+/// meet certain conditions. Some conditions include matching a transaction
+/// hash, containing an operation with a certain status, or containing an
+/// operation that affects a certain account.
+///
+/// NOTE: Our implementation requires an account (either `account_identifier`
+/// or `address`) and looks up the final blocks which changed the account in
+/// the state changes history index of the node, so it does not scan every
+/// block. The other conditions are applied to the transactions of these
+/// blocks. The blocks are processed from `max_block` backwards until the page
+/// is full, so the cost of a page grows with `offset + limit` rather than with
+/// the age of the account. As a result, `total_count` is exact only once
+/// `next_offset` is not returned, and the last page may be empty. Only the
+/// blocks kept by the node are searched, so archival nodes are required to
+/// search the whole history.
+async fn search_transactions(
+    genesis: web::Data<Arc<Genesis>>,
+    client_addr: web::Data<Addr<ClientActor>>,
+    view_client_addr: web::Data<Addr<ViewClientActor>>,
+    body: Json<models::SearchTransactionsRequest>,
+) -> Result<Json<models::SearchTransactionsResponse>, models::Error> {
+    let Json(models::SearchTransactionsRequest {
+        network_identifier,
+        operator,
+        max_block,
+        offset,
+        limit,
+        transaction_identifier,
+        account_identifier,
+        status,
+        type_,
+        address,
+        success,
+    }) = body;
+
+    check_network_identifier(&client_addr, network_identifier).await?;
+
+    if let Some(models::Operator::Or) = operator {
+        return Err(errors::ErrorKind::InvalidInput(
+            "Only the `and` operator is supported".to_string(),
+        )
+        .into());
+    }
+    let account_id: near_primitives::types::AccountId = account_identifier
+        .as_ref()
+        .map(|account_identifier| account_identifier.address.clone())
+        .or_else(|| address.clone())
+        .ok_or_else(|| {
+            errors::ErrorKind::InvalidInput(
+                "Either account_identifier or address is required".to_string(),
+            )
+        })?
+        .into();
+    let offset = offset.map(|offset| parse_index("offset", offset)).transpose()?.unwrap_or(0);
+    let limit = parse_limit(limit, MAX_SEARCH_TRANSACTIONS_LIMIT)?;
+
+    let final_height = get_final_block(&view_client_addr).await?.header.height;
+    let max_height = match max_block {
+        Some(max_block) => std::cmp::min(parse_index("max_block", max_block)?, final_height),
+        None => final_height,
+    };
+    let filter = crate::adapters::TransactionsFilter {
+        transaction_identifier,
+        account_identifier,
+        address,
+        operation_type: type_,
+        operation_status: status,
+        success,
+    };
+    let mut page = crate::adapters::TransactionsPage::new(offset, limit);
+    // The history is walked backwards from `max_height`, a window of heights
+    // at a time, until the page is full.
+    let lowest_height = genesis.config.genesis_height;
+    let mut to_height = max_height;
+    let mut window = SEARCH_TRANSACTIONS_HISTORY_WINDOW;
+    let exhausted = 'search: loop {
+        if page.is_full() {
+            break false;
+        }
+        let from_height = std::cmp::max(to_height.saturating_sub(window - 1), lowest_height);
+        let account_history =
+            get_account_history(&view_client_addr, &account_id, from_height, to_height).await?;
+        window = if account_history.is_empty() {
+            std::cmp::min(
+                window.saturating_mul(2),
+                near_client_primitives::types::MAX_STATE_CHANGES_HISTORY_RANGE,
+            )
+        } else {
+            SEARCH_TRANSACTIONS_HISTORY_WINDOW
+        };
+        // Most recent blocks go first.
+        let mut account_history = account_history.into_iter().rev().peekable();
+        while let Some(state_change) = account_history.next() {
+            let block = view_client_addr
+                .send(near_client::GetBlock(
+                    near_primitives::types::BlockId::Hash(state_change.block_hash).into(),
+                ))
+                .await?
+                .map_err(|err| errors::ErrorKind::InternalError(err.to_string()))?;
+            let block_identifier: models::BlockIdentifier = (&block.header).into();
+            let block_transactions = crate::adapters::collect_transactions(
+                Arc::clone(&genesis),
+                Addr::clone(&view_client_addr),
+                &block,
+            )
+            .await?;
+            for transaction in block_transactions {
+                if filter.matches(&transaction) {
+                    page.push(&block_identifier, transaction);
+                }
+            }
+            if page.is_full() && account_history.peek().is_some() {
+                break 'search false;
+            }
+        }
+        if from_height == lowest_height {
+            break true;
+        }
+        to_height = from_height - 1;
+    };
+
+    Ok(Json(page.into_response(exhausted)))
+}
+
+/// Returns the final blocks in `from_height..=to_height` which changed the
+/// account, in the order of heights.
+async fn get_account_history(
+    view_client_addr: &web::Data<Addr<ViewClientActor>>,
+    account_id: &near_primitives::types::AccountId,
+    from_height: near_primitives::types::BlockHeight,
+    to_height: near_primitives::types::BlockHeight,
+) -> Result<Vec<near_primitives::views::StateChangeInBlockView>, errors::ErrorKind> {
+    let mut account_history = vec![];
+    let mut next_from_height = Some(from_height);
+    while let Some(from_height) = next_from_height {
+        let history = view_client_addr
+            .send(near_client::GetStateChangesHistory {
+                key: near_primitives::views::StateKeyView::Account {
+                    account_id: account_id.clone(),
+                },
+                from_height,
+                to_height: Some(to_height),
+                limit: near_client::MAX_STATE_CHANGES_HISTORY_LIMIT,
+            })
+            .await??;
+        account_history.extend(history.changes);
+        next_from_height = history.next_from_height;
+    }
+    Ok(account_history)
+}
+
+/// Parses the parameters of a `/call` method into the query it maps to.
+fn parse_call_parameters(
+    method: models::CallMethod,
+    parameters: crate::utils::JsonObject,
+) -> Result<
+    (near_primitives::types::BlockReference, near_primitives::views::QueryRequest),
+    errors::ErrorKind,
+> {
+    Ok(match method {
+        models::CallMethod::CallFunction => {
+            let models::CallFunctionParameters { account_id, method_name, args, block_identifier } =
+                parameters.parse()?;
+            (
+                parse_block_reference(block_identifier)?,
+                near_primitives::views::QueryRequest::CallFunction {
+                    account_id: account_id.into(),
+                    method_name,
+                    args: args.into_inner().into(),
+                },
+            )
+        }
+        models::CallMethod::ViewAccessKey => {
+            let models::ViewAccessKeyParameters { account_id, public_key, block_identifier } =
+                parameters.parse()?;
+            (
+                parse_block_reference(block_identifier)?,
+                near_primitives::views::QueryRequest::ViewAccessKey {
+                    account_id: account_id.into(),
+                    public_key: (&public_key).try_into().map_err(|err| {
+                        errors::ErrorKind::InvalidInput(format!(
+                            "public key could not be parsed due to: {:?}",
+                            err
+                        ))
+                    })?,
+                },
+            )
+        }
+        models::CallMethod::ViewAccessKeyList => {
+            let models::ViewAccessKeyListParameters { account_id, block_identifier } =
+                parameters.parse()?;
+            (
+                parse_block_reference(block_identifier)?,
+                near_primitives::views::QueryRequest::ViewAccessKeyList {
+                    account_id: account_id.into(),
+                },
+            )
+        }
+        models::CallMethod::ViewState => {
+            let models::ViewStateParameters { account_id, prefix, block_identifier } =
+                parameters.parse()?;
+            (
+                parse_block_reference(block_identifier)?,
+                near_primitives::views::QueryRequest::ViewState {
+                    account_id: account_id.into(),
+                    prefix: prefix.map(|prefix| prefix.into_inner()).unwrap_or_default().into(),
+                },
+            )
+        }
+    })
+}
+
+#[api_v2_operation]
+/// Make a Network-Specific Procedure Call
+///
+/// Call invokes an arbitrary, network-specific procedure call with
+/// network-specific parameters. The guidance for what this endpoint should or
+/// could do is purposely left vague. In Ethereum, this could be used to invoke
+/// eth_call to implement an entire Rosetta API interface for some smart
+/// contract that is not parsed by the implementation creator (like a DEX).
+///
+/// NOTE: Our implementation maps the methods to the view queries of NEAR:
+/// `call_function` (e.g. to check staking-pool and lockup balances),
+/// `view_access_key`, `view_access_key_list` and `view_state`. The query is
+/// performed at the final block unless `block_identifier` parameter is
+/// specified.
+async fn call(
+    client_addr: web::Data<Addr<ClientActor>>,
+    view_client_addr: web::Data<Addr<ViewClientActor>>,
+    body: Json<models::CallRequest>,
+) -> Result<Json<models::CallResponse>, models::Error> {
+    let Json(models::CallRequest { network_identifier, method, parameters }) = body;
+
+    check_network_identifier(&client_addr, network_identifier).await?;
+
+    let (block_id, request) = parse_call_parameters(method, parameters)?;
+    let idempotent = matches!(
+        block_id,
+        near_primitives::types::BlockReference::BlockId(near_primitives::types::BlockId::Hash(_))
+    );
+
+    let response = crate::utils::query(block_id, request, &view_client_addr).await?;
+    let block_identifier = models::BlockIdentifier {
+        index: response.block_height.try_into().unwrap(),
+        hash: response.block_hash.to_base(),
+    };
+    let result = match response.kind {
+        near_primitives::views::QueryResponseKind::CallResult(call_result) => {
+            crate::utils::JsonObject::from_serializable(&models::CallFunctionResult {
+                block_identifier,
+                result: call_result.result.into(),
+                logs: call_result.logs,
+            })
+        }
+        near_primitives::views::QueryResponseKind::AccessKey(access_key) => {
+            crate::utils::JsonObject::from_serializable(&models::ViewAccessKeyResult {
+                block_identifier,
+                access_key,
+            })
+        }
+        near_primitives::views::QueryResponseKind::AccessKeyList(access_key_list) => {
+            crate::utils::JsonObject::from_serializable(&models::ViewAccessKeyListResult {
+                block_identifier,
+                keys: access_key_list
+                    .keys
+                    .into_iter()
+                    .map(|key| models::AccessKeyInfo {
+                        public_key: (&key.public_key).into(),
+                        access_key: key.access_key,
+                    })
+                    .collect(),
+            })
+        }
+        near_primitives::views::QueryResponseKind::ViewState(view_state) => {
+            let decode = |value: &str| {
+                near_primitives::serialize::from_base64(value).map(Into::into).map_err(|err| {
+                    errors::ErrorKind::InternalInvariantError(format!(
+                        "state item could not be decoded due to: {}",
+                        err
+                    ))
+                })
+            };
+            let values = view_state
+                .values
+                .iter()
+                .map(|item| {
+                    Ok(models::StateItem { key: decode(&item.key)?, value: decode(&item.value)? })
+                })
+                .collect::<Result<_, errors::ErrorKind>>()?;
+            crate::utils::JsonObject::from_serializable(&models::ViewStateResult {
+                block_identifier,
+                values,
+            })
+        }
+        kind => {
+            return Err(errors::ErrorKind::InternalInvariantError(format!(
+                "queried {:?}, but received {:?}.",
+                method, kind
+            ))
+            .into())
+        }
+    };
+
+    Ok(Json(models::CallResponse { result, idempotent }))
+}
+
 #[api_v2_operation]
 /// Derive an Address from a PublicKey (offline API, only for implicit accounts)
 ///
@@ -822,6 +1317,11 @@ pub fn start_rosetta_rpc(
             .service(
                 web::resource("/mempool/transaction").route(web::post().to(mempool_transaction)),
             )
+            .service(web::resource("/call").route(web::post().to(call)))
+            .service(web::resource("/events/blocks").route(web::post().to(events_blocks)))
+            .service(
+                web::resource("/search/transactions").route(web::post().to(search_transactions)),
+            )
             .service(
                 web::resource("/construction/derive").route(web::post().to(construction_derive)),
             )
@@ -854,3 +1354,462 @@ pub fn start_rosetta_rpc(
     .disable_signals()
     .run()
 }
+
+#[cfg(test)]
+mod tests {
+    use actix::System;
+    use serde_json::json;
+
+    use near_actix_test_utils::run_actix;
+    use near_chain_configs::{GenesisConfig, GenesisRecords};
+    use near_client::test_utils::setup_no_network;
+    use near_logger_utils::init_test_logger;
+    use near_network::test_utils::open_port;
+
+    use super::*;
+
+    /// Starts a node without network, which stays at the genesis block, and the
+    /// Rosetta RPC server for it. Returns the URL of the server.
+    fn start_all() -> String {
+        let (client_addr, view_client_addr) = setup_no_network(
+            vec!["test1".parse().unwrap(), "test2".parse().unwrap()],
+            "other".parse().unwrap(),
+            true,
+            false,
+        );
+        let genesis = Genesis {
+            config: GenesisConfig::from_json(include_str!(
+                "../../../nearcore/res/genesis_config.json"
+            )),
+            records: GenesisRecords(vec![]),
+            records_file: Default::default(),
+        };
+        let addr = format!("127.0.0.1:{}", open_port());
+        start_rosetta_rpc(
+            RosettaRpcConfig::new(&addr),
+            Arc::new(genesis),
+            client_addr,
+            view_client_addr,
+        );
+        format!("http://{}", addr)
+    }
+
+    async fn post<T: serde::de::DeserializeOwned>(
+        url: &str,
+        path: &str,
+        mut body: serde_json::Value,
+    ) -> Result<T, models::Error> {
+        body["network_identifier"] = json!({"blockchain": BLOCKCHAIN, "network": "unittest"});
+        let mut response =
+            awc::Client::new().post(format!("{}{}", url, path)).send_json(&body).await.unwrap();
+        if response.status().is_success() {
+            Ok(response.json().await.unwrap())
+        } else {
+            Err(response.json().await.unwrap())
+        }
+    }
+
+    fn assert_invalid_input<T: std::fmt::Debug>(result: Result<T, models::Error>) {
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.code,
+            models::Error::from_error_kind(errors::ErrorKind::InvalidInput(String::new())).code,
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_block_events_heights() {
+        assert_eq!(block_events_heights(Some(5), 3, 0, 100), 5..=7);
+        // The last heights without an offset.
+        assert_eq!(block_events_heights(None, 3, 0, 100), 98..=100);
+        assert_eq!(block_events_heights(None, 10, 0, 3), 0..=3);
+        // Garbage collected heights are skipped.
+        assert_eq!(block_events_heights(Some(5), 10, 8, 100), 8..=17);
+        assert_eq!(block_events_heights(None, 10, 95, 100), 95..=100);
+        // Heights after the final block are not returned yet.
+        assert_eq!(block_events_heights(Some(98), 10, 0, 100), 98..=100);
+        assert!(block_events_heights(Some(101), 10, 0, 100).is_empty());
+    }
+
+    #[test]
+    fn test_events_blocks() {
+        init_test_logger();
+
+        run_actix(async {
+            let url = start_all();
+            actix::spawn(async move {
+                let response: models::EventsBlocksResponse =
+                    post(&url, "/events/blocks", json!({"offset": 0, "limit": 10})).await.unwrap();
+                assert_eq!(response.max_sequence, 0);
+                assert_eq!(
+                    response.events.iter().map(|event| event.sequence).collect::<Vec<_>>(),
+                    vec![0]
+                );
+                assert_eq!(response.events[0].type_, models::BlockEventType::BlockAdded);
+
+                // Without an offset, the last events are returned.
+                let latest: models::EventsBlocksResponse =
+                    post(&url, "/events/blocks", json!({"limit": 1})).await.unwrap();
+                assert_eq!(latest, response);
+
+                // There are no events after the final block yet.
+                let response: models::EventsBlocksResponse =
+                    post(&url, "/events/blocks", json!({"offset": 1})).await.unwrap();
+                assert_eq!(response.max_sequence, 0);
+                assert!(response.events.is_empty());
+
+                assert_invalid_input(
+                    post::<models::EventsBlocksResponse>(
+                        &url,
+                        "/events/blocks",
+                        json!({"limit": 0}),
+                    )
+                    .await,
+                );
+                assert_invalid_input(
+                    post::<models::EventsBlocksResponse>(
+                        &url,
+                        "/events/blocks",
+                        json!({"offset": -1}),
+                    )
+                    .await,
+                );
+
+                System::current().stop();
+            });
+        });
+    }
+
+    #[test]
+    fn test_search_transactions() {
+        init_test_logger();
+
+        run_actix(async {
+            let url = start_all();
+            actix::spawn(async move {
+                // The genesis block has no transactions of the account.
+                let response: models::SearchTransactionsResponse = post(
+                    &url,
+                    "/search/transactions",
+                    json!({"address": "test1", "offset": 0, "limit": 10}),
+                )
+                .await
+                .unwrap();
+                assert!(response.transactions.is_empty());
+                assert_eq!(response.total_count, 0);
+                assert_eq!(response.next_offset, None);
+
+                let response: models::SearchTransactionsResponse = post(
+                    &url,
+                    "/search/transactions",
+                    json!({
+                        "account_identifier": {"address": "test1"},
+                        "operator": "and",
+                        "max_block": 1000,
+                        "offset": 10,
+                    }),
+                )
+                .await
+                .unwrap();
+                assert!(response.transactions.is_empty());
+                assert_eq!(response.next_offset, None);
+
+                // An account is required.
+                assert_invalid_input(
+                    post::<models::SearchTransactionsResponse>(
+                        &url,
+                        "/search/transactions",
+                        json!({"type": "TRANSFER"}),
+                    )
+                    .await,
+                );
+                assert_invalid_input(
+                    post::<models::SearchTransactionsResponse>(
+                        &url,
+                        "/search/transactions",
+                        json!({"address": "test1", "operator": "or"}),
+                    )
+                    .await,
+                );
+                assert_invalid_input(
+                    post::<models::SearchTransactionsResponse>(
+                        &url,
+                        "/search/transactions",
+                        json!({"address": "test1", "offset": -1}),
+                    )
+                    .await,
+                );
+                assert_invalid_input(
+                    post::<models::SearchTransactionsResponse>(
+                        &url,
+                        "/search/transactions",
+                        json!({"address": "test1", "limit": 0}),
+                    )
+                    .await,
+                );
+
+                System::current().stop();
+            });
+        });
+    }
+
+    fn call_parameters(
+        method: models::CallMethod,
+        parameters: serde_json::Value,
+    ) -> Result<
+        (near_primitives::types::BlockReference, near_primitives::views::QueryRequest),
+        errors::ErrorKind,
+    > {
+        parse_call_parameters(method, crate::utils::JsonObject::from_serializable(&parameters))
+    }
+
+    #[test]
+    fn test_account_balance() {
+        init_test_logger();
+
+        run_actix(async {
+            let url = start_all();
+            actix::spawn(async move {
+                let account_balance = |sub_account: serde_json::Value| {
+                    post::<models::AccountBalanceResponse>(
+                        &url,
+                        "/account/balance",
+                        json!({
+                            "account_identifier": {"address": "test1", "sub_account": sub_account},
+                            "block_identifier": {"index": 0},
+                        }),
+                    )
+                };
+
+                let response = account_balance(serde_json::Value::Null).await.unwrap();
+                assert_eq!(response.block_identifier.index, 0);
+                assert_eq!(response.balances, vec![models::Amount::from_yoctonear(1000)]);
+                let response = account_balance(json!({"address": "LOCKED"})).await.unwrap();
+                assert_eq!(response.balances, vec![models::Amount::from_yoctonear(0)]);
+
+                // The pool the balance is delegated to has to be given.
+                assert_invalid_input(account_balance(json!({"address": "POOL_STAKED"})).await);
+                // The contracts of the test runtime return nothing, so they are
+                // neither staking pools nor lockup contracts.
+                assert_invalid_input(
+                    account_balance(json!({
+                        "address": "POOL_WITHDRAWABLE",
+                        "metadata": {"pool_id": "test2"},
+                    }))
+                    .await,
+                );
+                for address in
+                    ["LOCKUP_LOCKED", "LOCKUP_OWNERS_BALANCE", "LOCKUP_LIQUID_OWNERS_BALANCE"]
+                {
+                    assert_invalid_input(account_balance(json!({ "address": address })).await);
+                }
+
+                System::current().stop();
+            });
+        });
+    }
+
+    #[test]
+    fn test_parse_call_parameters() {
+        use near_primitives::types::{BlockId, BlockReference, Finality};
+        use near_primitives::views::QueryRequest;
+
+        let block_hash = near_primitives::hash::hash(b"block");
+        let public_key =
+            near_crypto::SecretKey::from_seed(near_crypto::KeyType::ED25519, "alice").public_key();
+
+        assert_eq!(
+            call_parameters(
+                models::CallMethod::CallFunction,
+                json!({
+                    "account_id": "pool.near",
+                    "method_name": "get_account_staked_balance",
+                    "args": hex::encode(br#"{"account_id":"alice.near"}"#),
+                    "block_identifier": {"index": 5},
+                }),
+            )
+            .unwrap(),
+            (
+                BlockId::Height(5).into(),
+                QueryRequest::CallFunction {
+                    account_id: "pool.near".parse().unwrap(),
+                    method_name: "get_account_staked_balance".to_string(),
+                    args: br#"{"account_id":"alice.near"}"#.to_vec().into(),
+                },
+            )
+        );
+        assert_eq!(
+            call_parameters(
+                models::CallMethod::ViewAccessKey,
+                json!({
+                    "account_id": "alice.near",
+                    "public_key": {
+                        "hex_bytes": hex::encode(public_key.key_data()),
+                        "curve_type": "edwards25519",
+                    },
+                    "block_identifier": {"hash": block_hash.to_string()},
+                }),
+            )
+            .unwrap(),
+            (
+                BlockId::Hash(block_hash).into(),
+                QueryRequest::ViewAccessKey {
+                    account_id: "alice.near".parse().unwrap(),
+                    public_key: public_key.clone(),
+                },
+            )
+        );
+        // The final block is used if the block is not specified.
+        assert_eq!(
+            call_parameters(
+                models::CallMethod::ViewAccessKeyList,
+                json!({"account_id": "alice.near"}),
+            )
+            .unwrap(),
+            (
+                BlockReference::Finality(Finality::Final),
+                QueryRequest::ViewAccessKeyList { account_id: "alice.near".parse().unwrap() },
+            )
+        );
+        assert_eq!(
+            call_parameters(
+                models::CallMethod::ViewState,
+                json!({"account_id": "alice.near", "prefix": hex::encode(b"STATE")}),
+            )
+            .unwrap(),
+            (
+                BlockReference::Finality(Finality::Final),
+                QueryRequest::ViewState {
+                    account_id: "alice.near".parse().unwrap(),
+                    prefix: b"STATE".to_vec().into(),
+                },
+            )
+        );
+        // All the keys are returned without a prefix.
+        assert_eq!(
+            call_parameters(models::CallMethod::ViewState, json!({"account_id": "alice.near"}))
+                .unwrap()
+                .1,
+            QueryRequest::ViewState {
+                account_id: "alice.near".parse().unwrap(),
+                prefix: vec![].into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_call_parameters() {
+        let invalid_parameters = vec![
+            // Missing parameters.
+            (models::CallMethod::CallFunction, json!({"account_id": "pool.near", "args": ""})),
+            (models::CallMethod::ViewAccessKey, json!({"account_id": "alice.near"})),
+            (models::CallMethod::ViewAccessKeyList, json!({})),
+            (models::CallMethod::ViewState, json!({"prefix": ""})),
+            // Invalid values.
+            (
+                models::CallMethod::CallFunction,
+                json!({"account_id": "pool.near", "method_name": "get", "args": "not hex"}),
+            ),
+            (models::CallMethod::ViewAccessKeyList, json!({"account_id": "Invalid Account"})),
+            (
+                models::CallMethod::ViewAccessKey,
+                json!({
+                    "account_id": "alice.near",
+                    "public_key": {"hex_bytes": "0102", "curve_type": "edwards25519"},
+                }),
+            ),
+            (
+                models::CallMethod::ViewState,
+                json!({"account_id": "alice.near", "block_identifier": {"index": -1}}),
+            ),
+            (
+                models::CallMethod::ViewState,
+                json!({"account_id": "alice.near", "block_identifier": {"hash": "not a hash"}}),
+            ),
+        ];
+        for (method, parameters) in invalid_parameters {
+            let result = call_parameters(method, parameters.clone());
+            assert!(
+                matches!(result, Err(errors::ErrorKind::InvalidInput(_))),
+                "{:?} {} resulted in {:?}",
+                method,
+                parameters,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_call() {
+        init_test_logger();
+
+        run_actix(async {
+            let url = start_all();
+            actix::spawn(async move {
+                let call = |method: &str, parameters: serde_json::Value| {
+                    post::<models::CallResponse>(
+                        &url,
+                        "/call",
+                        json!({"method": method, "parameters": parameters}),
+                    )
+                };
+
+                let response = call(
+                    "call_function",
+                    json!({
+                        "account_id": "test1",
+                        "method_name": "get_balance",
+                        "args": "",
+                        "block_identifier": {"index": 0},
+                    }),
+                )
+                .await
+                .unwrap();
+                assert!(!response.idempotent);
+                let result: models::CallFunctionResult = response.result.parse().unwrap();
+                assert_eq!(result.block_identifier.index, 0);
+                assert!(result.logs.is_empty());
+
+                // Calls at a block given by its hash always return the same result.
+                let response = call(
+                    "view_access_key_list",
+                    json!({
+                        "account_id": "test1",
+                        "block_identifier": {"hash": result.block_identifier.hash},
+                    }),
+                )
+                .await
+                .unwrap();
+                assert!(response.idempotent);
+                let result: models::ViewAccessKeyListResult = response.result.parse().unwrap();
+                assert_eq!(result.keys.len(), 1);
+                assert_eq!(result.keys[0].public_key.curve_type, models::CurveType::Edwards25519);
+
+                let public_key = result.keys[0].public_key.clone();
+                let response = call(
+                    "view_access_key",
+                    json!({"account_id": "test1", "public_key": public_key}),
+                )
+                .await
+                .unwrap();
+                let result: models::ViewAccessKeyResult = response.result.parse().unwrap();
+                assert_eq!(
+                    result.access_key.permission,
+                    near_primitives::views::AccessKeyPermissionView::FullAccess
+                );
+
+                let response = call("view_state", json!({"account_id": "test1"})).await.unwrap();
+                let result: models::ViewStateResult = response.result.parse().unwrap();
+                assert!(result.values.is_empty());
+
+                assert_invalid_input(call("view_state", json!({})).await);
+                // Unknown methods are rejected when the request is parsed.
+                assert_invalid_input(call("view_account", json!({"account_id": "test1"})).await);
+
+                System::current().stop();
+            });
+        });
+    }
+}
@@ -2,7 +2,7 @@ use paperclip::actix::{api_v2_errors, Apiv2Schema};
 
 use near_primitives::serialize::BaseEncode;
 
-use crate::utils::{BlobInHexString, BorshInHexString};
+use crate::utils::{BlobInHexString, BorshInHexString, JsonObject};
 
 /// An AccountBalanceRequest is utilized to make a balance request on the
 /// /account/balance endpoint. If the block_identifier is populated, a
@@ -96,6 +96,12 @@ pub(crate) struct Allow {
     /// Any Rosetta implementation that supports querying the balance of an
     /// account at any height in the past should set this to true.
     pub historical_balance_lookup: bool,
+
+    /// All methods that are supported by the /call endpoint. Communicating
+    /// which parameters should be provided to /call is the responsibility of
+    /// the implementer (this is en lieu of defining an entire type system and
+    /// requiring the implementer to define that in Allow).
+    pub call_methods: Vec<CallMethod>,
 }
 
 /// Amount is some Value of a Currency. It is considered invalid to specify a
@@ -159,6 +165,34 @@ pub(crate) struct Block {
      * pub metadata: Option<serde_json::Value>, */
 }
 
+/// BlockEvent represents the addition or removal of a BlockIdentifier from
+/// storage. Streaming BlockEvents allows lightweight clients to update their
+/// own state without needing to implement their own syncing logic.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct BlockEvent {
+    /// sequence is the unique identifier of a BlockEvent within the context of
+    /// a NetworkIdentifier.
+    ///
+    /// NOTE: Our implementation uses the block height as the sequence.
+    pub sequence: i64,
+
+    pub block_identifier: BlockIdentifier,
+
+    #[serde(rename = "type")]
+    pub type_: BlockEventType,
+}
+
+/// BlockEventType determines if a BlockEvent represents the addition or
+/// removal of a block.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BlockEventType {
+    BlockAdded,
+    /// NOTE: Our implementation only streams final blocks, which are never
+    /// removed, so it never returns this event type.
+    BlockRemoved,
+}
+
 /// The block_identifier uniquely identifies a block in a particular network.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub(crate) struct BlockIdentifier {
@@ -203,6 +237,15 @@ pub(crate) struct BlockResponse {
     pub other_transactions: Option<Vec<TransactionIdentifier>>,
 }
 
+/// BlockTransaction contains a populated Transaction and the BlockIdentifier
+/// that contains it.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct BlockTransaction {
+    pub block_identifier: BlockIdentifier,
+
+    pub transaction: Transaction,
+}
+
 /// A BlockTransactionRequest is used to fetch a Transaction included in a block
 /// that is not returned in a BlockResponse.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
@@ -220,6 +263,166 @@ pub(crate) struct BlockTransactionResponse {
     pub transaction: Transaction,
 }
 
+/// CallRequest is the input to the `/call` endpoint.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct CallRequest {
+    pub network_identifier: NetworkIdentifier,
+
+    /// Method is some network-specific procedure call. This method could map
+    /// to a network-specific RPC endpoint, a method in an SDK generated from a
+    /// smart contract, or some hybrid of the two.
+    pub method: CallMethod,
+
+    /// Parameters is some network-specific argument for a method. It is up to
+    /// the caller to determine which parameters to provide when invoking
+    /// /call.
+    ///
+    /// NOTE: See the `Call*Parameters` types of the methods.
+    pub parameters: JsonObject,
+}
+
+/// CallResponse contains the result of a `/call` invocation.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct CallResponse {
+    /// Result contains the result of the `/call` invocation. This result will
+    /// not be inspected or interpreted by Rosetta tooling and is left to the
+    /// caller to decode.
+    ///
+    /// NOTE: See the `Call*Result` types of the methods.
+    pub result: JsonObject,
+
+    /// Idempotent indicates that if `/call` is invoked with the same
+    /// CallRequest again, at any point in time, it will return the same
+    /// CallResponse.
+    ///
+    /// NOTE: Our implementation only considers calls at a block identified by
+    /// its hash to be idempotent.
+    pub idempotent: bool,
+}
+
+/// The methods of `/call`, which map to the view queries of NEAR.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    Apiv2Schema,
+    strum::EnumIter,
+)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CallMethod {
+    CallFunction,
+    ViewAccessKey,
+    ViewAccessKeyList,
+    ViewState,
+}
+
+/// Parameters of the `call_function` method, which calls a view function of a
+/// contract.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct CallFunctionParameters {
+    pub account_id: super::types::AccountId,
+
+    pub method_name: String,
+
+    /// Arguments of the function, usually JSON.
+    pub args: BlobInHexString<Vec<u8>>,
+
+    /// The final block is used if the block is not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+}
+
+/// Result of the `call_function` method.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct CallFunctionResult {
+    pub block_identifier: BlockIdentifier,
+
+    /// The value returned by the function, usually JSON.
+    pub result: BlobInHexString<Vec<u8>>,
+
+    pub logs: Vec<String>,
+}
+
+/// Parameters of the `view_access_key` method.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ViewAccessKeyParameters {
+    pub account_id: super::types::AccountId,
+
+    pub public_key: PublicKey,
+
+    /// The final block is used if the block is not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+}
+
+/// Result of the `view_access_key` method.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ViewAccessKeyResult {
+    pub block_identifier: BlockIdentifier,
+
+    pub access_key: near_primitives::views::AccessKeyView,
+}
+
+/// Parameters of the `view_access_key_list` method.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ViewAccessKeyListParameters {
+    pub account_id: super::types::AccountId,
+
+    /// The final block is used if the block is not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+}
+
+/// Result of the `view_access_key_list` method.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ViewAccessKeyListResult {
+    pub block_identifier: BlockIdentifier,
+
+    pub keys: Vec<AccessKeyInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct AccessKeyInfo {
+    pub public_key: PublicKey,
+
+    pub access_key: near_primitives::views::AccessKeyView,
+}
+
+/// Parameters of the `view_state` method, which returns the contract data of
+/// an account.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ViewStateParameters {
+    pub account_id: super::types::AccountId,
+
+    /// Only the keys starting with the prefix are returned. All the keys are
+    /// returned if the prefix is not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<BlobInHexString<Vec<u8>>>,
+
+    /// The final block is used if the block is not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+}
+
+/// Result of the `view_state` method.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ViewStateResult {
+    pub block_identifier: BlockIdentifier,
+
+    pub values: Vec<StateItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct StateItem {
+    pub key: BlobInHexString<Vec<u8>>,
+
+    pub value: BlobInHexString<Vec<u8>>,
+}
+
 /// ConstructionDeriveRequest is passed to the `/construction/derive`
 /// endpoint. Network is provided in the request because some blockchains
 /// have different address formats for different networks.
@@ -549,6 +752,39 @@ impl actix_web::ResponseError for Error {
     }
 }
 
+/// EventsBlocksRequest is utilized to fetch a sequence of BlockEvents
+/// indicating which blocks were added and removed from storage to reach the
+/// current state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct EventsBlocksRequest {
+    pub network_identifier: NetworkIdentifier,
+
+    /// offset is the offset into the event stream to sync events from. If
+    /// this field is not populated, we return the limit events backwards from
+    /// tip. If this is set to 0, we start from the beginning.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+
+    /// limit is the maximum number of events to fetch in one call. The
+    /// implementation may return <= limit events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
+
+/// EventsBlocksResponse contains an ordered collection of BlockEvents and the
+/// max retrievable sequence.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct EventsBlocksResponse {
+    /// max_sequence is the maximum available sequence number to fetch.
+    pub max_sequence: i64,
+
+    /// events is an array of BlockEvents indicating the order to add and
+    /// remove blocks to maintain a canonical view of blockchain state.
+    /// Lightweight clients can use this event stream to update state without
+    /// implementing their own block syncing logic.
+    pub events: Vec<BlockEvent>,
+}
+
 /// A MempoolResponse contains all transaction identifiers in the mempool for a
 /// particular network_identifier.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
@@ -707,6 +943,9 @@ pub(crate) enum OperationType {
     DeployContract,
     InitiateFunctionCall,
     FunctionCall,
+    DepositAndStake,
+    Unstake,
+    Withdraw,
 }
 
 #[derive(
@@ -754,6 +993,10 @@ pub(crate) struct OperationMetadata {
     /// Has to be specified for FUNCTION_CALL operation
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attached_gas: Option<crate::utils::SignedDiff<near_primitives::types::Gas>>,
+    /// Specified for DEPOSIT_AND_STAKE, UNSTAKE and WITHDRAW operations unless
+    /// the whole balance is requested (`unstake_all` and `withdraw_all` calls)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_amount: Option<Amount>,
 }
 
 /// Operations contain all balance-changing information within a transaction.
@@ -843,6 +1086,16 @@ pub(crate) struct OperationStatus {
     pub successful: bool,
 }
 
+/// Operator is used by query-related endpoints to determine how to apply
+/// conditions. If this field is not populated, the default and value will be
+/// used.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Operator {
+    Or,
+    And,
+}
+
 /// When fetching data by BlockIdentifier, it may be possible to only specify
 /// the index or hash. If neither property is specified, it is assumed that the
 /// client is making a request at the current block.
@@ -889,16 +1142,138 @@ pub(crate) struct Peer {
      * pub metadata: Option<serde_json::Value>, */
 }
 
+/// SearchTransactionsRequest is used to search for transactions matching a set
+/// of provided conditions in canonical blocks.
+///
+/// NOTE: Our implementation requires either account_identifier or address,
+/// and only supports the and operator.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct SearchTransactionsRequest {
+    pub network_identifier: NetworkIdentifier,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<Operator>,
+
+    /// max_block is the largest block index to consider when searching for
+    /// transactions. If this field is not populated, the current block is
+    /// considered the max_block. If you do not specify a max_block, it is
+    /// possible a newly synced block will interfere with paginated transaction
+    /// queries (as the offset could become invalid with newly added rows).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<i64>,
+
+    /// offset is the offset into the query result to start returning
+    /// transactions. If any search conditions are changed, the query offset
+    /// will change and you must restart your search iteration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+
+    /// limit is the maximum number of transactions to return in one call. The
+    /// implementation may return <= limit transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_identifier: Option<TransactionIdentifier>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_identifier: Option<AccountIdentifier>,
+
+    /// status is the network-specific operation status.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<OperationStatusKind>,
+
+    /// type is the network-specific operation type.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<OperationType>,
+
+    /// address is AccountIdentifier.Address. This is used to get all
+    /// transactions related to an AccountIdentifier.Address, regardless of
+    /// SubAccountIdentifier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<super::types::AccountId>,
+
+    /// success is a synthetic condition populated by parsing network-specific
+    /// operation statuses (using the mapping provided in /network/options).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    /* Rosetta Spec also optionally provides:
+     *
+     * #[serde(skip_serializing_if = "Option::is_none")]
+     * pub coin_identifier: Option<CoinIdentifier>,
+     *
+     * #[serde(skip_serializing_if = "Option::is_none")]
+     * pub currency: Option<Currency>, */
+}
+
+/// SearchTransactionsResponse contains an ordered collection of
+/// BlockTransactions that match the query in SearchTransactionsRequest. These
+/// BlockTransactions are sorted from most recent block to oldest block.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct SearchTransactionsResponse {
+    /// transactions is an array of BlockTransactions sorted by most recent
+    /// BlockIdentifier (meaning that transactions in recent blocks appear
+    /// first). If there are many transactions for a particular search,
+    /// transactions may not contain all matching transactions. It is up to the
+    /// caller to paginate these transactions using the max_block field.
+    pub transactions: Vec<BlockTransaction>,
+
+    /// total_count is the number of results for a given search. Callers
+    /// typically use this value to concurrently fetch results by offset or to
+    /// display a virtual page number associated with results.
+    ///
+    /// NOTE: Our implementation does not process the whole history of the
+    /// account upfront, so unless next_offset is not populated, the count only
+    /// includes the results found in the blocks processed to return this page.
+    pub total_count: i64,
+
+    /// next_offset is the next offset to use when paginating through
+    /// transaction results. If this field is not populated, there are no more
+    /// transactions to query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<i64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub(crate) enum SubAccount {
     LiquidBalanceForStorage,
     Locked,
+    /// The balance staked in a staking pool (`pool_id` metadata is required).
+    PoolStaked,
+    /// The balance unstaked from a staking pool which cannot be withdrawn yet
+    /// (`pool_id` metadata is required).
+    PoolUnstaked,
+    /// The balance unstaked from a staking pool which can be withdrawn
+    /// (`pool_id` metadata is required).
+    PoolWithdrawable,
+    /// The balance still locked by a lockup contract (`get_locked_amount`).
+    LockupLocked,
+    /// The unlocked balance of a lockup contract, including the balance
+    /// delegated to its staking pool (`get_owners_balance`).
+    LockupOwnersBalance,
+    /// The unlocked balance of a lockup contract which the owner can transfer
+    /// right away (`get_liquid_owners_balance`).
+    LockupLiquidOwnersBalance,
+}
+
+impl SubAccount {
+    /// Returns the identifier of a sub-account of the balance delegated to a
+    /// staking pool.
+    pub(crate) fn in_pool(
+        self,
+        pool_id: super::types::AccountId,
+    ) -> crate::models::SubAccountIdentifier {
+        crate::models::SubAccountIdentifier {
+            address: self,
+            metadata: Some(SubAccountMetadata { pool_id }),
+        }
+    }
 }
 
 impl From<SubAccount> for crate::models::SubAccountIdentifier {
     fn from(sub_account: SubAccount) -> Self {
-        crate::models::SubAccountIdentifier { address: sub_account }
+        crate::models::SubAccountIdentifier { address: sub_account, metadata: None }
     }
 }
 
@@ -910,14 +1285,20 @@ pub(crate) struct SubAccountIdentifier {
     /// The SubAccount address may be a cryptographic value or some other
     /// identifier (ex: bonded) that uniquely specifies a SubAccount.
     pub address: SubAccount,
-    /* Rosetta Spec also optionally provides:
-     *
-     * /// If the SubAccount address is not sufficient to uniquely specify a
-     * /// SubAccount, any other identifying information can be stored here.  It is
-     * /// important to note that two SubAccounts with identical addresses but
-     * /// differing metadata will not be considered equal by clients.
-     * #[serde(skip_serializing_if = "Option::is_none")]
-     * pub metadata: Option<serde_json::Value>, */
+
+    /// If the SubAccount address is not sufficient to uniquely specify a
+    /// SubAccount, any other identifying information can be stored here.  It is
+    /// important to note that two SubAccounts with identical addresses but
+    /// differing metadata will not be considered equal by clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<SubAccountMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub(crate) struct SubAccountMetadata {
+    /// The staking pool the balance of POOL_STAKED, POOL_UNSTAKED and
+    /// POOL_WITHDRAWABLE sub-accounts is delegated to.
+    pub pool_id: super::types::AccountId,
 }
 
 /// In blockchains with sharded state, the SubNetworkIdentifier is required to
@@ -128,6 +128,32 @@ where
     }
 }
 
+/// An arbitrary JSON object (e.g. parameters and results of `/call` methods,
+/// whose types depend on the method).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub(crate) struct JsonObject(serde_json::Map<String, serde_json::Value>);
+
+impl paperclip::v2::schema::TypedData for JsonObject {
+    fn data_type() -> paperclip::v2::models::DataType {
+        paperclip::v2::models::DataType::Object
+    }
+}
+
+impl JsonObject {
+    pub fn from_serializable<T: serde::Serialize>(value: &T) -> Self {
+        match serde_json::to_value(value) {
+            Ok(serde_json::Value::Object(object)) => Self(object),
+            other => panic!("a JSON object was expected, but {:?} was serialized", other),
+        }
+    }
+
+    pub fn parse<T: serde::de::DeserializeOwned>(self) -> crate::errors::Result<T> {
+        serde_json::from_value(serde_json::Value::Object(self.0))
+            .map_err(|err| crate::errors::ErrorKind::InvalidInput(err.to_string()))
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub(crate) struct SignedDiff<T>
 where
@@ -407,6 +433,153 @@ pub(crate) async fn query_access_key(
     }
 }
 
+pub(crate) async fn query(
+    block_id: near_primitives::types::BlockReference,
+    request: near_primitives::views::QueryRequest,
+    view_client_addr: &Addr<ViewClientActor>,
+) -> crate::errors::Result<near_primitives::views::QueryResponse> {
+    Ok(view_client_addr.send(near_client::Query::new(block_id, request)).await??)
+}
+
+pub(crate) async fn call_view_function(
+    block_id: near_primitives::types::BlockReference,
+    account_id: near_primitives::types::AccountId,
+    method_name: &str,
+    args: serde_json::Value,
+    view_client_addr: &Addr<ViewClientActor>,
+) -> crate::errors::Result<Vec<u8>> {
+    let request = near_primitives::views::QueryRequest::CallFunction {
+        account_id,
+        method_name: method_name.to_string(),
+        args: serde_json::to_vec(&args).expect("JSON values are always serializable").into(),
+    };
+    let response = query(block_id, request, view_client_addr).await?;
+    match response.kind {
+        near_primitives::views::QueryResponseKind::CallResult(call_result) => {
+            Ok(call_result.result)
+        }
+        _ => Err(crate::errors::ErrorKind::InternalInvariantError(format!(
+            "queried CallFunction, but received {:?}.",
+            response.kind
+        ))),
+    }
+}
+
+/// The account of a delegator as returned by the `get_account` view method of
+/// the standard staking pool contract.
+#[derive(serde::Deserialize)]
+struct StakingPoolAccountView {
+    #[serde(with = "near_primitives::serialize::u128_dec_format")]
+    unstaked_balance: near_primitives::types::Balance,
+    #[serde(with = "near_primitives::serialize::u128_dec_format")]
+    staked_balance: near_primitives::types::Balance,
+    can_withdraw: bool,
+}
+
+/// Balances of an account delegated to a staking pool.
+pub(crate) struct StakingPoolBalances {
+    pub staked: near_primitives::types::Balance,
+    pub unstaked: near_primitives::types::Balance,
+    pub withdrawable: near_primitives::types::Balance,
+}
+
+impl From<StakingPoolAccountView> for StakingPoolBalances {
+    fn from(account: StakingPoolAccountView) -> Self {
+        let (unstaked, withdrawable) = if account.can_withdraw {
+            (0, account.unstaked_balance)
+        } else {
+            (account.unstaked_balance, 0)
+        };
+        Self { staked: account.staked_balance, unstaked, withdrawable }
+    }
+}
+
+pub(crate) async fn query_staking_pool_balances(
+    block_id: near_primitives::types::BlockReference,
+    pool_id: near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    view_client_addr: &Addr<ViewClientActor>,
+) -> crate::errors::Result<StakingPoolBalances> {
+    let result = call_view_function(
+        block_id,
+        pool_id.clone(),
+        "get_account",
+        serde_json::json!({ "account_id": account_id }),
+        view_client_addr,
+    )
+    .await?;
+    let account: StakingPoolAccountView = parse_view_result(&result, &pool_id, "staking pool")?;
+    Ok(account.into())
+}
+
+/// Checks whether the contract deployed to the account implements the
+/// standard staking pool interface, i.e. returns its staking key.
+pub(crate) async fn is_staking_pool(
+    block_id: near_primitives::types::BlockReference,
+    account_id: near_primitives::types::AccountId,
+    view_client_addr: &Addr<ViewClientActor>,
+) -> crate::errors::Result<bool> {
+    let staking_key = call_view_function(
+        block_id,
+        account_id,
+        "get_staking_key",
+        serde_json::json!({}),
+        view_client_addr,
+    )
+    .await;
+    is_staking_key(staking_key)
+}
+
+/// Checks whether a `get_staking_key` call returned a public key. The call
+/// fails if the account has no contract or the contract has no such method.
+fn is_staking_key(staking_key: crate::errors::Result<Vec<u8>>) -> crate::errors::Result<bool> {
+    match staking_key {
+        Ok(result) => Ok(serde_json::from_slice::<near_crypto::PublicKey>(&result).is_ok()),
+        Err(crate::errors::ErrorKind::NotFound(_))
+        | Err(crate::errors::ErrorKind::InvalidInput(_)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// A balance as returned by the view methods of the standard lockup contract.
+#[derive(serde::Deserialize)]
+struct LockupBalance(
+    #[serde(with = "near_primitives::serialize::u128_dec_format")] near_primitives::types::Balance,
+);
+
+/// Calls one of the balance view methods (e.g. `get_locked_amount`) of the
+/// standard lockup contract deployed to the account.
+pub(crate) async fn query_lockup_balance(
+    block_id: near_primitives::types::BlockReference,
+    lockup_account_id: near_primitives::types::AccountId,
+    method_name: &str,
+    view_client_addr: &Addr<ViewClientActor>,
+) -> crate::errors::Result<near_primitives::types::Balance> {
+    let result = call_view_function(
+        block_id,
+        lockup_account_id.clone(),
+        method_name,
+        serde_json::json!({}),
+        view_client_addr,
+    )
+    .await?;
+    let LockupBalance(balance) = parse_view_result(&result, &lockup_account_id, "lockup")?;
+    Ok(balance)
+}
+
+fn parse_view_result<T: serde::de::DeserializeOwned>(
+    result: &[u8],
+    contract_account_id: &near_primitives::types::AccountId,
+    contract_kind: &str,
+) -> crate::errors::Result<T> {
+    serde_json::from_slice(result).map_err(|err| {
+        crate::errors::ErrorKind::InvalidInput(format!(
+            "{} does not look like a {} contract: {}",
+            contract_account_id, contract_kind, err
+        ))
+    })
+}
+
 pub(crate) async fn query_protocol_config(
     block_hash: near_primitives::hash::CryptoHash,
     view_client_addr: &Addr<ViewClientActor>,
@@ -458,3 +631,92 @@ where
         self.known_value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staking_pool_balances(account: serde_json::Value) -> StakingPoolBalances {
+        serde_json::from_value::<StakingPoolAccountView>(account).unwrap().into()
+    }
+
+    #[test]
+    fn test_staking_pool_balances() {
+        let balances = staking_pool_balances(serde_json::json!({
+            "account_id": "alice.near",
+            "unstaked_balance": "300",
+            "staked_balance": "1000",
+            "can_withdraw": false,
+        }));
+        assert_eq!((balances.staked, balances.unstaked, balances.withdrawable), (1000, 300, 0));
+
+        // The unstaked balance can be withdrawn in full once it is unlocked.
+        let balances = staking_pool_balances(serde_json::json!({
+            "account_id": "alice.near",
+            "unstaked_balance": "300",
+            "staked_balance": "1000",
+            "can_withdraw": true,
+        }));
+        assert_eq!((balances.staked, balances.unstaked, balances.withdrawable), (1000, 0, 300));
+
+        // Balances larger than u64::MAX are passed as strings.
+        let balances = staking_pool_balances(serde_json::json!({
+            "account_id": "alice.near",
+            "unstaked_balance": "0",
+            "staked_balance": "100000000000000000000000000",
+            "can_withdraw": true,
+        }));
+        assert_eq!(balances.staked, 100_000_000_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_parse_view_result() {
+        let lockup_account_id: near_primitives::types::AccountId =
+            "alice.lockup.near".parse().unwrap();
+        let LockupBalance(balance) =
+            parse_view_result(br#""5000000000000000000000000""#, &lockup_account_id, "lockup")
+                .unwrap();
+        assert_eq!(balance, 5_000_000_000_000_000_000_000_000);
+
+        for result in [&b"5000"[..], b"", br#"{"amount": "5000"}"#] {
+            assert!(matches!(
+                parse_view_result::<LockupBalance>(result, &lockup_account_id, "lockup"),
+                Err(crate::errors::ErrorKind::InvalidInput(_))
+            ));
+        }
+        assert!(matches!(
+            parse_view_result::<StakingPoolAccountView>(
+                br#"{"staked_balance": "1000"}"#,
+                &lockup_account_id,
+                "staking pool"
+            ),
+            Err(crate::errors::ErrorKind::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_staking_key() {
+        let staking_key =
+            near_crypto::SecretKey::from_seed(near_crypto::KeyType::ED25519, "pool").public_key();
+        assert!(is_staking_key(Ok(serde_json::to_vec(&staking_key).unwrap())).unwrap());
+
+        // Contracts which happen to have a `get_staking_key` method returning
+        // something else are not staking pools.
+        assert!(!is_staking_key(Ok(vec![])).unwrap());
+        assert!(!is_staking_key(Ok(br#"{"key": "value"}"#.to_vec())).unwrap());
+        // Accounts without the contract or the method.
+        assert!(!is_staking_key(Err(crate::errors::ErrorKind::NotFound(
+            "Contract code has never been observed".to_string()
+        )))
+        .unwrap());
+        assert!(!is_staking_key(Err(crate::errors::ErrorKind::InvalidInput(
+            "MethodNotFound".to_string()
+        )))
+        .unwrap());
+
+        assert!(matches!(
+            is_staking_key(Err(crate::errors::ErrorKind::InternalError("error".to_string()))),
+            Err(crate::errors::ErrorKind::InternalError(_))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 //! Chain Client Configuration
 use std::cmp::min;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
@@ -101,6 +102,12 @@ pub struct ClientConfig {
     /// genesis file.  The value only affects the RPCs without influencing the
     /// protocol thus changing it per-node doesn’t affect the blockchain.
     pub max_gas_burnt_view: Option<Gas>,
+    /// Directory with a dump of state sync data to read the state from before requesting it from
+    /// peers, see `near_chain::state_sync_dump`.
+    pub state_sync_dump_dir: Option<PathBuf>,
+    /// If set, the store validator runs in the background and checks about this many records
+    /// per second, resuming from where it stopped after a restart.
+    pub store_validator_keys_per_second: Option<u64>,
 }
 
 impl ClientConfig {
@@ -159,6 +166,8 @@ impl ClientConfig {
             view_client_throttle_period: Duration::from_secs(1),
             trie_viewer_state_size_limit: None,
             max_gas_burnt_view: None,
+            state_sync_dump_dir: None,
+            store_validator_keys_per_second: None,
         }
     }
 }
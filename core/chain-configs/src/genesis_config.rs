@@ -82,6 +82,7 @@ fn default_simple_nightshade_shard_layout() -> Option<ShardLayout> {
     ));
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, SmartDefault, Serialize, Deserialize)]
 pub struct GenesisConfig {
     /// Protocol version that this genesis works with.
@@ -107,6 +108,7 @@ pub struct GenesisConfig {
     /// Threshold of stake that needs to indicate that they ready for upgrade.
     #[serde(default = "default_protocol_upgrade_stake_threshold")]
     #[default(Rational::new(8, 10))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub protocol_upgrade_stake_threshold: Rational,
     /// Number of epochs after stake threshold was achieved to start next prtocol version.
     pub protocol_upgrade_num_epochs: EpochHeight,
@@ -116,8 +118,10 @@ pub struct GenesisConfig {
     pub gas_limit: Gas,
     /// Minimum gas price. It is also the initial gas price.
     #[serde(with = "u128_dec_format_compatible")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub min_gas_price: Balance,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     #[default(MAX_GAS_PRICE)]
     pub max_gas_price: Balance,
     /// Criterion for kicking out block producers (this is a number between 0 and 100)
@@ -127,13 +131,16 @@ pub struct GenesisConfig {
     /// Online minimum threshold below which validator doesn't receive reward.
     #[serde(default = "default_online_min_threshold")]
     #[default(Rational::new(90, 100))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub online_min_threshold: Rational,
     /// Online maximum threshold above which validator gets full reward.
     #[serde(default = "default_online_max_threshold")]
     #[default(Rational::new(99, 100))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub online_max_threshold: Rational,
     /// Gas price adjustment rate
     #[default(Rational::from_integer(0))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub gas_price_adjustment_rate: Rational,
     /// List of initial validators.
     pub validators: Vec<AccountInfo>,
@@ -141,12 +148,15 @@ pub struct GenesisConfig {
     pub transaction_validity_period: NumBlocks,
     /// Protocol treasury rate
     #[default(Rational::from_integer(0))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub protocol_reward_rate: Rational,
     /// Maximum inflation on the total supply every epoch.
     #[default(Rational::from_integer(0))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub max_inflation_rate: Rational,
     /// Total supply of tokens at genesis.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub total_supply: Balance,
     /// Expected number of blocks per year
     pub num_blocks_per_year: NumBlocks,
@@ -155,6 +165,7 @@ pub struct GenesisConfig {
     pub protocol_treasury_account: AccountId,
     /// Fishermen stake threshold.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub fishermen_threshold: Balance,
     /// The minimum stake required for staking is last seat price divided by this number.
     #[serde(default = "default_minimum_stake_divisor")]
@@ -179,6 +190,7 @@ pub struct GenesisConfig {
     /// See https://github.com/near/NEPs/pull/167 for details
     #[serde(default = "default_minimum_stake_ratio")]
     #[default(Rational::new(160, 1_000_000))]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub minimum_stake_ratio: Rational,
 }
 
@@ -543,6 +555,7 @@ impl Genesis {
 // Note: this type cannot be placed in primitives/src/view.rs because of `RuntimeConfig` dependency issues.
 // Ideally we should create `RuntimeConfigView`, but given the deeply nested nature and the number of fields inside
 // `RuntimeConfig`, it should be its own endeavor.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProtocolConfigView {
     /// Current Protocol Version
@@ -563,6 +576,7 @@ pub struct ProtocolConfigView {
     /// Enable dynamic re-sharding.
     pub dynamic_resharding: bool,
     /// Threshold of stake that needs to indicate that they ready for upgrade.
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub protocol_upgrade_stake_threshold: Rational,
     /// Epoch length counted in block heights.
     pub epoch_length: BlockHeightDelta,
@@ -570,27 +584,34 @@ pub struct ProtocolConfigView {
     pub gas_limit: Gas,
     /// Minimum gas price. It is also the initial gas price.
     #[serde(with = "u128_dec_format_compatible")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub min_gas_price: Balance,
     /// Maximum gas price.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub max_gas_price: Balance,
     /// Criterion for kicking out block producers (this is a number between 0 and 100)
     pub block_producer_kickout_threshold: u8,
     /// Criterion for kicking out chunk producers (this is a number between 0 and 100)
     pub chunk_producer_kickout_threshold: u8,
     /// Online minimum threshold below which validator doesn't receive reward.
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub online_min_threshold: Rational,
     /// Online maximum threshold above which validator gets full reward.
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub online_max_threshold: Rational,
     /// Gas price adjustment rate
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub gas_price_adjustment_rate: Rational,
     /// Runtime configuration (mostly economics constants).
     pub runtime_config: RuntimeConfig,
     /// Number of blocks for which a given transaction is valid
     pub transaction_validity_period: NumBlocks,
     /// Protocol treasury rate
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub protocol_reward_rate: Rational,
     /// Maximum inflation on the total supply every epoch.
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub max_inflation_rate: Rational,
     /// Expected number of blocks per year
     pub num_blocks_per_year: NumBlocks,
@@ -598,6 +619,7 @@ pub struct ProtocolConfigView {
     pub protocol_treasury_account: AccountId,
     /// Fishermen stake threshold.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub fishermen_threshold: Balance,
     /// The minimum stake required for staking is last seat price divided by this number.
     pub minimum_stake_divisor: u64,
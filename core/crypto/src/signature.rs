@@ -332,6 +332,18 @@ impl<'de> serde::Deserialize<'de> for PublicKey {
     }
 }
 
+/// Public keys are serialized as `<curve>:<base58>` strings, e.g. `ed25519:6E8sCci...`.
+#[cfg(feature = "schemars_feature")]
+impl schemars::JsonSchema for PublicKey {
+    fn schema_name() -> String {
+        "PublicKey".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
 impl From<&PublicKey> for String {
     fn from(public_key: &PublicKey) -> Self {
         match public_key {
@@ -894,6 +906,18 @@ impl<'de> serde::Deserialize<'de> for Signature {
     }
 }
 
+/// Signatures are serialized as `<curve>:<base58>` strings, like public keys.
+#[cfg(feature = "schemars_feature")]
+impl schemars::JsonSchema for Signature {
+    fn schema_name() -> String {
+        "Signature".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -9,6 +9,7 @@ pub use near_account_id as id;
 use crate::hash::CryptoHash;
 use crate::serialize::{option_u128_dec_format, u128_dec_format_compatible};
 use crate::types::{Balance, Nonce, StorageUsage};
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy,
 )]
@@ -23,13 +24,16 @@ impl Default for AccountVersion {
 }
 
 /// Per account information stored in the state.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub struct Account {
     /// The total not locked tokens.
     #[serde(with = "u128_dec_format_compatible")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     amount: Balance,
     /// The amount locked due to staking.
     #[serde(with = "u128_dec_format_compatible")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     locked: Balance,
     /// Hash of the code stored in the storage for this account.
     code_hash: CryptoHash,
@@ -151,6 +155,7 @@ impl BorshSerialize for Account {
 /// that can be issued.
 /// `account_id,public_key` is a key in the state
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
 )]
@@ -174,6 +179,7 @@ impl AccessKey {
 
 /// Defines permissions for AccessKey
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
 )]
@@ -190,6 +196,7 @@ pub enum AccessKeyPermission {
 /// It also restrict the account ID of the receiver for this function call.
 /// It also can restrict the method name for the allowed function calls.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
 )]
@@ -201,6 +208,7 @@ pub struct FunctionCallPermission {
     /// NOTE: To change or increase the allowance, the old access key needs to be deleted and a new
     /// access key should be created.
     #[serde(with = "option_u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "Option<String>"))]
     pub allowance: Option<Balance>,
 
     // This isn't an AccountId because already existing records in testnet genesis have invalid
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Clone, Debug, Hash, Serialize, Deserialize, PartialEq, Eq)]
 pub struct VMConfig {
     /// Costs for runtime externals
@@ -21,6 +22,7 @@ pub struct VMConfig {
 
 /// Describes limits for VM and Runtime.
 /// TODO #4139: consider switching to strongly-typed wrappers instead of raw quantities
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct VMLimitConfig {
     /// Max amount of gas that can be used, excluding gas attached to promises.
@@ -166,6 +168,7 @@ pub struct ViewConfig {
     pub max_gas_burnt: Gas,
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct ExtCostsConfig {
     /// Base cost for calling a host function.
@@ -75,6 +75,18 @@ impl<'de> Deserialize<'de> for CryptoHash {
     }
 }
 
+/// Hashes are serialized as base58 strings.
+#[cfg(feature = "schemars_feature")]
+impl schemars::JsonSchema for CryptoHash {
+    fn schema_name() -> String {
+        "CryptoHash".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
 impl std::str::FromStr for CryptoHash {
     type Err = Box<dyn std::error::Error>;
 
@@ -12,6 +12,7 @@ use crate::types::Gas;
 /// by the receiver).
 /// NOTE: `send_sir` or `send_not_sir` fees are usually burned when the item is being created.
 /// And `execution` fee is burned when the item is being executed.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct Fee {
     /// Fee for sending an object from the sender to itself, guaranteeing that it does not leave
@@ -43,6 +44,7 @@ impl Fee {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct RuntimeFeesConfig {
     /// Describes the cost of creating an action receipt, `ActionReceipt`, excluding the actual cost
@@ -59,13 +61,16 @@ pub struct RuntimeFeesConfig {
     pub storage_usage_config: StorageUsageConfig,
 
     /// Fraction of the burnt gas to reward to the contract account for execution.
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub burnt_gas_reward: Rational,
 
     /// Pessimistic gas price inflation ratio.
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "(isize, isize)"))]
     pub pessimistic_gas_price_inflation_ratio: Rational,
 }
 
 /// Describes the cost of creating a data receipt, `DataReceipt`.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct DataReceiptCreationConfig {
     /// Base cost of creating a data receipt.
@@ -84,6 +89,7 @@ pub struct DataReceiptCreationConfig {
 }
 
 /// Describes the cost of creating a specific action, `Action`. Includes all variants.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct ActionCreationConfig {
     /// Base cost of creating an account.
@@ -116,6 +122,7 @@ pub struct ActionCreationConfig {
 }
 
 /// Describes the cost of creating an access key.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct AccessKeyCreationConfig {
     /// Base cost of creating a full access access-key.
@@ -127,6 +134,7 @@ pub struct AccessKeyCreationConfig {
 }
 
 /// Describes cost of storage per block
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct StorageUsageConfig {
     /// Number of bytes for an account record, including rounding up for account id.
@@ -111,6 +111,7 @@ impl Challenge {
 pub type Challenges = Vec<Challenge>;
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct SlashedValidator {
     pub account_id: AccountId,
@@ -13,6 +13,7 @@ use near_vm_errors::{CompilationError, FunctionCallErrorSer, MethodResolveError,
 
 /// Error returned in the ExecutionOutcome in case of failure
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
 )]
@@ -105,6 +106,7 @@ impl std::error::Error for StorageError {}
 
 /// An error happened during TX execution
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
 )]
@@ -127,8 +129,10 @@ pub enum InvalidTxError {
     NotEnoughBalance {
         signer_id: AccountId,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         balance: Balance,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         cost: Balance,
     },
     /// Signer account doesn't have enough balance after transaction.
@@ -137,6 +141,7 @@ pub enum InvalidTxError {
         signer_id: AccountId,
         /// Required balance to cover the state.
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         amount: Balance,
     },
     /// An integer overflow occurred during transaction cost estimation.
@@ -152,6 +157,7 @@ pub enum InvalidTxError {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
 )]
@@ -169,8 +175,10 @@ pub enum InvalidAccessKeyError {
         account_id: AccountId,
         public_key: PublicKey,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         allowance: Balance,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         cost: Balance,
     },
     /// Having a deposit with a function call action is not allowed with a function call access key.
@@ -179,6 +187,7 @@ pub enum InvalidAccessKeyError {
 
 /// Describes the error for validating a list of actions.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcError,
 )]
@@ -211,6 +220,7 @@ pub enum ActionsValidationError {
 
 /// Describes the error for validating a receipt.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcError,
 )]
@@ -328,6 +338,7 @@ impl Display for ActionsValidationError {
 
 /// An error happened during Acton execution
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
 )]
@@ -379,6 +390,7 @@ impl From<FunctionCallErrorSer> for ContractCallError {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
 )]
@@ -410,6 +422,7 @@ pub enum ActionErrorKind {
         account_id: AccountId,
         /// Balance required to complete an action.
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         amount: Balance,
     },
     /// Account is not yet staked, but tries to unstake
@@ -418,17 +431,22 @@ pub enum ActionErrorKind {
     TriesToStake {
         account_id: AccountId,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         stake: Balance,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         locked: Balance,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         balance: Balance,
     },
     InsufficientStake {
         account_id: AccountId,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         stake: Balance,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         minimum_stake: Balance,
     },
     /// An error occurred during a `FunctionCall` Action, parameter is debug message.
@@ -7,11 +7,13 @@ use crate::serialize::u128_dec_format;
 use crate::types::{AccountId, Balance};
 
 /// The structure that holds the parameters of the runtime, mostly economics.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct RuntimeConfig {
     /// Amount of yN per byte required to have on the account.  See
     /// <https://nomicon.io/Economics/README.html#state-stake> for details.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub storage_amount_per_byte: Balance,
     /// Costs of different actions that need to be performed when sending and processing transaction
     /// and receipts.
@@ -44,6 +46,7 @@ impl RuntimeConfig {
 }
 
 /// The structure describes configuration for creation of new accounts.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct AccountCreationConfig {
     /// The minimum length of the top-level account ID that is allowed to be created by any account.
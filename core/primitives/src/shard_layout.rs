@@ -53,6 +53,7 @@ use std::collections::HashMap;
 
 pub type ShardVersion = u32;
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum ShardLayout {
     V0(ShardLayoutV0),
@@ -64,6 +65,7 @@ pub enum ShardLayout {
 /// to keep backward compatibility for some existing tests.
 /// `parent_shards` for `ShardLayoutV1` is always `None`, meaning it can only be the first shard layout
 /// a chain uses.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ShardLayoutV0 {
     /// Map accounts evenly across all shards
@@ -78,6 +80,7 @@ pub struct ShardLayoutV0 {
 /// will be `[[0, 1, 2, 3]]`
 type ShardSplitMap = Vec<Vec<ShardId>>;
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ShardLayoutV1 {
     /// num_shards = fixed_shards.len() + boundary_accounts.len() + 1
@@ -293,6 +296,41 @@ impl ShardUId {
     }
 }
 
+/// Formats the shard uid as `s{shard_id}.v{version}`, e.g. `s2.v1`.
+impl std::fmt::Display for ShardUId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "s{}.v{}", self.shard_id, self.version)
+    }
+}
+
+impl std::str::FromStr for ShardUId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("ShardUId {:?} is not in the s<shard_id>.v<version> format", s);
+        let (shard_id, version) =
+            s.strip_prefix('s').and_then(|s| s.split_once(".v")).ok_or_else(error)?;
+        Ok(Self {
+            shard_id: shard_id.parse().map_err(|_| error())?,
+            version: version.parse().map_err(|_| error())?,
+        })
+    }
+}
+
+/// Serialized as a string so that shard uids can be used as keys of JSON maps, e.g. in configs.
+impl Serialize for ShardUId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ShardUId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl TryFrom<&[u8]> for ShardUId {
     type Error = Box<dyn std::error::Error>;
 
@@ -339,6 +377,17 @@ mod tests {
     use rand::{Rng, SeedableRng};
     use std::collections::HashMap;
 
+    #[test]
+    fn test_shard_uid_to_string() {
+        let shard_uid = ShardUId { version: 1, shard_id: 2 };
+        assert_eq!(shard_uid.to_string(), "s2.v1");
+        assert_eq!("s2.v1".parse::<ShardUId>(), Ok(shard_uid));
+        assert_eq!(serde_json::to_string(&shard_uid).unwrap(), "\"s2.v1\"");
+        assert!("2.v1".parse::<ShardUId>().is_err());
+        assert!("s2.1".parse::<ShardUId>().is_err());
+        assert!("s2.vx".parse::<ShardUId>().is_err());
+    }
+
     #[test]
     fn test_shard_layout_v0() {
         let num_shards = 4;
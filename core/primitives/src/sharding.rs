@@ -20,6 +20,7 @@ use reed_solomon_erasure::ReconstructShard;
 use std::sync::Arc;
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize,
     BorshDeserialize,
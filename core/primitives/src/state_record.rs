@@ -18,6 +18,7 @@ use crate::trie_key::trie_key_parsers::{
 use crate::types::AccountId;
 
 /// Record in the state storage.
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum StateRecord {
     /// Account information.
@@ -26,14 +27,17 @@ pub enum StateRecord {
     Data {
         account_id: AccountId,
         #[serde(with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         data_key: Vec<u8>,
         #[serde(with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         value: Vec<u8>,
     },
     /// Contract code encoded in base64.
     Contract {
         account_id: AccountId,
         #[serde(with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         code: Vec<u8>,
     },
     /// Access key associated with some account.
@@ -45,6 +49,7 @@ pub enum StateRecord {
         account_id: AccountId,
         data_id: CryptoHash,
         #[serde(with = "option_base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "Option<String>"))]
         data: Option<Vec<u8>>,
     },
     /// Delayed Receipt.
@@ -29,6 +29,17 @@ pub struct StateHeaderKey(pub ShardId, pub CryptoHash);
 #[derive(PartialEq, Eq, Clone, Debug, BorshSerialize, BorshDeserialize)]
 pub struct StatePartKey(pub CryptoHash, pub ShardId, pub u64 /* PartId */);
 
+/// Progress of syncing a state part, stored in `ColStateDlInfos` under its `StatePartKey` so that
+/// an interrupted state sync resumes with the parts it hasn't finished yet.
+#[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, BorshSerialize, BorshDeserialize)]
+pub enum StatePartStatus {
+    /// The part is validated and saved in `ColStateParts`.
+    Downloaded,
+    /// The part is written to the state.
+    Applied,
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct ShardStateSyncResponseHeaderV1 {
@@ -391,6 +391,83 @@ pub mod trie_key_parsers {
         })
     }
 
+    fn parse_hash_after_account_id(
+        col: &[u8],
+        raw_key: &[u8],
+        col_name: &str,
+    ) -> Result<(AccountId, CryptoHash), std::io::Error> {
+        let account_id = parse_account_id_from_trie_key_with_separator(col, raw_key, col_name)?;
+        let prefix_len = col.len() + account_id.len() + ACCOUNT_DATA_SEPARATOR.len();
+        let hash = CryptoHash::try_from(&raw_key[prefix_len..]).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Can't parse CryptoHash for TrieKey::{}", col_name),
+            )
+        })?;
+        Ok((account_id, hash))
+    }
+
+    /// Decodes a raw key of any record stored in the state trie.
+    pub fn parse_trie_key_from_raw_key(raw_key: &[u8]) -> Result<TrieKey, std::io::Error> {
+        let column = raw_key.get(..1).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "raw key is empty")
+        })?;
+        Ok(match column {
+            col::ACCOUNT => {
+                TrieKey::Account { account_id: parse_account_id_from_account_key(raw_key)? }
+            }
+            col::CONTRACT_CODE => TrieKey::ContractCode {
+                account_id: parse_account_id_from_contract_code_key(raw_key)?,
+            },
+            col::ACCESS_KEY => parse_trie_key_access_key_from_raw_key(raw_key)?,
+            col::RECEIVED_DATA => {
+                let (receiver_id, data_id) =
+                    parse_hash_after_account_id(column, raw_key, "ReceivedData")?;
+                TrieKey::ReceivedData { receiver_id, data_id }
+            }
+            col::POSTPONED_RECEIPT_ID => {
+                let (receiver_id, data_id) =
+                    parse_hash_after_account_id(column, raw_key, "PostponedReceiptId")?;
+                TrieKey::PostponedReceiptId { receiver_id, data_id }
+            }
+            col::PENDING_DATA_COUNT => {
+                let (receiver_id, receipt_id) =
+                    parse_hash_after_account_id(column, raw_key, "PendingDataCount")?;
+                TrieKey::PendingDataCount { receiver_id, receipt_id }
+            }
+            col::POSTPONED_RECEIPT => {
+                let (receiver_id, receipt_id) =
+                    parse_hash_after_account_id(column, raw_key, "PostponedReceipt")?;
+                TrieKey::PostponedReceipt { receiver_id, receipt_id }
+            }
+            // Delayed receipts are stored next to their indices, see `TrieKey::to_vec`.
+            col::DELAYED_RECEIPT_INDICES if raw_key.len() == col::DELAYED_RECEIPT_INDICES.len() => {
+                TrieKey::DelayedReceiptIndices
+            }
+            col::DELAYED_RECEIPT_INDICES => {
+                let index =
+                    raw_key[col::DELAYED_RECEIPT_INDICES.len()..].try_into().map_err(|_| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "raw key has invalid index to be TrieKey::DelayedReceipt",
+                        )
+                    })?;
+                TrieKey::DelayedReceipt { index: u64::from_le_bytes(index) }
+            }
+            col::CONTRACT_DATA => {
+                let account_id = parse_account_id_from_contract_data_key(raw_key)?;
+                let key = parse_data_key_from_contract_data_key(raw_key, &account_id)?.to_vec();
+                TrieKey::ContractData { account_id, key }
+            }
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "raw key does not start with a known column marker",
+                ))
+            }
+        })
+    }
+
     pub fn get_raw_prefix_for_access_keys(account_id: &AccountId) -> Vec<u8> {
         let mut res = Vec::with_capacity(col::ACCESS_KEY.len() * 2 + account_id.len());
         res.extend(col::ACCESS_KEY);
@@ -618,4 +695,30 @@ mod tests {
         let raw_key = key.to_vec();
         assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
     }
+
+    #[test]
+    fn test_parse_trie_key_from_raw_key() {
+        let account_id: AccountId = "alice.near".parse().unwrap();
+        let hash = CryptoHash::hash_bytes(b"data");
+        let keys = vec![
+            TrieKey::Account { account_id: account_id.clone() },
+            TrieKey::ContractCode { account_id: account_id.clone() },
+            TrieKey::AccessKey {
+                account_id: account_id.clone(),
+                public_key: PublicKey::empty(KeyType::ED25519),
+            },
+            TrieKey::ReceivedData { receiver_id: account_id.clone(), data_id: hash },
+            TrieKey::PostponedReceiptId { receiver_id: account_id.clone(), data_id: hash },
+            TrieKey::PendingDataCount { receiver_id: account_id.clone(), receipt_id: hash },
+            TrieKey::PostponedReceipt { receiver_id: account_id.clone(), receipt_id: hash },
+            TrieKey::DelayedReceiptIndices,
+            TrieKey::DelayedReceipt { index: 1234 },
+            TrieKey::ContractData { account_id, key: b"key,with,separators".to_vec() },
+        ];
+        for key in keys {
+            assert_eq!(trie_key_parsers::parse_trie_key_from_raw_key(&key.to_vec()).unwrap(), key);
+        }
+        assert!(trie_key_parsers::parse_trie_key_from_raw_key(&[]).is_err());
+        assert!(trie_key_parsers::parse_trie_key_from_raw_key(&[42]).is_err());
+    }
 }
@@ -22,6 +22,7 @@ pub type StateRoot = CryptoHash;
 
 /// Different types of finality.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
 pub enum Finality {
     #[serde(rename = "optimistic")]
@@ -38,6 +39,7 @@ impl Default for Finality {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccountWithPublicKey {
     pub account_id: AccountId,
@@ -45,11 +47,13 @@ pub struct AccountWithPublicKey {
 }
 
 /// Account info for validators
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct AccountInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub amount: Balance,
 }
 
@@ -243,6 +247,14 @@ pub struct StateChangeWithCause {
 
 pub type StateChanges = Vec<StateChangeWithCause>;
 
+/// The last change of a key made by a block.
+#[derive(Debug)]
+pub struct StateChangeInBlock {
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    pub change: StateChangeWithCause,
+}
+
 #[easy_ext::ext(StateChangesExt)]
 impl StateChanges {
     pub fn from_changes(
@@ -424,6 +436,7 @@ impl StateRootNode {
 /// EpochId of epoch T is the hash of last block in T-2
 /// EpochId of first two epochs is 0
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     Debug,
     Clone,
@@ -827,6 +840,7 @@ pub struct ChunkExtraV1 {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum BlockId {
@@ -837,6 +851,7 @@ pub enum BlockId {
 pub type MaybeBlockId = Option<BlockId>;
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SyncCheckpoint {
@@ -845,6 +860,7 @@ pub enum SyncCheckpoint {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum BlockReference {
@@ -883,6 +899,7 @@ pub struct BlockChunkValidatorStats {
     pub chunk_stats: ValidatorStats,
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum EpochReference {
@@ -912,6 +929,7 @@ impl Serialize for EpochReference {
 
 /// Reasons for removing a validator from the validator set.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum ValidatorKickoutReason {
     /// Slashed validators are kicked out.
@@ -925,14 +943,17 @@ pub enum ValidatorKickoutReason {
     /// Validator stake is now below threshold
     NotEnoughStake {
         #[serde(with = "u128_dec_format", rename = "stake_u128")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         stake: Balance,
         #[serde(with = "u128_dec_format", rename = "threshold_u128")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         threshold: Balance,
     },
     /// Enough stake but is not chosen because of seat limits.
     DidNotGetASeat,
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransactionOrReceiptId {
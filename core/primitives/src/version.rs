@@ -6,6 +6,7 @@ use crate::types::Balance;
 
 /// Data structure for semver version and github tag or commit.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Version {
     pub version: String,
@@ -16,7 +17,7 @@ pub struct Version {
 pub type DbVersion = u32;
 
 /// Current version of the database.
-pub const DB_VERSION: DbVersion = 30;
+pub const DB_VERSION: DbVersion = 32;
 
 /// Protocol version type.
 pub use near_primitives_core::types::ProtocolVersion;
@@ -141,6 +142,10 @@ pub enum ProtocolFeature {
     #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
     /// Add `AccessKey` nonce range for implicit accounts, as in `AccessKeyNonceRange` feature.
     AccessKeyNonceForImplicitAccounts,
+    /// Read contract storage from the flat state and stop charging `touching_trie_node` for
+    /// storage reads, which depends on the trie nodes a read goes through.
+    #[cfg(feature = "protocol_feature_flat_state")]
+    FlatStateReads,
 }
 
 /// Current latest stable version of the protocol.
@@ -151,7 +156,7 @@ pub const PROTOCOL_VERSION: ProtocolVersion = 49;
 
 /// Current latest nightly version of the protocol.
 #[cfg(feature = "nightly_protocol")]
-pub const PROTOCOL_VERSION: ProtocolVersion = 125;
+pub const PROTOCOL_VERSION: ProtocolVersion = 126;
 
 impl ProtocolFeature {
     pub const fn protocol_version(self) -> ProtocolVersion {
@@ -188,6 +193,8 @@ impl ProtocolFeature {
             ProtocolFeature::RoutingExchangeAlgorithm => 117,
             #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
             ProtocolFeature::AccessKeyNonceForImplicitAccounts => 125,
+            #[cfg(feature = "protocol_feature_flat_state")]
+            ProtocolFeature::FlatStateReads => 126,
         }
     }
 }
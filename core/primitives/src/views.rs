@@ -41,22 +41,26 @@ use crate::transaction::{
     DeployContractAction, ExecutionMetadata, ExecutionOutcome, ExecutionOutcomeWithIdAndProof,
     ExecutionStatus, FunctionCallAction, SignedTransaction, StakeAction, TransferAction,
 };
+use crate::trie_key::TrieKey;
 use crate::types::{
     AccountId, AccountWithPublicKey, Balance, BlockHeight, CompiledContractCache, EpochHeight,
-    EpochId, FunctionArgs, Gas, Nonce, NumBlocks, ShardId, StateChangeCause, StateChangeKind,
-    StateChangeValue, StateChangeWithCause, StateChangesRequest, StateRoot, StorageUsage, StoreKey,
-    StoreValue, ValidatorKickoutReason,
+    EpochId, FunctionArgs, Gas, Nonce, NumBlocks, ShardId, StateChangeCause, StateChangeInBlock,
+    StateChangeKind, StateChangeValue, StateChangeWithCause, StateChangesRequest, StateRoot,
+    StorageUsage, StoreKey, StoreValue, ValidatorKickoutReason,
 };
 use crate::version::{ProtocolVersion, Version};
 use validator_stake_view::ValidatorStakeView;
 
 /// A view of the account
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 pub struct AccountView {
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub amount: Balance,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub locked: Balance,
     pub code_hash: CryptoHash,
     pub storage_usage: StorageUsage,
@@ -67,9 +71,11 @@ pub struct AccountView {
 
 /// A view of the contract code.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub struct ContractCodeView {
     #[serde(rename = "code_base64", with = "base64_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub code: Vec<u8>,
     pub hash: CryptoHash,
 }
@@ -140,10 +146,12 @@ impl From<ContractCodeView> for ContractCode {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 pub enum AccessKeyPermissionView {
     FunctionCall {
         #[serde(with = "option_u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "Option<String>"))]
         allowance: Option<Balance>,
         receiver_id: String,
         method_names: Vec<String>,
@@ -180,6 +188,7 @@ impl From<AccessKeyPermissionView> for AccessKeyPermission {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 pub struct AccessKeyView {
     pub nonce: Nonce,
@@ -203,6 +212,7 @@ pub type TrieProofPath = Vec<String>;
 
 /// Item of the state, key and value are serialized in base64 and proof for inclusion of given state item.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct StateItem {
     pub key: String,
@@ -211,6 +221,7 @@ pub struct StateItem {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct ViewStateResult {
     pub values: Vec<StateItem>,
@@ -218,6 +229,7 @@ pub struct ViewStateResult {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default,
 )]
@@ -234,6 +246,7 @@ pub struct QueryError {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct AccessKeyInfoView {
     pub public_key: PublicKey,
@@ -241,6 +254,7 @@ pub struct AccessKeyInfoView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct AccessKeyList {
     pub keys: Vec<AccessKeyInfoView>,
@@ -264,6 +278,7 @@ pub enum QueryResponseKind {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(tag = "request_type", rename_all = "snake_case")]
 pub enum QueryRequest {
@@ -276,6 +291,7 @@ pub enum QueryRequest {
     ViewState {
         account_id: AccountId,
         #[serde(rename = "prefix_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         prefix: StoreKey,
     },
     ViewAccessKey {
@@ -289,6 +305,7 @@ pub enum QueryRequest {
         account_id: AccountId,
         method_name: String,
         #[serde(rename = "args_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         args: FunctionArgs,
     },
 }
@@ -302,6 +319,7 @@ pub struct QueryResponse {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct StatusSyncInfo {
     pub latest_block_hash: CryptoHash,
@@ -316,6 +334,7 @@ pub struct StatusSyncInfo {
 
 // TODO: add more information to ValidatorInfo
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct ValidatorInfo {
     pub account_id: AccountId,
@@ -324,6 +343,7 @@ pub struct ValidatorInfo {
 
 // TODO: add more information to status.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct StatusResponse {
     /// Binary version.
@@ -356,6 +376,7 @@ impl From<Challenge> for ChallengeView {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockHeaderView {
     pub height: BlockHeight,
@@ -374,20 +395,25 @@ pub struct BlockHeaderView {
     /// Legacy json number. Should not be used.
     pub timestamp: u64,
     #[serde(with = "u64_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub timestamp_nanosec: u64,
     pub random_value: CryptoHash,
     pub validator_proposals: Vec<ValidatorStakeView>,
     pub chunk_mask: Vec<bool>,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub gas_price: Balance,
     pub block_ordinal: Option<NumBlocks>,
     /// TODO(2271): deprecated.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub rent_paid: Balance,
     /// TODO(2271): deprecated.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub validator_reward: Balance,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub total_supply: Balance,
     pub challenges_result: ChallengesResult,
     pub last_final_block: CryptoHash,
@@ -560,6 +586,7 @@ impl From<BlockHeaderView> for BlockHeader {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct BlockHeaderInnerLiteView {
     pub height: BlockHeight,
@@ -570,6 +597,7 @@ pub struct BlockHeaderInnerLiteView {
     /// Legacy json number. Should not be used.
     pub timestamp: u64,
     #[serde(with = "u64_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub timestamp_nanosec: u64,
     pub next_bp_hash: CryptoHash,
     pub block_merkle_root: CryptoHash,
@@ -630,6 +658,7 @@ impl From<BlockHeaderInnerLiteView> for BlockHeaderInnerLite {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChunkHeaderView {
     pub chunk_hash: CryptoHash,
@@ -645,11 +674,14 @@ pub struct ChunkHeaderView {
     pub gas_limit: Gas,
     /// TODO(2271): deprecated.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub rent_paid: Balance,
     /// TODO(2271): deprecated.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub validator_reward: Balance,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub balance_burnt: Balance,
     pub outgoing_receipts_root: CryptoHash,
     pub tx_root: CryptoHash,
@@ -713,7 +745,8 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockView {
     pub author: AccountId,
     pub header: BlockHeaderView,
@@ -730,6 +763,7 @@ impl BlockView {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ChunkView {
     pub author: AccountId,
@@ -758,6 +792,7 @@ impl ChunkView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub enum ActionView {
     CreateAccount,
@@ -769,14 +804,17 @@ pub enum ActionView {
         args: String,
         gas: Gas,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         deposit: Balance,
     },
     Transfer {
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         deposit: Balance,
     },
     Stake {
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         stake: Balance,
         public_key: PublicKey,
     },
@@ -793,6 +831,7 @@ pub enum ActionView {
     #[cfg(feature = "protocol_feature_chunk_only_producers")]
     StakeChunkOnly {
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         stake: Balance,
         public_key: PublicKey,
     },
@@ -870,6 +909,7 @@ impl TryFrom<ActionView> for Action {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone)]
 pub struct SignedTransactionView {
     pub signer_id: AccountId,
@@ -902,6 +942,7 @@ impl From<SignedTransaction> for SignedTransactionView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub enum FinalExecutionStatus {
     /// The execution has not yet started.
@@ -943,6 +984,7 @@ pub enum ServerError {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub enum ExecutionStatusView {
     /// The execution is pending or unknown.
@@ -986,15 +1028,18 @@ impl From<ExecutionStatus> for ExecutionStatusView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone, Eq, Debug)]
 pub struct CostGasUsed {
     pub cost_category: String,
     pub cost: String,
     #[serde(with = "u64_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub gas_used: Gas,
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone, Eq, Debug)]
 pub struct ExecutionMetadataView {
     version: u32,
@@ -1038,6 +1083,7 @@ impl From<ExecutionMetadata> for ExecutionMetadataView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ExecutionOutcomeView {
     /// Logs from this transaction or receipt.
@@ -1050,6 +1096,7 @@ pub struct ExecutionOutcomeView {
     /// This value doesn't always equal to the `gas_burnt` multiplied by the gas price, because
     /// the prepaid gas price might be lower than the actual gas price and it creates a deficit.
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub tokens_burnt: Balance,
     /// The id of the account on which the execution happens. For transaction this is signer_id,
     /// for receipt this is receiver_id.
@@ -1076,6 +1123,7 @@ impl From<ExecutionOutcome> for ExecutionOutcomeView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct ExecutionOutcomeWithIdView {
     pub proof: MerklePath,
@@ -1095,6 +1143,7 @@ impl From<ExecutionOutcomeWithIdAndProof> for ExecutionOutcomeWithIdView {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum FinalExecutionOutcomeViewEnum {
@@ -1104,6 +1153,7 @@ pub enum FinalExecutionOutcomeViewEnum {
 
 /// Final execution outcome of the transaction and all of subsequent the receipts.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct FinalExecutionOutcomeView {
     /// Execution status. Contains the result in case of successful execution.
@@ -1133,6 +1183,7 @@ impl fmt::Debug for FinalExecutionOutcomeView {
 /// Final execution outcome of the transaction and all of subsequent the receipts. Also includes
 /// the generated receipt.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct FinalExecutionOutcomeWithReceiptView {
     /// Final outcome view without receipts
@@ -1166,6 +1217,7 @@ pub mod validator_stake_view {
     pub use super::ValidatorStakeViewV1;
 
     #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+    #[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
     #[derive(
         BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq,
     )]
@@ -1202,6 +1254,7 @@ pub mod validator_stake_view {
 
     #[cfg(feature = "protocol_feature_chunk_only_producers")]
     #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+    #[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
     #[derive(
         BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq,
     )]
@@ -1209,6 +1262,7 @@ pub mod validator_stake_view {
         pub account_id: AccountId,
         pub public_key: PublicKey,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         pub stake: Balance,
         pub is_chunk_only: bool,
     }
@@ -1246,15 +1300,18 @@ pub mod validator_stake_view {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct ValidatorStakeViewV1 {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub stake: Balance,
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ReceiptView {
     pub predecessor_id: AccountId,
@@ -1265,6 +1322,7 @@ pub struct ReceiptView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct DataReceiverView {
     pub data_id: CryptoHash,
@@ -1272,12 +1330,14 @@ pub struct DataReceiverView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum ReceiptEnumView {
     Action {
         signer_id: AccountId,
         signer_public_key: PublicKey,
         #[serde(with = "u128_dec_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         gas_price: Balance,
         output_data_receivers: Vec<DataReceiverView>,
         input_data_ids: Vec<CryptoHash>,
@@ -1286,6 +1346,7 @@ pub enum ReceiptEnumView {
     Data {
         data_id: CryptoHash,
         #[serde(with = "option_base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "Option<String>"))]
         data: Option<Vec<u8>>,
     },
 }
@@ -1367,6 +1428,7 @@ impl TryFrom<ReceiptView> for Receipt {
 
 /// Information about this epoch validators and next epoch validators
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct EpochValidatorInfo {
     /// Validators for the current epoch
@@ -1388,6 +1450,7 @@ pub struct EpochValidatorInfo {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct ValidatorKickoutView {
     pub account_id: AccountId,
@@ -1395,12 +1458,14 @@ pub struct ValidatorKickoutView {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct CurrentEpochValidatorInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     pub is_slashed: bool,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub stake: Balance,
     pub shards: Vec<ShardId>,
     pub num_produced_blocks: NumBlocks,
@@ -1408,16 +1473,19 @@ pub struct CurrentEpochValidatorInfo {
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct NextEpochValidatorInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub stake: Balance,
     pub shards: Vec<ShardId>,
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct LightClientBlockView {
     pub prev_block_hash: CryptoHash,
@@ -1428,6 +1496,7 @@ pub struct LightClientBlockView {
     pub approvals_after_next: Vec<Option<Signature>>,
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct LightClientBlockLiteView {
     pub prev_block_hash: CryptoHash,
@@ -1445,9 +1514,11 @@ impl From<BlockHeader> for LightClientBlockLiteView {
     }
 }
 
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GasPriceView {
     #[serde(with = "u128_dec_format")]
+    #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
     pub gas_price: Balance,
 }
 
@@ -1455,6 +1526,7 @@ pub struct GasPriceView {
 ///
 /// [serializable view]: ./index.html
 /// [`StateChangesRequest`]: ../types/struct.StateChangesRequest.html
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequestView {
@@ -1473,6 +1545,7 @@ pub enum StateChangesRequestView {
     DataChanges {
         account_ids: Vec<AccountId>,
         #[serde(rename = "key_prefix_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         key_prefix: StoreKey,
     },
 }
@@ -1503,6 +1576,7 @@ impl From<StateChangesRequestView> for StateChangesRequest {
 ///
 /// [serializable view]: ./index.html
 /// [`StateChangeKind`]: ../types/struct.StateChangeKind.html
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum StateChangeKindView {
@@ -1530,7 +1604,8 @@ impl From<StateChangeKind> for StateChangeKindView {
 pub type StateChangesKindsView = Vec<StateChangeKindView>;
 
 /// See crate::types::StateChangeCause for details.
-#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum StateChangeCauseView {
     NotWritableToDisk,
@@ -1574,7 +1649,8 @@ impl From<StateChangeCause> for StateChangeCauseView {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case", tag = "type", content = "change")]
 pub enum StateChangeValueView {
     AccountUpdate {
@@ -1597,18 +1673,22 @@ pub enum StateChangeValueView {
     DataUpdate {
         account_id: AccountId,
         #[serde(rename = "key_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         key: StoreKey,
         #[serde(rename = "value_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         value: StoreValue,
     },
     DataDeletion {
         account_id: AccountId,
         #[serde(rename = "key_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         key: StoreKey,
     },
     ContractCodeUpdate {
         account_id: AccountId,
         #[serde(rename = "code_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
         code: Vec<u8>,
     },
     ContractCodeDeletion {
@@ -1616,6 +1696,21 @@ pub enum StateChangeValueView {
     },
 }
 
+impl StateChangeValueView {
+    pub fn affected_account_id(&self) -> &AccountId {
+        match self {
+            Self::AccountUpdate { account_id, .. }
+            | Self::AccountDeletion { account_id }
+            | Self::AccessKeyUpdate { account_id, .. }
+            | Self::AccessKeyDeletion { account_id, .. }
+            | Self::DataUpdate { account_id, .. }
+            | Self::DataDeletion { account_id, .. }
+            | Self::ContractCodeUpdate { account_id, .. }
+            | Self::ContractCodeDeletion { account_id } => account_id,
+        }
+    }
+}
+
 impl From<StateChangeValue> for StateChangeValueView {
     fn from(state_change: StateChangeValue) -> Self {
         match state_change {
@@ -1647,7 +1742,8 @@ impl From<StateChangeValue> for StateChangeValueView {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StateChangeWithCauseView {
     pub cause: StateChangeCauseView,
     #[serde(flatten)]
@@ -1662,3 +1758,58 @@ impl From<StateChangeWithCause> for StateChangeWithCauseView {
 }
 
 pub type StateChangesView = Vec<StateChangeWithCauseView>;
+
+/// Key of a single record of an account whose changes are reported, see
+/// [`StateChangesRequestView`].
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "key_type", rename_all = "snake_case")]
+pub enum StateKeyView {
+    Account {
+        account_id: AccountId,
+    },
+    AccessKey {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    ContractCode {
+        account_id: AccountId,
+    },
+    Data {
+        account_id: AccountId,
+        #[serde(rename = "key_base64", with = "base64_format")]
+        #[cfg_attr(feature = "schemars_feature", schemars(with = "String"))]
+        key: StoreKey,
+    },
+}
+
+impl From<StateKeyView> for TrieKey {
+    fn from(key: StateKeyView) -> Self {
+        match key {
+            StateKeyView::Account { account_id } => TrieKey::Account { account_id },
+            StateKeyView::AccessKey { account_id, public_key } => {
+                TrieKey::AccessKey { account_id, public_key }
+            }
+            StateKeyView::ContractCode { account_id } => TrieKey::ContractCode { account_id },
+            StateKeyView::Data { account_id, key } => {
+                TrieKey::ContractData { account_id, key: key.as_ref().to_vec() }
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "schemars_feature", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StateChangeInBlockView {
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    #[serde(flatten)]
+    pub change: StateChangeWithCauseView,
+}
+
+impl From<StateChangeInBlock> for StateChangeInBlockView {
+    fn from(state_change: StateChangeInBlock) -> Self {
+        let StateChangeInBlock { block_height, block_hash, change } = state_change;
+        Self { block_height, block_hash, change: change.into() }
+    }
+}
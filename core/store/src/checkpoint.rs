@@ -0,0 +1,297 @@
+//! Online backups of the node database based on RocksDB checkpoints.
+//!
+//! A checkpoint directory has the following layout:
+//! * `data/` — a RocksDB checkpoint of every column, which can be opened as a regular database;
+//! * `checkpoint.json` — [`CheckpointMetadata`] describing the copy.
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use near_primitives::block::Tip;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
+use near_primitives::version::{DbVersion, DB_VERSION};
+
+use crate::db::{DBError, RocksDB};
+use crate::migrations::get_store_version_from_store;
+use crate::{DBCol, Store, HEAD_KEY};
+
+/// Name of the directory inside a checkpoint which holds the database copy.
+pub const CHECKPOINT_DATA_DIR: &str = "data";
+/// Name of the file inside a checkpoint which holds [`CheckpointMetadata`].
+pub const CHECKPOINT_METADATA_FILE: &str = "checkpoint.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckpointMetadata {
+    /// Version of the database stored in the checkpoint.
+    pub db_version: DbVersion,
+    /// Height of the chain head at the moment the checkpoint was taken.
+    pub head_height: BlockHeight,
+    /// Hash of the chain head at the moment the checkpoint was taken.
+    pub head_hash: CryptoHash,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CheckpointError {
+    #[error("checkpoints are only supported by the RocksDB backend")]
+    UnsupportedBackend,
+    #[error("checkpoints of split hot and cold storage are not supported")]
+    SplitStorage,
+    #[error("{0} already exists")]
+    AlreadyExists(PathBuf),
+    #[error("{0} is not a subdirectory of the checkpoints directory")]
+    OutsideCheckpointsDir(PathBuf),
+    #[error("database has no head, nothing to back up")]
+    MissingHead,
+    #[error("checkpoint has DB version {found} which is newer than {supported} supported by this binary")]
+    UnsupportedVersion { found: DbVersion, supported: DbVersion },
+    #[error(
+        "checkpoint metadata expects DB version {expected}, but the database has version {found}"
+    )]
+    VersionMismatch { expected: DbVersion, found: DbVersion },
+    #[error("database error: {0}")]
+    DB(#[from] DBError),
+    #[error("IO error: {0}")]
+    IO(#[from] io::Error),
+    #[error("failed to (de)serialize checkpoint metadata: {0}")]
+    Metadata(#[from] serde_json::Error),
+}
+
+/// Creates a checkpoint of `store` in the `path` directory, which must not exist.
+///
+/// The store can keep being written to while the checkpoint is being taken. The head height
+/// and the DB version are read back from the copy, so the metadata always matches its content.
+pub fn create_checkpoint(
+    store: &Store,
+    path: &Path,
+) -> Result<CheckpointMetadata, CheckpointError> {
+    if store.cold_store().is_some() {
+        return Err(CheckpointError::SplitStorage);
+    }
+    let rocksdb = store.get_rocksdb().ok_or(CheckpointError::UnsupportedBackend)?;
+    if path.exists() {
+        return Err(CheckpointError::AlreadyExists(path.to_path_buf()));
+    }
+    fs::create_dir_all(path)?;
+    let data_path = path.join(CHECKPOINT_DATA_DIR);
+    rocksdb.create_checkpoint(&data_path)?;
+
+    let metadata = {
+        let checkpoint = Store::new(Arc::pin(RocksDB::new_read_only_all_columns(&data_path)?));
+        let head = checkpoint
+            .get_ser::<Tip>(DBCol::ColBlockMisc, HEAD_KEY)?
+            .ok_or(CheckpointError::MissingHead)?;
+        CheckpointMetadata {
+            db_version: get_store_version_from_store(&checkpoint),
+            head_height: head.height,
+            head_hash: head.last_block_hash,
+        }
+    };
+    fs::write(path.join(CHECKPOINT_METADATA_FILE), serde_json::to_vec_pretty(&metadata)?)?;
+    Ok(metadata)
+}
+
+/// Resolves the checkpoint `path` requested through the admin RPC to a subdirectory of
+/// `checkpoints_dir`. Relative paths are resolved against `checkpoints_dir`, absolute ones
+/// have to point inside of it. `..` is rejected, so the node never writes anywhere else.
+pub fn resolve_checkpoint_path(
+    checkpoints_dir: &Path,
+    path: &Path,
+) -> Result<PathBuf, CheckpointError> {
+    let relative =
+        if path.is_absolute() { path.strip_prefix(checkpoints_dir).ok() } else { Some(path) };
+    match relative {
+        Some(relative)
+            if relative.components().next().is_some()
+                && relative
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_))) =>
+        {
+            Ok(checkpoints_dir.join(relative))
+        }
+        _ => Err(CheckpointError::OutsideCheckpointsDir(path.to_path_buf())),
+    }
+}
+
+/// Reads the metadata of the checkpoint stored in the `path` directory.
+pub fn read_checkpoint_metadata(path: &Path) -> Result<CheckpointMetadata, CheckpointError> {
+    let metadata = fs::read(path.join(CHECKPOINT_METADATA_FILE))?;
+    Ok(serde_json::from_slice(&metadata)?)
+}
+
+/// Restores the checkpoint stored in `checkpoint_path` as the database in `store_path`, which
+/// must not exist.
+///
+/// `ColDbVersion` of the copy is checked against the metadata and against the versions this
+/// binary can migrate from before anything is written to `store_path`.
+pub fn restore_checkpoint(
+    checkpoint_path: &Path,
+    store_path: &Path,
+) -> Result<CheckpointMetadata, CheckpointError> {
+    let metadata = read_checkpoint_metadata(checkpoint_path)?;
+    let data_path = checkpoint_path.join(CHECKPOINT_DATA_DIR);
+    let db_version = RocksDB::get_version(&data_path)?;
+    if db_version != metadata.db_version {
+        return Err(CheckpointError::VersionMismatch {
+            expected: metadata.db_version,
+            found: db_version,
+        });
+    }
+    if db_version > DB_VERSION {
+        return Err(CheckpointError::UnsupportedVersion {
+            found: db_version,
+            supported: DB_VERSION,
+        });
+    }
+    if store_path.exists() {
+        return Err(CheckpointError::AlreadyExists(store_path.to_path_buf()));
+    }
+
+    fs::create_dir_all(store_path)?;
+    for entry in fs::read_dir(&data_path)? {
+        let source = entry?.path();
+        let target = store_path.join(source.file_name().expect("read_dir yields file names"));
+        // SST files are never modified, so they can be shared with the checkpoint. MANIFEST,
+        // WAL and the rest are written to by RocksDB and have to be copied.
+        let is_sst = source.extension().map_or(false, |extension| extension == "sst");
+        if !is_sst || fs::hard_link(&source, &target).is_err() {
+            fs::copy(&source, &target)?;
+        }
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::block::Tip;
+    use near_primitives::hash::hash;
+    use near_primitives::types::EpochId;
+    use near_primitives::version::DB_VERSION;
+
+    use std::path::Path;
+
+    use crate::checkpoint::{
+        create_checkpoint, read_checkpoint_metadata, resolve_checkpoint_path, restore_checkpoint,
+        CheckpointError, CHECKPOINT_DATA_DIR, CHECKPOINT_METADATA_FILE,
+    };
+    use crate::migrations::{get_store_version, set_store_version};
+    use crate::{create_split_store, create_store, DBCol, HEAD_KEY};
+
+    fn set_head(store: &crate::Store, height: u64) {
+        let tip = Tip {
+            height,
+            last_block_hash: hash(&height.to_le_bytes()),
+            prev_block_hash: hash(&(height - 1).to_le_bytes()),
+            epoch_id: EpochId::default(),
+            next_epoch_id: EpochId::default(),
+        };
+        let mut store_update = store.store_update();
+        store_update.set_ser(DBCol::ColBlockMisc, HEAD_KEY, &tip).unwrap();
+        store_update.commit().unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_and_restore() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_checkpoint").tempdir().unwrap();
+        let store = create_store(&tmp_dir.path().join("db"));
+        set_store_version(&store, DB_VERSION);
+        set_head(&store, 10);
+        let mut store_update = store.store_update();
+        store_update.set(DBCol::ColBlock, &[1], &[1]);
+        store_update.commit().unwrap();
+
+        let checkpoint_path = tmp_dir.path().join("checkpoint");
+        let metadata = create_checkpoint(&store, &checkpoint_path).unwrap();
+        assert_eq!(metadata.head_height, 10);
+        assert_eq!(metadata.db_version, DB_VERSION);
+        assert_eq!(read_checkpoint_metadata(&checkpoint_path).unwrap(), metadata);
+        assert!(matches!(
+            create_checkpoint(&store, &checkpoint_path),
+            Err(CheckpointError::AlreadyExists(_))
+        ));
+
+        // Writes after the checkpoint must not end up in it.
+        set_head(&store, 11);
+        let mut store_update = store.store_update();
+        store_update.set(DBCol::ColBlock, &[2], &[2]);
+        store_update.commit().unwrap();
+
+        let restored_path = tmp_dir.path().join("restored");
+        assert_eq!(restore_checkpoint(&checkpoint_path, &restored_path).unwrap(), metadata);
+        assert_eq!(get_store_version(&restored_path), DB_VERSION);
+        let restored = create_store(&restored_path);
+        assert_eq!(restored.get(DBCol::ColBlock, &[1]).unwrap(), Some(vec![1]));
+        assert_eq!(restored.get(DBCol::ColBlock, &[2]).unwrap(), None);
+        let head = restored.get_ser::<Tip>(DBCol::ColBlockMisc, HEAD_KEY).unwrap().unwrap();
+        assert_eq!(head.height, 10);
+    }
+
+    #[test]
+    fn test_checkpoint_of_split_store() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_checkpoint_split").tempdir().unwrap();
+        let hot = create_store(&tmp_dir.path().join("hot"));
+        let cold = create_store(&tmp_dir.path().join("cold"));
+        let split = create_split_store(&hot, &cold);
+        set_head(&split, 10);
+        let checkpoint_path = tmp_dir.path().join("checkpoint");
+        assert!(matches!(
+            create_checkpoint(&split, &checkpoint_path),
+            Err(CheckpointError::SplitStorage)
+        ));
+        assert!(!checkpoint_path.exists());
+    }
+
+    #[test]
+    fn test_restore_checks_db_version() {
+        let tmp_dir =
+            tempfile::Builder::new().prefix("_test_checkpoint_version").tempdir().unwrap();
+        let checkpoint_path = tmp_dir.path().join("checkpoint");
+        {
+            let store = create_store(&tmp_dir.path().join("db"));
+            set_store_version(&store, DB_VERSION);
+            set_head(&store, 10);
+            create_checkpoint(&store, &checkpoint_path).unwrap();
+        }
+        {
+            let checkpoint = create_store(&checkpoint_path.join(CHECKPOINT_DATA_DIR));
+            set_store_version(&checkpoint, DB_VERSION + 1);
+        }
+        let restored_path = tmp_dir.path().join("restored");
+        assert!(matches!(
+            restore_checkpoint(&checkpoint_path, &restored_path),
+            Err(CheckpointError::VersionMismatch { .. })
+        ));
+
+        let mut metadata = read_checkpoint_metadata(&checkpoint_path).unwrap();
+        metadata.db_version = DB_VERSION + 1;
+        std::fs::write(
+            checkpoint_path.join(CHECKPOINT_METADATA_FILE),
+            serde_json::to_vec(&metadata).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            restore_checkpoint(&checkpoint_path, &restored_path),
+            Err(CheckpointError::UnsupportedVersion { .. })
+        ));
+        assert!(!restored_path.exists());
+    }
+
+    #[test]
+    fn test_resolve_checkpoint_path() {
+        let dir = Path::new("/data/checkpoints");
+        let resolve = |path: &str| resolve_checkpoint_path(dir, Path::new(path)).ok();
+        assert_eq!(resolve("daily"), Some(dir.join("daily")));
+        assert_eq!(resolve("2021/11/30"), Some(dir.join("2021/11/30")));
+        assert_eq!(resolve("/data/checkpoints/daily"), Some(dir.join("daily")));
+        assert_eq!(resolve(""), None);
+        assert_eq!(resolve("/data/checkpoints"), None);
+        assert_eq!(resolve("../daily"), None);
+        assert_eq!(resolve("daily/../../daily"), None);
+        assert_eq!(resolve("./daily"), None);
+        assert_eq!(resolve("/data/checkpoints/../daily"), None);
+        assert_eq!(resolve("/home/near/.near/data"), None);
+    }
+}
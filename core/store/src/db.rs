@@ -3,14 +3,16 @@ use std::cmp;
 use std::collections::HashMap;
 use std::io;
 use std::marker::PhantomPinned;
-use std::sync::RwLock;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use rocksdb::{
     BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, Direction, Env, IteratorMode,
-    Options, ReadOptions, WriteBatch, DB,
+    Options, ReadOptions, Snapshot, WriteBatch, DB,
 };
-use strum::EnumIter;
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, EnumString};
 use tracing::warn;
 
 use near_primitives::version::DbVersion;
@@ -21,10 +23,17 @@ use std::path::Path;
 use std::sync::atomic::Ordering;
 
 pub(crate) mod refcount;
+#[cfg(feature = "sled_backend")]
+pub(crate) mod sled_db;
+pub(crate) mod split_db;
 pub(crate) mod v6_to_v7;
 
+#[cfg(feature = "sled_backend")]
+pub use sled_db::SledDB;
+pub use split_db::SplitDB;
+
 #[derive(Debug, Clone, PartialEq)]
-pub struct DBError(rocksdb::Error);
+pub struct DBError(String);
 
 impl std::fmt::Display for DBError {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
@@ -36,7 +45,7 @@ impl std::error::Error for DBError {}
 
 impl From<rocksdb::Error> for DBError {
     fn from(err: rocksdb::Error) -> Self {
-        DBError(err)
+        DBError(err.into_string())
     }
 }
 
@@ -49,7 +58,20 @@ impl Into<io::Error> for DBError {
 /// This enum holds the information about the columns that we use within the RocksDB storage.
 /// You can think about our storage as 2-dimensional table (with key and column as indexes/coordinates).
 // TODO(mm-near): add info about the RC in the columns.
-#[derive(PartialEq, Debug, Copy, Clone, EnumIter, BorshDeserialize, BorshSerialize, Hash, Eq)]
+#[derive(
+    PartialEq,
+    Debug,
+    Copy,
+    Clone,
+    EnumIter,
+    EnumString,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Hash,
+    Eq,
+)]
 pub enum DBCol {
     /// Column to indicate which version of database this is.
     /// - *Rows*: single row [VERSION_KEY]
@@ -199,10 +221,22 @@ pub enum DBCol {
     ColHeaderHashesByHeight = 48,
     /// State changes made by a chunk, used for splitting states
     ColStateChangesForSplitStates = 49,
+    /// Latest values of the state, see `trie::flat_state`.
+    /// - *Rows*: shard_uid + trie key, or shard_uid for the root of the state the values belong to
+    /// - *Column type*: value, or StateRoot
+    ColFlatState = 50,
+    /// Changes reverting the flat state to the previous state.
+    /// - *Rows*: shard_uid + state root
+    /// - *Column type*: FlatStateDelta
+    ColFlatStateDeltas = 51,
+    /// Blocks which changed a key reported in `ColStateChanges`, to look up the history of a key.
+    /// - *Rows*: trie key + block height (big-endian) + block hash
+    /// - *Column type*: empty, the changes are in `ColStateChanges`
+    ColStateChangesHistory = 52,
 }
 
 // Do not move this line from enum DBCol
-pub const NUM_COLS: usize = 50;
+pub const NUM_COLS: usize = 53;
 
 impl std::fmt::Display for DBCol {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
@@ -259,6 +293,9 @@ impl std::fmt::Display for DBCol {
             Self::ColStateChangesForSplitStates => {
                 "state changes indexed by block hash and shard id"
             }
+            Self::ColFlatState => "flat state",
+            Self::ColFlatStateDeltas => "flat state deltas",
+            Self::ColStateChangesHistory => "blocks which changed a key",
         };
         write!(formatter, "{}", desc)
     }
@@ -268,6 +305,11 @@ impl DBCol {
     pub fn is_rc(&self) -> bool {
         IS_COL_RC[*self as usize]
     }
+
+    /// Whether GC of an archival node with split storage moves the column to the cold database.
+    pub fn is_cold(&self) -> bool {
+        IS_COL_COLD[*self as usize]
+    }
 }
 
 // List of columns for which GC should be implemented
@@ -292,6 +334,8 @@ pub static SHOULD_COL_GC: [bool; NUM_COLS] = {
     col_gc[DBCol::ColEpochValidatorInfo as usize] = false; // https://github.com/nearprotocol/nearcore/pull/2952
     col_gc[DBCol::ColEpochStart as usize] = false; // https://github.com/nearprotocol/nearcore/pull/2952
     col_gc[DBCol::ColCachedContractCode as usize] = false;
+    col_gc[DBCol::ColFlatState as usize] = false; // follows the head, only deltas are GCed
+    col_gc[DBCol::ColFlatStateDeltas as usize] = false; // removed along with ColTrieChanges
     col_gc
 };
 
@@ -317,17 +361,66 @@ pub static IS_COL_RC: [bool; NUM_COLS] = {
     col_rc
 };
 
+// List of columns which are moved to the cold database of a split store instead of being GCed
+
+pub static IS_COL_COLD: [bool; NUM_COLS] = {
+    let mut col_cold = [false; NUM_COLS];
+    col_cold[DBCol::ColBlock as usize] = true;
+    col_cold[DBCol::ColBlockExtra as usize] = true;
+    col_cold[DBCol::ColBlockInfo as usize] = true;
+    col_cold[DBCol::ColNextBlockHashes as usize] = true;
+    col_cold[DBCol::ColChunks as usize] = true;
+    col_cold[DBCol::ColPartialChunks as usize] = true;
+    col_cold[DBCol::ColChunkExtra as usize] = true;
+    col_cold[DBCol::ColTransactions as usize] = true;
+    col_cold[DBCol::ColReceipts as usize] = true;
+    col_cold[DBCol::ColReceiptIdToShardId as usize] = true;
+    col_cold[DBCol::ColIncomingReceipts as usize] = true;
+    col_cold[DBCol::ColOutgoingReceipts as usize] = true;
+    col_cold[DBCol::ColTransactionResult as usize] = true;
+    col_cold[DBCol::ColOutcomeIds as usize] = true;
+    col_cold[DBCol::ColStateChanges as usize] = true;
+    col_cold[DBCol::ColStateChangesHistory as usize] = true;
+    col_cold[DBCol::ColState as usize] = true;
+    // Height indexes, without them blocks and chunks in the cold database can't be found by height.
+    col_cold[DBCol::ColBlockPerHeight as usize] = true;
+    col_cold[DBCol::ColHeaderHashesByHeight as usize] = true;
+    col_cold[DBCol::ColChunkHashesByHeight as usize] = true;
+    col_cold[DBCol::ColChunkPerHeightShard as usize] = true;
+    col_cold
+};
+
+/// Storage engine used to persist the columns of a [`Store`](crate::Store).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DBBackend {
+    /// RocksDB, the default production backend.
+    RocksDB,
+    /// Pure-Rust embedded store, available when `near-store` is built with the
+    /// `sled_backend` feature.
+    Sled,
+}
+
+impl Default for DBBackend {
+    fn default() -> Self {
+        DBBackend::RocksDB
+    }
+}
+
 pub const HEAD_KEY: &[u8; 4] = b"HEAD";
 pub const TAIL_KEY: &[u8; 4] = b"TAIL";
 pub const CHUNK_TAIL_KEY: &[u8; 10] = b"CHUNK_TAIL";
 pub const FORK_TAIL_KEY: &[u8; 9] = b"FORK_TAIL";
+pub const FLAT_STATE_DELTAS_TAIL_KEY: &[u8; 22] = b"FLAT_STATE_DELTAS_TAIL";
 pub const HEADER_HEAD_KEY: &[u8; 11] = b"HEADER_HEAD";
 pub const FINAL_HEAD_KEY: &[u8; 10] = b"FINAL_HEAD";
 pub const LATEST_KNOWN_KEY: &[u8; 12] = b"LATEST_KNOWN";
 pub const LARGEST_TARGET_HEIGHT_KEY: &[u8; 21] = b"LARGEST_TARGET_HEIGHT";
 pub const VERSION_KEY: &[u8; 7] = b"VERSION";
+pub const MIGRATION_CHECKPOINT_KEY: &[u8; 20] = b"MIGRATION_CHECKPOINT";
 pub const GENESIS_JSON_HASH_KEY: &[u8; 17] = b"GENESIS_JSON_HASH";
 pub const GENESIS_STATE_ROOTS_KEY: &[u8; 19] = b"GENESIS_STATE_ROOTS";
+pub const STORE_VALIDATOR_CURSOR_KEY: &[u8; 22] = b"STORE_VALIDATOR_CURSOR";
 
 pub struct DBTransaction {
     pub ops: Vec<DBOp>,
@@ -529,6 +622,13 @@ pub trait Database: Sync + Send {
         &'a self,
         column: DBCol,
     ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+    /// Iterates over the records of `col` with keys not less than `from` in key order,
+    /// without applying the refcount logic.
+    fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
     fn iter_prefix<'a>(
         &'a self,
         col: DBCol,
@@ -538,6 +638,9 @@ pub trait Database: Sync + Send {
     fn as_rocksdb(&self) -> Option<&RocksDB> {
         None
     }
+    fn as_split_db(&self) -> Option<&SplitDB> {
+        None
+    }
 }
 
 impl Database for RocksDB {
@@ -559,6 +662,23 @@ impl Database for RocksDB {
         }
     }
 
+    fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        let read_options = rocksdb_read_options();
+        unsafe {
+            let cf_handle = &*self.cfs[col as usize];
+            let iterator = self.db.iterator_cf_opt(
+                cf_handle,
+                read_options,
+                IteratorMode::From(from, Direction::Forward),
+            );
+            Box::new(iterator)
+        }
+    }
+
     fn iter<'a>(&'a self, col: DBCol) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
         let read_options = rocksdb_read_options();
         unsafe {
@@ -658,6 +778,21 @@ impl Database for TestDB {
         Box::new(iterator)
     }
 
+    fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        // Records of a column are kept in a hash map, so they have to be sorted by key.
+        let mut records: Vec<_> = self.db.read().unwrap()[col as usize]
+            .iter()
+            .filter(|(key, _)| key.as_slice() >= from)
+            .map(|(k, v)| (k.clone().into_boxed_slice(), v.clone().into_boxed_slice()))
+            .collect();
+        records.sort();
+        Box::new(records.into_iter())
+    }
+
     fn iter_prefix<'a>(
         &'a self,
         col: DBCol,
@@ -695,6 +830,117 @@ impl Database for TestDB {
     }
 }
 
+/// Read-only view of a database pinned to its state at the moment of creation.
+///
+/// For RocksDB the view is backed by a RocksDB snapshot, so reads spanning several columns
+/// never observe a partially written transaction. Other backends don't support snapshots and
+/// the view reads the latest data of the underlying database. Writes always fail.
+pub struct DBSnapshot {
+    // Borrows from `db`, so it has to be declared (and therefore dropped) first.
+    snapshot: Option<Snapshot<'static>>,
+    db: Pin<Arc<dyn Database>>,
+}
+
+impl DBSnapshot {
+    pub(crate) fn new(db: Pin<Arc<dyn Database>>) -> Self {
+        // Safety: the snapshot borrows the RocksDB instance which is pinned behind `db`, and
+        // `db` outlives the snapshot since the snapshot is dropped first.
+        let snapshot = db.as_rocksdb().map(|rocksdb| unsafe {
+            std::mem::transmute::<Snapshot<'_>, Snapshot<'static>>(rocksdb.db.snapshot())
+        });
+        DBSnapshot { snapshot, db }
+    }
+
+    fn rocksdb_snapshot(&self) -> Option<(&Snapshot<'static>, &RocksDB)> {
+        match (&self.snapshot, self.db.as_rocksdb()) {
+            (Some(snapshot), Some(rocksdb)) => Some((snapshot, rocksdb)),
+            _ => None,
+        }
+    }
+}
+
+impl Database for DBSnapshot {
+    fn get(&self, col: DBCol, key: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
+        match self.rocksdb_snapshot() {
+            Some((snapshot, rocksdb)) => {
+                let cf_handle = unsafe { &*rocksdb.cfs[col as usize] };
+                let result = snapshot.get_cf_opt(cf_handle, key, rocksdb_read_options())?;
+                Ok(RocksDB::get_with_rc_logic(col, result))
+            }
+            None => self.db.get(col, key),
+        }
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        match self.rocksdb_snapshot() {
+            Some(_) => RocksDB::iter_with_rc_logic(col, self.iter_without_rc_logic(col)),
+            None => self.db.iter(col),
+        }
+    }
+
+    fn iter_without_rc_logic<'a>(
+        &'a self,
+        col: DBCol,
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        match self.rocksdb_snapshot() {
+            Some((snapshot, rocksdb)) => {
+                let cf_handle = unsafe { &*rocksdb.cfs[col as usize] };
+                Box::new(snapshot.iterator_cf_opt(
+                    cf_handle,
+                    rocksdb_read_options(),
+                    IteratorMode::Start,
+                ))
+            }
+            None => self.db.iter_without_rc_logic(col),
+        }
+    }
+
+    fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        match self.rocksdb_snapshot() {
+            Some((snapshot, rocksdb)) => {
+                let cf_handle = unsafe { &*rocksdb.cfs[col as usize] };
+                Box::new(snapshot.iterator_cf_opt(
+                    cf_handle,
+                    rocksdb_read_options(),
+                    IteratorMode::From(from, Direction::Forward),
+                ))
+            }
+            None => self.db.iter_without_rc_logic_from(col, from),
+        }
+    }
+
+    fn iter_prefix<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        match self.rocksdb_snapshot() {
+            Some((snapshot, rocksdb)) => {
+                let mut read_options = rocksdb_read_options();
+                read_options.set_prefix_same_as_start(true);
+                let cf_handle = unsafe { &*rocksdb.cfs[col as usize] };
+                let iterator = snapshot
+                    .iterator_cf_opt(
+                        cf_handle,
+                        read_options,
+                        IteratorMode::From(key_prefix, Direction::Forward),
+                    )
+                    .take_while(move |(key, _value)| key.starts_with(key_prefix));
+                RocksDB::iter_with_rc_logic(col, iterator)
+            }
+            None => self.db.iter_prefix(col, key_prefix),
+        }
+    }
+
+    fn write(&self, _transaction: DBTransaction) -> Result<(), DBError> {
+        Err(DBError("cannot write to a read-only database snapshot".to_string()))
+    }
+}
+
 /// DB level options
 fn rocksdb_options() -> Options {
     let mut opts = Options::default();
@@ -783,10 +1029,57 @@ impl RocksDB {
         RocksDBOptions::default().read_only(path)
     }
 
+    /// Opens a read only database with all the columns available.
+    pub(crate) fn new_read_only_all_columns<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, DBError> {
+        use strum::IntoEnumIterator;
+        let cf_names = DBCol::iter().map(|col| format!("col{}", col as usize)).collect();
+        RocksDBOptions::default().cf_names(cf_names).read_only(path)
+    }
+
     pub fn new<P: AsRef<std::path::Path>>(path: P) -> Result<Self, DBError> {
         RocksDBOptions::default().read_write(path)
     }
 
+    /// Creates a consistent point-in-time copy of every column at `path`, which must not exist.
+    ///
+    /// Memtables are flushed first and SST files are hard-linked when `path` is on the same
+    /// filesystem, so this is cheap enough to do while the node keeps writing.
+    pub fn create_checkpoint<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), DBError> {
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.db)?;
+        checkpoint.create_checkpoint(path)?;
+        Ok(())
+    }
+
+    /// Returns RocksDB estimates of the size of the column.
+    pub fn column_stats(&self, col: DBCol) -> Result<ColumnStats, DBError> {
+        let cf_handle = unsafe { &*self.cfs[col as usize] };
+        let property = |name: &str| -> Result<u64, DBError> {
+            Ok(self.db.property_int_value_cf(cf_handle, name)?.unwrap_or(0))
+        };
+        Ok(ColumnStats {
+            total_sst_files_size: property("rocksdb.total-sst-files-size")?,
+            live_sst_files_size: property("rocksdb.live-sst-files-size")?,
+            estimate_live_data_size: property("rocksdb.estimate-live-data-size")?,
+            estimate_num_keys: property("rocksdb.estimate-num-keys")?,
+            memtables_size: property("rocksdb.cur-size-all-mem-tables")?,
+        })
+    }
+
+    /// Compacts the whole column, which drops deleted keys and, for refcounted columns, keys
+    /// with zero refcount. Blocks until the compaction is finished, the database stays
+    /// available for reads and writes meanwhile.
+    ///
+    /// Hangs forever with the `single_thread_rocksdb` feature.
+    pub fn compact(&self, col: DBCol) {
+        self.db.compact_range_cf(
+            unsafe { &*self.cfs[col as usize] },
+            Option::<&[u8]>::None,
+            Option::<&[u8]>::None,
+        );
+    }
+
     /// Checks if there is enough memory left to perform a write. Not having enough memory left can
     /// lead to difficult to recover from state, thus a PreWriteCheckErr is pretty much
     /// unrecoverable in most cases.
@@ -811,6 +1104,22 @@ impl RocksDB {
     }
 }
 
+/// Size of a column as estimated by RocksDB, see [`RocksDB::column_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnStats {
+    /// Size of all SST files of the column, including the ones which are not used anymore but
+    /// are kept by snapshots, iterators or an ongoing compaction.
+    pub total_sst_files_size: u64,
+    /// Size of the SST files of the latest version of the column.
+    pub live_sst_files_size: u64,
+    /// Size of the data which would be left after a full compaction.
+    pub estimate_live_data_size: u64,
+    /// Number of keys, which is an overestimate for columns with deletes or merges.
+    pub estimate_num_keys: u64,
+    /// Size of the data which is not flushed to SST files yet.
+    pub memtables_size: u64,
+}
+
 fn available_space<P: AsRef<Path> + std::fmt::Debug>(
     path: P,
 ) -> std::io::Result<bytesize::ByteSize> {
@@ -857,20 +1166,13 @@ impl TestDB {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::db::DBCol::ColState;
     use crate::db::{rocksdb_read_options, DBError, Database, RocksDB};
     use crate::{create_store, DBCol};
 
     impl RocksDB {
-        #[cfg(not(feature = "single_thread_rocksdb"))]
-        fn compact(&self, col: DBCol) {
-            self.db.compact_range_cf(
-                unsafe { &*self.cfs[col as usize] },
-                Option::<&[u8]>::None,
-                Option::<&[u8]>::None,
-            );
-        }
-
         fn get_no_empty_filtering(
             &self,
             col: DBCol,
@@ -911,6 +1213,63 @@ mod tests {
         assert_eq!(store.get(ColState, &[1]).unwrap(), None);
     }
 
+    #[test]
+    fn test_iter_without_rc_logic_from() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_iter_from").tempdir().unwrap();
+        #[allow(unused_mut)]
+        let mut stores = vec![create_store(tmp_dir.path()), crate::test_utils::create_test_store()];
+        #[cfg(feature = "sled_backend")]
+        let sled_dir = tempfile::Builder::new().prefix("_test_iter_from_sled").tempdir().unwrap();
+        #[cfg(feature = "sled_backend")]
+        stores.push(crate::create_store_with_backend(sled_dir.path(), crate::DBBackend::Sled));
+        for store in stores {
+            let mut store_update = store.store_update();
+            for key in [[3], [1], [4], [2]] {
+                store_update.set(DBCol::ColBlockMisc, &key, &key);
+            }
+            store_update.commit().unwrap();
+            for store in [Arc::new(store.snapshot()), store] {
+                let keys: Vec<_> = store
+                    .iter_without_rc_logic_from(DBCol::ColBlockMisc, &[2])
+                    .map(|(key, _)| key.to_vec())
+                    .collect();
+                assert_eq!(keys, vec![vec![2], vec![3], vec![4]]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_isolation() {
+        let tmp_dir =
+            tempfile::Builder::new().prefix("_test_snapshot_isolation").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        {
+            let mut store_update = store.store_update();
+            store_update.set(DBCol::ColBlockMisc, &[1], &[1]);
+            store_update.update_refcount(ColState, &[1], &[1], 1);
+            store_update.commit().unwrap();
+        }
+        let snapshot = store.snapshot();
+        {
+            let mut store_update = store.store_update();
+            store_update.set(DBCol::ColBlockMisc, &[1], &[2]);
+            store_update.set(DBCol::ColBlockMisc, &[2], &[2]);
+            store_update.update_refcount(ColState, &[1], &[1], -1);
+            store_update.commit().unwrap();
+        }
+        assert_eq!(store.get(DBCol::ColBlockMisc, &[1]).unwrap(), Some(vec![2]));
+        assert_eq!(store.get(ColState, &[1]).unwrap(), None);
+        assert_eq!(snapshot.get(DBCol::ColBlockMisc, &[1]).unwrap(), Some(vec![1]));
+        assert_eq!(snapshot.get(DBCol::ColBlockMisc, &[2]).unwrap(), None);
+        assert_eq!(snapshot.get(ColState, &[1]).unwrap(), Some(vec![1]));
+        assert_eq!(snapshot.iter(DBCol::ColBlockMisc).count(), 1);
+        assert_eq!(snapshot.iter_prefix(ColState, &[1]).count(), 1);
+
+        let mut store_update = snapshot.store_update();
+        store_update.set(DBCol::ColBlockMisc, &[3], &[3]);
+        assert!(store_update.commit().is_err());
+    }
+
     #[test]
     fn rocksdb_merge_sanity() {
         let tmp_dir = tempfile::Builder::new().prefix("_test_snapshot_sanity").tempdir().unwrap();
@@ -964,4 +1323,103 @@ mod tests {
             assert_eq!(store.get(ColState, &[1]).unwrap(), None);
         }
     }
+
+    #[test]
+    fn test_refcount_and_column_stats() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_column_stats").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let rocksdb = store.get_rocksdb().unwrap();
+        {
+            let mut store_update = store.store_update();
+            store_update.update_refcount(ColState, &[1], &[1], 1);
+            store_update.update_refcount(ColState, &[2], &[2], 2);
+            store_update.update_refcount(ColState, &[3], &[3], 1);
+            store_update.commit().unwrap();
+        }
+        {
+            let mut store_update = store.store_update();
+            store_update.update_refcount(ColState, &[2], &[2], -1);
+            store_update.update_refcount(ColState, &[3], &[3], -1);
+            store_update.commit().unwrap();
+        }
+        let stats = store.refcount_stats(ColState);
+        assert_eq!((stats.live_keys, stats.live_bytes), (2, 2 * (1 + 1 + 8)));
+        assert_eq!((stats.garbage_keys, stats.garbage_bytes), (1, 1));
+        assert_eq!(stats.negative_keys, 0);
+        assert!((stats.garbage_ratio() - 1.0 / 3.0).abs() < 1e-9);
+
+        // Nothing is flushed to SST files yet.
+        let column_stats = rocksdb.column_stats(ColState).unwrap();
+        assert_eq!(column_stats.total_sst_files_size, 0);
+        assert!(column_stats.memtables_size > 0);
+
+        #[cfg(not(feature = "single_thread_rocksdb"))]
+        {
+            // As in `rocksdb_merge_sanity`, the merged empty value is dropped by the second
+            // compaction.
+            rocksdb.compact(ColState);
+            rocksdb.compact(ColState);
+            let stats = store.refcount_stats(ColState);
+            assert_eq!((stats.live_keys, stats.garbage_keys), (2, 0));
+            assert!(rocksdb.column_stats(ColState).unwrap().live_sst_files_size > 0);
+        }
+    }
+
+    #[cfg(feature = "sled_backend")]
+    #[test]
+    fn sled_refcount_sanity() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_sled_refcount").tempdir().unwrap();
+        let store = crate::create_store_with_backend(tmp_dir.path(), crate::DBBackend::Sled);
+        {
+            let mut store_update = store.store_update();
+            store_update.update_refcount(ColState, &[1], &[1], 1);
+            store_update.update_refcount(ColState, &[1], &[1], 1);
+            store_update.update_refcount(ColState, &[2], &[2], 1);
+            store_update.commit().unwrap();
+        }
+        assert_eq!(store.get(ColState, &[1]).unwrap(), Some(vec![1]));
+        assert_eq!(
+            store.iter_without_rc_logic(ColState).next().map(|(_, value)| value.to_vec()),
+            Some(vec![1, 2, 0, 0, 0, 0, 0, 0, 0])
+        );
+        {
+            let mut store_update = store.store_update();
+            store_update.update_refcount(ColState, &[1], &[1], -2);
+            store_update.commit().unwrap();
+        }
+        // Refcount goes to 0 -> the record is removed right away
+        assert_eq!(store.get(ColState, &[1]).unwrap(), None);
+        assert_eq!(store.iter(ColState).count(), 1);
+        {
+            let mut store_update = store.store_update();
+            store_update.delete_all(ColState);
+            store_update.commit().unwrap();
+        }
+        assert_eq!(store.get(ColState, &[2]).unwrap(), None);
+    }
+
+    #[cfg(feature = "sled_backend")]
+    #[test]
+    fn sled_delete_all_in_batches() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_sled_delete_all").tempdir().unwrap();
+        let store = crate::create_store_with_backend(tmp_dir.path(), crate::DBBackend::Sled);
+        {
+            let mut store_update = store.store_update();
+            for i in 0..25_000u32 {
+                store_update.set(DBCol::ColBlockMisc, &i.to_le_bytes(), &[1]);
+            }
+            store_update.commit().unwrap();
+        }
+        // Operations before and after `DeleteAll` keep their order.
+        {
+            let mut store_update = store.store_update();
+            store_update.set(DBCol::ColBlockMisc, b"before", &[1]);
+            store_update.delete_all(DBCol::ColBlockMisc);
+            store_update.set(DBCol::ColBlockMisc, b"after", &[2]);
+            store_update.commit().unwrap();
+        }
+        assert_eq!(store.get(DBCol::ColBlockMisc, b"before").unwrap(), None);
+        assert_eq!(store.get(DBCol::ColBlockMisc, b"after").unwrap(), Some(vec![2]));
+        assert_eq!(store.iter(DBCol::ColBlockMisc).count(), 1);
+    }
 }
@@ -66,6 +66,56 @@ pub fn decode_value_with_rc(bytes: &[u8]) -> (Option<&[u8]>, i64) {
     }
 }
 
+/// Number and size of the records of a refcounted column, split by their refcount.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefcountStats {
+    /// Records with positive refcount.
+    pub live_keys: u64,
+    /// Size of keys and values of the records with positive refcount.
+    pub live_bytes: u64,
+    /// Records with zero refcount, which stay on disk until their SST file is compacted.
+    pub garbage_keys: u64,
+    /// Size of keys and values of the records with zero refcount. Their values hold nothing
+    /// but the refcount, so this is mostly the size of the keys.
+    pub garbage_bytes: u64,
+    /// Records with negative refcount. Any of them means there is a bug in gc.
+    pub negative_keys: u64,
+}
+
+impl RefcountStats {
+    /// Collects stats of records read with `iter_without_rc_logic`.
+    pub fn from_records<I>(records: I) -> Self
+    where
+        I: Iterator<Item = (Box<[u8]>, Box<[u8]>)>,
+    {
+        let mut stats = RefcountStats::default();
+        for (key, value) in records {
+            match decode_value_with_rc(&value).1.cmp(&0) {
+                Ordering::Greater => {
+                    stats.live_keys += 1;
+                    stats.live_bytes += (key.len() + value.len()) as u64;
+                }
+                Ordering::Equal => {
+                    stats.garbage_keys += 1;
+                    stats.garbage_bytes += (key.len() + value.len()) as u64;
+                }
+                Ordering::Less => stats.negative_keys += 1,
+            }
+        }
+        stats
+    }
+
+    /// Share of the records with zero refcount among all records of the column.
+    pub fn garbage_ratio(&self) -> f64 {
+        let total = self.live_keys + self.garbage_keys + self.negative_keys;
+        if total == 0 {
+            0.0
+        } else {
+            self.garbage_keys as f64 / total as f64
+        }
+    }
+}
+
 pub(crate) fn encode_value_with_rc(data: &[u8], rc: i64) -> Vec<u8> {
     if rc == 0 {
         return vec![];
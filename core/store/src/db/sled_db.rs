@@ -0,0 +1,169 @@
+use sled::transaction::{ConflictableTransactionError, TransactionError};
+use sled::Transactional;
+use strum::IntoEnumIterator;
+
+use crate::db::refcount::merge_refcounted_records;
+use crate::db::{DBCol, DBError, DBOp, DBTransaction, Database, RocksDB};
+
+/// Pure-Rust persistent implementation of [`Database`] on top of `sled`.
+///
+/// Every [`DBCol`] is stored in its own sled tree named the same way as RocksDB column
+/// families (`col{N}`). Sled has no merge operands that can be part of a multi-tree
+/// transaction, so refcounted columns are merged eagerly inside the transaction using the
+/// same [`merge_refcounted_records`] logic as RocksDB's merge operator. Records whose
+/// refcount drops to zero are removed right away instead of waiting for compaction.
+/// `DeleteAll` operations split the transaction: the column is cleared in batches between
+/// the transactional writes of the operations before and after it.
+pub struct SledDB {
+    db: sled::Db,
+    trees: Vec<sled::Tree>,
+}
+
+impl From<sled::Error> for DBError {
+    fn from(err: sled::Error) -> Self {
+        DBError(err.to_string())
+    }
+}
+
+impl SledDB {
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Result<Self, DBError> {
+        let db = sled::Config::new().path(path).open()?;
+        let trees = DBCol::iter()
+            .map(|col| db.open_tree(format!("col{}", col as usize)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SledDB { db, trees })
+    }
+
+    fn iter_tree<'a>(
+        iterator: sled::Iter,
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        Box::new(iterator.map(|item| {
+            let (key, value) = item.expect("Failed to read from sled database");
+            (key.to_vec().into_boxed_slice(), value.to_vec().into_boxed_slice())
+        }))
+    }
+}
+
+impl Database for SledDB {
+    fn get(&self, col: DBCol, key: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
+        let result = self.trees[col as usize].get(key)?.map(|value| value.to_vec());
+        Ok(RocksDB::get_with_rc_logic(col, result))
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        RocksDB::iter_with_rc_logic(col, self.iter_without_rc_logic(col))
+    }
+
+    fn iter_without_rc_logic<'a>(
+        &'a self,
+        col: DBCol,
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        SledDB::iter_tree(self.trees[col as usize].iter())
+    }
+
+    fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        SledDB::iter_tree(self.trees[col as usize].range(from..))
+    }
+
+    fn iter_prefix<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        RocksDB::iter_with_rc_logic(
+            col,
+            SledDB::iter_tree(self.trees[col as usize].scan_prefix(key_prefix)),
+        )
+    }
+
+    fn write(&self, transaction: DBTransaction) -> Result<(), DBError> {
+        let mut ops = transaction.ops.as_slice();
+        while !ops.is_empty() {
+            let end = ops.iter().position(|op| matches!(op, DBOp::DeleteAll { .. }));
+            let (transactional, rest) = ops.split_at(end.unwrap_or(ops.len()));
+            self.write_transactional(transactional)?;
+            ops = match rest.split_first() {
+                Some((DBOp::DeleteAll { col }, rest)) => {
+                    self.delete_all(*col)?;
+                    rest
+                }
+                _ => rest,
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Number of keys removed per sled batch when clearing a whole column.
+const DELETE_ALL_BATCH_SIZE: usize = 10_000;
+
+impl SledDB {
+    fn write_transactional(&self, ops: &[DBOp]) -> Result<(), DBError> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let result = self.trees.as_slice().transaction(|trees| {
+            for op in ops {
+                match op {
+                    DBOp::Insert { col, key, value } => {
+                        trees[*col as usize].insert(key.as_slice(), value.as_slice())?;
+                    }
+                    DBOp::UpdateRefcount { col, key, value } => {
+                        assert!(col.is_rc());
+                        let tree = &trees[*col as usize];
+                        let mut merged =
+                            tree.get(key.as_slice())?.map(|v| v.to_vec()).unwrap_or_default();
+                        merge_refcounted_records(&mut merged, value);
+                        if merged.is_empty() {
+                            tree.remove(key.as_slice())?;
+                        } else {
+                            tree.insert(key.as_slice(), merged)?;
+                        }
+                    }
+                    DBOp::Delete { col, key } => {
+                        trees[*col as usize].remove(key.as_slice())?;
+                    }
+                    DBOp::DeleteAll { .. } => unreachable!("DeleteAll is applied in batches"),
+                }
+            }
+            Ok::<_, ConflictableTransactionError<()>>(())
+        });
+        match result {
+            Ok(()) => Ok(()),
+            Err(TransactionError::Storage(err)) => Err(err.into()),
+            Err(TransactionError::Abort(())) => unreachable!("sled transaction is never aborted"),
+        }
+    }
+
+    /// Clears the column in batches of [`DELETE_ALL_BATCH_SIZE`] keys, so that a large
+    /// column doesn't have to fit into a single sled transaction. Unlike the rest of the
+    /// transaction the removal is not atomic: after a crash the column may be partially
+    /// cleared.
+    fn delete_all(&self, col: DBCol) -> Result<(), DBError> {
+        let tree = &self.trees[col as usize];
+        loop {
+            let mut batch = sled::Batch::default();
+            let mut count = 0;
+            for key in tree.iter().keys().take(DELETE_ALL_BATCH_SIZE) {
+                batch.remove(key?);
+                count += 1;
+            }
+            if count == 0 {
+                return Ok(());
+            }
+            tree.apply_batch(batch)?;
+        }
+    }
+}
+
+impl Drop for SledDB {
+    fn drop(&mut self) {
+        if let Err(err) = self.db.flush() {
+            tracing::warn!(target: "store", "Failed to flush sled database: {}", err);
+        }
+    }
+}
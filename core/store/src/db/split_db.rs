@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::db::{DBCol, DBError, DBSnapshot, DBTransaction, Database, RocksDB};
+
+/// [`Database`] of an archival node which keeps the data older than the GC horizon in a
+/// separate "cold" database, which can live on cheaper disks.
+///
+/// All writes go to the hot database. Instead of deleting the data of cold columns (see
+/// [`DBCol::is_cold`]), GC copies it to the cold database, which is never GCed. Reads of cold
+/// columns fall through to the cold database when the key is not in the hot one, and iterators
+/// merge both databases in key order, preferring the hot value when a key is in both.
+pub struct SplitDB {
+    hot: Pin<Arc<dyn Database>>,
+    cold: Pin<Arc<dyn Database>>,
+}
+
+impl SplitDB {
+    pub fn new(hot: Pin<Arc<dyn Database>>, cold: Pin<Arc<dyn Database>>) -> Self {
+        SplitDB { hot, cold }
+    }
+
+    pub fn cold(&self) -> Pin<Arc<dyn Database>> {
+        self.cold.clone()
+    }
+
+    /// Whether `snapshot` pins the hot data to a single committed state.
+    pub(crate) fn supports_snapshots(&self) -> bool {
+        self.hot.as_rocksdb().is_some()
+    }
+
+    /// Returns a split database which reads the hot data from a snapshot. The cold database is
+    /// only appended to, so it's read directly.
+    pub(crate) fn snapshot(&self) -> Self {
+        SplitDB { hot: Arc::pin(DBSnapshot::new(self.hot.clone())), cold: self.cold.clone() }
+    }
+
+    fn merge_iters<'a>(
+        col: DBCol,
+        hot: Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>,
+        cold: Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>,
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        if col.is_cold() {
+            Box::new(MergeIterator { hot: hot.peekable(), cold: cold.peekable() })
+        } else {
+            hot
+        }
+    }
+}
+
+/// Merges two iterators which are sorted by key, taking the item of `hot` for equal keys.
+struct MergeIterator<I: Iterator<Item = (Box<[u8]>, Box<[u8]>)>> {
+    hot: Peekable<I>,
+    cold: Peekable<I>,
+}
+
+impl<I: Iterator<Item = (Box<[u8]>, Box<[u8]>)>> Iterator for MergeIterator<I> {
+    type Item = (Box<[u8]>, Box<[u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.hot.peek(), self.cold.peek()) {
+            (Some((hot_key, _)), Some((cold_key, _))) => hot_key.cmp(cold_key),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+        match ordering {
+            Ordering::Less => self.hot.next(),
+            Ordering::Equal => {
+                self.cold.next();
+                self.hot.next()
+            }
+            Ordering::Greater => self.cold.next(),
+        }
+    }
+}
+
+impl Database for SplitDB {
+    fn get(&self, col: DBCol, key: &[u8]) -> Result<Option<Vec<u8>>, DBError> {
+        match self.hot.get(col, key)? {
+            None if col.is_cold() => self.cold.get(col, key),
+            result => Ok(result),
+        }
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        SplitDB::merge_iters(col, self.hot.iter(col), self.cold.iter(col))
+    }
+
+    fn iter_without_rc_logic<'a>(
+        &'a self,
+        col: DBCol,
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        SplitDB::merge_iters(
+            col,
+            self.hot.iter_without_rc_logic(col),
+            self.cold.iter_without_rc_logic(col),
+        )
+    }
+
+    fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        SplitDB::merge_iters(
+            col,
+            self.hot.iter_without_rc_logic_from(col, from),
+            self.cold.iter_without_rc_logic_from(col, from),
+        )
+    }
+
+    fn iter_prefix<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        SplitDB::merge_iters(
+            col,
+            self.hot.iter_prefix(col, key_prefix),
+            self.cold.iter_prefix(col, key_prefix),
+        )
+    }
+
+    fn write(&self, batch: DBTransaction) -> Result<(), DBError> {
+        self.hot.write(batch)
+    }
+
+    /// The data is spread over two databases, so code working with a single RocksDB instance,
+    /// e.g. checkpoints, would silently skip the cold one.
+    fn as_rocksdb(&self) -> Option<&RocksDB> {
+        None
+    }
+
+    fn as_split_db(&self) -> Option<&SplitDB> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create_split_store;
+    use crate::db::DBCol::{ColBlock, ColBlockMisc, ColState};
+    use crate::test_utils::create_test_store;
+
+    #[test]
+    fn test_split_db_reads() {
+        // Iterators of `TestDB` are not sorted, so the test uses RocksDB.
+        let hot_dir = tempfile::Builder::new().prefix("_test_split_db_hot").tempdir().unwrap();
+        let cold_dir = tempfile::Builder::new().prefix("_test_split_db_cold").tempdir().unwrap();
+        let hot = crate::create_store(hot_dir.path());
+        let cold = crate::create_store(cold_dir.path());
+        let split = create_split_store(&hot, &cold);
+        let mut store_update = cold.store_update();
+        store_update.set(ColBlock, &[1], &[1]);
+        store_update.set(ColBlock, &[2], &[2]);
+        store_update.set(ColBlockMisc, &[1], &[1]);
+        store_update.update_refcount(ColState, &[1], &[1], 1);
+        store_update.commit().unwrap();
+        let mut store_update = split.store_update();
+        store_update.set(ColBlock, &[2], &[3]);
+        store_update.set(ColBlock, &[3], &[3]);
+        store_update.commit().unwrap();
+
+        // Writes go to the hot database only.
+        assert_eq!(hot.get(ColBlock, &[3]).unwrap(), Some(vec![3]));
+        assert_eq!(cold.get(ColBlock, &[3]).unwrap(), None);
+
+        assert_eq!(split.get(ColBlock, &[1]).unwrap(), Some(vec![1]));
+        assert_eq!(split.get(ColBlock, &[2]).unwrap(), Some(vec![3]));
+        assert_eq!(split.get(ColState, &[1]).unwrap(), Some(vec![1]));
+        // Columns which are not cold are never read from the cold database.
+        assert_eq!(split.get(ColBlockMisc, &[1]).unwrap(), None);
+        assert_eq!(split.iter(ColBlockMisc).count(), 0);
+
+        let items: Vec<_> =
+            split.iter(ColBlock).map(|(key, value)| (key.to_vec(), value.to_vec())).collect();
+        assert_eq!(items, vec![(vec![1], vec![1]), (vec![2], vec![3]), (vec![3], vec![3])]);
+        assert_eq!(split.iter_prefix(ColBlock, &[2]).count(), 1);
+        assert_eq!(split.snapshot().get(ColBlock, &[1]).unwrap(), Some(vec![1]));
+        assert!(split.cold_store().is_some());
+        assert!(split.get_rocksdb().is_none());
+        assert!(split.supports_snapshots());
+        assert!(hot.cold_store().is_none());
+        assert!(create_test_store().cold_store().is_none());
+    }
+}
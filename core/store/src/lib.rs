@@ -12,8 +12,9 @@ use cached::{Cached, SizedCache};
 
 pub use db::DBCol::{self, *};
 pub use db::{
-    CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY,
-    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC, TAIL_KEY,
+    CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FLAT_STATE_DELTAS_TAIL_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY,
+    HEAD_KEY, LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC,
+    STORE_VALIDATOR_CURSOR_KEY, TAIL_KEY,
 };
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
@@ -26,18 +27,25 @@ pub use near_primitives::shard_layout::ShardUId;
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{AccountId, CompiledContractCache, StateRoot};
 
-pub use crate::db::refcount::decode_value_with_rc;
 use crate::db::refcount::encode_value_with_rc;
+pub use crate::db::refcount::{decode_value_with_rc, RefcountStats};
+pub use crate::db::DBBackend;
+pub use crate::db::SplitDB;
 use crate::db::{
-    DBOp, DBTransaction, Database, RocksDB, GENESIS_JSON_HASH_KEY, GENESIS_STATE_ROOTS_KEY,
+    DBOp, DBSnapshot, DBTransaction, Database, RocksDB, GENESIS_JSON_HASH_KEY,
+    GENESIS_STATE_ROOTS_KEY,
 };
 pub use crate::trie::{
-    iterator::TrieIterator, split_state, update::TrieUpdate, update::TrieUpdateIterator,
-    update::TrieUpdateValuePtr, ApplyStatePartResult, KeyForStateChanges, PartialStorage,
-    ShardTries, Trie, TrieChanges, WrappedTrieChanges,
+    flat_state, iterator::TrieDiffItem, iterator::TrieDiffIterator, iterator::TrieIterator,
+    split_state, update::TrieUpdate, update::TrieUpdateIterator, update::TrieUpdateValuePtr,
+    ApplyStatePartResult, KeyForStateChanges, KeyForStateChangesHistory, PartialStorage,
+    ShardCacheConfig, ShardTries, Trie, TrieCacheConfig, TrieChanges, TrieKeysProof,
+    TrieRangeProof, WrappedTrieChanges,
 };
 
+pub mod checkpoint;
 pub mod db;
+mod metrics;
 pub mod migrations;
 pub mod test_utils;
 mod trie;
@@ -79,6 +87,31 @@ impl Store {
         StoreUpdate::new(self.storage.clone())
     }
 
+    /// Returns a read-only store pinned to the current state of the database.
+    ///
+    /// Reads through the returned store observe a single committed state even when they span
+    /// several columns and other threads keep committing updates. Writing to it fails.
+    pub fn snapshot(&self) -> Store {
+        match self.storage.as_split_db() {
+            Some(split_db) => Store { storage: Arc::pin(split_db.snapshot()) },
+            None => Store { storage: Arc::pin(DBSnapshot::new(self.storage.clone())) },
+        }
+    }
+
+    /// Whether `snapshot` pins the store to a single committed state, which is only supported by
+    /// RocksDB.
+    pub fn supports_snapshots(&self) -> bool {
+        match self.storage.as_split_db() {
+            Some(split_db) => split_db.supports_snapshots(),
+            None => self.storage.as_rocksdb().is_some(),
+        }
+    }
+
+    /// Returns the cold database of a split store, see [`SplitDB`].
+    pub fn cold_store(&self) -> Option<Store> {
+        self.storage.as_split_db().map(|split_db| Store::new(split_db.cold()))
+    }
+
     pub fn iter<'a>(
         &'a self,
         column: DBCol,
@@ -93,6 +126,22 @@ impl Store {
         self.storage.iter_without_rc_logic(column)
     }
 
+    /// Like [`Store::iter_without_rc_logic`], but starts at the first key not less than `from`
+    /// and always yields the records in key order.
+    pub fn iter_without_rc_logic_from<'a>(
+        &'a self,
+        column: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        self.storage.iter_without_rc_logic_from(column, from)
+    }
+
+    /// Reads the whole refcounted `column` and counts its live and garbage records.
+    pub fn refcount_stats(&self, column: DBCol) -> RefcountStats {
+        assert!(column.is_rc(), "{:?} is not refcounted", column);
+        RefcountStats::from_records(self.storage.iter_without_rc_logic(column))
+    }
+
     pub fn iter_prefix<'a>(
         &'a self,
         column: DBCol,
@@ -300,6 +349,36 @@ pub fn create_store(path: &Path) -> Arc<Store> {
     Arc::new(Store::new(db))
 }
 
+/// Opens the store at `path` in read-only mode, which works while a node keeps the database
+/// open. Refcounted columns can't be read from such a store: their merge operator can't be set
+/// for column families opened read-only, so records which are not compacted yet fail to load.
+pub fn create_read_only_store(path: &Path) -> Arc<Store> {
+    let db = Arc::pin(
+        RocksDB::new_read_only_all_columns(path).expect("Failed to open the database read-only"),
+    );
+    Arc::new(Store::new(db))
+}
+
+/// Combines the stores of an archival node into a split store, which keeps the data older than
+/// the GC horizon in `cold`.
+pub fn create_split_store(hot: &Store, cold: &Store) -> Arc<Store> {
+    Arc::new(Store::new(Arc::pin(SplitDB::new(hot.storage.clone(), cold.storage.clone()))))
+}
+
+/// Opens the store at `path` using the given storage backend.
+pub fn create_store_with_backend(path: &Path, backend: DBBackend) -> Arc<Store> {
+    match backend {
+        DBBackend::RocksDB => create_store(path),
+        #[cfg(feature = "sled_backend")]
+        DBBackend::Sled => {
+            let db = Arc::pin(db::SledDB::new(path).expect("Failed to open the database"));
+            Arc::new(Store::new(db))
+        }
+        #[cfg(not(feature = "sled_backend"))]
+        DBBackend::Sled => panic!("near-store was built without the `sled_backend` feature"),
+    }
+}
+
 /// Reads an object from Trie.
 /// # Errors
 /// see StorageError
@@ -0,0 +1,31 @@
+use lazy_static::lazy_static;
+use near_metrics::{
+    try_create_int_counter_vec, try_create_int_gauge_vec, IntCounterVec, IntGaugeVec,
+};
+
+lazy_static! {
+    pub static ref TRIE_CACHE_HITS: IntCounterVec = try_create_int_counter_vec(
+        "near_trie_cache_hits_total",
+        "Number of trie node reads served from the trie cache",
+        &["shard_uid", "is_view"],
+    )
+    .unwrap();
+    pub static ref TRIE_CACHE_MISSES: IntCounterVec = try_create_int_counter_vec(
+        "near_trie_cache_misses_total",
+        "Number of trie node reads which had to go to the database",
+        &["shard_uid", "is_view"],
+    )
+    .unwrap();
+    pub static ref TRIE_CACHE_EVICTIONS: IntCounterVec = try_create_int_counter_vec(
+        "near_trie_cache_evictions_total",
+        "Number of trie cache entries evicted to stay within the memory limit",
+        &["shard_uid", "is_view"],
+    )
+    .unwrap();
+    pub static ref TRIE_CACHE_BYTES: IntGaugeVec = try_create_int_gauge_vec(
+        "near_trie_cache_bytes",
+        "Total size of the keys and values held in the trie cache",
+        &["shard_uid", "is_view"],
+    )
+    .unwrap();
+}
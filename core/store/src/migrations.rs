@@ -1,5 +1,6 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::io;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -39,17 +40,32 @@ use crate::migrations::v6_to_v7::{
 use crate::migrations::v8_to_v9::{
     recompute_col_rc, repair_col_receipt_id_to_shard_id, repair_col_transactions,
 };
-use crate::trie::{TrieCache, TrieCachingStorage};
+use crate::trie::{TrieCache, TrieCachingStorage, DEFAULT_SHARD_CACHE_MAX_BYTES};
 use crate::{create_store, Store, StoreUpdate, Trie, TrieUpdate, FINAL_HEAD_KEY, HEAD_KEY};
 use std::path::Path;
 
+mod registry;
 pub mod v6_to_v7;
 pub mod v8_to_v9;
 
+pub use registry::{
+    DryRunReport, Migration, MigrationRegistry, MigrationRunner, RecordChange, VersionBump,
+    DEFAULT_MIGRATION_BATCH_SIZE,
+};
+
 pub fn get_store_version(path: &Path) -> DbVersion {
     RocksDB::get_version(path).expect("Failed to open the database")
 }
 
+/// Reads the version of an already opened store, regardless of its backend.
+pub fn get_store_version_from_store(store: &Store) -> DbVersion {
+    let version = store
+        .get(DBCol::ColDbVersion, VERSION_KEY)
+        .expect("Failed to read the database")
+        .expect("Failed to find version in first column. Database must be corrupted.");
+    serde_json::from_slice(&version).expect("Failed to parse version. Database must be corrupted.")
+}
+
 fn set_store_version_inner(store_update: &mut StoreUpdate, db_version: u32) {
     store_update.set(
         DBCol::ColDbVersion,
@@ -337,26 +353,6 @@ impl<'a> BatchedStoreUpdate<'a> {
     }
 }
 
-fn map_col<T, U, F>(store: &Store, col: DBCol, f: F) -> Result<(), std::io::Error>
-where
-    T: BorshDeserialize,
-    U: BorshSerialize,
-    F: Fn(T) -> U,
-{
-    let keys: Vec<_> = store.iter(col).map(|(key, _)| key).collect();
-    let mut store_update = BatchedStoreUpdate::new(store, 10_000_000);
-
-    for key in keys {
-        let value: T = store.get_ser(col, key.as_ref())?.unwrap();
-        let new_value = f(value);
-        store_update.set_ser(col, key.as_ref(), &new_value)?;
-    }
-
-    store_update.finish()?;
-
-    Ok(())
-}
-
 #[allow(unused)]
 fn map_col_from_key<U, F>(store: &Store, col: DBCol, f: F) -> Result<(), std::io::Error>
 where
@@ -387,24 +383,55 @@ where
 }
 
 /// Lift all chunks to the versioned structure
-pub fn migrate_13_to_14(path: &Path) {
-    let store = create_store(path);
+pub struct Migration13To14;
 
-    map_col(&store, DBCol::ColPartialChunks, |pec: PartialEncodedChunkV1| {
-        PartialEncodedChunk::V1(pec)
-    })
-    .unwrap();
-    map_col(&store, DBCol::ColInvalidChunks, |chunk: EncodedShardChunkV1| {
-        EncodedShardChunk::V1(chunk)
-    })
-    .unwrap();
-    map_col(&store, DBCol::ColChunks, |chunk: ShardChunkV1| ShardChunk::V1(chunk)).unwrap();
-    map_col(&store, DBCol::ColStateHeaders, |header: ShardStateSyncResponseHeaderV1| {
-        ShardStateSyncResponseHeader::V1(header)
-    })
-    .unwrap();
+impl Migration for Migration13To14 {
+    fn from_version(&self) -> DbVersion {
+        13
+    }
 
-    set_store_version(&store, 14);
+    fn description(&self) -> &'static str {
+        "store versioned enums for shard chunks"
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        &[
+            DBCol::ColPartialChunks,
+            DBCol::ColInvalidChunks,
+            DBCol::ColChunks,
+            DBCol::ColStateHeaders,
+        ]
+    }
+
+    fn migrate_record(
+        &self,
+        _store: &Store,
+        col: DBCol,
+        _key: &[u8],
+        value: &[u8],
+    ) -> io::Result<RecordChange> {
+        match col {
+            DBCol::ColPartialChunks => {
+                RecordChange::convert(value, |pec: PartialEncodedChunkV1| {
+                    PartialEncodedChunk::V1(pec)
+                })
+            }
+            DBCol::ColInvalidChunks => {
+                RecordChange::convert(value, |chunk: EncodedShardChunkV1| {
+                    EncodedShardChunk::V1(chunk)
+                })
+            }
+            DBCol::ColChunks => {
+                RecordChange::convert(value, |chunk: ShardChunkV1| ShardChunk::V1(chunk))
+            }
+            DBCol::ColStateHeaders => {
+                RecordChange::convert(value, |header: ShardStateSyncResponseHeaderV1| {
+                    ShardStateSyncResponseHeader::V1(header)
+                })
+            }
+            _ => unreachable!(),
+        }
+    }
 }
 
 /// Make execution outcome ids in `ColOutcomeIds` ordered by replaying the chunks.
@@ -412,7 +439,7 @@ pub fn migrate_14_to_15(path: &Path) {
     let store = create_store(path);
     let trie_store = Box::new(TrieCachingStorage::new(
         store.clone(),
-        TrieCache::new(),
+        TrieCache::new(ShardUId::single_shard(), false, DEFAULT_SHARD_CACHE_MAX_BYTES),
         ShardUId::single_shard(),
     ));
     let trie = Rc::new(Trie::new(trie_store, ShardUId::single_shard()));
@@ -636,61 +663,91 @@ pub fn migrate_17_to_18(path: &Path) {
     set_store_version(&store, 18);
 }
 
-pub fn migrate_20_to_21(path: &Path) {
-    let store = create_store(path);
-    let mut store_update = store.store_update();
-    store_update.delete(DBCol::ColBlockMisc, GENESIS_JSON_HASH_KEY);
-    store_update.commit().unwrap();
+pub struct Migration20To21;
 
-    set_store_version(&store, 21);
+impl Migration for Migration20To21 {
+    fn from_version(&self) -> DbVersion {
+        20
+    }
+
+    fn description(&self) -> &'static str {
+        "delete genesis json hash due to change in Genesis::json_hash function"
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        &[DBCol::ColBlockMisc]
+    }
+
+    fn migrate_record(
+        &self,
+        _store: &Store,
+        _col: DBCol,
+        key: &[u8],
+        _value: &[u8],
+    ) -> io::Result<RecordChange> {
+        if key == GENESIS_JSON_HASH_KEY {
+            Ok(RecordChange::Delete)
+        } else {
+            Ok(RecordChange::Keep)
+        }
+    }
 }
 
-pub fn migrate_21_to_22(path: &Path) {
-    use near_primitives::epoch_manager::BlockInfoV1;
-    use near_primitives::epoch_manager::SlashState;
-    use near_primitives::types::validator_stake::ValidatorStakeV1;
-    use near_primitives::types::{BlockHeight, EpochId};
-    use near_primitives::version::ProtocolVersion;
-    #[derive(BorshDeserialize)]
-    struct OldBlockInfo {
-        pub hash: CryptoHash,
-        pub height: BlockHeight,
-        pub last_finalized_height: BlockHeight,
-        pub last_final_block_hash: CryptoHash,
-        pub prev_hash: CryptoHash,
-        pub epoch_first_block: CryptoHash,
-        pub epoch_id: EpochId,
-        pub proposals: Vec<ValidatorStakeV1>,
-        pub chunk_mask: Vec<bool>,
-        pub latest_protocol_version: ProtocolVersion,
-        pub slashed: HashMap<AccountId, SlashState>,
-        pub total_supply: Balance,
+pub struct Migration21To22;
+
+impl Migration for Migration21To22 {
+    fn from_version(&self) -> DbVersion {
+        21
     }
-    let store = create_store(path);
-    map_col_from_key(&store, DBCol::ColBlockInfo, |key| {
-        let old_block_info =
-            store.get_ser::<OldBlockInfo>(DBCol::ColBlockInfo, key).unwrap().unwrap();
-        if key == &[0; 32] {
-            // dummy value
-            return BlockInfoV1 {
-                hash: old_block_info.hash,
-                height: old_block_info.height,
-                last_finalized_height: old_block_info.last_finalized_height,
-                last_final_block_hash: old_block_info.last_final_block_hash,
-                prev_hash: old_block_info.prev_hash,
-                epoch_first_block: old_block_info.epoch_first_block,
-                epoch_id: old_block_info.epoch_id,
-                proposals: old_block_info.proposals,
-                chunk_mask: old_block_info.chunk_mask,
-                latest_protocol_version: old_block_info.latest_protocol_version,
-                slashed: old_block_info.slashed,
-                total_supply: old_block_info.total_supply,
-                timestamp_nanosec: 0,
-            };
+
+    fn description(&self) -> &'static str {
+        "rectify inflation: add `timestamp` to `BlockInfo`"
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        &[DBCol::ColBlockInfo]
+    }
+
+    fn migrate_record(
+        &self,
+        store: &Store,
+        _col: DBCol,
+        key: &[u8],
+        value: &[u8],
+    ) -> io::Result<RecordChange> {
+        use near_primitives::epoch_manager::BlockInfoV1;
+        use near_primitives::epoch_manager::SlashState;
+        use near_primitives::types::validator_stake::ValidatorStakeV1;
+        use near_primitives::types::{BlockHeight, EpochId};
+        use near_primitives::version::ProtocolVersion;
+        #[derive(BorshDeserialize)]
+        struct OldBlockInfo {
+            pub hash: CryptoHash,
+            pub height: BlockHeight,
+            pub last_finalized_height: BlockHeight,
+            pub last_final_block_hash: CryptoHash,
+            pub prev_hash: CryptoHash,
+            pub epoch_first_block: CryptoHash,
+            pub epoch_id: EpochId,
+            pub proposals: Vec<ValidatorStakeV1>,
+            pub chunk_mask: Vec<bool>,
+            pub latest_protocol_version: ProtocolVersion,
+            pub slashed: HashMap<AccountId, SlashState>,
+            pub total_supply: Balance,
         }
-        let block_header =
-            store.get_ser::<BlockHeader>(DBCol::ColBlockHeader, key).unwrap().unwrap();
-        BlockInfoV1 {
+        let old_block_info = OldBlockInfo::try_from_slice(value)?;
+        let timestamp_nanosec = if key == &[0; 32] {
+            // dummy value
+            0
+        } else {
+            store
+                .get_ser::<BlockHeader>(DBCol::ColBlockHeader, key)?
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "Block header of BlockInfo not found")
+                })?
+                .raw_timestamp()
+        };
+        let block_info = BlockInfoV1 {
             hash: old_block_info.hash,
             height: old_block_info.height,
             last_finalized_height: old_block_info.last_finalized_height,
@@ -703,20 +760,30 @@ pub fn migrate_21_to_22(path: &Path) {
             latest_protocol_version: old_block_info.latest_protocol_version,
             slashed: old_block_info.slashed,
             total_supply: old_block_info.total_supply,
-            timestamp_nanosec: block_header.raw_timestamp(),
-        }
-    })
-    .unwrap();
-    set_store_version(&store, 22);
+            timestamp_nanosec,
+        };
+        Ok(RecordChange::Set(block_info.try_to_vec()?))
+    }
 }
 
-pub fn migrate_25_to_26(path: &Path) {
-    let store = create_store(path);
-    let mut store_update = store.store_update();
-    store_update.delete_all(DBCol::ColCachedContractCode);
-    store_update.commit().unwrap();
+pub struct Migration25To26;
+
+impl Migration for Migration25To26 {
+    fn from_version(&self) -> DbVersion {
+        25
+    }
+
+    fn description(&self) -> &'static str {
+        "delete compiled contracts compiled by the previous version of the runtime"
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        &[DBCol::ColCachedContractCode]
+    }
 
-    set_store_version(&store, 26);
+    fn migrate_record(&self, _: &Store, _: DBCol, _: &[u8], _: &[u8]) -> io::Result<RecordChange> {
+        Ok(RecordChange::Delete)
+    }
 }
 
 pub fn migrate_26_to_27(path: &Path, is_archival: bool) {
@@ -736,101 +803,195 @@ pub fn migrate_26_to_27(path: &Path, is_archival: bool) {
     set_store_version(&store, 27);
 }
 
-pub fn migrate_28_to_29(path: &Path) {
-    let store = create_store(path);
-    let mut store_update = store.store_update();
-    store_update.delete_all(DBCol::_ColNextBlockWithNewChunk);
-    store_update.delete_all(DBCol::_ColLastBlockWithNewChunk);
-    store_update.commit().unwrap();
+pub struct Migration28To29;
 
-    set_store_version(&store, 29);
-}
+impl Migration for Migration28To29 {
+    fn from_version(&self) -> DbVersion {
+        28
+    }
 
-pub fn migrate_29_to_30(path: &Path) {
-    use near_primitives::epoch_manager::block_info::{BlockInfo, BlockInfoV1};
-    use near_primitives::epoch_manager::epoch_info::EpochSummary;
-    use near_primitives::epoch_manager::AGGREGATOR_KEY;
-    use near_primitives::types::chunk_extra::{ChunkExtra, ChunkExtraV1};
-    use near_primitives::types::validator_stake::ValidatorStakeV1;
-    use near_primitives::types::{
-        BlockChunkValidatorStats, EpochId, ProtocolVersion, ShardId, ValidatorId,
-        ValidatorKickoutReason, ValidatorStats,
-    };
-    use std::collections::BTreeMap;
+    fn description(&self) -> &'static str {
+        "delete ColNextBlockWithNewChunk, ColLastBlockWithNewChunk"
+    }
 
-    let store = create_store(path);
+    fn columns(&self) -> &'static [DBCol] {
+        &[DBCol::_ColNextBlockWithNewChunk, DBCol::_ColLastBlockWithNewChunk]
+    }
 
-    #[derive(BorshDeserialize)]
-    pub struct OldEpochSummary {
-        pub prev_epoch_last_block_hash: CryptoHash,
-        pub all_proposals: Vec<ValidatorStakeV1>,
-        pub validator_kickout: HashMap<AccountId, ValidatorKickoutReason>,
-        pub validator_block_chunk_stats: HashMap<AccountId, BlockChunkValidatorStats>,
-        pub next_version: ProtocolVersion,
+    fn migrate_record(&self, _: &Store, _: DBCol, _: &[u8], _: &[u8]) -> io::Result<RecordChange> {
+        Ok(RecordChange::Delete)
     }
+}
 
-    #[derive(BorshDeserialize)]
-    pub struct OldEpochInfoAggregator {
-        pub block_tracker: HashMap<ValidatorId, ValidatorStats>,
-        pub shard_tracker: HashMap<ShardId, HashMap<ValidatorId, ValidatorStats>>,
-        pub version_tracker: HashMap<ValidatorId, ProtocolVersion>,
-        pub all_proposals: BTreeMap<AccountId, ValidatorStakeV1>,
-        pub epoch_id: EpochId,
-        pub last_block_hash: CryptoHash,
+pub struct Migration29To30;
+
+impl Migration for Migration29To30 {
+    fn from_version(&self) -> DbVersion {
+        29
     }
-    #[derive(BorshSerialize)]
-    pub struct NewEpochInfoAggregator {
-        pub block_tracker: HashMap<ValidatorId, ValidatorStats>,
-        pub shard_tracker: HashMap<ShardId, HashMap<ValidatorId, ValidatorStats>>,
-        pub version_tracker: HashMap<ValidatorId, ProtocolVersion>,
-        pub all_proposals: BTreeMap<AccountId, ValidatorStake>,
-        pub epoch_id: EpochId,
-        pub last_block_hash: CryptoHash,
+
+    fn description(&self) -> &'static str {
+        "migrate all structures that use ValidatorStake to versionized version"
     }
 
-    map_col(&store, DBCol::ColChunkExtra, |extra: ChunkExtraV1| ChunkExtra::V1(extra)).unwrap();
+    fn columns(&self) -> &'static [DBCol] {
+        &[
+            DBCol::ColChunkExtra,
+            DBCol::ColBlockInfo,
+            DBCol::ColEpochValidatorInfo,
+            DBCol::ColEpochInfo,
+        ]
+    }
 
-    map_col(&store, DBCol::ColBlockInfo, |info: BlockInfoV1| BlockInfo::V1(info)).unwrap();
+    fn migrate_record(
+        &self,
+        _store: &Store,
+        col: DBCol,
+        key: &[u8],
+        value: &[u8],
+    ) -> io::Result<RecordChange> {
+        use near_primitives::epoch_manager::block_info::{BlockInfo, BlockInfoV1};
+        use near_primitives::epoch_manager::epoch_info::EpochSummary;
+        use near_primitives::epoch_manager::AGGREGATOR_KEY;
+        use near_primitives::types::chunk_extra::{ChunkExtra, ChunkExtraV1};
+        use near_primitives::types::validator_stake::ValidatorStakeV1;
+        use near_primitives::types::{
+            BlockChunkValidatorStats, EpochId, ProtocolVersion, ShardId, ValidatorId,
+            ValidatorKickoutReason, ValidatorStats,
+        };
+        use std::collections::BTreeMap;
+
+        #[derive(BorshDeserialize)]
+        pub struct OldEpochSummary {
+            pub prev_epoch_last_block_hash: CryptoHash,
+            pub all_proposals: Vec<ValidatorStakeV1>,
+            pub validator_kickout: HashMap<AccountId, ValidatorKickoutReason>,
+            pub validator_block_chunk_stats: HashMap<AccountId, BlockChunkValidatorStats>,
+            pub next_version: ProtocolVersion,
+        }
 
-    map_col(&store, DBCol::ColEpochValidatorInfo, |info: OldEpochSummary| EpochSummary {
-        prev_epoch_last_block_hash: info.prev_epoch_last_block_hash,
-        all_proposals: info.all_proposals.into_iter().map(ValidatorStake::V1).collect(),
-        validator_kickout: info.validator_kickout,
-        validator_block_chunk_stats: info.validator_block_chunk_stats,
-        next_version: info.next_version,
-    })
-    .unwrap();
+        #[derive(BorshDeserialize)]
+        pub struct OldEpochInfoAggregator {
+            pub block_tracker: HashMap<ValidatorId, ValidatorStats>,
+            pub shard_tracker: HashMap<ShardId, HashMap<ValidatorId, ValidatorStats>>,
+            pub version_tracker: HashMap<ValidatorId, ProtocolVersion>,
+            pub all_proposals: BTreeMap<AccountId, ValidatorStakeV1>,
+            pub epoch_id: EpochId,
+            pub last_block_hash: CryptoHash,
+        }
+        #[derive(BorshSerialize)]
+        pub struct NewEpochInfoAggregator {
+            pub block_tracker: HashMap<ValidatorId, ValidatorStats>,
+            pub shard_tracker: HashMap<ShardId, HashMap<ValidatorId, ValidatorStats>>,
+            pub version_tracker: HashMap<ValidatorId, ProtocolVersion>,
+            pub all_proposals: BTreeMap<AccountId, ValidatorStake>,
+            pub epoch_id: EpochId,
+            pub last_block_hash: CryptoHash,
+        }
 
-    // DBCol::ColEpochInfo has a special key which contains a different type than all other
-    // values (EpochInfoAggregator), so we cannot use `map_col` on it. We need to handle
-    // the AGGREGATOR_KEY differently from all others.
-    let col = DBCol::ColEpochInfo;
-    let keys: Vec<_> = store.iter(col).map(|(key, _)| key).collect();
-    let mut store_update = BatchedStoreUpdate::new(&store, 10_000_000);
-    for key in keys {
-        if key.as_ref() == AGGREGATOR_KEY {
-            let value: OldEpochInfoAggregator = store.get_ser(col, key.as_ref()).unwrap().unwrap();
-            let new_value = NewEpochInfoAggregator {
-                block_tracker: value.block_tracker,
-                shard_tracker: value.shard_tracker,
-                version_tracker: value.version_tracker,
-                epoch_id: value.epoch_id,
-                last_block_hash: value.last_block_hash,
-                all_proposals: value
-                    .all_proposals
-                    .into_iter()
-                    .map(|(account, stake)| (account, ValidatorStake::V1(stake)))
-                    .collect(),
-            };
-            store_update.set_ser(col, key.as_ref(), &new_value).unwrap();
-        } else {
-            let value: EpochInfoV1 = store.get_ser(col, key.as_ref()).unwrap().unwrap();
-            let new_value = EpochInfo::V1(value);
-            store_update.set_ser(col, key.as_ref(), &new_value).unwrap();
+        match col {
+            DBCol::ColChunkExtra => {
+                RecordChange::convert(value, |extra: ChunkExtraV1| ChunkExtra::V1(extra))
+            }
+            DBCol::ColBlockInfo => {
+                RecordChange::convert(value, |info: BlockInfoV1| BlockInfo::V1(info))
+            }
+            DBCol::ColEpochValidatorInfo => {
+                RecordChange::convert(value, |info: OldEpochSummary| EpochSummary {
+                    prev_epoch_last_block_hash: info.prev_epoch_last_block_hash,
+                    all_proposals: info.all_proposals.into_iter().map(ValidatorStake::V1).collect(),
+                    validator_kickout: info.validator_kickout,
+                    validator_block_chunk_stats: info.validator_block_chunk_stats,
+                    next_version: info.next_version,
+                })
+            }
+            // ColEpochInfo has a special key which contains a different type than all other
+            // values (EpochInfoAggregator), so it has to be handled differently.
+            DBCol::ColEpochInfo if key == AGGREGATOR_KEY => {
+                RecordChange::convert(value, |value: OldEpochInfoAggregator| {
+                    NewEpochInfoAggregator {
+                        block_tracker: value.block_tracker,
+                        shard_tracker: value.shard_tracker,
+                        version_tracker: value.version_tracker,
+                        epoch_id: value.epoch_id,
+                        last_block_hash: value.last_block_hash,
+                        all_proposals: value
+                            .all_proposals
+                            .into_iter()
+                            .map(|(account, stake)| (account, ValidatorStake::V1(stake)))
+                            .collect(),
+                    }
+                })
+            }
+            DBCol::ColEpochInfo => {
+                RecordChange::convert(value, |info: EpochInfoV1| EpochInfo::V1(info))
+            }
+            _ => unreachable!(),
         }
     }
+}
 
-    store_update.finish().unwrap();
+pub fn migrate_30_to_31(path: &Path) {
+    use crate::ShardTries;
+    use near_primitives::shard_layout::get_block_shard_uid_rev;
+    use near_primitives::types::chunk_extra::ChunkExtra;
+
+    let store = Arc::new(create_store(path));
+    // Build the flat state of every shard at the state after the head block, which is the state
+    // the next block is applied to.
+    if let Some(head) = store.get_ser::<Tip>(ColBlockMisc, HEAD_KEY).unwrap() {
+        for (key, value) in store.iter_prefix(DBCol::ColChunkExtra, head.last_block_hash.as_ref()) {
+            let (_, shard_uid) = get_block_shard_uid_rev(&key).unwrap();
+            let chunk_extra = ChunkExtra::try_from_slice(&value).unwrap();
+            let tries = ShardTries::new(store.clone(), shard_uid.version, 1);
+            tries.rebuild_flat_state(shard_uid, chunk_extra.state_root()).unwrap();
+        }
+    }
+    set_store_version(&store, 31);
+}
+
+/// Indexes `ColStateChanges` in `ColStateChangesHistory`.
+#[derive(Default)]
+pub struct Migration31To32 {
+    /// Store to read block headers from, if it isn't the migrated one. A cold database doesn't
+    /// keep the headers, they are read from the hot one.
+    pub header_store: Option<Store>,
+}
+
+impl Migration for Migration31To32 {
+    fn from_version(&self) -> DbVersion {
+        31
+    }
 
-    set_store_version(&store, 30);
+    fn description(&self) -> &'static str {
+        "add ColStateChangesHistory"
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        &[DBCol::ColStateChanges]
+    }
+
+    fn migrate_record(
+        &self,
+        store: &Store,
+        _col: DBCol,
+        key: &[u8],
+        _value: &[u8],
+    ) -> io::Result<RecordChange> {
+        use crate::KeyForStateChangesHistory;
+
+        // Keys of `ColStateChanges` are `block_hash + trie_key`.
+        let (block_hash, raw_key) = key.split_at(std::mem::size_of::<CryptoHash>());
+        let header_store = self.header_store.as_ref().unwrap_or(store);
+        let header = match header_store.get_ser::<BlockHeader>(ColBlockHeader, block_hash)? {
+            Some(header) => header,
+            None => return Ok(RecordChange::Keep),
+        };
+        let key = KeyForStateChangesHistory::new(raw_key, header.height(), header.hash());
+        Ok(RecordChange::SetDerived {
+            col: DBCol::ColStateChangesHistory,
+            key: key.into(),
+            value: vec![],
+        })
+    }
 }
@@ -0,0 +1,1415 @@
+use std::io;
+use std::path::Path;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::info;
+
+use near_primitives::version::DbVersion;
+
+use crate::db::{DBCol, MIGRATION_CHECKPOINT_KEY};
+use crate::migrations::{get_store_version, set_store_version_inner};
+use crate::{create_store, Store};
+
+/// Size in bytes of the changes a migration commits in a single database transaction by
+/// default, the same as the limit of `BatchedStoreUpdate` used by legacy migrations.
+pub const DEFAULT_MIGRATION_BATCH_SIZE: usize = 10_000_000;
+
+/// What a migration does with a single record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordChange {
+    Keep,
+    Set(Vec<u8>),
+    Delete,
+    /// Keeps the record and sets a record derived from it in another column, e.g. an index.
+    SetDerived {
+        col: DBCol,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+}
+
+impl RecordChange {
+    /// Re-encodes a Borsh value of type `T` as `U`.
+    pub fn convert<T, U, F>(value: &[u8], f: F) -> io::Result<Self>
+    where
+        T: BorshDeserialize,
+        U: BorshSerialize,
+        F: FnOnce(T) -> U,
+    {
+        Ok(RecordChange::Set(f(T::try_from_slice(value)?).try_to_vec()?))
+    }
+}
+
+/// Migration of the database from version `from_version()` to `from_version() + 1`, which is
+/// done record by record.
+///
+/// Records are migrated in batches of changes of limited size. Each batch is committed together
+/// with a checkpoint, so an interrupted migration continues after the last migrated record
+/// instead of starting over.
+pub trait Migration {
+    fn from_version(&self) -> DbVersion;
+
+    fn description(&self) -> &'static str;
+
+    /// Columns the migration touches. Their records are passed to `migrate_record` column by
+    /// column, in key order.
+    fn columns(&self) -> &'static [DBCol];
+
+    /// Decides what happens with a record. Values are passed and written as they are stored,
+    /// including the refcount of refcounted columns. `store` must only be used to read records
+    /// the migration doesn't change.
+    fn migrate_record(
+        &self,
+        store: &Store,
+        col: DBCol,
+        key: &[u8],
+        value: &[u8],
+    ) -> io::Result<RecordChange>;
+}
+
+/// Migration which only bumps the version, e.g. because it adds a column, which RocksDB creates
+/// when opening the database.
+pub struct VersionBump {
+    pub from_version: DbVersion,
+    pub description: &'static str,
+}
+
+impl Migration for VersionBump {
+    fn from_version(&self) -> DbVersion {
+        self.from_version
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        &[]
+    }
+
+    fn migrate_record(&self, _: &Store, _: DBCol, _: &[u8], _: &[u8]) -> io::Result<RecordChange> {
+        Ok(RecordChange::Keep)
+    }
+}
+
+/// Position of an unfinished migration, stored in `ColDbVersion` next to the version.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+struct MigrationCheckpoint {
+    from_version: DbVersion,
+    col: DBCol,
+    /// Last migrated key of `col`, `None` if migration of `col` has not started yet.
+    last_key: Option<Vec<u8>>,
+}
+
+/// Result of a dry run of a migration.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DryRunReport {
+    pub from_version: DbVersion,
+    pub description: &'static str,
+    /// Number of visited records per column.
+    pub visited: Vec<(DBCol, u64)>,
+    /// Number of records the migration would change or delete.
+    pub changed: u64,
+    /// Whether the migration was written before the migration framework, in which case the
+    /// number of changed records is not known.
+    pub legacy: bool,
+}
+
+/// Runs a record migration on an open store.
+pub struct MigrationRunner<'a> {
+    store: &'a Store,
+    migration: &'a dyn Migration,
+    /// A batch is committed once the size in bytes of its changes reaches this limit.
+    batch_size: usize,
+}
+
+impl<'a> MigrationRunner<'a> {
+    pub fn new(store: &'a Store, migration: &'a dyn Migration, batch_size: usize) -> Self {
+        assert!(batch_size > 0);
+        MigrationRunner { store, migration, batch_size }
+    }
+
+    /// Migrates the next batch of records, starting after the stored checkpoint. Returns `true`
+    /// when all records are migrated and the version of the database is bumped.
+    pub fn step(&self) -> io::Result<bool> {
+        let from_version = self.migration.from_version();
+        let columns = self.migration.columns();
+        let checkpoint = self
+            .store
+            .get_ser::<MigrationCheckpoint>(DBCol::ColDbVersion, MIGRATION_CHECKPOINT_KEY)?
+            .filter(|checkpoint| checkpoint.from_version == from_version);
+        let (first_col, mut last_key) = match checkpoint {
+            Some(checkpoint) => {
+                let index =
+                    columns.iter().position(|col| *col == checkpoint.col).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "Invalid migration checkpoint")
+                    })?;
+                (index, checkpoint.last_key)
+            }
+            None => (0, None),
+        };
+
+        let mut store_update = self.store.store_update();
+        let mut migrated = 0;
+        let mut batch_size = 0;
+        for &col in columns[first_col..].iter() {
+            let from = last_key.take();
+            let records =
+                self.store.iter_without_rc_logic_from(col, from.as_deref().unwrap_or_default());
+            for (key, value) in records {
+                if from.as_deref() == Some(key.as_ref()) {
+                    continue;
+                }
+                if batch_size >= self.batch_size {
+                    info!(target: "near", "Migrate DB from version {}: {} records of {:?} migrated", from_version, migrated, col);
+                    let checkpoint = MigrationCheckpoint { from_version, col, last_key };
+                    store_update.set_ser(
+                        DBCol::ColDbVersion,
+                        MIGRATION_CHECKPOINT_KEY,
+                        &checkpoint,
+                    )?;
+                    store_update.commit()?;
+                    return Ok(false);
+                }
+                match self.migration.migrate_record(self.store, col, &key, &value)? {
+                    RecordChange::Keep => {}
+                    RecordChange::Set(value) => {
+                        batch_size += key.len() + value.len() + 8;
+                        store_update.set(col, &key, &value)
+                    }
+                    RecordChange::Delete => {
+                        batch_size += key.len() + 8;
+                        store_update.delete(col, &key)
+                    }
+                    RecordChange::SetDerived { col, key, value } => {
+                        batch_size += key.len() + value.len() + 8;
+                        store_update.set(col, &key, &value)
+                    }
+                }
+                last_key = Some(key.into_vec());
+                migrated += 1;
+            }
+            last_key = None;
+        }
+        store_update.delete(DBCol::ColDbVersion, MIGRATION_CHECKPOINT_KEY);
+        set_store_version_inner(&mut store_update, from_version + 1);
+        store_update.commit()?;
+        Ok(true)
+    }
+
+    pub fn run(&self) -> io::Result<()> {
+        while !self.step()? {}
+        Ok(())
+    }
+
+    /// Counts the records the migration would change, without writing anything.
+    pub fn dry_run(&self) -> io::Result<DryRunReport> {
+        let mut report = DryRunReport {
+            from_version: self.migration.from_version(),
+            description: self.migration.description(),
+            ..Default::default()
+        };
+        for &col in self.migration.columns() {
+            let mut visited = 0;
+            for (key, value) in self.store.iter_without_rc_logic(col) {
+                visited += 1;
+                match self.migration.migrate_record(self.store, col, &key, &value)? {
+                    RecordChange::Keep => {}
+                    RecordChange::Set(new_value) if new_value == value.as_ref() => {}
+                    RecordChange::Set(_)
+                    | RecordChange::Delete
+                    | RecordChange::SetDerived { .. } => report.changed += 1,
+                }
+            }
+            report.visited.push((col, visited));
+        }
+        Ok(report)
+    }
+}
+
+struct LegacyMigration<'a> {
+    from_version: DbVersion,
+    description: &'static str,
+    columns: &'static [DBCol],
+    run: Box<dyn Fn(&Path) + 'a>,
+}
+
+enum RegisteredMigration<'a> {
+    Records(Box<dyn Migration + 'a>),
+    /// Migration written as a single function of the database path, which sets the version
+    /// itself. It starts over if interrupted and can't be dry-run.
+    Legacy(LegacyMigration<'a>),
+}
+
+impl<'a> RegisteredMigration<'a> {
+    fn from_version(&self) -> DbVersion {
+        match self {
+            RegisteredMigration::Records(migration) => migration.from_version(),
+            RegisteredMigration::Legacy(migration) => migration.from_version,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            RegisteredMigration::Records(migration) => migration.description(),
+            RegisteredMigration::Legacy(migration) => migration.description,
+        }
+    }
+
+    fn columns(&self) -> &'static [DBCol] {
+        match self {
+            RegisteredMigration::Records(migration) => migration.columns(),
+            RegisteredMigration::Legacy(migration) => migration.columns,
+        }
+    }
+}
+
+/// All migrations of the database, one per version.
+pub struct MigrationRegistry<'a> {
+    migrations: Vec<RegisteredMigration<'a>>,
+    batch_size: usize,
+}
+
+impl<'a> Default for MigrationRegistry<'a> {
+    fn default() -> Self {
+        MigrationRegistry { migrations: vec![], batch_size: DEFAULT_MIGRATION_BATCH_SIZE }
+    }
+}
+
+impl<'a> MigrationRegistry<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size in bytes of the changes committed at once by record migrations.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size;
+    }
+
+    fn push(&mut self, migration: RegisteredMigration<'a>) {
+        if let Some(last) = self.migrations.last() {
+            assert_eq!(
+                last.from_version() + 1,
+                migration.from_version(),
+                "Migrations must be registered in the order of versions"
+            );
+        }
+        self.migrations.push(migration);
+    }
+
+    pub fn register(&mut self, migration: impl Migration + 'a) {
+        self.push(RegisteredMigration::Records(Box::new(migration)));
+    }
+
+    pub fn register_legacy(
+        &mut self,
+        from_version: DbVersion,
+        description: &'static str,
+        columns: &'static [DBCol],
+        run: impl Fn(&Path) + 'a,
+    ) {
+        self.push(RegisteredMigration::Legacy(LegacyMigration {
+            from_version,
+            description,
+            columns,
+            run: Box::new(run),
+        }));
+    }
+
+    /// Version of the database after all registered migrations.
+    pub fn latest_version(&self) -> Option<DbVersion> {
+        self.migrations.last().map(|migration| migration.from_version() + 1)
+    }
+
+    fn get(&self, version: DbVersion) -> Option<&RegisteredMigration<'a>> {
+        let first = self.migrations.first()?.from_version();
+        self.migrations.get(version.checked_sub(first)? as usize)
+    }
+
+    /// Applies the migrations from the current version of the database at `path` up to
+    /// `latest_version()`.
+    pub fn apply(&self, path: &Path) -> io::Result<()> {
+        loop {
+            let version = get_store_version(path);
+            let migration = match self.get(version) {
+                Some(migration) => migration,
+                None => return Ok(()),
+            };
+            info!(target: "near", "Migrate DB from version {} to {}: {} (columns {:?})", version, version + 1, migration.description(), migration.columns());
+            match migration {
+                RegisteredMigration::Records(migration) => {
+                    let store = create_store(path);
+                    MigrationRunner::new(&store, migration.as_ref(), self.batch_size).run()?;
+                }
+                RegisteredMigration::Legacy(migration) => (migration.run)(path),
+            }
+        }
+    }
+
+    /// Reports what the next migration of the database at `path` would change. Later
+    /// migrations depend on the results of earlier ones, so only the next one is dry-run.
+    pub fn dry_run(&self, path: &Path) -> io::Result<Option<DryRunReport>> {
+        let migration = match self.get(get_store_version(path)) {
+            Some(migration) => migration,
+            None => return Ok(None),
+        };
+        match migration {
+            RegisteredMigration::Records(migration) => {
+                let store = create_store(path);
+                MigrationRunner::new(&store, migration.as_ref(), self.batch_size)
+                    .dry_run()
+                    .map(Some)
+            }
+            RegisteredMigration::Legacy(migration) => Ok(Some(DryRunReport {
+                from_version: migration.from_version,
+                description: migration.description,
+                legacy: true,
+                ..Default::default()
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::db::GENESIS_JSON_HASH_KEY;
+    use crate::migrations::{
+        fill_col_outcomes_by_hash, fill_col_transaction_refcount, get_store_version_from_store,
+        migrate_10_to_11, migrate_11_to_12, migrate_14_to_15, migrate_17_to_18, migrate_26_to_27,
+        migrate_30_to_31, migrate_6_to_7, migrate_7_to_8, migrate_8_to_9, migrate_9_to_10,
+        set_store_version, Migration13To14, Migration20To21, Migration21To22, Migration25To26,
+        Migration28To29, Migration29To30, Migration31To32,
+    };
+    use crate::{KeyForStateChanges, KeyForStateChangesHistory, FINAL_HEAD_KEY, HEAD_KEY};
+
+    type Records = Vec<(DBCol, Vec<u8>, Vec<u8>)>;
+
+    fn write_records(store: &Store, records: &[(DBCol, Vec<u8>, Vec<u8>)], version: DbVersion) {
+        let mut store_update = store.store_update();
+        for (col, key, value) in records {
+            store_update.set(*col, key, value);
+        }
+        set_store_version_inner(&mut store_update, version);
+        store_update.commit().unwrap();
+    }
+
+    fn read_records(store: &Store, columns: &[DBCol]) -> Records {
+        let mut records = vec![];
+        for &col in columns {
+            for (key, value) in store.iter_without_rc_logic(col) {
+                records.push((col, key.into_vec(), value.into_vec()));
+            }
+        }
+        records
+    }
+
+    fn sorted(columns: &[DBCol], mut records: Records) -> Records {
+        records.retain(|(col, _, _)| columns.contains(col));
+        records.sort_by_key(|(col, key, _)| {
+            (columns.iter().position(|c| c == col).unwrap(), key.clone())
+        });
+        records
+    }
+
+    /// Runs `migration` against a database filled with `fixture` and checks that afterwards its
+    /// columns contain exactly `expected`. Records are committed one by one and the migration is
+    /// interrupted after the first one, to check that it continues from the checkpoint.
+    fn check_migration(migration: &dyn Migration, fixture: Records, expected: Records) {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let from_version = migration.from_version();
+        write_records(&store, &fixture, from_version);
+        let columns = migration.columns();
+        let fixture = sorted(columns, fixture);
+        let expected = sorted(columns, expected);
+
+        let report = MigrationRunner::new(&store, migration, 1).dry_run().unwrap();
+        let changed = fixture
+            .iter()
+            .filter(|(col, key, value)| {
+                !expected.iter().any(|record| record == &(*col, key.clone(), value.clone()))
+            })
+            .count();
+        assert_eq!(report.changed, changed as u64);
+        assert_eq!(read_records(&store, columns), fixture);
+
+        if fixture.len() > 1 {
+            assert!(!MigrationRunner::new(&store, migration, 1).step().unwrap());
+            assert_eq!(get_store_version_from_store(&store), from_version);
+        }
+        MigrationRunner::new(&store, migration, 1).run().unwrap();
+        assert_eq!(get_store_version_from_store(&store), from_version + 1);
+        assert_eq!(store.get(DBCol::ColDbVersion, MIGRATION_CHECKPOINT_KEY).unwrap(), None);
+        assert_eq!(read_records(&store, columns), expected);
+    }
+
+    #[test]
+    fn test_migration_20_to_21() {
+        let head = (DBCol::ColBlockMisc, b"HEAD".to_vec(), vec![1]);
+        check_migration(
+            &Migration20To21,
+            vec![(DBCol::ColBlockMisc, GENESIS_JSON_HASH_KEY.to_vec(), vec![2; 32]), head.clone()],
+            vec![head],
+        );
+    }
+
+    #[test]
+    fn test_migration_25_to_26() {
+        let block = (DBCol::ColBlock, vec![1], vec![1]);
+        check_migration(
+            &Migration25To26,
+            vec![
+                (DBCol::ColCachedContractCode, vec![1], vec![1, 2, 3]),
+                (DBCol::ColCachedContractCode, vec![2], vec![4, 5, 6]),
+                block.clone(),
+            ],
+            vec![block],
+        );
+    }
+
+    #[test]
+    fn test_migration_28_to_29() {
+        check_migration(
+            &Migration28To29,
+            vec![
+                (DBCol::_ColNextBlockWithNewChunk, vec![1], vec![1]),
+                (DBCol::_ColLastBlockWithNewChunk, vec![1], vec![1]),
+                (DBCol::_ColLastBlockWithNewChunk, vec![2], vec![2]),
+            ],
+            vec![],
+        );
+    }
+
+    #[test]
+    fn test_migration_31_to_32() {
+        use near_primitives::block::Block;
+        use near_primitives::hash::{hash, CryptoHash};
+        use near_primitives::trie_key::TrieKey;
+        use near_primitives::utils::from_timestamp;
+        use near_primitives::version::PROTOCOL_VERSION;
+
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let block = Block::genesis(
+            PROTOCOL_VERSION,
+            vec![],
+            from_timestamp(0),
+            7,
+            0,
+            0,
+            CryptoHash::default(),
+        );
+        let header = block.header();
+        let raw_key = TrieKey::Account { account_id: "alice.near".parse().unwrap() }.to_vec();
+        let longer_key = TrieKey::Account { account_id: "alice.near.x".parse().unwrap() }.to_vec();
+        let fixture = vec![
+            (DBCol::ColBlockHeader, header.hash().as_ref().to_vec(), header.try_to_vec().unwrap()),
+            (
+                DBCol::ColStateChanges,
+                KeyForStateChanges::new(header.hash(), &raw_key).into(),
+                vec![1],
+            ),
+            // Changes of blocks without a header are not indexed.
+            (
+                DBCol::ColStateChanges,
+                KeyForStateChanges::new(&hash(&[1]), &raw_key).into(),
+                vec![2],
+            ),
+            (
+                DBCol::ColStateChangesHistory,
+                KeyForStateChangesHistory::new(&longer_key, 5, &hash(&[2])).into(),
+                vec![],
+            ),
+        ];
+        write_records(&store, &fixture, 31);
+
+        let migration = Migration31To32::default();
+        let report = MigrationRunner::new(&store, &migration, 1).dry_run().unwrap();
+        assert_eq!(report.changed, 1);
+        MigrationRunner::new(&store, &migration, 1).run().unwrap();
+        assert_eq!(get_store_version_from_store(&store), 32);
+        let columns = [DBCol::ColBlockHeader, DBCol::ColStateChanges];
+        assert_eq!(read_records(&store, &columns), sorted(&columns, fixture));
+        let start = KeyForStateChangesHistory::first_from_height(&raw_key, 0);
+        assert_eq!(start.find_iter(&store).collect::<Vec<_>>(), vec![(7, *header.hash())]);
+        let start = KeyForStateChangesHistory::first_from_height(&raw_key, 8);
+        assert_eq!(start.find_iter(&store).count(), 0);
+
+        // A cold database reads the headers from the hot one.
+        let cold_dir = tempfile::Builder::new().prefix("_test_migration_cold").tempdir().unwrap();
+        let cold_store = create_store(cold_dir.path());
+        write_records(&cold_store, &fixture[1..2], 31);
+        let migration = Migration31To32 { header_store: Some(store.clone()) };
+        MigrationRunner::new(&cold_store, &migration, 1).run().unwrap();
+        let start = KeyForStateChangesHistory::first_from_height(&raw_key, 0);
+        assert_eq!(start.find_iter(&cold_store).collect::<Vec<_>>(), vec![(7, *header.hash())]);
+    }
+
+    #[test]
+    fn test_migration_13_to_14() {
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::sharding::{
+            EncodedShardChunk, EncodedShardChunkBody, EncodedShardChunkV1, PartialEncodedChunk,
+            PartialEncodedChunkV1, ShardChunk, ShardChunkHeaderV1, ShardChunkV1,
+        };
+        use near_primitives::syncing::{
+            ShardStateSyncResponseHeader, ShardStateSyncResponseHeaderV1,
+        };
+        use near_primitives::types::StateRootNode;
+        use near_primitives::validator_signer::EmptyValidatorSigner;
+
+        let header = ShardChunkHeaderV1::new(
+            CryptoHash::default(),
+            CryptoHash::default(),
+            CryptoHash::default(),
+            CryptoHash::default(),
+            1,
+            2,
+            0,
+            0,
+            1000,
+            0,
+            CryptoHash::default(),
+            CryptoHash::default(),
+            vec![],
+            &EmptyValidatorSigner::default(),
+        );
+        let key = header.chunk_hash().try_to_vec().unwrap();
+        let partial_chunk =
+            PartialEncodedChunkV1 { header: header.clone(), parts: vec![], receipts: vec![] };
+        let invalid_chunk = EncodedShardChunkV1 {
+            header: header.clone(),
+            content: EncodedShardChunkBody { parts: vec![None] },
+        };
+        let chunk = ShardChunkV1 {
+            chunk_hash: header.chunk_hash(),
+            header: header.clone(),
+            transactions: vec![],
+            receipts: vec![],
+        };
+        let state_header = ShardStateSyncResponseHeaderV1 {
+            chunk: chunk.clone(),
+            chunk_proof: vec![],
+            prev_chunk_header: Some(header),
+            prev_chunk_proof: None,
+            incoming_receipts_proofs: vec![],
+            root_proofs: vec![],
+            state_root_node: StateRootNode::empty(),
+        };
+        let record = |col, value: Vec<u8>| (col, key.clone(), value);
+        check_migration(
+            &Migration13To14,
+            vec![
+                record(DBCol::ColPartialChunks, partial_chunk.try_to_vec().unwrap()),
+                record(DBCol::ColInvalidChunks, invalid_chunk.try_to_vec().unwrap()),
+                record(DBCol::ColChunks, chunk.try_to_vec().unwrap()),
+                record(DBCol::ColStateHeaders, state_header.try_to_vec().unwrap()),
+            ],
+            vec![
+                record(
+                    DBCol::ColPartialChunks,
+                    PartialEncodedChunk::V1(partial_chunk).try_to_vec().unwrap(),
+                ),
+                record(
+                    DBCol::ColInvalidChunks,
+                    EncodedShardChunk::V1(invalid_chunk).try_to_vec().unwrap(),
+                ),
+                record(DBCol::ColChunks, ShardChunk::V1(chunk).try_to_vec().unwrap()),
+                record(
+                    DBCol::ColStateHeaders,
+                    ShardStateSyncResponseHeader::V1(state_header).try_to_vec().unwrap(),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_migration_21_to_22() {
+        use near_primitives::block::Block;
+        use near_primitives::epoch_manager::BlockInfoV1;
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::utils::from_timestamp;
+        use near_primitives::version::PROTOCOL_VERSION;
+
+        let block = Block::genesis(
+            PROTOCOL_VERSION,
+            vec![],
+            from_timestamp(1_000_000),
+            0,
+            0,
+            0,
+            CryptoHash::default(),
+        );
+        let header = block.header();
+        let header_key = header.hash().as_ref().to_vec();
+        let block_info = BlockInfoV1 { hash: *header.hash(), height: 5, ..Default::default() };
+        // The old `BlockInfo` is the new one without the timestamp, which is its last field.
+        let old_block_info = |block_info: &BlockInfoV1| {
+            let bytes = block_info.try_to_vec().unwrap();
+            bytes[..bytes.len() - 8].to_vec()
+        };
+        let dummy_info = BlockInfoV1::default();
+        check_migration(
+            &Migration21To22,
+            vec![
+                (DBCol::ColBlockHeader, header_key.clone(), header.try_to_vec().unwrap()),
+                (DBCol::ColBlockInfo, header_key.clone(), old_block_info(&block_info)),
+                (DBCol::ColBlockInfo, vec![0; 32], old_block_info(&dummy_info)),
+            ],
+            vec![
+                (
+                    DBCol::ColBlockInfo,
+                    header_key,
+                    BlockInfoV1 { timestamp_nanosec: header.raw_timestamp(), ..block_info }
+                        .try_to_vec()
+                        .unwrap(),
+                ),
+                (DBCol::ColBlockInfo, vec![0; 32], dummy_info.try_to_vec().unwrap()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_migration_29_to_30() {
+        use near_crypto::{KeyType, PublicKey};
+        use near_primitives::epoch_manager::block_info::{BlockInfo, BlockInfoV1};
+        use near_primitives::epoch_manager::epoch_info::{EpochInfo, EpochInfoV1};
+        use near_primitives::epoch_manager::AGGREGATOR_KEY;
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::types::chunk_extra::{ChunkExtra, ChunkExtraV1};
+        use near_primitives::types::validator_stake::{ValidatorStake, ValidatorStakeV1};
+        use near_primitives::types::{AccountId, EpochId};
+        use std::collections::{BTreeMap, HashMap};
+
+        let stake = ValidatorStakeV1 {
+            account_id: "test".parse().unwrap(),
+            public_key: PublicKey::empty(KeyType::ED25519),
+            stake: 10,
+        };
+        let chunk_extra = ChunkExtraV1 {
+            state_root: CryptoHash::default(),
+            outcome_root: CryptoHash::default(),
+            validator_proposals: vec![stake.clone()],
+            gas_used: 1,
+            gas_limit: 2,
+            balance_burnt: 3,
+        };
+        let block_info = BlockInfoV1 { height: 5, ..Default::default() };
+        let epoch_info = EpochInfoV1 { epoch_height: 7, ..Default::default() };
+        // Epoch summaries and the aggregator only change the encoding of their validator stakes.
+        let no_stats: HashMap<u64, u64> = HashMap::new();
+        let epoch_summary = |proposals: Vec<u8>| {
+            [
+                CryptoHash::default().try_to_vec().unwrap(),
+                proposals,
+                no_stats.try_to_vec().unwrap(),
+                no_stats.try_to_vec().unwrap(),
+                7u32.try_to_vec().unwrap(),
+            ]
+            .concat()
+        };
+        let aggregator = |all_proposals: Vec<u8>| {
+            [
+                no_stats.try_to_vec().unwrap(),
+                no_stats.try_to_vec().unwrap(),
+                no_stats.try_to_vec().unwrap(),
+                all_proposals,
+                EpochId::default().try_to_vec().unwrap(),
+                CryptoHash::default().try_to_vec().unwrap(),
+            ]
+            .concat()
+        };
+        let account_id: AccountId = "test".parse().unwrap();
+        let old_proposals = vec![stake.clone()].try_to_vec().unwrap();
+        let new_proposals = vec![ValidatorStake::V1(stake.clone())].try_to_vec().unwrap();
+        let old_all_proposals: BTreeMap<_, _> = [(account_id.clone(), stake.clone())].into();
+        let new_all_proposals: BTreeMap<_, _> = [(account_id, ValidatorStake::V1(stake))].into();
+        let epoch_key = vec![1; 32];
+        check_migration(
+            &Migration29To30,
+            vec![
+                (DBCol::ColChunkExtra, vec![1], chunk_extra.try_to_vec().unwrap()),
+                (DBCol::ColBlockInfo, vec![2], block_info.try_to_vec().unwrap()),
+                (DBCol::ColEpochValidatorInfo, vec![3], epoch_summary(old_proposals)),
+                (DBCol::ColEpochInfo, epoch_key.clone(), epoch_info.try_to_vec().unwrap()),
+                (
+                    DBCol::ColEpochInfo,
+                    AGGREGATOR_KEY.to_vec(),
+                    aggregator(old_all_proposals.try_to_vec().unwrap()),
+                ),
+            ],
+            vec![
+                (DBCol::ColChunkExtra, vec![1], ChunkExtra::V1(chunk_extra).try_to_vec().unwrap()),
+                (DBCol::ColBlockInfo, vec![2], BlockInfo::V1(block_info).try_to_vec().unwrap()),
+                (DBCol::ColEpochValidatorInfo, vec![3], epoch_summary(new_proposals)),
+                (DBCol::ColEpochInfo, epoch_key, EpochInfo::V1(epoch_info).try_to_vec().unwrap()),
+                (
+                    DBCol::ColEpochInfo,
+                    AGGREGATOR_KEY.to_vec(),
+                    aggregator(new_all_proposals.try_to_vec().unwrap()),
+                ),
+            ],
+        );
+    }
+
+    /// Runs the legacy migration `run` against a database filled with `fixture` and checks that
+    /// afterwards `columns` contain exactly `expected`.
+    fn check_legacy_migration(
+        from_version: DbVersion,
+        run: impl Fn(&Path),
+        columns: &[DBCol],
+        fixture: Records,
+        expected: Records,
+    ) {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration").tempdir().unwrap();
+        write_records(&create_store(tmp_dir.path()), &fixture, from_version);
+        run(tmp_dir.path());
+        assert_eq!(get_store_version(tmp_dir.path()), from_version + 1);
+        let store = create_store(tmp_dir.path());
+        assert_eq!(read_records(&store, columns), sorted(columns, expected));
+    }
+
+    // Migrations 12 -> 13, 18 -> 19, 19 -> 20, 22 -> 23 and 23 -> 24 re-apply blocks of the
+    // mainnet history with the runtime of the node or write receipts restored on mainnet, so
+    // they need the genesis and the runtime and aren't covered by fixtures here. Migration
+    // 24 -> 25 is tested in `nearcore`, where it is defined.
+
+    /// A chunk of the format of the legacy migrations.
+    fn chunk_v1(
+        transactions: Vec<near_primitives::transaction::SignedTransaction>,
+        receipts: Vec<near_primitives::receipt::Receipt>,
+    ) -> near_primitives::sharding::ShardChunkV1 {
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::sharding::{ShardChunkHeaderV1, ShardChunkV1};
+        use near_primitives::validator_signer::EmptyValidatorSigner;
+
+        let header = ShardChunkHeaderV1::new(
+            CryptoHash::default(),
+            CryptoHash::default(),
+            CryptoHash::default(),
+            CryptoHash::default(),
+            1,
+            2,
+            0,
+            0,
+            1000,
+            0,
+            CryptoHash::default(),
+            CryptoHash::default(),
+            vec![],
+            &EmptyValidatorSigner::default(),
+        );
+        ShardChunkV1 { chunk_hash: header.chunk_hash(), header, transactions, receipts }
+    }
+
+    fn chunk_record(chunk: &near_primitives::sharding::ShardChunkV1) -> (DBCol, Vec<u8>, Vec<u8>) {
+        (DBCol::ColChunks, chunk.chunk_hash.as_ref().to_vec(), chunk.try_to_vec().unwrap())
+    }
+
+    #[test]
+    fn test_migration_2_to_3() {
+        use near_primitives::hash::hash;
+        use near_primitives::transaction::{
+            ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof,
+        };
+        use std::collections::HashSet;
+
+        let block_hash = hash(&[1]);
+        let outcome = |id: u8| ExecutionOutcomeWithIdAndProof {
+            block_hash,
+            outcome_with_id: ExecutionOutcomeWithId { id: hash(&[id]), ..Default::default() },
+            ..Default::default()
+        };
+        let outcome_record = |id: u8| {
+            (
+                DBCol::ColTransactionResult,
+                hash(&[id]).as_ref().to_vec(),
+                outcome(id).try_to_vec().unwrap(),
+            )
+        };
+        let outcome_ids = |ids: &[u8]| {
+            let ids: HashSet<_> = ids.iter().map(|id| hash(&[*id])).collect();
+            (DBCol::ColOutcomeIds, block_hash.as_ref().to_vec(), ids.try_to_vec().unwrap())
+        };
+        // Outcomes are added to the ids already stored for the block.
+        check_legacy_migration(
+            2,
+            |path| {
+                let store = create_store(path);
+                fill_col_outcomes_by_hash(&store);
+                set_store_version(&store, 3);
+            },
+            &[DBCol::ColTransactionResult, DBCol::ColOutcomeIds],
+            vec![outcome_record(2), outcome_record(3), outcome_ids(&[4])],
+            vec![outcome_record(2), outcome_record(3), outcome_ids(&[2, 3, 4])],
+        );
+    }
+
+    #[test]
+    fn test_migration_3_to_4() {
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::transaction::SignedTransaction;
+
+        let tx = SignedTransaction::empty(CryptoHash::default());
+        let chunk = chunk_v1(vec![tx.clone()], vec![]);
+        check_legacy_migration(
+            3,
+            |path| {
+                let store = create_store(path);
+                fill_col_transaction_refcount(&store);
+                set_store_version(&store, 4);
+            },
+            &[DBCol::ColChunks, DBCol::_ColTransactionRefCount],
+            vec![chunk_record(&chunk)],
+            vec![
+                chunk_record(&chunk),
+                (
+                    DBCol::_ColTransactionRefCount,
+                    tx.get_hash().as_ref().to_vec(),
+                    1u64.try_to_vec().unwrap(),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_migration_6_to_7() {
+        use crate::db::refcount::encode_value_with_rc;
+        use near_primitives::hash::{hash, CryptoHash};
+        use near_primitives::receipt::Receipt;
+        use near_primitives::transaction::SignedTransaction;
+        use near_primitives::types::AccountId;
+
+        let tx = SignedTransaction::empty(CryptoHash::default());
+        let tx_key = tx.get_hash().as_ref().to_vec();
+        let tx_bytes = tx.try_to_vec().unwrap();
+        let receiver_id: AccountId = "alice.near".parse().unwrap();
+        let receipts = [
+            Receipt::new_balance_refund(&receiver_id, 1),
+            Receipt::new_balance_refund(&receiver_id, 2),
+        ];
+        let receipt_key = |i: usize| receipts[i].receipt_id.as_ref().to_vec();
+        let chunk = chunk_v1(vec![tx], receipts.to_vec());
+        let shard_id = 0u64.try_to_vec().unwrap();
+        check_legacy_migration(
+            6,
+            migrate_6_to_7,
+            &[
+                DBCol::ColState,
+                DBCol::ColTransactions,
+                DBCol::_ColTransactionRefCount,
+                DBCol::ColReceiptIdToShardId,
+                DBCol::ColChunks,
+            ],
+            vec![
+                // The refcount of `ColState` grows from 4 to 8 bytes, values too short to have
+                // one are dropped.
+                (DBCol::ColState, vec![1], vec![7, 1, 0, 0, 0]),
+                (DBCol::ColState, vec![2], vec![7]),
+                (DBCol::ColTransactions, tx_key.clone(), tx_bytes.clone()),
+                (DBCol::_ColTransactionRefCount, tx_key.clone(), 2u64.try_to_vec().unwrap()),
+                (DBCol::ColReceiptIdToShardId, receipt_key(0), shard_id.clone()),
+                // Receipts which aren't in any chunk are dropped.
+                (DBCol::ColReceiptIdToShardId, hash(&[3]).as_ref().to_vec(), shard_id.clone()),
+                chunk_record(&chunk),
+            ],
+            vec![
+                (DBCol::ColState, vec![1], vec![7, 1, 0, 0, 0, 0, 0, 0, 0]),
+                (DBCol::ColTransactions, tx_key, encode_value_with_rc(&tx_bytes, 2)),
+                (DBCol::ColReceiptIdToShardId, receipt_key(0), encode_value_with_rc(&shard_id, 1)),
+                (DBCol::ColReceiptIdToShardId, receipt_key(1), encode_value_with_rc(&shard_id, 1)),
+                chunk_record(&chunk),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_migration_7_to_8() {
+        let header = (DBCol::ColStateHeaders, vec![1], vec![1]);
+        check_legacy_migration(
+            7,
+            migrate_7_to_8,
+            &[DBCol::ColStateParts, DBCol::ColStateHeaders],
+            vec![
+                (DBCol::ColStateParts, vec![1], vec![1]),
+                (DBCol::ColStateParts, vec![2], vec![2]),
+                header.clone(),
+            ],
+            vec![header],
+        );
+    }
+
+    #[test]
+    fn test_migration_8_to_9() {
+        use crate::db::refcount::encode_value_with_rc;
+        use near_primitives::hash::{hash, CryptoHash};
+        use near_primitives::receipt::Receipt;
+        use near_primitives::transaction::SignedTransaction;
+
+        let tx = SignedTransaction::empty(CryptoHash::default());
+        let tx_bytes = tx.try_to_vec().unwrap();
+        let chunk = chunk_v1(vec![tx.clone()], vec![]);
+        let receipt = Receipt::new_balance_refund(&"alice.near".parse().unwrap(), 1);
+        let outgoing_receipts =
+            (DBCol::ColOutgoingReceipts, vec![1], vec![receipt.clone()].try_to_vec().unwrap());
+        let shard_id = 0u64.try_to_vec().unwrap();
+        // Both columns are recomputed from scratch, so stale records are dropped and refcounts
+        // are fixed.
+        check_legacy_migration(
+            8,
+            migrate_8_to_9,
+            &[
+                DBCol::ColTransactions,
+                DBCol::ColReceiptIdToShardId,
+                DBCol::ColChunks,
+                DBCol::ColOutgoingReceipts,
+            ],
+            vec![
+                (
+                    DBCol::ColTransactions,
+                    tx.get_hash().as_ref().to_vec(),
+                    encode_value_with_rc(&tx_bytes, 3),
+                ),
+                (
+                    DBCol::ColTransactions,
+                    hash(&[1]).as_ref().to_vec(),
+                    encode_value_with_rc(&[1], 1),
+                ),
+                (
+                    DBCol::ColReceiptIdToShardId,
+                    hash(&[2]).as_ref().to_vec(),
+                    encode_value_with_rc(&shard_id, 1),
+                ),
+                chunk_record(&chunk),
+                outgoing_receipts.clone(),
+            ],
+            vec![
+                (
+                    DBCol::ColTransactions,
+                    tx.get_hash().as_ref().to_vec(),
+                    encode_value_with_rc(&tx_bytes, 1),
+                ),
+                (
+                    DBCol::ColReceiptIdToShardId,
+                    receipt.receipt_id.as_ref().to_vec(),
+                    encode_value_with_rc(&shard_id, 1),
+                ),
+                chunk_record(&chunk),
+                outgoing_receipts,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_migration_9_to_10() {
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::sharding::PartialEncodedChunkV1;
+        use near_primitives::transaction::SignedTransaction;
+
+        let chunk = chunk_v1(vec![SignedTransaction::empty(CryptoHash::default())], vec![]);
+        let columns = [DBCol::ColChunks, DBCol::ColPartialChunks];
+        // Partial chunks are only added on archival nodes.
+        check_legacy_migration(
+            9,
+            |path| migrate_9_to_10(path, false),
+            &columns,
+            vec![chunk_record(&chunk)],
+            vec![chunk_record(&chunk)],
+        );
+
+        // Encoding the chunk isn't reproduced here, only the shape of the partial chunk is checked.
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration").tempdir().unwrap();
+        write_records(&create_store(tmp_dir.path()), &[chunk_record(&chunk)], 9);
+        migrate_9_to_10(tmp_dir.path(), true);
+        assert_eq!(get_store_version(tmp_dir.path()), 10);
+        let store = create_store(tmp_dir.path());
+        let partial_chunk = store
+            .get_ser::<PartialEncodedChunkV1>(DBCol::ColPartialChunks, chunk.chunk_hash.as_ref())
+            .unwrap()
+            .unwrap();
+        assert_eq!(partial_chunk.header, chunk.header);
+        assert_eq!(partial_chunk.parts.len(), 100);
+        assert_eq!(partial_chunk.receipts.len(), 1);
+    }
+
+    #[test]
+    fn test_migration_10_to_11() {
+        use near_primitives::block::{Block, Tip};
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::utils::from_timestamp;
+        use near_primitives::version::PROTOCOL_VERSION;
+
+        let block = Block::genesis(
+            PROTOCOL_VERSION,
+            vec![],
+            from_timestamp(0),
+            3,
+            0,
+            0,
+            CryptoHash::default(),
+        );
+        let header = block.header();
+        let tip = Tip::from_header(header).try_to_vec().unwrap();
+        let header_record =
+            (DBCol::ColBlockHeader, header.hash().as_ref().to_vec(), header.try_to_vec().unwrap());
+        let head = (DBCol::ColBlockMisc, HEAD_KEY.to_vec(), tip.clone());
+        // Without a final block the final head is the first block of the chain.
+        check_legacy_migration(
+            10,
+            migrate_10_to_11,
+            &[DBCol::ColBlockMisc, DBCol::ColBlockHeader],
+            vec![header_record.clone(), head.clone()],
+            vec![header_record, head, (DBCol::ColBlockMisc, FINAL_HEAD_KEY.to_vec(), tip)],
+        );
+    }
+
+    #[test]
+    fn test_migration_11_to_12() {
+        use crate::db::refcount::encode_value_with_rc;
+        use near_primitives::hash::hash;
+        use near_primitives::receipt::Receipt;
+
+        let receipt = Receipt::new_balance_refund(&"alice.near".parse().unwrap(), 1);
+        let chunk = chunk_v1(vec![], vec![receipt.clone()]);
+        check_legacy_migration(
+            11,
+            migrate_11_to_12,
+            &[DBCol::ColReceipts, DBCol::ColChunks],
+            vec![
+                (DBCol::ColReceipts, hash(&[1]).as_ref().to_vec(), encode_value_with_rc(&[1], 1)),
+                chunk_record(&chunk),
+            ],
+            vec![
+                (
+                    DBCol::ColReceipts,
+                    receipt.receipt_id.as_ref().to_vec(),
+                    encode_value_with_rc(&receipt.try_to_vec().unwrap(), 1),
+                ),
+                chunk_record(&chunk),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_migration_14_to_15() {
+        use near_crypto::EmptySigner;
+        use near_primitives::block::Block;
+        use near_primitives::epoch_manager::epoch_info::EpochInfoV1;
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::sharding::{EncodedShardChunk, ReedSolomonWrapper};
+        use near_primitives::transaction::SignedTransaction;
+        use near_primitives::types::EpochId;
+        use near_primitives::utils::{from_timestamp, get_block_shard_id};
+        use near_primitives::validator_signer::EmptyValidatorSigner;
+        use near_primitives::version::PROTOCOL_VERSION;
+        use std::collections::HashSet;
+
+        use crate::Trie;
+
+        let tx = SignedTransaction::from_actions(
+            1,
+            "alice.near".parse().unwrap(),
+            "bob.near".parse().unwrap(),
+            &EmptySigner {},
+            vec![],
+            CryptoHash::default(),
+        );
+        let (encoded_chunk, _) = EncodedShardChunk::new(
+            CryptoHash::default(),
+            Trie::empty_root(),
+            CryptoHash::default(),
+            0,
+            0,
+            &mut ReedSolomonWrapper::new(1, 2),
+            0,
+            1000,
+            0,
+            CryptoHash::default(),
+            vec![],
+            vec![tx.clone()],
+            &vec![],
+            CryptoHash::default(),
+            &EmptyValidatorSigner::default(),
+            PROTOCOL_VERSION,
+        )
+        .unwrap();
+        let mut chunk = encoded_chunk.decode_chunk(1).unwrap();
+        chunk.set_height_included(0);
+        let block = Block::genesis(
+            PROTOCOL_VERSION,
+            vec![chunk.cloned_header()],
+            from_timestamp(0),
+            0,
+            0,
+            0,
+            CryptoHash::default(),
+        );
+        let block_hash = *block.hash();
+        let unchanged = vec![
+            (DBCol::ColBlock, block_hash.as_ref().to_vec(), block.try_to_vec().unwrap()),
+            (DBCol::ColChunks, chunk.chunk_hash().as_ref().to_vec(), chunk.try_to_vec().unwrap()),
+            (
+                DBCol::ColEpochInfo,
+                EpochId::default().as_ref().to_vec(),
+                EpochInfoV1::default().try_to_vec().unwrap(),
+            ),
+        ];
+        let outcome_ids: HashSet<_> = [tx.get_hash()].into();
+        // The unordered outcome ids of a block become ordered outcome ids of each of its chunks.
+        check_legacy_migration(
+            14,
+            migrate_14_to_15,
+            &[DBCol::ColOutcomeIds, DBCol::ColBlock, DBCol::ColChunks, DBCol::ColEpochInfo],
+            unchanged
+                .iter()
+                .cloned()
+                .chain(std::iter::once((
+                    DBCol::ColOutcomeIds,
+                    block_hash.as_ref().to_vec(),
+                    outcome_ids.try_to_vec().unwrap(),
+                )))
+                .collect(),
+            unchanged
+                .into_iter()
+                .chain(std::iter::once((
+                    DBCol::ColOutcomeIds,
+                    get_block_shard_id(&block_hash, 0),
+                    vec![tx.get_hash()].try_to_vec().unwrap(),
+                )))
+                .collect(),
+        );
+    }
+
+    #[test]
+    fn test_migration_17_to_18() {
+        use near_primitives::challenge::SlashedValidator;
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::types::validator_stake::ValidatorStakeV1;
+        use near_primitives::types::EpochId;
+
+        let old_block_info = [
+            5u64.try_to_vec().unwrap(),
+            4u64.try_to_vec().unwrap(),
+            CryptoHash::default().try_to_vec().unwrap(),
+            CryptoHash::default().try_to_vec().unwrap(),
+            CryptoHash::default().try_to_vec().unwrap(),
+            EpochId::default().try_to_vec().unwrap(),
+            Vec::<ValidatorStakeV1>::new().try_to_vec().unwrap(),
+            vec![true].try_to_vec().unwrap(),
+            40u32.try_to_vec().unwrap(),
+            Vec::<SlashedValidator>::new().try_to_vec().unwrap(),
+            100u128.try_to_vec().unwrap(),
+        ]
+        .concat();
+        let key = vec![3; 32];
+        // The hash of the block, which is the key of the record, is prepended.
+        check_legacy_migration(
+            17,
+            migrate_17_to_18,
+            &[DBCol::ColBlockInfo],
+            vec![(DBCol::ColBlockInfo, key.clone(), old_block_info.clone())],
+            vec![(DBCol::ColBlockInfo, key.clone(), [key, old_block_info].concat())],
+        );
+    }
+
+    #[test]
+    fn test_migration_26_to_27() {
+        use near_primitives::hash::hash;
+        use near_primitives::merkle::PartialMerkleTree;
+        use near_primitives::utils::index_to_bytes;
+
+        let block_hash = hash(&[1]);
+        let mut tree = PartialMerkleTree::default();
+        tree.insert(hash(&[0]));
+        let fixture = vec![
+            (DBCol::ColBlockHeight, index_to_bytes(1), block_hash.try_to_vec().unwrap()),
+            (DBCol::ColBlockMerkleTree, block_hash.as_ref().to_vec(), tree.try_to_vec().unwrap()),
+        ];
+        let columns = [DBCol::ColBlockHeight, DBCol::ColBlockMerkleTree, DBCol::ColBlockOrdinal];
+        // Block ordinals are only added on archival nodes.
+        check_legacy_migration(
+            26,
+            |path| migrate_26_to_27(path, false),
+            &columns,
+            fixture.clone(),
+            fixture.clone(),
+        );
+        let ordinal = (DBCol::ColBlockOrdinal, index_to_bytes(1), block_hash.try_to_vec().unwrap());
+        check_legacy_migration(
+            26,
+            |path| migrate_26_to_27(path, true),
+            &columns,
+            fixture.clone(),
+            fixture.into_iter().chain(std::iter::once(ordinal)).collect(),
+        );
+    }
+
+    #[test]
+    fn test_migration_30_to_31() {
+        use crate::test_utils::test_populate_trie;
+        use crate::trie::flat_state;
+        use crate::{ShardTries, Trie};
+        use near_primitives::block::{Block, Tip};
+        use near_primitives::hash::CryptoHash;
+        use near_primitives::shard_layout::{get_block_shard_uid, ShardUId};
+        use near_primitives::trie_key::TrieKey;
+        use near_primitives::types::chunk_extra::ChunkExtra;
+        use near_primitives::utils::from_timestamp;
+        use near_primitives::version::PROTOCOL_VERSION;
+        use std::sync::Arc;
+
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration").tempdir().unwrap();
+        let shard_uid = ShardUId::single_shard();
+        let key = TrieKey::ContractData { account_id: "alice.near".parse().unwrap(), key: vec![1] }
+            .to_vec();
+        let root = {
+            let store = create_store(tmp_dir.path());
+            let tries = ShardTries::new(Arc::new(store.clone()), 0, 1);
+            let root = test_populate_trie(
+                &tries,
+                &Trie::empty_root(),
+                shard_uid,
+                vec![(key.clone(), Some(vec![2]))],
+            );
+            let block = Block::genesis(
+                PROTOCOL_VERSION,
+                vec![],
+                from_timestamp(0),
+                0,
+                0,
+                0,
+                CryptoHash::default(),
+            );
+            let header = block.header();
+            write_records(
+                &store,
+                &[
+                    (
+                        DBCol::ColBlockMisc,
+                        HEAD_KEY.to_vec(),
+                        Tip::from_header(header).try_to_vec().unwrap(),
+                    ),
+                    (
+                        DBCol::ColChunkExtra,
+                        get_block_shard_uid(header.hash(), &shard_uid),
+                        ChunkExtra::new_with_only_state_root(&root).try_to_vec().unwrap(),
+                    ),
+                ],
+                30,
+            );
+            root
+        };
+
+        // The flat state is built at the state after the head block.
+        migrate_30_to_31(tmp_dir.path());
+        assert_eq!(get_store_version(tmp_dir.path()), 31);
+        let store = create_store(tmp_dir.path());
+        assert_eq!(flat_state::get_head(&store, shard_uid).unwrap(), Some(root));
+        let trie = ShardTries::new(Arc::new(store), 0, 1).get_trie_for_shard(shard_uid);
+        assert_eq!(trie.get(&root, &key).unwrap(), Some(vec![2]));
+        assert_eq!(trie.counter.get(), 0);
+    }
+
+    #[test]
+    fn test_version_bump() {
+        check_migration(&VersionBump { from_version: 1, description: "" }, vec![], vec![]);
+    }
+
+    /// Records the keys it is called with and doubles every value.
+    struct Doubling {
+        visited: RefCell<Vec<(DBCol, Vec<u8>)>>,
+    }
+
+    impl Migration for Doubling {
+        fn from_version(&self) -> DbVersion {
+            1
+        }
+
+        fn description(&self) -> &'static str {
+            "double values"
+        }
+
+        fn columns(&self) -> &'static [DBCol] {
+            &[DBCol::ColBlock, DBCol::ColChunks]
+        }
+
+        fn migrate_record(
+            &self,
+            _: &Store,
+            col: DBCol,
+            key: &[u8],
+            value: &[u8],
+        ) -> io::Result<RecordChange> {
+            self.visited.borrow_mut().push((col, key.to_vec()));
+            Ok(RecordChange::Set(value.iter().map(|v| v * 2).collect()))
+        }
+    }
+
+    #[test]
+    fn test_resume_visits_each_record_once() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration_resume").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let fixture: Records = (0..5u8)
+            .map(|i| (DBCol::ColBlock, vec![i], vec![i]))
+            .chain((0..3u8).map(|i| (DBCol::ColChunks, vec![i], vec![i])))
+            .collect();
+        write_records(&store, &fixture, 1);
+
+        let migration = Doubling { visited: RefCell::new(vec![]) };
+        let mut steps = 0;
+        // Every step starts with a new runner, as after a restart of the node. Every change
+        // takes 10 bytes, so a batch holds 3 records.
+        while !MigrationRunner::new(&store, &migration, 30).step().unwrap() {
+            steps += 1;
+        }
+        assert_eq!(steps, 2);
+        let visited: Vec<_> = fixture.iter().map(|(col, key, _)| (*col, key.clone())).collect();
+        assert_eq!(migration.visited.into_inner(), visited);
+        let expected: Records =
+            fixture.into_iter().map(|(col, key, value)| (col, key, vec![value[0] * 2])).collect();
+        assert_eq!(read_records(&store, &[DBCol::ColBlock, DBCol::ColChunks]), expected);
+        assert_eq!(get_store_version_from_store(&store), 2);
+    }
+
+    #[test]
+    fn test_registry_apply() {
+        let tmp_dir =
+            tempfile::Builder::new().prefix("_test_migration_registry").tempdir().unwrap();
+        {
+            let store = create_store(tmp_dir.path());
+            write_records(&store, &[(DBCol::ColBlock, vec![1], vec![2])], 1);
+        }
+        let legacy_runs = RefCell::new(0);
+        let mut registry = MigrationRegistry::new();
+        registry.register(Doubling { visited: RefCell::new(vec![]) });
+        registry.register_legacy(2, "legacy", &[], |path| {
+            *legacy_runs.borrow_mut() += 1;
+            set_store_version(&create_store(path), 3);
+        });
+        registry.register(VersionBump { from_version: 3, description: "bump" });
+        assert_eq!(registry.latest_version(), Some(4));
+
+        let report = registry.dry_run(tmp_dir.path()).unwrap().unwrap();
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.visited, vec![(DBCol::ColBlock, 1), (DBCol::ColChunks, 0)]);
+        assert_eq!(report.changed, 1);
+
+        registry.apply(tmp_dir.path()).unwrap();
+        assert_eq!(*legacy_runs.borrow(), 1);
+        assert_eq!(get_store_version(tmp_dir.path()), 4);
+        assert_eq!(registry.dry_run(tmp_dir.path()).unwrap(), None);
+        let store = create_store(tmp_dir.path());
+        assert_eq!(store.get(DBCol::ColBlock, &[1]).unwrap(), Some(vec![4]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Migrations must be registered in the order of versions")]
+    fn test_registry_order() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(VersionBump { from_version: 1, description: "" });
+        registry.register(VersionBump { from_version: 3, description: "" });
+    }
+}
@@ -0,0 +1,696 @@
+//! Flat storage of the state: the value of every trie key in the latest state of a shard.
+//!
+//! `ColFlatState` maps `shard_uid || trie_key` to the value of the key in the state whose root
+//! is stored under the bare `shard_uid` key, the flat state head. Reading a value of the head
+//! state takes a single lookup instead of a walk from the trie root; reads of any other state
+//! fall back to the trie.
+//!
+//! The flat state follows the chain as [`WrappedTrieChanges`](crate::WrappedTrieChanges) are
+//! saved. Every step stores a [`FlatStateDelta`] in `ColFlatStateDeltas` which allows to move
+//! the flat state back to the parent state when the chain switches to another fork, and to find
+//! the keys to re-read when it switches back to a fork which was already applied. A delta is
+//! removed together with the trie changes of its block, or once its block is more than
+//! [`MAX_FORK_DELTAS`] blocks below the final head.
+//!
+//! If the flat state can't be moved to the parent state of a chunk with the stored deltas, the
+//! block only stores its delta and the flat state catches up with the chain in a background
+//! thread, see [`catch_up`]. Reads of the shard go through the trie in the meantime.
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use tracing::{debug, info};
+
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::types::{RawStateChangesWithTrieKey, StateRoot};
+
+use crate::db::DBCol;
+use crate::trie::iterator::TrieDiffIterator;
+use crate::trie::{TrieChanges, POISONED_LOCK_ERR};
+use crate::{StorageError, Store, StoreUpdate, Trie};
+
+/// How many deltas can be walked from the flat state head, and from the parent state of a chunk,
+/// to find the state where their forks split. When there is none, the flat state catches up in
+/// the background. Deltas of older final blocks are never walked and can be pruned.
+pub const MAX_FORK_DELTAS: usize = 64;
+
+/// Size in bytes of the batches in which the flat state of a shard is rebuilt or caught up.
+const REBUILD_BATCH_SIZE: usize = 10_000_000;
+
+/// Changes needed to move the flat state of a shard back to the previous state.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct FlatStateDelta {
+    /// Root of the state this delta reverts to.
+    pub old_root: StateRoot,
+    /// Values of the changed keys in the state with `old_root`.
+    pub prev_values: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+/// Shards whose flat state is catching up with the chain, with the root of the state it's moved
+/// to. The block path never moves the flat state of these shards, it only stores the deltas.
+#[derive(Default)]
+pub(crate) struct FlatStateCatchups(Mutex<HashMap<ShardUId, StateRoot>>);
+
+impl FlatStateCatchups {
+    /// Sets the state the flat state of the shard catches up with. Returns `true` if there was
+    /// no catch-up of the shard in progress, the caller must run [`catch_up`] then.
+    pub(crate) fn start(&self, shard_uid: ShardUId, state_root: StateRoot) -> bool {
+        self.0.lock().expect(POISONED_LOCK_ERR).insert(shard_uid, state_root).is_none()
+    }
+
+    pub(crate) fn is_running(&self, shard_uid: ShardUId) -> bool {
+        self.0.lock().expect(POISONED_LOCK_ERR).contains_key(&shard_uid)
+    }
+
+    fn target(&self, shard_uid: ShardUId) -> Option<StateRoot> {
+        self.0.lock().expect(POISONED_LOCK_ERR).get(&shard_uid).cloned()
+    }
+
+    /// Ends the catch-up of the shard if it wasn't moved to another state meanwhile.
+    fn finish(&self, shard_uid: ShardUId, state_root: &StateRoot) -> bool {
+        let mut catchups = self.0.lock().expect(POISONED_LOCK_ERR);
+        if catchups.get(&shard_uid) != Some(state_root) {
+            return false;
+        }
+        catchups.remove(&shard_uid);
+        true
+    }
+
+    fn abort(&self, shard_uid: ShardUId) {
+        self.0.lock().expect(POISONED_LOCK_ERR).remove(&shard_uid);
+    }
+}
+
+fn head_key(shard_uid: ShardUId) -> [u8; 8] {
+    shard_uid.to_bytes()
+}
+
+fn value_key(shard_uid: ShardUId, trie_key: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(8 + trie_key.len());
+    key.extend_from_slice(&shard_uid.to_bytes());
+    key.extend_from_slice(trie_key);
+    key
+}
+
+fn delta_key(shard_uid: ShardUId, state_root: &StateRoot) -> [u8; 40] {
+    let mut key = [0; 40];
+    key[0..8].copy_from_slice(&shard_uid.to_bytes());
+    key[8..].copy_from_slice(state_root.as_ref());
+    key
+}
+
+fn storage_error(_err: std::io::Error) -> StorageError {
+    StorageError::StorageInternalError
+}
+
+/// Reader of the flat state of one shard.
+#[derive(Clone)]
+pub struct FlatState {
+    store: Arc<Store>,
+    shard_uid: ShardUId,
+    /// Snapshot of the store and the flat state head in it, reused while reads are of the head
+    /// state. Values of the head state in a snapshot never change, even when it's outdated.
+    snapshot: Arc<Mutex<Option<(Store, Option<StateRoot>)>>>,
+}
+
+impl FlatState {
+    /// Returns `None` if the store can't read the head and the value atomically, which is only
+    /// supported by RocksDB snapshots. Tries of such stores always read from the trie.
+    pub(crate) fn new(store: Arc<Store>, shard_uid: ShardUId) -> Option<Self> {
+        if !store.supports_snapshots() {
+            return None;
+        }
+        Some(FlatState { store, shard_uid, snapshot: Default::default() })
+    }
+
+    /// Returns the value of `key` in the state with `root`, or `None` if the flat state of the
+    /// shard is not at `root`.
+    pub(crate) fn get(
+        &self,
+        root: &StateRoot,
+        key: &[u8],
+    ) -> Result<Option<Option<Vec<u8>>>, StorageError> {
+        let mut guard = self.snapshot.lock().expect(POISONED_LOCK_ERR);
+        if guard.as_ref().map_or(true, |(_, head)| head.as_ref() != Some(root)) {
+            // Reads of other states only cost a head lookup, a snapshot is taken once the flat
+            // state moves to `root`.
+            if get_head(&self.store, self.shard_uid)?.as_ref() != Some(root) {
+                return Ok(None);
+            }
+            let snapshot = self.store.snapshot();
+            let head = get_head(&snapshot, self.shard_uid)?;
+            *guard = Some((snapshot, head));
+        }
+        match &*guard {
+            Some((snapshot, Some(head))) if head == root => snapshot
+                .get(DBCol::ColFlatState, &value_key(self.shard_uid, key))
+                .map(Some)
+                .map_err(storage_error),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Returns the root of the state the flat state of the shard is at.
+pub fn get_head(store: &Store, shard_uid: ShardUId) -> Result<Option<StateRoot>, StorageError> {
+    store.get_ser(DBCol::ColFlatState, &head_key(shard_uid)).map_err(storage_error)
+}
+
+/// Moves the flat state of the shard from `trie_changes.old_root` to `trie_changes.new_root`.
+///
+/// `state_changes` must hold all the changes between the two states. If the flat state is at
+/// another fork, it is first moved to `old_root`: the deltas from the head back to the state
+/// where the forks split are reverted and the keys changed on the way from that state to
+/// `old_root` are read from the trie. If there is no such state within [`MAX_FORK_DELTAS`]
+/// deltas, or the flat state of the shard is catching up, only the delta is stored, and the
+/// flat state has to catch up with `old_root`. Returns `true` if a new catch-up must be started
+/// with [`catch_up`]. Only writes to `store_update`, and does nothing for stores which never
+/// read from the flat state.
+pub(crate) fn update(
+    store: &Store,
+    trie: &Trie,
+    shard_uid: ShardUId,
+    catchups: &FlatStateCatchups,
+    trie_changes: &TrieChanges,
+    state_changes: &[RawStateChangesWithTrieKey],
+    store_update: &mut StoreUpdate,
+) -> Result<bool, StorageError> {
+    let TrieChanges { old_root, new_root, .. } = *trie_changes;
+    if old_root == new_root || !store.supports_snapshots() {
+        return Ok(false);
+    }
+    // Values of the keys which differ from the flat state stored in the database.
+    let values = if catchups.is_running(shard_uid) {
+        None
+    } else {
+        match get_head(store, shard_uid)? {
+            Some(head) if head == new_root => return Ok(false),
+            Some(head) => values_at_parent(store, trie, shard_uid, head, old_root)?,
+            None => None,
+        }
+    };
+
+    let mut prev_values = Vec::with_capacity(state_changes.len());
+    let start_catch_up = match values {
+        Some(mut values) => {
+            for change in state_changes {
+                let key = change.trie_key.to_vec();
+                let prev_value = match values.get(&key) {
+                    Some(value) => value.clone(),
+                    None => store
+                        .get(DBCol::ColFlatState, &value_key(shard_uid, &key))
+                        .map_err(storage_error)?,
+                };
+                let value = change
+                    .changes
+                    .last()
+                    .expect("State changes of a key always have at least one change")
+                    .data
+                    .clone();
+                prev_values.push((key.clone(), prev_value));
+                values.insert(key, value);
+            }
+
+            for (key, value) in values {
+                match value {
+                    Some(value) => {
+                        store_update.set(DBCol::ColFlatState, &value_key(shard_uid, &key), &value)
+                    }
+                    None => store_update.delete(DBCol::ColFlatState, &value_key(shard_uid, &key)),
+                }
+            }
+            store_update
+                .set_ser(DBCol::ColFlatState, &head_key(shard_uid), &new_root)
+                .map_err(storage_error)?;
+            false
+        }
+        None => {
+            for change in state_changes {
+                let key = change.trie_key.to_vec();
+                let prev_value = trie.get_from_trie(&old_root, &key)?;
+                prev_values.push((key, prev_value));
+            }
+            // `old_root` is already stored, unlike `new_root` which is written by `store_update`.
+            let start_catch_up = catchups.start(shard_uid, old_root);
+            if start_catch_up {
+                debug!(
+                    target: "store",
+                    "Flat state of shard {} can't be moved to {}, catching up",
+                    shard_uid,
+                    old_root
+                );
+            }
+            start_catch_up
+        }
+    };
+    store_update
+        .set_ser(
+            DBCol::ColFlatStateDeltas,
+            &delta_key(shard_uid, &new_root),
+            &FlatStateDelta { old_root, prev_values },
+        )
+        .map_err(storage_error)?;
+    Ok(start_catch_up)
+}
+
+/// Returns up to [`MAX_FORK_DELTAS`] deltas on the way from `root` back to its ancestors, the
+/// first one reverts `root`.
+fn ancestor_deltas(
+    store: &Store,
+    shard_uid: ShardUId,
+    mut root: StateRoot,
+) -> Result<Vec<(StateRoot, FlatStateDelta)>, StorageError> {
+    let mut deltas = vec![];
+    while deltas.len() < MAX_FORK_DELTAS {
+        let delta = store
+            .get_ser::<FlatStateDelta>(DBCol::ColFlatStateDeltas, &delta_key(shard_uid, &root))
+            .map_err(storage_error)?;
+        match delta {
+            Some(delta) => {
+                let old_root = delta.old_root;
+                deltas.push((root, delta));
+                root = old_root;
+            }
+            None => break,
+        }
+    }
+    Ok(deltas)
+}
+
+/// Returns the values of the keys which differ between the flat state at `head` and the state
+/// with `parent_root`, or `None` if the state where their forks split isn't found.
+fn values_at_parent(
+    store: &Store,
+    trie: &Trie,
+    shard_uid: ShardUId,
+    head: StateRoot,
+    parent_root: StateRoot,
+) -> Result<Option<HashMap<Vec<u8>, Option<Vec<u8>>>>, StorageError> {
+    let head_deltas = ancestor_deltas(store, shard_uid, head)?;
+    let head_ancestors: HashSet<StateRoot> =
+        std::iter::once(head).chain(head_deltas.iter().map(|(_, delta)| delta.old_root)).collect();
+    let parent_deltas = ancestor_deltas(store, shard_uid, parent_root)?;
+    let fork_root = match std::iter::once(parent_root)
+        .chain(parent_deltas.iter().map(|(_, delta)| delta.old_root))
+        .find(|root| head_ancestors.contains(root))
+    {
+        Some(fork_root) => fork_root,
+        None => return Ok(None),
+    };
+
+    let mut values = HashMap::new();
+    // Older deltas are applied later, so they overwrite the values of newer ones.
+    for (_, delta) in head_deltas.into_iter().take_while(|(root, _)| *root != fork_root) {
+        values.extend(delta.prev_values);
+    }
+    // Keys changed between the fork root and `parent_root` get their values in `parent_root`.
+    let mut changed_keys = HashSet::new();
+    for (_, delta) in parent_deltas.into_iter().take_while(|(root, _)| *root != fork_root) {
+        changed_keys.extend(delta.prev_values.into_iter().map(|(key, _)| key));
+    }
+    for key in changed_keys {
+        let value = trie.get_from_trie(&parent_root, &key)?;
+        values.insert(key, value);
+    }
+    Ok(Some(values))
+}
+
+/// Removes the delta which reverts the state with `trie_changes.new_root`, once the trie
+/// changes of its block are garbage collected.
+pub(crate) fn remove_delta(
+    shard_uid: ShardUId,
+    trie_changes: &TrieChanges,
+    store_update: &mut StoreUpdate,
+) {
+    if trie_changes.old_root != trie_changes.new_root {
+        remove_state_delta(shard_uid, &trie_changes.new_root, store_update);
+    }
+}
+
+/// Removes the delta which reverts the state with `state_root`.
+pub(crate) fn remove_state_delta(
+    shard_uid: ShardUId,
+    state_root: &StateRoot,
+    store_update: &mut StoreUpdate,
+) {
+    store_update.delete(DBCol::ColFlatStateDeltas, &delta_key(shard_uid, state_root));
+}
+
+/// Writes the genesis values of the shard and moves the flat state to `trie_changes.new_root`.
+///
+/// Only valid while the genesis state of the shard is being built from the empty root, so that
+/// every key is set once and no deltas are needed.
+pub(crate) fn update_genesis(
+    shard_uid: ShardUId,
+    trie_changes: &TrieChanges,
+    state_changes: &[RawStateChangesWithTrieKey],
+    store_update: &mut StoreUpdate,
+) -> Result<(), StorageError> {
+    for change in state_changes {
+        if let Some(value) = change.changes.last().and_then(|change| change.data.as_ref()) {
+            store_update.set(
+                DBCol::ColFlatState,
+                &value_key(shard_uid, &change.trie_key.to_vec()),
+                value,
+            );
+        }
+    }
+    store_update
+        .set_ser(DBCol::ColFlatState, &head_key(shard_uid), &trie_changes.new_root)
+        .map_err(storage_error)
+}
+
+/// Moves the flat state of the shard to the state registered in `catchups`, until the state
+/// isn't changed while it's moved. Ends the catch-up of the shard, also on failure.
+///
+/// Commits its own store updates, so it must not run on the block processing path. The head is
+/// removed while the values are written, so reads of the shard go through the trie meanwhile.
+pub(crate) fn catch_up(
+    store: &Store,
+    trie: &Trie,
+    shard_uid: ShardUId,
+    catchups: &FlatStateCatchups,
+) -> Result<(), StorageError> {
+    let result = catch_up_to_targets(store, trie, shard_uid, catchups);
+    if result.is_err() {
+        catchups.abort(shard_uid);
+    }
+    result
+}
+
+fn catch_up_to_targets(
+    store: &Store,
+    trie: &Trie,
+    shard_uid: ShardUId,
+    catchups: &FlatStateCatchups,
+) -> Result<(), StorageError> {
+    while let Some(state_root) = catchups.target(shard_uid) {
+        match get_head(store, shard_uid)? {
+            Some(head) if head == state_root => {}
+            Some(head) => {
+                if let Err(err) = move_by_diff(store, trie, shard_uid, &head, &state_root) {
+                    // The nodes of the head state may be garbage collected already.
+                    debug!(
+                        target: "store",
+                        "Can't move flat state of shard {} from {} to {}: {:?}",
+                        shard_uid,
+                        head,
+                        state_root,
+                        err
+                    );
+                    rebuild(store, trie, shard_uid, &state_root)?;
+                }
+            }
+            None => rebuild(store, trie, shard_uid, &state_root)?,
+        }
+        if catchups.finish(shard_uid, &state_root) {
+            info!(target: "store", "Flat state of shard {} caught up with {}", shard_uid, state_root);
+        }
+    }
+    Ok(())
+}
+
+/// Moves the flat state of the shard from `head` to `state_root` by writing the values which
+/// differ between the two tries, at a cost which depends on the size of the difference.
+fn move_by_diff(
+    store: &Store,
+    trie: &Trie,
+    shard_uid: ShardUId,
+    head: &StateRoot,
+    state_root: &StateRoot,
+) -> Result<(), StorageError> {
+    let mut store_update = store.store_update();
+    store_update.delete(DBCol::ColFlatState, &head_key(shard_uid));
+    store_update.commit().map_err(storage_error)?;
+
+    let mut store_update = store.store_update();
+    let mut batch_size = 0;
+    for item in TrieDiffIterator::new(trie, head, trie, state_root)? {
+        let item = item?;
+        let key = value_key(shard_uid, &item.key);
+        match &item.new_value {
+            Some(value) => store_update.set(DBCol::ColFlatState, &key, value),
+            None => store_update.delete(DBCol::ColFlatState, &key),
+        }
+        batch_size += key.len() + item.new_value.map_or(0, |value| value.len());
+        if batch_size > REBUILD_BATCH_SIZE {
+            std::mem::replace(&mut store_update, store.store_update())
+                .commit()
+                .map_err(storage_error)?;
+            batch_size = 0;
+        }
+    }
+    store_update
+        .set_ser(DBCol::ColFlatState, &head_key(shard_uid), state_root)
+        .map_err(storage_error)?;
+    store_update.commit().map_err(storage_error)
+}
+
+/// Replaces the flat state of the shard with the content of the state with `state_root`.
+///
+/// Walks the whole trie, so it's only used when there is no flat state to move, or its state
+/// can't be read anymore. The head is removed first, so reads of the shard go through the trie
+/// until the rebuild is finished. Does nothing for stores which never read from the flat state.
+fn rebuild(
+    store: &Store,
+    trie: &Trie,
+    shard_uid: ShardUId,
+    state_root: &StateRoot,
+) -> Result<(), StorageError> {
+    if !store.supports_snapshots() {
+        return Ok(());
+    }
+    info!(target: "store", "Rebuilding flat state of shard {} at {}", shard_uid, state_root);
+    let mut store_update = store.store_update();
+    store_update.delete(DBCol::ColFlatState, &head_key(shard_uid));
+    for (key, _) in store.iter_prefix(DBCol::ColFlatState, &head_key(shard_uid)) {
+        if key.len() > head_key(shard_uid).len() {
+            store_update.delete(DBCol::ColFlatState, &key);
+        }
+    }
+    // Deletions and insertions of the same key can't be a part of a single transaction.
+    store_update.commit().map_err(storage_error)?;
+
+    let mut store_update = store.store_update();
+    let mut batch_size = 0;
+    for item in trie.iter(state_root)? {
+        let (key, value) = item?;
+        store_update.set(DBCol::ColFlatState, &value_key(shard_uid, &key), &value);
+        batch_size += key.len() + value.len();
+        if batch_size > REBUILD_BATCH_SIZE {
+            std::mem::replace(&mut store_update, store.store_update())
+                .commit()
+                .map_err(storage_error)?;
+            batch_size = 0;
+        }
+    }
+    store_update
+        .set_ser(DBCol::ColFlatState, &head_key(shard_uid), state_root)
+        .map_err(storage_error)?;
+    store_update.commit().map_err(storage_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use near_primitives::hash::hash;
+    use near_primitives::shard_layout::ShardUId;
+    use near_primitives::trie_key::TrieKey;
+    use near_primitives::types::{StateChangeCause, StateRoot};
+
+    use crate::trie::flat_state::{get_head, MAX_FORK_DELTAS};
+    use crate::{create_store, ShardTries, Store, Trie, WrappedTrieChanges};
+
+    fn data_key(key: u8) -> TrieKey {
+        TrieKey::ContractData { account_id: "alice".parse().unwrap(), key: vec![key] }
+    }
+
+    /// Applies `changes` on top of `root` as a chunk of a new block and returns the new root.
+    fn apply_chunk(
+        tries: &ShardTries,
+        root: StateRoot,
+        block: u8,
+        changes: &[(u8, Option<u8>)],
+    ) -> StateRoot {
+        let shard_uid = ShardUId::single_shard();
+        let mut trie_update = tries.new_trie_update(shard_uid, root);
+        for (key, value) in changes {
+            match value {
+                Some(value) => trie_update.set(data_key(*key), vec![*value]),
+                None => trie_update.remove(data_key(*key)),
+            }
+        }
+        trie_update.commit(StateChangeCause::InitialState);
+        let (trie_changes, state_changes) = trie_update.finalize().unwrap();
+        let new_root = trie_changes.new_root;
+        let mut wrapped = WrappedTrieChanges::new(
+            tries.clone(),
+            shard_uid,
+            trie_changes,
+            state_changes,
+            hash(&[block]),
+        );
+        let mut store_update = tries.get_store().store_update();
+        wrapped.wrapped_into(&mut store_update).unwrap();
+        store_update.commit().unwrap();
+        new_root
+    }
+
+    fn assert_values(tries: &ShardTries, root: &StateRoot, expected: &[(u8, Option<u8>)]) {
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard());
+        for (key, value) in expected {
+            let value = value.map(|value| vec![value]);
+            assert_eq!(trie.get(root, &data_key(*key).to_vec()).unwrap(), value);
+        }
+        // Values of the head state are read without touching trie nodes.
+        assert_eq!(trie.counter.get(), 0);
+    }
+
+    /// Checks values of a state the flat state is not at, which are read from the trie.
+    fn assert_trie_values(tries: &ShardTries, root: &StateRoot, expected: &[(u8, Option<u8>)]) {
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard());
+        for (key, value) in expected {
+            let value = value.map(|value| vec![value]);
+            assert_eq!(trie.get(root, &data_key(*key).to_vec()).unwrap(), value);
+        }
+        assert_ne!(trie.counter.get(), 0);
+    }
+
+    fn wait_for_catch_up(tries: &ShardTries) {
+        while tries.is_flat_state_catching_up(ShardUId::single_shard()) {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    fn create_genesis(tries: &ShardTries, values: &[(u8, Option<u8>)]) -> StateRoot {
+        let shard_uid = ShardUId::single_shard();
+        let mut trie_update = tries.new_trie_update(shard_uid, Trie::empty_root());
+        for (key, value) in values {
+            trie_update.set(data_key(*key), vec![value.unwrap()]);
+        }
+        trie_update.commit(StateChangeCause::InitialState);
+        let (trie_changes, state_changes) = trie_update.finalize().unwrap();
+        let (mut store_update, root) = tries.apply_all(&trie_changes, shard_uid).unwrap();
+        tries
+            .apply_genesis_flat_state(&trie_changes, &state_changes, shard_uid, &mut store_update)
+            .unwrap();
+        store_update.commit().unwrap();
+        root
+    }
+
+    fn create_tries(store: &Store) -> ShardTries {
+        ShardTries::new(Arc::new(store.clone()), 0, 1)
+    }
+
+    #[test]
+    fn test_flat_state_follows_forks() {
+        let tmp_dir = tempfile::Builder::new().prefix("_test_flat_state").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let tries = create_tries(&store);
+        let shard_uid = ShardUId::single_shard();
+
+        let genesis = create_genesis(&tries, &[(1, Some(1)), (2, Some(2))]);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(genesis));
+        assert_values(&tries, &genesis, &[(1, Some(1)), (2, Some(2)), (3, None)]);
+
+        let root_a = apply_chunk(&tries, genesis, 1, &[(1, Some(10)), (2, None), (3, Some(3))]);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root_a));
+        assert_values(&tries, &root_a, &[(1, Some(10)), (2, None), (3, Some(3))]);
+        let root_b = apply_chunk(&tries, root_a, 2, &[(3, Some(30))]);
+        assert_values(&tries, &root_b, &[(1, Some(10)), (2, None), (3, Some(30))]);
+
+        // A fork from genesis reverts both blocks.
+        let root_c = apply_chunk(&tries, genesis, 3, &[(4, Some(4))]);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root_c));
+        assert_values(&tries, &root_c, &[(1, Some(1)), (2, Some(2)), (3, None), (4, Some(4))]);
+
+        // Other states are still readable through the trie.
+        let trie = tries.get_trie_for_shard(shard_uid);
+        assert_eq!(trie.get(&root_b, &data_key(3).to_vec()).unwrap(), Some(vec![30]));
+        assert_ne!(trie.counter.get(), 0);
+    }
+
+    #[test]
+    fn test_flat_state_switches_forks() {
+        let tmp_dir =
+            tempfile::Builder::new().prefix("_test_flat_state_switches_forks").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let tries = create_tries(&store);
+        let shard_uid = ShardUId::single_shard();
+
+        let genesis = create_genesis(&tries, &[(1, Some(1)), (2, Some(2))]);
+        let root_a = apply_chunk(&tries, genesis, 1, &[(1, Some(10)), (3, Some(3))]);
+        let root_b = apply_chunk(&tries, genesis, 2, &[(2, None), (4, Some(4))]);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root_b));
+
+        // Going back to the already applied fork re-reads the keys changed on it.
+        let root_c = apply_chunk(&tries, root_a, 3, &[(5, Some(5))]);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root_c));
+        assert_values(
+            &tries,
+            &root_c,
+            &[(1, Some(10)), (2, Some(2)), (3, Some(3)), (4, None), (5, Some(5))],
+        );
+
+        // A fork which splits further than the deltas are walked only stores the delta, the
+        // flat state catches up with the parent state in the background.
+        let mut root = root_c;
+        for block in 0..(MAX_FORK_DELTAS + 1) as u8 {
+            root = apply_chunk(&tries, root, 10 + block, &[(6, Some(block))]);
+        }
+        let root_d = apply_chunk(&tries, root_b, 4, &[(1, None)]);
+        assert_ne!(get_head(&store, shard_uid).unwrap(), Some(root_d));
+        assert_trie_values(
+            &tries,
+            &root_d,
+            &[(1, None), (2, None), (3, None), (4, Some(4)), (5, None), (6, None)],
+        );
+        wait_for_catch_up(&tries);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root_b));
+        assert_values(&tries, &root_b, &[(1, Some(1)), (2, None), (4, Some(4)), (6, None)]);
+
+        // The next block moves the flat state with the stored delta.
+        let root_e = apply_chunk(&tries, root_d, 5, &[(7, Some(7))]);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root_e));
+        assert_values(&tries, &root_e, &[(1, None), (2, None), (4, Some(4)), (7, Some(7))]);
+
+        // Moving back to the long fork is done the same way.
+        let root_f = apply_chunk(&tries, root, 6, &[(8, Some(8))]);
+        assert_trie_values(&tries, &root_f, &[(1, Some(10)), (7, None), (8, Some(8))]);
+        wait_for_catch_up(&tries);
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(root));
+        let root_g = apply_chunk(&tries, root_f, 7, &[(9, Some(9))]);
+        assert_values(
+            &tries,
+            &root_g,
+            &[
+                (1, Some(10)),
+                (2, Some(2)),
+                (4, None),
+                (6, Some(MAX_FORK_DELTAS as u8)),
+                (7, None),
+                (8, Some(8)),
+                (9, Some(9)),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_flat_state_rebuild() {
+        let tmp_dir =
+            tempfile::Builder::new().prefix("_test_flat_state_rebuild").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let tries = create_tries(&store);
+        let shard_uid = ShardUId::single_shard();
+
+        let genesis = create_genesis(&tries, &[(1, Some(1)), (2, Some(2))]);
+        let root_a = apply_chunk(&tries, genesis, 1, &[(1, Some(10)), (2, None)]);
+        tries.rebuild_flat_state(shard_uid, &genesis).unwrap();
+        assert_eq!(get_head(&store, shard_uid).unwrap(), Some(genesis));
+        assert_values(&tries, &genesis, &[(1, Some(1)), (2, Some(2))]);
+
+        tries.rebuild_flat_state(shard_uid, &root_a).unwrap();
+        assert_values(&tries, &root_a, &[(1, Some(10)), (2, None)]);
+    }
+}
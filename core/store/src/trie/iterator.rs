@@ -240,6 +240,48 @@ impl<'a> TrieIterator<'a> {
         Ok(trie_items)
     }
 
+    /// Returns key-value pairs with keys in [start, end), where `None` stands for no upper bound.
+    ///
+    /// Unlike `get_trie_items`, it doesn't retrieve nodes and values which only hold keys at or
+    /// after `end`, so every node it touches is needed to prove the result to a verifier which
+    /// runs the same traversal.
+    pub(crate) fn get_range_items(
+        &mut self,
+        start: &[u8],
+        end: Option<&[u8]>,
+    ) -> Result<Vec<TrieItem>, StorageError> {
+        self.seek(start)?;
+        let end_nibbles: Option<Vec<u8>> = end.map(|end| NibbleSlice::new(end).iter().collect());
+        let is_past_end = |key_nibbles: &[u8]| match &end_nibbles {
+            Some(end_nibbles) => key_nibbles >= &end_nibbles[..],
+            None => false,
+        };
+        let mut trie_items = vec![];
+        while let Some(iter_step) = self.iter_step() {
+            match iter_step {
+                IterStep::PopTrail => {
+                    self.trail.pop();
+                }
+                IterStep::Descend(hash) => {
+                    // Every key in the subtree starts with `key_nibbles`.
+                    if is_past_end(&self.key_nibbles) {
+                        break;
+                    }
+                    let node = self.trie.retrieve_node(&hash)?;
+                    self.descend_into_node(node);
+                }
+                IterStep::Continue => {}
+                IterStep::Value(hash) => {
+                    if is_past_end(&self.key_nibbles) {
+                        break;
+                    }
+                    trie_items.push((self.key(), self.trie.retrieve_raw_bytes(&hash)?));
+                }
+            }
+        }
+        Ok(trie_items)
+    }
+
     /// Visits all nodes belonging to the interval [path_begin, path_end) in depth-first search
     /// order and return TrieTraversalItem for each visited node.
     /// Used to generate and apply state parts for state sync.
@@ -329,6 +371,181 @@ impl<'a> Iterator for TrieIterator<'a> {
     }
 }
 
+/// Change of a single key between two tries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieDiffItem {
+    pub key: Vec<u8>,
+    /// Value in the first trie, `None` if the key was added.
+    pub old_value: Option<Vec<u8>>,
+    /// Value in the second trie, `None` if the key was removed.
+    pub new_value: Option<Vec<u8>>,
+}
+
+/// Node or value the traversal of one of the tries is positioned at.
+struct DiffCursor {
+    /// Path to the node or value in nibbles.
+    path: Vec<u8>,
+    step: DiffStep,
+}
+
+#[derive(Clone, Copy)]
+enum DiffStep {
+    Descend(CryptoHash),
+    Value(CryptoHash),
+}
+
+impl DiffCursor {
+    /// Order of the traversal: nodes and values are visited by their path, and the node at a
+    /// path is visited before the value stored at the same path.
+    fn cmp_position(&self, other: &DiffCursor) -> std::cmp::Ordering {
+        let is_value = |step: &DiffStep| matches!(step, DiffStep::Value(_));
+        (&self.path, is_value(&self.step)).cmp(&(&other.path, is_value(&other.step)))
+    }
+}
+
+/// Iterates over keys whose values differ between two tries, in key order.
+///
+/// Both tries are traversed in lockstep, and subtrees whose nodes have the same hash at the same
+/// path are skipped without reading them, so the cost depends on the size of the difference
+/// rather than on the size of the tries.
+pub struct TrieDiffIterator<'a> {
+    old: TrieIterator<'a>,
+    new: TrieIterator<'a>,
+    old_cursor: Option<DiffCursor>,
+    new_cursor: Option<DiffCursor>,
+    finished: bool,
+}
+
+impl<'a> TrieDiffIterator<'a> {
+    pub fn new(
+        old_trie: &'a Trie,
+        old_root: &CryptoHash,
+        new_trie: &'a Trie,
+        new_root: &CryptoHash,
+    ) -> Result<Self, StorageError> {
+        Ok(TrieDiffIterator {
+            old: TrieIterator::new(old_trie, old_root)?,
+            new: TrieIterator::new(new_trie, new_root)?,
+            old_cursor: None,
+            new_cursor: None,
+            finished: old_root == new_root,
+        })
+    }
+
+    fn next_cursor(iterator: &mut TrieIterator<'a>) -> Option<DiffCursor> {
+        loop {
+            let step = match iterator.iter_step()? {
+                IterStep::PopTrail => {
+                    iterator.trail.pop();
+                    continue;
+                }
+                IterStep::Continue => continue,
+                IterStep::Descend(hash) => DiffStep::Descend(hash),
+                IterStep::Value(hash) => DiffStep::Value(hash),
+            };
+            return Some(DiffCursor { path: iterator.key_nibbles.clone(), step });
+        }
+    }
+
+    fn descend(iterator: &mut TrieIterator<'a>, hash: &CryptoHash) -> Result<(), StorageError> {
+        let node = iterator.trie.retrieve_node(hash)?;
+        iterator.descend_into_node(node);
+        Ok(())
+    }
+
+    fn key(path: &[u8]) -> Vec<u8> {
+        path.chunks(2).map(|nibbles| nibbles[0] * 16 + nibbles[1]).collect()
+    }
+
+    fn diff_step(&mut self) -> Result<Option<TrieDiffItem>, StorageError> {
+        if self.old_cursor.is_none() {
+            self.old_cursor = Self::next_cursor(&mut self.old);
+        }
+        if self.new_cursor.is_none() {
+            self.new_cursor = Self::next_cursor(&mut self.new);
+        }
+        let ordering = match (&self.old_cursor, &self.new_cursor) {
+            (None, None) => {
+                self.finished = true;
+                return Ok(None);
+            }
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (Some(old), Some(new)) => old.cmp_position(new),
+        };
+        match ordering {
+            std::cmp::Ordering::Less => {
+                let old = self.old_cursor.take().unwrap();
+                match old.step {
+                    DiffStep::Descend(hash) => Self::descend(&mut self.old, &hash)?,
+                    DiffStep::Value(hash) => {
+                        return Ok(Some(TrieDiffItem {
+                            key: Self::key(&old.path),
+                            old_value: Some(self.old.trie.retrieve_raw_bytes(&hash)?),
+                            new_value: None,
+                        }))
+                    }
+                }
+            }
+            std::cmp::Ordering::Greater => {
+                let new = self.new_cursor.take().unwrap();
+                match new.step {
+                    DiffStep::Descend(hash) => Self::descend(&mut self.new, &hash)?,
+                    DiffStep::Value(hash) => {
+                        return Ok(Some(TrieDiffItem {
+                            key: Self::key(&new.path),
+                            old_value: None,
+                            new_value: Some(self.new.trie.retrieve_raw_bytes(&hash)?),
+                        }))
+                    }
+                }
+            }
+            std::cmp::Ordering::Equal => {
+                let old = self.old_cursor.take().unwrap();
+                let new = self.new_cursor.take().unwrap();
+                match (old.step, new.step) {
+                    // Identical subtrees are not visited.
+                    (DiffStep::Descend(old_hash), DiffStep::Descend(new_hash)) => {
+                        if old_hash != new_hash {
+                            Self::descend(&mut self.old, &old_hash)?;
+                            Self::descend(&mut self.new, &new_hash)?;
+                        }
+                    }
+                    (DiffStep::Value(old_hash), DiffStep::Value(new_hash)) => {
+                        if old_hash != new_hash {
+                            return Ok(Some(TrieDiffItem {
+                                key: Self::key(&old.path),
+                                old_value: Some(self.old.trie.retrieve_raw_bytes(&old_hash)?),
+                                new_value: Some(self.new.trie.retrieve_raw_bytes(&new_hash)?),
+                            }));
+                        }
+                    }
+                    _ => unreachable!("Cursors at the same position have the same kind"),
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for TrieDiffIterator<'a> {
+    type Item = Result<TrieDiffItem, StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            match self.diff_step() {
+                Ok(Some(item)) => return Some(Ok(item)),
+                Ok(None) => {}
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -341,7 +558,7 @@ mod tests {
     use crate::test_utils::{
         create_tries, create_tries_complex, gen_changes, simplify_changes, test_populate_trie,
     };
-    use crate::trie::iterator::IterStep;
+    use crate::trie::iterator::{IterStep, TrieDiffItem, TrieDiffIterator};
     use crate::trie::nibble_slice::NibbleSlice;
     use crate::Trie;
     use near_primitives::shard_layout::ShardUId;
@@ -477,4 +694,68 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_diff() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let tries = create_tries();
+            let shard_uid = ShardUId::single_shard();
+            let trie = tries.get_trie_for_shard(shard_uid);
+            let old_changes = simplify_changes(&gen_changes(&mut rng, 20));
+            let old_root =
+                test_populate_trie(&tries, &Trie::empty_root(), shard_uid, old_changes.clone());
+            let new_changes = simplify_changes(&gen_changes(&mut rng, 5));
+            let new_root = test_populate_trie(&tries, &old_root, shard_uid, new_changes);
+
+            let old_map: BTreeMap<_, _> =
+                trie.iter(&old_root).unwrap().map(Result::unwrap).collect();
+            let new_map: BTreeMap<_, _> =
+                trie.iter(&new_root).unwrap().map(Result::unwrap).collect();
+            let mut keys: Vec<_> = old_map.keys().chain(new_map.keys()).cloned().collect();
+            keys.sort();
+            keys.dedup();
+            let expected: Vec<_> = keys
+                .into_iter()
+                .filter(|key| old_map.get(key) != new_map.get(key))
+                .map(|key| TrieDiffItem {
+                    old_value: old_map.get(&key).cloned(),
+                    new_value: new_map.get(&key).cloned(),
+                    key,
+                })
+                .collect();
+            let diff: Vec<_> = TrieDiffIterator::new(&trie, &old_root, &trie, &new_root)
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(diff, expected);
+
+            let diff = TrieDiffIterator::new(&trie, &old_root, &trie, &old_root).unwrap();
+            assert_eq!(diff.count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_diff_skips_unchanged_subtrees() {
+        let tries = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let changes: Vec<_> =
+            (0..1000u32).map(|i| (i.to_be_bytes().to_vec(), Some(vec![1]))).collect();
+        let old_root = test_populate_trie(&tries, &Trie::empty_root(), shard_uid, changes);
+        let key = 500u32.to_be_bytes().to_vec();
+        let new_root =
+            test_populate_trie(&tries, &old_root, shard_uid, vec![(key.clone(), Some(vec![2]))]);
+
+        let trie = tries.get_trie_for_shard(shard_uid).recording_reads();
+        let diff: Vec<_> = TrieDiffIterator::new(&trie, &old_root, &trie, &new_root)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            diff,
+            vec![TrieDiffItem { key, old_value: Some(vec![1]), new_value: Some(vec![2]) }]
+        );
+        // Only the nodes on the paths to the changed value in both tries are read.
+        assert!(trie.recorded_storage().unwrap().nodes.0.len() < 40);
+    }
 }
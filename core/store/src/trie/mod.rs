@@ -14,19 +14,28 @@ use near_primitives::hash::{hash, CryptoHash};
 pub use near_primitives::shard_layout::ShardUId;
 use near_primitives::types::{StateRoot, StateRootNode};
 
+use crate::trie::flat_state::FlatState;
 use crate::trie::insert_delete::NodesStorage;
 use crate::trie::iterator::TrieIterator;
 use crate::trie::nibble_slice::NibbleSlice;
-pub use crate::trie::shard_tries::{KeyForStateChanges, ShardTries, WrappedTrieChanges};
+pub use crate::trie::proofs::{TrieKeysProof, TrieRangeProof};
+pub use crate::trie::shard_tries::{
+    KeyForStateChanges, KeyForStateChangesHistory, ShardTries, WrappedTrieChanges,
+};
+pub use crate::trie::trie_storage::{ShardCacheConfig, TrieCacheConfig};
 use crate::trie::trie_storage::{
     TouchedNodesCounter, TrieMemoryPartialStorage, TrieRecordingStorage, TrieStorage,
 };
-pub(crate) use crate::trie::trie_storage::{TrieCache, TrieCachingStorage};
+pub(crate) use crate::trie::trie_storage::{
+    TrieCache, TrieCachingStorage, DEFAULT_SHARD_CACHE_MAX_BYTES,
+};
 use crate::StorageError;
 
+pub mod flat_state;
 mod insert_delete;
 pub mod iterator;
 mod nibble_slice;
+mod proofs;
 mod shard_tries;
 pub mod split_state;
 mod state_parts;
@@ -408,6 +417,8 @@ impl RawTrieNodeWithSize {
 
 pub struct Trie {
     pub(crate) storage: Box<dyn TrieStorage>,
+    /// Flat state of the shard, used by `get` to read values without walking the trie.
+    pub(crate) flat_state: Option<FlatState>,
     pub counter: TouchedNodesCounter,
 }
 
@@ -472,7 +483,7 @@ pub struct ApplyStatePartResult {
 
 impl Trie {
     pub fn new(store: Box<dyn TrieStorage>, _shard_uid: ShardUId) -> Self {
-        Trie { storage: store, counter: TouchedNodesCounter::default() }
+        Trie { storage: store, flat_state: None, counter: TouchedNodesCounter::default() }
     }
 
     pub fn recording_reads(&self) -> Self {
@@ -483,7 +494,11 @@ impl Trie {
             shard_uid: storage.shard_uid,
             recorded: RefCell::new(Default::default()),
         };
-        Trie { storage: Box::new(storage), counter: TouchedNodesCounter::default() }
+        Trie {
+            storage: Box::new(storage),
+            flat_state: None,
+            counter: TouchedNodesCounter::default(),
+        }
     }
 
     pub fn empty_root() -> StateRoot {
@@ -506,6 +521,7 @@ impl Trie {
                 recorded_storage,
                 visited_nodes: Default::default(),
             }),
+            flat_state: None,
             counter: TouchedNodesCounter::default(),
         }
     }
@@ -698,7 +714,25 @@ impl Trie {
         self.lookup(root, key)
     }
 
+    /// Returns the value of `key` in the state with `root`.
+    ///
+    /// The value is read from the flat state when it is at `root`. Note that in this case no
+    /// trie nodes are touched, so reads which are charged per touched node must use `get_ref`
+    /// or reset the counter, see `TrieUpdate::get_flat`.
     pub fn get(&self, root: &CryptoHash, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        if let Some(flat_state) = &self.flat_state {
+            if let Some(value) = flat_state.get(root, key)? {
+                return Ok(value);
+            }
+        }
+        self.get_from_trie(root, key)
+    }
+
+    pub(crate) fn get_from_trie(
+        &self,
+        root: &CryptoHash,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, StorageError> {
         match self.get_ref(root, key)? {
             Some((_length, hash)) => self.retrieve_raw_bytes(&hash).map(Some),
             None => Ok(None),
@@ -0,0 +1,244 @@
+//! Merkle proofs of the content of a key range or of a set of keys of the trie.
+//!
+//! A proof holds every trie node and value touched while reading the keys from the trie. The
+//! verifier repeats the same reads on top of the proof nodes, which are looked up by hash
+//! starting from the state root, so it gets the same result or fails with
+//! [`StorageError::TrieNodeMissing`] if the proof is incomplete.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use near_primitives::challenge::PartialState;
+use near_primitives::types::StateRoot;
+
+use crate::trie::iterator::TrieItem;
+use crate::{PartialStorage, StorageError, Trie, TrieIterator};
+
+/// Proof of all key-value pairs of the trie with keys in [start, end).
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrieRangeProof {
+    pub start: Vec<u8>,
+    /// `None` if the range is not bounded from above.
+    pub end: Option<Vec<u8>>,
+    pub nodes: PartialState,
+}
+
+/// Proof of the values of a batch of keys, including the absence of some of them.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrieKeysProof {
+    pub keys: Vec<Vec<u8>>,
+    pub nodes: PartialState,
+}
+
+/// Returns the smallest key which is greater than all keys starting with `prefix`, or `None` if
+/// there is no such key.
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last != u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+impl Trie {
+    /// Returns the key-value pairs with keys in [start, end) together with a proof of them.
+    ///
+    /// # Panics
+    /// storage must be a TrieCachingStorage
+    pub fn get_range_with_proof(
+        &self,
+        root: &StateRoot,
+        start: &[u8],
+        end: Option<&[u8]>,
+    ) -> Result<(Vec<TrieItem>, TrieRangeProof), StorageError> {
+        let trie = self.recording_reads();
+        let items = TrieIterator::new(&trie, root)?.get_range_items(start, end)?;
+        let proof = TrieRangeProof {
+            start: start.to_vec(),
+            end: end.map(<[u8]>::to_vec),
+            nodes: trie.recorded_storage().expect("Storage is recording").nodes,
+        };
+        Ok((items, proof))
+    }
+
+    /// Returns the key-value pairs with keys starting with `prefix` together with a proof of
+    /// them, e.g. all access keys of an account.
+    ///
+    /// # Panics
+    /// storage must be a TrieCachingStorage
+    pub fn get_prefix_with_proof(
+        &self,
+        root: &StateRoot,
+        prefix: &[u8],
+    ) -> Result<(Vec<TrieItem>, TrieRangeProof), StorageError> {
+        self.get_range_with_proof(root, prefix, prefix_end(prefix).as_deref())
+    }
+
+    /// Returns the values of `keys` together with a single proof of all of them.
+    ///
+    /// # Panics
+    /// storage must be a TrieCachingStorage
+    pub fn get_keys_with_proof(
+        &self,
+        root: &StateRoot,
+        keys: &[Vec<u8>],
+    ) -> Result<(Vec<Option<Vec<u8>>>, TrieKeysProof), StorageError> {
+        let trie = self.recording_reads();
+        let values = keys.iter().map(|key| trie.get(root, key)).collect::<Result<_, _>>()?;
+        let proof = TrieKeysProof {
+            keys: keys.to_vec(),
+            nodes: trie.recorded_storage().expect("Storage is recording").nodes,
+        };
+        Ok((values, proof))
+    }
+
+    /// Returns the key-value pairs of the range of `proof` in the state with `root`.
+    ///
+    /// # Errors
+    /// StorageError::TrieNodeMissing if the proof doesn't contain all nodes of the range.
+    pub fn verify_range_proof(
+        root: &StateRoot,
+        proof: &TrieRangeProof,
+    ) -> Result<Vec<TrieItem>, StorageError> {
+        let trie = Trie::from_recorded_storage(PartialStorage { nodes: proof.nodes.clone() });
+        TrieIterator::new(&trie, root)?.get_range_items(&proof.start, proof.end.as_deref())
+    }
+
+    /// Returns the values of the keys of `proof` in the state with `root`, in the same order.
+    ///
+    /// # Errors
+    /// StorageError::TrieNodeMissing if the proof doesn't contain all nodes of the keys.
+    pub fn verify_keys_proof(
+        root: &StateRoot,
+        proof: &TrieKeysProof,
+    ) -> Result<Vec<Option<Vec<u8>>>, StorageError> {
+        let trie = Trie::from_recorded_storage(PartialStorage { nodes: proof.nodes.clone() });
+        proof.keys.iter().map(|key| trie.get(root, key)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    use near_primitives::shard_layout::ShardUId;
+
+    use crate::test_utils::{create_tries, gen_changes, simplify_changes, test_populate_trie};
+    use crate::trie::proofs::prefix_end;
+    use crate::{StorageError, Trie};
+
+    #[test]
+    fn test_prefix_end() {
+        assert_eq!(prefix_end(b"ab"), Some(b"ac".to_vec()));
+        assert_eq!(prefix_end(&[1, 255]), Some(vec![2]));
+        assert_eq!(prefix_end(&[255, 255]), None);
+        assert_eq!(prefix_end(&[]), None);
+    }
+
+    #[test]
+    fn test_range_proof() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let tries = create_tries();
+            let trie = tries.get_trie_for_shard(ShardUId::single_shard());
+            let changes = simplify_changes(&gen_changes(&mut rng, 20));
+            let map: BTreeMap<_, _> = changes
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                .collect();
+            let root = test_populate_trie(
+                &tries,
+                &Trie::empty_root(),
+                ShardUId::single_shard(),
+                changes.clone(),
+            );
+
+            for _ in 0..10 {
+                let alphabet = &b"abcdefgh"[0..rng.gen_range(2, 8)];
+                let mut gen_key = || -> Vec<u8> {
+                    let key_length = rng.gen_range(0, 4);
+                    (0..key_length).map(|_| *alphabet.choose(&mut rng).unwrap()).collect()
+                };
+                let (start, end) = (gen_key(), gen_key());
+                let end = if end.is_empty() { None } else { Some(end) };
+                let expected: Vec<_> = map
+                    .iter()
+                    .filter(|(key, _)| {
+                        **key >= start && end.as_ref().map_or(true, |end| *key < end)
+                    })
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+
+                let (items, proof) =
+                    trie.get_range_with_proof(&root, &start, end.as_deref()).unwrap();
+                assert_eq!(items, expected);
+                assert_eq!(Trie::verify_range_proof(&root, &proof).unwrap(), expected);
+
+                // Every node of the proof is needed to verify it.
+                for i in 0..proof.nodes.0.len() {
+                    let mut incomplete_proof = proof.clone();
+                    incomplete_proof.nodes.0.remove(i);
+                    assert_eq!(
+                        Trie::verify_range_proof(&root, &incomplete_proof),
+                        Err(StorageError::TrieNodeMissing)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefix_proof() {
+        let tries = create_tries();
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard());
+        let changes = vec![
+            (b"aa".to_vec(), Some(vec![1])),
+            (b"ab".to_vec(), Some(vec![2])),
+            (b"abc".to_vec(), Some(vec![3])),
+            (b"b".to_vec(), Some(vec![4])),
+        ];
+        let root =
+            test_populate_trie(&tries, &Trie::empty_root(), ShardUId::single_shard(), changes);
+        let (items, proof) = trie.get_prefix_with_proof(&root, b"ab").unwrap();
+        let expected = vec![(b"ab".to_vec(), vec![2]), (b"abc".to_vec(), vec![3])];
+        assert_eq!(items, expected);
+        assert_eq!(Trie::verify_range_proof(&root, &proof).unwrap(), expected);
+
+        // The proof can't be reused for another range.
+        let mut other_proof = proof.clone();
+        other_proof.end = None;
+        assert_eq!(
+            Trie::verify_range_proof(&root, &other_proof),
+            Err(StorageError::TrieNodeMissing)
+        );
+    }
+
+    #[test]
+    fn test_keys_proof() {
+        let tries = create_tries();
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard());
+        let changes = vec![
+            (b"aa".to_vec(), Some(vec![1])),
+            (b"ab".to_vec(), Some(vec![2])),
+            (b"b".to_vec(), Some(vec![3])),
+        ];
+        let root =
+            test_populate_trie(&tries, &Trie::empty_root(), ShardUId::single_shard(), changes);
+        let keys = vec![b"ab".to_vec(), b"abc".to_vec(), b"b".to_vec()];
+        let (values, proof) = trie.get_keys_with_proof(&root, &keys).unwrap();
+        let expected = vec![Some(vec![2]), None, Some(vec![3])];
+        assert_eq!(values, expected);
+        assert_eq!(Trie::verify_keys_proof(&root, &proof).unwrap(), expected);
+
+        let mut other_proof = proof.clone();
+        other_proof.keys.push(b"aa".to_vec());
+        assert_eq!(
+            Trie::verify_keys_proof(&root, &other_proof),
+            Err(StorageError::TrieNodeMissing)
+        );
+    }
+}
@@ -8,39 +8,74 @@ use near_primitives::shard_layout;
 use near_primitives::shard_layout::{ShardUId, ShardVersion};
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{
-    NumShards, RawStateChange, RawStateChangesWithTrieKey, StateChangeCause, StateRoot,
+    BlockHeight, NumShards, RawStateChange, RawStateChangesWithTrieKey, StateChangeCause, StateRoot,
 };
+use tracing::error;
 
 use crate::db::{DBCol, DBOp, DBTransaction};
-use crate::trie::trie_storage::{TrieCache, TrieCachingStorage};
+use crate::trie::flat_state::{self, FlatState, FlatStateCatchups};
+use crate::trie::trie_storage::{TrieCache, TrieCacheConfig, TrieCachingStorage};
 use crate::trie::{TrieRefcountChange, POISONED_LOCK_ERR};
 use crate::{StorageError, Store, StoreUpdate, Trie, TrieChanges, TrieUpdate};
 
 struct ShardTriesInner {
     store: Arc<Store>,
+    cache_config: TrieCacheConfig,
     /// Cache reserved for client actor to use
     caches: RwLock<HashMap<ShardUId, TrieCache>>,
     /// Cache for readers.
     view_caches: RwLock<HashMap<ShardUId, TrieCache>>,
+    /// Shards whose flat state is catching up with the chain in the background.
+    flat_state_catchups: FlatStateCatchups,
 }
 
 #[derive(Clone)]
 pub struct ShardTries(Arc<ShardTriesInner>);
 
 impl ShardTries {
-    fn get_new_cache(shards: &[ShardUId]) -> HashMap<ShardUId, TrieCache> {
-        shards.iter().map(|&shard_id| (shard_id, TrieCache::new())).collect()
+    fn create_cache(
+        cache_config: &TrieCacheConfig,
+        shard_uid: ShardUId,
+        is_view: bool,
+    ) -> TrieCache {
+        let shard_cache_config =
+            if is_view { &cache_config.view_shard_cache } else { &cache_config.shard_cache };
+        TrieCache::new(shard_uid, is_view, shard_cache_config.max_bytes(shard_uid))
+    }
+
+    fn get_new_cache(
+        cache_config: &TrieCacheConfig,
+        shards: &[ShardUId],
+        is_view: bool,
+    ) -> HashMap<ShardUId, TrieCache> {
+        shards
+            .iter()
+            .map(|&shard_uid| (shard_uid, Self::create_cache(cache_config, shard_uid, is_view)))
+            .collect()
     }
 
     pub fn new(store: Arc<Store>, shard_version: ShardVersion, num_shards: NumShards) -> Self {
+        Self::new_with_cache_config(store, TrieCacheConfig::default(), shard_version, num_shards)
+    }
+
+    pub fn new_with_cache_config(
+        store: Arc<Store>,
+        cache_config: TrieCacheConfig,
+        shard_version: ShardVersion,
+        num_shards: NumShards,
+    ) -> Self {
         assert_ne!(num_shards, 0);
         let shards: Vec<_> = (0..num_shards)
             .map(|shard_id| ShardUId { version: shard_version, shard_id: shard_id as u32 })
             .collect();
+        let caches = Self::get_new_cache(&cache_config, &shards, false);
+        let view_caches = Self::get_new_cache(&cache_config, &shards, true);
         ShardTries(Arc::new(ShardTriesInner {
             store,
-            caches: RwLock::new(Self::get_new_cache(&shards)),
-            view_caches: RwLock::new(Self::get_new_cache(&shards)),
+            cache_config,
+            caches: RwLock::new(caches),
+            view_caches: RwLock::new(view_caches),
+            flat_state_catchups: FlatStateCatchups::default(),
         }))
     }
 
@@ -60,10 +95,15 @@ impl ShardTries {
         let caches_to_use = if is_view { &self.0.view_caches } else { &self.0.caches };
         let cache = {
             let mut caches = caches_to_use.write().expect(POISONED_LOCK_ERR);
-            caches.entry(shard_uid).or_insert_with(TrieCache::new).clone()
+            caches
+                .entry(shard_uid)
+                .or_insert_with(|| Self::create_cache(&self.0.cache_config, shard_uid, is_view))
+                .clone()
         };
         let store = Box::new(TrieCachingStorage::new(self.0.store.clone(), cache, shard_uid));
-        Trie::new(store, shard_uid)
+        let mut trie = Trie::new(store, shard_uid);
+        trie.flat_state = FlatState::new(self.0.store.clone(), shard_uid);
+        trie
     }
 
     pub fn get_trie_for_shard(&self, shard_uid: ShardUId) -> Trie {
@@ -100,7 +140,10 @@ impl ShardTries {
             }
         }
         for (shard_uid, ops) in shards {
-            let cache = caches.entry(shard_uid).or_insert_with(TrieCache::new).clone();
+            let cache = caches
+                .entry(shard_uid)
+                .or_insert_with(|| Self::create_cache(&self.0.cache_config, shard_uid, false))
+                .clone();
             cache.update_cache(ops);
         }
         Ok(())
@@ -198,6 +241,7 @@ impl ShardTries {
         shard_uid: ShardUId,
         store_update: &mut StoreUpdate,
     ) -> Result<(), StorageError> {
+        flat_state::remove_delta(shard_uid, trie_changes, store_update);
         ShardTries::apply_deletions_inner(
             &trie_changes.deletions,
             self.clone(),
@@ -206,12 +250,36 @@ impl ShardTries {
         )
     }
 
+    /// Writes the trie nodes and values removed by `apply_deletions` to `cold_store_update` of
+    /// the cold database of a split store, which keeps them forever.
+    pub fn copy_deletions_to_cold(
+        trie_changes: &TrieChanges,
+        shard_uid: ShardUId,
+        cold_store_update: &mut StoreUpdate,
+    ) {
+        for TrieRefcountChange { trie_node_or_value_hash, trie_node_or_value, rc } in
+            trie_changes.deletions.iter()
+        {
+            let key = TrieCachingStorage::get_key_from_shard_uid_and_hash(
+                shard_uid,
+                trie_node_or_value_hash,
+            );
+            cold_store_update.update_refcount(
+                DBCol::ColState,
+                key.as_ref(),
+                &trie_node_or_value,
+                *rc as i64,
+            );
+        }
+    }
+
     pub fn revert_insertions(
         &self,
         trie_changes: &TrieChanges,
         shard_uid: ShardUId,
         store_update: &mut StoreUpdate,
     ) -> Result<(), StorageError> {
+        flat_state::remove_delta(shard_uid, trie_changes, store_update);
         ShardTries::apply_deletions_inner(
             &trie_changes.insertions,
             self.clone(),
@@ -254,6 +322,64 @@ impl ShardTries {
         }
         (store_update, trie_changes.new_root)
     }
+
+    /// Moves the flat state of the shard to `trie_changes.new_root` while the genesis state is
+    /// built, `state_changes` must be the changes of `trie_changes`.
+    pub fn apply_genesis_flat_state(
+        &self,
+        trie_changes: &TrieChanges,
+        state_changes: &[RawStateChangesWithTrieKey],
+        shard_uid: ShardUId,
+        store_update: &mut StoreUpdate,
+    ) -> Result<(), StorageError> {
+        flat_state::update_genesis(shard_uid, trie_changes, state_changes, store_update)
+    }
+
+    /// Moves the flat state of the shard to the state with `state_root`, e.g. after state sync.
+    ///
+    /// Blocks until the flat state is moved, unless it's already catching up with the chain in
+    /// the background, then the running catch-up moves it to `state_root` instead.
+    pub fn rebuild_flat_state(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+    ) -> Result<(), StorageError> {
+        if !self.0.flat_state_catchups.start(shard_uid, *state_root) {
+            return Ok(());
+        }
+        let trie = self.get_view_trie_for_shard(shard_uid);
+        flat_state::catch_up(&self.0.store, &trie, shard_uid, &self.0.flat_state_catchups)
+    }
+
+    /// Moves the flat state of the shard to the state registered by the block processing in a
+    /// background thread.
+    fn spawn_flat_state_catch_up(&self, shard_uid: ShardUId) {
+        let tries = self.clone();
+        std::thread::spawn(move || {
+            let trie = tries.get_view_trie_for_shard(shard_uid);
+            let catchups = &tries.0.flat_state_catchups;
+            if let Err(err) = flat_state::catch_up(&tries.0.store, &trie, shard_uid, catchups) {
+                error!(target: "store", "Can't catch up flat state of shard {}: {:?}", shard_uid, err);
+            }
+        });
+    }
+
+    /// Whether the flat state of the shard is catching up with the chain, reads of the shard go
+    /// through the trie until it's done.
+    pub fn is_flat_state_catching_up(&self, shard_uid: ShardUId) -> bool {
+        self.0.flat_state_catchups.is_running(shard_uid)
+    }
+
+    /// Removes the flat state delta which reverts the state with `state_root`, once its block is
+    /// too far below the final head to be reverted.
+    pub fn remove_flat_state_delta(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+        store_update: &mut StoreUpdate,
+    ) {
+        flat_state::remove_state_delta(shard_uid, state_root, store_update);
+    }
 }
 
 pub struct WrappedTrieChanges {
@@ -279,10 +405,32 @@ impl WrappedTrieChanges {
         &self.state_changes
     }
 
+    pub fn block_hash(&self) -> &CryptoHash {
+        &self.block_hash
+    }
+
     pub fn insertions_into(&self, store_update: &mut StoreUpdate) -> Result<(), StorageError> {
         self.tries.apply_insertions(&self.trie_changes, self.shard_uid, store_update)
     }
 
+    /// Moves the flat state of the shard to the new state root. If it is too far from the parent
+    /// state, only the delta is saved and the flat state catches up in the background.
+    pub fn flat_state_into(&self, store_update: &mut StoreUpdate) -> Result<(), StorageError> {
+        let start_catch_up = flat_state::update(
+            &self.tries.0.store,
+            &self.tries.get_trie_for_shard(self.shard_uid),
+            self.shard_uid,
+            &self.tries.0.flat_state_catchups,
+            &self.trie_changes,
+            &self.state_changes,
+            store_update,
+        )?;
+        if start_catch_up {
+            self.tries.spawn_flat_state_catch_up(self.shard_uid);
+        }
+        Ok(())
+    }
+
     /// Save state changes into Store.
     ///
     /// NOTE: the changes are drained from `self`.
@@ -304,16 +452,9 @@ impl WrappedTrieChanges {
                 "Resharding changes must never be finalized."
             );
 
-            // Filtering trie keys for user facing RPC reporting.
-            // NOTE: If the trie key is not one of the account specific, it may cause key conflict
-            // when the node tracks multiple shards. See #2563.
-            match &change_with_trie_key.trie_key {
-                TrieKey::Account { .. }
-                | TrieKey::ContractCode { .. }
-                | TrieKey::AccessKey { .. }
-                | TrieKey::ContractData { .. } => {}
-                _ => continue,
-            };
+            if !is_reported_trie_key(&change_with_trie_key.trie_key) {
+                continue;
+            }
             let storage_key = KeyForStateChanges::new_from_trie_key(
                 &self.block_hash,
                 &change_with_trie_key.trie_key,
@@ -326,11 +467,32 @@ impl WrappedTrieChanges {
         }
     }
 
+    /// Records the block as a change of the keys `state_changes_into` saves, see
+    /// `KeyForStateChangesHistory`. Must be called before the changes are drained.
+    pub fn state_changes_history_into(
+        &self,
+        block_height: BlockHeight,
+        store_update: &mut StoreUpdate,
+    ) {
+        for change_with_trie_key in self.state_changes.iter() {
+            if !is_reported_trie_key(&change_with_trie_key.trie_key) {
+                continue;
+            }
+            let key = KeyForStateChangesHistory::new(
+                &change_with_trie_key.trie_key.to_vec(),
+                block_height,
+                &self.block_hash,
+            );
+            store_update.set(DBCol::ColStateChangesHistory, key.as_ref(), &[]);
+        }
+    }
+
     pub fn wrapped_into(
         &mut self,
         mut store_update: &mut StoreUpdate,
     ) -> Result<(), Box<dyn std::error::Error>> {
         self.insertions_into(&mut store_update)?;
+        self.flat_state_into(&mut store_update)?;
         self.state_changes_into(&mut store_update);
         store_update.set_ser(
             DBCol::ColTrieChanges,
@@ -341,6 +503,19 @@ impl WrappedTrieChanges {
     }
 }
 
+/// Whether changes of the key are saved for user facing RPC reporting.
+/// NOTE: If the trie key is not one of the account specific, it may cause key conflict when the
+/// node tracks multiple shards. See #2563.
+fn is_reported_trie_key(trie_key: &TrieKey) -> bool {
+    matches!(
+        trie_key,
+        TrieKey::Account { .. }
+            | TrieKey::ContractCode { .. }
+            | TrieKey::AccessKey { .. }
+            | TrieKey::ContractData { .. }
+    )
+}
+
 #[derive(derive_more::AsRef, derive_more::Into)]
 pub struct KeyForStateChanges(Vec<u8>);
 
@@ -410,3 +585,51 @@ impl KeyForStateChanges {
         })
     }
 }
+
+/// Key of `ColStateChangesHistory`: `trie_key_len + trie_key + block_height + block_hash`. The
+/// trie key is prefixed by its length, so the rows of a key are contiguous and aren't interleaved
+/// with the rows of longer keys starting with it. The height is big-endian, so the blocks which
+/// changed a key are ordered by height. The hash tells apart the blocks of different forks at the
+/// same height.
+#[derive(derive_more::AsRef, derive_more::Into)]
+pub struct KeyForStateChangesHistory(Vec<u8>);
+
+impl KeyForStateChangesHistory {
+    const LEN_PREFIX_LEN: usize = std::mem::size_of::<u32>();
+    const SUFFIX_LEN: usize =
+        std::mem::size_of::<BlockHeight>() + std::mem::size_of::<CryptoHash>();
+
+    pub fn new(raw_key: &[u8], block_height: BlockHeight, block_hash: &CryptoHash) -> Self {
+        let mut key = Vec::with_capacity(Self::LEN_PREFIX_LEN + raw_key.len() + Self::SUFFIX_LEN);
+        key.extend(&(raw_key.len() as u32).to_be_bytes());
+        key.extend(raw_key);
+        key.extend(&block_height.to_be_bytes());
+        key.extend(block_hash.as_ref());
+        Self(key)
+    }
+
+    /// Key to start the lookup of the changes of `raw_key` from, see `find_iter`.
+    pub fn first_from_height(raw_key: &[u8], block_height: BlockHeight) -> Self {
+        Self::new(raw_key, block_height, &CryptoHash::default())
+    }
+
+    /// Returns the heights and hashes of the blocks which changed the key, in the order of
+    /// heights, starting at the height of `self`. Blocks of all forks are returned.
+    pub fn find_iter<'a: 'b, 'b>(
+        &'a self,
+        store: &'b Store,
+    ) -> impl Iterator<Item = (BlockHeight, CryptoHash)> + 'b {
+        let prefix = &self.0[..self.0.len() - Self::SUFFIX_LEN];
+        store
+            .iter_without_rc_logic_from(DBCol::ColStateChangesHistory, &self.0)
+            .take_while(move |(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| {
+                let suffix = &key[key.len() - Self::SUFFIX_LEN..];
+                let (height, hash) = suffix.split_at(std::mem::size_of::<BlockHeight>());
+                (
+                    BlockHeight::from_be_bytes(height.try_into().unwrap()),
+                    CryptoHash::try_from(hash).unwrap(),
+                )
+            })
+    }
+}
@@ -2,27 +2,161 @@ use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
-use cached::{Cached, SizedCache};
+use lru::LruCache;
+use near_metrics::{IntCounter, IntGauge};
+use serde::{Deserialize, Serialize};
 
 use near_primitives::hash::CryptoHash;
 
 use crate::db::refcount::decode_value_with_rc;
+use crate::metrics;
 use crate::trie::POISONED_LOCK_ERR;
 use crate::{ColState, StorageError, Store};
 use near_primitives::shard_layout::ShardUId;
 use std::cell::RefCell;
 use std::io::ErrorKind;
 
+/// Memory limits of the trie caches of all shards of one kind.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ShardCacheConfig {
+    /// Limit in bytes for the cache of every shard not listed in `per_shard_max_bytes`.
+    pub default_max_bytes: u64,
+    /// Limits in bytes for specific shards, e.g. `{"s3.v1": 1000000000}`.
+    pub per_shard_max_bytes: HashMap<ShardUId, u64>,
+}
+
+impl Default for ShardCacheConfig {
+    fn default() -> Self {
+        Self {
+            default_max_bytes: DEFAULT_SHARD_CACHE_MAX_BYTES,
+            per_shard_max_bytes: HashMap::new(),
+        }
+    }
+}
+
+impl ShardCacheConfig {
+    pub fn max_bytes(&self, shard_uid: ShardUId) -> u64 {
+        self.per_shard_max_bytes.get(&shard_uid).copied().unwrap_or(self.default_max_bytes)
+    }
+}
+
+/// Memory limits of the trie caches held by [`ShardTries`](crate::ShardTries).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct TrieCacheConfig {
+    /// Caches used by the client to apply chunks.
+    pub shard_cache: ShardCacheConfig,
+    /// Caches used to serve view requests, e.g. from RPC.
+    pub view_shard_cache: ShardCacheConfig,
+}
+
+struct TrieCacheMetrics {
+    hits: IntCounter,
+    misses: IntCounter,
+    evictions: IntCounter,
+    bytes: IntGauge,
+}
+
+impl TrieCacheMetrics {
+    fn new(shard_uid: ShardUId, is_view: bool) -> Self {
+        let labels = [&shard_uid.to_string() as &str, if is_view { "1" } else { "0" }];
+        Self {
+            hits: metrics::TRIE_CACHE_HITS.with_label_values(&labels),
+            misses: metrics::TRIE_CACHE_MISSES.with_label_values(&labels),
+            evictions: metrics::TRIE_CACHE_EVICTIONS.with_label_values(&labels),
+            bytes: metrics::TRIE_CACHE_BYTES.with_label_values(&labels),
+        }
+    }
+}
+
+/// LRU cache of trie nodes and values bounded by the total size of its entries.
+struct TrieCacheInner {
+    cache: LruCache<CryptoHash, Vec<u8>>,
+    total_bytes: u64,
+    max_bytes: u64,
+    metrics: TrieCacheMetrics,
+}
+
+impl TrieCacheInner {
+    fn entry_size(value: &[u8]) -> u64 {
+        (std::mem::size_of::<CryptoHash>() + value.len()) as u64
+    }
+
+    fn get(&mut self, hash: &CryptoHash) -> Option<Vec<u8>> {
+        let value = self.cache.get(hash).cloned();
+        if value.is_some() {
+            self.metrics.hits.inc();
+        } else {
+            self.metrics.misses.inc();
+        }
+        value
+    }
+
+    fn put(&mut self, hash: CryptoHash, value: Vec<u8>) {
+        let size = Self::entry_size(&value);
+        if size > self.max_bytes {
+            self.pop(&hash);
+            return;
+        }
+        if let Some(old_value) = self.cache.put(hash, value) {
+            self.sub_bytes(Self::entry_size(&old_value));
+        }
+        self.add_bytes(size);
+        while self.total_bytes > self.max_bytes {
+            let (_, evicted) = self.cache.pop_lru().expect("cache can't be empty while over limit");
+            self.sub_bytes(Self::entry_size(&evicted));
+            self.metrics.evictions.inc();
+        }
+    }
+
+    fn pop(&mut self, hash: &CryptoHash) {
+        if let Some(value) = self.cache.pop(hash) {
+            self.sub_bytes(Self::entry_size(&value));
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cache.clear();
+        self.sub_bytes(self.total_bytes);
+    }
+
+    fn add_bytes(&mut self, bytes: u64) {
+        self.total_bytes += bytes;
+        self.metrics.bytes.add(bytes as i64);
+    }
+
+    fn sub_bytes(&mut self, bytes: u64) {
+        self.total_bytes -= bytes;
+        self.metrics.bytes.sub(bytes as i64);
+    }
+}
+
+impl Drop for TrieCacheInner {
+    fn drop(&mut self) {
+        // Several caches may report to the same gauge, so only our share is removed.
+        self.metrics.bytes.sub(self.total_bytes as i64);
+    }
+}
+
 #[derive(Clone)]
-pub struct TrieCache(Arc<Mutex<SizedCache<CryptoHash, Vec<u8>>>>);
+pub struct TrieCache(Arc<Mutex<TrieCacheInner>>);
 
 impl TrieCache {
-    pub fn new() -> Self {
-        Self(Arc::new(Mutex::new(SizedCache::with_size(TRIE_MAX_CACHE_SIZE))))
+    /// Creates a cache for `shard_uid` holding at most `max_bytes` of keys and values.
+    /// `is_view` only affects the labels of the exported metrics.
+    pub fn new(shard_uid: ShardUId, is_view: bool, max_bytes: u64) -> Self {
+        let max_bytes = if cfg!(feature = "no_cache") { 0 } else { max_bytes };
+        Self(Arc::new(Mutex::new(TrieCacheInner {
+            cache: LruCache::unbounded(),
+            total_bytes: 0,
+            max_bytes,
+            metrics: TrieCacheMetrics::new(shard_uid, is_view),
+        })))
     }
 
     pub fn clear(&self) {
-        self.0.lock().expect(POISONED_LOCK_ERR).cache_clear()
+        self.0.lock().expect(POISONED_LOCK_ERR).clear()
     }
 
     pub fn update_cache(&self, ops: Vec<(CryptoHash, Option<Vec<u8>>)>) {
@@ -31,13 +165,13 @@ impl TrieCache {
             if let Some(value_rc) = opt_value_rc {
                 if let (Some(value), _rc) = decode_value_with_rc(&value_rc) {
                     if value.len() < TRIE_LIMIT_CACHED_VALUE_SIZE {
-                        guard.cache_set(hash, value.to_vec());
+                        guard.put(hash, value.to_vec());
                     }
                 } else {
-                    guard.cache_remove(&hash);
+                    guard.pop(&hash);
                 }
             } else {
-                guard.cache_remove(&hash);
+                guard.pop(&hash);
             }
         }
     }
@@ -117,16 +251,10 @@ impl TrieStorage for TrieMemoryPartialStorage {
     }
 }
 
-/// Maximum number of cache entries.
-/// It was chosen to fit into RAM well. RAM spend on trie cache should not exceed
-/// 50_000 * 4 (number of shards) * TRIE_LIMIT_CACHED_VALUE_SIZE = 800 MB.
-/// In our tests on a single shard, it barely occupied 40 MB, which is dominated by state cache size
-/// with 512 MB limit. The total RAM usage for a single shard was 1 GB.
-#[cfg(not(feature = "no_cache"))]
-const TRIE_MAX_CACHE_SIZE: usize = 50000;
-
-#[cfg(feature = "no_cache")]
-const TRIE_MAX_CACHE_SIZE: usize = 1;
+/// Default limit of the total size of keys and values in a single shard cache.
+/// The previous limit of 50_000 entries occupied about 40 MB on a single shard, so this keeps
+/// at least as many nodes in memory while putting a hard bound on the worst case.
+pub const DEFAULT_SHARD_CACHE_MAX_BYTES: u64 = 50_000_000;
 
 /// Values above this size (in bytes) are never cached.
 /// Note that Trie inner nodes are always smaller than this.
@@ -168,8 +296,8 @@ impl TrieCachingStorage {
 impl TrieStorage for TrieCachingStorage {
     fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Vec<u8>, StorageError> {
         let mut guard = self.cache.0.lock().expect(POISONED_LOCK_ERR);
-        if let Some(val) = guard.cache_get(hash) {
-            Ok(val.clone())
+        if let Some(val) = guard.get(hash) {
+            Ok(val)
         } else {
             let key = Self::get_key_from_shard_uid_and_hash(self.shard_uid, hash);
             let val = self
@@ -178,7 +306,7 @@ impl TrieStorage for TrieCachingStorage {
                 .map_err(|_| StorageError::StorageInternalError)?;
             if let Some(val) = val {
                 if val.len() < TRIE_LIMIT_CACHED_VALUE_SIZE {
-                    guard.cache_set(*hash, val.clone());
+                    guard.put(*hash, val.clone());
                 }
                 Ok(val)
             } else {
@@ -209,7 +337,55 @@ impl TouchedNodesCounter {
         self.counter.store(0, Ordering::SeqCst);
     }
 
+    pub fn set(&self, value: u64) {
+        self.counter.store(value, Ordering::SeqCst);
+    }
+
     pub fn get(&self) -> u64 {
         self.counter.load(Ordering::SeqCst)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::hash;
+    use near_primitives::shard_layout::ShardUId;
+
+    use crate::trie::trie_storage::TrieCache;
+    use crate::trie::POISONED_LOCK_ERR;
+
+    #[test]
+    #[cfg(not(feature = "no_cache"))]
+    fn test_trie_cache_evicts_by_size() {
+        let shard_uid = ShardUId { version: 1, shard_id: 100 };
+        // Each entry takes 32 bytes for the key and 18 for the value.
+        let cache = TrieCache::new(shard_uid, false, 100);
+        let (a, b, c) = (hash(&[1]), hash(&[2]), hash(&[3]));
+        let mut guard = cache.0.lock().expect(POISONED_LOCK_ERR);
+        guard.put(a, vec![1; 18]);
+        guard.put(b, vec![2; 18]);
+        assert_eq!(guard.total_bytes, 100);
+        assert_eq!(guard.metrics.bytes.get(), 100);
+
+        // `a` becomes the most recently used entry, so `b` is evicted.
+        assert_eq!(guard.get(&a), Some(vec![1; 18]));
+        guard.put(c, vec![3; 18]);
+        assert_eq!(guard.get(&b), None);
+        assert_eq!(guard.get(&a), Some(vec![1; 18]));
+        assert_eq!(guard.total_bytes, 100);
+        assert_eq!(guard.metrics.evictions.get(), 1);
+        assert_eq!(guard.metrics.hits.get(), 2);
+        assert_eq!(guard.metrics.misses.get(), 1);
+
+        // Values larger than the whole cache are never stored.
+        guard.put(b, vec![2; 100]);
+        assert_eq!(guard.get(&b), None);
+        assert_eq!(guard.total_bytes, 100);
+
+        guard.pop(&a);
+        assert_eq!(guard.total_bytes, 50);
+        drop(guard);
+        cache.clear();
+        assert_eq!(cache.0.lock().expect(POISONED_LOCK_ERR).metrics.bytes.get(), 0);
+    }
+}
@@ -33,12 +33,14 @@ pub struct TrieUpdate {
 pub enum TrieUpdateValuePtr<'a> {
     HashAndSize(&'a Trie, u32, CryptoHash),
     MemoryRef(&'a Vec<u8>),
+    Value(Vec<u8>),
 }
 
 impl<'a> TrieUpdateValuePtr<'a> {
     pub fn len(&self) -> u32 {
         match self {
             TrieUpdateValuePtr::MemoryRef(value) => value.len() as u32,
+            TrieUpdateValuePtr::Value(value) => value.len() as u32,
             TrieUpdateValuePtr::HashAndSize(_, length, _) => *length,
         }
     }
@@ -46,6 +48,7 @@ impl<'a> TrieUpdateValuePtr<'a> {
     pub fn deref_value(&self) -> Result<Vec<u8>, StorageError> {
         match self {
             TrieUpdateValuePtr::MemoryRef(value) => Ok((*value).clone()),
+            TrieUpdateValuePtr::Value(value) => Ok(value.clone()),
             TrieUpdateValuePtr::HashAndSize(trie, _, hash) => trie.retrieve_raw_bytes(hash),
         }
     }
@@ -87,6 +90,24 @@ impl TrieUpdate {
         })
     }
 
+    /// Same as `get_ref`, but the value of the state with `root` is read from the flat state
+    /// when it is at `root`. Trie nodes touched by the read are not counted, so that the
+    /// counter doesn't depend on whether the flat state of this node follows the chain.
+    pub fn get_flat(&self, key: &TrieKey) -> Result<Option<TrieUpdateValuePtr<'_>>, StorageError> {
+        let key = key.to_vec();
+        if let Some(key_value) = self.prospective.get(&key) {
+            return Ok(key_value.value.as_ref().map(TrieUpdateValuePtr::MemoryRef));
+        } else if let Some(changes_with_trie_key) = self.committed.get(&key) {
+            if let Some(RawStateChange { data, .. }) = changes_with_trie_key.changes.last() {
+                return Ok(data.as_ref().map(TrieUpdateValuePtr::MemoryRef));
+            }
+        }
+        let touched_nodes = self.trie.counter.get();
+        let value = self.trie.get(&self.root, &key);
+        self.trie.counter.set(touched_nodes);
+        Ok(value?.map(TrieUpdateValuePtr::Value))
+    }
+
     pub fn set(&mut self, trie_key: TrieKey, value: Vec<u8>) {
         // NOTE: Converting `TrieKey` to a `Vec<u8>` is useful here for 2 reasons:
         // - Using `Vec<u8>` for sorting `BTreeMap` in the same order as a `Trie` and
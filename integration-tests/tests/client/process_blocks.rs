@@ -11,6 +11,7 @@ use near_primitives::num_rational::Rational;
 
 use near_actix_test_utils::run_actix;
 use near_chain::chain::{ApplyStatePartsRequest, NUM_EPOCHS_TO_KEEP_STORE_DATA};
+use near_chain::state_sync_dump::{read_state_header, read_state_part};
 use near_chain::types::LatestKnown;
 use near_chain::validate::validate_chunk_with_chunk_extra;
 use near_chain::{
@@ -48,7 +49,9 @@ use near_primitives::sharding::{
     EncodedShardChunk, ReedSolomonWrapper, ShardChunkHeader, ShardChunkHeaderInner,
     ShardChunkHeaderV3,
 };
-use near_primitives::syncing::{get_num_state_parts, ShardStateSyncResponseHeader, StatePartKey};
+use near_primitives::syncing::{
+    get_num_state_parts, ShardStateSyncResponseHeader, StatePartKey, StatePartStatus,
+};
 use near_primitives::transaction::{
     Action, DeployContractAction, ExecutionStatus, FunctionCallAction, SignedTransaction,
     Transaction,
@@ -1599,6 +1602,8 @@ fn test_process_block_after_state_sync() {
     env.clients[0]
         .runtime_adapter
         .apply_state_part(0, chunk_extra.state_root(), 0, 1, &state_part, &epoch_id)
+        .unwrap()
+        .commit()
         .unwrap();
     let block = env.clients[0].produce_block(sync_height + 1).unwrap().unwrap();
     let (_, res) = env.clients[0].process_block(block.into(), Provenance::PRODUCED);
@@ -2354,6 +2359,8 @@ fn test_catchup_gas_price_change() {
                 &part,
                 &msg.epoch_id,
             )
+            .unwrap()
+            .commit()
             .unwrap();
         }
     };
@@ -2373,6 +2380,94 @@ fn test_catchup_gas_price_change() {
     assert_eq!(chunk_extra_after_sync, expected_chunk_extra);
 }
 
+#[test]
+fn test_set_state_parts() {
+    init_test_logger();
+    let epoch_length = 5;
+    let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    genesis.config.epoch_length = epoch_length;
+    let chain_genesis = ChainGenesis::from(&genesis);
+    let mut env = TestEnv::builder(chain_genesis)
+        .clients_count(2)
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 2))
+        .build();
+    let mut blocks = vec![];
+    for i in 1..=6 {
+        let block = env.clients[0].produce_block(i).unwrap().unwrap();
+        blocks.push(block.clone());
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+        env.process_block(1, block, Provenance::NONE);
+    }
+
+    let sync_hash = *blocks[5].hash();
+    let header = env.clients[0].chain.get_state_response_header(0, sync_hash).unwrap();
+    let num_parts = get_num_state_parts(header.state_root_node().memory_usage);
+    env.clients[1].chain.set_state_header(0, sync_hash, header).unwrap();
+    let parts: Vec<_> = (0..num_parts)
+        .map(|part_id| {
+            (part_id, env.clients[0].chain.get_state_response_part(0, part_id, sync_hash).unwrap())
+        })
+        .collect();
+
+    // An invalid part is reported and not saved.
+    let mut invalid_part = parts[0].clone();
+    invalid_part.1.push(0);
+    let valid =
+        env.clients[1].chain.set_state_parts(0, sync_hash, num_parts, &[invalid_part]).unwrap();
+    assert_eq!(valid, vec![false]);
+    assert_eq!(env.clients[1].chain.store().get_state_part_status(sync_hash, 0, 0).unwrap(), None);
+
+    let valid = env.clients[1].chain.set_state_parts(0, sync_hash, num_parts, &parts).unwrap();
+    assert_eq!(valid, vec![true; num_parts as usize]);
+    for part_id in 0..num_parts {
+        assert_eq!(
+            env.clients[1].chain.store().get_state_part_status(sync_hash, 0, part_id).unwrap(),
+            Some(StatePartStatus::Downloaded)
+        );
+    }
+}
+
+#[test]
+fn test_state_sync_from_dump() {
+    init_test_logger();
+    let epoch_length = 5;
+    let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    genesis.config.epoch_length = epoch_length;
+    let chain_genesis = ChainGenesis::from(&genesis);
+    let mut env = TestEnv::builder(chain_genesis)
+        .clients_count(2)
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 2))
+        .build();
+    let mut blocks = vec![];
+    for i in 1..=6 {
+        let block = env.clients[0].produce_block(i).unwrap().unwrap();
+        blocks.push(block.clone());
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+        env.process_block(1, block, Provenance::NONE);
+    }
+
+    let sync_hash = *blocks[5].hash();
+    assert!(env.clients[0].chain.check_sync_hash_validity(&sync_hash).unwrap());
+    let dump_dir = tempfile::Builder::new().prefix("state_sync_dump").tempdir().unwrap();
+    let num_parts =
+        env.clients[0].chain.dump_state_sync_data(0, sync_hash, dump_dir.path()).unwrap();
+
+    // The dump holds exactly what peers would have sent, so it passes the same validation.
+    let header = read_state_header(dump_dir.path(), &sync_hash, 0).unwrap().unwrap();
+    assert_eq!(header, env.clients[0].chain.get_state_response_header(0, sync_hash).unwrap());
+    env.clients[1].chain.set_state_header(0, sync_hash, header).unwrap();
+    for part_id in 0..num_parts {
+        let part = read_state_part(dump_dir.path(), &sync_hash, 0, part_id).unwrap().unwrap();
+        assert_eq!(
+            part,
+            env.clients[0].chain.get_state_response_part(0, part_id, sync_hash).unwrap()
+        );
+        env.clients[1].chain.set_state_part(0, sync_hash, part_id, num_parts, &part).unwrap();
+    }
+    assert_eq!(read_state_part(dump_dir.path(), &sync_hash, 0, num_parts).unwrap(), None);
+    assert_eq!(read_state_header(dump_dir.path(), &sync_hash, 1).unwrap(), None);
+}
+
 #[test]
 fn test_block_execution_outcomes() {
     let epoch_length = 5;
@@ -3975,6 +4070,8 @@ mod contract_precompilation_tests {
         env.clients[1]
             .runtime_adapter
             .apply_state_part(0, chunk_extra.state_root(), 0, 1, &state_part, &epoch_id)
+            .unwrap()
+            .commit()
             .unwrap();
     }
 
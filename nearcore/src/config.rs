@@ -1,7 +1,7 @@
 use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -16,6 +16,8 @@ use near_chain_configs::{
     get_initial_supply, ClientConfig, Genesis, GenesisConfig, LogSummaryStyle,
 };
 use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
+#[cfg(feature = "grpc_rpc")]
+use near_grpc_rpc::GrpcRpcConfig;
 #[cfg(feature = "json_rpc")]
 use near_jsonrpc::RpcConfig;
 use near_network::test_utils::open_port;
@@ -34,6 +36,7 @@ use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner
 use near_primitives::version::PROTOCOL_VERSION;
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::RosettaRpcConfig;
+use near_store::{DBBackend, TrieCacheConfig};
 use near_telemetry::TelemetryConfig;
 
 /// Initial balance used in tests.
@@ -413,6 +416,9 @@ pub struct Config {
     #[cfg(feature = "rosetta_rpc")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rosetta_rpc: Option<RosettaRpcConfig>,
+    #[cfg(feature = "grpc_rpc")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grpc_rpc: Option<GrpcRpcConfig>,
     pub telemetry: TelemetryConfig,
     pub network: Network,
     pub consensus: Consensus,
@@ -432,6 +438,23 @@ pub struct Config {
     /// If set, overrides value in genesis configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_gas_burnt_view: Option<Gas>,
+    /// Storage engine used for the node database.
+    pub store_backend: DBBackend,
+    /// Memory limits of the trie node caches, per shard.
+    pub trie_cache: TrieCacheConfig,
+    /// Directory with a dump of state sync data created by `state-viewer dump_state_parts`.
+    /// State sync reads the header and parts it has instead of requesting them from peers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_sync_dump_dir: Option<PathBuf>,
+    /// Directory of the cold database of an archival node, relative to the home directory
+    /// unless absolute. If set, GC moves blocks, chunks, outcomes and state older than the GC
+    /// horizon there instead of keeping everything in the main database.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cold_store_path: Option<PathBuf>,
+    /// If set, validates the database in the background, checking about this many records per
+    /// second. Errors are logged; use the `store-validator` tool to repair them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_validator_keys_per_second: Option<u64>,
 }
 
 impl Default for Config {
@@ -445,6 +468,8 @@ impl Default for Config {
             rpc: Some(RpcConfig::default()),
             #[cfg(feature = "rosetta_rpc")]
             rosetta_rpc: None,
+            #[cfg(feature = "grpc_rpc")]
+            grpc_rpc: None,
             telemetry: TelemetryConfig::default(),
             network: Network::default(),
             consensus: Consensus::default(),
@@ -458,6 +483,11 @@ impl Default for Config {
             view_client_throttle_period: default_view_client_throttle_period(),
             trie_viewer_state_size_limit: default_trie_viewer_state_size_limit(),
             max_gas_burnt_view: None,
+            store_backend: DBBackend::default(),
+            trie_cache: TrieCacheConfig::default(),
+            state_sync_dump_dir: None,
+            cold_store_path: None,
+            store_validator_keys_per_second: None,
         }
     }
 }
@@ -604,6 +634,8 @@ pub struct NearConfig {
     pub rpc_config: Option<RpcConfig>,
     #[cfg(feature = "rosetta_rpc")]
     pub rosetta_rpc_config: Option<RosettaRpcConfig>,
+    #[cfg(feature = "grpc_rpc")]
+    pub grpc_rpc_config: Option<GrpcRpcConfig>,
     pub telemetry_config: TelemetryConfig,
     pub genesis: Genesis,
     pub validator_signer: Option<Arc<dyn ValidatorSigner>>,
@@ -662,6 +694,8 @@ impl NearConfig {
                 view_client_throttle_period: config.view_client_throttle_period,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
                 max_gas_burnt_view: config.max_gas_burnt_view,
+                state_sync_dump_dir: config.state_sync_dump_dir,
+                store_validator_keys_per_second: config.store_validator_keys_per_second,
             },
             network_config: NetworkConfig {
                 public_key: network_key_pair.public_key,
@@ -712,6 +746,8 @@ impl NearConfig {
             rpc_config: config.rpc,
             #[cfg(feature = "rosetta_rpc")]
             rosetta_rpc_config: config.rosetta_rpc,
+            #[cfg(feature = "grpc_rpc")]
+            grpc_rpc_config: config.grpc_rpc,
             genesis,
             validator_signer,
         }
@@ -12,30 +12,25 @@ use tracing::{error, info, trace};
 use near_chain::ChainGenesis;
 #[cfg(feature = "test_features")]
 use near_client::AdversarialControls;
-use near_client::{start_client, start_view_client, ClientActor, ViewClientActor};
+use near_client::{
+    start_chain_events_actor, start_client, start_store_validator_actor, start_view_client,
+    ChainEvents, ClientActor, ViewClientActor,
+};
 
+#[cfg(feature = "grpc_rpc")]
+use near_grpc_rpc::start_grpc_rpc;
 use near_network::routing::start_routing_table_actor;
 use near_network::types::NetworkRecipient;
 use near_network::PeerManagerActor;
 use near_primitives::network::PeerId;
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::start_rosetta_rpc;
-use near_store::migrations::migrate_29_to_30;
-use near_store::migrations::{
-    fill_col_outcomes_by_hash, fill_col_transaction_refcount, get_store_version, migrate_10_to_11,
-    migrate_11_to_12, migrate_13_to_14, migrate_14_to_15, migrate_17_to_18, migrate_21_to_22,
-    migrate_25_to_26, migrate_28_to_29, migrate_6_to_7, migrate_7_to_8, migrate_8_to_9,
-    migrate_9_to_10, set_store_version,
-};
-use near_store::migrations::{migrate_20_to_21, migrate_26_to_27};
-use near_store::{create_store, Store};
+use near_store::migrations::{get_store_version, set_store_version, MigrationRegistry};
+use near_store::{create_split_store, create_store, create_store_with_backend, DBBackend, Store};
 use near_telemetry::TelemetryActor;
 
 pub use crate::config::{init_configs, load_config, load_test_config, NearConfig, NEAR_BASE};
-use crate::migrations::{
-    migrate_12_to_13, migrate_18_to_19, migrate_19_to_20, migrate_22_to_23, migrate_23_to_24,
-    migrate_24_to_25,
-};
+use crate::migrations::{cold_migration_registry, migration_registry};
 pub use crate::runtime::NightshadeRuntime;
 pub use crate::shard_tracker::TrackedConfig;
 
@@ -77,6 +72,10 @@ pub fn get_default_home() -> PathBuf {
 
 /// Function checks current version of the database and applies migrations to the database.
 pub fn apply_store_migrations(path: &Path, near_config: &NearConfig) {
+    apply_migrations(path, migration_registry(near_config))
+}
+
+fn apply_migrations(path: &Path, registry: MigrationRegistry<'_>) {
     let db_version = get_store_version(path);
     if db_version > near_primitives::version::DB_VERSION {
         error!(target: "near", "DB version {} is created by a newer version of neard, please update neard or delete data", db_version);
@@ -86,176 +85,7 @@ pub fn apply_store_migrations(path: &Path, near_config: &NearConfig) {
         return;
     }
 
-    // Add migrations here based on `db_version`.
-    if db_version <= 1 {
-        // version 1 => 2: add gc column
-        // Does not need to do anything since open db with option `create_missing_column_families`
-        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
-        info!(target: "near", "Migrate DB from version 1 to 2");
-        let store = create_store(&path);
-        set_store_version(&store, 2);
-    }
-    if db_version <= 2 {
-        // version 2 => 3: add ColOutcomesByBlockHash + rename LastComponentNonce -> ColLastComponentNonce
-        // The column number is the same, so we don't need additional updates
-        info!(target: "near", "Migrate DB from version 2 to 3");
-        let store = create_store(&path);
-        fill_col_outcomes_by_hash(&store);
-        set_store_version(&store, 3);
-    }
-    if db_version <= 3 {
-        // version 3 => 4: add ColTransactionRefCount
-        info!(target: "near", "Migrate DB from version 3 to 4");
-        let store = create_store(&path);
-        fill_col_transaction_refcount(&store);
-        set_store_version(&store, 4);
-    }
-    if db_version <= 4 {
-        info!(target: "near", "Migrate DB from version 4 to 5");
-        // version 4 => 5: add ColProcessedBlockHeights
-        // we don't need to backfill the old heights since at worst we will just process some heights
-        // again.
-        let store = create_store(&path);
-        set_store_version(&store, 5);
-    }
-    if db_version <= 5 {
-        info!(target: "near", "Migrate DB from version 5 to 6");
-        // version 5 => 6: add merge operator to ColState
-        // we don't have merge records before so old storage works
-        let store = create_store(&path);
-        set_store_version(&store, 6);
-    }
-    if db_version <= 6 {
-        info!(target: "near", "Migrate DB from version 6 to 7");
-        // version 6 => 7:
-        // - make ColState use 8 bytes for refcount (change to merge operator)
-        // - move ColTransactionRefCount into ColTransactions
-        // - make ColReceiptIdToShardId refcounted
-        migrate_6_to_7(path);
-    }
-    if db_version <= 7 {
-        info!(target: "near", "Migrate DB from version 7 to 8");
-        // version 7 => 8:
-        // delete values in column `StateColParts`
-        migrate_7_to_8(path);
-    }
-    if db_version <= 8 {
-        info!(target: "near", "Migrate DB from version 8 to 9");
-        // version 8 => 9:
-        // Repair `ColTransactions`, `ColReceiptIdToShardId`
-        migrate_8_to_9(path);
-    }
-    if db_version <= 9 {
-        info!(target: "near", "Migrate DB from version 9 to 10");
-        // version 9 => 10;
-        // populate partial encoded chunks for chunks that exist in storage
-        migrate_9_to_10(path, near_config.client_config.archive);
-    }
-    if db_version <= 10 {
-        info!(target: "near", "Migrate DB from version 10 to 11");
-        // version 10 => 11
-        // Add final head
-        migrate_10_to_11(path);
-    }
-    if db_version <= 11 {
-        info!(target: "near", "Migrate DB from version 11 to 12");
-        // version 11 => 12;
-        // populate ColReceipts with existing receipts
-        migrate_11_to_12(path);
-    }
-    if db_version <= 12 {
-        info!(target: "near", "Migrate DB from version 12 to 13");
-        // version 12 => 13;
-        // migrate ColTransactionResult to fix the inconsistencies there
-        migrate_12_to_13(path, near_config);
-    }
-    if db_version <= 13 {
-        info!(target: "near", "Migrate DB from version 13 to 14");
-        // version 13 => 14;
-        // store versioned enums for shard chunks
-        migrate_13_to_14(path);
-    }
-    if db_version <= 14 {
-        info!(target: "near", "Migrate DB from version 14 to 15");
-        // version 14 => 15;
-        // Change ColOutcomesByBlockHash to be ordered within each shard
-        migrate_14_to_15(path);
-    }
-    if db_version <= 15 {
-        info!(target: "near", "Migrate DB from version 15 to 16");
-        // version 15 => 16: add column for compiled contracts
-        let store = create_store(&path);
-        set_store_version(&store, 16);
-    }
-    if db_version <= 16 {
-        info!(target: "near", "Migrate DB from version 16 to 17");
-        // version 16 => 17: add column for storing epoch validator info
-        let store = create_store(&path);
-        set_store_version(&store, 17);
-    }
-    if db_version <= 17 {
-        info!(target: "near", "Migrate DB from version 17 to 18");
-        // version 17 => 18: add `hash` to `BlockInfo` and ColHeaderHashesByHeight
-        migrate_17_to_18(&path);
-    }
-    if db_version <= 18 {
-        info!(target: "near", "Migrate DB from version 18 to 19");
-        // version 18 => 19: populate ColEpochValidatorInfo for archival nodes
-        migrate_18_to_19(&path, near_config);
-    }
-    if db_version <= 19 {
-        info!(target: "near", "Migrate DB from version 19 to 20");
-        // version 19 => 20: fix execution outcome
-        migrate_19_to_20(&path, &near_config);
-    }
-    if db_version <= 20 {
-        info!(target: "near", "Migrate DB from version 20 to 21");
-        // version 20 => 21: delete genesis json hash due to change in Genesis::json_hash function
-        migrate_20_to_21(&path);
-    }
-    if db_version <= 21 {
-        info!(target: "near", "Migrate DB from version 21 to 22");
-        // version 21 => 22: rectify inflation: add `timestamp` to `BlockInfo`
-        migrate_21_to_22(&path);
-    }
-    if db_version <= 22 {
-        info!(target: "near", "Migrate DB from version 22 to 23");
-        migrate_22_to_23(&path, &near_config);
-    }
-    if db_version <= 23 {
-        info!(target: "near", "Migrate DB from version 23 to 24");
-        migrate_23_to_24(&path, &near_config);
-    }
-    if db_version <= 24 {
-        info!(target: "near", "Migrate DB from version 24 to 25");
-        migrate_24_to_25(&path);
-    }
-    if db_version <= 25 {
-        info!(target: "near", "Migrate DB from version 25 to 26");
-        migrate_25_to_26(&path);
-    }
-    if db_version <= 26 {
-        info!(target: "near", "Migrate DB from version 26 to 27");
-        migrate_26_to_27(&path, near_config.client_config.archive);
-    }
-    if db_version <= 27 {
-        // version 27 => 28: add ColStateChangesForSplitStates
-        // Does not need to do anything since open db with option `create_missing_column_families`
-        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
-        info!(target: "near", "Migrate DB from version 27 to 28");
-        let store = create_store(&path);
-        set_store_version(&store, 28);
-    }
-    if db_version <= 28 {
-        // version 28 => 29: delete ColNextBlockWithNewChunk, ColLastBlockWithNewChunk
-        info!(target: "near", "Migrate DB from version 28 to 29");
-        migrate_28_to_29(&path);
-    }
-    if db_version <= 29 {
-        // version 29 => 30: migrate all structures that use ValidatorStake to versionized version
-        info!(target: "near", "Migrate DB from version 29 to 30");
-        migrate_29_to_30(&path);
-    }
+    registry.apply(path).expect("Failed to migrate the database");
 
     #[cfg(feature = "nightly_protocol")]
     {
@@ -275,14 +105,52 @@ pub fn apply_store_migrations(path: &Path, near_config: &NearConfig) {
 pub fn init_and_migrate_store(home_dir: &Path, near_config: &NearConfig) -> Arc<Store> {
     let path = get_store_path(home_dir);
     let store_exists = store_path_exists(&path);
-    if store_exists {
+    let backend = near_config.config.store_backend;
+    // Migrations are written against RocksDB, other backends can only open databases created
+    // by the same version of neard.
+    if store_exists && backend == DBBackend::RocksDB {
         apply_store_migrations(&path, near_config);
     }
-    let store = create_store(&path);
+    let store = create_store_with_backend(&path, backend);
     if !store_exists {
         set_store_version(&store, near_primitives::version::DB_VERSION);
+    } else if backend != DBBackend::RocksDB {
+        let db_version = near_store::migrations::get_store_version_from_store(&store);
+        if db_version != near_primitives::version::DB_VERSION {
+            error!(target: "near", "DB version {} is not supported by {:?} backend, please delete data", db_version, backend);
+            std::process::exit(1);
+        }
+    }
+    match &near_config.config.cold_store_path {
+        Some(cold_store_path) => open_split_store(home_dir, near_config, &store, cold_store_path),
+        None => store,
+    }
+}
+
+/// Opens the cold database of an archival node and combines it with the main one.
+fn open_split_store(
+    home_dir: &Path,
+    near_config: &NearConfig,
+    hot_store: &Store,
+    cold_store_path: &Path,
+) -> Arc<Store> {
+    if !near_config.client_config.archive {
+        error!(target: "near", "cold_store_path is set, but split storage is only supported by archival nodes");
+        std::process::exit(1);
+    }
+    let path = home_dir.join(cold_store_path);
+    let store_exists = store_path_exists(&path);
+    // The cold database holds a subset of the columns of the main one, so the same migrations
+    // bring it to the current version, reading what the cold database lacks from the hot one.
+    if store_exists {
+        apply_migrations(&path, cold_migration_registry(near_config, hot_store));
+    }
+    let cold_store = create_store(&path);
+    if !store_exists {
+        set_store_version(&cold_store, near_primitives::version::DB_VERSION);
     }
-    store
+    info!(target: "near", "Opened cold database at {}", path.display());
+    create_split_store(hot_store, &cold_store)
 }
 
 pub struct NearNode {
@@ -327,12 +195,42 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
     let view_client = start_view_client(
         config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
         chain_genesis.clone(),
-        view_client_runtime,
+        view_client_runtime.clone(),
         network_adapter.clone(),
         config.client_config.clone(),
         #[cfg(feature = "test_features")]
         adv.clone(),
     );
+    let store_validator_arbiter =
+        config.client_config.store_validator_keys_per_second.map(|keys_per_second| {
+            if keys_per_second == 0 {
+                error!(target: "near", "store_validator_keys_per_second must be positive");
+                std::process::exit(1);
+            }
+            let (_, arbiter) = start_store_validator_actor(
+                config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
+                config.genesis.config.clone(),
+                runtime.clone(),
+                Arc::clone(&store),
+                keys_per_second,
+            );
+            arbiter
+        });
+    // Chain events are only published if there is a server to subscribe to them.
+    #[allow(unused_mut)]
+    let mut publish_chain_events = false;
+    #[cfg(feature = "json_rpc")]
+    {
+        publish_chain_events |= config.rpc_config.is_some();
+    }
+    #[cfg(feature = "grpc_rpc")]
+    {
+        publish_chain_events |= config.grpc_rpc_config.is_some();
+    }
+    let chain_events = publish_chain_events.then(ChainEvents::new);
+    let chain_events_actor = chain_events.clone().map(|chain_events| {
+        start_chain_events_actor(chain_genesis.clone(), view_client_runtime, chain_events)
+    });
     let (client_actor, client_arbiter_handle) = start_client(
         config.client_config,
         chain_genesis,
@@ -341,6 +239,7 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
         network_adapter.clone(),
         config.validator_signer,
         telemetry,
+        chain_events_actor.as_ref().map(|(addr, _)| addr.clone()),
         #[cfg(feature = "test_features")]
         adv.clone(),
     );
@@ -352,6 +251,8 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
     let view_client1 = view_client.clone().recipient();
     config.network_config.verify();
     let network_config = config.network_config;
+    #[cfg(feature = "json_rpc")]
+    let checkpoint_store = store.clone();
     let routing_table_addr =
         start_routing_table_actor(PeerId::new(network_config.public_key.clone()), store.clone());
     #[cfg(all(feature = "json_rpc", feature = "test_features"))]
@@ -367,13 +268,31 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
         .unwrap()
     });
 
+    // Clients have the same access rules and rate limits on all the RPC servers.
+    #[cfg(feature = "json_rpc")]
+    let access_control = Arc::new(near_jsonrpc::AccessControl::new(
+        config
+            .rpc_config
+            .as_ref()
+            .map(|rpc_config| rpc_config.access_config.clone())
+            .unwrap_or_default(),
+    ));
     #[cfg(feature = "json_rpc")]
     if let Some(rpc_config) = config.rpc_config {
+        let checkpoint_addr = rpc_config.checkpoints_dir.as_ref().map(|checkpoints_dir| {
+            near_client::start_checkpoint_actor(checkpoint_store, home_dir.join(checkpoints_dir))
+        });
         rpc_servers.extend_from_slice(&near_jsonrpc::start_http(
             rpc_config,
             config.genesis.config.clone(),
             client_actor.clone(),
             view_client.clone(),
+            chain_events
+                .as_ref()
+                .expect("chain events are published when JSON RPC is enabled")
+                .clone(),
+            checkpoint_addr,
+            access_control.clone(),
             #[cfg(feature = "test_features")]
             network_actor.clone(),
             #[cfg(feature = "test_features")]
@@ -394,6 +313,20 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
         ));
     }
 
+    #[cfg(feature = "grpc_rpc")]
+    if let Some(grpc_rpc_config) = config.grpc_rpc_config {
+        start_grpc_rpc(
+            grpc_rpc_config,
+            client_actor.clone(),
+            view_client.clone(),
+            chain_events
+                .as_ref()
+                .expect("chain events are published when gRPC RPC is enabled")
+                .clone(),
+            access_control,
+        );
+    }
+
     network_adapter.set_recipient(network_actor.recipient());
 
     rpc_servers.shrink_to_fit();
@@ -404,10 +337,9 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
     #[cfg(feature = "performance_stats")]
     reset_memory_usage_max();
 
-    NearNode {
-        client: client_actor,
-        view_client,
-        rpc_servers,
-        arbiters: vec![client_arbiter_handle, arbiter.handle()],
-    }
+    let mut arbiters = vec![client_arbiter_handle, arbiter.handle()];
+    arbiters.extend(store_validator_arbiter);
+    arbiters.extend(chain_events_actor.map(|(_, arbiter)| arbiter));
+
+    NearNode { client: client_actor, view_client, rpc_servers, arbiters }
 }
@@ -19,8 +19,14 @@ use near_primitives::transaction::{
 use near_primitives::types::{AccountId, Balance, Gas};
 use near_primitives::types::{BlockHeight, ShardId};
 use near_store::db::DBCol::ColReceipts;
-use near_store::migrations::{set_store_version, BatchedStoreUpdate};
-use near_store::{create_store, DBCol, StoreUpdate};
+use near_store::migrations::{
+    fill_col_outcomes_by_hash, fill_col_transaction_refcount, migrate_10_to_11, migrate_11_to_12,
+    migrate_14_to_15, migrate_17_to_18, migrate_26_to_27, migrate_30_to_31, migrate_6_to_7,
+    migrate_7_to_8, migrate_8_to_9, migrate_9_to_10, set_store_version, BatchedStoreUpdate,
+    Migration13To14, Migration20To21, Migration21To22, Migration25To26, Migration28To29,
+    Migration29To30, Migration31To32, MigrationRegistry, VersionBump,
+};
+use near_store::{create_store, DBCol, Store, StoreUpdate};
 use std::path::Path;
 
 fn get_chunk(chain_store: &ChainStore, chunk_hash: ChunkHash) -> ShardChunkV1 {
@@ -478,6 +484,171 @@ pub fn migrate_24_to_25(path: &Path) {
     set_store_version(&store, 25);
 }
 
+/// Migrations of the database from version 1 to `DB_VERSION`, one per version.
+pub fn migration_registry(near_config: &NearConfig) -> MigrationRegistry<'_> {
+    registry_with_header_store(near_config, None)
+}
+
+/// Migrations of the cold database of split storage. It doesn't keep the block headers, so
+/// migrations needing them read them from the already migrated hot database.
+pub fn cold_migration_registry<'a>(
+    near_config: &'a NearConfig,
+    hot_store: &Store,
+) -> MigrationRegistry<'a> {
+    registry_with_header_store(near_config, Some(hot_store.clone()))
+}
+
+fn registry_with_header_store(
+    near_config: &NearConfig,
+    header_store: Option<Store>,
+) -> MigrationRegistry<'_> {
+    use DBCol::*;
+
+    let is_archival = near_config.client_config.archive;
+    let mut registry = MigrationRegistry::new();
+    // Adding a column doesn't need to do anything since db is opened with option
+    // `create_missing_column_families`. Nevertheless the version is bumped, because the binary of
+    // the previous version can't open the new db.
+    registry.register(VersionBump { from_version: 1, description: "add gc column" });
+    registry.register_legacy(
+        2,
+        "add ColOutcomesByBlockHash + rename LastComponentNonce -> ColLastComponentNonce",
+        &[ColOutcomeIds],
+        |path| {
+            let store = create_store(path);
+            fill_col_outcomes_by_hash(&store);
+            set_store_version(&store, 3);
+        },
+    );
+    registry.register_legacy(3, "add ColTransactionRefCount", &[_ColTransactionRefCount], |path| {
+        let store = create_store(path);
+        fill_col_transaction_refcount(&store);
+        set_store_version(&store, 4);
+    });
+    // We don't need to backfill the old heights since at worst we will just process some heights
+    // again.
+    registry.register(VersionBump { from_version: 4, description: "add ColProcessedBlockHeights" });
+    // We don't have merge records before so old storage works.
+    registry
+        .register(VersionBump { from_version: 5, description: "add merge operator to ColState" });
+    registry.register_legacy(
+        6,
+        "make ColState use 8 bytes for refcount, move ColTransactionRefCount into ColTransactions, make ColReceiptIdToShardId refcounted",
+        &[ColState, ColTransactions, _ColTransactionRefCount, ColReceiptIdToShardId],
+        migrate_6_to_7,
+    );
+    registry.register_legacy(
+        7,
+        "delete values in column ColStateParts",
+        &[ColStateParts],
+        migrate_7_to_8,
+    );
+    registry.register_legacy(
+        8,
+        "repair ColTransactions, ColReceiptIdToShardId",
+        &[ColTransactions, ColReceiptIdToShardId],
+        migrate_8_to_9,
+    );
+    registry.register_legacy(
+        9,
+        "populate partial encoded chunks for chunks that exist in storage",
+        &[ColChunks, ColPartialChunks],
+        move |path| migrate_9_to_10(path, is_archival),
+    );
+    registry.register_legacy(
+        10,
+        "add final head",
+        &[ColBlockMisc, ColBlockHeader],
+        migrate_10_to_11,
+    );
+    registry.register_legacy(
+        11,
+        "populate ColReceipts with existing receipts",
+        &[ColChunks, ColReceipts],
+        migrate_11_to_12,
+    );
+    registry.register_legacy(
+        12,
+        "migrate ColTransactionResult to fix the inconsistencies there",
+        &[ColTransactionResult],
+        move |path| migrate_12_to_13(path, near_config),
+    );
+    registry.register(Migration13To14);
+    registry.register_legacy(
+        14,
+        "change ColOutcomesByBlockHash to be ordered within each shard",
+        &[ColOutcomeIds, ColBlock, ColChunks, ColEpochInfo],
+        migrate_14_to_15,
+    );
+    registry.register(VersionBump {
+        from_version: 15,
+        description: "add column for compiled contracts",
+    });
+    registry.register(VersionBump {
+        from_version: 16,
+        description: "add column for storing epoch validator info",
+    });
+    registry.register_legacy(
+        17,
+        "add `hash` to `BlockInfo` and ColHeaderHashesByHeight",
+        &[ColBlockInfo, ColBlockHeader, ColHeaderHashesByHeight],
+        migrate_17_to_18,
+    );
+    registry.register_legacy(
+        18,
+        "populate ColEpochValidatorInfo for archival nodes",
+        &[ColEpochValidatorInfo, ColEpochStart],
+        move |path| migrate_18_to_19(path, near_config),
+    );
+    registry.register_legacy(
+        19,
+        "fix execution outcome",
+        &[ColTransactionResult, ColOutcomeIds],
+        move |path| migrate_19_to_20(path, near_config),
+    );
+    registry.register(Migration20To21);
+    registry.register(Migration21To22);
+    registry.register_legacy(
+        22,
+        "fix outcomes of the chunks which weren't applied",
+        &[ColTransactionResult, ColOutcomeIds],
+        move |path| migrate_22_to_23(path, near_config),
+    );
+    registry.register_legacy(
+        23,
+        "restore receipts lost in the apply_chunks bug",
+        &[ColReceipts],
+        move |path| migrate_23_to_24(path, near_config),
+    );
+    registry.register_legacy(
+        24,
+        "fix ColTransactionResult with unknown MethodResolveError",
+        &[ColTransactionResult],
+        migrate_24_to_25,
+    );
+    registry.register(Migration25To26);
+    registry.register_legacy(
+        26,
+        "add ColBlockOrdinal for archival nodes",
+        &[ColBlockHeight, ColBlockMerkleTree, ColBlockOrdinal],
+        move |path| migrate_26_to_27(path, is_archival),
+    );
+    registry.register(VersionBump {
+        from_version: 27,
+        description: "add ColStateChangesForSplitStates",
+    });
+    registry.register(Migration28To29);
+    registry.register(Migration29To30);
+    registry.register_legacy(
+        30,
+        "add ColFlatState and ColFlatStateDeltas",
+        &[ColFlatState, ColChunkExtra, ColBlockMisc, ColState],
+        migrate_30_to_31,
+    );
+    registry.register(Migration31To32 { header_store });
+    registry
+}
+
 lazy_static_include::lazy_static_include_bytes! {
     /// File with account ids and deltas that need to be applied in order to fix storage usage
     /// difference between actual and stored usage, introduced due to bug in access key deletion,
@@ -541,4 +712,44 @@ mod tests {
         let testnet_migration_data = load_migration_data(&"testnet".to_string());
         assert!(testnet_migration_data.restored_receipts.is_empty());
     }
+
+    #[test]
+    fn test_migrate_24_to_25() {
+        use near_store::migrations::get_store_version;
+
+        let outcome = |id: u8| ExecutionOutcomeWithIdAndProof {
+            outcome_with_id: ExecutionOutcomeWithId { id: hash(&[id]), ..Default::default() },
+            ..Default::default()
+        };
+        let migrated = vec![outcome(1)].try_to_vec().unwrap();
+        // The old outcome has no metadata, which is the last byte of the new one.
+        let old = vec![outcome(2)].try_to_vec().unwrap();
+        let old = &old[..old.len() - 1];
+        let tmp_dir = tempfile::Builder::new().prefix("_test_migration").tempdir().unwrap();
+        {
+            let store = create_store(tmp_dir.path());
+            let mut store_update = store.store_update();
+            store_update.set(DBCol::ColTransactionResult, hash(&[1]).as_ref(), &migrated);
+            store_update.set(DBCol::ColTransactionResult, hash(&[2]).as_ref(), old);
+            store_update.commit().unwrap();
+            set_store_version(&store, 24);
+        }
+
+        migrate_24_to_25(tmp_dir.path());
+        assert_eq!(get_store_version(tmp_dir.path()), 25);
+        let store = create_store(tmp_dir.path());
+        assert_eq!(
+            store.get(DBCol::ColTransactionResult, hash(&[1]).as_ref()).unwrap(),
+            Some(migrated)
+        );
+        assert_eq!(
+            store
+                .get_ser::<Vec<ExecutionOutcomeWithIdAndProof>>(
+                    DBCol::ColTransactionResult,
+                    hash(&[2]).as_ref()
+                )
+                .unwrap(),
+            Some(vec![outcome(2)])
+        );
+    }
 }
@@ -46,7 +46,7 @@ use near_vm_runner::precompile_contract;
 use near_store::{
     get_genesis_hash, get_genesis_state_roots, set_genesis_hash, set_genesis_state_roots,
     ApplyStatePartResult, ColState, PartialStorage, ShardTries, Store, StoreCompiledContractCache,
-    StoreUpdate, Trie, WrappedTrieChanges,
+    StoreUpdate, Trie, TrieCacheConfig, WrappedTrieChanges,
 };
 use node_runtime::adapter::ViewRuntimeAdapter;
 use node_runtime::state_viewer::TrieViewer;
@@ -159,6 +159,7 @@ impl NightshadeRuntime {
             trie_viewer_state_size_limit,
             max_gas_burnt_view,
             None,
+            config.config.trie_cache.clone(),
         )
     }
 
@@ -170,6 +171,7 @@ impl NightshadeRuntime {
         trie_viewer_state_size_limit: Option<u64>,
         max_gas_burnt_view: Option<Gas>,
         runtime_config_store: Option<RuntimeConfigStore>,
+        trie_cache_config: TrieCacheConfig,
     ) -> Self {
         let runtime_config_store = match runtime_config_store {
             Some(store) => store,
@@ -188,8 +190,9 @@ impl NightshadeRuntime {
         );
         let state_roots =
             Self::initialize_genesis_state_if_needed(store.clone(), home_dir, genesis);
-        let tries = ShardTries::new(
+        let tries = ShardTries::new_with_cache_config(
             store.clone(),
+            trie_cache_config,
             genesis_config.shard_layout.version(),
             genesis.config.num_block_producer_seats_per_shard.len() as NumShards,
         );
@@ -219,7 +222,16 @@ impl NightshadeRuntime {
         tracked_config: TrackedConfig,
         runtime_config_store: RuntimeConfigStore,
     ) -> Self {
-        Self::new(home_dir, store, genesis, tracked_config, None, None, Some(runtime_config_store))
+        Self::new(
+            home_dir,
+            store,
+            genesis,
+            tracked_config,
+            None,
+            None,
+            Some(runtime_config_store),
+            TrieCacheConfig::default(),
+        )
     }
 
     pub fn test(home_dir: &Path, store: Arc<Store>, genesis: &Genesis) -> Self {
@@ -1607,6 +1619,22 @@ impl RuntimeAdapter for NightshadeRuntime {
         Ok(result)
     }
 
+    fn obtain_state_parts(
+        &self,
+        shard_id: ShardId,
+        block_hash: &CryptoHash,
+        state_root: &StateRoot,
+        part_ids: &[u64],
+        num_parts: u64,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        part_ids
+            .par_iter()
+            .map(|&part_id| {
+                self.obtain_state_part(shard_id, block_hash, state_root, part_id, num_parts)
+            })
+            .collect()
+    }
+
     fn validate_state_part(
         &self,
         state_root: &StateRoot,
@@ -1717,7 +1745,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         num_parts: u64,
         data: &[u8],
         epoch_id: &EpochId,
-    ) -> Result<(), Error> {
+    ) -> Result<StoreUpdate, Error> {
         let part = BorshDeserialize::try_from_slice(data)
             .expect("Part was already validated earlier, so could never fail here");
         let ApplyStatePartResult { trie_changes, contract_codes } =
@@ -1727,7 +1755,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         let (store_update, _) =
             tries.apply_all(&trie_changes, shard_uid).expect("TrieChanges::into never fails");
         self.precompile_contracts(epoch_id, contract_codes)?;
-        Ok(store_update.commit()?)
+        Ok(store_update)
     }
 
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
@@ -2117,6 +2145,7 @@ mod test {
                 None,
                 None,
                 Some(RuntimeConfigStore::free()),
+                TrieCacheConfig::default(),
             );
             let (_store, state_roots) = runtime.genesis_state();
             let genesis_hash = hash(&vec![0]);
@@ -2689,6 +2718,8 @@ mod test {
         new_env
             .runtime
             .apply_state_part(0, &env.state_roots[0], 0, 1, &state_part, epoch_id)
+            .unwrap()
+            .commit()
             .unwrap();
         new_env.state_roots[0] = env.state_roots[0].clone();
         for _ in 3..=5 {
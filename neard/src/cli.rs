@@ -61,6 +61,8 @@ impl NeardCmd {
             NeardSubCommand::StateViewer(cmd) => {
                 cmd.run(&home_dir);
             }
+            NeardSubCommand::Checkpoint(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::Migrate(cmd) => cmd.run(&home_dir),
         }
     }
 }
@@ -105,6 +107,12 @@ pub(super) enum NeardSubCommand {
     /// View DB state.
     #[clap(name = "view_state")]
     StateViewer(StateViewerSubCommand),
+    /// Create or restore database checkpoints
+    #[clap(name = "checkpoint")]
+    Checkpoint(CheckpointCmd),
+    /// Migrates the database to the version of this binary without starting the node
+    #[clap(name = "migrate")]
+    Migrate(MigrateCmd),
 }
 
 #[derive(Clap)]
@@ -339,6 +347,177 @@ impl TestnetCmd {
     }
 }
 
+#[derive(Clap)]
+pub(super) struct CheckpointCmd {
+    #[clap(subcommand)]
+    subcmd: CheckpointSubCommand,
+}
+
+#[derive(Clap)]
+enum CheckpointSubCommand {
+    /// Asks the running node to write a consistent copy of its database to the given directory.
+    /// Goes through the admin RPC, which has to be enabled with `rpc.checkpoints_dir` in
+    /// config.json and only accepts connections from localhost.
+    #[cfg(feature = "json_rpc")]
+    #[clap(name = "create")]
+    Create(CreateCheckpointCmd),
+    /// Restores a checkpoint as the database of this home directory. The node must be stopped
+    /// and must not have a database.
+    #[clap(name = "restore")]
+    Restore(RestoreCheckpointCmd),
+}
+
+impl CheckpointCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        match self.subcmd {
+            #[cfg(feature = "json_rpc")]
+            CheckpointSubCommand::Create(cmd) => cmd.run(home_dir),
+            CheckpointSubCommand::Restore(cmd) => cmd.run(home_dir),
+        }
+    }
+}
+
+#[cfg(feature = "json_rpc")]
+#[derive(Clap)]
+struct CreateCheckpointCmd {
+    /// Directory to write the checkpoint to, relative to the checkpoints directory of the node.
+    /// It must not exist. The checkpoints directory should be on the same filesystem as the
+    /// node data, so that files can be hard-linked instead of copied.
+    #[clap(parse(from_os_str))]
+    path: PathBuf,
+    /// RPC endpoint of the running node. Defaults to the RPC port from config.json on localhost.
+    #[clap(long)]
+    rpc_addr: Option<String>,
+}
+
+#[cfg(feature = "json_rpc")]
+impl CreateCheckpointCmd {
+    fn run(self, home_dir: &Path) {
+        let rpc_addr = self.rpc_addr.unwrap_or_else(|| {
+            let config = nearcore::config::Config::from_file(
+                &home_dir.join(nearcore::config::CONFIG_FILENAME),
+            );
+            let addr: SocketAddr = config
+                .rpc
+                .unwrap_or_default()
+                .addr
+                .parse()
+                .expect("Failed to parse RPC address from config");
+            format!("http://127.0.0.1:{}", addr.port())
+        });
+
+        let request = near_jsonrpc_primitives::types::checkpoint::RpcCreateCheckpointRequest {
+            path: self.path,
+        };
+        let sys = actix::System::new();
+        let result = sys.block_on(async move {
+            near_jsonrpc_client::new_client(&rpc_addr).adm_create_checkpoint(request).await
+        });
+        match result {
+            Ok(checkpoint) => println!(
+                "Created checkpoint in {} at height {} ({}), DB version {}",
+                checkpoint.path.display(),
+                checkpoint.head_height,
+                checkpoint.head_hash,
+                checkpoint.db_version
+            ),
+            Err(err) => {
+                eprintln!("Failed to create checkpoint: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+struct RestoreCheckpointCmd {
+    /// Directory with a checkpoint created by `checkpoint create`.
+    #[clap(parse(from_os_str))]
+    path: PathBuf,
+}
+
+impl RestoreCheckpointCmd {
+    fn run(self, home_dir: &Path) {
+        let store_path = get_store_path(home_dir);
+        match near_store::checkpoint::restore_checkpoint(&self.path, &store_path) {
+            Ok(metadata) => println!(
+                "Restored checkpoint at height {} ({}), DB version {} to {}",
+                metadata.head_height,
+                metadata.head_hash,
+                metadata.db_version,
+                store_path.display()
+            ),
+            Err(err) => {
+                eprintln!("Failed to restore checkpoint: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct MigrateCmd {
+    /// Only reports how many records the next migration would change, without changing anything.
+    #[clap(long)]
+    dry_run: bool,
+    /// Size in bytes of the changes a migration commits at once. An interrupted migration
+    /// continues after the last committed batch.
+    #[clap(long, default_value = "10000000")]
+    batch_size: usize,
+}
+
+impl MigrateCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        let near_config = nearcore::config::load_config_without_genesis_records(home_dir);
+        let store_path = get_store_path(home_dir);
+        if !nearcore::store_path_exists(&store_path) {
+            eprintln!("No database at {}", store_path.display());
+            std::process::exit(1);
+        }
+        let db_version = near_store::migrations::get_store_version(&store_path);
+        if db_version > near_primitives::version::DB_VERSION {
+            eprintln!("DB version {} is created by a newer version of neard", db_version);
+            std::process::exit(1);
+        }
+        let mut registry = nearcore::migrations::migration_registry(&near_config);
+        registry.set_batch_size(self.batch_size);
+        if !self.dry_run {
+            if let Err(err) = registry.apply(&store_path) {
+                eprintln!("Failed to migrate database: {}", err);
+                std::process::exit(1);
+            }
+            println!(
+                "Migrated database to version {}",
+                near_store::migrations::get_store_version(&store_path)
+            );
+            return;
+        }
+        match registry.dry_run(&store_path) {
+            Ok(None) => println!("Database is up to date"),
+            Ok(Some(report)) => {
+                println!(
+                    "Migration from version {} to {}: {}",
+                    report.from_version,
+                    report.from_version + 1,
+                    report.description
+                );
+                if report.legacy {
+                    println!("Migration doesn't support dry run");
+                    return;
+                }
+                for (col, visited) in report.visited {
+                    println!("{:?}: {} records", col, visited);
+                }
+                println!("Records to change: {}", report.changed);
+            }
+            Err(err) => {
+                eprintln!("Failed to dry run migration: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
 fn init_logging(verbose: Option<&str>) {
     let mut env_filter = EnvFilter::new(
         "tokio_reactor=info,near=info,stats=info,telemetry=info,delay_detector=info,\
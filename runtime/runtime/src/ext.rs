@@ -5,6 +5,7 @@ use log::debug;
 
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
+use near_primitives::checked_feature;
 use near_primitives::contract::ContractCode;
 use near_primitives::errors::{ExternalError, StorageError};
 use near_primitives::hash::CryptoHash;
@@ -137,6 +138,24 @@ impl<'a> RuntimeExt<'a> {
     pub fn protocol_version(&self) -> ProtocolVersion {
         self.current_protocol_version
     }
+
+    /// Reads the value of `storage_key` from the flat state once the protocol charges storage
+    /// reads without counting touched trie nodes.
+    fn get_storage_value(
+        &self,
+        storage_key: &TrieKey,
+    ) -> ExtResult<Option<TrieUpdateValuePtr<'_>>> {
+        let value = if checked_feature!(
+            "protocol_feature_flat_state",
+            FlatStateReads,
+            self.current_protocol_version
+        ) {
+            self.trie_update.get_flat(storage_key)
+        } else {
+            self.trie_update.get_ref(storage_key)
+        };
+        value.map_err(wrap_storage_error)
+    }
 }
 
 fn wrap_storage_error(error: StorageError) -> VMLogicError {
@@ -157,9 +176,7 @@ impl<'a> External for RuntimeExt<'a> {
 
     fn storage_get<'b>(&'b self, key: &[u8]) -> ExtResult<Option<Box<dyn ValuePtr + 'b>>> {
         let storage_key = self.create_storage_key(key);
-        self.trie_update
-            .get_ref(&storage_key)
-            .map_err(wrap_storage_error)
+        self.get_storage_value(&storage_key)
             .map(|option| option.map(|ptr| Box::new(RuntimeExtValuePtr(ptr)) as Box<_>))
     }
 
@@ -171,7 +188,7 @@ impl<'a> External for RuntimeExt<'a> {
 
     fn storage_has_key(&mut self, key: &[u8]) -> ExtResult<bool> {
         let storage_key = self.create_storage_key(key);
-        self.trie_update.get_ref(&storage_key).map(|x| x.is_some()).map_err(wrap_storage_error)
+        self.get_storage_value(&storage_key).map(|x| x.is_some())
     }
 
     fn storage_remove_subtree(&mut self, prefix: &[u8]) -> ExtResult<()> {
@@ -22,6 +22,30 @@ use near_store::{
 use crate::config::RuntimeConfig;
 use crate::Runtime;
 
+/// Returns the number of bytes the record adds to the `storage_usage` of its account, or `None`
+/// if the record doesn't count towards the storage usage of any account.
+pub fn record_storage_usage(config: &StorageUsageConfig, record: &StateRecord) -> Option<u64> {
+    match record {
+        StateRecord::Account { .. } => Some(config.num_bytes_account),
+        StateRecord::Data { data_key, value, .. } => {
+            Some(config.num_extra_bytes_record + data_key.len() as u64 + value.len() as u64)
+        }
+        StateRecord::Contract { code, .. } => Some(code.len() as u64),
+        StateRecord::AccessKey { public_key, access_key, .. } => {
+            let public_key: PublicKey = public_key.clone();
+            let access_key: AccessKey = access_key.clone().into();
+            Some(
+                config.num_extra_bytes_record
+                    + public_key.try_to_vec().unwrap().len() as u64
+                    + access_key.try_to_vec().unwrap().len() as u64,
+            )
+        }
+        StateRecord::PostponedReceipt(_) => None,
+        StateRecord::ReceivedData { .. } => None,
+        StateRecord::DelayedReceipt(_) => None,
+    }
+}
+
 pub struct StorageComputer<'a> {
     result: HashMap<AccountId, u64>,
     config: &'a StorageUsageConfig,
@@ -33,32 +57,9 @@ impl<'a> StorageComputer<'a> {
     }
 
     pub fn process_record(&mut self, record: &StateRecord) {
-        let account_and_storage = match record {
-            StateRecord::Account { account_id, .. } => {
-                Some((account_id.clone(), self.config.num_bytes_account))
-            }
-            StateRecord::Data { account_id, data_key, value } => {
-                let storage_usage =
-                    self.config.num_extra_bytes_record + data_key.len() as u64 + value.len() as u64;
-                Some((account_id.clone(), storage_usage))
-            }
-            StateRecord::Contract { account_id, code } => {
-                Some((account_id.clone(), code.len() as u64))
-            }
-            StateRecord::AccessKey { account_id, public_key, access_key } => {
-                let public_key: PublicKey = public_key.clone();
-                let access_key: AccessKey = access_key.clone().into();
-                let storage_usage = self.config.num_extra_bytes_record
-                    + public_key.try_to_vec().unwrap().len() as u64
-                    + access_key.try_to_vec().unwrap().len() as u64;
-                Some((account_id.clone(), storage_usage))
-            }
-            StateRecord::PostponedReceipt(_) => None,
-            StateRecord::ReceivedData { .. } => None,
-            StateRecord::DelayedReceipt(_) => None,
-        };
-        if let Some((account_id, storage_usage)) = account_and_storage {
-            *self.result.entry(account_id).or_default() += storage_usage;
+        if let Some(storage_usage) = record_storage_usage(self.config, record) {
+            let account_id = state_record_to_account_id(record);
+            *self.result.entry(account_id.clone()).or_default() += storage_usage;
         }
     }
 
@@ -83,10 +84,14 @@ impl GenesisStateApplier {
         shard_uid: ShardUId,
     ) {
         state_update.commit(StateChangeCause::InitialState);
-        let trie_changes = state_update.finalize_genesis().expect("Genesis state update failed");
+        let (trie_changes, state_changes) =
+            state_update.finalize().expect("Genesis state update failed");
 
-        let (store_update, new_state_root) =
+        let (mut store_update, new_state_root) =
             tries.apply_all(&trie_changes, shard_uid).expect("Failed to apply genesis chunk");
+        tries
+            .apply_genesis_flat_state(&trie_changes, &state_changes, shard_uid, &mut store_update)
+            .expect("Failed to apply genesis flat state");
         store_update.commit().expect("Store update failed on genesis initialization");
         *current_state_root = new_state_root;
     }
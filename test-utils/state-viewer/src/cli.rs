@@ -5,12 +5,14 @@ use clap::{AppSettings, Clap};
 use once_cell::sync::Lazy;
 
 use near_logger_utils::init_integration_logger;
+use near_primitives::hash::CryptoHash;
 use near_primitives::types::{BlockHeight, ShardId};
 use near_primitives::version::{DB_VERSION, PROTOCOL_VERSION};
-use near_store::{create_store, Store};
+use near_store::{create_read_only_store, create_store, DBCol, Store};
 use nearcore::{get_default_home, get_store_path, load_config, NearConfig};
 
 use crate::commands::*;
+use crate::export::ExportFormat;
 
 static DEFAULT_HOME: Lazy<PathBuf> = Lazy::new(|| get_default_home());
 
@@ -80,24 +82,68 @@ pub enum StateViewerSubCommand {
     /// Dump contract data in storage of given account to binary file.
     #[clap(name = "dump_account_storage")]
     DumpAccountStorage(DumpAccountStorageCmd),
+    /// Dump state sync headers and parts into a directory which other nodes can sync state from.
+    #[clap(name = "dump_state_parts")]
+    DumpStateParts(DumpStatePartsCmd),
+    /// Print the size of database columns and the share of garbage in refcounted columns.
+    #[clap(name = "db_stats")]
+    DbStats(DbStatsCmd),
+    /// Run a manual compaction of database columns, e.g. after a lot of data got garbage
+    /// collected. The node has to be stopped, since RocksDB can't compact a database opened
+    /// read-only or as a secondary instance.
+    #[clap(name = "compact_db")]
+    CompactDb(CompactDbCmd),
+    /// Print the keys whose values differ between the states of two shards after two blocks,
+    /// possibly from two databases.
+    #[clap(name = "diff_state")]
+    DiffState(DiffStateCmd),
+    /// Export accounts, access keys, contract code hashes, contract storage and transaction
+    /// outcomes as CSV or Parquet files.
+    #[clap(name = "export")]
+    Export(ExportCmd),
+    /// Recompute storage usage of all accounts and report the accounts whose stored storage
+    /// usage is different.
+    #[clap(name = "audit_storage_usage")]
+    AuditStorageUsage(AuditStorageUsageCmd),
 }
 
 impl StateViewerSubCommand {
     pub fn run(self, home_dir: &Path) {
+        let store_path = get_store_path(&home_dir);
+        // These commands open the database on their own, see their docs.
+        let cmd = match self {
+            StateViewerSubCommand::DbStats(cmd) => return cmd.run(&store_path),
+            StateViewerSubCommand::CompactDb(cmd) => return cmd.run(&store_path),
+            cmd => cmd,
+        };
         let near_config = load_config(home_dir);
-        let store = create_store(&get_store_path(&home_dir));
-        match self {
-            StateViewerSubCommand::Peers => peers(store),
-            StateViewerSubCommand::State => state(home_dir, near_config, store),
-            StateViewerSubCommand::DumpState(cmd) => cmd.run(home_dir, near_config, store),
-            StateViewerSubCommand::Chain(cmd) => cmd.run(home_dir, near_config, store),
+        let store = create_store(&store_path);
+        // Commands which only read from the database work on a snapshot so that all the columns
+        // they touch are consistent with each other.
+        let snapshot = Arc::new(store.snapshot());
+        match cmd {
+            StateViewerSubCommand::Peers => peers(snapshot),
+            StateViewerSubCommand::State => state(home_dir, near_config, snapshot),
+            StateViewerSubCommand::DumpState(cmd) => cmd.run(home_dir, near_config, snapshot),
+            StateViewerSubCommand::Chain(cmd) => cmd.run(home_dir, near_config, snapshot),
             StateViewerSubCommand::Replay(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ApplyRange(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::Apply(cmd) => cmd.run(home_dir, near_config, store),
-            StateViewerSubCommand::ViewChain(cmd) => cmd.run(near_config, store),
-            StateViewerSubCommand::CheckBlock => check_block_chunk_existence(store, near_config),
-            StateViewerSubCommand::DumpCode(cmd) => cmd.run(home_dir, near_config, store),
-            StateViewerSubCommand::DumpAccountStorage(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::ViewChain(cmd) => cmd.run(near_config, snapshot),
+            StateViewerSubCommand::CheckBlock => check_block_chunk_existence(snapshot, near_config),
+            StateViewerSubCommand::DumpCode(cmd) => cmd.run(home_dir, near_config, snapshot),
+            StateViewerSubCommand::DumpAccountStorage(cmd) => {
+                cmd.run(home_dir, near_config, snapshot)
+            }
+            StateViewerSubCommand::DumpStateParts(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::DbStats(_) | StateViewerSubCommand::CompactDb(_) => {
+                unreachable!()
+            }
+            StateViewerSubCommand::DiffState(cmd) => cmd.run(near_config, snapshot),
+            StateViewerSubCommand::Export(cmd) => cmd.run(home_dir, near_config, snapshot),
+            StateViewerSubCommand::AuditStorageUsage(cmd) => {
+                cmd.run(home_dir, near_config, snapshot)
+            }
         }
     }
 }
@@ -243,3 +289,153 @@ impl DumpAccountStorageCmd {
         );
     }
 }
+
+#[derive(Clap)]
+pub struct DumpStatePartsCmd {
+    /// Hash of the first block of the epoch to dump the state for. Defaults to the epoch of the
+    /// chain head.
+    #[clap(long)]
+    sync_hash: Option<CryptoHash>,
+    /// Shard to dump. Defaults to all shards.
+    #[clap(long)]
+    shard_id: Option<ShardId>,
+    /// Directory to write the dump to.
+    #[clap(long, parse(from_os_str))]
+    output: PathBuf,
+}
+
+impl DumpStatePartsCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Arc<Store>) {
+        dump_state_parts(self.sync_hash, self.shard_id, &self.output, home_dir, near_config, store);
+    }
+}
+
+#[derive(Clap)]
+pub struct DbStatsCmd {
+    /// Columns to print, e.g. `ColState`. Defaults to all columns.
+    #[clap(long)]
+    column: Vec<DBCol>,
+    /// Also read refcounted columns to count garbage records, which takes long for `ColState`.
+    /// Refcounted columns can't be read while the node is running, so without this flag the
+    /// database is opened read-only and can be inspected on a live node.
+    #[clap(long)]
+    count_refcounts: bool,
+}
+
+impl DbStatsCmd {
+    pub fn run(self, store_path: &Path) {
+        let store = if self.count_refcounts {
+            create_store(store_path)
+        } else {
+            create_read_only_store(store_path)
+        };
+        db_stats(self.column, self.count_refcounts, store);
+    }
+}
+
+#[derive(Clap)]
+pub struct CompactDbCmd {
+    /// Columns to compact, e.g. `ColState`. Defaults to all columns.
+    #[clap(long)]
+    column: Vec<DBCol>,
+}
+
+impl CompactDbCmd {
+    pub fn run(self, store_path: &Path) {
+        compact_db(self.column, create_store(store_path));
+    }
+}
+
+#[derive(Clap)]
+pub struct DiffStateCmd {
+    /// Block after which the first state is taken.
+    #[clap(long)]
+    block_hash: CryptoHash,
+    #[clap(long, default_value = "0")]
+    shard_id: ShardId,
+    /// Block after which the second state is taken. Defaults to `--block-hash`.
+    #[clap(long)]
+    other_block_hash: Option<CryptoHash>,
+    /// Shard of the second state. Defaults to `--shard-id`.
+    #[clap(long)]
+    other_shard_id: Option<ShardId>,
+    /// Home directory of a node whose database the second state is read from, e.g. to compare
+    /// the state after `apply_range` with the state of another node. Defaults to this database.
+    /// The database is opened read-only, so the node may keep running. `ColState` records which
+    /// are not compacted yet can't be read from a read-only database; if the command fails on
+    /// them, stop the node and run `compact-db --column ColState` on its home first.
+    #[clap(long, parse(from_os_str))]
+    other_home: Option<PathBuf>,
+    /// Print the values of differing keys, not only the keys.
+    #[clap(long)]
+    values: bool,
+}
+
+impl DiffStateCmd {
+    pub fn run(self, near_config: NearConfig, store: Arc<Store>) {
+        let other_store = match &self.other_home {
+            Some(other_home) => create_read_only_store(&get_store_path(other_home)),
+            None => store.clone(),
+        };
+        diff_state(
+            (self.block_hash, self.shard_id),
+            (
+                self.other_block_hash.unwrap_or(self.block_hash),
+                self.other_shard_id.unwrap_or(self.shard_id),
+            ),
+            self.values,
+            near_config,
+            store,
+            other_store,
+        );
+    }
+}
+
+#[derive(Clap)]
+pub struct ExportCmd {
+    /// Directory to write the files to, one file per table.
+    #[clap(long, parse(from_os_str))]
+    output: PathBuf,
+    /// `csv` or `parquet`.
+    #[clap(long, default_value = "parquet")]
+    format: ExportFormat,
+    /// Height of the block whose state (before applying the block) is exported. Defaults to the
+    /// chain head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+    /// Also export outcomes of transactions and receipts of blocks from this height up to the
+    /// exported state.
+    #[clap(long)]
+    outcomes_start_height: Option<BlockHeight>,
+}
+
+impl ExportCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Arc<Store>) {
+        export_data(
+            self.format,
+            &self.output,
+            self.height,
+            self.outcomes_start_height,
+            home_dir,
+            near_config,
+            store,
+        );
+    }
+}
+
+#[derive(Clap)]
+pub struct AuditStorageUsageCmd {
+    /// JSON file to write the report to.
+    #[clap(long, parse(from_os_str), default_value = "storage_usage_report.json")]
+    output: PathBuf,
+    /// Height of the block whose state (before applying the block) is audited. Defaults to the
+    /// chain head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+}
+
+impl AuditStorageUsageCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Arc<Store>) {
+        audit_storage_usage(&self.output, self.height, home_dir, near_config, store);
+    }
+}
@@ -4,30 +4,39 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use ansi_term::Color::Red;
+use ansi_term::Color::{Green, Red, Yellow};
+use bytesize::ByteSize;
 
 use borsh::BorshSerialize;
 use near_chain::chain::collect_receipts_from_response;
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
+use near_chain::state_sync_dump::shard_dump_dir;
 use near_chain::types::{ApplyTransactionResult, BlockHeaderInfo};
-use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
+use near_chain::{
+    Chain, ChainGenesis, ChainStore, ChainStoreAccess, ChainStoreUpdate, DoomslugThresholdMode,
+    RuntimeAdapter,
+};
 use near_epoch_manager::EpochManager;
 use near_network::iter_peers_from_store;
 use near_primitives::block::BlockHeader;
 use near_primitives::hash::CryptoHash;
-use near_primitives::serialize::to_base;
+use near_primitives::serialize::{to_base, to_base64};
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::state_record::StateRecord;
+use near_primitives::trie_key::trie_key_parsers::parse_trie_key_from_raw_key;
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{BlockHeight, ShardId, StateRoot};
 use near_store::test_utils::create_test_store;
-use near_store::{Store, TrieIterator};
+use near_store::{DBCol, ShardTries, Store, Trie, TrieDiffItem, TrieDiffIterator, TrieIterator};
 use nearcore::{NearConfig, NightshadeRuntime};
 use node_runtime::adapter::ViewRuntimeAdapter;
+use strum::IntoEnumIterator;
 
 use crate::apply_chain_range::apply_chain_range;
+use crate::export::{ExportFormat, Exporter};
 use crate::state_dump::state_dump;
+use crate::storage_usage::{ShardStorageUsageAuditor, StorageUsageReport};
 
 pub(crate) fn peers(store: Arc<Store>) {
     iter_peers_from_store(store, |(peer_id, peer_info)| {
@@ -185,6 +194,301 @@ pub(crate) fn dump_account_storage(
     std::process::exit(1);
 }
 
+pub(crate) fn dump_state_parts(
+    sync_hash: Option<CryptoHash>,
+    shard_id: Option<ShardId>,
+    output: &Path,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Arc<Store>,
+) {
+    let runtime = Arc::new(NightshadeRuntime::with_config(
+        &home_dir,
+        store,
+        &near_config,
+        None,
+        near_config.client_config.max_gas_burnt_view,
+    ));
+    let mut chain = Chain::new_for_view_client(
+        runtime.clone(),
+        &ChainGenesis::from(&near_config.genesis),
+        DoomslugThresholdMode::TwoThirds,
+    )
+    .unwrap();
+    let sync_hash = sync_hash.unwrap_or_else(|| {
+        let head = chain.head().unwrap();
+        let epoch_start_height = runtime.get_epoch_start_height(&head.last_block_hash).unwrap();
+        *chain.get_header_by_height(epoch_start_height).unwrap().hash()
+    });
+    let epoch_id = chain.get_block_header(&sync_hash).unwrap().epoch_id().clone();
+    let shard_ids = match shard_id {
+        Some(shard_id) => vec![shard_id],
+        None => (0..runtime.num_shards(&epoch_id).unwrap()).collect(),
+    };
+    for shard_id in shard_ids {
+        let num_parts = chain.dump_state_sync_data(shard_id, sync_hash, output).unwrap();
+        println!(
+            "Dumped header and {} parts of shard {} to {}",
+            num_parts,
+            shard_id,
+            shard_dump_dir(output, &sync_hash, shard_id).display()
+        );
+    }
+}
+
+fn print_column_stats(store: &Store, columns: &[DBCol]) {
+    let rocksdb = store.get_rocksdb().expect("Column stats are only available for RocksDB");
+    println!(
+        "{:<32} {:>12} {:>12} {:>12} {:>12} {:>14}",
+        "column", "sst", "live sst", "live data", "memtables", "est. keys"
+    );
+    for &col in columns {
+        let stats = rocksdb.column_stats(col).unwrap();
+        println!(
+            "{:<32} {:>12} {:>12} {:>12} {:>12} {:>14}",
+            format!("{:?}", col),
+            ByteSize(stats.total_sst_files_size).to_string(),
+            ByteSize(stats.live_sst_files_size).to_string(),
+            ByteSize(stats.estimate_live_data_size).to_string(),
+            ByteSize(stats.memtables_size).to_string(),
+            stats.estimate_num_keys
+        );
+    }
+}
+
+pub(crate) fn db_stats(columns: Vec<DBCol>, count_refcounts: bool, store: Arc<Store>) {
+    let columns = if columns.is_empty() { DBCol::iter().collect() } else { columns };
+    print_column_stats(&store, &columns);
+    if !count_refcounts {
+        return;
+    }
+    for col in columns.into_iter().filter(|col| col.is_rc()) {
+        println!("Counting refcounts of {:?}, this reads the whole column", col);
+        let stats = store.refcount_stats(col);
+        println!(
+            "{:?}: {} live records ({}), {} garbage records ({}), garbage ratio {:.2}%",
+            col,
+            stats.live_keys,
+            ByteSize(stats.live_bytes),
+            stats.garbage_keys,
+            ByteSize(stats.garbage_bytes),
+            stats.garbage_ratio() * 100.0
+        );
+        if stats.negative_keys > 0 {
+            println!(
+                "{}",
+                Red.bold().paint(format!(
+                    "{:?}: {} records with negative refcount",
+                    col, stats.negative_keys
+                ))
+            );
+        }
+    }
+}
+
+pub(crate) fn compact_db(columns: Vec<DBCol>, store: Arc<Store>) {
+    let rocksdb = store.get_rocksdb().expect("Compaction is only available for RocksDB");
+    let columns = if columns.is_empty() { DBCol::iter().collect() } else { columns };
+    print_column_stats(&store, &columns);
+    for &col in &columns {
+        println!("Compacting {:?}", col);
+        rocksdb.compact(col);
+    }
+    print_column_stats(&store, &columns);
+}
+
+pub(crate) fn export_data(
+    format: ExportFormat,
+    output: &Path,
+    height: Option<BlockHeight>,
+    outcomes_start_height: Option<BlockHeight>,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Arc<Store>,
+) {
+    let exporter = Exporter::new(format, output).unwrap();
+    let mode = match height {
+        Some(height) => LoadTrieMode::Height(height),
+        None => LoadTrieMode::Latest,
+    };
+    let (runtime, state_roots, header) =
+        load_trie_stop_at_height(store.clone(), home_dir, &near_config, mode);
+    println!("Exporting state before block #{} {}", header.height(), header.hash());
+    let mut state_writer = exporter.state_writer().unwrap();
+    for (shard_id, state_root) in state_roots.iter().enumerate() {
+        let trie = runtime.get_trie_for_shard(shard_id as u64, header.prev_hash()).unwrap();
+        for item in TrieIterator::new(&trie, state_root).unwrap() {
+            let (key, value) = item.unwrap();
+            state_writer.write(&key, value).unwrap();
+        }
+    }
+    state_writer.finish().unwrap();
+
+    if let Some(start_height) = outcomes_start_height {
+        let end_height = header.height();
+        println!("Exporting outcomes of blocks #{}..=#{}", start_height, end_height);
+        let mut chain_store = ChainStore::new(store, near_config.genesis.config.genesis_height);
+        let mut outcomes_writer = exporter.outcomes_writer().unwrap();
+        for height in start_height..=end_height {
+            let block_hash = match chain_store.get_block_hash_by_height(height) {
+                Ok(block_hash) => block_hash,
+                // No block at this height.
+                Err(_) => continue,
+            };
+            let num_shards = chain_store.get_block(&block_hash).unwrap().chunks().len();
+            for shard_id in 0..num_shards as ShardId {
+                for id in chain_store
+                    .get_outcomes_by_block_hash_and_shard_id(&block_hash, shard_id)
+                    .unwrap()
+                {
+                    // Outcomes of the same id can be stored for several forks.
+                    for outcome in chain_store.get_outcomes_by_id(&id).unwrap() {
+                        if outcome.block_hash == block_hash {
+                            outcomes_writer.write(height, shard_id, outcome).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+        outcomes_writer.finish().unwrap();
+    }
+    println!("Exported into {}", output.display());
+}
+
+pub(crate) fn audit_storage_usage(
+    output: &Path,
+    height: Option<BlockHeight>,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Arc<Store>,
+) {
+    let mode = match height {
+        Some(height) => LoadTrieMode::Height(height),
+        None => LoadTrieMode::Latest,
+    };
+    let (runtime, state_roots, header) =
+        load_trie_stop_at_height(store, home_dir, &near_config, mode);
+    let protocol_config = runtime.get_protocol_config(header.epoch_id()).unwrap();
+    let config = &protocol_config.runtime_config.transaction_costs.storage_usage_config;
+    println!(
+        "Auditing storage usage in the state before block #{} {}",
+        header.height(),
+        header.hash()
+    );
+    let mut report = StorageUsageReport {
+        block_height: header.height(),
+        block_hash: *header.hash(),
+        protocol_version: protocol_config.genesis_config.protocol_version,
+        accounts_checked: 0,
+        mismatches: vec![],
+    };
+    for (shard_id, state_root) in state_roots.iter().enumerate() {
+        let shard_id = shard_id as ShardId;
+        let trie = runtime.get_trie_for_shard(shard_id, header.prev_hash()).unwrap();
+        let mut auditor = ShardStorageUsageAuditor::new(shard_id, config);
+        for item in TrieIterator::new(&trie, state_root).unwrap() {
+            let (key, value) = item.unwrap();
+            if let Some(state_record) = StateRecord::from_raw_key_value(key, value) {
+                auditor.process_record(&state_record);
+            }
+        }
+        let mismatches_before = report.mismatches.len();
+        auditor.finish(&mut report);
+        println!(
+            "Shard {}: {} accounts with mismatched storage usage",
+            shard_id,
+            report.mismatches.len() - mismatches_before
+        );
+    }
+    serde_json::to_writer_pretty(&File::create(output).unwrap(), &report).unwrap();
+    println!(
+        "Checked {} accounts, {} mismatched, report saved to {}",
+        report.accounts_checked,
+        report.mismatches.len(),
+        output.display()
+    );
+}
+
+/// Returns the trie of the shard and its state root after the block is applied.
+fn load_state_after_block(
+    store: Arc<Store>,
+    near_config: &NearConfig,
+    block_hash: &CryptoHash,
+    shard_id: ShardId,
+) -> (Trie, StateRoot) {
+    let mut chain_store = ChainStore::new(store.clone(), near_config.genesis.config.genesis_height);
+    let epoch_id =
+        chain_store.get_block_header(block_hash).expect("Block not found").epoch_id().clone();
+    let mut epoch_manager =
+        EpochManager::new_from_genesis_config(store.clone(), &near_config.genesis.config)
+            .expect("Failed to start Epoch Manager");
+    let shard_layout = epoch_manager.get_shard_layout(&epoch_id).unwrap().clone();
+    let shard_uid = ShardUId::from_shard_id_and_layout(shard_id, &shard_layout);
+    let state_root = *chain_store
+        .get_chunk_extra(block_hash, &shard_uid)
+        .expect("Chunk extra of the shard not found")
+        .state_root();
+    let tries = ShardTries::new(store, shard_uid.version, shard_layout.num_shards());
+    (tries.get_trie_for_shard(shard_uid), state_root)
+}
+
+fn format_trie_key(raw_key: &[u8]) -> String {
+    match parse_trie_key_from_raw_key(raw_key) {
+        Ok(TrieKey::ContractData { account_id, key }) => {
+            format!("ContractData {{ account_id: {}, key: {} }}", account_id, to_base64(&key))
+        }
+        Ok(trie_key) => format!("{:?}", trie_key),
+        Err(_) => format!("unknown key {}", to_base64(raw_key)),
+    }
+}
+
+fn format_trie_value(raw_key: &[u8], value: Vec<u8>) -> String {
+    if parse_trie_key_from_raw_key(raw_key).is_ok() {
+        if let Some(state_record) = StateRecord::from_raw_key_value(raw_key.to_vec(), value.clone())
+        {
+            return state_record.to_string();
+        }
+    }
+    to_base64(&value)
+}
+
+pub(crate) fn diff_state(
+    (block_hash, shard_id): (CryptoHash, ShardId),
+    (other_block_hash, other_shard_id): (CryptoHash, ShardId),
+    print_values: bool,
+    near_config: NearConfig,
+    store: Arc<Store>,
+    other_store: Arc<Store>,
+) {
+    let (trie, state_root) = load_state_after_block(store, &near_config, &block_hash, shard_id);
+    let (other_trie, other_state_root) =
+        load_state_after_block(other_store, &near_config, &other_block_hash, other_shard_id);
+    println!(
+        "Comparing state {} of shard {} after block {} with state {} of shard {} after block {}",
+        state_root, shard_id, block_hash, other_state_root, other_shard_id, other_block_hash
+    );
+    let mut num_changed = 0;
+    for item in TrieDiffIterator::new(&trie, &state_root, &other_trie, &other_state_root).unwrap() {
+        let TrieDiffItem { key, old_value, new_value } = item.unwrap();
+        let sign = match (&old_value, &new_value) {
+            (None, _) => Green.paint("+"),
+            (_, None) => Red.paint("-"),
+            _ => Yellow.paint("~"),
+        };
+        println!("{} {}", sign, format_trie_key(&key));
+        if print_values {
+            if let Some(value) = old_value {
+                println!("    {}", Red.paint(format_trie_value(&key, value)));
+            }
+            if let Some(value) = new_value {
+                println!("    {}", Green.paint(format_trie_value(&key, value)));
+            }
+        }
+        num_changed += 1;
+    }
+    println!("{} keys differ", num_changed);
+}
+
 pub(crate) fn print_chain(
     start_height: BlockHeight,
     end_height: BlockHeight,
@@ -0,0 +1,466 @@
+//! Export of state and chain data as tables, one file per table, for analytics.
+//!
+//! Rows are written as they are read, so the memory used doesn't depend on the size of the
+//! state. Parquet files are written in row groups of `PARQUET_ROW_GROUP_SIZE` rows.
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{FileWriter, SerializedFileWriter};
+use parquet::schema::parser::parse_message_type;
+
+use near_primitives::account::{AccessKey, Account};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::serialize::to_base64;
+use near_primitives::transaction::ExecutionOutcomeWithIdAndProof;
+use near_primitives::trie_key::trie_key_parsers::parse_trie_key_from_raw_key;
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::views::{AccessKeyPermissionView, ExecutionStatusView};
+
+const PARQUET_ROW_GROUP_SIZE: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!("Unknown export format {}, expected csv or parquet", s)),
+        }
+    }
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ColumnType {
+    Utf8,
+    Binary,
+    UInt64,
+}
+
+struct Table {
+    name: &'static str,
+    columns: &'static [(&'static str, ColumnType)],
+}
+
+/// Balances don't fit into 64 bits and are exported as decimal strings.
+const ACCOUNTS: Table = Table {
+    name: "accounts",
+    columns: &[
+        ("account_id", ColumnType::Utf8),
+        ("amount", ColumnType::Utf8),
+        ("locked", ColumnType::Utf8),
+        ("code_hash", ColumnType::Utf8),
+        ("storage_usage", ColumnType::UInt64),
+    ],
+};
+
+/// Permissions are exported as JSON, in the same format as in RPC responses.
+const ACCESS_KEYS: Table = Table {
+    name: "access_keys",
+    columns: &[
+        ("account_id", ColumnType::Utf8),
+        ("public_key", ColumnType::Utf8),
+        ("nonce", ColumnType::UInt64),
+        ("permission", ColumnType::Utf8),
+    ],
+};
+
+const CONTRACT_CODE: Table = Table {
+    name: "contract_code",
+    columns: &[
+        ("account_id", ColumnType::Utf8),
+        ("code_hash", ColumnType::Utf8),
+        ("code_size", ColumnType::UInt64),
+    ],
+};
+
+const CONTRACT_DATA: Table = Table {
+    name: "contract_data",
+    columns: &[
+        ("account_id", ColumnType::Utf8),
+        ("key", ColumnType::Binary),
+        ("value", ColumnType::Binary),
+    ],
+};
+
+/// Statuses are exported as JSON, in the same format as in RPC responses.
+const OUTCOMES: Table = Table {
+    name: "outcomes",
+    columns: &[
+        ("block_height", ColumnType::UInt64),
+        ("block_hash", ColumnType::Utf8),
+        ("shard_id", ColumnType::UInt64),
+        ("id", ColumnType::Utf8),
+        ("executor_id", ColumnType::Utf8),
+        ("gas_burnt", ColumnType::UInt64),
+        ("tokens_burnt", ColumnType::Utf8),
+        ("status", ColumnType::Utf8),
+    ],
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Utf8(String),
+    Binary(Vec<u8>),
+    UInt64(u64),
+}
+
+trait TableWriter {
+    fn write_row(&mut self, row: Vec<Value>) -> io::Result<()>;
+
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// Writes binary values encoded as base64.
+struct CsvTableWriter {
+    writer: csv::Writer<File>,
+}
+
+impl CsvTableWriter {
+    fn new(path: &Path, table: &Table) -> io::Result<Self> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(table.columns.iter().map(|(name, _)| name))?;
+        Ok(CsvTableWriter { writer })
+    }
+}
+
+impl TableWriter for CsvTableWriter {
+    fn write_row(&mut self, row: Vec<Value>) -> io::Result<()> {
+        let record = row.into_iter().map(|value| match value {
+            Value::Utf8(value) => value,
+            Value::Binary(value) => to_base64(&value),
+            Value::UInt64(value) => value.to_string(),
+        });
+        Ok(self.writer.write_record(record)?)
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+enum ColumnBuffer {
+    ByteArray(Vec<ByteArray>),
+    Int64(Vec<i64>),
+}
+
+fn parquet_error(err: ParquetError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+struct ParquetTableWriter {
+    writer: SerializedFileWriter<File>,
+    buffers: Vec<ColumnBuffer>,
+    num_rows: usize,
+}
+
+impl ParquetTableWriter {
+    fn new(path: &Path, table: &Table) -> io::Result<Self> {
+        let fields: Vec<_> = table
+            .columns
+            .iter()
+            .map(|(name, column_type)| match column_type {
+                ColumnType::Utf8 => format!("required binary {} (UTF8);", name),
+                ColumnType::Binary => format!("required binary {};", name),
+                ColumnType::UInt64 => format!("required int64 {} (UINT_64);", name),
+            })
+            .collect();
+        let message_type = format!("message {} {{ {} }}", table.name, fields.join(" "));
+        let schema = Arc::new(parse_message_type(&message_type).map_err(parquet_error)?);
+        let properties = Arc::new(WriterProperties::builder().build());
+        let writer = SerializedFileWriter::new(File::create(path)?, schema, properties)
+            .map_err(parquet_error)?;
+        let buffers = table
+            .columns
+            .iter()
+            .map(|(_, column_type)| match column_type {
+                ColumnType::Utf8 | ColumnType::Binary => ColumnBuffer::ByteArray(vec![]),
+                ColumnType::UInt64 => ColumnBuffer::Int64(vec![]),
+            })
+            .collect();
+        Ok(ParquetTableWriter { writer, buffers, num_rows: 0 })
+    }
+
+    fn write_row_group(&mut self) -> Result<(), ParquetError> {
+        let mut row_group = self.writer.next_row_group()?;
+        for buffer in self.buffers.iter_mut() {
+            let mut column = row_group.next_column()?.expect("Schema has a column per buffer");
+            match (&mut column, buffer) {
+                (ColumnWriter::ByteArrayColumnWriter(writer), ColumnBuffer::ByteArray(values)) => {
+                    writer.write_batch(values, None, None)?;
+                    values.clear();
+                }
+                (ColumnWriter::Int64ColumnWriter(writer), ColumnBuffer::Int64(values)) => {
+                    writer.write_batch(values, None, None)?;
+                    values.clear();
+                }
+                _ => unreachable!("Column types match the schema"),
+            }
+            row_group.close_column(column)?;
+        }
+        self.writer.close_row_group(row_group)?;
+        self.num_rows = 0;
+        Ok(())
+    }
+}
+
+impl TableWriter for ParquetTableWriter {
+    fn write_row(&mut self, row: Vec<Value>) -> io::Result<()> {
+        for (buffer, value) in self.buffers.iter_mut().zip(row) {
+            match (buffer, value) {
+                (ColumnBuffer::ByteArray(values), Value::Utf8(value)) => {
+                    values.push(ByteArray::from(value.into_bytes()))
+                }
+                (ColumnBuffer::ByteArray(values), Value::Binary(value)) => {
+                    values.push(ByteArray::from(value))
+                }
+                (ColumnBuffer::Int64(values), Value::UInt64(value)) => values.push(value as i64),
+                _ => unreachable!("Values match the schema"),
+            }
+        }
+        self.num_rows += 1;
+        if self.num_rows == PARQUET_ROW_GROUP_SIZE {
+            self.write_row_group().map_err(parquet_error)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        if self.num_rows > 0 {
+            self.write_row_group().map_err(parquet_error)?;
+        }
+        self.writer.close().map_err(parquet_error)?;
+        Ok(())
+    }
+}
+
+/// Creates the files of the exported tables in a directory.
+pub(crate) struct Exporter {
+    format: ExportFormat,
+    output: PathBuf,
+}
+
+impl Exporter {
+    pub fn new(format: ExportFormat, output: &Path) -> io::Result<Self> {
+        fs::create_dir_all(output)?;
+        Ok(Exporter { format, output: output.to_path_buf() })
+    }
+
+    fn create(&self, table: &Table) -> io::Result<Box<dyn TableWriter>> {
+        let path = self.output.join(format!("{}.{}", table.name, self.format.extension()));
+        Ok(match self.format {
+            ExportFormat::Csv => Box::new(CsvTableWriter::new(&path, table)?),
+            ExportFormat::Parquet => Box::new(ParquetTableWriter::new(&path, table)?),
+        })
+    }
+
+    pub fn state_writer(&self) -> io::Result<StateWriter> {
+        Ok(StateWriter {
+            accounts: self.create(&ACCOUNTS)?,
+            access_keys: self.create(&ACCESS_KEYS)?,
+            contract_code: self.create(&CONTRACT_CODE)?,
+            contract_data: self.create(&CONTRACT_DATA)?,
+        })
+    }
+
+    pub fn outcomes_writer(&self) -> io::Result<OutcomesWriter> {
+        Ok(OutcomesWriter { outcomes: self.create(&OUTCOMES)? })
+    }
+}
+
+/// Writes state records into the tables of accounts, access keys, contract code and contract
+/// data. Other records, e.g. postponed receipts, are skipped.
+pub(crate) struct StateWriter {
+    accounts: Box<dyn TableWriter>,
+    access_keys: Box<dyn TableWriter>,
+    contract_code: Box<dyn TableWriter>,
+    contract_data: Box<dyn TableWriter>,
+}
+
+impl StateWriter {
+    pub fn write(&mut self, key: &[u8], value: Vec<u8>) -> io::Result<()> {
+        match parse_trie_key_from_raw_key(key)? {
+            TrieKey::Account { account_id } => {
+                let account = Account::try_from_slice(&value)?;
+                self.accounts.write_row(vec![
+                    Value::Utf8(account_id.to_string()),
+                    Value::Utf8(account.amount().to_string()),
+                    Value::Utf8(account.locked().to_string()),
+                    Value::Utf8(account.code_hash().to_string()),
+                    Value::UInt64(account.storage_usage()),
+                ])
+            }
+            TrieKey::AccessKey { account_id, public_key } => {
+                let access_key = AccessKey::try_from_slice(&value)?;
+                let permission = AccessKeyPermissionView::from(access_key.permission);
+                self.access_keys.write_row(vec![
+                    Value::Utf8(account_id.to_string()),
+                    Value::Utf8(public_key.to_string()),
+                    Value::UInt64(access_key.nonce),
+                    Value::Utf8(serde_json::to_string(&permission)?),
+                ])
+            }
+            TrieKey::ContractCode { account_id } => self.contract_code.write_row(vec![
+                Value::Utf8(account_id.to_string()),
+                Value::Utf8(hash(&value).to_string()),
+                Value::UInt64(value.len() as u64),
+            ]),
+            TrieKey::ContractData { account_id, key } => self.contract_data.write_row(vec![
+                Value::Utf8(account_id.to_string()),
+                Value::Binary(key),
+                Value::Binary(value),
+            ]),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.accounts.finish()?;
+        self.access_keys.finish()?;
+        self.contract_code.finish()?;
+        self.contract_data.finish()
+    }
+}
+
+/// Writes outcomes of transactions and receipts executed in blocks.
+pub(crate) struct OutcomesWriter {
+    outcomes: Box<dyn TableWriter>,
+}
+
+impl OutcomesWriter {
+    pub fn write(
+        &mut self,
+        block_height: BlockHeight,
+        shard_id: ShardId,
+        outcome: ExecutionOutcomeWithIdAndProof,
+    ) -> io::Result<()> {
+        let block_hash: CryptoHash = outcome.block_hash;
+        let id = outcome.outcome_with_id.id;
+        let outcome = outcome.outcome_with_id.outcome;
+        let status = ExecutionStatusView::from(outcome.status);
+        self.outcomes.write_row(vec![
+            Value::UInt64(block_height),
+            Value::Utf8(block_hash.to_string()),
+            Value::UInt64(shard_id),
+            Value::Utf8(id.to_string()),
+            Value::Utf8(outcome.executor_id.to_string()),
+            Value::UInt64(outcome.gas_burnt),
+            Value::Utf8(outcome.tokens_burnt.to_string()),
+            Value::Utf8(serde_json::to_string(&status)?),
+        ])
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.outcomes.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use borsh::BorshSerialize;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::account::{AccessKey, Account};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::trie_key::TrieKey;
+
+    use super::{ExportFormat, Exporter};
+
+    fn write_state(format: ExportFormat, output: &std::path::Path) {
+        let exporter = Exporter::new(format, output).unwrap();
+        let mut writer = exporter.state_writer().unwrap();
+        let account_id = "alice.near".parse().unwrap();
+        let account = Account::new(10u128.pow(30), 0, CryptoHash::default(), 100);
+        writer
+            .write(
+                &TrieKey::Account { account_id: account_id.clone() }.to_vec(),
+                account.try_to_vec().unwrap(),
+            )
+            .unwrap();
+        writer
+            .write(
+                &TrieKey::AccessKey {
+                    account_id: account_id.clone(),
+                    public_key: PublicKey::empty(KeyType::ED25519),
+                }
+                .to_vec(),
+                AccessKey::full_access().try_to_vec().unwrap(),
+            )
+            .unwrap();
+        writer
+            .write(
+                &TrieKey::ContractData { account_id, key: b"key".to_vec() }.to_vec(),
+                b"value".to_vec(),
+            )
+            .unwrap();
+        writer.write(&TrieKey::DelayedReceiptIndices.to_vec(), vec![0; 16]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let output = tempfile::tempdir().unwrap();
+        write_state(ExportFormat::Csv, output.path());
+        let accounts = std::fs::read_to_string(output.path().join("accounts.csv")).unwrap();
+        assert_eq!(
+            accounts,
+            "account_id,amount,locked,code_hash,storage_usage\n\
+             alice.near,1000000000000000000000000000000,0,11111111111111111111111111111111,100\n"
+        );
+        let contract_data =
+            std::fs::read_to_string(output.path().join("contract_data.csv")).unwrap();
+        assert_eq!(contract_data, "account_id,key,value\nalice.near,a2V5,dmFsdWU=\n");
+        let contract_code =
+            std::fs::read_to_string(output.path().join("contract_code.csv")).unwrap();
+        assert_eq!(contract_code, "account_id,code_hash,code_size\n");
+    }
+
+    #[test]
+    fn test_export_parquet() {
+        let output = tempfile::tempdir().unwrap();
+        write_state(ExportFormat::Parquet, output.path());
+        let reader = SerializedFileReader::new(
+            File::open(output.path().join("access_keys.parquet")).unwrap(),
+        )
+        .unwrap();
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get_string(0).unwrap(), "alice.near");
+        assert_eq!(rows[0].get_ulong(2).unwrap(), 0);
+        assert_eq!(rows[0].get_string(3).unwrap(), "\"FullAccess\"");
+        let reader = SerializedFileReader::new(
+            File::open(output.path().join("contract_data.parquet")).unwrap(),
+        )
+        .unwrap();
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows[0].get_bytes(2).unwrap().data(), b"value");
+    }
+}
@@ -2,7 +2,9 @@
 
 mod apply_chain_range;
 mod commands;
+mod export;
 mod state_dump;
+mod storage_usage;
 
 pub mod cli;
 pub use cli::StateViewerSubCommand;
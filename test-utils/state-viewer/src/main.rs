@@ -1,7 +1,9 @@
 mod apply_chain_range;
 mod cli;
 mod commands;
+mod export;
 mod state_dump;
+mod storage_usage;
 
 fn main() {
     cli::StateViewerCmd::parse_and_run();
@@ -0,0 +1,195 @@
+//! Audit of the `storage_usage` stored in account records.
+//!
+//! Storage usage of every account is recomputed from the records of its shard the same way the
+//! runtime computes it for genesis records, and compared to the value stored in the account.
+//! Only one shard is kept in memory at a time, and only per-account counters, not the records.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::runtime::fees::StorageUsageConfig;
+use near_primitives::state_record::{state_record_to_account_id, StateRecord};
+use near_primitives::types::{AccountId, BlockHeight, ShardId};
+use near_primitives::version::ProtocolVersion;
+use node_runtime::record_storage_usage;
+
+/// Recomputed storage usage of an account, split by the kind of records it comes from.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct StorageUsageBreakdown {
+    pub account: u64,
+    pub access_keys: u64,
+    pub contract_code: u64,
+    pub contract_data: u64,
+}
+
+impl StorageUsageBreakdown {
+    pub fn total(&self) -> u64 {
+        self.account + self.access_keys + self.contract_code + self.contract_data
+    }
+}
+
+/// Account whose stored storage usage is different from the recomputed one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StorageUsageMismatch {
+    pub account_id: AccountId,
+    pub shard_id: ShardId,
+    pub stored: u64,
+    pub computed: u64,
+    /// `computed - stored`, the amount to add to the stored value to fix it.
+    pub delta: i64,
+    pub breakdown: StorageUsageBreakdown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StorageUsageReport {
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    /// Protocol version whose storage usage config was used for the recomputation.
+    pub protocol_version: ProtocolVersion,
+    pub accounts_checked: u64,
+    /// Mismatched accounts, ordered by shard and account id.
+    pub mismatches: Vec<StorageUsageMismatch>,
+}
+
+/// Recomputes storage usage of the accounts of a single shard from its records.
+pub(crate) struct ShardStorageUsageAuditor<'a> {
+    shard_id: ShardId,
+    config: &'a StorageUsageConfig,
+    /// Stored storage usage, if the account record was seen, and the recomputed one.
+    accounts: HashMap<AccountId, (Option<u64>, StorageUsageBreakdown)>,
+}
+
+impl<'a> ShardStorageUsageAuditor<'a> {
+    pub fn new(shard_id: ShardId, config: &'a StorageUsageConfig) -> Self {
+        Self { shard_id, config, accounts: HashMap::new() }
+    }
+
+    pub fn process_record(&mut self, record: &StateRecord) {
+        let storage_usage = match record_storage_usage(self.config, record) {
+            Some(storage_usage) => storage_usage,
+            None => return,
+        };
+        let account_id = state_record_to_account_id(record);
+        let (stored, breakdown) = self.accounts.entry(account_id.clone()).or_default();
+        match record {
+            StateRecord::Account { account, .. } => {
+                *stored = Some(account.storage_usage());
+                breakdown.account += storage_usage;
+            }
+            StateRecord::AccessKey { .. } => breakdown.access_keys += storage_usage,
+            StateRecord::Contract { .. } => breakdown.contract_code += storage_usage,
+            StateRecord::Data { .. } => breakdown.contract_data += storage_usage,
+            _ => unreachable!("only account records count towards storage usage"),
+        }
+    }
+
+    /// Adds the results of the shard to the report. Records of accounts which don't exist are
+    /// ignored since there is no stored storage usage to fix.
+    pub fn finish(self, report: &mut StorageUsageReport) {
+        let mut mismatches = vec![];
+        for (account_id, (stored, breakdown)) in self.accounts {
+            let stored = match stored {
+                Some(stored) => stored,
+                None => continue,
+            };
+            report.accounts_checked += 1;
+            let computed = breakdown.total();
+            if computed != stored {
+                mismatches.push(StorageUsageMismatch {
+                    account_id,
+                    shard_id: self.shard_id,
+                    stored,
+                    computed,
+                    delta: computed as i64 - stored as i64,
+                    breakdown,
+                });
+            }
+        }
+        mismatches.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        report.mismatches.extend(mismatches);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::account::{AccessKey, Account};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::runtime::config_store::RuntimeConfigStore;
+    use near_primitives::state_record::StateRecord;
+    use near_primitives::version::PROTOCOL_VERSION;
+
+    use super::{ShardStorageUsageAuditor, StorageUsageBreakdown, StorageUsageReport};
+
+    fn account_record(account_id: &str, storage_usage: u64) -> StateRecord {
+        StateRecord::Account {
+            account_id: account_id.parse().unwrap(),
+            account: Account::new(0, 0, CryptoHash::default(), storage_usage),
+        }
+    }
+
+    #[test]
+    fn test_audit() {
+        let config_store = RuntimeConfigStore::new(None);
+        let config =
+            &config_store.get_config(PROTOCOL_VERSION).transaction_costs.storage_usage_config;
+        let access_key = StateRecord::AccessKey {
+            account_id: "alice.near".parse().unwrap(),
+            public_key: PublicKey::empty(KeyType::ED25519),
+            access_key: AccessKey::full_access(),
+        };
+        let data = StateRecord::Data {
+            account_id: "alice.near".parse().unwrap(),
+            data_key: b"key".to_vec(),
+            value: b"value".to_vec(),
+        };
+        let code =
+            StateRecord::Contract { account_id: "alice.near".parse().unwrap(), code: vec![0; 10] };
+        let access_keys_usage = node_runtime::record_storage_usage(config, &access_key).unwrap();
+        let data_usage = config.num_extra_bytes_record + 8;
+        let alice_usage = config.num_bytes_account + access_keys_usage + data_usage + 10;
+
+        let mut report = StorageUsageReport {
+            block_height: 1,
+            block_hash: CryptoHash::default(),
+            protocol_version: PROTOCOL_VERSION,
+            accounts_checked: 0,
+            mismatches: vec![],
+        };
+        let mut auditor = ShardStorageUsageAuditor::new(0, config);
+        for record in [
+            account_record("alice.near", alice_usage - 5),
+            account_record("bob.near", config.num_bytes_account),
+            access_key,
+            code,
+            data,
+            // Data of an account which doesn't exist is not reported.
+            StateRecord::Data {
+                account_id: "carol.near".parse().unwrap(),
+                data_key: vec![],
+                value: vec![],
+            },
+        ] {
+            auditor.process_record(&record);
+        }
+        auditor.finish(&mut report);
+
+        assert_eq!(report.accounts_checked, 2);
+        assert_eq!(report.mismatches.len(), 1);
+        let mismatch = &report.mismatches[0];
+        assert_eq!(mismatch.account_id.as_ref(), "alice.near");
+        assert_eq!(mismatch.stored, alice_usage - 5);
+        assert_eq!(mismatch.computed, alice_usage);
+        assert_eq!(mismatch.delta, 5);
+        assert_eq!(
+            mismatch.breakdown,
+            StorageUsageBreakdown {
+                account: config.num_bytes_account,
+                access_keys: access_keys_usage,
+                contract_code: 10,
+                contract_data: data_usage,
+            }
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::path::Path;
 use std::process;
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use ansi_term::Color::{Green, Red, White, Yellow};
 use clap::{App, Arg, SubCommand};
@@ -23,6 +25,18 @@ fn main() {
                 .help("Directory for config and data (default \"~/.near\")")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("keys-per-second")
+                .long("keys-per-second")
+                .help("Validate at most this many records per second, resuming from where the previous interrupted run stopped. Refcounts aren't checked in this mode")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("repair")
+                .long("repair")
+                .help("Apply the safe repairs of the found errors")
+                .takes_value(false),
+        )
         .subcommand(SubCommand::with_name("validate"))
         .get_matches();
 
@@ -39,6 +53,7 @@ fn main() {
         None,
         None,
         None,
+        near_config.config.trie_cache.clone(),
     ));
 
     let mut store_validator = StoreValidator::new(
@@ -47,7 +62,32 @@ fn main() {
         runtime_adapter.clone(),
         store.clone(),
     );
-    store_validator.validate();
+    match matches.value_of("keys-per-second").map(|value| value.parse::<u64>()) {
+        Some(Ok(0)) => {
+            println!("{} must be positive", Red.bold().paint("Invalid --keys-per-second:"));
+            process::exit(1);
+        }
+        Some(Ok(keys_per_second)) => loop {
+            let step_start = Instant::now();
+            match store_validator.validate_step(keys_per_second) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(err) => {
+                    println!("{} {}", Red.bold().paint("Validation step failed:"), err);
+                    process::exit(1);
+                }
+            }
+            if let Some(delay) = Duration::from_secs(1).checked_sub(step_start.elapsed()) {
+                thread::sleep(delay);
+            }
+        },
+        Some(Err(err)) => {
+            println!("{} {}", Red.bold().paint("Invalid --keys-per-second:"), err);
+            process::exit(1);
+        }
+        // A single pass reads a snapshot, so it can suggest refcount repairs.
+        None => store_validator.validate_snapshot(),
+    }
 
     if store_validator.tests_done() == 0 {
         println!("{}", Red.bold().paint("No conditions has been validated"));
@@ -60,12 +100,28 @@ fn main() {
     );
     for error in store_validator.errors.iter() {
         println!(
-            "{}  {}  {}",
+            "{}  {}  {}  {}",
             Red.bold().paint(&error.col),
             Yellow.bold().paint(&error.key),
-            error.err
+            error.err,
+            serde_json::to_string(&error.repair).unwrap()
         );
     }
+    if matches.is_present("repair") {
+        match store_validator.apply_safe_repairs() {
+            Ok(applied) => {
+                println!(
+                    "{} {}",
+                    White.bold().paint("Repairs applied:"),
+                    Green.bold().paint(applied.to_string())
+                )
+            }
+            Err(err) => {
+                println!("{} {}", Red.bold().paint("Failed to apply repairs:"), err);
+                process::exit(1);
+            }
+        }
+    }
     if store_validator.is_failed() {
         println!("Errors found: {}", Red.bold().paint(store_validator.num_failed().to_string()));
         process::exit(1);
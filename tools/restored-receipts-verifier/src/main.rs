@@ -55,6 +55,7 @@ fn main() -> Result<()> {
         None,
         near_config.client_config.max_gas_burnt_view,
         None,
+        near_config.config.trie_cache.clone(),
     );
 
     let mut receipts_missing = Vec::<Receipt>::new();
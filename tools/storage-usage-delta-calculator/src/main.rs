@@ -10,7 +10,8 @@ use std::io::Error;
 /// Calculates delta between actual storage usage and one saved in state
 /// output.json should contain dump of current state,
 /// run state-viewer --home ~/.near/mainnet/ dump_state
-/// to get it
+/// to get it.
+/// `state-viewer audit_storage_usage` does the same on the node's database without the dump.
 fn main() -> Result<(), Error> {
     env_logger::Builder::new().filter(None, LevelFilter::Debug).init();
 